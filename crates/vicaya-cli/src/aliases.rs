@@ -0,0 +1,276 @@
+//! `vicaya aliases`: manage query shorthand, and expand it for `vicaya search`.
+
+use clap::Subcommand;
+use std::collections::BTreeMap;
+use vicaya_core::ipc::SearchResult;
+use vicaya_core::permissions::AttributeFilter;
+use vicaya_core::query::{self, CmpU64, Niyama};
+use vicaya_core::{Config, Error, Result};
+
+use crate::render;
+
+/// How many times the requested limit is ranked when result niyamas
+/// (`ext:`, `type:`, ...) will drop some of it, up to
+/// [`NIYAMA_OVERFETCH_CAP`] extra results.
+const NIYAMA_OVERFETCH: usize = 10;
+const NIYAMA_OVERFETCH_CAP: usize = 1_000;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum AliasAction {
+    /// List the defined aliases
+    List {
+        /// Output format (table, json, plain)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+    /// Define an alias, replacing any existing one with the same name
+    Add {
+        /// Alias name, used as `@NAME` in queries
+        name: String,
+
+        /// Query text the alias stands for, e.g. `ext:toml,yaml type:file`
+        #[arg(required = true, num_args = 1..)]
+        expansion: Vec<String>,
+    },
+    /// Remove an alias
+    Rm {
+        /// Alias name
+        name: String,
+    },
+}
+
+pub(crate) fn run(action: AliasAction) -> Result<()> {
+    let config_path = vicaya_core::paths::config_path();
+
+    match action {
+        AliasAction::List { format } => {
+            let aliases = if config_path.exists() {
+                Config::load(&config_path)?.aliases
+            } else {
+                BTreeMap::new()
+            };
+            print_aliases(&aliases, &format);
+        }
+        AliasAction::Add { name, expansion } => {
+            if !vicaya_core::aliases::is_valid_name(&name) {
+                return Err(Error::Config(format!(
+                    "Invalid alias name '{name}': use letters, digits, `-` and `_`"
+                )));
+            }
+            let expansion = expansion.join(" ");
+            if expansion.trim().is_empty() {
+                return Err(Error::Config("Alias expansion is empty".to_string()));
+            }
+            if !config_path.exists() {
+                return Err(Error::Config(format!(
+                    "No config at {}; run `vicaya init` first",
+                    config_path.display()
+                )));
+            }
+            match Config::set_alias(&config_path, &name, &expansion)? {
+                Some(previous) => println!("✓ Updated @{name}: {expansion} (was: {previous})"),
+                None => println!("✓ Added @{name}: {expansion}"),
+            }
+        }
+        AliasAction::Rm { name } => {
+            let removed = if config_path.exists() {
+                Config::remove_alias(&config_path, &name)?
+            } else {
+                None
+            };
+            match removed {
+                Some(expansion) => println!("Removed @{name} ({expansion})"),
+                None => return Err(Error::Other(format!("No alias named '{name}'"))),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn print_aliases(aliases: &BTreeMap<String, String>, format: &str) {
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(aliases).unwrap()),
+        "plain" => {
+            for (name, expansion) in aliases {
+                println!("{name}\t{expansion}");
+            }
+        }
+        _ => {
+            if aliases.is_empty() {
+                println!("No aliases yet. Add one with `vicaya aliases add <NAME> <QUERY>`.");
+                return;
+            }
+            let rows: Vec<Vec<String>> = aliases
+                .iter()
+                .map(|(name, expansion)| vec![format!("@{name}"), expansion.clone()])
+                .collect();
+            let table = render::Table::new(
+                vec![
+                    render::Column::left("ALIAS", 0),
+                    render::Column::left("EXPANSION", 0),
+                ],
+                &rows,
+                None,
+                render::color_enabled(),
+            );
+            table.print_header();
+            for row in &rows {
+                println!("{}", table.row(row));
+            }
+        }
+    }
+}
+
+/// A `vicaya search` query after alias expansion.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct ExpandedQuery {
+    /// Text sent to the daemon as the search term.
+    pub term: String,
+    /// `owner:`, `readonly:` and `executable:` niyamas.
+    pub attributes: AttributeFilter,
+    /// The `depth:` niyama.
+    pub max_depth: Option<usize>,
+    /// The `hidden:` niyama.
+    pub hidden: Option<bool>,
+    /// The `dirsize:` niyama.
+    pub dir_size: Option<CmpU64>,
+    /// `type:`, `ext:`, `path:`, `in-bundle:`, `mtime:` and `size:`,
+    /// checked on the results.
+    pub niyamas: Vec<Niyama>,
+    /// Results to show once the niyamas have been applied.
+    pub limit: usize,
+}
+
+impl ExpandedQuery {
+    /// Results to ask for so that `limit` remain after the niyamas.
+    pub(crate) fn fetch_limit(&self) -> usize {
+        if self.niyamas.is_empty() {
+            return self.limit;
+        }
+        self.limit
+            .saturating_mul(NIYAMA_OVERFETCH)
+            .min(self.limit.saturating_add(NIYAMA_OVERFETCH_CAP))
+    }
+
+    /// Drop results failing a niyama and keep at most `limit`.
+    pub(crate) fn retain_matching(&self, results: &mut Vec<SearchResult>) {
+        if self.niyamas.is_empty() {
+            return;
+        }
+        results.retain(|r| {
+            self.niyamas
+                .iter()
+                .all(|niyama| niyama.matches(&r.path, r.is_dir, r.size, r.mtime))
+        });
+        results.truncate(self.limit);
+    }
+}
+
+/// Expand `@name` aliases in `query` and parse its niyamas with the parser
+/// the TUI and the daemon use. Malformed niyamas are an error.
+pub(crate) fn expand_query(
+    query: &str,
+    aliases: &BTreeMap<String, String>,
+    limit: usize,
+) -> Result<ExpandedQuery> {
+    let expanded = vicaya_core::aliases::expand(query, aliases);
+    let parsed = query::parse_query(&expanded);
+    if let Some(message) = parsed.error_message() {
+        return Err(Error::Other(message));
+    }
+
+    Ok(ExpandedQuery {
+        // Queries without niyamas are sent as typed.
+        term: if parsed.niyamas.is_empty() {
+            expanded.into_owned()
+        } else {
+            parsed.term
+        },
+        attributes: query::attribute_filter(&parsed.niyamas),
+        max_depth: query::max_depth(&parsed.niyamas),
+        hidden: query::hidden(&parsed.niyamas),
+        dir_size: query::dir_size(&parsed.niyamas),
+        niyamas: parsed
+            .niyamas
+            .into_iter()
+            .filter(|niyama| !niyama.is_daemon_side())
+            .collect(),
+        limit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn search_queries_expand_aliases_and_split_niyamas() {
+        let aliases = BTreeMap::from([
+            ("cfg".to_string(), "ext:toml,yaml type:file".to_string()),
+            ("mine".to_string(), "owner:alice executable:no".to_string()),
+        ]);
+
+        let parsed = expand_query("server @cfg @mine depth:<=3", &aliases, 20).unwrap();
+        assert_eq!(parsed.term, "server");
+        assert_eq!(
+            parsed.attributes,
+            AttributeFilter {
+                owner: Some("alice".to_string()),
+                readonly: None,
+                executable: Some(false),
+                content_hash: None,
+            }
+        );
+        assert_eq!(parsed.max_depth, Some(3));
+        assert_eq!(
+            parsed.niyamas.iter().map(Niyama::raw).collect::<Vec<_>>(),
+            vec!["type:file", "ext:toml,yaml"]
+        );
+        assert_eq!(parsed.hidden, None);
+
+        let dotfiles = expand_query("bashrc hidden:yes", &aliases, 20).unwrap();
+        assert_eq!(dotfiles.term, "bashrc");
+        assert_eq!(dotfiles.hidden, Some(true));
+
+        let plain = expand_query("  spaced  query ", &aliases, 20).unwrap();
+        assert_eq!(plain.term, "  spaced  query ");
+        assert_eq!(plain.fetch_limit(), 20);
+
+        let err = expand_query("server size:>10xb", &aliases, 20).unwrap_err();
+        assert!(err.to_string().contains("`size:>10xb`"), "{err}");
+    }
+
+    #[test]
+    fn result_niyamas_widen_the_fetch_and_filter_the_results() {
+        let mut parsed = expand_query("main ext:rs", &BTreeMap::new(), 20).unwrap();
+        assert_eq!(parsed.fetch_limit(), 200);
+        parsed.limit = 500;
+        assert_eq!(parsed.fetch_limit(), 1_500);
+        parsed.limit = 1;
+
+        let result = |path: &str| SearchResult {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+        let mut results = vec![
+            result("/a/main.py"),
+            result("/a/main.rs"),
+            result("/b/main.rs"),
+        ];
+        parsed.retain_matching(&mut results);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "/a/main.rs");
+    }
+}
@@ -0,0 +1,149 @@
+//! `vicaya bookmark`: manage directory bookmarks for TUI ksetra jumps.
+
+use clap::Subcommand;
+use serde::Serialize;
+use std::path::PathBuf;
+use vicaya_core::bookmarks::{Bookmark, BookmarkStore, HOTKEY_SLOTS};
+use vicaya_core::smriti::{SmritiEntry, SmritiStore};
+use vicaya_core::Result;
+
+/// Suggestions shown under `vicaya bookmark list`.
+const SUGGESTION_LIMIT: usize = 5;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum BookmarkAction {
+    /// Bookmark a directory (defaults to the current directory)
+    Add {
+        /// Directory to bookmark
+        #[arg(default_value = ".")]
+        dir: PathBuf,
+
+        /// Bookmark name (defaults to the directory name)
+        #[arg(short, long)]
+        name: Option<String>,
+    },
+    /// List bookmarks and frequently entered scopes worth bookmarking
+    List {
+        /// Output format (table, json, plain)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+    /// Remove a bookmark by name, slot number, or path
+    Rm {
+        /// Bookmark name, slot number, or path
+        bookmark: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+struct BookmarkListing<'a> {
+    bookmarks: &'a [Bookmark],
+    suggestions: Vec<Suggestion<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+struct Suggestion<'a> {
+    path: &'a str,
+    enter_count: u64,
+}
+
+pub(crate) fn run(action: BookmarkAction) -> Result<()> {
+    let path = vicaya_core::paths::bookmarks_path();
+    let mut store = BookmarkStore::load(&path)?;
+
+    match action {
+        BookmarkAction::Add { dir, name } => {
+            let dir = vicaya_core::paths::resolve_scope_dir(&dir)?;
+            let now = chrono::Utc::now().timestamp();
+            let bookmark = store.add(name.as_deref(), &dir, now)?.clone();
+            store.save_atomic(&path)?;
+            let slot = store
+                .bookmarks
+                .iter()
+                .position(|b| b.path == bookmark.path)
+                .map(|index| index + 1)
+                .unwrap_or_default();
+            println!("Bookmarked {} as '{}'", bookmark.path, bookmark.name);
+            if slot <= HOTKEY_SLOTS {
+                println!("Press {slot} in the TUI results to jump there");
+            }
+        }
+        BookmarkAction::List { format } => {
+            let entries = smriti_entries();
+            let suggestions: Vec<Suggestion> = store
+                .suggestions(&entries, SUGGESTION_LIMIT)
+                .into_iter()
+                .map(|entry| Suggestion {
+                    path: &entry.path,
+                    enter_count: entry.enter_count,
+                })
+                .collect();
+            print_listing(
+                &BookmarkListing {
+                    bookmarks: &store.bookmarks,
+                    suggestions,
+                },
+                &format,
+            );
+        }
+        BookmarkAction::Rm { bookmark } => match store.remove(&bookmark) {
+            Some(removed) => {
+                store.save_atomic(&path)?;
+                println!("Removed bookmark '{}' ({})", removed.name, removed.path);
+            }
+            None => {
+                return Err(vicaya_core::Error::Other(format!(
+                    "No bookmark matching '{bookmark}'"
+                )));
+            }
+        },
+    }
+
+    Ok(())
+}
+
+fn print_listing(listing: &BookmarkListing, format: &str) {
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(listing).unwrap()),
+        "plain" => {
+            for bookmark in listing.bookmarks {
+                println!("{}", bookmark.path);
+            }
+        }
+        _ => {
+            if listing.bookmarks.is_empty() {
+                println!("No bookmarks yet. Add one with `vicaya bookmark add <DIR>`.");
+            } else {
+                println!("{:<6} {:<20} PATH", "SLOT", "NAME");
+                for (idx, bookmark) in listing.bookmarks.iter().enumerate() {
+                    let slot = if idx < HOTKEY_SLOTS {
+                        (idx + 1).to_string()
+                    } else {
+                        "-".to_string()
+                    };
+                    println!("{:<6} {:<20} {}", slot, bookmark.name, bookmark.path);
+                }
+            }
+            if !listing.suggestions.is_empty() {
+                println!();
+                println!("Frequently entered ksetra (vicaya bookmark add <DIR>):");
+                for suggestion in &listing.suggestions {
+                    println!(
+                        "  {} (entered {}×)",
+                        suggestion.path, suggestion.enter_count
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Smriti entries read straight from disk; suggestions are best-effort, so a
+/// missing or unreadable store yields none.
+fn smriti_entries() -> Vec<SmritiEntry> {
+    std::fs::read_to_string(vicaya_core::paths::smriti_path())
+        .ok()
+        .and_then(|content| serde_json::from_str::<SmritiStore>(&content).ok())
+        .map(|store| store.entries.into_values().collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,141 @@
+//! `vicaya complete-path PREFIX`: path completion for shell plugins.
+//!
+//! The prefix is resolved against the current directory (`~` and variables
+//! expanded) and sent to the daemon as `Request::CompletePath`, which answers
+//! from the index with the most frecent matches under the prefix's directory.
+//! Completions are printed one per line in the form the prefix was typed in
+//! (`~/Pro` completes to `~/Projects/`), directories with a trailing `/`.
+//! Nothing is printed when the daemon is not running, so a shell falls back
+//! to its own completion instead of waiting for one to start.
+
+use clap::Args;
+use std::path::{Path, PathBuf};
+use vicaya_client::Client;
+use vicaya_core::ipc::{PathCompletion, Request, Response};
+use vicaya_core::{Error, Result};
+
+#[derive(Args, Debug)]
+pub(crate) struct CompletePathArgs {
+    /// Path typed so far; its last component may be partial
+    #[arg(default_value = "", allow_hyphen_values = true)]
+    prefix: String,
+
+    /// Maximum number of completions
+    #[arg(short, long, default_value = "20")]
+    limit: usize,
+
+    /// Only complete directories (for `cd`)
+    #[arg(long)]
+    dirs: bool,
+}
+
+/// A prefix split into what the user typed up to its last `/`, the absolute
+/// directory that names, and the partial component after it.
+#[derive(Debug, PartialEq, Eq)]
+struct Prefix {
+    typed_dir: String,
+    dir: PathBuf,
+    partial: String,
+}
+
+impl Prefix {
+    fn parse(prefix: &str) -> Result<Self> {
+        let prefix = if prefix == "~" { "~/" } else { prefix };
+        let (typed_dir, partial) = match prefix.rfind('/') {
+            Some(slash) => prefix.split_at(slash + 1),
+            None => ("", prefix),
+        };
+        let dir = vicaya_core::paths::resolve_user_path(Path::new(match typed_dir {
+            "" => ".",
+            typed => typed,
+        }))?;
+        Ok(Self {
+            typed_dir: typed_dir.to_string(),
+            dir,
+            partial: partial.to_string(),
+        })
+    }
+
+    /// The absolute prefix the daemon completes.
+    fn absolute(&self) -> String {
+        let dir = self.dir.to_string_lossy();
+        format!("{}/{}", dir.trim_end_matches('/'), self.partial)
+    }
+
+    /// `completion` as the user would have typed it.
+    fn display(&self, completion: &PathCompletion) -> Option<String> {
+        let relative = Path::new(&completion.path).strip_prefix(&self.dir).ok()?;
+        let slash = if completion.is_dir { "/" } else { "" };
+        Some(format!(
+            "{}{}{slash}",
+            self.typed_dir,
+            relative.to_string_lossy()
+        ))
+    }
+}
+
+pub(crate) fn run(args: CompletePathArgs) -> Result<()> {
+    if !vicaya_core::daemon::is_running() {
+        return Ok(());
+    }
+    let prefix = Prefix::parse(&args.prefix)?;
+    let request = Request::CompletePath {
+        prefix: prefix.absolute(),
+        limit: args.limit,
+        dirs_only: args.dirs,
+    };
+    let completions = match Client::connect()?.request(&request)? {
+        Response::PathCompletions { completions } => completions,
+        Response::Error { message } => return Err(Error::Other(message)),
+        _ => return Err(Error::Other("Unexpected response from daemon".to_string())),
+    };
+    for completion in &completions {
+        if let Some(shown) = prefix.display(completion) {
+            println!("{shown}");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefixes_resolve_to_absolute_paths_and_completions_keep_the_typed_form() {
+        let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+        let cwd = std::env::current_dir().unwrap();
+
+        let tilde = Prefix::parse("~/Pro").unwrap();
+        assert_eq!(tilde.dir, home);
+        assert_eq!(tilde.absolute(), format!("{}/Pro", home.display()));
+        let projects = PathCompletion {
+            path: home.join("Projects").to_string_lossy().to_string(),
+            is_dir: true,
+        };
+        assert_eq!(tilde.display(&projects).as_deref(), Some("~/Projects/"));
+        assert_eq!(Prefix::parse("~").unwrap().typed_dir, "~/");
+
+        let relative = Prefix::parse("src/ma").unwrap();
+        assert_eq!(relative.dir, cwd.join("src"));
+        let deep = PathCompletion {
+            path: cwd.join("src/bin/main.rs").to_string_lossy().to_string(),
+            is_dir: false,
+        };
+        assert_eq!(relative.display(&deep).as_deref(), Some("src/bin/main.rs"));
+
+        let bare = Prefix::parse("Car").unwrap();
+        assert_eq!(
+            (bare.typed_dir.as_str(), bare.dir.as_path()),
+            ("", cwd.as_path())
+        );
+        assert_eq!(Prefix::parse("/").unwrap().absolute(), "/");
+        assert_eq!(Prefix::parse("/us").unwrap().absolute(), "/us");
+
+        let elsewhere = PathCompletion {
+            path: "/elsewhere".to_string(),
+            is_dir: false,
+        };
+        assert_eq!(bare.display(&elsewhere), None);
+    }
+}
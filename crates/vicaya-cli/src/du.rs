@@ -0,0 +1,137 @@
+//! `vicaya du [PATH]`: disk usage of an indexed directory.
+//!
+//! The daemon answers `Request::DirUsage` from the directory totals it keeps
+//! current as watcher updates arrive, so the report comes back at once
+//! instead of after a walk of the tree. Only indexed files count: entries
+//! left out by exclusions, ignore files, or `max_indexed_files` are not in
+//! the totals.
+
+use clap::Args;
+use serde::Serialize;
+use std::path::PathBuf;
+use vicaya_client::Client;
+use vicaya_core::ipc::{DirSize, DirUsageEntry, Request, Response};
+use vicaya_core::{Error, Result};
+
+use crate::render;
+
+#[derive(Args, Debug)]
+pub(crate) struct DuArgs {
+    /// Directory to report on
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// Largest subdirectories to list
+    #[arg(short, long, default_value = "20")]
+    limit: usize,
+
+    /// Output format (table, json, plain)
+    #[arg(short, long, default_value = "table")]
+    format: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Report<'a> {
+    path: &'a str,
+    total: DirSize,
+    entries: &'a [DirUsageEntry],
+}
+
+pub(crate) fn run(args: DuArgs) -> Result<()> {
+    if !matches!(args.format.as_str(), "table" | "json" | "plain") {
+        return Err(Error::Config(format!(
+            "unknown format `{}` (expected table, json, or plain)",
+            args.format
+        )));
+    }
+    crate::ensure_daemon_running(args.format == "json")?;
+    let path = vicaya_core::paths::resolve_user_path(&args.path)?
+        .to_string_lossy()
+        .into_owned();
+    let (total, entries) = match Client::connect()?.request(&Request::DirUsage {
+        path: path.clone(),
+        limit: args.limit,
+    })? {
+        Response::DirUsage { total, entries } => (total, entries),
+        Response::Error { message } => return Err(Error::Other(message)),
+        _ => return Err(Error::Other("Unexpected response from daemon".to_string())),
+    };
+
+    let report = Report {
+        path: &path,
+        total,
+        entries: &entries,
+    };
+    match args.format.as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&report).unwrap()),
+        "plain" => {
+            for entry in &entries {
+                println!("{}\t{}", entry.size.bytes, entry.path);
+            }
+            println!("{}\t{}", total.bytes, path);
+        }
+        _ => print_table(&report),
+    }
+    Ok(())
+}
+
+fn print_table(report: &Report<'_>) {
+    let row = |size: &DirSize, path: &str| {
+        vec![
+            format_size(size.bytes),
+            size.files.to_string(),
+            format!("{path}/"),
+        ]
+    };
+    let mut rows: Vec<Vec<String>> = report
+        .entries
+        .iter()
+        .map(|entry| row(&entry.size, &entry.path))
+        .collect();
+    rows.push(row(&report.total, report.path.trim_end_matches('/')));
+    let table = render::Table::new(
+        vec![
+            render::Column::right("SIZE", 9),
+            render::Column::right("FILES", 7),
+            render::Column::flexible("PATH"),
+        ],
+        &rows,
+        render::terminal_width(),
+        render::color_enabled(),
+    );
+    table.print_header();
+    for row in &rows {
+        println!("{}", table.row(row));
+    }
+}
+
+/// `512 B`, `1.5 KB`, `20.0 MB`, `3.25 GB`.
+fn format_size(bytes: u64) -> String {
+    const KB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f < KB {
+        format!("{bytes} B")
+    } else if bytes_f < KB * KB {
+        format!("{:.1} KB", bytes_f / KB)
+    } else if bytes_f < KB * KB * KB {
+        format!("{:.1} MB", bytes_f / (KB * KB))
+    } else {
+        format!("{:.2} GB", bytes_f / (KB * KB * KB))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_use_the_largest_fitting_unit() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(20 * 1024 * 1024), "20.0 MB");
+        assert_eq!(
+            format_size(3 * 1024 * 1024 * 1024 + 256 * 1024 * 1024),
+            "3.25 GB"
+        );
+    }
+}
@@ -0,0 +1,395 @@
+//! `vicaya suggest-exclusions`: find low-value subtrees worth excluding.
+//!
+//! Walks the on-disk index for directories that hold many entries but are
+//! rarely searched for by name (dependency trees, build products, caches),
+//! estimates what excluding each would save, and optionally appends the chosen
+//! patterns to the config and purges the entries from the running daemon.
+
+use clap::Args;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use vicaya_client::Client;
+use vicaya_core::ipc::{Request, Response};
+use vicaya_core::{Config, Error, Result};
+use vicaya_index::FileMeta;
+use vicaya_scanner::IndexSnapshot;
+
+/// Directory names that are almost always generated or vendored.
+const KNOWN_SUBTREES: &[(&str, &str)] = &[
+    ("node_modules", "JavaScript dependencies"),
+    ("bower_components", "JavaScript dependencies"),
+    ("jspm_packages", "JavaScript dependencies"),
+    (".pnpm-store", "pnpm store"),
+    (".yarn", "Yarn cache"),
+    (".npm", "npm cache"),
+    (".next", "Next.js build output"),
+    (".nuxt", "Nuxt build output"),
+    (".turbo", "Turborepo cache"),
+    ("DerivedData", "Xcode build products"),
+    ("xcuserdata", "Xcode user state"),
+    ("CoreSimulator", "iOS simulator devices"),
+    ("Pods", "CocoaPods dependencies"),
+    ("Carthage", "Carthage dependencies"),
+    (".build", "SwiftPM build output"),
+    ("target", "Cargo/Maven build output"),
+    ("CMakeFiles", "CMake build files"),
+    (".gradle", "Gradle cache"),
+    (".m2", "Maven repository"),
+    (".ivy2", "Ivy repository"),
+    (".cargo", "Cargo registry"),
+    (".rustup", "Rust toolchains"),
+    ("__pycache__", "Python bytecode"),
+    (".venv", "Python virtualenv"),
+    ("venv", "Python virtualenv"),
+    ("site-packages", "Python packages"),
+    (".tox", "tox environments"),
+    (".terraform", "Terraform providers"),
+    ("vendor", "vendored dependencies"),
+    (".git", "Git objects"),
+    (".Trash", "Trash"),
+];
+
+#[derive(Args, Debug)]
+pub(crate) struct SuggestExclusionsArgs {
+    /// Only suggest patterns that match at least this many indexed entries
+    #[arg(long, default_value = "1000")]
+    pub(crate) min_files: usize,
+
+    /// Maximum number of suggestions
+    #[arg(short, long, default_value = "15")]
+    pub(crate) limit: usize,
+
+    /// Apply every suggestion without prompting
+    #[arg(short, long)]
+    pub(crate) yes: bool,
+
+    /// Output format (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub(crate) format: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Analysis {
+    indexed_files: usize,
+    suggestions: Vec<Suggestion>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Suggestion {
+    /// Exclusion pattern to append (a single path component).
+    pattern: String,
+    reason: &'static str,
+    /// Indexed entries the pattern would remove.
+    files: usize,
+    /// Distinct directories the pattern matches.
+    locations: usize,
+    /// The location holding the most entries.
+    largest: String,
+    /// Approximate daemon memory the entries take.
+    estimated_bytes: u64,
+}
+
+pub(crate) fn run(args: SuggestExclusionsArgs) -> Result<()> {
+    let config = crate::load_config()?;
+    let index_file = config.index_path.join("index.bin");
+    let snapshot = IndexSnapshot::load(&index_file).map_err(|e| {
+        Error::Other(format!(
+            "Failed to load the index at {}: {e}; run `vicaya rebuild` first",
+            index_file.display()
+        ))
+    })?;
+
+    let mut analysis = analyze(&snapshot, &config, args.min_files);
+    analysis.suggestions.truncate(args.limit);
+
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&analysis).unwrap());
+        if !args.yes {
+            return Ok(());
+        }
+    } else {
+        print_analysis(&analysis);
+    }
+    if analysis.suggestions.is_empty() {
+        return Ok(());
+    }
+
+    let chosen: Vec<String> = if args.yes {
+        analysis
+            .suggestions
+            .iter()
+            .map(|s| s.pattern.clone())
+            .collect()
+    } else if std::io::stdin().is_terminal() {
+        print!("\nExclude which? (e.g. `1 3`, `all`; Enter to skip): ");
+        std::io::stdout().flush()?;
+        let mut input = String::new();
+        std::io::stdin().lock().read_line(&mut input)?;
+        parse_selection(&input, analysis.suggestions.len())
+            .map_err(Error::Other)?
+            .into_iter()
+            .map(|idx| analysis.suggestions[idx].pattern.clone())
+            .collect()
+    } else {
+        println!("\nRe-run with --yes to apply all suggestions.");
+        Vec::new()
+    };
+    if chosen.is_empty() {
+        return Ok(());
+    }
+
+    apply(&chosen)
+}
+
+/// Append `patterns` to the config and purge their entries from the daemon.
+fn apply(patterns: &[String]) -> Result<()> {
+    let config_path = vicaya_core::paths::config_path();
+    if !config_path.exists() {
+        return Err(Error::Config(format!(
+            "No config at {}; run `vicaya init` first",
+            config_path.display()
+        )));
+    }
+    let added = Config::append_exclusions(&config_path, patterns)?;
+    println!(
+        "✓ Added {} exclusion pattern(s) to {}",
+        added.len(),
+        config_path.display()
+    );
+    if added.is_empty() {
+        return Ok(());
+    }
+
+    if !vicaya_core::daemon::is_running() {
+        println!("  The daemon drops the excluded entries when it next starts.");
+        return Ok(());
+    }
+    match Client::connect()?.request(&Request::Exclude { patterns: added })? {
+        Response::Excluded { removed } => {
+            println!(
+                "✓ Purged {} entries from the running daemon",
+                crate::format_number(removed)
+            );
+            Ok(())
+        }
+        Response::Error { message } => Err(Error::Other(message)),
+        _ => Err(Error::Other("Unexpected response from daemon".to_string())),
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    reason: &'static str,
+    files: usize,
+    estimated_bytes: u64,
+    locations: HashMap<PathBuf, usize>,
+}
+
+fn analyze(snapshot: &IndexSnapshot, config: &Config, min_files: usize) -> Analysis {
+    let roots = config.effective_roots();
+    let mut tallies: BTreeMap<String, Tally> = BTreeMap::new();
+    let mut indexed_files = 0;
+
+    for (_, meta) in snapshot.file_table.iter() {
+        if meta.is_tombstone() {
+            continue;
+        }
+        indexed_files += 1;
+        let Some(path) = snapshot.file_table.path_of(meta, &snapshot.string_arena) else {
+            continue;
+        };
+        let path = Path::new(&path);
+        // Components at or above an index root are the user's choice of root,
+        // not something to exclude.
+        let Some(root) = roots.iter().find(|root| path.starts_with(root)) else {
+            continue;
+        };
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+
+        let mut location = root.clone();
+        for component in relative.components() {
+            location.push(component);
+            let Some(name) = component.as_os_str().to_str() else {
+                continue;
+            };
+            let Some(reason) = low_value_reason(name) else {
+                continue;
+            };
+            if config.exclusions.iter().any(|pattern| pattern == name) {
+                continue;
+            }
+            // Charge the entry to the outermost match only: excluding that
+            // directory removes everything below it anyway.
+            let tally = tallies.entry(name.to_string()).or_default();
+            tally.reason = reason;
+            tally.files += 1;
+            tally.estimated_bytes += estimated_entry_bytes(meta);
+            *tally.locations.entry(location).or_default() += 1;
+            break;
+        }
+    }
+
+    let mut suggestions: Vec<Suggestion> = tallies
+        .into_iter()
+        .filter(|(_, tally)| tally.files >= min_files)
+        .map(|(pattern, tally)| {
+            let largest = tally
+                .locations
+                .iter()
+                .max_by(|a, b| a.1.cmp(b.1).then_with(|| b.0.cmp(a.0)))
+                .map(|(path, _)| path.to_string_lossy().to_string())
+                .unwrap_or_default();
+            Suggestion {
+                pattern,
+                reason: tally.reason,
+                files: tally.files,
+                locations: tally.locations.len(),
+                largest,
+                estimated_bytes: tally.estimated_bytes,
+            }
+        })
+        .collect();
+    suggestions.sort_by(|a, b| {
+        b.files
+            .cmp(&a.files)
+            .then_with(|| a.pattern.cmp(&b.pattern))
+    });
+
+    Analysis {
+        indexed_files,
+        suggestions,
+    }
+}
+
+/// Why a directory named `name` is probably not worth indexing.
+fn low_value_reason(name: &str) -> Option<&'static str> {
+    if let Some((_, reason)) = KNOWN_SUBTREES.iter().find(|(known, _)| *known == name) {
+        return Some(reason);
+    }
+    let lower = name.to_ascii_lowercase();
+    (lower.ends_with("cache") || lower.ends_with("caches")).then_some("cache directory")
+}
+
+/// Rough daemon memory per entry: the file table row, the basename in the
+/// string arena, one trigram posting per name trigram, and the path and name
+/// map slots.
+fn estimated_entry_bytes(meta: &FileMeta) -> u64 {
+    const POSTING_BYTES: usize = std::mem::size_of::<u32>();
+    const MAP_SLOT_BYTES: usize = 2 * (std::mem::size_of::<u64>() + POSTING_BYTES);
+    let row = std::mem::size_of::<FileMeta>();
+    let trigrams = meta.name_len.saturating_sub(2);
+    (row + meta.name_len + trigrams * POSTING_BYTES + MAP_SLOT_BYTES) as u64
+}
+
+/// Parse `1 3`, `1,2`, or `all` into zero-based suggestion indices.
+fn parse_selection(input: &str, count: usize) -> std::result::Result<Vec<usize>, String> {
+    let input = input.trim();
+    if input.eq_ignore_ascii_case("all") {
+        return Ok((0..count).collect());
+    }
+    let mut chosen = Vec::new();
+    for token in input.split([',', ' ']).filter(|token| !token.is_empty()) {
+        let idx = token
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| (1..=count).contains(&n))
+            .ok_or_else(|| format!("`{token}` is not a suggestion number (1-{count})"))?;
+        if !chosen.contains(&(idx - 1)) {
+            chosen.push(idx - 1);
+        }
+    }
+    Ok(chosen)
+}
+
+fn print_analysis(analysis: &Analysis) {
+    println!(
+        "Indexed entries: {}",
+        crate::format_number(analysis.indexed_files)
+    );
+    if analysis.suggestions.is_empty() {
+        println!("No large low-value subtrees found outside the current exclusions.");
+        return;
+    }
+
+    println!(
+        "\n{:>3}  {:<20} {:>10} {:>6} {:>9}  REASON",
+        "#", "PATTERN", "ENTRIES", "DIRS", "MEMORY"
+    );
+    for (idx, suggestion) in analysis.suggestions.iter().enumerate() {
+        let share = suggestion.files as f64 * 100.0 / analysis.indexed_files.max(1) as f64;
+        println!(
+            "{:>3}  {:<20} {:>10} {:>6} {:>9}  {} ({share:.1}% of index)",
+            idx + 1,
+            suggestion.pattern,
+            crate::format_number(suggestion.files),
+            suggestion.locations,
+            format!("{:.1} MB", suggestion.estimated_bytes as f64 / 1_048_576.0),
+            suggestion.reason,
+        );
+        println!("     largest: {}", suggestion.largest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vicaya_scanner::Scanner;
+
+    #[test]
+    fn analysis_suggests_unexcluded_high_cardinality_subtrees() {
+        let root = tempfile::tempdir().unwrap();
+        let write = |rel: &str| {
+            let path = root.path().join(rel);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        };
+        for idx in 0..4 {
+            write(&format!("app/DerivedData/Build/obj{idx}.o"));
+            write(&format!("web/GPUCache/data_{idx}"));
+        }
+        write("lib/DerivedData/x.o");
+        write("web/node_modules/left-pad/index.js");
+        write("src/main.rs");
+
+        let mut config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            exclusions: vec!["node_modules".to_string()],
+            respect_ignore_files: false,
+            ..Config::default()
+        };
+        config.index_path = root.path().join(".index");
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+
+        let analysis = analyze(&snapshot, &config, 5);
+        let found: Vec<(&str, usize, usize)> = analysis
+            .suggestions
+            .iter()
+            .map(|s| (s.pattern.as_str(), s.files, s.locations))
+            .collect();
+        // DerivedData: two directories, Build/, and five objects.
+        assert_eq!(found, vec![("DerivedData", 8, 2), ("GPUCache", 5, 1)]);
+        let derived = &analysis.suggestions[0];
+        assert_eq!(derived.reason, "Xcode build products");
+        assert!(derived.largest.ends_with("app/DerivedData"));
+        assert!(derived.estimated_bytes >= 8 * std::mem::size_of::<FileMeta>() as u64);
+        assert!(analysis.indexed_files > 13);
+
+        config.exclusions.push("DerivedData".to_string());
+        let analysis = analyze(&snapshot, &config, 5);
+        assert_eq!(analysis.suggestions.len(), 1);
+    }
+
+    #[test]
+    fn selections_accept_numbers_lists_and_all() {
+        assert_eq!(parse_selection("1 3", 3), Ok(vec![0, 2]));
+        assert_eq!(parse_selection("2,2, 1\n", 3), Ok(vec![1, 0]));
+        assert_eq!(parse_selection("ALL", 2), Ok(vec![0, 1]));
+        assert_eq!(parse_selection("  ", 2), Ok(Vec::new()));
+        assert!(parse_selection("4", 3).is_err());
+        assert!(parse_selection("x", 3).is_err());
+    }
+}
@@ -0,0 +1,453 @@
+//! `vicaya find`: answer common `find(1)` invocations from the index.
+//!
+//! Supports starting paths followed by an implicitly ANDed list of
+//! `-name`, `-iname`, `-path`, `-ipath`, `-type f|d`, `-mtime`, `-mmin`,
+//! `-size`, `-mindepth`, `-maxdepth`, `-print` and `-print0`. Anything else
+//! (`-o`, `!`, parentheses, `-exec`, ...) is rejected rather than silently
+//! answered differently from `find`.
+//!
+//! The longest literal run of the `-name` patterns becomes the search term
+//! (`-path` literals may span directories, which the name index cannot
+//! match); without one of at least three characters the scope is listed
+//! instead. Every test is then applied to the returned metadata, so the
+//! output only differs from `find` where the index is stale or capped.
+
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use globset::{GlobBuilder, GlobMatcher};
+use vicaya_client::Client;
+use vicaya_core::config::IndexProfile;
+use vicaya_core::ipc::{
+    DaemonStatus, Request, Response, SearchFilters, SearchFlags, SearchOptions, SearchSort,
+};
+use vicaya_core::{Error, Result};
+
+/// Most results one starting path can return.
+const FIND_LIMIT: usize = 100_000;
+/// Shortest term worth a trigram search; shorter ones list the scope.
+const MIN_TERM_LEN: usize = 3;
+
+#[derive(Debug, Args)]
+pub(crate) struct FindArgs {
+    /// Starting paths, then find-style tests: -name GLOB, -iname GLOB,
+    /// -path GLOB, -ipath GLOB, -type f|d, -mtime [+-]N, -mmin [+-]N,
+    /// -size [+-]N[cwbkMG], -mindepth N, -maxdepth N, -print, -print0
+    #[arg(
+        trailing_var_arg = true,
+        allow_hyphen_values = true,
+        value_name = "PATH... EXPRESSION"
+    )]
+    args: Vec<String>,
+}
+
+/// A parsed command line.
+#[derive(Debug)]
+struct Expression {
+    roots: Vec<String>,
+    tests: Vec<Test>,
+    /// `-name` patterns, for picking the search term.
+    patterns: Vec<String>,
+    min_depth: usize,
+    max_depth: Option<usize>,
+    print0: bool,
+}
+
+/// One test of the expression; a result must pass all of them.
+#[derive(Debug)]
+enum Test {
+    Name(GlobMatcher),
+    Path(GlobMatcher),
+    Type {
+        dir: bool,
+    },
+    /// Age in whole `unit`s of seconds, as `-mtime` (days) and `-mmin` count it.
+    Age {
+        cmp: Compare,
+        unit: i64,
+    },
+    /// Size in `unit`-byte blocks, rounded up.
+    Size {
+        cmp: Compare,
+        unit: u64,
+    },
+}
+
+/// A numeric argument: `+N` more than N, `-N` less than N, `N` exactly N.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Compare {
+    ordering: Ordering,
+    value: u64,
+}
+
+impl Compare {
+    fn parse(primary: &str, raw: &str) -> Result<Self> {
+        let (ordering, digits) = match raw.as_bytes().first() {
+            Some(b'+') => (Ordering::Greater, &raw[1..]),
+            Some(b'-') => (Ordering::Less, &raw[1..]),
+            _ => (Ordering::Equal, raw),
+        };
+        let value = digits
+            .parse()
+            .map_err(|_| usage(format!("{primary}: invalid number `{raw}`")))?;
+        Ok(Self { ordering, value })
+    }
+
+    fn matches(self, actual: u64) -> bool {
+        actual.cmp(&self.value) == self.ordering
+    }
+}
+
+impl Expression {
+    /// The first primary a lite index cannot answer: it keeps neither sizes
+    /// nor directories.
+    fn needs_full_index(&self) -> Option<&'static str> {
+        self.tests.iter().find_map(|test| match test {
+            Test::Size { .. } => Some("-size"),
+            Test::Type { dir: true } => Some("-type d"),
+            _ => None,
+        })
+    }
+}
+
+impl Test {
+    /// Whether `result`, printed as `shown`, passes this test.
+    fn matches(&self, result: &vicaya_core::ipc::SearchResult, shown: &str, now: i64) -> bool {
+        match self {
+            Test::Name(glob) => glob.is_match(&result.name),
+            Test::Path(glob) => glob.is_match(shown),
+            Test::Type { dir } => result.is_dir == *dir,
+            Test::Age { cmp, unit } => {
+                let age = (now - result.mtime).max(0) / unit;
+                cmp.matches(age as u64)
+            }
+            Test::Size { cmp, unit } => cmp.matches(result.size.div_ceil(*unit)),
+        }
+    }
+}
+
+pub(crate) fn run(args: FindArgs) -> Result<()> {
+    let expression = parse(&args.args)?;
+    crate::ensure_daemon_running(true)?;
+    let mut client = Client::connect()?;
+    if let Some(primary) = expression.needs_full_index() {
+        if let Response::Status(DaemonStatus {
+            index_profile: IndexProfile::Lite,
+            ..
+        }) = client.request(&Request::Status)?
+        {
+            return Err(Error::Other(format!(
+                "find: {primary} needs index_profile = \"full\"; the daemon keeps a lite index"
+            )));
+        }
+    }
+    let now = chrono::Utc::now().timestamp();
+    let separator = if expression.print0 { '\0' } else { '\n' };
+
+    for root in &expression.roots {
+        let scope = vicaya_core::paths::resolve_scope_dir(Path::new(root))
+            .map_err(|e| Error::Other(format!("find: '{root}': {e}")))?;
+        let request = Request::Search(search_options(&expression, &scope));
+        let (results, truncated) = match client.request(&request)? {
+            Response::SearchResults {
+                results, truncated, ..
+            } => (results, truncated),
+            Response::Error { message } => return Err(Error::Other(message)),
+            _ => return Err(Error::Other("Unexpected response from daemon".to_string())),
+        };
+        if truncated || results.len() >= FIND_LIMIT {
+            eprintln!("note: results under {root} may be incomplete (query budget or limit)");
+        }
+
+        for result in &results {
+            let Some(shown) = display_path(root, &scope, &result.path) else {
+                continue;
+            };
+            let depth = Path::new(&result.path)
+                .strip_prefix(&scope)
+                .map_or(0, |rel| rel.components().count());
+            if depth < expression.min_depth
+                || expression.max_depth.is_some_and(|max| depth > max)
+                || !expression
+                    .tests
+                    .iter()
+                    .all(|test| test.matches(result, &shown, now))
+            {
+                continue;
+            }
+            print!("{shown}{separator}");
+        }
+    }
+    Ok(())
+}
+
+/// The daemon query for one starting path.
+fn search_options(expression: &Expression, scope: &Path) -> SearchOptions {
+    let term = search_term(&expression.patterns);
+    let scope = scope.to_string_lossy().to_string();
+    SearchOptions {
+        scope: Some(scope.clone()),
+        filters: SearchFilters {
+            scope: Some(scope),
+            // Like find(1), list dotfiles whatever `show_hidden` says.
+            hidden: Some(true),
+            ..SearchFilters::default()
+        },
+        sort: SearchSort::Path,
+        fuzzy: false,
+        flags: SearchFlags {
+            recent_if_empty: true,
+            ..SearchFlags::default()
+        },
+        ..SearchOptions::new(term, FIND_LIMIT)
+    }
+}
+
+/// The longest run of literal characters in any pattern, if it is long
+/// enough for the trigram index; otherwise empty, which lists the scope.
+fn search_term(patterns: &[String]) -> String {
+    let longest = patterns
+        .iter()
+        .flat_map(|pattern| pattern.split(['*', '?', '[', ']', '\\', '{', '}']))
+        .max_by_key(|run| run.chars().count())
+        .unwrap_or_default();
+    if longest.chars().count() >= MIN_TERM_LEN {
+        longest.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// `path` as find would print it under `root`: the starting path as given,
+/// then the rest relative to it.
+fn display_path(root: &str, scope: &Path, path: &str) -> Option<String> {
+    let rel = Path::new(path).strip_prefix(scope).ok()?;
+    if rel.as_os_str().is_empty() {
+        return Some(root.to_string());
+    }
+    let shown: PathBuf = Path::new(root).join(rel);
+    Some(shown.to_string_lossy().to_string())
+}
+
+fn parse(args: &[String]) -> Result<Expression> {
+    let split = args
+        .iter()
+        .position(|arg| arg.starts_with('-') || arg == "!" || arg == "(")
+        .unwrap_or(args.len());
+    let mut roots: Vec<String> = args[..split].to_vec();
+    if roots.is_empty() {
+        roots.push(".".to_string());
+    }
+
+    let mut expression = Expression {
+        roots,
+        tests: Vec::new(),
+        patterns: Vec::new(),
+        min_depth: 0,
+        max_depth: None,
+        print0: false,
+    };
+    let mut rest = args[split..].iter();
+    while let Some(primary) = rest.next() {
+        let mut value = || {
+            rest.next()
+                .map(String::as_str)
+                .ok_or_else(|| usage(format!("missing argument to `{primary}`")))
+        };
+        match primary.as_str() {
+            "-name" | "-iname" | "-path" | "-ipath" | "-wholename" | "-iwholename" => {
+                let pattern = value()?;
+                let glob = GlobBuilder::new(pattern)
+                    .case_insensitive(primary.starts_with("-i"))
+                    .backslash_escape(true)
+                    .build()
+                    .map_err(|e| usage(format!("{primary}: {e}")))?
+                    .compile_matcher();
+                if primary.ends_with("name") && !primary.contains("whole") {
+                    expression.patterns.push(pattern.to_string());
+                    expression.tests.push(Test::Name(glob));
+                } else {
+                    expression.tests.push(Test::Path(glob));
+                }
+            }
+            "-type" => {
+                let dir = match value()? {
+                    "f" => false,
+                    "d" => true,
+                    other => return Err(usage(format!("-type {other}: only f and d are indexed"))),
+                };
+                expression.tests.push(Test::Type { dir });
+            }
+            "-mtime" | "-mmin" => {
+                let cmp = Compare::parse(primary, value()?)?;
+                let unit = if primary == "-mtime" { 86_400 } else { 60 };
+                expression.tests.push(Test::Age { cmp, unit });
+            }
+            "-size" => {
+                let raw = value()?;
+                let (digits, unit) = match raw.char_indices().last() {
+                    Some((i, suffix)) if suffix.is_ascii_alphabetic() => {
+                        let unit = match suffix {
+                            'c' => 1,
+                            'w' => 2,
+                            'b' => 512,
+                            'k' => 1024,
+                            'M' => 1024 * 1024,
+                            'G' => 1024 * 1024 * 1024,
+                            _ => return Err(usage(format!("-size: unknown unit in `{raw}`"))),
+                        };
+                        (&raw[..i], unit)
+                    }
+                    _ => (raw, 512),
+                };
+                let cmp = Compare::parse(primary, digits)?;
+                expression.tests.push(Test::Size { cmp, unit });
+            }
+            "-mindepth" | "-maxdepth" => {
+                let raw = value()?;
+                let depth = raw
+                    .parse()
+                    .map_err(|_| usage(format!("{primary}: invalid depth `{raw}`")))?;
+                if primary == "-mindepth" {
+                    expression.min_depth = depth;
+                } else {
+                    expression.max_depth = Some(depth);
+                }
+            }
+            "-print" | "-a" | "-and" => {}
+            "-print0" => expression.print0 = true,
+            other => {
+                return Err(usage(format!(
+                    "`{other}` is not supported; use find(1) for this expression"
+                )))
+            }
+        }
+    }
+    Ok(expression)
+}
+
+fn usage(message: String) -> Error {
+    Error::Config(format!("find: {message}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    fn result(path: &str, size: u64, mtime: i64, is_dir: bool) -> vicaya_core::ipc::SearchResult {
+        vicaya_core::ipc::SearchResult {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            score: 1.0,
+            size,
+            mtime,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir,
+            total_size: None,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn parse_maps_find_primaries_onto_tests_and_a_term() {
+        let expression = parse(&args(
+            "src docs -iname *.RS -type f -size +1k -mtime -7 -print0",
+        ))
+        .unwrap();
+        assert_eq!(expression.roots, ["src", "docs"]);
+        assert!(expression.print0);
+        assert_eq!(search_term(&expression.patterns), ".RS");
+        assert_eq!(expression.needs_full_index(), Some("-size"));
+        assert_eq!(
+            parse(&args(". -type d")).unwrap().needs_full_index(),
+            Some("-type d")
+        );
+        assert_eq!(
+            parse(&args(". -type f -mtime -1"))
+                .unwrap()
+                .needs_full_index(),
+            None
+        );
+
+        let now = 100 * 86_400;
+        let passes = |r: &vicaya_core::ipc::SearchResult| {
+            expression
+                .tests
+                .iter()
+                .all(|test| test.matches(r, &r.path, now))
+        };
+        assert!(passes(&result(
+            "/p/src/main.rs",
+            2_000,
+            now - 86_400,
+            false
+        )));
+        assert!(!passes(&result("/p/src/main.rs", 1_024, now, false)));
+        assert!(!passes(&result(
+            "/p/src/main.rs",
+            2_000,
+            now - 8 * 86_400,
+            false
+        )));
+        assert!(!passes(&result("/p/src/old.rs", 2_000, now, true)));
+        assert!(!passes(&result("/p/src/main.go", 2_000, now, false)));
+
+        let expression = parse(&args("-name config*.toml -maxdepth 2")).unwrap();
+        assert_eq!(expression.roots, ["."]);
+        assert_eq!(expression.max_depth, Some(2));
+        assert_eq!(search_term(&expression.patterns), "config");
+
+        let expression = parse(&args(". -path ./src/*.rs")).unwrap();
+        assert!(expression.patterns.is_empty());
+        let main = result("/p/src/main.rs", 0, 0, false);
+        assert!(expression.tests[0].matches(&main, "./src/main.rs", 0));
+        assert!(!expression.tests[0].matches(&main, "./lib/main.rs", 0));
+    }
+
+    #[test]
+    fn size_reads_a_directorys_own_size_like_find() {
+        let mut dir = result("/p/target", 4_096, 0, true);
+        dir.total_size = Some(50 * 1024 * 1024);
+        let size = |line: &str| parse(&args(line)).unwrap().tests.remove(0);
+        assert!(size(". -size -9").matches(&dir, &dir.path, 0));
+        assert!(!size(". -size +1M").matches(&dir, &dir.path, 0));
+    }
+
+    #[test]
+    fn parse_rejects_what_it_cannot_answer_like_find() {
+        for line in [
+            ". -name a -o -name b",
+            ". ! -name a",
+            ". -exec rm {} ;",
+            ". -type l",
+            ". -name",
+            ". -size 10x",
+            ". -mtime soon",
+        ] {
+            assert!(parse(&args(line)).is_err(), "{line}");
+        }
+    }
+
+    #[test]
+    fn display_path_keeps_the_starting_path_as_given() {
+        let scope = Path::new("/home/me/code");
+        assert_eq!(
+            display_path(".", scope, "/home/me/code/src/main.rs").as_deref(),
+            Some("./src/main.rs")
+        );
+        assert_eq!(
+            display_path("~/code", scope, "/home/me/code").as_deref(),
+            Some("~/code")
+        );
+        assert_eq!(display_path(".", scope, "/elsewhere/x"), None);
+    }
+}
@@ -0,0 +1,180 @@
+//! `vicaya grep --engine index`: content search over files the index lists.
+//!
+//! Instead of walking the scope, the candidates come from a daemon search
+//! for everything under it, so exclusions, ignore files and `show_hidden`
+//! apply exactly as they do to name search. `--ext` and `--max-filesize`
+//! then drop candidates by indexed metadata before any file is opened, and
+//! the rest are scanned in parallel by
+//! [`content_search::search_files`](vicaya_core::content_search::search_files).
+//! Files created since the last index update are not searched.
+
+use std::path::{Path, PathBuf};
+
+use vicaya_client::Client;
+use vicaya_core::content_search::{self, ContentSearchEngine, ContentSearchReport};
+use vicaya_core::ipc::{Request, Response, SearchFilters, SearchFlags, SearchOptions, SearchSort};
+use vicaya_core::query::parse_size_expr;
+use vicaya_core::{Error, Result};
+
+use crate::render::{self, Tone};
+
+/// Most candidates one search lists from the index.
+const CANDIDATE_LIMIT: usize = 100_000;
+/// Largest file searched when `--max-filesize` is not given; the cap the
+/// ripgrep engine uses.
+const DEFAULT_MAX_FILESIZE: u64 = 2 * 1024 * 1024;
+
+/// Candidate filters applied to indexed metadata.
+#[derive(Debug, Default)]
+pub(crate) struct CandidateFilters {
+    /// Lowercase extensions without the dot; empty keeps every file.
+    exts: Vec<String>,
+    max_filesize: Option<u64>,
+}
+
+impl CandidateFilters {
+    /// Filters from `--ext` values (`rs`, `.md`, or `rs,md`) and a
+    /// `--max-filesize` such as `512k` or `2M`.
+    pub(crate) fn parse(exts: &[String], max_filesize: Option<&str>) -> Result<Self> {
+        let exts = exts
+            .iter()
+            .flat_map(|ext| ext.split(','))
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        let max_filesize = max_filesize
+            .map(|value| {
+                parse_size_expr(&format!("<={}", value.trim()))
+                    .map(|cmp| cmp.value)
+                    .ok_or_else(|| {
+                        Error::Config(format!(
+                            "invalid --max-filesize '{value}' (expected a size such as 512k or 2M)"
+                        ))
+                    })
+            })
+            .transpose()?;
+        Ok(Self { exts, max_filesize })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.exts.is_empty() && self.max_filesize.is_none()
+    }
+
+    fn max_filesize(&self) -> u64 {
+        self.max_filesize.unwrap_or(DEFAULT_MAX_FILESIZE)
+    }
+
+    fn keeps(&self, path: &str, size: u64) -> bool {
+        let ext_ok = self.exts.is_empty()
+            || Path::new(path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| self.exts.contains(&ext.to_lowercase()));
+        ext_ok && size <= self.max_filesize()
+    }
+}
+
+/// Search the indexed files under `scope` for `query`.
+pub(crate) fn search(
+    query: &str,
+    scope: &Path,
+    limit: usize,
+    filters: &CandidateFilters,
+    machine_output: bool,
+) -> Result<ContentSearchReport> {
+    let candidates = if scope.is_file() {
+        vec![scope.to_path_buf()]
+    } else {
+        crate::ensure_daemon_running(machine_output)?;
+        candidates(scope, filters)?
+    };
+    Ok(ContentSearchReport {
+        engine: ContentSearchEngine::Index,
+        hits: content_search::search_files(query, &candidates, limit, filters.max_filesize()),
+    })
+}
+
+/// Indexed files under `scope` that pass `filters`, in path order.
+fn candidates(scope: &Path, filters: &CandidateFilters) -> Result<Vec<PathBuf>> {
+    let scope = scope.to_string_lossy().to_string();
+    let request = Request::Search(SearchOptions {
+        scope: Some(scope.clone()),
+        filters: SearchFilters {
+            scope: Some(scope.clone()),
+            ..SearchFilters::default()
+        },
+        sort: SearchSort::Path,
+        fuzzy: false,
+        flags: SearchFlags {
+            recent_if_empty: true,
+            ..SearchFlags::default()
+        },
+        ..SearchOptions::new(String::new(), CANDIDATE_LIMIT)
+    });
+    let (results, truncated) = match Client::connect()?.request(&request)? {
+        Response::SearchResults {
+            results, truncated, ..
+        } => (results, truncated),
+        Response::Error { message } => return Err(Error::Other(message)),
+        _ => return Err(Error::Other("Unexpected response from daemon".to_string())),
+    };
+    if truncated || results.len() >= CANDIDATE_LIMIT {
+        eprintln!("note: files under {scope} may be incomplete (query budget or limit)");
+    }
+    Ok(results
+        .into_iter()
+        .filter(|result| !result.is_dir && filters.keeps(&result.path, result.size))
+        .map(|result| PathBuf::from(result.path))
+        .collect())
+}
+
+/// ripgrep's default terminal layout: each file's path once, then its
+/// matches as `line:column:text`, with a blank line between files.
+pub(crate) fn print_heading(report: &ContentSearchReport) {
+    let color = render::color_enabled();
+    let mut current: Option<&Path> = None;
+    for hit in &report.hits {
+        if current != Some(hit.path.as_path()) {
+            if current.is_some() {
+                println!();
+            }
+            println!(
+                "{}",
+                Tone::Hot.paint(&hit.path.display().to_string(), color)
+            );
+            current = Some(&hit.path);
+        }
+        let position = match hit.column {
+            Some(column) => format!("{}:{column}", hit.line_number),
+            None => hit.line_number.to_string(),
+        };
+        println!("{}:{}", Tone::Good.paint(&position, color), hit.line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn candidate_filters_parse_extension_lists_and_sizes() {
+        let filters =
+            CandidateFilters::parse(&["rs,.MD".to_string(), " toml ".to_string()], Some("512k"))
+                .unwrap();
+        assert_eq!(filters.exts, ["rs", "md", "toml"]);
+        assert_eq!(filters.max_filesize(), 512 * 1024);
+        assert!(filters.keeps("/repo/src/main.rs", 100));
+        assert!(filters.keeps("/repo/README.md", 512 * 1024));
+        assert!(!filters.keeps("/repo/README.md", 512 * 1024 + 1));
+        assert!(!filters.keeps("/repo/build.log", 100));
+        assert!(!filters.keeps("/repo/Makefile", 100));
+
+        let defaults = CandidateFilters::parse(&[], None).unwrap();
+        assert!(defaults.is_empty());
+        assert!(defaults.keeps("/repo/Makefile", DEFAULT_MAX_FILESIZE));
+        assert!(!defaults.keeps("/repo/Makefile", DEFAULT_MAX_FILESIZE + 1));
+
+        assert!(CandidateFilters::parse(&[], Some("huge")).is_err());
+        assert!(CandidateFilters::parse(&[], Some(">2M")).is_err());
+    }
+}
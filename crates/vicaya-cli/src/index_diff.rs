@@ -0,0 +1,328 @@
+//! `vicaya index diff`: compare two index snapshots.
+//!
+//! Reports entries added, removed, or changed (size or mtime) between two
+//! `index.bin` files, e.g. a copy taken before a reconcile and the one it
+//! wrote, to audit what changed or find watcher events that were missed.
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use vicaya_core::{Error, Result};
+use vicaya_scanner::IndexSnapshot;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum IndexAction {
+    /// Show entries added, removed, or changed between two index files
+    Diff(IndexDiffArgs),
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct IndexDiffArgs {
+    /// Older index file (e.g. a saved copy of index.bin)
+    pub(crate) old: PathBuf,
+
+    /// Newer index file
+    pub(crate) new: PathBuf,
+
+    /// Maximum paths listed per section in pretty output (0 lists all)
+    #[arg(short, long, default_value = "50")]
+    pub(crate) limit: usize,
+
+    /// Output format (pretty, json, plain)
+    #[arg(short, long, default_value = "pretty")]
+    pub(crate) format: String,
+}
+
+/// Size and mtime of one indexed entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+struct EntryStat {
+    size: u64,
+    mtime: i64,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Entry {
+    path: String,
+    #[serde(flatten)]
+    stat: EntryStat,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+struct Change {
+    path: String,
+    old: EntryStat,
+    new: EntryStat,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+struct DiffSummary {
+    old_entries: usize,
+    new_entries: usize,
+    added: usize,
+    removed: usize,
+    changed: usize,
+    unchanged: usize,
+    /// Change in the total size of indexed files, in bytes.
+    size_delta: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct IndexDiff {
+    summary: DiffSummary,
+    added: Vec<Entry>,
+    removed: Vec<Entry>,
+    changed: Vec<Change>,
+}
+
+pub(crate) fn run(action: IndexAction) -> Result<()> {
+    match action {
+        IndexAction::Diff(args) => {
+            let old = load(&args.old)?;
+            let new = load(&args.new)?;
+            let diff = diff(&old, &new);
+            match args.format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&diff).unwrap()),
+                "plain" => print_plain(&diff),
+                _ => print_pretty(&diff, args.limit),
+            }
+            Ok(())
+        }
+    }
+}
+
+fn load(path: &Path) -> Result<IndexSnapshot> {
+    IndexSnapshot::load(path)
+        .map_err(|e| Error::Other(format!("Failed to load {}: {e}", path.display())))
+}
+
+/// Live entries keyed by path.
+fn entries(snapshot: &IndexSnapshot) -> HashMap<String, EntryStat> {
+    snapshot
+        .file_table
+        .iter()
+        .filter(|(_, meta)| !meta.is_tombstone())
+        .filter_map(|(_, meta)| {
+            let path = snapshot.file_table.path_of(meta, &snapshot.string_arena)?;
+            Some((
+                path,
+                EntryStat {
+                    size: meta.size,
+                    mtime: meta.mtime,
+                },
+            ))
+        })
+        .collect()
+}
+
+fn diff(old: &IndexSnapshot, new: &IndexSnapshot) -> IndexDiff {
+    let old = entries(old);
+    let new = entries(new);
+    let mut summary = DiffSummary {
+        old_entries: old.len(),
+        new_entries: new.len(),
+        ..DiffSummary::default()
+    };
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+    let mut changed = Vec::new();
+
+    for (path, &stat) in &new {
+        match old.get(path) {
+            None => added.push(Entry {
+                path: path.clone(),
+                stat,
+            }),
+            Some(&before) if before != stat => changed.push(Change {
+                path: path.clone(),
+                old: before,
+                new: stat,
+            }),
+            Some(_) => summary.unchanged += 1,
+        }
+    }
+    for (path, &stat) in &old {
+        if !new.contains_key(path) {
+            removed.push(Entry {
+                path: path.clone(),
+                stat,
+            });
+        }
+    }
+    added.sort_by(|a, b| a.path.cmp(&b.path));
+    removed.sort_by(|a, b| a.path.cmp(&b.path));
+    changed.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let total = |entries: &HashMap<String, EntryStat>| -> i64 {
+        entries.values().map(|stat| stat.size as i64).sum()
+    };
+    summary.added = added.len();
+    summary.removed = removed.len();
+    summary.changed = changed.len();
+    summary.size_delta = total(&new) - total(&old);
+
+    IndexDiff {
+        summary,
+        added,
+        removed,
+        changed,
+    }
+}
+
+/// One `+`/`-`/`~` line per path, for piping into other tools.
+fn print_plain(diff: &IndexDiff) {
+    for entry in &diff.added {
+        println!("+ {}", entry.path);
+    }
+    for entry in &diff.removed {
+        println!("- {}", entry.path);
+    }
+    for change in &diff.changed {
+        println!("~ {}", change.path);
+    }
+}
+
+fn print_pretty(diff: &IndexDiff, limit: usize) {
+    let summary = &diff.summary;
+    println!(
+        "Entries: {} → {}",
+        crate::format_number(summary.old_entries),
+        crate::format_number(summary.new_entries)
+    );
+    println!(
+        "Added: {}  Removed: {}  Changed: {}  Unchanged: {}",
+        crate::format_number(summary.added),
+        crate::format_number(summary.removed),
+        crate::format_number(summary.changed),
+        crate::format_number(summary.unchanged)
+    );
+    let sign = if summary.size_delta < 0 { "-" } else { "+" };
+    println!(
+        "Size: {sign}{}",
+        format_size(summary.size_delta.unsigned_abs())
+    );
+
+    let shown = |len: usize| if limit == 0 { len } else { len.min(limit) };
+    let more = |len: usize| {
+        if len > shown(len) {
+            println!("  … {} more", crate::format_number(len - shown(len)));
+        }
+    };
+
+    if !diff.added.is_empty() {
+        println!("\nAdded:");
+        for entry in &diff.added[..shown(diff.added.len())] {
+            println!("  + {}  ({})", entry.path, format_size(entry.stat.size));
+        }
+        more(diff.added.len());
+    }
+    if !diff.removed.is_empty() {
+        println!("\nRemoved:");
+        for entry in &diff.removed[..shown(diff.removed.len())] {
+            println!("  - {}", entry.path);
+        }
+        more(diff.removed.len());
+    }
+    if !diff.changed.is_empty() {
+        println!("\nChanged:");
+        for change in &diff.changed[..shown(diff.changed.len())] {
+            let mut what = Vec::new();
+            if change.old.size != change.new.size {
+                what.push(format!(
+                    "size {} → {}",
+                    format_size(change.old.size),
+                    format_size(change.new.size)
+                ));
+            }
+            if change.old.mtime != change.new.mtime {
+                what.push(format!(
+                    "mtime {} → {}",
+                    format_mtime(change.old.mtime),
+                    format_mtime(change.new.mtime)
+                ));
+            }
+            println!("  ~ {}  ({})", change.path, what.join(", "));
+        }
+        more(diff.changed.len());
+    }
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn format_mtime(mtime: i64) -> String {
+    chrono::DateTime::from_timestamp(mtime, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| mtime.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vicaya_core::Config;
+    use vicaya_scanner::Scanner;
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_entries() {
+        let root = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        let mut config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            exclusions: Vec::new(),
+            respect_ignore_files: false,
+            ..Config::default()
+        };
+        config.index_path = index_dir.path().to_path_buf();
+        std::fs::write(root.path().join("kept.txt"), "same").unwrap();
+        std::fs::write(root.path().join("grown.txt"), "a").unwrap();
+        std::fs::write(root.path().join("gone.txt"), "bye").unwrap();
+
+        let before = Scanner::new(config.clone()).scan().unwrap();
+        let old_file = config.index_path.join("old.bin");
+        before.save(&old_file).unwrap();
+
+        std::fs::write(root.path().join("grown.txt"), "abcdef").unwrap();
+        std::fs::remove_file(root.path().join("gone.txt")).unwrap();
+        std::fs::write(root.path().join("new.txt"), "hi").unwrap();
+        let after = Scanner::new(config.clone()).scan().unwrap();
+
+        let diff = diff(&load(&old_file).unwrap(), &after);
+        let path = |name: &str| root.path().join(name).to_string_lossy().to_string();
+        assert_eq!(
+            diff.added.iter().map(|e| &e.path).collect::<Vec<_>>(),
+            vec![&path("new.txt")]
+        );
+        assert_eq!(
+            diff.removed.iter().map(|e| &e.path).collect::<Vec<_>>(),
+            vec![&path("gone.txt")]
+        );
+        // The root directory itself may also change (mtime, and size on
+        // some filesystems) as entries come and go.
+        let files: Vec<&Change> = diff
+            .changed
+            .iter()
+            .filter(|change| change.path.ends_with(".txt"))
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, path("grown.txt"));
+        assert_eq!((files[0].old.size, files[0].new.size), (1, 6));
+        assert_eq!(
+            diff.summary.unchanged + diff.summary.changed + diff.summary.added,
+            diff.summary.new_entries
+        );
+
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["summary"]["added"], 1);
+        assert_eq!(json["summary"]["removed"], 1);
+        assert_eq!(json["added"][0]["size"], 2);
+    }
+}
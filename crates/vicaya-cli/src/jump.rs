@@ -0,0 +1,42 @@
+//! `vicaya z QUERY...`: zoxide-style directory jumping.
+//!
+//! Prints the directory the daemon picks for the keywords (see
+//! `Request::Jump`) so a shell function can `cd` to it:
+//!
+//! ```sh
+//! z() { cd "$(vicaya z "$@")"; }
+//! ```
+//!
+//! Every answer is remembered as an `enter`, alongside TUI scope pushes, so
+//! the directories jumped to most often and most recently win later jumps.
+
+use clap::Args;
+use vicaya_client::Client;
+use vicaya_core::ipc::{Request, Response};
+use vicaya_core::{Error, Result};
+
+#[derive(Args, Debug)]
+pub(crate) struct JumpArgs {
+    /// Keywords that appear in the directory's path in order, the last one
+    /// in its name
+    #[arg(required = true, allow_hyphen_values = true)]
+    query: Vec<String>,
+}
+
+pub(crate) fn run(args: JumpArgs) -> Result<()> {
+    crate::ensure_daemon_running(true)?;
+    let query = args.query.join(" ");
+    match Client::connect()?.request(&Request::Jump {
+        query: query.clone(),
+    })? {
+        Response::JumpTarget { path: Some(path) } => {
+            println!("{path}");
+            Ok(())
+        }
+        Response::JumpTarget { path: None } => {
+            Err(Error::Other(format!("No directory matches \"{query}\"")))
+        }
+        Response::Error { message } => Err(Error::Other(message)),
+        _ => Err(Error::Other("Unexpected response from daemon".to_string())),
+    }
+}
@@ -0,0 +1,246 @@
+//! `vicaya search --format alfred|raycast`: launcher-ready result JSON.
+//!
+//! Both formats carry everything a launcher extension needs to render a row
+//! (title, path subtitle, file-kind icon) and to wire the secondary actions
+//! (reveal, copy) without post-processing the plain JSON output.
+
+use serde::Serialize;
+use std::path::Path;
+use vicaya_core::ipc::SearchResult;
+
+/// Alfred Script Filter output.
+#[derive(Debug, Serialize)]
+pub(crate) struct AlfredOutput {
+    items: Vec<AlfredItem>,
+}
+
+#[derive(Debug, Serialize)]
+struct AlfredItem {
+    uid: String,
+    #[serde(rename = "type")]
+    item_type: &'static str,
+    title: String,
+    subtitle: String,
+    arg: String,
+    autocomplete: String,
+    icon: AlfredIcon,
+    quicklookurl: String,
+    mods: AlfredMods,
+}
+
+#[derive(Debug, Serialize)]
+struct AlfredIcon {
+    #[serde(rename = "type")]
+    icon_type: &'static str,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AlfredMods {
+    cmd: AlfredMod,
+    alt: AlfredMod,
+}
+
+#[derive(Debug, Serialize)]
+struct AlfredMod {
+    arg: String,
+    subtitle: &'static str,
+    variables: AlfredModVariables,
+}
+
+#[derive(Debug, Serialize)]
+struct AlfredModVariables {
+    action: &'static str,
+}
+
+/// Raycast list output, shaped after Raycast's `List.Item` props.
+#[derive(Debug, Serialize)]
+pub(crate) struct RaycastOutput {
+    items: Vec<RaycastItem>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RaycastItem {
+    id: String,
+    title: String,
+    subtitle: String,
+    icon: RaycastIcon,
+    kind: &'static str,
+    accessories: Vec<RaycastAccessory>,
+    actions: Vec<RaycastAction>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RaycastIcon {
+    file_icon: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RaycastAccessory {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RaycastAction {
+    #[serde(rename = "type")]
+    action_type: &'static str,
+    title: &'static str,
+    path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shortcut: Option<&'static str>,
+}
+
+pub(crate) fn alfred(results: &[SearchResult]) -> AlfredOutput {
+    let items = results
+        .iter()
+        .map(|result| AlfredItem {
+            uid: result.path.clone(),
+            item_type: "file",
+            title: result.name.clone(),
+            subtitle: result.path.clone(),
+            arg: result.path.clone(),
+            autocomplete: result.name.clone(),
+            icon: AlfredIcon {
+                icon_type: "fileicon",
+                path: result.path.clone(),
+            },
+            quicklookurl: result.path.clone(),
+            mods: AlfredMods {
+                cmd: AlfredMod {
+                    arg: result.path.clone(),
+                    subtitle: "Reveal in Finder",
+                    variables: AlfredModVariables { action: "reveal" },
+                },
+                alt: AlfredMod {
+                    arg: result.path.clone(),
+                    subtitle: "Copy path",
+                    variables: AlfredModVariables { action: "copy" },
+                },
+            },
+        })
+        .collect();
+    AlfredOutput { items }
+}
+
+pub(crate) fn raycast(results: &[SearchResult]) -> RaycastOutput {
+    let items = results
+        .iter()
+        .map(|result| RaycastItem {
+            id: result.path.clone(),
+            title: result.name.clone(),
+            subtitle: result.path.clone(),
+            icon: RaycastIcon {
+                file_icon: result.path.clone(),
+            },
+            kind: file_kind(Path::new(&result.path)),
+            accessories: modified_accessory(result.mtime).into_iter().collect(),
+            actions: vec![
+                RaycastAction {
+                    action_type: "open",
+                    title: "Open",
+                    path: result.path.clone(),
+                    shortcut: None,
+                },
+                RaycastAction {
+                    action_type: "reveal",
+                    title: "Reveal in Finder",
+                    path: result.path.clone(),
+                    shortcut: Some("cmd+enter"),
+                },
+                RaycastAction {
+                    action_type: "copy",
+                    title: "Copy Path",
+                    path: result.path.clone(),
+                    shortcut: Some("opt+enter"),
+                },
+            ],
+        })
+        .collect();
+    RaycastOutput { items }
+}
+
+fn file_kind(path: &Path) -> &'static str {
+    match std::fs::symlink_metadata(path) {
+        Ok(meta) if meta.is_dir() => "directory",
+        Ok(meta) if meta.file_type().is_symlink() => "symlink",
+        Ok(_) => "file",
+        Err(_) => "missing",
+    }
+}
+
+fn modified_accessory(mtime: i64) -> Option<RaycastAccessory> {
+    let modified = chrono::DateTime::from_timestamp(mtime, 0)?;
+    Some(RaycastAccessory {
+        text: modified.format("%Y-%m-%d %H:%M").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn result(path: &str, name: &str) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            name: name.to_string(),
+            score: 0.9,
+            size: 10,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn alfred_items_follow_the_script_filter_schema() {
+        let output = serde_json::to_value(alfred(&[result("/tmp/a/main.rs", "main.rs")])).unwrap();
+        let item = &output["items"][0];
+
+        assert_eq!(item["uid"], "/tmp/a/main.rs");
+        assert_eq!(item["type"], "file");
+        assert_eq!(item["title"], "main.rs");
+        assert_eq!(item["subtitle"], "/tmp/a/main.rs");
+        assert_eq!(item["arg"], "/tmp/a/main.rs");
+        assert_eq!(
+            item["icon"],
+            json!({"type": "fileicon", "path": "/tmp/a/main.rs"})
+        );
+        assert_eq!(item["mods"]["cmd"]["variables"]["action"], "reveal");
+        assert_eq!(item["mods"]["alt"]["variables"]["action"], "copy");
+    }
+
+    #[test]
+    fn raycast_items_carry_kind_icon_and_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().to_string_lossy().to_string();
+        let output = serde_json::to_value(raycast(&[
+            result(&dir_path, "dir"),
+            result("/definitely/not/here.txt", "here.txt"),
+        ]))
+        .unwrap();
+
+        let item = &output["items"][0];
+        assert_eq!(item["kind"], "directory");
+        assert_eq!(item["icon"], json!({"fileIcon": dir_path}));
+        assert_eq!(item["accessories"], json!([{"text": "1970-01-01 00:00"}]));
+        let actions: Vec<&str> = item["actions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|action| action["type"].as_str().unwrap())
+            .collect();
+        assert_eq!(actions, vec!["open", "reveal", "copy"]);
+        assert!(item["actions"][0].get("shortcut").is_none());
+
+        assert_eq!(output["items"][1]["kind"], "missing");
+    }
+}
@@ -0,0 +1,72 @@
+//! `vicaya logs`: read the daemon's local logs.
+
+use clap::Subcommand;
+use vicaya_core::audit::AuditRecord;
+use vicaya_core::Result;
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum LogsAction {
+    /// Show who asked the daemon to rebuild, reconcile, exclude, clear Smriti,
+    /// upgrade or shut down, and how it went
+    Audit {
+        /// Show only the newest N records (0 = all)
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+
+        /// Output format (table, json)
+        #[arg(short, long, default_value = "table")]
+        format: String,
+    },
+}
+
+pub(crate) fn run(action: LogsAction) -> Result<()> {
+    match action {
+        LogsAction::Audit { limit, format } => {
+            let records = vicaya_core::audit::load(&vicaya_core::paths::audit_log_path())?;
+            let skip = match limit {
+                0 => 0,
+                limit => records.len().saturating_sub(limit),
+            };
+            print_audit(&records[skip..], &format);
+        }
+    }
+    Ok(())
+}
+
+fn print_audit(records: &[AuditRecord], format: &str) {
+    match format {
+        "json" => println!("{}", serde_json::to_string_pretty(records).unwrap()),
+        _ => {
+            if records.is_empty() {
+                println!("No privileged requests recorded yet.");
+                return;
+            }
+            println!(
+                "{:<19}  {:>7}  {:>5}  {:<12}  {:<7}  DETAIL",
+                "TIME", "PID", "UID", "REQUEST", "OUTCOME"
+            );
+            for record in records {
+                let detail = match (&record.detail, &record.message) {
+                    (Some(detail), Some(message)) => format!("{detail} ({message})"),
+                    (Some(detail), None) => detail.clone(),
+                    (None, Some(message)) => message.clone(),
+                    (None, None) => String::new(),
+                };
+                println!(
+                    "{:<19}  {:>7}  {:>5}  {:<12}  {:<7}  {}",
+                    crate::format_local_time(record.at).unwrap_or_default(),
+                    optional(record.peer_pid),
+                    optional(record.peer_uid),
+                    record.request,
+                    record.outcome.as_str(),
+                    detail
+                );
+            }
+        }
+    }
+}
+
+/// `value`, or `-` when the platform did not report it.
+fn optional(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| value.to_string())
+}
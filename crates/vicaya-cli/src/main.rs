@@ -1,18 +1,37 @@
 //! vicaya-cli: Command-line interface for vicaya.
 
-mod ipc_client;
+mod aliases;
+mod bookmark;
+mod complete;
+mod du;
+mod exclusions;
+mod find;
+mod grep;
+mod index_diff;
+mod jump;
+mod launcher;
+mod logs;
 mod metrics;
+mod offline;
+mod preview;
+mod reconcile;
+mod render;
+mod spotlight;
+mod status_report;
 mod upgrade;
 
 use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 use std::path::{Path, PathBuf};
 use tracing::info;
-use vicaya_core::ipc::{Request, Response};
+use vicaya_client::Client;
+use vicaya_core::i18n::{self, t};
+use vicaya_core::ipc::{
+    BatchResults, DaemonStatus, DirectoryRank, Request, Response, ScoreExplanation, SearchFilters,
+    SearchFlags, SearchOptions, SearchSort, MAX_SEARCH_BATCH,
+};
 use vicaya_core::{Config, Result};
 use vicaya_scanner::Scanner;
 
-use crate::ipc_client::IpcClient;
-
 #[derive(Debug, Parser)]
 #[command(name = "vicaya")]
 #[command(about = "विचय — blazing-fast filesystem search for macOS", long_about = None)]
@@ -22,6 +41,11 @@ struct Cli {
     #[arg(short = 'V', long = "version", action = ArgAction::SetTrue)]
     version: bool,
 
+    /// Use a named profile with its own config, index, and daemon
+    /// (also `VICAYA_PROFILE`)
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -39,24 +63,103 @@ enum Commands {
         force: bool,
     },
 
+    /// Check the config file for problems and report them with line/column
+    Doctor {
+        /// Config file to check (defaults to ~/.vicaya/config.toml)
+        #[arg(long, value_name = "PATH")]
+        config: Option<PathBuf>,
+
+        /// Output format (pretty, json)
+        #[arg(short, long, default_value = "pretty")]
+        format: String,
+    },
+
     /// Search for files
     Search {
         /// Search query
-        query: String,
+        #[arg(required_unless_present = "stdin")]
+        query: Option<String>,
 
         /// Maximum number of results
         #[arg(short, long, default_value = "20")]
         limit: usize,
 
-        /// Output format (table, json, plain)
+        /// Output format (table, json, plain, alfred, raycast)
         #[arg(short, long, default_value = "table")]
         format: String,
 
         /// Restrict results to this directory subtree
         #[arg(long, value_name = "DIR")]
         scope: Option<PathBuf>,
+
+        /// Only list results at most N levels below --scope, or below their
+        /// index root without one (1 = direct entries)
+        #[arg(long, value_name = "N")]
+        max_depth: Option<usize>,
+
+        /// Skip this many ranked results (for paging with --limit)
+        #[arg(long, default_value = "0")]
+        offset: usize,
+
+        /// Order of the listed results (relevance, name, path, mtime, size)
+        #[arg(long, default_value = "relevance", value_parser = SearchSort::parse)]
+        sort: SearchSort,
+
+        /// Only match substrings; skip abbreviation matches like `mr` → main.rs
+        #[arg(long)]
+        exact: bool,
+
+        /// Show how each result's score was reached (match strategy,
+        /// boosts, penalties, and tie-breakers)
+        #[arg(long)]
+        explain: bool,
+
+        /// Ranking policy: relevance (default) or recency (newest first)
+        #[arg(long, value_name = "NAME")]
+        scorer: Option<String>,
+
+        /// Rank directories against files: neutral (default), prefer, or demote
+        #[arg(long, default_value = "neutral", value_parser = DirectoryRank::parse)]
+        directories: DirectoryRank,
+
+        /// Search the saved index in-process instead of asking the daemon
+        #[arg(long)]
+        offline: bool,
+
+        /// When nothing under --scope matches, print nothing instead of the
+        /// matches outside it (only table output lists those)
+        #[arg(long)]
+        no_broaden: bool,
+
+        /// Read one query per line from stdin and answer them all in batched
+        /// round trips (table, json, or plain output)
+        #[arg(long, conflicts_with_all = ["query", "offline"])]
+        stdin: bool,
+
+        /// With --stdin, stop after this many results across all queries
+        #[arg(long, value_name = "N")]
+        total_limit: Option<usize>,
+
+        /// Print up to N lines of each text result under its path: lines
+        /// containing the query, or the first lines when none do (table and
+        /// plain output)
+        #[arg(long, value_name = "N", conflicts_with = "stdin")]
+        preview: Option<usize>,
     },
 
+    /// Answer a find(1) expression from the index: `vicaya find . -name '*.rs' -mtime -7`
+    Find(find::FindArgs),
+
+    /// Complete a path from the index, most frecent first (for shell plugins)
+    CompletePath(complete::CompletePathArgs),
+
+    /// Print the most frecent directory matching keywords, for `cd "$(vicaya z proj)"`
+    #[command(name = "z")]
+    Jump(jump::JumpArgs),
+
+    /// Show how much an indexed directory and its largest subdirectories hold
+    Du(du::DuArgs),
+
     /// Search file contents in a scope
     Grep {
         /// Literal content query
@@ -66,21 +169,33 @@ enum Commands {
         #[arg(short, long, default_value = "20")]
         limit: usize,
 
-        /// Output format (table, json, plain)
-        #[arg(short, long, default_value = "table")]
+        /// Output format: heading (ripgrep's layout, each path once), table,
+        /// json, or plain
+        #[arg(short, long, default_value = "heading")]
         format: String,
 
         /// Restrict content search to this directory or file
         #[arg(long, value_name = "PATH")]
         scope: Option<PathBuf>,
 
-        /// Content search engine
+        /// Content search engine; `index` scans only the files the index
+        /// lists, so it is opt-in: it misses files created since the last
+        /// index update and needs the daemon
         #[arg(long, value_enum)]
         engine: Option<ContentEngineCli>,
 
         /// Permit recursive grep when ripgrep/git-grep are unavailable
         #[arg(long)]
         allow_slow_fallback: bool,
+
+        /// Only search files with these extensions (index engine)
+        #[arg(long, value_name = "EXT", value_delimiter = ',')]
+        ext: Vec<String>,
+
+        /// Skip files larger than this, e.g. 512k or 2M (index engine;
+        /// default 2M)
+        #[arg(long, value_name = "SIZE")]
+        max_filesize: Option<String>,
     },
 
     /// Rebuild the index
@@ -90,6 +205,18 @@ enum Commands {
         dry_run: bool,
     },
 
+    /// Reconcile the index with disk now, or preview the reconcile schedule
+    Reconcile {
+        #[command(subcommand)]
+        action: reconcile::ReconcileAction,
+    },
+
+    /// Inspect index files
+    Index {
+        #[command(subcommand)]
+        action: index_diff::IndexAction,
+    },
+
     /// Show index status
     Status {
         /// Output format (pretty, json)
@@ -97,21 +224,48 @@ enum Commands {
         format: String,
     },
 
+    /// Print the JSON Schema of the daemon socket protocol (every Request and Response)
+    IpcSchema,
+
     /// Show runtime metrics (process, vmmap, index)
     Metrics(metrics::MetricsArgs),
 
+    /// Compare results with Spotlight (mdfind) and explain coverage gaps
+    CompareSpotlight(spotlight::CompareSpotlightArgs),
+
+    /// Suggest exclusions for large low-value subtrees (dependencies, caches)
+    SuggestExclusions(exclusions::SuggestExclusionsArgs),
+
     /// Inspect or clear Smriti usage memory
     Smriti {
         #[command(subcommand)]
         action: SmritiActionCli,
     },
 
+    /// Manage directory bookmarks (TUI keys 1-9 jump ksetra to them)
+    Bookmark {
+        #[command(subcommand)]
+        action: bookmark::BookmarkAction,
+    },
+
+    /// Manage query aliases (`@name` in a query expands to its definition)
+    Aliases {
+        #[command(subcommand)]
+        action: aliases::AliasAction,
+    },
+
     /// Upgrade vicaya to the latest GitHub release
     Upgrade(upgrade::UpgradeArgs),
 
     /// Alias for `upgrade`
     Update(upgrade::UpgradeArgs),
 
+    /// Read the daemon's local logs
+    Logs {
+        #[command(subcommand)]
+        action: logs::LogsAction,
+    },
+
     /// Manage the daemon
     Daemon {
         #[command(subcommand)]
@@ -127,6 +281,13 @@ enum DaemonAction {
     Stop,
     /// Check daemon status
     Status,
+    /// Hand the live index to a new daemon binary without a rescan
+    Upgrade {
+        /// Daemon binary to switch to (default: the running daemon's own
+        /// executable, e.g. after it was replaced in place)
+        #[arg(long)]
+        binary: Option<PathBuf>,
+    },
 }
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
@@ -135,15 +296,20 @@ enum ContentEngineCli {
     Ripgrep,
     GitGrep,
     Grep,
+    /// Scan the files the index lists under the scope
+    Index,
 }
 
-impl From<ContentEngineCli> for vicaya_core::content_search::ContentSearchEngineChoice {
-    fn from(value: ContentEngineCli) -> Self {
-        match value {
-            ContentEngineCli::Auto => Self::Auto,
-            ContentEngineCli::Ripgrep => Self::Ripgrep,
-            ContentEngineCli::GitGrep => Self::GitGrep,
-            ContentEngineCli::Grep => Self::Grep,
+impl ContentEngineCli {
+    /// The library engine selector; `None` for the CLI-only index engine.
+    fn choice(self) -> Option<vicaya_core::content_search::ContentSearchEngineChoice> {
+        use vicaya_core::content_search::ContentSearchEngineChoice as Choice;
+        match self {
+            Self::Auto => Some(Choice::Auto),
+            Self::Ripgrep => Some(Choice::Ripgrep),
+            Self::GitGrep => Some(Choice::GitGrep),
+            Self::Grep => Some(Choice::Grep),
+            Self::Index => None,
         }
     }
 }
@@ -181,9 +347,15 @@ enum SmritiActionCli {
 }
 
 fn main() -> Result<()> {
-    vicaya_core::logging::init();
+    vicaya_core::logging::init_stderr();
 
     let cli = Cli::parse();
+    vicaya_core::paths::init_profile(cli.profile.as_deref())?;
+    // A missing or broken config keeps English; the command that loads it
+    // reports the error.
+    if let Ok(config) = vicaya_core::Config::load(&vicaya_core::paths::config_path()) {
+        i18n::init(config.language);
+    }
 
     if cli.version {
         println!(
@@ -202,13 +374,95 @@ fn main() -> Result<()> {
         Some(Commands::Init { force }) => {
             init_config(force)?;
         }
+        Some(Commands::Doctor { config, format }) => {
+            doctor(config.as_deref(), &format)?;
+        }
         Some(Commands::Search {
             query,
             limit,
             format,
             scope,
+            max_depth,
+            offset,
+            sort,
+            exact,
+            explain,
+            scorer,
+            directories,
+            offline,
+            no_broaden,
+            stdin,
+            total_limit,
+            preview,
         }) => {
-            search(&query, limit, &format, scope.as_deref())?;
+            let query = query.unwrap_or_default();
+            // A broken config should not block searching; the daemon reports it.
+            let aliases = if query.contains(vicaya_core::aliases::ALIAS_SIGIL) {
+                load_config()
+                    .map(|config| config.aliases)
+                    .unwrap_or_default()
+            } else {
+                Default::default()
+            };
+            for name in vicaya_core::aliases::undefined(&query, &aliases) {
+                eprintln!("note: no alias named @{name}; searching for it as text");
+            }
+            let expanded = aliases::expand_query(&query, &aliases, limit)?;
+            let options = SearchOptions {
+                offset,
+                sort,
+                fuzzy: !exact,
+                flags: SearchFlags {
+                    explain,
+                    // Scripts reading other formats rely on --scope holding.
+                    no_broaden: no_broaden || format != "table",
+                    ..SearchFlags::default()
+                },
+                scorer,
+                directories,
+                filters: SearchFilters {
+                    attributes: expanded.attributes.clone(),
+                    max_depth: max_depth.or(expanded.max_depth),
+                    hidden: expanded.hidden,
+                    dir_size: expanded.dir_size,
+                    ..SearchFilters::default()
+                },
+                ..SearchOptions::new(expanded.term.clone(), expanded.fetch_limit())
+            };
+            if total_limit.is_some() && !stdin {
+                return Err(vicaya_core::Error::Config(
+                    "--total-limit only applies with --stdin".to_string(),
+                ));
+            }
+            if preview.is_some() && !matches!(format.as_str(), "table" | "plain") {
+                return Err(vicaya_core::Error::Config(format!(
+                    "--preview supports table and plain output, not `{format}`"
+                )));
+            }
+            // Like aliases, previews fall back to defaults on a broken config.
+            let preview = preview.map(|lines| {
+                let config = load_config().map(|config| config.preview);
+                preview::PreviewOptions::new(lines, &options.term, &config.unwrap_or_default())
+            });
+            if stdin {
+                search_stdin(options, total_limit, &format, scope.as_deref())?;
+            } else if offline {
+                search_offline(
+                    options,
+                    &format,
+                    scope.as_deref(),
+                    preview.as_ref(),
+                    &expanded,
+                )?;
+            } else {
+                search(
+                    options,
+                    &format,
+                    scope.as_deref(),
+                    preview.as_ref(),
+                    &expanded,
+                )?;
+            }
         }
         Some(Commands::Grep {
             query,
@@ -217,7 +471,10 @@ fn main() -> Result<()> {
             scope,
             engine,
             allow_slow_fallback,
+            ext,
+            max_filesize,
         }) => {
+            let filters = grep::CandidateFilters::parse(&ext, max_filesize.as_deref())?;
             grep(
                 &query,
                 limit,
@@ -225,20 +482,58 @@ fn main() -> Result<()> {
                 scope.as_deref(),
                 engine,
                 allow_slow_fallback,
+                &filters,
             )?;
         }
         Some(Commands::Rebuild { dry_run }) => {
             rebuild(dry_run)?;
         }
+        Some(Commands::Reconcile { action }) => {
+            reconcile::run(action)?;
+        }
         Some(Commands::Status { format }) => {
             status(&format)?;
         }
+        Some(Commands::Index { action }) => {
+            index_diff::run(action)?;
+        }
+        Some(Commands::IpcSchema) => {
+            let schema = vicaya_core::ipc::json_schema();
+            println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+        }
+        Some(Commands::Find(args)) => {
+            find::run(args)?;
+        }
+        Some(Commands::CompletePath(args)) => {
+            complete::run(args)?;
+        }
+        Some(Commands::Jump(args)) => {
+            jump::run(args)?;
+        }
+        Some(Commands::Du(args)) => {
+            du::run(args)?;
+        }
+        Some(Commands::SuggestExclusions(args)) => {
+            exclusions::run(args)?;
+        }
+        Some(Commands::CompareSpotlight(args)) => {
+            spotlight::run(args)?;
+        }
         Some(Commands::Metrics(args)) => {
             metrics::run(args)?;
         }
         Some(Commands::Smriti { action }) => {
             smriti_command(action)?;
         }
+        Some(Commands::Bookmark { action }) => {
+            bookmark::run(action)?;
+        }
+        Some(Commands::Aliases { action }) => {
+            aliases::run(action)?;
+        }
+        Some(Commands::Logs { action }) => {
+            logs::run(action)?;
+        }
         Some(Commands::Upgrade(args)) | Some(Commands::Update(args)) => {
             if let Err(err) = upgrade::run(args) {
                 upgrade::print_error(&err);
@@ -267,7 +562,13 @@ fn grep(
     scope: Option<&Path>,
     engine: Option<ContentEngineCli>,
     allow_slow_fallback: bool,
+    filters: &grep::CandidateFilters,
 ) -> Result<()> {
+    if !matches!(format, "heading" | "table" | "json" | "plain") {
+        return Err(vicaya_core::Error::Config(format!(
+            "unknown format `{format}` (expected heading, table, json, or plain)"
+        )));
+    }
     let config = load_config()?;
     if !config.content_search_enabled() {
         return Err(vicaya_core::Error::Other(
@@ -279,18 +580,27 @@ fn grep(
         .map(resolve_content_scope)
         .transpose()?
         .unwrap_or(std::env::current_dir()?);
-    let engine = engine
-        .map(Into::into)
-        .map(Ok)
-        .unwrap_or_else(|| config.content_search_engine())?;
-
-    let mut options = vicaya_core::content_search::ContentSearchOptions::new(query, scope, limit);
-    options.engine = engine;
-    options.allow_slow_fallback =
-        allow_slow_fallback || config.content_search_allow_slow_fallback();
-    options.rg_path = config.content_search.rg_path.clone();
-
-    let report = vicaya_core::content_search::search(&options)?;
+    let report = match engine.map(ContentEngineCli::choice) {
+        Some(None) => grep::search(query, &scope, limit, filters, format == "json")?,
+        choice => {
+            if !filters.is_empty() {
+                return Err(vicaya_core::Error::Config(
+                    "--ext and --max-filesize need --engine index".into(),
+                ));
+            }
+            let engine = choice
+                .flatten()
+                .map(Ok)
+                .unwrap_or_else(|| config.content_search_engine())?;
+            let mut options =
+                vicaya_core::content_search::ContentSearchOptions::new(query, scope, limit);
+            options.engine = engine;
+            options.allow_slow_fallback =
+                allow_slow_fallback || config.content_search_allow_slow_fallback();
+            options.rg_path = config.content_search.rg_path.clone();
+            vicaya_core::content_search::search(&options)?
+        }
+    };
 
     match format {
         "json" => {
@@ -311,6 +621,7 @@ fn grep(
                 );
             }
         }
+        "heading" => grep::print_heading(&report),
         _ => {
             println!("Engine: {}", report.engine.label());
             println!("{:<6} {:<10} {:<8} MATCH", "RANK", "LINE", "COL");
@@ -355,7 +666,13 @@ fn resolve_content_scope(path: &Path) -> Result<PathBuf> {
     Ok(normalized)
 }
 
-fn build_search_request(query: &str, limit: usize, scope: Option<&Path>) -> Result<Request> {
+fn build_search_request(options: SearchOptions, scope: Option<&Path>) -> Result<Request> {
+    search_options(options, scope).map(Request::Search)
+}
+
+/// Fill in the boost and filter scopes: `--scope` when given, else the
+/// current directory as a boost only.
+fn search_options(options: SearchOptions, scope: Option<&Path>) -> Result<SearchOptions> {
     let boost_scope = std::env::current_dir()
         .ok()
         .map(|p| p.to_string_lossy().to_string());
@@ -369,83 +686,352 @@ fn build_search_request(query: &str, limit: usize, scope: Option<&Path>) -> Resu
         boost_scope
     };
 
-    Ok(Request::Search {
-        query: query.to_string(),
-        limit,
+    Ok(SearchOptions {
         scope: boost_scope,
-        filter_scope,
-        recent_if_empty: false,
+        filters: SearchFilters {
+            scope: filter_scope,
+            ..options.filters
+        },
+        ..options
     })
 }
 
-fn search(query: &str, limit: usize, format: &str, scope: Option<&Path>) -> Result<()> {
-    // Auto-start daemon if not running
+fn search(
+    options: SearchOptions,
+    format: &str,
+    scope: Option<&Path>,
+    preview: Option<&preview::PreviewOptions>,
+    query: &aliases::ExpandedQuery,
+) -> Result<()> {
+    if start_daemon_for_offline_search()? {
+        return search_offline(options, format, scope, preview, query);
+    }
+    // Machine-readable formats keep stdout clean for the consuming program.
+    ensure_daemon_running(matches!(format, "json" | "alfred" | "raycast"))?;
+
+    let request = build_search_request(options, scope)?;
+
+    let response = Client::connect()?.request(&request)?;
+
+    match response {
+        Response::SearchResults {
+            mut results,
+            truncated,
+            suggestions,
+            mut outside_scope,
+        } => {
+            if truncated {
+                eprintln!("note: partial results (query budget exceeded; refine the query)");
+            }
+            query.retain_matching(&mut results);
+            query.retain_matching(&mut outside_scope);
+            match scope.filter(|_| !outside_scope.is_empty()) {
+                Some(scope) => {
+                    println!(
+                        "{}",
+                        render::Tone::Warn.paint(
+                            &format!("No matches under {}; results outside it:", scope.display()),
+                            render::color_enabled()
+                        )
+                    );
+                    print_search_results(&outside_scope, format, preview);
+                }
+                None => {
+                    print_search_results(&results, format, preview);
+                    print_suggestions(&suggestions);
+                }
+            }
+            Ok(())
+        }
+        Response::Error { message } => {
+            eprintln!("Error: {}", message);
+            Ok(())
+        }
+        _ => {
+            eprintln!("Unexpected response from daemon");
+            Ok(())
+        }
+    }
+}
+
+/// How long a command that started the daemon waits for its warm-up.
+const DAEMON_READY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Start the daemon if it is not running. Progress goes to stderr when
+/// `machine_output` keeps stdout for results.
+fn ensure_daemon_running(machine_output: bool) -> Result<()> {
     if !vicaya_core::daemon::is_running() {
-        if format == "json" {
+        if machine_output {
             eprintln!("Daemon is not running. Starting daemon...");
         } else {
             println!("Daemon is not running. Starting daemon...");
         }
         let pid = vicaya_core::daemon::start_daemon()?;
-        if format == "json" {
+        if machine_output {
             eprintln!("✓ Daemon started (PID: {})", pid);
         } else {
             println!("✓ Daemon started (PID: {})", pid);
         }
 
-        // Wait a moment for daemon to initialize
-        std::thread::sleep(std::time::Duration::from_millis(500));
+        // The socket accepts before warm-up ends; wait that out so the
+        // first search is not the slow one, but search anyway if it drags.
+        vicaya_core::daemon::wait_until_ready(DAEMON_READY_TIMEOUT);
     }
+    Ok(())
+}
 
-    let request = build_search_request(query, limit, scope)?;
+/// With `offline_while_starting`, start a stopped daemon without waiting for
+/// it and report that the caller should search the saved index instead.
+/// Always on stderr, which `search --offline` also uses for its note.
+fn start_daemon_for_offline_search() -> Result<bool> {
+    if vicaya_core::daemon::is_running() {
+        return Ok(false);
+    }
+    let offline = load_config().is_ok_and(|config| {
+        config.offline_while_starting && config.index_path.join("index.bin").exists()
+    });
+    if !offline {
+        return Ok(false);
+    }
+    let pid = vicaya_core::daemon::spawn_daemon()?;
+    eprintln!("Daemon is not running. Starting daemon (PID: {pid}) in the background...");
+    Ok(true)
+}
 
-    let response = IpcClient::connect()?.request(&request)?;
+/// `vicaya search --stdin`: one query per line, sent as `SearchBatch`
+/// requests of up to `MAX_SEARCH_BATCH` queries that share the other flags.
+fn search_stdin(
+    options: SearchOptions,
+    total_limit: Option<usize>,
+    format: &str,
+    scope: Option<&Path>,
+) -> Result<()> {
+    use std::io::BufRead;
 
-    match response {
-        Response::SearchResults { results } => {
-            match format {
-                "json" => {
-                    println!("{}", serde_json::to_string_pretty(&results).unwrap());
+    if !matches!(format, "table" | "json" | "plain") {
+        return Err(vicaya_core::Error::Config(format!(
+            "--stdin supports table, json, and plain output, not `{format}`"
+        )));
+    }
+    let terms: Vec<String> = std::io::stdin()
+        .lock()
+        .lines()
+        .map_while(std::result::Result::ok)
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if terms.is_empty() {
+        return Ok(());
+    }
+
+    ensure_daemon_running(format == "json")?;
+    let options = search_options(options, scope)?;
+    let mut client = Client::connect()?;
+    let mut remaining = total_limit;
+    let mut batches = Vec::with_capacity(terms.len());
+    for chunk in terms.chunks(MAX_SEARCH_BATCH) {
+        let request = Request::SearchBatch {
+            queries: chunk
+                .iter()
+                .map(|term| SearchOptions {
+                    term: term.clone(),
+                    ..options.clone()
+                })
+                .collect(),
+            total_limit: remaining,
+        };
+        match client.request(&request)? {
+            Response::SearchBatchResults { batches: answered } => {
+                if let Some(remaining) = remaining.as_mut() {
+                    let returned: usize = answered.iter().map(|b| b.results.len()).sum();
+                    *remaining = remaining.saturating_sub(returned);
                 }
-                "plain" => {
-                    for result in results {
-                        println!("{}", result.path);
-                    }
+                batches.extend(answered);
+            }
+            Response::Error { message } => return Err(vicaya_core::Error::Other(message)),
+            _ => {
+                return Err(vicaya_core::Error::Other(
+                    "Unexpected response from daemon".to_string(),
+                ))
+            }
+        }
+    }
+
+    print_batch_results(&terms, &batches, format);
+    Ok(())
+}
+
+/// One query's results from `--stdin`, as printed by `--format json`.
+#[derive(serde::Serialize)]
+struct BatchOutput<'a> {
+    query: &'a str,
+    results: &'a [vicaya_core::ipc::SearchResult],
+    truncated: bool,
+}
+
+fn print_batch_results(terms: &[String], batches: &[BatchResults], format: &str) {
+    match format {
+        "json" => {
+            let output: Vec<BatchOutput> = terms
+                .iter()
+                .zip(batches)
+                .map(|(term, batch)| BatchOutput {
+                    query: term,
+                    results: &batch.results,
+                    truncated: batch.truncated,
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output).unwrap());
+        }
+        "plain" => {
+            for (term, batch) in terms.iter().zip(batches) {
+                for result in &batch.results {
+                    println!("{term}\t{}", result.path);
                 }
-                _ => {
-                    // Table format
-                    println!("{:<6} {:<6} {:<20} PATH", "RANK", "SCORE", "MODIFIED");
-                    for (i, result) in results.iter().enumerate() {
-                        let mtime = chrono::DateTime::from_timestamp(result.mtime, 0)
-                            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
-                            .unwrap_or_default();
-                        println!(
-                            "{:<6} {:<6.2} {:<20} {}",
-                            i + 1,
-                            result.score,
-                            mtime,
-                            result.path
-                        );
-                    }
+            }
+        }
+        _ => {
+            for (i, (term, batch)) in terms.iter().zip(batches).enumerate() {
+                if i > 0 {
+                    println!();
                 }
+                let partial = if batch.truncated { "  (partial)" } else { "" };
+                println!("== {term}{partial}");
+                print_search_results(&batch.results, format, None);
             }
-            Ok(())
         }
-        Response::Error { message } => {
-            eprintln!("Error: {}", message);
-            Ok(())
+    }
+}
+
+fn search_offline(
+    options: SearchOptions,
+    format: &str,
+    scope: Option<&Path>,
+    preview: Option<&preview::PreviewOptions>,
+    query: &aliases::ExpandedQuery,
+) -> Result<()> {
+    let config = load_config()?;
+    let options = search_options(options, scope)?;
+    let mut found = offline::search(&config, &options)?;
+    query.retain_matching(&mut found.results);
+
+    // stderr, so machine-readable formats stay parseable.
+    let age = found
+        .age
+        .map(|age| format!("{} old", offline::format_age(age)))
+        .unwrap_or_else(|| "of unknown age".to_string());
+    eprintln!(
+        "offline: searched {} ({age}); changes since it was saved are not included",
+        found.index_file.display()
+    );
+    print_search_results(&found.results, format, preview);
+    print_suggestions(&found.suggestions);
+    Ok(())
+}
+
+/// "Did you mean" for a search that found nothing, on stderr so
+/// machine-readable output stays an empty result set.
+fn print_suggestions(suggestions: &[String]) {
+    if !suggestions.is_empty() {
+        eprintln!("No matches. Did you mean: {}?", suggestions.join(", "));
+    }
+}
+
+fn print_search_results(
+    results: &[vicaya_core::ipc::SearchResult],
+    format: &str,
+    preview: Option<&preview::PreviewOptions>,
+) {
+    match format {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(results).unwrap());
+        }
+        "alfred" => {
+            println!(
+                "{}",
+                serde_json::to_string(&launcher::alfred(results)).unwrap()
+            );
+        }
+        "raycast" => {
+            println!(
+                "{}",
+                serde_json::to_string(&launcher::raycast(results)).unwrap()
+            );
+        }
+        "plain" => {
+            for result in results {
+                println!("{}", result.path);
+                if let Some(options) = preview {
+                    preview::print(result, options, 2);
+                }
+            }
         }
         _ => {
-            eprintln!("Unexpected response from daemon");
-            Ok(())
+            let rows: Vec<Vec<String>> = results
+                .iter()
+                .enumerate()
+                .map(|(i, result)| {
+                    let mtime = chrono::DateTime::from_timestamp(result.mtime, 0)
+                        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    let path = if result.is_dir {
+                        format!("▣ {}/", result.path)
+                    } else {
+                        result.path.clone()
+                    };
+                    vec![
+                        (i + 1).to_string(),
+                        format!("{:.2}", result.score),
+                        mtime,
+                        path,
+                    ]
+                })
+                .collect();
+            let table = render::Table::new(
+                vec![
+                    render::Column::left("RANK", 6),
+                    render::Column::right("SCORE", 5),
+                    render::Column::left("MODIFIED", 20),
+                    render::Column::flexible("PATH"),
+                ],
+                &rows,
+                render::terminal_width(),
+                render::color_enabled(),
+            );
+            let indent = table.indent(1);
+            table.print_header();
+            for (result, row) in results.iter().zip(&rows) {
+                println!("{}", table.row(row));
+                if let Some(explain) = &result.explain {
+                    println!("{:indent$}{}", "", explanation_line(explain));
+                }
+                if let Some(options) = preview {
+                    preview::print(result, options, indent);
+                }
+            }
         }
     }
 }
 
+/// One-line score breakdown for `vicaya search --explain`.
+fn explanation_line(explain: &ScoreExplanation) -> String {
+    format!(
+        "↳ {} base {:.2}  smriti {:+.2}  recent {:+.2}  context {:+}  scope {:+}  dir {:+}  depth {}",
+        explain.strategy.label(),
+        explain.base_score,
+        explain.smriti_boost,
+        explain.recent_scope_boost,
+        explain.context_penalty,
+        explain.scope_boost,
+        explain.directory_bias,
+        explain.path_depth
+    )
+}
+
 fn rebuild(dry_run: bool) -> Result<()> {
     // If daemon is running, rebuild via IPC so the in-memory snapshot is updated too.
     if vicaya_core::daemon::is_running() {
-        if let Ok(mut client) = IpcClient::connect() {
+        if let Ok(mut client) = Client::connect() {
             let request = Request::Rebuild { dry_run };
             let response = client.request(&request)?;
 
@@ -485,302 +1071,260 @@ fn rebuild(dry_run: bool) -> Result<()> {
     } else {
         println!("Dry run: would index {} files", snapshot.file_table.len());
     }
+    if let Some(cap) = &snapshot.cap {
+        for line in index_cap_lines(cap) {
+            println!("{line}");
+        }
+    }
 
     Ok(())
 }
 
 fn status(format: &str) -> Result<()> {
-    use owo_colors::OwoColorize;
-
-    let mut client = IpcClient::connect()?;
+    let mut client = Client::connect()?;
     let request = Request::Status;
     let response = client.request(&request)?;
 
-    match response {
-        Response::Status {
-            pid,
-            build,
-            indexed_files,
-            trigram_count,
-            arena_size,
-            index_allocated_bytes,
-            state_allocated_bytes,
-            last_updated,
-            reconciling,
-        } => {
-            if format == "json" {
-                // JSON output
-                let json = serde_json::json!({
-                    "daemon": {
-                        "running": true,
-                        "pid": pid,
-                        "build": {
-                            "version": build.version,
-                            "git_sha": build.git_sha,
-                            "timestamp": build.timestamp,
-                            "target": build.target,
-                        }
-                    },
-                    "index": {
-                        "files": indexed_files,
-                        "trigrams": trigram_count,
-                        "arena_bytes": arena_size,
-                        "index_allocated_bytes": index_allocated_bytes,
-                        "state_allocated_bytes": state_allocated_bytes,
-                        "last_updated": last_updated,
-                        "reconciling": reconciling,
-                    },
-                    "metrics": {
-                        "bytes_per_file": if indexed_files > 0 { arena_size / indexed_files } else { 0 },
-                        "trigrams_per_file": if indexed_files > 0 { trigram_count as f64 / indexed_files as f64 } else { 0.0 },
-                        "arena_size_mb": arena_size as f64 / 1_048_576.0,
-                        "index_allocated_mb": index_allocated_bytes as f64 / 1_048_576.0,
-                        "state_allocated_mb": state_allocated_bytes as f64 / 1_048_576.0,
-                    }
-                });
-                println!("{}", serde_json::to_string_pretty(&json).unwrap());
-            } else {
-                // Pretty output
-                let config = load_config()?;
-                let index_file = config.index_path.join("index.bin");
-                let index_size = std::fs::metadata(&index_file).map(|m| m.len()).unwrap_or(0);
-
-                println!();
-                println!(
-                    "{}",
-                    "╭───────────────────────────────────────────────────────╮".bright_blue()
-                );
-                println!(
-                    "{} {:<53} {}",
-                    "│".bright_blue(),
-                    "Vicaya — Index Status".bold().bright_white(),
-                    "│".bright_blue()
-                );
-                println!(
-                    "{}",
-                    "├───────────────────────────────────────────────────────┤".bright_blue()
-                );
-
-                // Daemon info
-                // Note: "●" is 3 bytes but 1 char, so use .chars().count() for assertion
-                let plain_line = format!("  {} Daemon{:<43}", "●", "");
-                assert_eq!(plain_line.chars().count(), 53);
-                let daemon_line = format!(" Daemon{:<43}", "");
-                println!(
-                    "{}   {}{} {}",
-                    "│".bright_blue(),
-                    "●".bright_green(),
-                    daemon_line,
-                    "│".bright_blue()
-                );
-
-                if reconciling {
-                    let status_str = "running";
-                    let plain_line = format!("    Reconciliation: {:<33}", status_str);
-                    assert_eq!(plain_line.len(), 53);
-                    let status_line = format!("{:<33}", status_str)
-                        .bright_yellow()
-                        .bold()
-                        .to_string();
-                    println!(
-                        "{}     Reconciliation: {} {}",
-                        "│".bright_blue(),
-                        status_line,
-                        "│".bright_blue()
-                    );
-                }
-
-                let pid_str = pid.to_string();
-                let plain_line = format!("    PID: {:<44}", pid_str);
-                assert_eq!(plain_line.len(), 53);
-                let pid_line = format!("{:<44}", pid_str).bright_cyan().to_string();
-                println!(
-                    "{}     PID: {} {}",
-                    "│".bright_blue(),
-                    pid_line,
-                    "│".bright_blue()
-                );
-
-                println!(
-                    "{}",
-                    "├───────────────────────────────────────────────────────┤".bright_blue()
-                );
-
-                // Index stats
-                // Build plain line first, then apply colors
-                let title_line = format!("{:53}", "  Index Statistics");
-                println!(
-                    "{} {} {}",
-                    "│".bright_blue(),
-                    title_line.bold(),
-                    "│".bright_blue()
-                );
-
-                let files_str = format_number(indexed_files);
-                let plain_line = format!("    Files indexed:{:>35}", files_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Files indexed:".dimmed(),
-                    format!("{:>35}", files_str).bright_green().bold(),
-                    "│".bright_blue()
-                );
-
-                let trigrams_str = format_number(trigram_count);
-                let plain_line = format!("    Trigrams:{:>40}", trigrams_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Trigrams:".dimmed(),
-                    format!("{:>40}", trigrams_str).bright_yellow(),
-                    "│".bright_blue()
-                );
-
-                let state_mb = state_allocated_bytes as f64 / 1_048_576.0;
-                let state_str = format!("{:.1} MB", state_mb);
-                let plain_line = format!("    Memory (est):{:>36}", state_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Memory (est):".dimmed(),
-                    format!("{:>36}", state_str).bright_magenta(),
-                    "│".bright_blue()
-                );
-
-                let index_mem_mb = index_allocated_bytes as f64 / 1_048_576.0;
-                let index_mem_str = format!("{:.1} MB", index_mem_mb);
-                let plain_line = format!("    Index memory:{:>36}", index_mem_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Index memory:".dimmed(),
-                    format!("{:>36}", index_mem_str).bright_magenta(),
-                    "│".bright_blue()
-                );
-
-                let arena_mb = arena_size as f64 / 1_048_576.0;
-                let arena_str = format!("{:.1} MB", arena_mb);
-                let plain_line = format!("    String arena:{:>36}", arena_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    String arena:".dimmed(),
-                    format!("{:>36}", arena_str).bright_magenta(),
-                    "│".bright_blue()
-                );
-
-                let index_mb = index_size as f64 / 1_048_576.0;
-                let index_str = format!("{:.1} MB", index_mb);
-                let plain_line = format!("    Index file size:{:>33}", index_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Index file size:".dimmed(),
-                    format!("{:>33}", index_str).bright_magenta(),
-                    "│".bright_blue()
-                );
-
-                if last_updated > 0 {
-                    let dt = chrono::DateTime::from_timestamp(last_updated, 0)
-                        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                        .unwrap_or_default();
-                    let plain_line = format!("    Last updated:{:>36}", dt);
-                    assert_eq!(plain_line.len(), 53);
-                    println!(
-                        "{} {}{} {}",
-                        "│".bright_blue(),
-                        "    Last updated:".dimmed(),
-                        format!("{:>36}", dt).bright_cyan(),
-                        "│".bright_blue()
-                    );
-                }
-
-                println!(
-                    "{}",
-                    "├───────────────────────────────────────────────────────┤".bright_blue()
-                );
+    let response = match response {
+        Response::Error { message } => {
+            eprintln!("Error: {}", message);
+            return Ok(());
+        }
+        response => response,
+    };
+    let config = load_config()?;
+    let Some(mut report) = status_report::StatusReport::from_response(response, &config.index_path)
+    else {
+        eprintln!("Unexpected response from daemon");
+        return Ok(());
+    };
+    // Older daemons do not know the request; leave the breakdown out.
+    if let Ok(Response::RootStats { roots }) = client.request(&Request::RootStats) {
+        report.roots = roots;
+    }
 
-                // Efficiency metrics
-                let title_line = format!("{:53}", "  Efficiency Metrics");
-                println!(
-                    "{} {} {}",
-                    "│".bright_blue(),
-                    title_line.bold().bright_white(),
-                    "│".bright_blue()
-                );
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        return Ok(());
+    }
 
-                let bytes_per_file = if indexed_files > 0 {
-                    arena_size / indexed_files
-                } else {
-                    0
-                };
-                let bpf_str = format!("{} B", bytes_per_file);
-                let plain_line = format!("    Bytes per file:{:>34}", bpf_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Bytes per file:".dimmed(),
-                    format!("{:>34}", bpf_str).bright_green(),
-                    "│".bright_blue()
-                );
+    let status_report::StatusReport {
+        daemon,
+        index,
+        watcher,
+        journal,
+        reconcile,
+        metrics,
+        roots,
+        ..
+    } = report;
 
-                let trigrams_per_file = if indexed_files > 0 {
-                    trigram_count as f64 / indexed_files as f64
-                } else {
-                    0.0
-                };
-                let tpf_str = format!("{:.1}", trigrams_per_file);
-                let plain_line = format!("    Trigrams/file:{:>35}", tpf_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Trigrams/file:".dimmed(),
-                    format!("{:>35}", tpf_str).bright_yellow(),
-                    "│".bright_blue()
-                );
+    println!();
+    let mut panel = render::Panel::new(t("cli.status.title"));
+    panel.divider();
+    panel.spans(vec![
+        ("  ".to_string(), render::Tone::Plain),
+        ("●".to_string(), render::Tone::Good),
+        (format!(" {}", t("cli.status.daemon")), render::Tone::Plain),
+    ]);
+    if reconcile.running {
+        let status_str = match reconcile.progress {
+            Some(progress) => {
+                i18n::format("cli.status.running_progress", &[("progress", &progress)])
+            }
+            None => t("cli.status.running").to_string(),
+        };
+        panel.field(
+            t("cli.status.reconciliation"),
+            status_str,
+            render::Tone::Warn,
+        );
+        if let Some(scan) = reconcile.scan {
+            panel.field(t("cli.status.scan"), scan.to_string(), render::Tone::Info);
+        }
+    }
+    panel.field(
+        t("cli.status.pid"),
+        daemon.pid.to_string(),
+        render::Tone::Info,
+    );
+    if let Some(profile) = daemon.profile {
+        panel.field(t("cli.status.profile"), profile, render::Tone::Info);
+    }
 
-                let total_mb = (arena_size + index_size as usize) as f64 / 1_048_576.0;
-                let mb_per_kfile = if indexed_files > 0 {
-                    total_mb / (indexed_files as f64 / 1000.0)
-                } else {
-                    0.0
-                };
-                let mbpk_str = format!("{:.2} MB", mb_per_kfile);
-                let plain_line = format!("    Total/1K files:{:>34}", mbpk_str);
-                assert_eq!(plain_line.len(), 53);
-                println!(
-                    "{} {}{} {}",
-                    "│".bright_blue(),
-                    "    Total/1K files:".dimmed(),
-                    format!("{:>34}", mbpk_str).bright_magenta(),
-                    "│".bright_blue()
-                );
+    panel.section(t("cli.status.index_statistics"));
+    panel.field(
+        t("cli.status.files_indexed"),
+        format_number(index.files),
+        render::Tone::Good,
+    );
+    let profile_str = match index.profile {
+        vicaya_core::config::IndexProfile::Full => t("cli.status.profile_full"),
+        vicaya_core::config::IndexProfile::Lite => t("cli.status.profile_lite"),
+    };
+    panel.field(
+        t("cli.status.index_profile"),
+        profile_str,
+        render::Tone::Info,
+    );
+    panel.field(
+        t("cli.status.trigrams"),
+        format_number(index.trigrams),
+        render::Tone::Count,
+    );
+    let megabytes = |bytes: u64| format!("{:.1} MB", bytes as f64 / 1_048_576.0);
+    panel.field(
+        t("cli.status.memory"),
+        megabytes(index.state_allocated_bytes),
+        render::Tone::Hot,
+    );
+    panel.field(
+        t("cli.status.index_memory"),
+        megabytes(index.index_allocated_bytes),
+        render::Tone::Hot,
+    );
+    panel.field(
+        t("cli.status.string_arena"),
+        megabytes(index.arena_bytes as u64),
+        render::Tone::Hot,
+    );
+    panel.field(
+        t("cli.status.index_file_size"),
+        megabytes(index.file_bytes),
+        render::Tone::Hot,
+    );
+    if index.last_updated > 0 {
+        let dt = chrono::DateTime::from_timestamp(index.last_updated, 0)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default();
+        panel.field(t("cli.status.last_updated"), dt, render::Tone::Info);
+    }
+    for (label, timestamp) in [
+        (t("cli.status.last_reconcile"), reconcile.last),
+        (t("cli.status.next_reconcile"), reconcile.next),
+    ] {
+        if let Some(when) = timestamp.and_then(format_local_time) {
+            panel.field(label, when, render::Tone::Info);
+        }
+    }
+    panel.field(
+        t("cli.status.watcher_queue"),
+        format_number(watcher.pending_events),
+        render::Tone::Info,
+    );
+    panel.field(
+        t("cli.status.journal_entries"),
+        format_number(journal.entries),
+        render::Tone::Info,
+    );
+    if let Some(journal_fsync) = journal.fsync {
+        panel.field(
+            t("cli.status.journal_fsync"),
+            journal_fsync.to_string(),
+            render::Tone::Info,
+        );
+    }
+    if let Some(warmup_ms) = index.warmup_ms {
+        panel.field(
+            t("cli.status.warmup"),
+            format!("{warmup_ms} ms"),
+            render::Tone::Info,
+        );
+    }
 
-                println!(
-                    "{}",
-                    "╰───────────────────────────────────────────────────────╯".bright_blue()
+    if !roots.is_empty() {
+        panel.section(t("cli.status.index_roots"));
+        let total_memory: u64 = roots.iter().map(|root| root.memory_bytes).sum();
+        for root in &roots {
+            panel.field(
+                &format!("{}:", root.root),
+                i18n::format(
+                    "cli.status.root_files",
+                    &[("count", &format_number(root.files))],
+                ),
+                render::Tone::Good,
+            );
+            let share = if total_memory > 0 {
+                root.memory_bytes as f64 * 100.0 / total_memory as f64
+            } else {
+                0.0
+            };
+            panel.field(
+                t("cli.status.root_memory"),
+                format!("{} ({share:.0}%)", megabytes(root.memory_bytes)),
+                render::Tone::Hot,
+            );
+            if root.total_bytes > 0 {
+                panel.field(
+                    t("cli.status.root_size"),
+                    megabytes(root.total_bytes),
+                    render::Tone::Info,
                 );
-                println!();
             }
-            Ok(())
+            if let Some(when) = root.last_scan.and_then(format_local_time) {
+                panel.field(t("cli.status.root_last_scan"), when, render::Tone::Info);
+            }
         }
-        Response::Error { message } => {
-            eprintln!("Error: {}", message);
-            Ok(())
+    }
+
+    panel.section(t("cli.status.efficiency"));
+    panel.field(
+        t("cli.status.bytes_per_file"),
+        format!("{} B", metrics.bytes_per_file),
+        render::Tone::Good,
+    );
+    panel.field(
+        t("cli.status.trigrams_per_file"),
+        format!("{:.1}", metrics.trigrams_per_file),
+        render::Tone::Count,
+    );
+    panel.field(
+        t("cli.status.per_kfile"),
+        format!("{:.2} MB", metrics.mb_per_kfile),
+        render::Tone::Hot,
+    );
+    panel.print();
+    if let Some(cap) = &index.cap {
+        let color = render::color_enabled();
+        let mut lines = index_cap_lines(cap).into_iter();
+        if let Some(headline) = lines.next() {
+            println!("  {}", render::Tone::Warn.paint(&headline, color));
         }
-        _ => {
-            eprintln!("Unexpected response from daemon");
-            Ok(())
+        for line in lines {
+            println!("  {}", render::Tone::Label.paint(&line, color));
         }
     }
+    println!();
+    Ok(())
+}
+
+/// `vicaya status` warning for an index that hit `max_indexed_files`.
+fn index_cap_lines(cap: &vicaya_core::ipc::IndexCap) -> Vec<String> {
+    let mut lines = vec![format!(
+        "⚠ Index capped at {} entries (max_indexed_files): {} skipped",
+        format_number(cap.max_indexed_files),
+        format_number(cap.total_skipped())
+    )];
+    for root in cap.roots.iter().filter(|root| root.skipped > 0) {
+        let coverage = match root.complete_depth {
+            Some(depth) => format!("complete to depth {depth}"),
+            None => "not indexed".to_string(),
+        };
+        lines.push(format!(
+            "  {}: {} indexed, {} skipped ({coverage})",
+            root.root,
+            format_number(root.indexed),
+            format_number(root.skipped)
+        ));
+    }
+    if cap.skipped_live > 0 {
+        lines.push(format!(
+            "  {} new entries since the last scan",
+            format_number(cap.skipped_live)
+        ));
+    }
+    lines.push("  Raise max_indexed_files or add exclusions to index the rest".to_string());
+    lines
 }
 
 fn smriti_command(action: SmritiActionCli) -> Result<()> {
@@ -795,7 +1339,7 @@ fn smriti_command(action: SmritiActionCli) -> Result<()> {
             daemon_messages_to_stderr,
             &format!("✓ Daemon started (PID: {})", pid),
         );
-        std::thread::sleep(std::time::Duration::from_millis(500));
+        vicaya_core::daemon::wait_until_ready(DAEMON_READY_TIMEOUT);
     }
 
     match action {
@@ -815,7 +1359,7 @@ fn smriti_command(action: SmritiActionCli) -> Result<()> {
                 limit,
                 filter_scope,
             };
-            let response = IpcClient::connect()?.request(&request)?;
+            let response = Client::connect()?.request(&request)?;
             match response {
                 Response::SmritiEntries { entries } => match format.as_str() {
                     "json" => println!("{}", serde_json::to_string_pretty(&entries).unwrap()),
@@ -849,7 +1393,7 @@ fn smriti_command(action: SmritiActionCli) -> Result<()> {
             let request = Request::SmritiForget {
                 path: path.to_string_lossy().to_string(),
             };
-            match IpcClient::connect()?.request(&request)? {
+            match Client::connect()?.request(&request)? {
                 Response::Ok => println!("Forgot Smriti entry: {}", path.display()),
                 Response::SmritiForgot { removed: true } => {
                     println!("Forgot Smriti entry: {}", path.display())
@@ -867,7 +1411,7 @@ fn smriti_command(action: SmritiActionCli) -> Result<()> {
                     "Refusing to clear Smriti without --yes".to_string(),
                 ));
             }
-            match IpcClient::connect()?.request(&Request::SmritiClear)? {
+            match Client::connect()?.request(&Request::SmritiClear)? {
                 Response::Ok => println!("Smriti cleared"),
                 Response::Error { message } => eprintln!("Error: {}", message),
                 _ => eprintln!("Unexpected response from daemon"),
@@ -893,6 +1437,16 @@ fn print_smriti_daemon_message(to_stderr: bool, message: &str) {
     }
 }
 
+/// `timestamp` as local `YYYY-MM-DD HH:MM:SS`.
+fn format_local_time(timestamp: i64) -> Option<String> {
+    use chrono::TimeZone;
+
+    chrono::Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
 fn format_number(n: usize) -> String {
     let s = n.to_string();
     let mut result = String::new();
@@ -948,6 +1502,20 @@ fn daemon_command(action: DaemonAction) -> Result<()> {
                 }
             }
         }
+        DaemonAction::Upgrade { binary } => {
+            println!("Upgrading vicaya daemon...");
+
+            match vicaya_core::daemon::upgrade_daemon(binary.as_deref()) {
+                Ok(version) => {
+                    println!("✓ Daemon upgraded in place: {}", version);
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("✗ Failed to upgrade daemon: {}", e);
+                    Err(e)
+                }
+            }
+        }
         DaemonAction::Status => {
             if vicaya_core::daemon::is_running() {
                 let pid = vicaya_core::daemon::get_pid().unwrap_or(0);
@@ -959,23 +1527,39 @@ fn daemon_command(action: DaemonAction) -> Result<()> {
                 );
 
                 // Try to get detailed status from daemon
-                if let Ok(mut client) = IpcClient::connect() {
+                if let Ok(mut client) = Client::connect() {
                     let request = Request::Status;
-                    if let Ok(Response::Status {
+                    if let Ok(Response::Status(DaemonStatus {
                         pid,
                         indexed_files,
                         trigram_count,
                         arena_size,
                         last_updated,
                         reconciling,
+                        pending_watcher_events,
+                        journal_entries,
+                        reconcile_progress,
+                        reconcile_scan,
                         ..
-                    }) = client.request(&request)
+                    })) = client.request(&request)
                     {
                         println!("\nIndex Status:");
                         println!("  PID: {}", pid);
                         if reconciling {
-                            println!("  Reconciliation: running");
+                            match reconcile_progress {
+                                Some(progress) => {
+                                    println!("  Reconciliation: running ({}%)", progress)
+                                }
+                                None => println!("  Reconciliation: running"),
+                            }
+                            if let Some(scan) = reconcile_scan {
+                                println!("  Scan: {}", scan);
+                            }
                         }
+                        println!(
+                            "  Pending updates: {} watcher, {} journal",
+                            pending_watcher_events, journal_entries
+                        );
                         println!("  Files indexed: {}", indexed_files);
                         println!("  Trigrams: {}", trigram_count);
                         println!("  Arena size: {} bytes", arena_size);
@@ -994,11 +1578,34 @@ fn daemon_command(action: DaemonAction) -> Result<()> {
                 println!("\nTo start the daemon, run:");
                 println!("  vicaya daemon start");
             }
+            print_latest_crash();
             Ok(())
         }
     }
 }
 
+/// Point `vicaya daemon status` at the newest daemon crash report, if any.
+fn print_latest_crash() {
+    let Ok(Some((path, report))) = vicaya_core::crash::latest(&vicaya_core::paths::crash_dir())
+    else {
+        return;
+    };
+    let crashed_at = chrono::DateTime::from_timestamp(report.crashed_at, 0)
+        .map(|dt| {
+            dt.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    println!("\n⚠ Last crash: {} (PID {})", crashed_at, report.pid);
+    println!("  Thread: {}", report.thread);
+    match &report.location {
+        Some(location) => println!("  Panic: {} at {}", report.message, location),
+        None => println!("  Panic: {}", report.message),
+    }
+    println!("  Report: {}", path.display());
+}
+
 fn load_config() -> Result<Config> {
     let config_path = vicaya_core::paths::config_path();
 
@@ -1021,6 +1628,8 @@ fn init_config(force: bool) -> Result<()> {
     if config_path.exists() && !force {
         println!("✓ Config already exists at: {}", config_path.display());
         println!("  Use --force to overwrite");
+        let report = vicaya_core::config_validation::validate_file(&config_path)?;
+        print_config_report(&config_path, &report);
         return Ok(());
     }
 
@@ -1029,7 +1638,114 @@ fn init_config(force: bool) -> Result<()> {
     fs::create_dir_all(&index_dir)?;
 
     // Create default config with user's home directory
-    let config_content = format!(
+    let config_content = default_config_template(&index_dir, num_cpus::get().max(2));
+
+    // Validate before writing so a broken template never lands on disk
+    let report = vicaya_core::config_validation::validate_str(&config_content);
+    if report.has_errors() {
+        print_config_report(&config_path, &report);
+        return Err(vicaya_core::Error::Config(
+            "generated config failed validation".into(),
+        ));
+    }
+
+    // Write config file
+    fs::write(&config_path, &config_content)?;
+
+    println!("✓ Configuration initialized successfully!");
+    println!();
+    println!("Config file: {}", config_path.display());
+    println!("Index location: {}", index_dir.display());
+    println!();
+    if !report.issues.is_empty() {
+        print_config_report(&config_path, &report);
+        println!();
+    }
+    println!("📝 What's indexed:");
+    println!("  • Your home directory: ~/");
+    println!();
+    println!("📝 What's excluded (60+ patterns):");
+    println!("  • Version control: .git, .svn, .hg");
+    println!("  • Build outputs: target, build, dist, out");
+    println!("  • Dependencies: node_modules, vendor, .cargo");
+    println!("  • Python: __pycache__, .venv, *.pyc");
+    println!("  • IDEs: .idea, .vscode, .vs");
+    println!("  • Cache/temp: .cache, *.tmp, *.log");
+    println!("  • macOS: .DS_Store, ._*");
+    println!("  • Repository ignore files: .gitignore, .ignore, .git/info/exclude");
+    println!();
+    println!("Next steps:");
+    println!("  1. Edit {} to customize", config_path.display());
+    println!("  2. Run: vicaya rebuild");
+    println!("  3. Start daemon: vicaya-daemon");
+    println!("  4. Search: vicaya search <query>");
+
+    Ok(())
+}
+
+fn doctor(config: Option<&Path>, format: &str) -> Result<()> {
+    use render::Tone;
+
+    let config_path = config
+        .map(Path::to_path_buf)
+        .unwrap_or_else(vicaya_core::paths::config_path);
+    let exists = config_path.exists();
+    let report = if exists {
+        vicaya_core::config_validation::validate_file(&config_path)?
+    } else {
+        vicaya_core::config_validation::ConfigReport::default()
+    };
+    let daemon_running = vicaya_core::daemon::is_running();
+
+    if format == "json" {
+        let json = serde_json::json!({
+            "config": {
+                "path": config_path.display().to_string(),
+                "exists": exists,
+                "valid": !report.has_errors(),
+                "issues": report.issues,
+            },
+            "daemon": {
+                "running": daemon_running,
+            },
+        });
+        println!("{}", serde_json::to_string_pretty(&json).unwrap());
+    } else {
+        let color = render::color_enabled();
+        println!("Config: {}", config_path.display());
+        if exists {
+            print_config_report(&config_path, &report);
+            let errors = report.errors().count();
+            let warnings = report.warnings().count();
+            if errors + warnings > 0 {
+                println!("  {errors} error(s), {warnings} warning(s)");
+            }
+        } else {
+            println!(
+                "{} No config file; built-in defaults are in use (run `vicaya init`)",
+                Tone::Warn.paint("!", color)
+            );
+        }
+
+        if daemon_running {
+            println!("{} Daemon is running", Tone::Good.paint("✓", color));
+        } else {
+            println!(
+                "{} Daemon is not running (start it with `vicaya daemon start`)",
+                Tone::Warn.paint("!", color)
+            );
+        }
+    }
+
+    if report.has_errors() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Contents written by `vicaya init`.
+fn default_config_template(index_dir: &Path, scanner_threads: usize) -> String {
+    format!(
         r#"# vicaya configuration file
 # This file was automatically generated by `vicaya init`
 
@@ -1041,6 +1757,10 @@ index_roots = [
 
 # Directories and patterns to exclude from indexing
 # Organized by category for easy customization
+# Patterns are globs: `name` and `*.ext` match any path component,
+# `src/*/generated` or `**/build/**` match across components, and
+# `/Users/me/scratch` is anchored at the filesystem root. A leading `!`
+# re-includes a path an earlier pattern excluded (`"!target/doc"`).
 exclusions = [
     # Version control
     ".git",
@@ -1124,17 +1844,85 @@ exclusions = [
 # after toggling it.
 respect_ignore_files = true
 
+# Match exclusion patterns regardless of case (`build` also excludes `Build`).
+# case_insensitive_exclusions = false
+
+# Descend into macOS bundles (Foo.app, Bar.framework); by default each bundle is
+# indexed as a single entry.
+# index_bundle_contents = false
+
+# Index Time Machine backups and snapshot volumes (skipped automatically by default).
+# index_backup_volumes = false
+
+# Also index directory paths so terms like "projects/spartan" find the files
+# inside without a full scan; uses extra memory for every directory.
+# index_directory_paths = false
+
+# "full", or "lite" for low-memory machines: files only, without sizes or
+# inodes, so no directory results, size sorting, or inode rename tracking.
+# index_profile = "full"
+
 # Where to store the index file
 index_path = "{}"
 
 # Maximum memory to use for indexing (in MB)
 max_memory_mb = 512
 
+# Most entries to index (0 = no limit). Past it, earlier index_roots and
+# shallower paths win; `vicaya status` reports what was skipped.
+# max_indexed_files = 5000000
+
+# Return dotfiles and entries inside dot-directories; `hidden:` in a query
+# (or `.` in the TUI) overrides this per search.
+# show_hidden = true
+
+# Initial TUI ksetra offered when vicaya-tui starts without a directory:
+# "git" (enclosing project root), "cwd" (launch directory), or "off"
+# auto_scope = "off"
+
+# TUI result icons: "auto" (Unicode, or ASCII outside UTF-8 locales),
+# "nerd" (needs a Nerd Font), "unicode", or "ascii"
+# icons = "auto"
+
+# Language of CLI and TUI text: "en", "de", or "auto" (from LANG)
+# language = "en"
+
+# When `vicaya search` starts the daemon, answer that first search from the
+# saved index while the daemon loads instead of waiting for it
+# offline_while_starting = false
+
+# Reopen the TUI with the ksetra stack and drishti it was closed with
+# restore_tui_session = false
+
 [performance]
 # Number of threads for parallel scanning
 scanner_threads = {}
 # Hour of day (0-23) to run automatic reconciliation
 reconcile_hour = 3
+# Cron-style times (minute hour day-of-month month day-of-week, local time)
+# that replace reconcile_hour; `vicaya reconcile schedule` previews them
+# reconcile_schedule = ["0 3 * * *", "0 13 * * 1-5"]
+# Skip a scheduled reconcile within this many minutes of the last one
+# reconcile_min_gap_minutes = 60
+# Reconcile shortly after the machine wakes from sleep (the watcher can miss changes)
+# reconcile_on_wake = true
+# Per-search time budget in milliseconds; slower searches return partial results (0 = unlimited)
+query_timeout_ms = 250
+# Touch the N most common trigram postings after startup so first searches are fast (0 = off)
+# warmup_trigrams = 256
+
+# Threads and pause (ms after every 512 entries) for building a new index and
+# `vicaya rebuild`, and for scheduled reconciles; unset threads use scanner_threads.
+# [performance.initial_scan]
+# threads = 8
+# [performance.reconcile]
+# threads = 1
+# throttle_ms = 20
+
+# Usage memory: ranks what you open higher and marks it in TUI results.
+# [smriti]
+# enabled = true
+# badges = true
 
 [content_search]
 # Content search powers `vicaya grep` and the Antarvicaya TUI drishti.
@@ -1143,39 +1931,109 @@ enabled = true
 engine = "auto"
 # Keep this false unless you explicitly accept slower recursive grep fallback.
 allow_slow_fallback = false
+
+[preview]
+# TUI preview chunk size; press L in a truncated preview to load the next chunk.
+max_bytes = 262144
+max_lines = 4000
+
+# Index entries inside archives as virtual paths (app.jar!/META-INF/MANIFEST.MF).
+# [archives]
+# enabled = true
+# extensions = ["zip", "jar", "tar", "tar.gz", "tgz"]
+# max_archive_mb = 64
+
+# Fingerprint file contents while scanning (the hash: niyama). Each file up to
+# max_file_mb is opened and its first head_kb KiB hashed, so scans do more I/O.
+# [content_hash]
+# enabled = true
+# head_kb = 64
+# max_file_mb = 1024
+
+# Stop walking an index root after this many levels below it.
+# [root_max_depth]
+# "~" = 6
+
+# Applications offered by the TUI "open with" overlay (O), keyed by extension.
+# [associations]
+# md = ["Typora", "Visual Studio Code"]
+
+# Query shorthand: `@cfg` in a CLI or TUI query expands to its definition.
+# Manage with `vicaya aliases list|add|rm`.
+# [aliases]
+# cfg = "ext:toml,yaml,json type:file"
+# docs = "path:Documents"
+
+# Localhost HTTP endpoint (GET /search, GET /status); requires a token.
+# [http]
+# enabled = true
+# port = 8347
+# token = "change-me"
+
+# When watcher journal appends are forced to disk: none, interval, or every-batch.
+# [journal]
+# fsync = "interval"
+# fsync_interval_ms = 1000
+
+# Drop matches scoring below min_score (0.3 path-only, 0.5 substring, 0.9+ prefix);
+# searches that find nothing suggest up to `suggestions` similar names. Each
+# boost multiplies the score of matches under a glob or with an extension.
+# [ranking]
+# min_score = 0.4
+# suggestions = 3
+# boost = [{{ pattern = "~/Projects/**", factor = 1.2 }}, {{ ext = "png", factor = 0.8 }}]
+# Global searches favor files under scopes used recently in the TUI; the
+# boost halves every `recent_scope_half_life_mins` the scope goes unused.
+# recent_scope_boost = 0.1
+# recent_scope_half_life_mins = 30
+# Scoped searches that find nothing also list matches outside the scope.
+# auto_broaden = true
+
+# Opt-in local ranking log for `vicaya metrics ranking-report`; never uploaded.
+# [telemetry]
+# enabled = true
+
+# The Trash is skipped and files moved into it leave the index at once; the
+# TUI's Tyakta drishti lists the last `recently_deleted` of them.
+# [trash]
+# index = false
+# recently_deleted = 200
+
+# Fold names and queries alike: `resume` finds `résumé.pdf`, `foo bar` finds
+# `foo_bar` and `foo-bar`. The daemon re-derives its index when these change.
+# [normalization]
+# fold_diacritics = true
+# fold_separators = true
+
+# Commands run when matching indexed files change; {{path}}, {{paths}}, {{count}}
+# expand to the shell-quoted changed paths.
+# [[hooks]]
+# pattern = "~/code/app/**/*.rs"
+# command = "ctags -R -f .tags ."
+# cwd = "~/code/app"
+# debounce_ms = 500
 "#,
         index_dir.display(),
-        num_cpus::get().max(2)
-    );
-
-    // Write config file
-    fs::write(&config_path, config_content)?;
+        scanner_threads
+    )
+}
 
-    println!("✓ Configuration initialized successfully!");
-    println!();
-    println!("Config file: {}", config_path.display());
-    println!("Index location: {}", index_dir.display());
-    println!();
-    println!("📝 What's indexed:");
-    println!("  • Your home directory: ~/");
-    println!();
-    println!("📝 What's excluded (60+ patterns):");
-    println!("  • Version control: .git, .svn, .hg");
-    println!("  • Build outputs: target, build, dist, out");
-    println!("  • Dependencies: node_modules, vendor, .cargo");
-    println!("  • Python: __pycache__, .venv, *.pyc");
-    println!("  • IDEs: .idea, .vscode, .vs");
-    println!("  • Cache/temp: .cache, *.tmp, *.log");
-    println!("  • macOS: .DS_Store, ._*");
-    println!("  • Repository ignore files: .gitignore, .ignore, .git/info/exclude");
-    println!();
-    println!("Next steps:");
-    println!("  1. Edit {} to customize", config_path.display());
-    println!("  2. Run: vicaya rebuild");
-    println!("  3. Start daemon: vicaya-daemon");
-    println!("  4. Search: vicaya search <query>");
+/// Print config validation findings, or a success line when there are none.
+fn print_config_report(config_path: &Path, report: &vicaya_core::config_validation::ConfigReport) {
+    use render::Tone;
 
-    Ok(())
+    let color = render::color_enabled();
+    if report.issues.is_empty() {
+        println!("{} Config is valid", Tone::Good.paint("✓", color));
+        return;
+    }
+    for issue in &report.issues {
+        let marker = match issue.severity {
+            vicaya_core::config_validation::Severity::Error => Tone::Bad.paint("✗", color),
+            vicaya_core::config_validation::Severity::Warning => Tone::Warn.paint("!", color),
+        };
+        println!("{marker} {}:{issue}", config_path.display());
+    }
 }
 
 #[cfg(test)]
@@ -1195,6 +2053,55 @@ mod tests {
         }
     }
 
+    #[test]
+    fn cli_parses_search_stdin_batch_flags() {
+        let cli = Cli::parse_from(["vicaya", "search", "--stdin", "--total-limit", "50"]);
+        match cli.command {
+            Some(Commands::Search {
+                query,
+                stdin,
+                total_limit,
+                ..
+            }) => {
+                assert_eq!(query, None);
+                assert!(stdin);
+                assert_eq!(total_limit, Some(50));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        assert!(Cli::try_parse_from(["vicaya", "search"]).is_err());
+        assert!(Cli::try_parse_from(["vicaya", "search", "main.rs", "--stdin"]).is_err());
+        assert!(Cli::try_parse_from(["vicaya", "search", "--stdin", "--offline"]).is_err());
+    }
+
+    #[test]
+    fn cli_passes_find_expressions_through_untouched() {
+        for argv in [
+            &["vicaya", "find", "-name", "*.rs", "-print0"][..],
+            &["vicaya", "find", "src", "-type", "f", "-size", "-1k"][..],
+        ] {
+            match Cli::try_parse_from(argv).unwrap().command {
+                Some(Commands::Find(args)) => assert!(format!("{args:?}").contains(argv[3])),
+                other => panic!("unexpected command: {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn cli_parses_z_keywords_and_requires_one() {
+        match Cli::try_parse_from(["vicaya", "z", "proj", "vic"])
+            .unwrap()
+            .command
+        {
+            Some(Commands::Jump(args)) => {
+                assert!(format!("{args:?}").contains(r#"["proj", "vic"]"#))
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+        assert!(Cli::try_parse_from(["vicaya", "z"]).is_err());
+    }
+
     #[test]
     fn cli_parses_grep_engine_and_slow_fallback() {
         let cli = Cli::parse_from([
@@ -1221,6 +2128,75 @@ mod tests {
             }
             other => panic!("unexpected command: {other:?}"),
         }
+
+        let cli = Cli::parse_from(["vicaya", "grep", "needle"]);
+        match cli.command {
+            Some(Commands::Grep { format, engine, .. }) => {
+                assert_eq!(format, "heading");
+                assert!(engine.is_none());
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let cli = Cli::parse_from([
+            "vicaya",
+            "grep",
+            "needle",
+            "--engine",
+            "index",
+            "--ext",
+            "rs,md",
+            "--ext",
+            "toml",
+            "--max-filesize",
+            "512k",
+        ]);
+        match cli.command {
+            Some(Commands::Grep {
+                engine,
+                ext,
+                max_filesize,
+                ..
+            }) => {
+                assert!(matches!(engine, Some(ContentEngineCli::Index)));
+                assert!(engine.and_then(ContentEngineCli::choice).is_none());
+                assert_eq!(ext, ["rs", "md", "toml"]);
+                assert_eq!(max_filesize.as_deref(), Some("512k"));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_doctor_flags() {
+        let cli = Cli::parse_from([
+            "vicaya",
+            "doctor",
+            "--config",
+            "/tmp/vicaya.toml",
+            "--format",
+            "json",
+        ]);
+
+        match cli.command {
+            Some(Commands::Doctor { config, format }) => {
+                assert_eq!(config, Some(PathBuf::from("/tmp/vicaya.toml")));
+                assert_eq!(format, "json");
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn init_template_passes_config_validation() {
+        let content = default_config_template(Path::new("/tmp/vicaya/index"), 4);
+        let report = vicaya_core::config_validation::validate_str(&content);
+
+        assert!(
+            !report.has_errors(),
+            "{}",
+            report.render(Path::new("config.toml"))
+        );
     }
 
     #[test]
@@ -1279,6 +2255,75 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn index_cap_lines_name_the_skipped_roots() {
+        let cap = vicaya_core::ipc::IndexCap {
+            max_indexed_files: 1_000,
+            skipped: 250,
+            skipped_live: 3,
+            roots: vec![
+                vicaya_core::ipc::RootCoverage {
+                    root: "/Users/me".to_string(),
+                    indexed: 1_000,
+                    skipped: 50,
+                    complete_depth: Some(4),
+                },
+                vicaya_core::ipc::RootCoverage {
+                    root: "/Volumes/Data".to_string(),
+                    indexed: 0,
+                    skipped: 200,
+                    complete_depth: None,
+                },
+            ],
+        };
+        assert_eq!(
+            index_cap_lines(&cap),
+            vec![
+                "⚠ Index capped at 1,000 entries (max_indexed_files): 253 skipped",
+                "  /Users/me: 1,000 indexed, 50 skipped (complete to depth 4)",
+                "  /Volumes/Data: 0 indexed, 200 skipped (not indexed)",
+                "  3 new entries since the last scan",
+                "  Raise max_indexed_files or add exclusions to index the rest",
+            ]
+        );
+    }
+
+    #[test]
+    fn cli_parses_alias_subcommands() {
+        let add = Cli::parse_from(["vicaya", "aliases", "add", "cfg", "ext:toml", "type:file"]);
+        match add.command {
+            Some(Commands::Aliases {
+                action: aliases::AliasAction::Add { name, expansion },
+            }) => {
+                assert_eq!(name, "cfg");
+                assert_eq!(expansion, vec!["ext:toml", "type:file"]);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn cli_parses_bookmark_subcommands() {
+        let add = Cli::parse_from(["vicaya", "bookmark", "add", "--name", "foo"]);
+        match add.command {
+            Some(Commands::Bookmark {
+                action: bookmark::BookmarkAction::Add { dir, name },
+            }) => {
+                assert_eq!(dir, PathBuf::from("."));
+                assert_eq!(name.as_deref(), Some("foo"));
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+
+        let rm = Cli::parse_from(["vicaya", "bookmark", "rm", "3"]);
+        assert!(matches!(
+            rm.command,
+            Some(Commands::Bookmark {
+                action: bookmark::BookmarkAction::Rm { bookmark }
+            }) if bookmark == "3"
+        ));
+    }
+
     #[test]
     fn smriti_json_list_routes_daemon_start_messages_to_stderr() {
         let json_list = SmritiActionCli::List {
@@ -1310,18 +2355,17 @@ mod tests {
         std::env::set_current_dir(temp.path()).unwrap();
         let expected_cwd = std::env::current_dir().unwrap();
 
-        let request = build_search_request("query.rs", 20, None).unwrap();
+        let request = build_search_request(SearchOptions::new("query.rs", 20), None).unwrap();
 
         std::env::set_current_dir(old_cwd).unwrap();
 
         match request {
-            Request::Search {
-                scope,
-                filter_scope,
-                ..
-            } => {
-                assert_eq!(scope, Some(expected_cwd.to_string_lossy().to_string()));
-                assert!(filter_scope.is_none());
+            Request::Search(options) => {
+                assert_eq!(
+                    options.scope,
+                    Some(expected_cwd.to_string_lossy().to_string())
+                );
+                assert!(options.filters.scope.is_none());
             }
             other => panic!("unexpected request: {other:?}"),
         }
@@ -1333,20 +2377,17 @@ mod tests {
         let scoped = temp.path().join("repo");
         std::fs::create_dir_all(&scoped).unwrap();
 
-        let request = build_search_request("query.rs", 20, Some(&scoped)).unwrap();
+        let request =
+            build_search_request(SearchOptions::new("query.rs", 20), Some(&scoped)).unwrap();
         let expected = vicaya_core::paths::resolve_scope_dir(&scoped)
             .unwrap()
             .to_string_lossy()
             .to_string();
 
         match request {
-            Request::Search {
-                scope,
-                filter_scope,
-                ..
-            } => {
-                assert_eq!(scope, Some(expected.clone()));
-                assert_eq!(filter_scope, Some(expected));
+            Request::Search(options) => {
+                assert_eq!(options.scope, Some(expected.clone()));
+                assert_eq!(options.filters.scope, Some(expected));
             }
             other => panic!("unexpected request: {other:?}"),
         }
@@ -5,12 +5,17 @@ use serde::Serialize;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use vicaya_core::ipc::{BuildInfo, Request, Response};
+use vicaya_client::Client;
+use vicaya_core::accounting::SubsystemUsage;
+use vicaya_core::config::JournalFsync;
+use vicaya_core::ipc::{
+    BuildInfo, DaemonStatus, Framing, IndexCap, Request, Response, SearchOptions,
+};
+use vicaya_core::telemetry::RankingReport;
 use vicaya_core::Result;
 
-use crate::ipc_client::IpcClient;
-
-const INNER_WIDTH: usize = 53;
+use crate::render::{self, Panel, Tone};
+use crate::status_report::StatusReport;
 
 #[derive(Args, Debug)]
 pub(crate) struct MetricsArgs {
@@ -32,6 +37,21 @@ pub(crate) enum MetricsAction {
     Watch(MetricsWatchArgs),
     /// Benchmark IPC search performance.
     Bench(MetricsBenchArgs),
+    /// Summarize the local ranking telemetry log (`[telemetry] enabled`).
+    RankingReport(RankingReportArgs),
+    /// Delete the local ranking telemetry log.
+    RankingPurge {
+        /// Confirm deleting without prompting
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Args, Debug, Clone)]
+pub(crate) struct RankingReportArgs {
+    /// Output format (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub(crate) format: String,
 }
 
 #[derive(Args, Debug, Clone)]
@@ -75,6 +95,12 @@ pub(crate) struct MetricsBenchArgs {
     #[arg(long, default_value_t = 20)]
     pub(crate) limit: usize,
 
+    /// IPC framing to measure (json, msgpack). Give several, comma-separated,
+    /// to run the benchmark once per framing and compare them; the first is
+    /// the baseline.
+    #[arg(long, value_delimiter = ',', default_value = "json", value_parser = Framing::parse)]
+    pub(crate) framing: Vec<Framing>,
+
     /// Capture `vmmap` before and after the benchmark.
     #[arg(long)]
     pub(crate) vmmap_before_after: bool,
@@ -84,6 +110,8 @@ pub(crate) fn run(args: MetricsArgs) -> Result<()> {
     match args.action {
         Some(MetricsAction::Watch(watch)) => watch_metrics(watch),
         Some(MetricsAction::Bench(bench)) => bench_metrics(bench),
+        Some(MetricsAction::RankingReport(report)) => ranking_report(report),
+        Some(MetricsAction::RankingPurge { yes }) => ranking_purge(yes),
         None => snapshot_metrics(&args.format, !args.no_vmmap),
     }
 }
@@ -117,6 +145,9 @@ struct MetricsSnapshot {
     client: ClientSnapshot,
     daemon: DaemonSnapshot,
     index: Option<IndexSnapshot>,
+    /// The `vicaya status --format json` document, when the daemon answered.
+    status: Option<StatusReport>,
+    usage: Option<UsageSnapshot>,
     disk: DiskSnapshot,
     process: Option<ProcessSnapshot>,
     derived: DerivedSnapshot,
@@ -149,6 +180,44 @@ struct IndexSnapshot {
     state_allocated_bytes: u64,
     last_updated: i64,
     reconciling: bool,
+    reconcile_progress: Option<u8>,
+    pending_watcher_events: usize,
+    journal_entries: usize,
+    journal_fsync: Option<JournalFsync>,
+    warmup_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index_cap: Option<IndexCap>,
+}
+
+impl From<&StatusReport> for IndexSnapshot {
+    fn from(report: &StatusReport) -> Self {
+        Self {
+            files: report.index.files,
+            trigrams: report.index.trigrams,
+            arena_bytes: report.index.arena_bytes,
+            index_allocated_bytes: report.index.index_allocated_bytes,
+            state_allocated_bytes: report.index.state_allocated_bytes,
+            last_updated: report.index.last_updated,
+            reconciling: report.reconcile.running,
+            reconcile_progress: report.reconcile.progress,
+            pending_watcher_events: report.watcher.pending_events,
+            journal_entries: report.journal.entries,
+            journal_fsync: report.journal.fsync,
+            warmup_ms: report.index.warmup_ms,
+            index_cap: report.index.cap.clone(),
+        }
+    }
+}
+
+/// Daemon CPU and I/O per subsystem, from `Request::Metrics`.
+#[derive(Debug, Serialize)]
+struct UsageSnapshot {
+    uptime_secs: u64,
+    process_cpu_ns: u64,
+    /// Process CPU not charged to any subsystem (startup, IPC plumbing,
+    /// helper threads).
+    unattributed_cpu_ns: u64,
+    subsystems: Vec<SubsystemUsage>,
 }
 
 #[derive(Debug, Serialize)]
@@ -288,41 +357,31 @@ fn collect_snapshot(mode: SnapshotMode, ctx: &SnapshotContext) -> Result<Metrics
     let mut daemon_build = None;
     let mut connect_error = None;
     let mut index = None;
+    let mut status = None;
 
     if running {
-        match IpcClient::connect() {
+        match Client::connect() {
             Ok(mut client) => match client.request(&Request::Status) {
-                Ok(Response::Status {
-                    pid: status_pid,
-                    build,
-                    indexed_files,
-                    trigram_count,
-                    arena_size,
-                    index_allocated_bytes,
-                    state_allocated_bytes,
-                    last_updated,
-                    reconciling,
-                }) => {
-                    daemon_build = Some(build);
-                    if pid.is_none() && status_pid > 0 {
-                        // PID file may be missing; prefer daemon-reported PID when available.
-                        notes.push("PID file missing; using daemon-reported PID".to_string());
-                    }
-                    index = Some(IndexSnapshot {
-                        files: indexed_files,
-                        trigrams: trigram_count,
-                        arena_bytes: arena_size,
-                        index_allocated_bytes,
-                        state_allocated_bytes,
-                        last_updated,
-                        reconciling,
-                    });
-                }
                 Ok(Response::Error { message }) => {
                     connect_error = Some(message);
                 }
-                Ok(_) => {
-                    connect_error = Some("Unexpected response from daemon".to_string());
+                Ok(response) => {
+                    match StatusReport::from_response(response, Path::new(&ctx.index_dir)) {
+                        Some(report) => {
+                            daemon_build = Some(report.daemon.build.clone());
+                            if pid.is_none() && report.daemon.pid > 0 {
+                                // PID file may be missing; prefer daemon-reported PID when available.
+                                notes.push(
+                                    "PID file missing; using daemon-reported PID".to_string(),
+                                );
+                            }
+                            index = Some(IndexSnapshot::from(&report));
+                            status = Some(report);
+                        }
+                        None => {
+                            connect_error = Some("Unexpected response from daemon".to_string());
+                        }
+                    }
                 }
                 Err(e) => {
                     connect_error = Some(e.to_string());
@@ -336,6 +395,29 @@ fn collect_snapshot(mode: SnapshotMode, ctx: &SnapshotContext) -> Result<Metrics
         notes.push("Daemon is not running".to_string());
     }
 
+    let mut usage = None;
+    if daemon_build.is_some() {
+        match Client::connect().and_then(|mut client| client.request(&Request::Metrics)) {
+            Ok(Response::Metrics {
+                uptime_secs,
+                process_cpu_ns,
+                subsystems,
+            }) => {
+                let attributed: u64 = subsystems.iter().map(|usage| usage.cpu_ns).sum();
+                usage = Some(UsageSnapshot {
+                    uptime_secs,
+                    process_cpu_ns,
+                    unattributed_cpu_ns: process_cpu_ns.saturating_sub(attributed),
+                    subsystems,
+                });
+            }
+            // Older daemons answer with an error for the unknown request.
+            Ok(_) | Err(_) => {
+                notes.push("Daemon does not report resource usage".to_string());
+            }
+        }
+    }
+
     let daemon = DaemonSnapshot {
         running,
         pid,
@@ -365,6 +447,8 @@ fn collect_snapshot(mode: SnapshotMode, ctx: &SnapshotContext) -> Result<Metrics
         client: ctx.client.clone(),
         daemon,
         index,
+        status,
+        usage,
         disk,
         process,
         derived,
@@ -688,52 +772,29 @@ fn derive_metrics(
 }
 
 fn print_pretty_snapshot(snapshot: &MetricsSnapshot, padded: bool) {
-    use owo_colors::OwoColorize;
-
     if padded {
         println!();
     }
-    println!(
-        "{}",
-        "╭───────────────────────────────────────────────────────╮".bright_blue()
-    );
-    println!(
-        "{} {:<53} {}",
-        "│".bright_blue(),
-        "Vicaya — Runtime Metrics".bold().bright_white(),
-        "│".bright_blue()
-    );
-
-    println!(
-        "{}",
-        "├───────────────────────────────────────────────────────┤".bright_blue()
-    );
-
-    let title_line = format!("{:53}", "  Daemon");
-    println!(
-        "{} {} {}",
-        "│".bright_blue(),
-        title_line.bold().bright_white(),
-        "│".bright_blue()
-    );
+    let mut panel = Panel::new("Vicaya — Runtime Metrics");
+    panel.section("Daemon");
 
     let running_str = if snapshot.daemon.running {
         "● Running"
     } else {
         "○ Not running"
     };
-    print_kv_line(
-        "    Status:",
+    panel.field(
+        "Status:",
         running_str,
         if snapshot.daemon.running {
-            ValueStyle::Good
+            Tone::Good
         } else {
-            ValueStyle::Warn
+            Tone::Warn
         },
     );
 
     if let Some(pid) = snapshot.daemon.pid {
-        print_kv_line("    PID:", &pid.to_string(), ValueStyle::Neutral);
+        panel.field("PID:", pid.to_string(), Tone::Neutral);
     }
 
     if let Some(build) = snapshot.daemon.build.as_ref() {
@@ -746,257 +807,199 @@ fn print_pretty_snapshot(snapshot: &MetricsSnapshot, padded: bool) {
                 &build.git_sha
             }
         );
-        print_kv_line("    Build:", &build_str, ValueStyle::Neutral);
+        panel.field("Build:", &build_str, Tone::Neutral);
     }
 
     if let Some(err) = snapshot.daemon.connect_error.as_ref() {
-        print_kv_line("    IPC:", err, ValueStyle::Warn);
+        panel.field("IPC:", err, Tone::Warn);
     }
 
-    println!(
-        "{}",
-        "├───────────────────────────────────────────────────────┤".bright_blue()
-    );
-
-    let title_line = format!("{:53}", "  Process Memory (macOS)");
-    println!(
-        "{} {} {}",
-        "│".bright_blue(),
-        title_line.bold().bright_white(),
-        "│".bright_blue()
-    );
+    panel.section("Process Memory (macOS)");
 
     if let Some(process) = snapshot.process.as_ref() {
         if let Some(rss) = process.ps.rss_bytes {
-            print_kv_line("    RSS (ps):", &format_bytes_mb(rss), ValueStyle::Neutral);
+            panel.field("RSS (ps):", format_bytes_mb(rss), Tone::Neutral);
         }
 
         if process.vmmap.captured {
             if let Some(foot) = process.vmmap.physical_footprint_bytes {
-                print_kv_line(
-                    "    Physical footprint:",
-                    &format_bytes_mb(foot),
-                    ValueStyle::Hot,
-                );
+                panel.field("Physical footprint:", format_bytes_mb(foot), Tone::Hot);
             }
             if let Some(peak) = process.vmmap.physical_footprint_peak_bytes {
-                print_kv_line(
-                    "    Footprint (peak):",
-                    &format_bytes_mb(peak),
-                    ValueStyle::Neutral,
-                );
+                panel.field("Footprint (peak):", format_bytes_mb(peak), Tone::Neutral);
             }
             if let Some(malloc) = process.vmmap.malloc_zone_total.as_ref() {
-                print_kv_line(
-                    "    Malloc allocated:",
-                    &format_bytes_mb(malloc.allocated_bytes),
-                    ValueStyle::Hot,
+                panel.field(
+                    "Malloc allocated:",
+                    format_bytes_mb(malloc.allocated_bytes),
+                    Tone::Hot,
                 );
-                print_kv_line(
-                    "    Malloc frag:",
-                    &format!(
+                panel.field(
+                    "Malloc frag:",
+                    format!(
                         "{} ({}%)",
                         format_bytes_mb(malloc.frag_bytes),
                         malloc.frag_pct
                     ),
-                    ValueStyle::Neutral,
+                    Tone::Neutral,
                 );
             }
             if let Some(total) = process.vmmap.total.as_ref() {
-                print_kv_line(
-                    "    Swapped:",
-                    &format_bytes_mb(total.swapped_bytes),
-                    ValueStyle::Neutral,
+                panel.field(
+                    "Swapped:",
+                    format_bytes_mb(total.swapped_bytes),
+                    Tone::Neutral,
                 );
             }
             if let Some(err) = process.vmmap.error.as_ref() {
-                print_kv_line("    vmmap:", err, ValueStyle::Warn);
+                panel.field("vmmap:", err, Tone::Warn);
             }
         } else {
-            print_kv_line("    vmmap:", "skipped", ValueStyle::Neutral);
+            panel.field("vmmap:", "skipped", Tone::Neutral);
         }
     } else {
-        print_kv_line("    PID:", "unavailable", ValueStyle::Warn);
+        panel.field("PID:", "unavailable", Tone::Warn);
     }
 
-    println!(
-        "{}",
-        "├───────────────────────────────────────────────────────┤".bright_blue()
-    );
-
-    let title_line = format!("{:53}", "  Index");
-    println!(
-        "{} {} {}",
-        "│".bright_blue(),
-        title_line.bold().bright_white(),
-        "│".bright_blue()
-    );
+    panel.section("Index");
 
     if let Some(index) = snapshot.index.as_ref() {
-        print_kv_line(
-            "    Files indexed:",
-            &crate::format_number(index.files),
-            ValueStyle::Good,
+        panel.field(
+            "Files indexed:",
+            crate::format_number(index.files),
+            Tone::Good,
         );
-        print_kv_line(
-            "    Trigrams:",
-            &crate::format_number(index.trigrams),
-            ValueStyle::Neutral,
+        panel.field(
+            "Trigrams:",
+            crate::format_number(index.trigrams),
+            Tone::Neutral,
         );
-        print_kv_line(
-            "    State heap (est):",
-            &format_bytes_mb(index.state_allocated_bytes),
-            ValueStyle::Hot,
+        panel.field(
+            "State heap (est):",
+            format_bytes_mb(index.state_allocated_bytes),
+            Tone::Hot,
         );
-        print_kv_line(
-            "    Index heap (est):",
-            &format_bytes_mb(index.index_allocated_bytes),
-            ValueStyle::Neutral,
+        panel.field(
+            "Index heap (est):",
+            format_bytes_mb(index.index_allocated_bytes),
+            Tone::Neutral,
         );
-        print_kv_line(
-            "    String arena:",
-            &format_bytes_mb(index.arena_bytes as u64),
-            ValueStyle::Neutral,
+        panel.field(
+            "String arena:",
+            format_bytes_mb(index.arena_bytes as u64),
+            Tone::Neutral,
         );
         if index.reconciling {
-            print_kv_line("    Reconcile:", "running", ValueStyle::Warn);
+            let reconcile = match index.reconcile_progress {
+                Some(progress) => format!("running ({progress}%)"),
+                None => "running".to_string(),
+            };
+            panel.field("Reconcile:", &reconcile, Tone::Warn);
+        }
+        panel.field(
+            "Watcher queue:",
+            crate::format_number(index.pending_watcher_events),
+            Tone::Neutral,
+        );
+        panel.field(
+            "Journal entries:",
+            crate::format_number(index.journal_entries),
+            Tone::Neutral,
+        );
+        if let Some(journal_fsync) = index.journal_fsync {
+            panel.field("Journal fsync:", journal_fsync.to_string(), Tone::Neutral);
+        }
+        if let Some(warmup_ms) = index.warmup_ms {
+            panel.field("Warm-up:", format!("{warmup_ms} ms"), Tone::Neutral);
         }
     } else {
-        print_kv_line("    Status:", "unavailable", ValueStyle::Warn);
+        panel.field("Status:", "unavailable", Tone::Warn);
     }
 
-    println!(
-        "{}",
-        "├───────────────────────────────────────────────────────┤".bright_blue()
-    );
+    if let Some(usage) = snapshot.usage.as_ref() {
+        panel.section("Resource usage");
 
-    let title_line = format!("{:53}", "  Disk");
-    println!(
-        "{} {} {}",
-        "│".bright_blue(),
-        title_line.bold().bright_white(),
-        "│".bright_blue()
-    );
+        panel.field("Uptime:", format_uptime(usage.uptime_secs), Tone::Neutral);
+        panel.field(
+            "Process CPU:",
+            format_cpu_ns(usage.process_cpu_ns),
+            Tone::Hot,
+        );
+        panel.field(
+            "subsystem",
+            usage_columns("cpu", "read", "spans"),
+            Tone::Neutral,
+        );
+        for row in &usage.subsystems {
+            panel.field(
+                &format!("{}:", row.subsystem.label()),
+                usage_columns(
+                    &format_cpu_ns(row.cpu_ns),
+                    &row.read_bytes.map_or("n/a".to_string(), format_bytes_mb),
+                    &crate::format_number(row.spans as usize),
+                ),
+                Tone::Neutral,
+            );
+        }
+        panel.field(
+            "unattributed:",
+            usage_columns(&format_cpu_ns(usage.unattributed_cpu_ns), "", ""),
+            Tone::Neutral,
+        );
+    }
+
+    panel.section("Disk");
 
     let index_size = snapshot.disk.index_file.size_bytes;
     let journal_size = snapshot.disk.journal_file.size_bytes;
-    print_kv_line(
-        "    index.bin:",
-        &format_bytes_mb(index_size),
-        ValueStyle::Neutral,
-    );
-    print_kv_line(
-        "    index.journal:",
-        &format_bytes_mb(journal_size),
-        ValueStyle::Neutral,
+    panel.field("index.bin:", format_bytes_mb(index_size), Tone::Neutral);
+    panel.field(
+        "index.journal:",
+        format_bytes_mb(journal_size),
+        Tone::Neutral,
     );
 
-    println!(
-        "{}",
-        "├───────────────────────────────────────────────────────┤".bright_blue()
-    );
-
-    let title_line = format!("{:53}", "  Derived");
-    println!(
-        "{} {} {}",
-        "│".bright_blue(),
-        title_line.bold().bright_white(),
-        "│".bright_blue()
-    );
+    panel.section("Derived");
 
     if let Some(bpf) = snapshot.derived.bytes_per_file_arena {
-        print_kv_line(
-            "    Arena/file:",
-            &format!("{} B", bpf),
-            ValueStyle::Neutral,
-        );
+        panel.field("Arena/file:", format!("{} B", bpf), Tone::Neutral);
     }
     if let Some(bpf) = snapshot.derived.bytes_per_file_state_heap_est {
-        print_kv_line(
-            "    Heap est/file:",
-            &format!("{} B", bpf),
-            ValueStyle::Neutral,
-        );
+        panel.field("Heap est/file:", format!("{} B", bpf), Tone::Neutral);
     }
     if let Some(bpf) = snapshot.derived.bytes_per_file_physical_footprint {
-        print_kv_line(
-            "    Footprint/file:",
-            &format!("{} B", bpf),
-            ValueStyle::Hot,
-        );
+        panel.field("Footprint/file:", format!("{} B", bpf), Tone::Hot);
     }
     if let Some(ratio) = snapshot.derived.heap_est_to_footprint_ratio {
-        print_kv_line(
-            "    Footprint/heap:",
-            &format!("{:.2}×", ratio),
-            ValueStyle::Neutral,
-        );
+        panel.field("Footprint/heap:", format!("{:.2}×", ratio), Tone::Neutral);
     }
 
-    println!(
-        "{}",
-        "╰───────────────────────────────────────────────────────╯".bright_blue()
-    );
+    panel.print();
     if padded {
         println!();
     }
 }
 
-#[derive(Clone, Copy)]
-enum ValueStyle {
-    Good,
-    Warn,
-    Hot,
-    Neutral,
-}
-
-fn print_kv_line(label: &str, value: &str, style: ValueStyle) {
-    use owo_colors::OwoColorize;
-
-    let available = INNER_WIDTH.saturating_sub(label.len());
-    let value_aligned = fit_value_right(value, available);
-
-    let border = "│".bright_blue();
-    let label = label.dimmed();
-    match style {
-        ValueStyle::Good => println!(
-            "{border} {label}{} {border}",
-            value_aligned.bright_green().bold()
-        ),
-        ValueStyle::Warn => println!(
-            "{border} {label}{} {border}",
-            value_aligned.bright_yellow().bold()
-        ),
-        ValueStyle::Hot => println!(
-            "{border} {label}{} {border}",
-            value_aligned.bright_magenta()
-        ),
-        ValueStyle::Neutral => {
-            println!("{border} {label}{} {border}", value_aligned.bright_white())
-        }
-    };
+/// CPU, read, and span columns of the resource usage table.
+fn usage_columns(cpu: &str, read: &str, spans: &str) -> String {
+    format!("{cpu:>10} {read:>10} {spans:>8}")
 }
 
-fn fit_value_right(value: &str, width: usize) -> String {
-    if width == 0 {
-        return String::new();
-    }
-
-    let value = value.trim();
-    let len = value.chars().count();
-
-    if len <= width {
-        return format!("{value:>width$}");
+fn format_cpu_ns(ns: u64) -> String {
+    let ms = ns / 1_000_000;
+    if ms < 1_000 {
+        format!("{ms} ms")
+    } else {
+        format!("{:.2} s", ns as f64 / 1e9)
     }
+}
 
-    if width <= 3 {
-        return ".".repeat(width);
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3_599 => format!("{}m {:02}s", secs / 60, secs % 60),
+        _ => format!("{}h {:02}m", secs / 3_600, secs % 3_600 / 60),
     }
-
-    let tail_len = width - 3;
-    let mut tail: String = value.chars().rev().take(tail_len).collect();
-    tail = tail.chars().rev().collect();
-    format!("...{tail}")
 }
 
 fn format_bytes_mb(bytes: u64) -> String {
@@ -1096,6 +1099,8 @@ struct BenchReport {
     vmmap_after: Option<VmmapSnapshot>,
     summary: BenchSummary,
     samples: Vec<u64>,
+    /// One entry per `--framing`, the first matching `summary`.
+    framings: Vec<FramingBench>,
     notes: Vec<String>,
 }
 
@@ -1105,9 +1110,18 @@ struct BenchParams {
     warmup: u32,
     limit: usize,
     query_count: usize,
+    framing: Framing,
 }
 
 #[derive(Debug, Serialize)]
+struct FramingBench {
+    framing: Framing,
+    summary: BenchSummary,
+    /// Change in p50 latency against the first framing, in percent.
+    p50_change_pct: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
 struct BenchSummary {
     ok_runs: u32,
     error_runs: u32,
@@ -1123,9 +1137,6 @@ struct BenchSummary {
 }
 
 fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
-    use owo_colors::OwoColorize;
-    use std::time::Instant;
-
     let queries = load_queries(&args.queries)?;
     if queries.is_empty() {
         return Err(vicaya_core::Error::Config(
@@ -1158,9 +1169,9 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
     let mut index = None;
 
     // Capture daemon status once for the report.
-    if let Ok(mut client_ipc) = IpcClient::connect() {
+    if let Ok(mut client_ipc) = Client::connect() {
         match client_ipc.request(&Request::Status) {
-            Ok(Response::Status {
+            Ok(Response::Status(DaemonStatus {
                 build,
                 indexed_files,
                 trigram_count,
@@ -1169,8 +1180,14 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
                 state_allocated_bytes,
                 last_updated,
                 reconciling,
+                pending_watcher_events,
+                journal_entries,
+                reconcile_progress,
+                warmup_ms,
+                journal_fsync,
+                index_cap,
                 ..
-            }) => {
+            })) => {
                 daemon_build = Some(build);
                 index = Some(IndexSnapshot {
                     files: indexed_files,
@@ -1180,6 +1197,12 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
                     state_allocated_bytes,
                     last_updated,
                     reconciling,
+                    reconcile_progress,
+                    pending_watcher_events,
+                    journal_entries,
+                    journal_fsync,
+                    warmup_ms,
+                    index_cap,
                 });
             }
             Ok(Response::Error { message }) => connect_error = Some(message),
@@ -1215,65 +1238,21 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
         }
     }
 
-    // Warmup.
-    for i in 0..args.warmup {
-        let q = &queries[i as usize % queries.len()];
-        let request = Request::Search {
-            query: q.clone(),
-            limit: args.limit,
-            scope: std::env::current_dir()
-                .ok()
-                .map(|p| p.to_string_lossy().to_string()),
-            filter_scope: None,
-            recent_if_empty: false,
-        };
-        if let Ok(mut client_ipc) = IpcClient::connect() {
-            let _ = client_ipc.request(&request);
-        }
+    let mut framings: Vec<FramingBench> = Vec::new();
+    let mut baseline = None;
+    for &framing in &args.framing {
+        let (samples, summary) = bench_framing(&queries, &args, framing, &mut notes);
+        let p50_change_pct = framings
+            .first()
+            .and_then(|base| percent_change(base.summary.p50_us, summary.p50_us));
+        framings.push(FramingBench {
+            framing,
+            summary: summary.clone(),
+            p50_change_pct,
+        });
+        baseline.get_or_insert((samples, summary));
     }
-
-    let mut samples_us: Vec<u64> = Vec::with_capacity(args.runs as usize);
-    let mut error_runs: u32 = 0;
-
-    let start_all = Instant::now();
-    for i in 0..args.runs {
-        let q = &queries[i as usize % queries.len()];
-        let request = Request::Search {
-            query: q.clone(),
-            limit: args.limit,
-            scope: std::env::current_dir()
-                .ok()
-                .map(|p| p.to_string_lossy().to_string()),
-            filter_scope: None,
-            recent_if_empty: false,
-        };
-
-        let start = Instant::now();
-        let resp = match IpcClient::connect() {
-            Ok(mut client_ipc) => client_ipc.request(&request),
-            Err(e) => Err(e),
-        };
-        let elapsed = start.elapsed();
-
-        match resp {
-            Ok(Response::SearchResults { .. }) => {
-                samples_us.push(elapsed.as_micros().min(u128::from(u64::MAX)) as u64);
-            }
-            Ok(Response::Error { message }) => {
-                error_runs += 1;
-                notes.push(format!("daemon error: {message}"));
-            }
-            Ok(_) => {
-                error_runs += 1;
-                notes.push("unexpected response during bench".to_string());
-            }
-            Err(e) => {
-                error_runs += 1;
-                notes.push(format!("ipc error: {e}"));
-            }
-        }
-    }
-    let total_time = start_all.elapsed();
+    let (samples_us, summary) = baseline.unwrap_or_default();
 
     if args.vmmap_before_after {
         if let Some(pid) = pid {
@@ -1281,12 +1260,6 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
         }
     }
 
-    let mut sorted = samples_us.clone();
-    sorted.sort_unstable();
-    let ok_runs = sorted.len() as u32;
-
-    let summary = summarize_latencies(&sorted, ok_runs, error_runs, total_time);
-
     let report = BenchReport {
         schema_version: 1,
         captured_at_unix_ms,
@@ -1299,11 +1272,13 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
             warmup: args.warmup,
             limit: args.limit,
             query_count: queries.len(),
+            framing: args.framing.first().copied().unwrap_or_default(),
         },
         vmmap_before,
         vmmap_after,
         summary,
         samples: samples_us,
+        framings,
         notes,
     };
 
@@ -1313,7 +1288,10 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
         }
         _ => {
             println!();
-            println!("{}", "Vicaya — Bench".bold().bright_white());
+            println!(
+                "{}",
+                Tone::Title.paint("Vicaya — Bench", render::color_enabled())
+            );
             println!(
                 "  Runs: {} (warmup {}) | Queries: {} | Limit: {}",
                 args.runs,
@@ -1345,6 +1323,23 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
                 report.summary.qps,
                 report.summary.error_runs
             );
+            if report.framings.len() > 1 {
+                println!();
+                for bench in &report.framings {
+                    let change = match bench.p50_change_pct {
+                        Some(pct) => format!("{pct:+.1}% p50"),
+                        None => "baseline".to_string(),
+                    };
+                    println!(
+                        "  {:<8} p50 {:>8}  p95 {:>8}  mean {:>8}  {}",
+                        bench.framing.as_str(),
+                        format_us(bench.summary.p50_us),
+                        format_us(bench.summary.p95_us),
+                        format_us(bench.summary.mean_us),
+                        change
+                    );
+                }
+            }
             if let (Some(before), Some(after)) =
                 (report.vmmap_before.as_ref(), report.vmmap_after.as_ref())
             {
@@ -1368,6 +1363,76 @@ fn bench_metrics(args: MetricsBenchArgs) -> Result<()> {
     Ok(())
 }
 
+fn ranking_report(args: RankingReportArgs) -> Result<()> {
+    let path = vicaya_core::paths::telemetry_path();
+    let events = vicaya_core::telemetry::load_events(&path)?;
+    let report = RankingReport::from_events(&events);
+
+    match args.format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+        _ => {
+            let color = render::color_enabled();
+            println!();
+            println!("{}", Tone::Title.paint("Vicaya — Ranking Report", color));
+            if report.events == 0 {
+                println!("  No ranking telemetry recorded at {}", path.display());
+                if !crate::load_config().is_ok_and(|config| config.telemetry.enabled) {
+                    println!("  Enable it with `[telemetry] enabled = true` and use the TUI.");
+                }
+                println!();
+                return Ok(());
+            }
+            println!(
+                "  Selections: {} | Queries: {} | MRR: {:.3}",
+                crate::format_number(report.events),
+                crate::format_number(report.distinct_queries),
+                report.mrr
+            );
+            if let (Some(p50), Some(p90)) =
+                (report.time_to_select_p50_ms, report.time_to_select_p90_ms)
+            {
+                println!(
+                    "  Time to select: p50 {}  p90 {}",
+                    format_us(p50.saturating_mul(1_000)),
+                    format_us(p90.saturating_mul(1_000))
+                );
+            }
+            println!();
+            println!("  {:<6} {:>8} {:>7}", "Rank", "Count", "Share");
+            for bucket in &report.rank_distribution {
+                let bar = "█".repeat((bucket.share * 30.0).round() as usize);
+                println!(
+                    "  {:<6} {:>8} {:>6.1}% {}",
+                    bucket.label,
+                    crate::format_number(bucket.count),
+                    bucket.share * 100.0,
+                    Tone::Info.paint(&bar, color)
+                );
+            }
+            println!();
+        }
+    }
+
+    Ok(())
+}
+
+fn ranking_purge(yes: bool) -> Result<()> {
+    if !yes {
+        return Err(vicaya_core::Error::Other(
+            "Refusing to purge ranking telemetry without --yes".to_string(),
+        ));
+    }
+    let path = vicaya_core::paths::telemetry_path();
+    if vicaya_core::telemetry::purge(&path)? {
+        println!("Ranking telemetry purged: {}", path.display());
+    } else {
+        println!("No ranking telemetry to purge");
+    }
+    Ok(())
+}
+
 fn load_queries(path: &Path) -> Result<Vec<String>> {
     let content = std::fs::read_to_string(path)?;
     let mut out = Vec::new();
@@ -1381,6 +1446,78 @@ fn load_queries(path: &Path) -> Result<Vec<String>> {
     Ok(out)
 }
 
+/// Warm up, then time `args.runs` searches over connections using
+/// `framing`. Returns the sorted samples and their summary.
+fn bench_framing(
+    queries: &[String],
+    args: &MetricsBenchArgs,
+    framing: Framing,
+    notes: &mut Vec<String>,
+) -> (Vec<u64>, BenchSummary) {
+    use std::time::Instant;
+
+    let request_for = |i: u32| {
+        let q = &queries[i as usize % queries.len()];
+        Request::Search(SearchOptions {
+            scope: std::env::current_dir()
+                .ok()
+                .map(|p| p.to_string_lossy().to_string()),
+            ..SearchOptions::new(q.clone(), args.limit)
+        })
+    };
+
+    // Warmup.
+    for i in 0..args.warmup {
+        if let Ok(mut client_ipc) = Client::connect_with(framing) {
+            let _ = client_ipc.request(&request_for(i));
+        }
+    }
+
+    let mut samples_us: Vec<u64> = Vec::with_capacity(args.runs as usize);
+    let mut error_runs: u32 = 0;
+
+    let start_all = Instant::now();
+    for i in 0..args.runs {
+        let request = request_for(i);
+
+        let start = Instant::now();
+        let resp = match Client::connect_with(framing) {
+            Ok(mut client_ipc) => client_ipc.request(&request),
+            Err(e) => Err(e),
+        };
+        let elapsed = start.elapsed();
+
+        match resp {
+            Ok(Response::SearchResults { .. }) => {
+                samples_us.push(elapsed.as_micros().min(u128::from(u64::MAX)) as u64);
+            }
+            Ok(Response::Error { message }) => {
+                error_runs += 1;
+                notes.push(format!("daemon error: {message}"));
+            }
+            Ok(_) => {
+                error_runs += 1;
+                notes.push("unexpected response during bench".to_string());
+            }
+            Err(e) => {
+                error_runs += 1;
+                notes.push(format!("ipc error ({}): {e}", framing.as_str()));
+            }
+        }
+    }
+    let total_time = start_all.elapsed();
+
+    samples_us.sort_unstable();
+    let ok_runs = samples_us.len() as u32;
+    let summary = summarize_latencies(&samples_us, ok_runs, error_runs, total_time);
+    (samples_us, summary)
+}
+
+/// How much `value` differs from `base`, in percent of `base`.
+fn percent_change(base: u64, value: u64) -> Option<f64> {
+    (base > 0).then(|| (value as f64 - base as f64) / base as f64 * 100.0)
+}
+
 fn summarize_latencies(
     samples_sorted_us: &[u64],
     ok_runs: u32,
@@ -1498,6 +1635,12 @@ mod tests {
             state_allocated_bytes: 30_000,
             last_updated: 1_700_000_000,
             reconciling: true,
+            reconcile_progress: Some(42),
+            pending_watcher_events: 3,
+            journal_entries: 12,
+            journal_fsync: Some(JournalFsync::EveryBatch),
+            warmup_ms: Some(35),
+            index_cap: None,
         };
         let process = ProcessSnapshot {
             pid: 42,
@@ -1560,6 +1703,26 @@ mod tests {
                 connect_error: None,
             },
             index: Some(index),
+            status: None,
+            usage: Some(UsageSnapshot {
+                uptime_secs: 3_725,
+                process_cpu_ns: 4_000_000_000,
+                unattributed_cpu_ns: 250_000_000,
+                subsystems: vec![
+                    SubsystemUsage {
+                        subsystem: vicaya_core::accounting::Subsystem::Scan,
+                        cpu_ns: 3_000_000_000,
+                        read_bytes: Some(48 * 1024 * 1024),
+                        spans: 1,
+                    },
+                    SubsystemUsage {
+                        subsystem: vicaya_core::accounting::Subsystem::Query,
+                        cpu_ns: 750_000_000,
+                        read_bytes: None,
+                        spans: 1_200,
+                    },
+                ],
+            }),
             disk: DiskSnapshot {
                 config_path: "/tmp/vicaya/config.toml".to_string(),
                 index_dir: "/tmp/vicaya/index".to_string(),
@@ -1677,6 +1840,8 @@ TOTAL                             950.0M      2464K      2464K         0K      1
                 connect_error: Some("Daemon not running".to_string()),
             },
             index: None,
+            status: None,
+            usage: None,
             disk: DiskSnapshot {
                 config_path: "/tmp/vicaya/config.toml".to_string(),
                 index_dir: "/tmp/vicaya/index".to_string(),
@@ -1716,15 +1881,16 @@ TOTAL                             950.0M      2464K      2464K         0K      1
 
     #[test]
     fn formatting_and_summary_helpers_handle_edges() {
-        assert_eq!(fit_value_right("abc", 6), "   abc");
-        assert_eq!(fit_value_right("abcdef", 4), "...f");
-        assert_eq!(fit_value_right("abcdef", 3), "...");
-        assert_eq!(fit_value_right("abc", 0), "");
         assert_eq!(format_bytes_mb(512 * 1024), "0.5 MB");
         assert_eq!(format_bytes_mb(2 * 1024 * 1024 * 1024), "2.00 GB");
         assert_eq!(format_us(999), "999µs");
         assert_eq!(format_us(12_300), "12.3ms");
         assert_eq!(format_us(2_500_000), "2.5s");
+        assert_eq!(format_cpu_ns(999_999), "0 ms");
+        assert_eq!(format_cpu_ns(1_234_000_000), "1.23 s");
+        assert_eq!(format_uptime(59), "59s");
+        assert_eq!(format_uptime(125), "2m 05s");
+        assert_eq!(format_uptime(3_725), "1h 02m");
 
         let samples = vec![10, 20, 30, 40, 50];
         let summary = summarize_latencies(&samples, 5, 1, Duration::from_millis(250));
@@ -1808,6 +1974,7 @@ TOTAL                             950.0M      2464K      2464K         0K      1
             runs: 1,
             warmup: 0,
             limit: 5,
+            framing: vec![Framing::Json],
             vmmap_before_after: false,
         })
         .unwrap_err();
@@ -1821,6 +1988,7 @@ TOTAL                             950.0M      2464K      2464K         0K      1
             runs: 1,
             warmup: 0,
             limit: 5,
+            framing: vec![Framing::Json],
             vmmap_before_after: true,
         })
         .unwrap_err();
@@ -0,0 +1,273 @@
+//! `vicaya search --offline`: search `index.bin` in-process, without the daemon.
+//!
+//! For when the daemon cannot run (sandboxed CI, recovery). The last persisted
+//! snapshot is loaded and queried directly, so watcher updates journaled since
+//! it was written are missing; callers report the index age with the results.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use vicaya_core::ipc::{SearchOptions, SearchResult, SearchSort};
+use vicaya_core::{Config, Error, Result};
+use vicaya_index::query::{ranked_window, sort_results};
+use vicaya_index::{DirSizes, FileId, Query, QueryEngine, Scorer, ScorerRegistry};
+use vicaya_scanner::IndexSnapshot;
+
+/// Results of one offline search and where they came from.
+pub(crate) struct OfflineResults {
+    pub(crate) results: Vec<SearchResult>,
+    /// Near-miss names, when nothing matched.
+    pub(crate) suggestions: Vec<String>,
+    pub(crate) index_file: PathBuf,
+    /// Time since the index file was written, when the filesystem knows.
+    pub(crate) age: Option<Duration>,
+}
+
+pub(crate) fn search(config: &Config, options: &SearchOptions) -> Result<OfflineResults> {
+    let scorers = ScorerRegistry::from_config(&config.ranking)?;
+    let scorer = match options.scorer.as_deref() {
+        Some(name) => Some(scorers.get(name).ok_or_else(|| {
+            Error::Config(format!(
+                "unknown scorer '{name}' (expected one of: {})",
+                scorers.names().join(", ")
+            ))
+        })?),
+        None => scorers.get(vicaya_index::scorer::RELEVANCE_SCORER),
+    };
+    let index_file = config.index_path.join("index.bin");
+    let mut snapshot = IndexSnapshot::load(&index_file).map_err(|e| {
+        Error::Other(format!(
+            "Failed to load the index at {}: {e}; run `vicaya rebuild` first",
+            index_file.display()
+        ))
+    })?;
+    // Match what the daemon would find for terms that span directories.
+    snapshot.set_path_index(config.index_directory_paths);
+    snapshot
+        .file_table
+        .ensure_dir_lookup(&snapshot.string_arena);
+    let age = std::fs::metadata(&index_file)
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok());
+
+    let roots = config.effective_roots();
+    let (results, suggestions) = search_snapshot(&snapshot, options, scorer, config, &roots);
+    Ok(OfflineResults {
+        results,
+        suggestions,
+        index_file,
+        age,
+    })
+}
+
+/// Run `options` against `snapshot` the way the daemon would, minus Smriti
+/// boosts and the query budget.
+fn search_snapshot(
+    snapshot: &IndexSnapshot,
+    options: &SearchOptions,
+    scorer: Option<std::sync::Arc<dyn Scorer>>,
+    config: &Config,
+    roots: &[PathBuf],
+) -> (Vec<SearchResult>, Vec<String>) {
+    let ranking = &config.ranking;
+    if options.term.trim().is_empty() {
+        return (Vec::new(), Vec::new());
+    }
+    let expand = |scope: &Option<String>| {
+        scope
+            .as_deref()
+            .filter(|scope| !scope.trim().is_empty())
+            .map(|scope| vicaya_core::paths::expand_user_path(Path::new(scope)))
+    };
+    let engine = QueryEngine::new(
+        &snapshot.file_table,
+        &snapshot.string_arena,
+        &snapshot.trigram_index,
+    )
+    .with_path_index(snapshot.path_index.as_ref())
+    .with_roots(roots);
+    let query = Query {
+        term: options.term.clone(),
+        limit: ranked_window(options.offset, options.limit, options.sort),
+        scope: expand(&options.scope),
+        filter_scope: expand(&options.filters.scope),
+        budget: Default::default(),
+        attributes: options.filters.attributes.matcher(),
+        fuzzy: options.fuzzy,
+        explain: options.flags.explain,
+        scorer,
+        directory_bias: options.directories.bias(ranking.directory_weight),
+        min_score: if options.sort == SearchSort::Relevance {
+            ranking.min_score
+        } else {
+            0.0
+        },
+        max_depth: options.filters.max_depth,
+        hide_hidden: !options.filters.hidden.unwrap_or(config.show_hidden),
+    };
+    let (file_table, arena) = (&snapshot.file_table, &snapshot.string_arena);
+    let dir_sizes = std::cell::OnceCell::new();
+    let dir_sizes = || dir_sizes.get_or_init(|| DirSizes::build(file_table));
+    let mut results = match options.filters.dir_size {
+        Some(bound) => {
+            let file_ids: Vec<FileId> = file_table
+                .iter()
+                .filter(|(_, meta)| {
+                    meta.is_dir
+                        && !meta.is_tombstone()
+                        && bound.op.matches_u64(
+                            dir_sizes().of_entry(file_table, arena, meta).bytes,
+                            bound.value,
+                        )
+                })
+                .map(|(file_id, _)| file_id)
+                .collect();
+            engine.search_file_ids(&query, &file_ids)
+        }
+        None => engine.search(&query),
+    };
+    if results.is_empty() && options.offset == 0 {
+        return (Vec::new(), engine.suggest(&query, ranking.suggestions));
+    }
+    for result in results.iter_mut().filter(|r| r.is_dir) {
+        result.total_size = Some(
+            file_table
+                .find_dir(&result.path, arena)
+                .map(|dir| dir_sizes().of_dir(dir).bytes)
+                .unwrap_or(0),
+        );
+    }
+    sort_results(&mut results, options.sort);
+
+    let results = results
+        .into_iter()
+        .skip(options.offset)
+        .take(options.limit)
+        .map(|r| SearchResult {
+            path: r.path,
+            name: r.name,
+            score: r.score,
+            size: r.size,
+            mtime: r.mtime,
+            uid: r.uid,
+            gid: r.gid,
+            mode: r.mode,
+            indexed_at: r.indexed_at,
+            content_hash: r.content_hash.map(vicaya_core::content_hash::to_hex),
+            is_dir: r.is_dir,
+            total_size: r.total_size,
+            explain: r.explain,
+        })
+        .collect();
+    (results, Vec::new())
+}
+
+/// `42s`, `5m`, `3h`, `2d`.
+pub(crate) fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        secs @ 0..=59 => format!("{secs}s"),
+        secs @ 60..=3_599 => format!("{}m", secs / 60),
+        secs @ 3_600..=86_399 => format!("{}h", secs / 3_600),
+        secs => format!("{}d", secs / 86_400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vicaya_core::ipc::SearchFilters;
+    use vicaya_scanner::Scanner;
+
+    #[test]
+    fn offline_search_queries_the_saved_index_without_a_daemon() {
+        let root = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/query.rs"), "fn query() {}").unwrap();
+        std::fs::write(root.path().join("query_notes.md"), "").unwrap();
+        let config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            exclusions: Vec::new(),
+            respect_ignore_files: false,
+            index_path: index_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        Scanner::new(config.clone())
+            .scan()
+            .unwrap()
+            .save(&config.index_path.join("index.bin"))
+            .unwrap();
+
+        let found = search(&config, &SearchOptions::new("query", 10)).unwrap();
+        assert_eq!(found.index_file, index_dir.path().join("index.bin"));
+        assert!(found.age.is_some_and(|age| age < Duration::from_secs(60)));
+        let mut names: Vec<&str> = found.results.iter().map(|r| r.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["query.rs", "query_notes.md"]);
+
+        let typo = search(&config, &SearchOptions::new("qeury_notes", 10)).unwrap();
+        assert!(typo.results.is_empty());
+        assert_eq!(typo.suggestions, vec!["query_notes.md"]);
+
+        let scoped = SearchOptions {
+            filters: SearchFilters {
+                scope: Some(root.path().join("src").to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            sort: SearchSort::Name,
+            ..SearchOptions::new("query", 10)
+        };
+        let found = search(&config, &scoped).unwrap();
+        assert_eq!(found.results.len(), 1);
+        assert_eq!(found.results[0].name, "query.rs");
+
+        let shallow = SearchOptions {
+            filters: SearchFilters {
+                max_depth: Some(1),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("query", 10)
+        };
+        let found = search(&config, &shallow).unwrap();
+        assert_eq!(found.results.len(), 1);
+        assert_eq!(found.results[0].name, "query_notes.md");
+
+        // Directories weigh what they hold, and `dirsize:` picks them by it.
+        let sized = SearchOptions {
+            filters: SearchFilters {
+                dir_size: vicaya_core::query::parse_size_expr(">10"),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("src", 10)
+        };
+        let found = search(&config, &sized).unwrap();
+        assert_eq!(found.results.len(), 1);
+        assert!(found.results[0].is_dir);
+        assert_eq!(
+            found.results[0].total_size,
+            Some("fn query() {}".len() as u64)
+        );
+        let too_big = SearchOptions {
+            filters: SearchFilters {
+                dir_size: vicaya_core::query::parse_size_expr(">1kb"),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("src", 10)
+        };
+        assert!(search(&config, &too_big).unwrap().results.is_empty());
+
+        let missing = Config {
+            index_path: root.path().join("nope"),
+            ..config
+        };
+        assert!(search(&missing, &SearchOptions::new("query", 10)).is_err());
+    }
+
+    #[test]
+    fn ages_use_the_largest_whole_unit() {
+        assert_eq!(format_age(Duration::from_secs(42)), "42s");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 + 59)), "5m");
+        assert_eq!(format_age(Duration::from_secs(3 * 3_600)), "3h");
+        assert_eq!(format_age(Duration::from_secs(2 * 86_400 + 1)), "2d");
+    }
+}
@@ -0,0 +1,143 @@
+//! `vicaya search --preview N`: a few lines of each text result under its
+//! path, like ripgrep's context for terminal-only use.
+
+use owo_colors::OwoColorize;
+use vicaya_core::config::PreviewConfig;
+use vicaya_core::ipc::SearchResult;
+use vicaya_core::preview::{self, PreviewLine};
+
+/// How results are previewed, shared by every result of one search.
+pub(crate) struct PreviewOptions {
+    /// Lines shown per result.
+    lines: usize,
+    /// Lines containing this are shown first.
+    term: String,
+    /// Head of each file read to find them (`[preview] max_bytes`).
+    max_bytes: usize,
+    color: bool,
+}
+
+impl PreviewOptions {
+    pub(crate) fn new(lines: usize, term: &str, config: &PreviewConfig) -> Self {
+        Self {
+            lines,
+            term: term.trim().to_string(),
+            max_bytes: config.max_bytes.max(1),
+            color: crate::render::color_enabled(),
+        }
+    }
+}
+
+/// Print the preview of `result`, indented by `indent` columns.
+pub(crate) fn print(result: &SearchResult, options: &PreviewOptions, indent: usize) {
+    for line in render(result, options) {
+        println!("{:indent$}{line}", "");
+    }
+}
+
+/// Preview lines for `result`: nothing for directories, a note for binary or
+/// unreadable files, otherwise numbered lines with matches highlighted.
+fn render(result: &SearchResult, options: &PreviewOptions) -> Vec<String> {
+    if result.is_dir || options.lines == 0 {
+        return Vec::new();
+    }
+    let note = |text: String| {
+        if options.color {
+            text.dimmed().to_string()
+        } else {
+            text
+        }
+    };
+    let head = match preview::read_head(&result.path, options.max_bytes) {
+        Ok((head, _)) => head,
+        Err(e) => return vec![note(format!("(unreadable: {e})"))],
+    };
+    if preview::is_binary(&head) {
+        return vec![note("(binary file)".to_string())];
+    }
+
+    let (lines, _) = preview::pick_lines(
+        &String::from_utf8_lossy(&head),
+        &options.term,
+        options.lines,
+    );
+    let width = lines.last().map_or(1, |line| line.number.to_string().len());
+    lines
+        .iter()
+        .map(|line| format_line(line, width, &options.term, options.color))
+        .collect()
+}
+
+fn format_line(line: &PreviewLine, width: usize, term: &str, color: bool) -> String {
+    let number = format!("{:>width$}:", line.number);
+    if !color {
+        return format!("{number} {}", line.text);
+    }
+    let mut out = format!("{} ", number.green());
+    let mut last = 0;
+    for range in preview::match_ranges(&line.text, term) {
+        out.push_str(&line.text[last..range.start]);
+        let matched = &line.text[range.clone()];
+        out.push_str(&matched.bright_red().bold().to_string());
+        last = range.end;
+    }
+    out.push_str(&line.text[last..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &std::path::Path, is_dir: bool) -> SearchResult {
+        SearchResult {
+            path: path.to_string_lossy().to_string(),
+            name: String::new(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir,
+            total_size: None,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn render_numbers_matching_lines_and_notes_binary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let notes = dir.path().join("notes.md");
+        std::fs::write(&notes, "# Notes\nsee config.toml\nend\nconfig again\n").unwrap();
+        let binary = dir.path().join("blob.bin");
+        std::fs::write(&binary, b"\0\x01\x02").unwrap();
+        let options = PreviewOptions {
+            lines: 1,
+            term: "CONFIG".to_string(),
+            max_bytes: 1024,
+            color: false,
+        };
+
+        assert_eq!(
+            render(&result(&notes, false), &options),
+            ["2: see config.toml"]
+        );
+        assert_eq!(render(&result(&binary, false), &options), ["(binary file)"]);
+        assert!(render(&result(dir.path(), true), &options).is_empty());
+
+        let colored = format_line(
+            &PreviewLine {
+                number: 4,
+                text: "config again".to_string(),
+            },
+            2,
+            "config",
+            true,
+        );
+        assert!(colored.contains("config".bright_red().bold().to_string().as_str()));
+        assert!(colored.ends_with(" again"));
+    }
+}
@@ -0,0 +1,80 @@
+//! `vicaya reconcile`: start a reconcile by hand, or preview the schedule.
+
+use clap::Subcommand;
+use vicaya_client::Client;
+use vicaya_core::ipc::{Request, Response};
+use vicaya_core::schedule;
+use vicaya_core::{Error, Result};
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ReconcileAction {
+    /// Ask the running daemon to reconcile now; progress shows in `vicaya status`
+    Now,
+    /// List the next scheduled reconcile times from the config
+    Schedule {
+        /// How many upcoming runs to list
+        #[arg(short = 'n', long, default_value = "5")]
+        count: usize,
+    },
+}
+
+pub(crate) fn run(action: ReconcileAction) -> Result<()> {
+    match action {
+        ReconcileAction::Now => {
+            let mut client = Client::connect()?;
+            match client.request(&Request::Reconcile)? {
+                Response::ReconcileRequested {
+                    already_running: false,
+                } => println!("✓ Reconcile started; follow it with `vicaya status`"),
+                Response::ReconcileRequested {
+                    already_running: true,
+                } => println!("A reconcile is already running; follow it with `vicaya status`"),
+                Response::Error { message } => return Err(Error::Other(message)),
+                _ => return Err(Error::Other("Unexpected response from daemon".to_string())),
+            }
+        }
+        ReconcileAction::Schedule { count } => {
+            let config = crate::load_config()?;
+            let schedules = config.performance.reconcile_schedules();
+            for schedule in &schedules {
+                println!("{schedule}");
+            }
+            println!();
+            for run in upcoming(&schedules, chrono::Local::now().naive_local(), count) {
+                println!("  {}", run.format("%Y-%m-%d %H:%M (%a)"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The next `count` runs of any of `schedules` after `after`.
+fn upcoming(
+    schedules: &[schedule::CronSchedule],
+    after: chrono::NaiveDateTime,
+    count: usize,
+) -> Vec<chrono::NaiveDateTime> {
+    std::iter::successors(schedule::next_run(schedules, after), |&last| {
+        schedule::next_run(schedules, last)
+    })
+    .take(count)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upcoming_merges_schedules_in_order() {
+        let schedules = vec!["0 3 * * *".parse().unwrap(), "0 13 * * 6".parse().unwrap()];
+        // 2026-03-06 is a Friday.
+        let after =
+            chrono::NaiveDateTime::parse_from_str("2026-03-06 12:00", "%Y-%m-%d %H:%M").unwrap();
+        let runs: Vec<String> = upcoming(&schedules, after, 4)
+            .iter()
+            .map(|run| run.format("%a %H:%M").to_string())
+            .collect();
+        assert_eq!(runs, ["Sat 03:00", "Sat 13:00", "Sun 03:00", "Mon 03:00"]);
+    }
+}
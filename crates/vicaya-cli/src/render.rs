@@ -0,0 +1,460 @@
+//! Boxes and tables for human-readable output (`vicaya status`, `vicaya
+//! metrics`, `vicaya search`).
+//!
+//! Widths are counted in terminal columns with `unicode-width`, so wide and
+//! combining characters line up. Layouts shrink to the terminal when stdout
+//! is one, and color is only used then, unless `NO_COLOR` is set.
+
+use owo_colors::Style;
+use std::io::IsTerminal;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Inner width of a panel whose rows all fit; the status box's old width.
+const PANEL_WIDTH: usize = 53;
+/// Narrowest panel inner width, however small the terminal.
+const MIN_PANEL_WIDTH: usize = 24;
+/// Narrowest a table's flexible column gets before the row overflows.
+const MIN_FLEX_WIDTH: usize = 12;
+
+/// Whether output should be colored: stdout is a terminal and `NO_COLOR`
+/// is unset.
+pub(crate) fn color_enabled() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Columns of the terminal on stdout, or `None` when stdout is not one, so
+/// piped output is never cut. `COLUMNS` overrides the terminal's own size.
+pub(crate) fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    if let Some(columns) = std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse::<usize>().ok())
+        .filter(|&columns| columns > 0)
+    {
+        return Some(columns);
+    }
+    // SAFETY: `winsize` is plain data, and TIOCGWINSZ only writes to `size`,
+    // which outlives the call.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let rc = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (rc == 0 && size.ws_col > 0).then_some(size.ws_col as usize)
+}
+
+/// Terminal columns `text` takes.
+pub(crate) fn width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// `text` cut to at most `max` columns, keeping its end behind `...`, since
+/// paths and values differ most at the end.
+pub(crate) fn truncate_start(text: &str, max: usize) -> String {
+    if width(text) <= max {
+        return text.to_string();
+    }
+    if max <= 3 {
+        return ".".repeat(max);
+    }
+    let mut budget = max - 3;
+    let mut tail: Vec<char> = Vec::new();
+    for ch in text.chars().rev() {
+        let cols = ch.width().unwrap_or(0);
+        if cols > budget {
+            break;
+        }
+        budget -= cols;
+        tail.push(ch);
+    }
+    format!("...{}", tail.into_iter().rev().collect::<String>())
+}
+
+/// `text` cut to at most `max` columns, keeping its start.
+pub(crate) fn truncate_end(text: &str, max: usize) -> String {
+    if width(text) <= max {
+        return text.to_string();
+    }
+    if max <= 3 {
+        return ".".repeat(max);
+    }
+    let mut budget = max - 3;
+    let mut head = String::new();
+    for ch in text.chars() {
+        let cols = ch.width().unwrap_or(0);
+        if cols > budget {
+            break;
+        }
+        budget -= cols;
+        head.push(ch);
+    }
+    format!("{head}...")
+}
+
+/// `text` padded with spaces on the right to `cols` columns.
+pub(crate) fn pad_end(text: &str, cols: usize) -> String {
+    format!("{text}{}", " ".repeat(cols.saturating_sub(width(text))))
+}
+
+/// `text` padded with spaces on the left to `cols` columns.
+pub(crate) fn pad_start(text: &str, cols: usize) -> String {
+    format!("{}{text}", " ".repeat(cols.saturating_sub(width(text))))
+}
+
+/// How a piece of output is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Tone {
+    Plain,
+    Border,
+    Title,
+    Label,
+    /// Healthy values and headline counts.
+    Good,
+    Warn,
+    /// Errors and failed checks.
+    Bad,
+    /// Memory and other values worth watching.
+    Hot,
+    Info,
+    Count,
+    Neutral,
+}
+
+impl Tone {
+    fn style(self) -> Style {
+        let style = Style::new();
+        match self {
+            Tone::Plain => style,
+            Tone::Border => style.bright_blue(),
+            Tone::Title => style.bold().bright_white(),
+            Tone::Label => style.dimmed(),
+            Tone::Good => style.bright_green().bold(),
+            Tone::Warn => style.bright_yellow().bold(),
+            Tone::Bad => style.bright_red().bold(),
+            Tone::Hot => style.bright_magenta(),
+            Tone::Info => style.bright_cyan(),
+            Tone::Count => style.bright_yellow(),
+            Tone::Neutral => style.bright_white(),
+        }
+    }
+
+    pub(crate) fn paint(self, text: &str, color: bool) -> String {
+        if color && self != Tone::Plain {
+            self.style().style(text).to_string()
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+enum PanelRow {
+    Divider,
+    Heading(String),
+    Field {
+        label: String,
+        value: String,
+        tone: Tone,
+    },
+    Spans(Vec<(String, Tone)>),
+}
+
+/// A titled box of sections holding `label  value` fields, values aligned
+/// right. It is as wide as its widest row, at least [`PANEL_WIDTH`], and no
+/// wider than the terminal; values that do not fit lose their start.
+pub(crate) struct Panel {
+    rows: Vec<PanelRow>,
+}
+
+impl Panel {
+    pub(crate) fn new(title: &str) -> Self {
+        Self {
+            rows: vec![PanelRow::Heading(title.to_string())],
+        }
+    }
+
+    /// Start a section under a divider.
+    pub(crate) fn section(&mut self, heading: &str) {
+        self.rows.push(PanelRow::Divider);
+        self.rows.push(PanelRow::Heading(format!("  {heading}")));
+    }
+
+    /// A divider without a heading.
+    pub(crate) fn divider(&mut self) {
+        self.rows.push(PanelRow::Divider);
+    }
+
+    pub(crate) fn field(&mut self, label: &str, value: impl Into<String>, tone: Tone) {
+        self.rows.push(PanelRow::Field {
+            label: format!("    {label}"),
+            value: value.into(),
+            tone,
+        });
+    }
+
+    /// A free-form row of colored pieces, aligned left.
+    pub(crate) fn spans(&mut self, spans: Vec<(String, Tone)>) {
+        self.rows.push(PanelRow::Spans(spans));
+    }
+
+    pub(crate) fn print(&self) {
+        for line in self.render(terminal_width(), color_enabled()) {
+            println!("{line}");
+        }
+    }
+
+    /// The box as lines of at most `max_width` columns, when given.
+    pub(crate) fn render(&self, max_width: Option<usize>, color: bool) -> Vec<String> {
+        let natural = self
+            .rows
+            .iter()
+            .map(|row| match row {
+                PanelRow::Divider => 0,
+                PanelRow::Heading(text) => width(text),
+                PanelRow::Field { label, value, .. } => width(label) + 1 + width(value),
+                PanelRow::Spans(spans) => spans.iter().map(|(text, _)| width(text)).sum(),
+            })
+            .fold(PANEL_WIDTH, usize::max);
+        let inner = match max_width {
+            Some(max) => natural.min(max.saturating_sub(4)).max(MIN_PANEL_WIDTH),
+            None => natural,
+        };
+
+        let rule = "─".repeat(inner + 2);
+        let border = Tone::Border.paint("│", color);
+        let framed = |body: String| format!("{border} {body} {border}");
+
+        let mut lines = vec![Tone::Border.paint(&format!("╭{rule}╮"), color)];
+        for row in &self.rows {
+            lines.push(match row {
+                PanelRow::Divider => Tone::Border.paint(&format!("├{rule}┤"), color),
+                PanelRow::Heading(text) => {
+                    framed(Tone::Title.paint(&pad_end(&truncate_end(text, inner), inner), color))
+                }
+                PanelRow::Field { label, value, tone } => {
+                    // Keep at least half the row, and a space, for the value.
+                    let label = truncate_end(
+                        label,
+                        (inner / 2).max(inner.saturating_sub(width(value) + 1)),
+                    );
+                    let room = inner - width(&label);
+                    let value = truncate_start(value.trim(), room.saturating_sub(1));
+                    framed(format!(
+                        "{}{}",
+                        Tone::Label.paint(&label, color),
+                        tone.paint(&pad_start(&value, room), color)
+                    ))
+                }
+                PanelRow::Spans(spans) => {
+                    let mut room = inner;
+                    let mut body = String::new();
+                    for (text, tone) in spans {
+                        let text = truncate_end(text, room);
+                        room -= width(&text);
+                        body.push_str(&tone.paint(&text, color));
+                    }
+                    body.push_str(&" ".repeat(room));
+                    framed(body)
+                }
+            });
+        }
+        lines.push(Tone::Border.paint(&format!("╰{rule}╯"), color));
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Align {
+    Left,
+    Right,
+}
+
+/// One column of a [`Table`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Column {
+    pub(crate) header: &'static str,
+    pub(crate) align: Align,
+    /// Columns the cells take at least.
+    pub(crate) min_width: usize,
+    /// Takes the room the other columns leave and loses the start of cells
+    /// that do not fit. At most one column should be flexible.
+    pub(crate) flexible: bool,
+}
+
+impl Column {
+    pub(crate) fn left(header: &'static str, min_width: usize) -> Self {
+        Self {
+            header,
+            align: Align::Left,
+            min_width,
+            flexible: false,
+        }
+    }
+
+    pub(crate) fn right(header: &'static str, min_width: usize) -> Self {
+        Self {
+            align: Align::Right,
+            ..Self::left(header, min_width)
+        }
+    }
+
+    pub(crate) fn flexible(header: &'static str) -> Self {
+        Self {
+            flexible: true,
+            ..Self::left(header, 0)
+        }
+    }
+}
+
+/// Column widths for a set of rows, laid out one space apart.
+pub(crate) struct Table {
+    columns: Vec<Column>,
+    widths: Vec<usize>,
+    color: bool,
+}
+
+impl Table {
+    /// Size `columns` to fit `rows`. With `max_width`, a flexible column is
+    /// narrowed so rows fit in it.
+    pub(crate) fn new(
+        columns: Vec<Column>,
+        rows: &[Vec<String>],
+        max_width: Option<usize>,
+        color: bool,
+    ) -> Self {
+        let mut widths: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                rows.iter()
+                    .filter_map(|row| row.get(i))
+                    .map(|cell| width(cell))
+                    .fold(width(column.header).max(column.min_width), usize::max)
+            })
+            .collect();
+        if let (Some(max), Some(flex)) = (max_width, columns.iter().position(|c| c.flexible)) {
+            let others: usize = widths
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != flex)
+                .map(|(_, &cols)| cols + 1)
+                .sum();
+            let room = max.saturating_sub(others).max(MIN_FLEX_WIDTH);
+            widths[flex] = widths[flex].min(room);
+        }
+        Self {
+            columns,
+            widths,
+            color,
+        }
+    }
+
+    pub(crate) fn print_header(&self) {
+        println!("{}", self.header());
+    }
+
+    pub(crate) fn header(&self) -> String {
+        let headers: Vec<String> = self.columns.iter().map(|c| c.header.to_string()).collect();
+        Tone::Title.paint(&self.line(&headers), self.color)
+    }
+
+    pub(crate) fn row(&self, cells: &[String]) -> String {
+        self.line(cells)
+    }
+
+    /// Columns taken by the first `columns` columns and the spaces after
+    /// them, for lines indented under a row.
+    pub(crate) fn indent(&self, columns: usize) -> usize {
+        self.widths.iter().take(columns).map(|cols| cols + 1).sum()
+    }
+
+    fn line(&self, cells: &[String]) -> String {
+        let last = cells.len().min(self.columns.len()).saturating_sub(1);
+        let mut line = String::new();
+        for (i, (cell, column)) in cells.iter().zip(&self.columns).enumerate() {
+            let cols = self.widths[i];
+            let cell = if column.flexible {
+                truncate_start(cell, cols)
+            } else {
+                cell.clone()
+            };
+            if i > 0 {
+                line.push(' ');
+            }
+            match column.align {
+                // No trailing spaces after the last cell.
+                Align::Left if i == last => line.push_str(&cell),
+                Align::Left => line.push_str(&pad_end(&cell, cols)),
+                Align::Right => line.push_str(&pad_start(&cell, cols)),
+            }
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncation_counts_columns_not_bytes() {
+        assert_eq!(truncate_start("abc", 6), "abc");
+        assert_eq!(truncate_start("abcdef", 4), "...f");
+        assert_eq!(truncate_start("abcdef", 3), "...");
+        assert_eq!(truncate_start("/tmp/日本語.txt", 10), "...語.txt");
+        assert_eq!(truncate_start("/tmp/日本語.txt", 8), "....txt");
+        assert_eq!(truncate_end("Index Statistics", 8), "Index...");
+        assert_eq!(pad_start("日本", 6), "  日本");
+        assert_eq!(pad_end("é", 3), "é  ");
+    }
+
+    #[test]
+    fn panel_rows_line_up_with_wide_values_and_narrow_terminals() {
+        let mut panel = Panel::new("Vicaya — Index Status");
+        panel.spans(vec![
+            ("  ".to_string(), Tone::Plain),
+            ("●".to_string(), Tone::Good),
+            (" Daemon".to_string(), Tone::Plain),
+        ]);
+        panel.section("Index Statistics");
+        panel.field("Files indexed:", "1,234", Tone::Good);
+        panel.field("Profile:", "日本語プロファイル", Tone::Info);
+
+        let lines = panel.render(None, false);
+        assert!(lines.iter().all(|line| width(line) == PANEL_WIDTH + 4));
+        assert!(lines[5].ends_with("1,234 │"));
+        assert_eq!(lines.len(), 8);
+
+        let narrow = panel.render(Some(30), false);
+        assert!(narrow.iter().all(|line| width(line) == 30));
+        assert!(narrow[6].contains("..."));
+
+        let colored = panel.render(None, true);
+        assert!(colored[0].contains('\u{1b}'));
+    }
+
+    #[test]
+    fn table_fits_the_flexible_column_to_the_terminal() {
+        let columns = vec![
+            Column::left("RANK", 6),
+            Column::right("SCORE", 0),
+            Column::flexible("PATH"),
+        ];
+        let rows = vec![
+            vec![
+                "1".to_string(),
+                "0.93".to_string(),
+                "/Users/me/Projects/vicaya/src/main.rs".to_string(),
+            ],
+            vec!["2".to_string(), "0.5".to_string(), "/tmp/日本".to_string()],
+        ];
+
+        let wide = Table::new(columns.clone(), &rows, None, false);
+        assert_eq!(wide.header(), "RANK   SCORE PATH");
+        assert_eq!(wide.row(&rows[1]), "2        0.5 /tmp/日本");
+        assert_eq!(wide.indent(1), 7);
+
+        let narrow = Table::new(columns, &rows, Some(30), false);
+        let line = narrow.row(&rows[0]);
+        assert_eq!(width(&line), 30);
+        assert!(line.ends_with("...ya/src/main.rs"));
+    }
+}
@@ -0,0 +1,329 @@
+//! `vicaya compare-spotlight`: diff vicaya's results against Spotlight.
+//!
+//! Runs the same filename query through `mdfind -name` and the daemon, then
+//! explains every path Spotlight found that vicaya did not return.
+
+use clap::Args;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Instant;
+use vicaya_client::Client;
+use vicaya_core::ipc::{Response, SearchOptions};
+use vicaya_core::{Config, Error, Result};
+use vicaya_scanner::ExclusionReason;
+
+#[derive(Args, Debug)]
+pub(crate) struct CompareSpotlightArgs {
+    /// Filename query to run through both Spotlight and vicaya
+    pub(crate) query: String,
+
+    /// Maximum number of vicaya results to compare
+    #[arg(short, long, default_value = "1000")]
+    pub(crate) limit: usize,
+
+    /// Restrict both searches to this directory subtree
+    #[arg(long, value_name = "DIR")]
+    pub(crate) scope: Option<PathBuf>,
+
+    /// Output format (pretty, json)
+    #[arg(short, long, default_value = "pretty")]
+    pub(crate) format: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Comparison {
+    query: String,
+    spotlight: EngineRun,
+    vicaya: EngineRun,
+    both: usize,
+    only_spotlight: Vec<SpotlightOnly>,
+    only_vicaya: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct EngineRun {
+    results: usize,
+    elapsed_ms: u128,
+    /// Whether the engine stopped early (vicaya's limit or query budget).
+    truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct SpotlightOnly {
+    path: String,
+    #[serde(flatten)]
+    coverage: Coverage,
+}
+
+/// Why vicaya did not return a path Spotlight found.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+enum Coverage {
+    /// A configured exclusion pattern skips it.
+    Excluded { pattern: String },
+    /// It lives inside a bundle and `index_bundle_contents` is off.
+    InsideBundle { bundle: String },
+    /// It is part of a Time Machine backup or snapshot volume.
+    BackupVolume { volume: String },
+    /// It is in the Trash.
+    Trash { trash: String },
+    /// It lies deeper below its index root than `root_max_depth` allows.
+    BeyondMaxDepth { root: String, max_depth: usize },
+    /// A `.gitignore`-style rule skips it.
+    IgnoreFile,
+    /// It is not under any index root.
+    OutsideRoots,
+    /// It should be indexed; the index may be stale or it ranked below the limit.
+    NotReturned,
+}
+
+impl Coverage {
+    fn describe(&self) -> String {
+        match self {
+            Self::Excluded { pattern } => format!("excluded by `{pattern}`"),
+            Self::InsideBundle { bundle } => {
+                format!("inside bundle {bundle} (index_bundle_contents is off)")
+            }
+            Self::BackupVolume { volume } => {
+                format!("in backup volume {volume} (index_backup_volumes is off)")
+            }
+            Self::Trash { trash } => format!("in the trash {trash} (trash.index is off)"),
+            Self::BeyondMaxDepth { root, max_depth } => {
+                format!("deeper than root_max_depth {max_depth} of {root}")
+            }
+            Self::IgnoreFile => "ignored by .gitignore/.ignore".to_string(),
+            Self::OutsideRoots => "outside index_roots".to_string(),
+            Self::NotReturned => "indexable but not returned (stale index or limit)".to_string(),
+        }
+    }
+}
+
+pub(crate) fn run(args: CompareSpotlightArgs) -> Result<()> {
+    if !vicaya_core::daemon::is_running() {
+        return Err(Error::Other(
+            "Daemon is not running; start it with `vicaya daemon start`".to_string(),
+        ));
+    }
+    let config = crate::load_config()?;
+
+    let started = Instant::now();
+    let spotlight = run_mdfind(&args.query, args.scope.as_deref())?;
+    let spotlight_run = EngineRun {
+        results: spotlight.len(),
+        elapsed_ms: started.elapsed().as_millis(),
+        truncated: false,
+    };
+
+    let request = crate::build_search_request(
+        SearchOptions::new(args.query.as_str(), args.limit),
+        args.scope.as_deref(),
+    )?;
+    let started = Instant::now();
+    let response = Client::connect()?.request(&request)?;
+    let elapsed_ms = started.elapsed().as_millis();
+    let (vicaya, truncated) = match response {
+        Response::SearchResults {
+            results, truncated, ..
+        } => (
+            results.into_iter().map(|r| r.path).collect::<Vec<_>>(),
+            truncated,
+        ),
+        Response::Error { message } => return Err(Error::Other(message)),
+        _ => return Err(Error::Other("Unexpected response from daemon".to_string())),
+    };
+    let vicaya_run = EngineRun {
+        results: vicaya.len(),
+        elapsed_ms,
+        truncated: truncated || vicaya.len() >= args.limit,
+    };
+
+    let comparison = compare(
+        &args.query,
+        &config,
+        spotlight,
+        spotlight_run,
+        vicaya,
+        vicaya_run,
+    );
+    if args.format == "json" {
+        println!("{}", serde_json::to_string_pretty(&comparison).unwrap());
+    } else {
+        print_comparison(&comparison);
+    }
+    Ok(())
+}
+
+fn run_mdfind(query: &str, scope: Option<&Path>) -> Result<Vec<String>> {
+    let mut command = Command::new("mdfind");
+    command.arg("-name").arg(query);
+    if let Some(scope) = scope {
+        command
+            .arg("-onlyin")
+            .arg(vicaya_core::paths::resolve_scope_dir(scope)?);
+    }
+    let output = command.output().map_err(|e| {
+        if e.kind() == std::io::ErrorKind::NotFound {
+            Error::Other("`mdfind` not found; Spotlight comparison needs macOS".to_string())
+        } else {
+            Error::Other(format!("Failed to run mdfind: {e}"))
+        }
+    })?;
+    if !output.status.success() {
+        return Err(Error::Other(format!(
+            "mdfind failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+fn compare(
+    query: &str,
+    config: &Config,
+    spotlight: Vec<String>,
+    spotlight_run: EngineRun,
+    vicaya: Vec<String>,
+    vicaya_run: EngineRun,
+) -> Comparison {
+    let spotlight: BTreeSet<String> = spotlight.into_iter().collect();
+    let vicaya: BTreeSet<String> = vicaya.into_iter().collect();
+
+    let only_spotlight = spotlight
+        .difference(&vicaya)
+        .map(|path| SpotlightOnly {
+            path: path.clone(),
+            coverage: coverage(config, Path::new(path)),
+        })
+        .collect();
+
+    Comparison {
+        query: query.to_string(),
+        spotlight: spotlight_run,
+        vicaya: vicaya_run,
+        both: spotlight.intersection(&vicaya).count(),
+        only_spotlight,
+        only_vicaya: vicaya.difference(&spotlight).cloned().collect(),
+    }
+}
+
+fn coverage(config: &Config, path: &Path) -> Coverage {
+    let is_dir = path.is_dir();
+    match vicaya_scanner::exclusion_reason(config, path, is_dir) {
+        Some(ExclusionReason::Pattern(pattern)) => Coverage::Excluded { pattern },
+        Some(ExclusionReason::InsideBundle(bundle)) => Coverage::InsideBundle {
+            bundle: bundle.to_string_lossy().to_string(),
+        },
+        Some(ExclusionReason::BackupVolume(volume)) => Coverage::BackupVolume {
+            volume: volume.to_string_lossy().to_string(),
+        },
+        Some(ExclusionReason::Trash(trash)) => Coverage::Trash {
+            trash: trash.to_string_lossy().to_string(),
+        },
+        Some(ExclusionReason::BeyondMaxDepth { root, max_depth }) => Coverage::BeyondMaxDepth {
+            root: root.to_string_lossy().to_string(),
+            max_depth,
+        },
+        Some(ExclusionReason::IgnoreFile) => Coverage::IgnoreFile,
+        Some(ExclusionReason::OutsideRoots) => Coverage::OutsideRoots,
+        None => Coverage::NotReturned,
+    }
+}
+
+fn print_comparison(comparison: &Comparison) {
+    let engine_line = |name: &str, run: &EngineRun| {
+        let note = if run.truncated { " (truncated)" } else { "" };
+        println!(
+            "{name:<10} {:>6} results in {:>5} ms{note}",
+            run.results, run.elapsed_ms
+        );
+    };
+    println!("Query: {}", comparison.query);
+    engine_line("Spotlight", &comparison.spotlight);
+    engine_line("vicaya", &comparison.vicaya);
+    println!("Both:      {:>6}", comparison.both);
+
+    println!("\nOnly in Spotlight ({}):", comparison.only_spotlight.len());
+    for entry in &comparison.only_spotlight {
+        println!("  {}  — {}", entry.path, entry.coverage.describe());
+    }
+
+    println!("\nOnly in vicaya ({}):", comparison.only_vicaya.len());
+    for path in &comparison.only_vicaya {
+        println!("  {path}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(results: usize) -> EngineRun {
+        EngineRun {
+            results,
+            elapsed_ms: 0,
+            truncated: false,
+        }
+    }
+
+    #[test]
+    fn compare_explains_paths_only_spotlight_found() {
+        let root = tempfile::tempdir().unwrap();
+        let root_str = root.path().to_string_lossy().to_string();
+        let mut config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            exclusions: vec!["node_modules".to_string()],
+            respect_ignore_files: false,
+            ..Config::default()
+        };
+        config.index_path = root.path().join(".index");
+
+        let both = format!("{root_str}/src/report.md");
+        let excluded = format!("{root_str}/node_modules/report.js");
+        let missing = format!("{root_str}/docs/report.txt");
+        let only_vicaya = format!("{root_str}/report.rs");
+        let comparison = compare(
+            "report",
+            &config,
+            vec![
+                both.clone(),
+                excluded.clone(),
+                "/elsewhere/report.pdf".to_string(),
+                missing.clone(),
+            ],
+            run(4),
+            vec![both.clone(), only_vicaya.clone()],
+            run(2),
+        );
+
+        assert_eq!(comparison.both, 1);
+        assert_eq!(comparison.only_vicaya, vec![only_vicaya]);
+        let explained: Vec<(&str, &Coverage)> = comparison
+            .only_spotlight
+            .iter()
+            .map(|entry| (entry.path.as_str(), &entry.coverage))
+            .collect();
+        assert_eq!(
+            explained,
+            vec![
+                ("/elsewhere/report.pdf", &Coverage::OutsideRoots),
+                (missing.as_str(), &Coverage::NotReturned),
+                (
+                    excluded.as_str(),
+                    &Coverage::Excluded {
+                        pattern: "node_modules".to_string()
+                    }
+                ),
+            ]
+        );
+
+        let json = serde_json::to_value(&comparison.only_spotlight[2]).unwrap();
+        assert_eq!(json["reason"], "excluded");
+        assert_eq!(json["pattern"], "node_modules");
+    }
+}
@@ -0,0 +1,247 @@
+//! Versioned `vicaya status --format json` document.
+//!
+//! `vicaya status` and `vicaya metrics` both build their view of the daemon
+//! from this report, so the JSON shape is defined in one place. Bump
+//! [`STATUS_SCHEMA_VERSION`] when a field is renamed or removed; adding a
+//! field keeps the version.
+
+use serde::Serialize;
+use std::path::Path;
+use vicaya_core::config::{IndexProfile, JournalFsync, ResolvedScan};
+use vicaya_core::ipc::{self, BuildInfo, IndexCap, Response, RootStats};
+
+/// Version of the status JSON document.
+pub(crate) const STATUS_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct StatusReport {
+    pub(crate) schema_version: u32,
+    pub(crate) daemon: DaemonStatus,
+    pub(crate) index: IndexStatus,
+    pub(crate) watcher: WatcherStatus,
+    pub(crate) journal: JournalStatus,
+    pub(crate) reconcile: ReconcileStatus,
+    pub(crate) metrics: EfficiencyStatus,
+    /// Per-root breakdown from `Request::RootStats`; empty when the daemon
+    /// does not answer it.
+    pub(crate) roots: Vec<RootStats>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct DaemonStatus {
+    pub(crate) running: bool,
+    pub(crate) pid: i32,
+    pub(crate) profile: Option<String>,
+    pub(crate) build: BuildInfo,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct IndexStatus {
+    pub(crate) files: usize,
+    pub(crate) profile: IndexProfile,
+    pub(crate) trigrams: usize,
+    pub(crate) arena_bytes: usize,
+    pub(crate) index_allocated_bytes: u64,
+    pub(crate) state_allocated_bytes: u64,
+    pub(crate) last_updated: i64,
+    pub(crate) warmup_ms: Option<u64>,
+    pub(crate) cap: Option<IndexCap>,
+    /// On-disk `index.bin` path and size (0 when it has not been written).
+    pub(crate) file_path: String,
+    pub(crate) file_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct WatcherStatus {
+    /// Events received but not yet applied to the live index.
+    pub(crate) pending_events: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct JournalStatus {
+    pub(crate) entries: usize,
+    pub(crate) fsync: Option<JournalFsync>,
+    pub(crate) file_path: String,
+    pub(crate) file_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct ReconcileStatus {
+    pub(crate) running: bool,
+    pub(crate) progress: Option<u8>,
+    /// Scan profile, threads and throttle while `running`.
+    pub(crate) scan: Option<ResolvedScan>,
+    pub(crate) last: Option<i64>,
+    pub(crate) next: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct EfficiencyStatus {
+    pub(crate) bytes_per_file: usize,
+    pub(crate) trigrams_per_file: f64,
+    /// Arena plus on-disk index, per thousand indexed files.
+    pub(crate) mb_per_kfile: f64,
+}
+
+impl StatusReport {
+    /// Build the report from a `Response::Status`; `None` for any other
+    /// response. File sizes are read from `index_dir`.
+    pub(crate) fn from_response(response: Response, index_dir: &Path) -> Option<Self> {
+        let Response::Status(ipc::DaemonStatus {
+            pid,
+            build,
+            indexed_files,
+            trigram_count,
+            arena_size,
+            index_allocated_bytes,
+            state_allocated_bytes,
+            last_updated,
+            reconciling,
+            pending_watcher_events,
+            journal_entries,
+            reconcile_progress,
+            reconcile_scan,
+            warmup_ms,
+            journal_fsync,
+            index_cap,
+            last_reconcile,
+            next_reconcile,
+            index_profile,
+        }) = response
+        else {
+            return None;
+        };
+
+        let index_file = index_dir.join("index.bin");
+        let journal_file = index_dir.join("index.journal");
+        let file_bytes = |path: &Path| std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let index_file_bytes = file_bytes(&index_file);
+
+        let (bytes_per_file, trigrams_per_file, mb_per_kfile) = if indexed_files > 0 {
+            let total_mb = (arena_size as u64 + index_file_bytes) as f64 / 1_048_576.0;
+            (
+                arena_size / indexed_files,
+                trigram_count as f64 / indexed_files as f64,
+                total_mb / (indexed_files as f64 / 1000.0),
+            )
+        } else {
+            (0, 0.0, 0.0)
+        };
+
+        Some(Self {
+            schema_version: STATUS_SCHEMA_VERSION,
+            daemon: DaemonStatus {
+                running: true,
+                pid,
+                profile: vicaya_core::paths::profile(),
+                build,
+            },
+            index: IndexStatus {
+                files: indexed_files,
+                profile: index_profile,
+                trigrams: trigram_count,
+                arena_bytes: arena_size,
+                index_allocated_bytes,
+                state_allocated_bytes,
+                last_updated,
+                warmup_ms,
+                cap: index_cap,
+                file_path: index_file.to_string_lossy().to_string(),
+                file_bytes: index_file_bytes,
+            },
+            watcher: WatcherStatus {
+                pending_events: pending_watcher_events,
+            },
+            journal: JournalStatus {
+                entries: journal_entries,
+                fsync: journal_fsync,
+                file_bytes: file_bytes(&journal_file),
+                file_path: journal_file.to_string_lossy().to_string(),
+            },
+            reconcile: ReconcileStatus {
+                running: reconciling,
+                progress: reconcile_progress,
+                scan: reconcile_scan,
+                last: last_reconcile,
+                next: next_reconcile,
+            },
+            metrics: EfficiencyStatus {
+                bytes_per_file,
+                trigrams_per_file,
+                mb_per_kfile,
+            },
+            roots: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_response() -> Response {
+        Response::Status(ipc::DaemonStatus {
+            pid: 42,
+            build: BuildInfo {
+                version: "0.0.0-test".to_string(),
+                ..BuildInfo::default()
+            },
+            indexed_files: 1_000,
+            trigram_count: 4_000,
+            arena_size: 64_000,
+            index_allocated_bytes: 1,
+            state_allocated_bytes: 2,
+            last_updated: 1_700_000_000,
+            reconciling: true,
+            pending_watcher_events: 3,
+            journal_entries: 7,
+            reconcile_progress: Some(40),
+            reconcile_scan: Some(ResolvedScan {
+                profile: vicaya_core::config::ScanProfile::Reconcile,
+                threads: 2,
+                throttle_ms: 0,
+            }),
+            warmup_ms: Some(12),
+            journal_fsync: Some(JournalFsync::EveryBatch),
+            index_cap: None,
+            last_reconcile: Some(1_699_999_000),
+            next_reconcile: Some(1_700_003_600),
+            index_profile: IndexProfile::Full,
+        })
+    }
+
+    #[test]
+    fn status_json_is_versioned_and_carries_every_section() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("index.bin"), vec![0u8; 2_048]).unwrap();
+        std::fs::write(dir.path().join("index.journal"), vec![0u8; 128]).unwrap();
+
+        let report = StatusReport::from_response(status_response(), dir.path()).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+
+        assert_eq!(json["schema_version"], STATUS_SCHEMA_VERSION);
+        assert_eq!(json["daemon"]["running"], true);
+        assert_eq!(json["daemon"]["pid"], 42);
+        assert_eq!(json["daemon"]["build"]["version"], "0.0.0-test");
+        assert_eq!(json["index"]["files"], 1_000);
+        assert_eq!(json["index"]["file_bytes"], 2_048);
+        assert_eq!(json["watcher"]["pending_events"], 3);
+        assert_eq!(json["journal"]["entries"], 7);
+        assert_eq!(json["journal"]["file_bytes"], 128);
+        assert_eq!(json["reconcile"]["running"], true);
+        assert_eq!(json["reconcile"]["progress"], 40);
+        assert_eq!(json["reconcile"]["scan"]["profile"], "reconcile");
+        assert_eq!(json["reconcile"]["scan"]["threads"], 2);
+        assert_eq!(json["reconcile"]["next"], 1_700_003_600);
+        assert_eq!(json["metrics"]["bytes_per_file"], 64);
+    }
+
+    #[test]
+    fn other_responses_do_not_build_a_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let response = Response::Error {
+            message: "nope".to_string(),
+        };
+        assert!(StatusReport::from_response(response, dir.path()).is_none());
+    }
+}
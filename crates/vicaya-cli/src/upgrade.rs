@@ -91,8 +91,9 @@ struct GitHubAsset {
 /// Check for or install the latest vicaya release.
 ///
 /// The updater fetches release metadata, verifies the release archive checksum,
-/// stops the daemon before replacing binaries, and restores or restarts the
-/// daemon according to the previous daemon state and CLI flags.
+/// replaces the binaries, and hands a running daemon's index to the new daemon
+/// binary (falling back to a restart), or leaves it stopped with
+/// `--no-restart-daemon`.
 pub fn run(args: UpgradeArgs) -> Result<()> {
     let current = current_version()?;
     let release = fetch_latest_release()?;
@@ -144,7 +145,11 @@ pub fn run(args: UpgradeArgs) -> Result<()> {
     unpack_tarball(&bundle, unpacked.path())?;
     verify_bundle(unpacked.path())?;
 
-    if daemon_was_running {
+    // A daemon that will keep running stays up while the binaries are
+    // replaced (they are renamed into place) and then hands its index to the
+    // new binary, so searches keep working without a full rescan.
+    let hand_off = daemon_was_running && !args.no_restart_daemon;
+    if daemon_was_running && !hand_off {
         println!(
             "{} Stopping daemon before replacing binaries...",
             amber("!")
@@ -153,14 +158,28 @@ pub fn run(args: UpgradeArgs) -> Result<()> {
     }
 
     if let Err(err) = install_bundle(unpacked.path(), &install_dir) {
-        restart_daemon_after_failed_install(daemon_was_running);
+        // A daemon kept running for the handoff is still serving.
+        restart_daemon_after_failed_install(daemon_was_running && !hand_off);
         return Err(err);
     }
 
-    if daemon_was_running && !args.no_restart_daemon {
-        println!("{} Restarting daemon...", amber("!"));
-        let pid = vicaya_core::daemon::start_daemon()?;
-        println!("{} Daemon restarted (PID: {pid}).", green("✓"));
+    if hand_off {
+        println!("{} Handing the index to the new daemon...", amber("!"));
+        let daemon = install_dir.join("vicaya-daemon");
+        match vicaya_core::daemon::upgrade_daemon(Some(&daemon)) {
+            Ok(_) => println!("{} Daemon upgraded in place.", green("✓")),
+            Err(err) => {
+                println!(
+                    "{} In-place upgrade failed ({err}); restarting daemon...",
+                    amber("!")
+                );
+                if vicaya_core::daemon::is_running() {
+                    vicaya_core::daemon::stop_daemon()?;
+                }
+                let pid = vicaya_core::daemon::start_daemon()?;
+                println!("{} Daemon restarted (PID: {pid}).", green("✓"));
+            }
+        }
     } else if daemon_was_running {
         println!(
             "{} Daemon left stopped because --no-restart-daemon was set.",
@@ -541,7 +560,7 @@ fn cache_path() -> PathBuf {
         return path.join("update-check.json");
     }
 
-    vicaya_core::paths::vicaya_dir().join("update-check.json")
+    vicaya_core::paths::base_dir().join("update-check.json")
 }
 
 #[cfg(test)]
@@ -3,7 +3,9 @@ use std::process::Command;
 use std::time::{Duration, Instant};
 
 use tempfile::TempDir;
+use vicaya_core::audit::{AuditOutcome, AuditRecord};
 use vicaya_core::config::PerformanceConfig;
+use vicaya_core::crash::CrashReport;
 use vicaya_core::Config;
 
 struct DaemonGuard {
@@ -64,14 +66,48 @@ fn write_config(vicaya_dir: &Path, root: &Path) {
         index_roots: vec![root.to_path_buf()],
         exclusions: vec!["target".to_string(), "*.profraw".to_string()],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.join("index"),
         max_memory_mb: 64,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
     std::fs::create_dir_all(vicaya_dir).unwrap();
     config.save(&vicaya_dir.join("config.toml")).unwrap();
@@ -225,6 +261,238 @@ fn init_version_and_no_command_are_stable() {
     assert!(forced.contains("Configuration initialized successfully"));
 }
 
+#[test]
+fn ranking_report_aggregates_telemetry_and_purge_requires_confirmation() {
+    let vicaya_bin = PathBuf::from(env!("CARGO_BIN_EXE_vicaya"));
+    let daemon_bin = daemon_bin_for(&vicaya_bin);
+    let vicaya_dir = TempDir::new().unwrap();
+    let log = vicaya_dir.path().join("ranking-telemetry.jsonl");
+    let args = ["metrics", "ranking-report", "--format", "json"];
+
+    let empty = run_vicaya(&vicaya_bin, vicaya_dir.path(), &daemon_bin, &args);
+    let empty: serde_json::Value = serde_json::from_str(&empty).unwrap();
+    assert_eq!(empty["events"], 0);
+
+    write_file(
+        &log,
+        concat!(
+            r#"{"at":1,"query":"main","rank":1,"result_count":9,"time_to_select_ms":300,"action":"open"}"#,
+            "\n",
+            r#"{"at":2,"query":"lib","rank":2,"result_count":9,"time_to_select_ms":900,"action":"copy"}"#,
+            "\n",
+        ),
+    );
+    let report = run_vicaya(&vicaya_bin, vicaya_dir.path(), &daemon_bin, &args);
+    let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+    assert_eq!(report["events"], 2);
+    assert_eq!(report["mrr"], 0.75);
+    assert_eq!(report["rank_distribution"][1]["label"], "2");
+    assert_eq!(report["rank_distribution"][1]["count"], 1);
+
+    let refused = Command::new(&vicaya_bin)
+        .env("VICAYA_DIR", vicaya_dir.path())
+        .env("VICAYA_NO_UPDATE_CHECK", "1")
+        .args(["metrics", "ranking-purge"])
+        .output()
+        .unwrap();
+    assert!(!refused.status.success());
+    assert!(log.exists());
+
+    let purged = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["metrics", "ranking-purge", "--yes"],
+    );
+    assert!(purged.contains("Ranking telemetry purged"));
+    assert!(!log.exists());
+}
+
+#[test]
+fn bookmarks_are_added_listed_with_suggestions_and_removed() {
+    let vicaya_bin = PathBuf::from(env!("CARGO_BIN_EXE_vicaya"));
+    let daemon_bin = daemon_bin_for(&vicaya_bin);
+    let vicaya_dir = TempDir::new().unwrap();
+    let corpus = TempDir::new().unwrap();
+    let project = corpus.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let project = std::fs::canonicalize(project).unwrap();
+    let project_str = project.to_string_lossy().to_string();
+
+    let mut smriti = vicaya_core::smriti::SmritiStore::default();
+    for now in 0..4 {
+        smriti.record(
+            "/work/frequent".to_string(),
+            String::new(),
+            vicaya_core::smriti::SmritiAction::Enter,
+            now,
+        );
+    }
+    smriti
+        .save_atomic(&vicaya_dir.path().join("smriti.json"))
+        .unwrap();
+
+    let added = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["bookmark", "add", &project_str, "--name", "proj"],
+    );
+    assert!(added.contains("as 'proj'"), "{added}");
+    assert!(added.contains("Press 1"), "{added}");
+
+    let listing = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["bookmark", "list", "--format", "json"],
+    );
+    let listing: serde_json::Value = serde_json::from_str(&listing).unwrap();
+    assert_eq!(listing["bookmarks"][0]["name"], "proj");
+    assert_eq!(listing["bookmarks"][0]["path"], project_str.as_str());
+    assert_eq!(listing["suggestions"][0]["path"], "/work/frequent");
+    assert_eq!(listing["suggestions"][0]["enter_count"], 4);
+
+    let missing = Command::new(&vicaya_bin)
+        .env("VICAYA_DIR", vicaya_dir.path())
+        .env("VICAYA_NO_UPDATE_CHECK", "1")
+        .args(["bookmark", "rm", "nope"])
+        .output()
+        .unwrap();
+    assert!(!missing.status.success());
+
+    let removed = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["bookmark", "rm", "1"],
+    );
+    assert!(removed.contains("Removed bookmark 'proj'"), "{removed}");
+    let table = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["bookmark", "list"],
+    );
+    assert!(table.contains("No bookmarks yet"), "{table}");
+    assert!(table.contains("/work/frequent (entered 4×)"), "{table}");
+}
+
+#[test]
+fn daemon_status_points_at_the_latest_crash_report() {
+    let vicaya_bin = PathBuf::from(env!("CARGO_BIN_EXE_vicaya"));
+    let daemon_bin = daemon_bin_for(&vicaya_bin);
+    let vicaya_dir = TempDir::new().unwrap();
+
+    let status = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["daemon", "status"],
+    );
+    assert!(!status.contains("Last crash"), "{status}");
+
+    let report = CrashReport {
+        crashed_at: 1_700_000_000,
+        pid: 4242,
+        version: "vicaya-daemon 1.2.0".to_string(),
+        thread: "watcher".to_string(),
+        message: "index out of bounds".to_string(),
+        location: Some("crates/vicaya-daemon/src/main.rs:10:5".to_string()),
+        backtrace: String::new(),
+        recent_requests: Vec::new(),
+        index: None,
+    };
+    let path = report.write(&vicaya_dir.path().join("crashes")).unwrap();
+
+    let status = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["daemon", "status"],
+    );
+    assert!(status.contains("Daemon is not running"), "{status}");
+    assert!(status.contains("Last crash:"), "{status}");
+    assert!(status.contains("(PID 4242)"), "{status}");
+    assert!(status.contains("Thread: watcher"), "{status}");
+    assert!(
+        status.contains("Panic: index out of bounds at crates/vicaya-daemon/src/main.rs:10:5"),
+        "{status}"
+    );
+    assert!(status.contains(&path.display().to_string()), "{status}");
+}
+
+#[test]
+fn logs_audit_lists_the_newest_privileged_requests() {
+    let vicaya_bin = PathBuf::from(env!("CARGO_BIN_EXE_vicaya"));
+    let daemon_bin = daemon_bin_for(&vicaya_bin);
+    let vicaya_dir = TempDir::new().unwrap();
+
+    let empty = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["logs", "audit"],
+    );
+    assert!(
+        empty.contains("No privileged requests recorded yet."),
+        "{empty}"
+    );
+
+    let log = vicaya_dir.path().join("audit.jsonl");
+    let rebuild = AuditRecord {
+        at: 1_700_000_000,
+        peer_pid: Some(4242),
+        peer_uid: Some(501),
+        request: "rebuild".to_string(),
+        detail: Some("dry_run".to_string()),
+        outcome: AuditOutcome::Ok,
+        message: None,
+    };
+    let upgrade = AuditRecord {
+        at: 1_700_000_060,
+        peer_pid: None,
+        peer_uid: None,
+        request: "upgrade".to_string(),
+        detail: Some("/tmp/vicaya-daemon".to_string()),
+        outcome: AuditOutcome::Error,
+        message: Some("not executable".to_string()),
+    };
+    vicaya_core::audit::append(&log, &rebuild).unwrap();
+    vicaya_core::audit::append(&log, &upgrade).unwrap();
+
+    let table = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["logs", "audit"],
+    );
+    let lines: Vec<_> = table.lines().collect();
+    assert_eq!(lines.len(), 3, "{table}");
+    assert!(lines[0].starts_with("TIME"), "{table}");
+    assert!(
+        lines[1].contains("4242") && lines[1].contains("rebuild"),
+        "{table}"
+    );
+    assert!(
+        lines[1].contains("ok") && lines[1].ends_with("dry_run"),
+        "{table}"
+    );
+    assert!(
+        lines[2].contains("error") && lines[2].ends_with("/tmp/vicaya-daemon (not executable)"),
+        "{table}"
+    );
+
+    let json = run_vicaya(
+        &vicaya_bin,
+        vicaya_dir.path(),
+        &daemon_bin,
+        &["logs", "audit", "-n", "1", "--format", "json"],
+    );
+    let records: Vec<AuditRecord> = serde_json::from_str(&json).unwrap();
+    assert_eq!(records, vec![upgrade]);
+}
+
 #[test]
 fn daemon_backed_status_metrics_rebuild_and_search_formats_work_together() {
     let vicaya_bin = PathBuf::from(env!("CARGO_BIN_EXE_vicaya"));
@@ -298,8 +566,42 @@ fn daemon_backed_status_metrics_rebuild_and_search_formats_work_together() {
         Some(cargo.to_string_lossy().as_ref())
     );
 
+    // Index-assisted grep: candidates come from the index, so the excluded
+    // target/ directory is never read, and --ext prunes by extension.
+    let repo_scope = repo.to_string_lossy().to_string();
+    let grep = |extra: &[&str]| -> serde_json::Value {
+        let mut args = vec![
+            "grep",
+            "--engine=index",
+            "--format=json",
+            "--scope",
+            repo_scope.as_str(),
+        ];
+        args.extend_from_slice(extra);
+        serde_json::from_str(&run_vicaya(
+            &vicaya_bin,
+            vicaya_dir.path(),
+            &daemon_bin,
+            &args,
+        ))
+        .unwrap()
+    };
+    let demo = grep(&["demo"]);
+    assert_eq!(demo["engine"], "index");
+    assert_eq!(demo["hits"].as_array().unwrap().len(), 2);
+    assert!(grep(&["ignored"])["hits"].as_array().unwrap().is_empty());
+    let toml_only = grep(&["demo", "--ext=toml"]);
+    assert_eq!(
+        toml_only["hits"][0]["path"].as_str(),
+        Some(cargo.to_string_lossy().as_ref())
+    );
+    assert_eq!(toml_only["hits"][0]["line_number"], 2);
+    assert_eq!(toml_only["hits"][0]["column"], 9);
+    assert_eq!(toml_only["hits"].as_array().unwrap().len(), 1);
+
     let status_json = wait_for_status_json(&vicaya_bin, vicaya_dir.path(), &daemon_bin);
     assert_eq!(status_json["daemon"]["running"], true);
+    assert_eq!(status_json["schema_version"], 1);
     assert!(status_json["index"]["files"].as_u64().unwrap() >= 2);
 
     let pretty_status = run_vicaya(&vicaya_bin, vicaya_dir.path(), &daemon_bin, &["status"]);
@@ -87,14 +87,48 @@ fn cli_search_returns_json_results() {
         index_roots: vec![corpus_root.path().to_path_buf()],
         exclusions: Vec::new(),
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 64,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
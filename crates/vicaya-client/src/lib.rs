@@ -0,0 +1,633 @@
+//! vicaya-client: typed Rust client for the vicaya daemon.
+//!
+//! [`Client`] owns a connection to the daemon's socket and turns
+//! [`Request`]s into [`Response`]s, reconnecting and retrying as its
+//! [`ClientOptions`] allow. Typed methods ([`Client::search`],
+//! [`Client::status`], ...) unwrap the expected response and turn anything
+//! else into an error; [`Client::request`] sends any request as is.
+//! [`Client::subscribe`] opens a [`Subscription`] on a connection of its own,
+//! on which the daemon pushes what watcher updates change about a search.
+//!
+//! The CLI and the TUI both talk to the daemon through this crate.
+//!
+//! ```no_run
+//! use vicaya_client::Client;
+//! use vicaya_core::ipc::SearchOptions;
+//!
+//! let mut client = Client::connect()?;
+//! for result in client.search(SearchOptions::new("main.rs", 10))?.results {
+//!     println!("{}", result.path);
+//! }
+//! # Ok::<(), vicaya_core::Error>(())
+//! ```
+
+mod subscription;
+
+pub use subscription::{IndexChange, Subscription};
+pub use vicaya_core::ipc::DaemonStatus;
+
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use vicaya_core::ipc::{
+    DeletedEntry, Framing, Request, Response, RootStats, SearchOptions, SearchResult,
+};
+use vicaya_core::smriti::{SmritiAction, SmritiEntry};
+use vicaya_core::{Error, Result};
+
+/// Pause before reconnecting for another attempt.
+const RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// How a [`Client`] connects and retries.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Daemon socket; [`vicaya_core::ipc::socket_path`] by default.
+    pub socket_path: PathBuf,
+    /// Read and write timeout of the connection; `None` waits forever.
+    pub timeout: Option<Duration>,
+    /// Tries per request. Each retry reconnects first, so a client survives
+    /// a daemon restart between requests. Non-idempotent requests (rebuild,
+    /// trash) may run twice when a reply is lost, so keep this at 1 for them.
+    pub attempts: usize,
+    /// Wire encoding; MessagePack is negotiated on every new connection.
+    pub framing: Framing,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            socket_path: vicaya_core::ipc::socket_path(),
+            timeout: None,
+            attempts: 1,
+            framing: Framing::Json,
+        }
+    }
+}
+
+/// Connection to the vicaya daemon.
+pub struct Client {
+    options: ClientOptions,
+    stream: Option<UnixStream>,
+    /// A `Request::Framing` was sent but its reply not yet read. It is read
+    /// with the first response, so switching costs no extra round trip.
+    awaiting_framing: bool,
+}
+
+/// Answer to [`Client::search`].
+#[derive(Debug, Clone, Default)]
+pub struct SearchReply {
+    pub results: Vec<SearchResult>,
+    /// Whether the query budget cut the search short.
+    pub truncated: bool,
+    /// "Did you mean" terms for a search with few results.
+    pub suggestions: Vec<String>,
+    /// Matches outside a scope that had none, listed after `results`.
+    pub outside_scope: Vec<SearchResult>,
+}
+
+impl Client {
+    /// Connect to the daemon, failing when it is not running.
+    pub fn connect() -> Result<Self> {
+        Self::connect_with_options(ClientOptions::default())
+    }
+
+    /// Connect to the daemon and switch the connection to `framing`.
+    pub fn connect_with(framing: Framing) -> Result<Self> {
+        Self::connect_with_options(ClientOptions {
+            framing,
+            ..ClientOptions::default()
+        })
+    }
+
+    /// Connect with `options`, failing when the daemon is not running.
+    pub fn connect_with_options(options: ClientOptions) -> Result<Self> {
+        let mut client = Self {
+            options,
+            stream: None,
+            awaiting_framing: false,
+        };
+        client.open()?;
+        Ok(client)
+    }
+
+    /// A client that tries to connect now but, unlike
+    /// [`connect_with_options`](Self::connect_with_options), also works
+    /// while the daemon is down: each request connects again first.
+    pub fn new(options: ClientOptions) -> Self {
+        let mut client = Self {
+            options,
+            stream: None,
+            awaiting_framing: false,
+        };
+        let _ = client.open();
+        client
+    }
+
+    /// Whether the client holds a connection.
+    pub fn is_connected(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Drop the connection and try to open a new one.
+    pub fn reconnect(&mut self) {
+        self.stream = None;
+        let _ = self.open();
+    }
+
+    /// Send a request and receive a response, retrying as
+    /// [`ClientOptions::attempts`] allows.
+    pub fn request(&mut self, request: &Request) -> Result<Response> {
+        let attempts = self.options.attempts.max(1);
+        let mut attempt = 1;
+        loop {
+            match self.request_once(request) {
+                Ok(response) => return Ok(response),
+                Err(error) => {
+                    self.stream = None;
+                    if attempt >= attempts {
+                        return Err(error);
+                    }
+                }
+            }
+            attempt += 1;
+            std::thread::sleep(RETRY_DELAY);
+            let _ = self.open();
+        }
+    }
+
+    /// Search the index.
+    pub fn search(&mut self, options: SearchOptions) -> Result<SearchReply> {
+        match self.request(&Request::Search(options))? {
+            Response::SearchResults {
+                results,
+                truncated,
+                suggestions,
+                outside_scope,
+            } => Ok(SearchReply {
+                results,
+                truncated,
+                suggestions,
+                outside_scope,
+            }),
+            other => Err(unexpected("Search", other)),
+        }
+    }
+
+    /// Daemon and index status.
+    pub fn status(&mut self) -> Result<DaemonStatus> {
+        match self.request(&Request::Status)? {
+            Response::Status(status) => Ok(status),
+            other => Err(unexpected("Status", other)),
+        }
+    }
+
+    /// Rebuild the index; returns the number of files indexed.
+    pub fn rebuild(&mut self, dry_run: bool) -> Result<usize> {
+        match self.request(&Request::Rebuild { dry_run })? {
+            Response::RebuildComplete { files_indexed } => Ok(files_indexed),
+            other => Err(unexpected("Rebuild", other)),
+        }
+    }
+
+    /// Follow `options` on a connection of its own; see [`Subscription`].
+    pub fn subscribe(&self, options: SearchOptions) -> Result<Subscription> {
+        Subscription::open(&self.options, options)
+    }
+
+    /// Record a Smriti usage event.
+    pub fn record_smriti(&mut self, path: &str, query: &str, action: SmritiAction) -> Result<()> {
+        let request = Request::SmritiRecord {
+            path: path.to_string(),
+            query: query.to_string(),
+            action,
+        };
+        match self.request(&request)? {
+            Response::Ok => Ok(()),
+            other => Err(unexpected("Smriti", other)),
+        }
+    }
+
+    /// Report that searches were scoped to the directory `path`.
+    pub fn scope_used(&mut self, path: &str) -> Result<()> {
+        let request = Request::ScopeUsed {
+            path: path.to_string(),
+        };
+        match self.request(&request)? {
+            Response::Ok => Ok(()),
+            other => Err(unexpected("Scope", other)),
+        }
+    }
+
+    /// List Smriti usage entries.
+    pub fn smriti_list(
+        &mut self,
+        query: Option<&str>,
+        limit: usize,
+        filter_scope: Option<&Path>,
+    ) -> Result<Vec<SmritiEntry>> {
+        let request = Request::SmritiList {
+            query: query.map(str::to_string),
+            limit,
+            filter_scope: filter_scope.map(|p| p.to_string_lossy().to_string()),
+        };
+        match self.request(&request)? {
+            Response::SmritiEntries { entries } => Ok(entries),
+            other => Err(unexpected("Smriti", other)),
+        }
+    }
+
+    /// Forget one Smriti path.
+    pub fn smriti_forget(&mut self, path: &str) -> Result<()> {
+        let request = Request::SmritiForget {
+            path: path.to_string(),
+        };
+        match self.request(&request)? {
+            Response::Ok | Response::SmritiForgot { .. } => Ok(()),
+            other => Err(unexpected("Smriti", other)),
+        }
+    }
+
+    /// Clear all Smriti usage memory.
+    pub fn smriti_clear(&mut self) -> Result<()> {
+        match self.request(&Request::SmritiClear)? {
+            Response::Ok => Ok(()),
+            other => Err(unexpected("Smriti", other)),
+        }
+    }
+
+    /// Entries recently moved to the Trash, newest first.
+    pub fn recently_deleted(
+        &mut self,
+        query: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<DeletedEntry>> {
+        let request = Request::RecentlyDeleted {
+            query: query.map(str::to_string),
+            limit,
+        };
+        match self.request(&request)? {
+            Response::RecentlyDeleted { entries } => Ok(entries),
+            other => Err(unexpected("Trash", other)),
+        }
+    }
+
+    /// Per-root file counts, sizes and last-scan times.
+    pub fn root_stats(&mut self) -> Result<Vec<RootStats>> {
+        match self.request(&Request::RootStats)? {
+            Response::RootStats { roots } => Ok(roots),
+            other => Err(unexpected("Root stats", other)),
+        }
+    }
+
+    /// Used and indexed file names starting with `prefix`, best first.
+    pub fn suggest(&mut self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        let request = Request::Suggest {
+            prefix: prefix.to_string(),
+            limit,
+        };
+        match self.request(&request)? {
+            Response::Suggestions { names } => Ok(names),
+            other => Err(unexpected("Suggest", other)),
+        }
+    }
+
+    /// Report a path that no longer exists; returns whether the daemon
+    /// dropped an indexed entry for it.
+    pub fn report_missing(&mut self, path: &str) -> Result<bool> {
+        let request = Request::ReportMissing {
+            path: path.to_string(),
+        };
+        match self.request(&request)? {
+            Response::MissingReported { removed } => Ok(removed),
+            other => Err(unexpected("Report", other)),
+        }
+    }
+
+    /// Open a connection and start the framing switch on it.
+    fn open(&mut self) -> Result<()> {
+        let stream = connect_socket(&self.options)?;
+        self.awaiting_framing = false;
+        if self.options.framing != Framing::Json {
+            let mut writer = &stream;
+            Framing::Json.write(
+                &mut writer,
+                &Request::Framing {
+                    framing: self.options.framing,
+                },
+            )?;
+            self.awaiting_framing = true;
+        }
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn request_once(&mut self, request: &Request) -> Result<Response> {
+        if self.stream.is_none() {
+            self.open()?;
+        }
+        let framing = self.options.framing;
+        let stream = self
+            .stream
+            .as_ref()
+            .ok_or_else(|| Error::Ipc("Daemon not running".to_string()))?;
+        let mut writer = stream;
+        framing.write(&mut writer, request)?;
+
+        let mut reader = BufReader::new(stream);
+        if self.awaiting_framing {
+            self.awaiting_framing = false;
+            match Framing::Json.read(&mut reader)? {
+                Some(Response::Framing { .. }) => {}
+                Some(Response::Error { message }) => {
+                    return Err(Error::Ipc(format!(
+                        "Daemon does not support {} framing: {}",
+                        framing.as_str(),
+                        message
+                    )))
+                }
+                _ => {
+                    return Err(Error::Ipc(
+                        "Daemon did not accept the IPC framing".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let frame = framing
+            .read_frame(&mut reader)?
+            .ok_or_else(|| Error::Ipc("Daemon closed IPC connection".to_string()))?;
+        framing
+            .decode(&frame)
+            .map_err(|e| Error::Ipc(format!("Failed to parse response: {}", e)))
+    }
+}
+
+/// Connect to the socket in `options` and apply its timeout.
+fn connect_socket(options: &ClientOptions) -> Result<UnixStream> {
+    let stream = UnixStream::connect(&options.socket_path).map_err(|e| {
+        Error::Ipc(format!(
+            "Failed to connect to daemon at {}: {}. Is the daemon running?",
+            options.socket_path.display(),
+            e
+        ))
+    })?;
+    stream.set_read_timeout(options.timeout)?;
+    stream.set_write_timeout(options.timeout)?;
+    Ok(stream)
+}
+
+/// The error for a response a typed method did not expect.
+fn unexpected(context: &str, response: Response) -> Error {
+    match response {
+        Response::Error { message } => Error::Other(format!("{context} error: {message}")),
+        _ => Error::Ipc(format!("Unexpected response to {context} request")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+    use vicaya_core::ipc::{BuildInfo, SearchFilters, SearchFlags};
+    use vicaya_core::permissions::AttributeFilter;
+
+    fn options(dir: &Path) -> ClientOptions {
+        ClientOptions {
+            socket_path: dir.join("daemon.sock"),
+            timeout: Some(Duration::from_secs(5)),
+            attempts: 3,
+            framing: Framing::Json,
+        }
+    }
+
+    /// Accept `close_count` connections and drop each after reading one
+    /// request, then answer one request with `response`. Returns every
+    /// request read.
+    fn close_n_then_response_server(
+        dir: &Path,
+        close_count: usize,
+        response: Response,
+    ) -> std::thread::JoinHandle<Vec<Request>> {
+        let listener = UnixListener::bind(dir.join("daemon.sock")).unwrap();
+
+        std::thread::spawn(move || {
+            let mut requests = Vec::new();
+            for _ in 0..close_count {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let line = vicaya_core::ipc::read_message(&mut reader)
+                    .unwrap()
+                    .unwrap();
+                requests.push(Request::from_json(&line).unwrap());
+            }
+
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let line = vicaya_core::ipc::read_message(&mut reader)
+                .unwrap()
+                .unwrap();
+            requests.push(Request::from_json(&line).unwrap());
+            let mut json = response.to_json().unwrap();
+            json.push('\n');
+            stream.write_all(json.as_bytes()).unwrap();
+            requests
+        })
+    }
+
+    fn response_server(dir: &Path, response: Response) -> std::thread::JoinHandle<Request> {
+        let handle = close_n_then_response_server(dir, 0, response);
+        std::thread::spawn(move || handle.join().unwrap().remove(0))
+    }
+
+    fn wire_result(path: &str) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            name: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            score: 1.0,
+            size: 12,
+            mtime: 1_700_000_000,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        }
+    }
+
+    fn status_response(reconciling: bool) -> Response {
+        Response::Status(DaemonStatus {
+            pid: 99,
+            build: BuildInfo {
+                version: "1.2.0".to_string(),
+                git_sha: "abc1234".to_string(),
+                timestamp: "2026-05-19T00:00:00Z".to_string(),
+                target: "aarch64-apple-darwin".to_string(),
+            },
+            indexed_files: 42,
+            trigram_count: 777,
+            arena_size: 4096,
+            index_allocated_bytes: 8192,
+            state_allocated_bytes: 16384,
+            last_updated: 1_700_000_000,
+            reconciling,
+            pending_watcher_events: 5,
+            journal_entries: 9,
+            reconcile_progress: reconciling.then_some(42),
+            reconcile_scan: None,
+            warmup_ms: Some(12),
+            journal_fsync: None,
+            index_cap: None,
+            last_reconcile: None,
+            next_reconcile: None,
+            index_profile: Default::default(),
+        })
+    }
+
+    #[test]
+    fn search_sends_options_and_returns_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let response = Response::SearchResults {
+            results: vec![wire_result("/tmp/repo/Cargo.toml")],
+            truncated: true,
+            suggestions: vec!["cargo".to_string()],
+            outside_scope: Vec::new(),
+        };
+        let handle = response_server(dir.path(), response);
+
+        let mut client = Client::new(options(dir.path()));
+        assert!(client.is_connected());
+        let reply = client
+            .search(SearchOptions {
+                scope: Some("/tmp/repo".to_string()),
+                filters: SearchFilters {
+                    scope: Some("/tmp/repo/src".to_string()),
+                    attributes: AttributeFilter {
+                        owner: Some("root".to_string()),
+                        ..AttributeFilter::default()
+                    },
+                    ..SearchFilters::default()
+                },
+                flags: SearchFlags {
+                    explain: true,
+                    ..SearchFlags::default()
+                },
+                scorer: Some("recency".to_string()),
+                ..SearchOptions::new("Cargo", 5)
+            })
+            .unwrap();
+
+        assert!(reply.truncated);
+        assert_eq!(reply.suggestions, ["cargo"]);
+        assert_eq!(reply.results[0].name, "Cargo.toml");
+        match handle.join().unwrap() {
+            Request::Search(options) => {
+                assert_eq!(options.term, "Cargo");
+                assert_eq!(options.limit, 5);
+                assert_eq!(options.scope.as_deref(), Some("/tmp/repo"));
+                assert_eq!(options.filters.scope.as_deref(), Some("/tmp/repo/src"));
+                assert!(options.flags.explain);
+                assert_eq!(options.scorer.as_deref(), Some("recency"));
+                assert_eq!(options.filters.attributes.owner.as_deref(), Some("root"));
+            }
+            other => panic!("unexpected request: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn status_and_rebuild_map_daemon_responses() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = response_server(dir.path(), status_response(true));
+        let status = Client::new(options(dir.path())).status().unwrap();
+        assert!(matches!(handle.join().unwrap(), Request::Status));
+        assert_eq!(status.pid, 99);
+        assert_eq!(status.indexed_files, 42);
+        assert_eq!(status.trigram_count, 777);
+        assert!(status.reconciling);
+        assert_eq!(status.pending_watcher_events, 5);
+        assert_eq!(status.journal_entries, 9);
+        assert_eq!(status.reconcile_progress, Some(42));
+        assert_eq!(status.warmup_ms, Some(12));
+
+        let dir = tempfile::tempdir().unwrap();
+        let handle = response_server(dir.path(), Response::RebuildComplete { files_indexed: 12 });
+        assert_eq!(Client::new(options(dir.path())).rebuild(true).unwrap(), 12);
+        assert!(matches!(
+            handle.join().unwrap(),
+            Request::Rebuild { dry_run: true }
+        ));
+    }
+
+    #[test]
+    fn requests_reconnect_across_a_short_daemon_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let handle = close_n_then_response_server(
+            dir.path(),
+            2,
+            Response::SearchResults {
+                results: vec![wire_result("/tmp/repo/main.rs")],
+                truncated: false,
+                suggestions: Vec::new(),
+                outside_scope: Vec::new(),
+            },
+        );
+
+        let mut client = Client::new(options(dir.path()));
+        let results = client
+            .search(SearchOptions::new("main", 10))
+            .unwrap()
+            .results;
+        let requests = handle.join().unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(requests.len(), 3);
+        assert!(requests
+            .iter()
+            .all(|req| matches!(req, Request::Search(options) if options.term == "main")));
+    }
+
+    #[test]
+    fn one_attempt_fails_on_a_dropped_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let _handle = close_n_then_response_server(dir.path(), 1, status_response(false));
+        let mut client = Client::connect_with_options(ClientOptions {
+            attempts: 1,
+            ..options(dir.path())
+        })
+        .unwrap();
+        let err = client.status().unwrap_err();
+        assert!(err.to_string().contains("closed"), "{err}");
+        assert!(!client.is_connected());
+        // The next request connects again.
+        assert_eq!(client.status().unwrap().indexed_files, 42);
+    }
+
+    #[test]
+    fn daemon_errors_and_missing_daemons_become_client_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = Client::connect_with_options(options(dir.path()));
+        assert!(missing.is_err());
+        let mut lazy = Client::new(options(dir.path()));
+        assert!(!lazy.is_connected());
+        assert!(lazy.suggest("ma", 1).is_err());
+
+        let handle = response_server(
+            dir.path(),
+            Response::Error {
+                message: "boom".to_string(),
+            },
+        );
+        let err = lazy.search(SearchOptions::new("x", 1)).unwrap_err();
+        assert!(err.to_string().contains("Search error: boom"), "{err}");
+        assert!(matches!(handle.join().unwrap(), Request::Search(_)));
+    }
+}
@@ -0,0 +1,157 @@
+//! `Subscribe` connections: the daemon pushes what watcher updates change
+//! about one search.
+
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use vicaya_core::ipc::{Framing, Request, Response, SearchOptions, SearchResult};
+use vicaya_core::{Error, Result};
+
+use crate::ClientOptions;
+
+/// How long [`Subscription::next_change`] waits for a push.
+const SUBSCRIPTION_POLL: Duration = Duration::from_millis(100);
+/// Time allowed to finish reading a push once it started arriving, and to
+/// write a request, when the client has no timeout of its own.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A `Subscribe` connection, opened with [`Client::subscribe`](crate::Client::subscribe)
+/// or [`Subscription::open`].
+pub struct Subscription {
+    stream: UnixStream,
+    reader: BufReader<UnixStream>,
+    message_timeout: Duration,
+    /// `Subscribe` requests not yet acknowledged. Pushes before the last
+    /// acknowledgement belong to an earlier search and are skipped.
+    unacknowledged: usize,
+}
+
+/// One pushed `IndexChanged`.
+#[derive(Debug, Clone, Default)]
+pub struct IndexChange {
+    /// Paths deleted, moved away, or no longer matching.
+    pub removed: Vec<String>,
+    /// New or changed matches.
+    pub added: Vec<SearchResult>,
+}
+
+impl Subscription {
+    /// Subscribe to `options` on a connection of its own, made as `client`
+    /// describes. Pushes are always JSON, whatever framing it names.
+    pub fn open(client: &ClientOptions, options: SearchOptions) -> Result<Self> {
+        let message_timeout = client.timeout.unwrap_or(MESSAGE_TIMEOUT);
+        let stream = crate::connect_socket(client)?;
+        stream.set_read_timeout(Some(SUBSCRIPTION_POLL))?;
+        stream.set_write_timeout(Some(message_timeout))?;
+        let mut subscription = Self {
+            reader: BufReader::new(stream.try_clone()?),
+            stream,
+            message_timeout,
+            unacknowledged: 0,
+        };
+        subscription.resubscribe(options)?;
+        Ok(subscription)
+    }
+
+    /// Follow `options` instead, on the same connection.
+    pub fn resubscribe(&mut self, options: SearchOptions) -> Result<()> {
+        Framing::Json.write(&mut self.stream, &Request::Subscribe(options))?;
+        self.unacknowledged += 1;
+        Ok(())
+    }
+
+    /// The next change to the current search, or `None` when nothing
+    /// arrived within a short poll.
+    pub fn next_change(&mut self) -> Result<Option<IndexChange>> {
+        match self.reader.fill_buf() {
+            Ok([]) => return Err(Error::Ipc("Daemon closed IPC connection".to_string())),
+            Ok(_) => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                return Ok(None)
+            }
+            Err(e) => return Err(e.into()),
+        }
+        // The daemon writes a message at once; allow for a large one.
+        self.stream.set_read_timeout(Some(self.message_timeout))?;
+        let message = Framing::Json.read(&mut self.reader);
+        self.stream.set_read_timeout(Some(SUBSCRIPTION_POLL))?;
+        let response =
+            message?.ok_or_else(|| Error::Ipc("Daemon closed IPC connection".to_string()))?;
+
+        match response {
+            Response::Subscribed => {
+                self.unacknowledged = self.unacknowledged.saturating_sub(1);
+                Ok(None)
+            }
+            Response::IndexChanged { removed, added } if self.unacknowledged == 0 => {
+                Ok(Some(IndexChange { removed, added }))
+            }
+            Response::IndexChanged { .. } => Ok(None),
+            other => Err(crate::unexpected("Subscribe", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn pushes_for_an_earlier_search_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let listener = UnixListener::bind(dir.path().join("daemon.sock")).unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut terms = Vec::new();
+            for _ in 0..2 {
+                match Framing::Json.read(&mut reader).unwrap().unwrap() {
+                    Request::Subscribe(options) => terms.push(options.term),
+                    other => panic!("unexpected request: {other:?}"),
+                }
+            }
+            let push = |removed: &str| Response::IndexChanged {
+                removed: vec![removed.to_string()],
+                added: Vec::new(),
+            };
+            for response in [
+                Response::Subscribed,
+                push("/old/a"),
+                Response::Subscribed,
+                push("/new/b"),
+            ] {
+                Framing::Json.write(&mut stream, &response).unwrap();
+            }
+            stream.flush().unwrap();
+            terms
+        });
+
+        let options = ClientOptions {
+            socket_path: dir.path().join("daemon.sock"),
+            ..ClientOptions::default()
+        };
+        let mut subscription = Subscription::open(&options, SearchOptions::new("old", 10)).unwrap();
+        subscription
+            .resubscribe(SearchOptions::new("new", 10))
+            .unwrap();
+
+        let mut changes = Vec::new();
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while changes.is_empty() && std::time::Instant::now() < deadline {
+            if let Some(change) = subscription.next_change().unwrap() {
+                changes.push(change);
+            }
+        }
+        assert_eq!(server.join().unwrap(), ["old", "new"]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].removed, ["/new/b"]);
+    }
+}
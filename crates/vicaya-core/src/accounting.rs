@@ -0,0 +1,223 @@
+//! Per-subsystem CPU and I/O accounting for the daemon.
+//!
+//! Work is attributed at thread level: a [`ThreadUsage`] sample is taken
+//! before and after a span of work on the current thread, and the difference
+//! is added to the subsystem's [`UsageCounters`] slot. CPU time comes from the
+//! thread CPU clock on every platform. Bytes read come from the thread's block
+//! input count, which only Linux exposes per thread; elsewhere they are
+//! reported as unknown. Work handed to other threads (e.g. rayon pools) is not
+//! attributed.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// A daemon subsystem that spends CPU and I/O.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Subsystem {
+    /// Filesystem walks (initial build, rebuilds, reconcile scans).
+    Scan,
+    /// Rebuilding state from a scan, journal replay, and index persistence.
+    Reconcile,
+    /// Journaling and applying watcher events.
+    Watcher,
+    /// Serving search and Smriti requests.
+    Query,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 4] = [
+        Subsystem::Scan,
+        Subsystem::Reconcile,
+        Subsystem::Watcher,
+        Subsystem::Query,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Subsystem::Scan => "scan",
+            Subsystem::Reconcile => "reconcile",
+            Subsystem::Watcher => "watcher",
+            Subsystem::Query => "query",
+        }
+    }
+
+    fn slot(self) -> usize {
+        self as usize
+    }
+}
+
+/// Cumulative usage of one subsystem, as reported by `Request::Metrics`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SubsystemUsage {
+    pub subsystem: Subsystem,
+    /// Thread CPU time (user + system) in nanoseconds.
+    pub cpu_ns: u64,
+    /// Bytes read from storage; `None` where the platform has no per-thread
+    /// I/O counter.
+    #[serde(default)]
+    pub read_bytes: Option<u64>,
+    /// Number of measured spans (scans, batches, requests).
+    pub spans: u64,
+}
+
+/// CPU and read counters of the current thread at one instant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ThreadUsage {
+    pub cpu_ns: u64,
+    pub read_bytes: Option<u64>,
+}
+
+impl ThreadUsage {
+    /// Sample the current thread.
+    pub fn now() -> Self {
+        Self {
+            cpu_ns: thread_cpu_ns(),
+            read_bytes: thread_read_bytes(),
+        }
+    }
+
+    /// Usage of the current thread since `self` was sampled on it.
+    pub fn elapsed(&self) -> Self {
+        let now = Self::now();
+        Self {
+            cpu_ns: now.cpu_ns.saturating_sub(self.cpu_ns),
+            read_bytes: now
+                .read_bytes
+                .zip(self.read_bytes)
+                .map(|(now, then)| now.saturating_sub(then)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Slot {
+    cpu_ns: AtomicU64,
+    read_bytes: AtomicU64,
+    spans: AtomicU64,
+}
+
+/// Lock-free cumulative usage per [`Subsystem`].
+#[derive(Debug, Default)]
+pub struct UsageCounters {
+    slots: [Slot; 4],
+    read_bytes_known: AtomicBool,
+}
+
+impl UsageCounters {
+    /// Run `work` on the current thread and charge its usage to `subsystem`.
+    pub fn measure<T>(&self, subsystem: Subsystem, work: impl FnOnce() -> T) -> T {
+        let started = ThreadUsage::now();
+        let result = work();
+        self.record(subsystem, started.elapsed());
+        result
+    }
+
+    /// Charge an already measured span to `subsystem`.
+    pub fn record(&self, subsystem: Subsystem, usage: ThreadUsage) {
+        let slot = &self.slots[subsystem.slot()];
+        slot.cpu_ns.fetch_add(usage.cpu_ns, Ordering::Relaxed);
+        slot.spans.fetch_add(1, Ordering::Relaxed);
+        if let Some(read_bytes) = usage.read_bytes {
+            slot.read_bytes.fetch_add(read_bytes, Ordering::Relaxed);
+            self.read_bytes_known.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Cumulative usage of every subsystem, in [`Subsystem::ALL`] order.
+    pub fn snapshot(&self) -> Vec<SubsystemUsage> {
+        let read_bytes_known = self.read_bytes_known.load(Ordering::Relaxed);
+        Subsystem::ALL
+            .iter()
+            .map(|&subsystem| {
+                let slot = &self.slots[subsystem.slot()];
+                SubsystemUsage {
+                    subsystem,
+                    cpu_ns: slot.cpu_ns.load(Ordering::Relaxed),
+                    read_bytes: read_bytes_known.then(|| slot.read_bytes.load(Ordering::Relaxed)),
+                    spans: slot.spans.load(Ordering::Relaxed),
+                }
+            })
+            .collect()
+    }
+}
+
+/// CPU time (user + system) of the whole process in nanoseconds.
+pub fn process_cpu_ns() -> u64 {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return 0;
+    }
+    timeval_ns(usage.ru_utime) + timeval_ns(usage.ru_stime)
+}
+
+fn timeval_ns(tv: libc::timeval) -> u64 {
+    (tv.tv_sec.max(0) as u64) * 1_000_000_000 + (tv.tv_usec.max(0) as u64) * 1_000
+}
+
+fn thread_cpu_ns() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    if unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) } != 0 {
+        return 0;
+    }
+    (ts.tv_sec.max(0) as u64) * 1_000_000_000 + ts.tv_nsec.max(0) as u64
+}
+
+/// Linux counts block input for each thread in 512-byte units.
+#[cfg(target_os = "linux")]
+fn thread_read_bytes() -> Option<u64> {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_THREAD, &mut usage) } != 0 {
+        return None;
+    }
+    Some((usage.ru_inblock.max(0) as u64) * 512)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn thread_read_bytes() -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measured_work_is_charged_to_its_subsystem() {
+        let counters = UsageCounters::default();
+        let sum = counters.measure(Subsystem::Query, || {
+            let started = std::time::Instant::now();
+            let mut sum = 0u64;
+            while started.elapsed() < std::time::Duration::from_millis(20) {
+                sum = sum.wrapping_add(std::hint::black_box(1));
+            }
+            sum
+        });
+        assert!(sum > 0);
+        counters.record(
+            Subsystem::Scan,
+            ThreadUsage {
+                cpu_ns: 5,
+                read_bytes: Some(4096),
+            },
+        );
+
+        let usage = counters.snapshot();
+        assert_eq!(
+            usage.iter().map(|u| u.subsystem).collect::<Vec<_>>(),
+            Subsystem::ALL.to_vec()
+        );
+        let query = usage[Subsystem::Query.slot()];
+        assert_eq!(query.spans, 1);
+        assert!(query.cpu_ns > 0, "busy loop should take thread CPU time");
+        assert_eq!(usage[Subsystem::Scan.slot()].cpu_ns, 5);
+        assert_eq!(usage[Subsystem::Scan.slot()].read_bytes, Some(4096));
+        assert_eq!(usage[Subsystem::Watcher.slot()].spans, 0);
+        assert!(process_cpu_ns() >= query.cpu_ns);
+    }
+}
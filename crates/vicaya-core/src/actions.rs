@@ -0,0 +1,111 @@
+//! Result actions (reveal, open, copy) shared by the TUI and the daemon.
+//!
+//! Every launched program is detached from the caller's stdio and reaped on
+//! a background thread, so a long-lived daemon does not collect zombies.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::{Error, Result};
+
+/// Reveal `path` in Finder (macOS) or open its parent directory elsewhere.
+///
+/// Archive members (`app.jar!/META-INF/MANIFEST.MF`) reveal their archive.
+pub fn reveal(path: &Path) -> Result<()> {
+    let path = path
+        .to_str()
+        .and_then(crate::archive::split_virtual_path)
+        .map_or(path, |(archive, _)| archive);
+    ensure_exists(path)?;
+    if cfg!(target_os = "macos") {
+        spawn_detached("open", &["-R".to_string()], path)
+    } else {
+        let parent = path.parent().unwrap_or(path);
+        spawn_detached("xdg-open", &[], parent)
+    }
+}
+
+/// Open `path` in the desktop's default text editor without waiting for it.
+///
+/// Terminal editors from `$EDITOR` need a tty, so this uses `open -t` on
+/// macOS and `xdg-open` elsewhere.
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    ensure_exists(path)?;
+    if cfg!(target_os = "macos") {
+        spawn_detached("open", &["-t".to_string()], path)
+    } else {
+        spawn_detached("xdg-open", &[], path)
+    }
+}
+
+/// Copy `text` to the system clipboard (`pbcopy` or `xclip`).
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("pbcopy")
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", "clipboard"]);
+        command
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(Error::Other(format!(
+            "clipboard helper exited with {status}"
+        )));
+    }
+    Ok(())
+}
+
+/// Spawn `program args… path` with null stdio and reap it in the background.
+pub fn spawn_detached(program: &str, args: &[String], path: &Path) -> Result<()> {
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| Error::Other(format!("failed to launch {program}: {e}")))?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
+fn ensure_exists(path: &Path) -> Result<()> {
+    if path.exists() {
+        Ok(())
+    } else {
+        Err(Error::Other(format!("{} does not exist", path.display())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_reject_missing_paths_before_launching_anything() {
+        let missing = Path::new("/definitely/not/a/vicaya/path");
+        for result in [reveal(missing), open_in_editor(missing)] {
+            let message = result.unwrap_err().to_string();
+            assert!(message.contains("does not exist"), "{message}");
+        }
+    }
+
+    #[test]
+    fn spawn_detached_reports_missing_programs() {
+        let err = spawn_detached("vicaya-no-such-program", &[], Path::new("/")).unwrap_err();
+        assert!(err.to_string().contains("vicaya-no-such-program"), "{err}");
+    }
+}
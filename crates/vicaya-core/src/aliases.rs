@@ -0,0 +1,92 @@
+//! Query aliases.
+//!
+//! `[aliases]` in the config maps short names to query snippets, e.g.
+//! `cfg = "ext:toml,yaml,json type:file"`. A query token `@cfg` is replaced
+//! by its snippet before the query is parsed, in both the CLI and the TUI.
+//! Expansion is a single pass: an alias that mentions another `@name` keeps
+//! it as typed, so aliases can never loop.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+/// Prefix that marks an alias reference in a query.
+pub const ALIAS_SIGIL: char = '@';
+
+/// Whether `name` can be used as an alias: ASCII letters, digits, `-` and `_`.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Replace every `@name` token naming a defined alias with its expansion.
+///
+/// Unknown names are left alone so `@` can still appear in search terms.
+pub fn expand<'a>(query: &'a str, aliases: &BTreeMap<String, String>) -> Cow<'a, str> {
+    let defined = |token: &str| {
+        token
+            .strip_prefix(ALIAS_SIGIL)
+            .and_then(|name| aliases.get(name))
+    };
+    if aliases.is_empty() || !query.split_whitespace().any(|t| defined(t).is_some()) {
+        return Cow::Borrowed(query);
+    }
+
+    let expanded: Vec<&str> = query
+        .split_whitespace()
+        .map(|token| defined(token).map_or(token, |expansion| expansion.trim()))
+        .filter(|token| !token.is_empty())
+        .collect();
+    Cow::Owned(expanded.join(" "))
+}
+
+/// Names of the `@name` tokens in `query` that are not defined aliases.
+pub fn undefined<'a>(query: &'a str, aliases: &BTreeMap<String, String>) -> Vec<&'a str> {
+    query
+        .split_whitespace()
+        .filter_map(|token| token.strip_prefix(ALIAS_SIGIL))
+        .filter(|name| is_valid_name(name) && !aliases.contains_key(*name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn aliases() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            (
+                "cfg".to_string(),
+                "ext:toml,yaml,json type:file".to_string(),
+            ),
+            ("docs".to_string(), "path:Documents".to_string()),
+            ("loop".to_string(), "@loop".to_string()),
+        ])
+    }
+
+    #[test]
+    fn defined_aliases_expand_in_place_once() {
+        let aliases = aliases();
+        assert_eq!(
+            expand("@cfg server @docs", &aliases),
+            "ext:toml,yaml,json type:file server path:Documents"
+        );
+        assert_eq!(expand("@loop", &aliases), "@loop");
+        assert_eq!(
+            expand("me@example.com @nope", &aliases),
+            "me@example.com @nope"
+        );
+        assert!(matches!(expand("plain query", &aliases), Cow::Borrowed(_)));
+        assert_eq!(undefined("@cfg @nope me@x @", &aliases), vec!["nope"]);
+    }
+
+    #[test]
+    fn alias_names_are_plain_words() {
+        assert!(is_valid_name("cfg"));
+        assert!(is_valid_name("rust-src_2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("a b"));
+        assert!(!is_valid_name("@cfg"));
+    }
+}
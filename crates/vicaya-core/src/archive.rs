@@ -0,0 +1,378 @@
+//! Archive members as virtual paths (`[archives]`).
+//!
+//! With `archives.enabled`, the scanner lists the members of zip-family and
+//! tar archives and indexes each one as `<archive>!/<member>`, e.g.
+//! `~/dl/app.jar!/META-INF/MANIFEST.MF`. Listing only reads headers;
+//! [`read_member`] decompresses a single member on demand for previews.
+
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path};
+
+use crate::config::ArchiveConfig;
+
+/// Separates the archive path from the member path in a virtual path.
+pub const SEPARATOR: &str = "!/";
+
+/// Members listed per archive; the rest are skipped.
+pub const MAX_MEMBERS: usize = 100_000;
+
+/// Container formats vicaya can list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// Zip and its relatives (`jar`, `war`, `whl`, ...).
+    Zip,
+    /// Uncompressed tar.
+    Tar,
+    /// Gzip-compressed tar (`tar.gz`, `tgz`).
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Format of `path` when its name ends with one of the configured
+    /// `extensions`. Tar suffixes are read as tar; anything else as zip.
+    pub fn detect(path: &Path, extensions: &[String]) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        let ext = extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .filter(|ext| name.len() > ext.len() + 1 && name.ends_with(&format!(".{ext}")))
+            .max_by_key(|ext| ext.len())?;
+        Some(match ext.as_str() {
+            "tar" => Self::Tar,
+            "tgz" => Self::TarGz,
+            ext if ext.ends_with("tar.gz") => Self::TarGz,
+            _ => Self::Zip,
+        })
+    }
+
+    /// Format of the archive at `path` judged by its magic bytes: zip local
+    /// file headers, gzip streams, and otherwise tar.
+    pub fn sniff(path: &Path) -> crate::Result<Self> {
+        let mut magic = [0u8; 4];
+        let read = File::open(path)?.read(&mut magic)?;
+        Ok(match &magic[..read] {
+            [b'P', b'K', ..] => Self::Zip,
+            [0x1f, 0x8b, ..] => Self::TarGz,
+            _ => Self::Tar,
+        })
+    }
+}
+
+/// One file or directory inside an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveMember {
+    /// Path inside the archive, `/`-separated, without a leading or
+    /// trailing slash.
+    pub path: String,
+    /// Uncompressed size in bytes (0 for directories).
+    pub size: u64,
+    /// Directory entry rather than a file.
+    pub is_dir: bool,
+}
+
+/// Whether `path` should be listed under `config`: archives must be enabled,
+/// match an extension, and fit in `max_archive_mb`.
+pub fn indexable(config: &ArchiveConfig, path: &Path, size: u64) -> Option<ArchiveFormat> {
+    if !config.enabled || size > config.max_archive_mb.saturating_mul(1024 * 1024) {
+        return None;
+    }
+    ArchiveFormat::detect(path, &config.extensions)
+}
+
+/// Join an archive path and a member path into a virtual path.
+pub fn virtual_path(archive: &str, member: &str) -> String {
+    format!("{archive}{SEPARATOR}{member}")
+}
+
+/// Split a virtual path into the archive file and the member inside it.
+///
+/// Real directories may contain `!/` too, so only a prefix that is an
+/// existing file counts as the archive.
+pub fn split_virtual_path(path: &str) -> Option<(&Path, &str)> {
+    path.match_indices(SEPARATOR).find_map(|(idx, _)| {
+        let archive = Path::new(&path[..idx]);
+        let member = &path[idx + SEPARATOR.len()..];
+        (!member.is_empty() && archive.is_file()).then_some((archive, member))
+    })
+}
+
+/// List the members of `path`, at most [`MAX_MEMBERS`].
+///
+/// Members whose names would escape the archive (`..`, absolute paths) and
+/// anything other than files and directories are skipped.
+pub fn list_members(path: &Path, format: ArchiveFormat) -> crate::Result<Vec<ArchiveMember>> {
+    let file = File::open(path)?;
+    match format {
+        ArchiveFormat::Zip => {
+            let mut archive =
+                zip::ZipArchive::new(BufReader::new(file)).map_err(|e| unreadable(path, e))?;
+            let mut members = Vec::new();
+            for index in 0..archive.len().min(MAX_MEMBERS) {
+                let entry = archive
+                    .by_index_raw(index)
+                    .map_err(|e| unreadable(path, e))?;
+                if let Some(name) = member_name(entry.name()) {
+                    members.push(ArchiveMember {
+                        path: name,
+                        size: if entry.is_dir() { 0 } else { entry.size() },
+                        is_dir: entry.is_dir(),
+                    });
+                }
+            }
+            Ok(members)
+        }
+        ArchiveFormat::Tar => list_tar(path, file),
+        ArchiveFormat::TarGz => list_tar(path, flate2::read::GzDecoder::new(file)),
+    }
+}
+
+/// Read at most `max_bytes` of `member` from the archive at `path`.
+///
+/// The format is sniffed from the file's leading bytes, so previews work
+/// without the scanner's extension list. Returns the bytes and the
+/// member's full uncompressed size.
+pub fn read_member(path: &Path, member: &str, max_bytes: usize) -> crate::Result<(Vec<u8>, u64)> {
+    let format = ArchiveFormat::sniff(path)?;
+    let file = File::open(path)?;
+    let mut buf = Vec::new();
+    let size = match format {
+        ArchiveFormat::Zip => {
+            let mut archive =
+                zip::ZipArchive::new(BufReader::new(file)).map_err(|e| unreadable(path, e))?;
+            // Directory entries are stored with a trailing slash.
+            let name = if archive.file_names().any(|name| name == member) {
+                member.to_string()
+            } else {
+                format!("{member}/")
+            };
+            let entry = archive.by_name(&name).map_err(|e| match e {
+                zip::result::ZipError::FileNotFound => missing_member(path, member),
+                other => unreadable(path, other),
+            })?;
+            let size = entry.size();
+            entry.take(max_bytes as u64).read_to_end(&mut buf)?;
+            size
+        }
+        ArchiveFormat::Tar => read_tar_member(path, file, member, max_bytes, &mut buf)?,
+        ArchiveFormat::TarGz => read_tar_member(
+            path,
+            flate2::read::GzDecoder::new(file),
+            member,
+            max_bytes,
+            &mut buf,
+        )?,
+    };
+    Ok((buf, size))
+}
+
+fn list_tar(path: &Path, reader: impl Read) -> crate::Result<Vec<ArchiveMember>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for entry in archive.entries().map_err(|e| unreadable(path, e))? {
+        if members.len() >= MAX_MEMBERS {
+            break;
+        }
+        let entry = entry.map_err(|e| unreadable(path, e))?;
+        let kind = entry.header().entry_type();
+        if !(kind.is_file() || kind.is_dir()) {
+            continue;
+        }
+        let Ok(raw) = entry.path() else {
+            continue;
+        };
+        if let Some(name) = member_name(&raw.to_string_lossy()) {
+            members.push(ArchiveMember {
+                path: name,
+                size: if kind.is_dir() { 0 } else { entry.size() },
+                is_dir: kind.is_dir(),
+            });
+        }
+    }
+    Ok(members)
+}
+
+fn read_tar_member(
+    path: &Path,
+    reader: impl Read,
+    member: &str,
+    max_bytes: usize,
+    buf: &mut Vec<u8>,
+) -> crate::Result<u64> {
+    let mut archive = tar::Archive::new(reader);
+    for entry in archive.entries().map_err(|e| unreadable(path, e))? {
+        let entry = entry.map_err(|e| unreadable(path, e))?;
+        let name = entry
+            .path()
+            .ok()
+            .and_then(|raw| member_name(&raw.to_string_lossy()));
+        if name.as_deref() == Some(member) {
+            let size = entry.size();
+            entry.take(max_bytes as u64).read_to_end(buf)?;
+            return Ok(size);
+        }
+    }
+    Err(missing_member(path, member))
+}
+
+/// Normalize a member name, rejecting ones that would escape the archive.
+fn member_name(raw: &str) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in Path::new(raw.trim_end_matches('/')).components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn missing_member(path: &Path, member: &str) -> crate::Error {
+    crate::Error::Other(format!("{} has no member {}", path.display(), member))
+}
+
+fn unreadable(path: &Path, err: impl std::fmt::Display) -> crate::Error {
+    crate::Error::Other(format!("unreadable archive {}: {}", path.display(), err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn extensions() -> Vec<String> {
+        crate::config::default_archive_extensions()
+    }
+
+    fn write_zip(path: &Path, members: &[(&str, &str)]) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::FileOptions::default();
+        for (name, content) in members {
+            if name.ends_with('/') {
+                zip.add_directory(*name, options).unwrap();
+            } else {
+                zip.start_file(*name, options).unwrap();
+                zip.write_all(content.as_bytes()).unwrap();
+            }
+        }
+        zip.finish().unwrap();
+    }
+
+    fn write_tar_gz(path: &Path, members: &[(&str, &str)]) {
+        let gz = flate2::write::GzEncoder::new(
+            File::create(path).unwrap(),
+            flate2::Compression::default(),
+        );
+        let mut tar = tar::Builder::new(gz);
+        for (name, content) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn formats_follow_the_configured_extensions() {
+        let detect = |name: &str| ArchiveFormat::detect(Path::new(name), &extensions());
+        assert_eq!(detect("/dl/app.JAR"), Some(ArchiveFormat::Zip));
+        assert_eq!(detect("/dl/src.tar.gz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(detect("/dl/src.tgz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(detect("/dl/src.tar"), Some(ArchiveFormat::Tar));
+        assert_eq!(detect("/dl/notes.txt"), None);
+        assert_eq!(detect("/dl/.zip"), None);
+
+        let wheels = vec![".whl".to_string()];
+        assert_eq!(
+            ArchiveFormat::detect(Path::new("/dl/pkg.whl"), &wheels),
+            Some(ArchiveFormat::Zip)
+        );
+        assert_eq!(ArchiveFormat::detect(Path::new("/dl/a.zip"), &wheels), None);
+    }
+
+    #[test]
+    fn zip_and_tar_members_are_listed_and_read_on_demand() {
+        let dir = tempfile::tempdir().unwrap();
+        let zip_path = dir.path().join("app.jar");
+        write_zip(
+            &zip_path,
+            &[
+                ("META-INF/", ""),
+                ("META-INF/MANIFEST.MF", "Main-Class: App\n"),
+                ("../escape.txt", "nope"),
+            ],
+        );
+        let members = list_members(&zip_path, ArchiveFormat::Zip).unwrap();
+        assert_eq!(
+            members,
+            vec![
+                ArchiveMember {
+                    path: "META-INF".to_string(),
+                    size: 0,
+                    is_dir: true,
+                },
+                ArchiveMember {
+                    path: "META-INF/MANIFEST.MF".to_string(),
+                    size: 16,
+                    is_dir: false,
+                },
+            ]
+        );
+        let (bytes, size) = read_member(&zip_path, "META-INF/MANIFEST.MF", 4).unwrap();
+        assert_eq!((bytes.as_slice(), size), (&b"Main"[..], 16));
+
+        let tar_path = dir.path().join("src.tar.gz");
+        write_tar_gz(&tar_path, &[("pkg/lib.rs", "fn main() {}\n")]);
+        let members = list_members(&tar_path, ArchiveFormat::TarGz).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].path, "pkg/lib.rs");
+        let (bytes, size) = read_member(&tar_path, "pkg/lib.rs", 1024).unwrap();
+        assert_eq!((bytes.as_slice(), size), (&b"fn main() {}\n"[..], 13));
+        assert!(read_member(&tar_path, "pkg/missing.rs", 1024).is_err());
+
+        std::fs::write(dir.path().join("broken.zip"), "not a zip").unwrap();
+        let err = list_members(&dir.path().join("broken.zip"), ArchiveFormat::Zip)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unreadable archive"), "{err}");
+    }
+
+    #[test]
+    fn virtual_paths_split_at_the_archive_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let odd_dir = dir.path().join("wow!");
+        std::fs::create_dir(&odd_dir).unwrap();
+        let archive = odd_dir.join("a.zip");
+        write_zip(&archive, &[("x!/y.txt", "")]);
+        let archive_str = archive.to_string_lossy().to_string();
+
+        let member = virtual_path(&archive_str, "x!/y.txt");
+        assert_eq!(
+            split_virtual_path(&member),
+            Some((archive.as_path(), "x!/y.txt"))
+        );
+        assert_eq!(split_virtual_path(&archive_str), None);
+        assert_eq!(split_virtual_path(&format!("{archive_str}!/")), None);
+        assert_eq!(
+            split_virtual_path(&odd_dir.join("plain.txt").to_string_lossy()),
+            None
+        );
+    }
+
+    #[test]
+    fn only_enabled_small_enough_archives_are_indexable() {
+        let mut config = ArchiveConfig::default();
+        let path = Path::new("/dl/a.zip");
+        assert_eq!(indexable(&config, path, 10), None);
+
+        config.enabled = true;
+        config.max_archive_mb = 1;
+        assert_eq!(indexable(&config, path, 10), Some(ArchiveFormat::Zip));
+        assert_eq!(indexable(&config, path, 2 * 1024 * 1024), None);
+    }
+}
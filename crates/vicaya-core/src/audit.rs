@@ -0,0 +1,127 @@
+//! Audit log of privileged daemon requests.
+//!
+//! Requests that rebuild, reconcile or reconfigure the index, clear Smriti,
+//! or stop or replace the daemon arrive over a socket any local process can
+//! reach. The daemon appends one [`AuditRecord`] per such request to a
+//! newline-delimited JSON log in the vicaya directory, with the peer's pid
+//! and uid when the platform reports them, and `vicaya logs audit` shows it.
+
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+/// One privileged request and what came of it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Epoch seconds when the request was answered.
+    pub at: i64,
+    /// Process id of the connected client, when known.
+    #[serde(default)]
+    pub peer_pid: Option<i32>,
+    /// User id of the connected client, when known.
+    #[serde(default)]
+    pub peer_uid: Option<u32>,
+    /// Request kind, e.g. `"rebuild"` or `"shutdown"`.
+    pub request: String,
+    /// Request arguments worth keeping, e.g. `"dry_run"` or the patterns.
+    #[serde(default)]
+    pub detail: Option<String>,
+    pub outcome: AuditOutcome,
+    /// The daemon's error message when the request failed.
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// Whether a privileged request succeeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Ok,
+    Error,
+}
+
+impl AuditOutcome {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Ok => "ok",
+            Self::Error => "error",
+        }
+    }
+}
+
+/// Append `record` to the log at `path`, creating parent directories as needed.
+pub fn append(path: &Path, record: &AuditRecord) -> crate::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line =
+        serde_json::to_string(record).map_err(|e| crate::Error::Serialization(e.to_string()))?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read every record from the log at `path`, oldest first; a missing log
+/// has none.
+///
+/// Lines that fail to parse (e.g. a torn final write) are skipped.
+pub fn load(path: &Path) -> crate::Result<Vec<AuditRecord>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut records = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if let Ok(record) = serde_json::from_str(&line) {
+            records.push(record);
+        }
+    }
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn records_round_trip_and_torn_lines_are_skipped() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("state").join("audit.jsonl");
+        assert!(load(&path).unwrap().is_empty());
+
+        let rebuild = AuditRecord {
+            at: 1_700_000_000,
+            peer_pid: Some(4242),
+            peer_uid: Some(501),
+            request: "rebuild".to_string(),
+            detail: Some("dry_run".to_string()),
+            outcome: AuditOutcome::Ok,
+            message: None,
+        };
+        let shutdown = AuditRecord {
+            at: 1_700_000_060,
+            peer_pid: None,
+            peer_uid: None,
+            request: "shutdown".to_string(),
+            detail: None,
+            outcome: AuditOutcome::Error,
+            message: Some("busy".to_string()),
+        };
+        append(&path, &rebuild).unwrap();
+        append(&path, &shutdown).unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"{\"at\":17").unwrap();
+
+        assert_eq!(load(&path).unwrap(), vec![rebuild, shutdown]);
+    }
+}
@@ -0,0 +1,276 @@
+//! Directory bookmarks for quick ksetra jumps.
+//!
+//! Bookmarks live in a small versioned JSON file under the vicaya directory.
+//! `vicaya bookmark add/list/rm` edits it and the TUI reads it: the first
+//! nine bookmarks are bound to keys `1`-`9`. Scopes the user keeps entering
+//! (Smriti `enter` actions) are offered as suggestions via
+//! [`BookmarkStore::suggestions`].
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::smriti::SmritiEntry;
+
+const CURRENT_VERSION: u16 = 1;
+
+/// Bookmarks bound to number keys in the TUI.
+pub const HOTKEY_SLOTS: usize = 9;
+
+/// Smriti `enter` actions needed before a scope is suggested.
+pub const SUGGEST_MIN_ENTERS: u64 = 3;
+
+/// One bookmarked directory.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    /// Short unique name, e.g. `foo` for `~/Projects/foo`.
+    pub name: String,
+    /// Absolute directory path.
+    pub path: String,
+    /// Epoch seconds when the bookmark was added.
+    pub added_at: i64,
+}
+
+/// Versioned on-disk bookmark list. Order is significant: the first
+/// [`HOTKEY_SLOTS`] bookmarks map to keys `1`-`9`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookmarkStore {
+    /// On-disk schema version.
+    pub version: u16,
+    /// Bookmarks in slot order.
+    pub bookmarks: Vec<Bookmark>,
+}
+
+impl Default for BookmarkStore {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            bookmarks: Vec::new(),
+        }
+    }
+}
+
+impl BookmarkStore {
+    /// Load bookmarks from JSON, returning an empty store when the file is absent.
+    pub fn load(path: &Path) -> crate::Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&content).map_err(|e| {
+            crate::Error::Config(format!(
+                "failed to parse bookmarks {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Persist the store as pretty JSON using a temporary file followed by `rename`.
+    pub fn save_atomic(&self, path: &Path) -> crate::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        let tmp_path = PathBuf::from(tmp);
+        let content = serde_json::to_vec_pretty(self)
+            .map_err(|e| crate::Error::Serialization(e.to_string()))?;
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(&content)?;
+        file.sync_data()?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Bookmark `path` under `name` (defaults to the directory name).
+    ///
+    /// Names must be unique; bookmarking an already bookmarked path renames
+    /// it instead of adding a duplicate.
+    pub fn add(&mut self, name: Option<&str>, path: &Path, now: i64) -> crate::Result<&Bookmark> {
+        let path = path.to_string_lossy().to_string();
+        let name = match name.map(str::trim) {
+            Some(name) => name.to_string(),
+            None => default_name(&path),
+        };
+        if name.is_empty() || name.chars().all(|c| c.is_ascii_digit()) {
+            return Err(crate::Error::Other(format!(
+                "invalid bookmark name '{name}': names must not be empty or numeric"
+            )));
+        }
+        if let Some(existing) = self
+            .bookmarks
+            .iter()
+            .find(|bookmark| bookmark.name == name && bookmark.path != path)
+        {
+            return Err(crate::Error::Other(format!(
+                "bookmark '{}' already points at {}",
+                name, existing.path
+            )));
+        }
+
+        let index = match self.bookmarks.iter().position(|b| b.path == path) {
+            Some(index) => {
+                self.bookmarks[index].name = name;
+                index
+            }
+            None => {
+                self.bookmarks.push(Bookmark {
+                    name,
+                    path,
+                    added_at: now,
+                });
+                self.bookmarks.len() - 1
+            }
+        };
+        Ok(&self.bookmarks[index])
+    }
+
+    /// Remove the bookmark named `key`, at 1-based slot `key`, or for path `key`.
+    pub fn remove(&mut self, key: &str) -> Option<Bookmark> {
+        let index = self.find(key)?;
+        Some(self.bookmarks.remove(index))
+    }
+
+    /// Look up a bookmark by name, 1-based slot, or path.
+    pub fn get(&self, key: &str) -> Option<&Bookmark> {
+        self.find(key).map(|index| &self.bookmarks[index])
+    }
+
+    /// The bookmark bound to 1-based `slot`, if any.
+    pub fn slot(&self, slot: usize) -> Option<&Bookmark> {
+        slot.checked_sub(1)
+            .and_then(|index| self.bookmarks.get(index))
+    }
+
+    /// Scopes entered at least [`SUGGEST_MIN_ENTERS`] times that are not
+    /// bookmarked yet, most entered first, at most `limit`.
+    pub fn suggestions<'a>(
+        &self,
+        entries: impl IntoIterator<Item = &'a SmritiEntry>,
+        limit: usize,
+    ) -> Vec<&'a SmritiEntry> {
+        let mut suggested: Vec<&SmritiEntry> = entries
+            .into_iter()
+            .filter(|entry| entry.enter_count >= SUGGEST_MIN_ENTERS)
+            .filter(|entry| !self.bookmarks.iter().any(|b| b.path == entry.path))
+            .collect();
+        suggested.sort_by(|a, b| {
+            b.enter_count
+                .cmp(&a.enter_count)
+                .then_with(|| b.last_used.cmp(&a.last_used))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        suggested.truncate(limit);
+        suggested
+    }
+
+    fn find(&self, key: &str) -> Option<usize> {
+        if let Some(index) = self.bookmarks.iter().position(|b| b.name == key) {
+            return Some(index);
+        }
+        if let Ok(slot) = key.parse::<usize>() {
+            return slot
+                .checked_sub(1)
+                .filter(|index| *index < self.bookmarks.len());
+        }
+        let path = crate::paths::expand_user_path(Path::new(key));
+        self.bookmarks
+            .iter()
+            .position(|b| Path::new(&b.path) == path)
+    }
+}
+
+fn default_name(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smriti::{SmritiAction, SmritiStore};
+
+    #[test]
+    fn add_names_slots_and_removes_bookmarks() {
+        let mut store = BookmarkStore::default();
+        store.add(None, Path::new("/work/foo"), 1).unwrap();
+        store
+            .add(Some("notes"), Path::new("/home/me/notes"), 2)
+            .unwrap();
+
+        assert_eq!(store.slot(1).unwrap().name, "foo");
+        assert_eq!(store.slot(2).unwrap().path, "/home/me/notes");
+        assert!(store.slot(0).is_none());
+        assert!(store.slot(3).is_none());
+
+        // Re-adding a path renames it; reusing a name elsewhere is refused.
+        store.add(Some("bar"), Path::new("/work/foo"), 3).unwrap();
+        assert_eq!(store.bookmarks.len(), 2);
+        assert_eq!(store.slot(1).unwrap().name, "bar");
+        assert!(store.add(Some("bar"), Path::new("/work/other"), 4).is_err());
+        assert!(store.add(Some("42"), Path::new("/work/other"), 4).is_err());
+
+        assert_eq!(store.get("notes").unwrap().path, "/home/me/notes");
+        assert_eq!(store.remove("1").unwrap().name, "bar");
+        assert_eq!(store.remove("/home/me/notes").unwrap().name, "notes");
+        assert!(store.remove("notes").is_none());
+        assert!(store.bookmarks.is_empty());
+    }
+
+    #[test]
+    fn frequently_entered_scopes_are_suggested_unless_bookmarked() {
+        let mut smriti = SmritiStore::default();
+        for (path, enters) in [("/work/a", 5), ("/work/b", 3), ("/work/c", 1)] {
+            for now in 0..enters {
+                smriti.record(path.to_string(), String::new(), SmritiAction::Enter, now);
+            }
+        }
+        for _ in 0..10 {
+            smriti.record(
+                "/work/file.rs".to_string(),
+                String::new(),
+                SmritiAction::Open,
+                1,
+            );
+        }
+
+        let mut store = BookmarkStore::default();
+        let paths = |suggested: Vec<&SmritiEntry>| -> Vec<String> {
+            suggested.into_iter().map(|e| e.path.clone()).collect()
+        };
+        assert_eq!(
+            paths(store.suggestions(smriti.entries.values(), 5)),
+            vec!["/work/a", "/work/b"]
+        );
+
+        store.add(None, Path::new("/work/a"), 0).unwrap();
+        assert_eq!(
+            paths(store.suggestions(smriti.entries.values(), 5)),
+            vec!["/work/b"]
+        );
+        assert!(store.suggestions(smriti.entries.values(), 0).is_empty());
+    }
+
+    #[test]
+    fn store_round_trips_and_missing_file_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("bookmarks.json");
+        assert_eq!(
+            BookmarkStore::load(&path).unwrap(),
+            BookmarkStore::default()
+        );
+
+        let mut store = BookmarkStore::default();
+        store.add(Some("foo"), Path::new("/work/foo"), 7).unwrap();
+        store.save_atomic(&path).unwrap();
+        assert_eq!(BookmarkStore::load(&path).unwrap(), store);
+
+        std::fs::write(&path, "{").unwrap();
+        let err = BookmarkStore::load(&path).unwrap_err().to_string();
+        assert!(err.contains("failed to parse bookmarks"), "{err}");
+    }
+}
@@ -1,7 +1,14 @@
 //! Configuration management for vicaya.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::filter::{ExclusionCache, ExclusionSet};
+use crate::i18n::Language;
+use crate::schedule::CronSchedule;
 
 /// Main configuration structure for vicaya.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,12 +23,84 @@ pub struct Config {
     #[serde(default = "default_respect_ignore_files")]
     pub respect_ignore_files: bool,
 
+    /// Match exclusions regardless of case, as macOS volumes usually are
+    /// (`Build` then also excludes `build`).
+    #[serde(default)]
+    pub case_insensitive_exclusions: bool,
+
+    /// Descend into macOS bundles (`Foo.app`, `Bar.framework`, ...). By
+    /// default a bundle is indexed as one entry and its contents are skipped.
+    #[serde(default)]
+    pub index_bundle_contents: bool,
+
+    /// Index Time Machine backups and snapshot volumes. By default they are
+    /// detected and skipped, since they repeat the whole disk.
+    #[serde(default)]
+    pub index_backup_volumes: bool,
+
+    /// Also keep a trigram index of directory paths, so terms that span
+    /// directories (`projects/spartan`) find files without a linear scan.
+    /// Costs memory in proportion to the number of directories; off by default.
+    #[serde(default)]
+    pub index_directory_paths: bool,
+
+    /// How much the index keeps per entry: `full`, or `lite` for low-memory
+    /// machines (files only, without sizes or inodes).
+    #[serde(default)]
+    pub index_profile: IndexProfile,
+
+    /// Compiled form of `exclusions`, rebuilt when the patterns change.
+    #[serde(skip)]
+    pub exclusion_cache: ExclusionCache,
+
     /// Path to store the index data.
     pub index_path: PathBuf,
 
     /// Maximum memory usage in MB.
     pub max_memory_mb: usize,
 
+    /// Most entries the index holds; 0 means no limit. When a scan finds
+    /// more, earlier `index_roots` win, and shallower paths within a root.
+    #[serde(default)]
+    pub max_indexed_files: usize,
+
+    /// Deepest level indexed below an index root, keyed by the root as
+    /// written in `index_roots`, e.g. `"~/Projects" = 4`. The root's direct
+    /// entries are level 1; roots without an entry are walked in full.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub root_max_depth: BTreeMap<PathBuf, usize>,
+
+    /// Whether searches return dotfiles and entries inside dot-directories
+    /// unless they ask otherwise (`hidden:` in a prashna).
+    #[serde(default = "default_show_hidden")]
+    pub show_hidden: bool,
+
+    /// Initial TUI ksetra suggested from the launch directory: `git` (the
+    /// enclosing project root), `cwd`, or `off`.
+    #[serde(default)]
+    pub auto_scope: AutoScope,
+
+    /// TUI result icons: `auto` (Unicode glyphs, or ASCII when the terminal
+    /// locale is not UTF-8), `nerd` for a Nerd Font, `unicode`, or `ascii`.
+    #[serde(default)]
+    pub icons: IconStyle,
+
+    /// Language of CLI and TUI text: `en`, `de`, or `auto` to follow the
+    /// locale environment.
+    #[serde(default)]
+    pub language: Language,
+
+    /// When `vicaya search` has to start the daemon, answer that search from
+    /// the saved `index.bin` (like `--offline`) instead of waiting for the
+    /// daemon to load it.
+    #[serde(default)]
+    pub offline_while_starting: bool,
+
+    /// Reopen the TUI with the ksetra stack and drishti it was closed with
+    /// (`vicaya-tui --fresh` skips it for one launch).
+    #[serde(default)]
+    pub restore_tui_session: bool,
+
     /// Performance settings.
     pub performance: PerformanceConfig,
 
@@ -32,6 +111,56 @@ pub struct Config {
     /// Content search settings.
     #[serde(default)]
     pub content_search: ContentSearchConfig,
+
+    /// TUI preview pane settings.
+    #[serde(default)]
+    pub preview: PreviewConfig,
+
+    /// Index entries inside zip/tar archives as virtual paths.
+    #[serde(default)]
+    pub archives: ArchiveConfig,
+
+    /// Scan-time content fingerprints for the `hash:` niyama.
+    #[serde(default)]
+    pub content_hash: ContentHashConfig,
+
+    /// TUI "open with" applications keyed by file extension, e.g.
+    /// `md = ["Typora", "code"]`.
+    #[serde(default)]
+    pub associations: BTreeMap<String, Vec<String>>,
+
+    /// Query shorthand expanded by the CLI and TUI: `@cfg` in a query is
+    /// replaced by the `cfg` entry, e.g. `cfg = "ext:toml,yaml type:file"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+
+    /// Optional localhost HTTP endpoint served by the daemon.
+    #[serde(default)]
+    pub http: HttpConfig,
+
+    /// Watcher journal durability settings.
+    #[serde(default)]
+    pub journal: JournalConfig,
+
+    /// Opt-in local ranking telemetry.
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+
+    /// Search ranking adjustments.
+    #[serde(default)]
+    pub ranking: RankingConfig,
+
+    /// How files moved to the Trash are handled.
+    #[serde(default)]
+    pub trash: TrashConfig,
+
+    /// Folding applied to names and queries alike before matching.
+    #[serde(default)]
+    pub normalization: NormalizationConfig,
+
+    /// Commands the daemon runs when matching indexed files change.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<HookConfig>,
 }
 
 /// Performance-related configuration.
@@ -40,8 +169,213 @@ pub struct PerformanceConfig {
     /// Number of parallel scanner threads.
     pub scanner_threads: usize,
 
-    /// Reconciliation hour (0-23).
+    /// Reconciliation hour (0-23), used when `reconcile_schedule` is empty.
     pub reconcile_hour: u8,
+
+    /// Wall-clock budget per search in milliseconds; slower searches return
+    /// partial results. 0 disables the limit.
+    #[serde(default = "default_query_timeout_ms")]
+    pub query_timeout_ms: u64,
+
+    /// Maximum candidates scored per search. 0 disables the limit.
+    #[serde(default)]
+    pub query_max_candidates: usize,
+
+    /// Posting lists of the N most common trigrams the daemon touches after
+    /// loading its index, so first searches do not wait on page-ins. 0
+    /// disables warm-up.
+    #[serde(default)]
+    pub warmup_trigrams: usize,
+
+    /// Cron-style times (`minute hour day-of-month month day-of-week`, local
+    /// time) for the full reconcile; see [`crate::schedule`]. Empty means
+    /// daily at `reconcile_hour`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reconcile_schedule: Vec<String>,
+
+    /// Skip a scheduled reconcile when the last one finished less than this
+    /// many minutes earlier. 0 never skips.
+    #[serde(default = "default_reconcile_min_gap_minutes")]
+    pub reconcile_min_gap_minutes: u64,
+
+    /// Reconcile shortly after the machine wakes from sleep, since the
+    /// watcher can miss changes made while it was suspended. Rate-limited by
+    /// `reconcile_min_gap_minutes`.
+    #[serde(default = "default_reconcile_on_wake")]
+    pub reconcile_on_wake: bool,
+
+    /// Threads and throttling for scans that build a new index.
+    #[serde(default)]
+    pub initial_scan: ScanSettings,
+
+    /// Threads and throttling for full reconciles of a loaded index.
+    #[serde(default)]
+    pub reconcile: ScanSettings,
+}
+
+impl PerformanceConfig {
+    /// The parsed reconcile schedule. Entries that do not parse are left out
+    /// (config validation reports them); with none left, the daily
+    /// `reconcile_hour` applies.
+    pub fn reconcile_schedules(&self) -> Vec<CronSchedule> {
+        let schedules: Vec<CronSchedule> = self
+            .reconcile_schedule
+            .iter()
+            .filter_map(|entry| entry.parse().ok())
+            .collect();
+        if schedules.is_empty() {
+            vec![CronSchedule::daily_at(self.reconcile_hour)]
+        } else {
+            schedules
+        }
+    }
+
+    /// Threads and pause a scan with `profile` runs with.
+    pub fn scan_settings(&self, profile: ScanProfile) -> ResolvedScan {
+        let settings = match profile {
+            ScanProfile::InitialScan => &self.initial_scan,
+            ScanProfile::Reconcile => &self.reconcile,
+        };
+        ResolvedScan {
+            profile,
+            threads: settings.threads.unwrap_or(self.scanner_threads).max(1),
+            throttle_ms: settings.throttle_ms,
+        }
+    }
+}
+
+/// Which `[performance.*]` scan settings a full scan runs with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ScanProfile {
+    /// Building an index from nothing: `[performance.initial_scan]`.
+    #[default]
+    InitialScan,
+    /// Rescanning under a live index: `[performance.reconcile]`.
+    Reconcile,
+}
+
+impl ScanProfile {
+    /// The config section name, as shown in status output.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InitialScan => "initial_scan",
+            Self::Reconcile => "reconcile",
+        }
+    }
+}
+
+/// `[performance.initial_scan]` / `[performance.reconcile]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanSettings {
+    /// Threads reading entry metadata; unset uses `scanner_threads`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+
+    /// Pause after every batch of scanned entries, in milliseconds, to leave
+    /// disk and CPU to other work. 0 scans flat out.
+    #[serde(default)]
+    pub throttle_ms: u64,
+}
+
+/// The settings a scan actually runs with, after `scanner_threads` filled
+/// in what its profile leaves unset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct ResolvedScan {
+    pub profile: ScanProfile,
+    pub threads: usize,
+    pub throttle_ms: u64,
+}
+
+impl std::fmt::Display for ResolvedScan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = if self.threads == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} profile, {} thread{plural}",
+            self.profile.as_str(),
+            self.threads
+        )?;
+        if self.throttle_ms > 0 {
+            write!(f, ", {} ms throttle", self.throttle_ms)?;
+        }
+        Ok(())
+    }
+}
+
+/// Search ranking configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingConfig {
+    /// Tie-breaker points directories gain in searches that prefer them (the
+    /// TUI's Sthana drishti) and lose in searches that prefer files (Patra).
+    /// 0 ranks directories like files.
+    #[serde(default = "default_directory_weight")]
+    pub directory_weight: i32,
+    /// Relevance-ranked matches scoring below this (0.0 to 1.0; a path-only
+    /// match scores 0.3, a substring 0.5) are dropped. 0.0 keeps every match.
+    #[serde(default)]
+    pub min_score: f32,
+    /// "Did you mean" names offered when a search finds nothing; 0 disables.
+    #[serde(default = "default_ranking_suggestions")]
+    pub suggestions: usize,
+    /// Score multipliers for matches under a path or with an extension,
+    /// applied by the relevance scorer.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub boost: Vec<RankingBoost>,
+    /// Score added in global searches to results under a search scope used
+    /// this session, fading with `recent_scope_half_life_mins`; 0 disables.
+    #[serde(default = "default_recent_scope_boost")]
+    pub recent_scope_boost: f32,
+    /// Minutes after which an unused scope's boost has halved.
+    #[serde(default = "default_recent_scope_half_life_mins")]
+    pub recent_scope_half_life_mins: u64,
+    /// When a scoped search finds nothing, search every root as well and
+    /// return those matches labeled as outside the scope.
+    #[serde(default = "default_auto_broaden")]
+    pub auto_broaden: bool,
+}
+
+/// One `[ranking] boost` entry: `{ pattern = "~/Projects/**", factor = 1.2 }`
+/// or `{ ext = "png", factor = 0.8 }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RankingBoost {
+    /// Glob over result paths; patterns without `/` match the file name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// File extension, without the dot, compared case-insensitively.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ext: Option<String>,
+    /// Multiplier for the match score; above 1 promotes, below 1 demotes.
+    pub factor: f32,
+}
+
+/// Name and query normalization. Changing either option re-derives the
+/// trigram index the next time the daemon loads it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NormalizationConfig {
+    /// Match accented letters by their base letter, so `resume` finds
+    /// `résumé.pdf`.
+    #[serde(default)]
+    pub fold_diacritics: bool,
+
+    /// Treat `-`, `_`, `.` and space as the same separator, so `foo bar`
+    /// finds `foo_bar` and `foo-bar`.
+    #[serde(default)]
+    pub fold_separators: bool,
+}
+
+/// Trash handling.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashConfig {
+    /// Index Trash directories like any other. By default they are skipped
+    /// and entries moved into them leave the index at once.
+    #[serde(default)]
+    pub index: bool,
+
+    /// Entries moved to the Trash the daemon remembers for the recently
+    /// deleted list. 0 disables the list.
+    #[serde(default = "default_recently_deleted")]
+    pub recently_deleted: usize,
 }
 
 /// Smriti usage-memory configuration.
@@ -58,6 +392,11 @@ pub struct SmritiConfig {
     /// Maximum ranking boost applied to matching search results.
     #[serde(default = "default_smriti_max_boost")]
     pub max_boost: f32,
+
+    /// Mark TUI results used before with how often and how recently, e.g.
+    /// `opened 12× · 2d ago`.
+    #[serde(default = "default_smriti_badges")]
+    pub badges: bool,
 }
 
 /// Content-search configuration.
@@ -80,6 +419,322 @@ pub struct ContentSearchConfig {
     pub rg_path: Option<PathBuf>,
 }
 
+/// TUI preview configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewConfig {
+    /// Bytes read per preview chunk; `L` in the preview pane loads the next one.
+    #[serde(default = "default_preview_max_bytes")]
+    pub max_bytes: usize,
+
+    /// Lines shown per preview chunk.
+    #[serde(default = "default_preview_max_lines")]
+    pub max_lines: usize,
+}
+
+/// Archive member indexing (`app.jar!/META-INF/MANIFEST.MF`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveConfig {
+    /// List archive members during scans and reconciles.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// File suffixes treated as archives. `tar`, `tar.gz`, and `tgz` are read
+    /// as tar; anything else as zip (`jar`, `whl`, ...).
+    #[serde(default = "default_archive_extensions")]
+    pub extensions: Vec<String>,
+
+    /// Larger archives are indexed as plain files.
+    #[serde(default = "default_archive_max_mb")]
+    pub max_archive_mb: u64,
+}
+
+/// Content fingerprints (`hash:`): BLAKE3 of each file's head and size,
+/// computed while scanning. Off by default because every hashed file is
+/// opened and read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentHashConfig {
+    /// Fingerprint files during scans, reconciles, and watcher updates.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Leading KiB of each file that are hashed.
+    #[serde(default = "default_content_hash_head_kb")]
+    pub head_kb: u64,
+
+    /// Larger files are left unhashed.
+    #[serde(default = "default_content_hash_max_file_mb")]
+    pub max_file_mb: u64,
+}
+
+impl ContentHashConfig {
+    /// The fingerprint of `path` when hashing is on and a file of `size`
+    /// bytes qualifies.
+    pub fn compute(&self, path: &Path, size: u64, is_dir: bool) -> Option<std::num::NonZeroU64> {
+        if !self.enabled || is_dir || size > self.max_file_mb.saturating_mul(1024 * 1024) {
+            return None;
+        }
+        crate::content_hash::compute(path, size, self.head_bytes())
+    }
+
+    /// Bytes hashed from the start of each file.
+    pub fn head_bytes(&self) -> usize {
+        self.head_kb.saturating_mul(1024) as usize
+    }
+}
+
+/// Daemon HTTP endpoint configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpConfig {
+    /// Serve `GET /search` and `GET /status` on 127.0.0.1.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Localhost port to listen on.
+    #[serde(default = "default_http_port")]
+    pub port: u16,
+
+    /// Bearer token every request must carry; the endpoint stays off without one.
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// One `[[hooks]]` entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookConfig {
+    /// Glob over changed paths; patterns without `/` match the file name.
+    pub pattern: String,
+
+    /// Command run with `sh -c`; `{path}`, `{paths}`, and `{count}` are
+    /// replaced with the shell-quoted changed paths.
+    pub command: String,
+
+    /// Quiet period after the last matching change before the command runs.
+    #[serde(default = "default_hook_debounce_ms")]
+    pub debounce_ms: u64,
+
+    /// Working directory for the command (defaults to the daemon's).
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+}
+
+/// Which initial ksetra the TUI offers when launched without a scope.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoScope {
+    /// The enclosing project root (see [`crate::paths::find_project_root`]).
+    Git,
+    /// The launch directory itself.
+    Cwd,
+    /// Start with a global ksetra.
+    #[default]
+    Off,
+}
+
+impl AutoScope {
+    /// The scope this mode suggests for a TUI launched in `cwd`.
+    pub fn resolve(self, cwd: &Path) -> Option<PathBuf> {
+        match self {
+            Self::Git => crate::paths::find_project_root(cwd),
+            Self::Cwd => Some(cwd.to_path_buf()),
+            Self::Off => None,
+        }
+    }
+}
+
+impl std::str::FromStr for AutoScope {
+    type Err = crate::Error;
+
+    fn from_str(value: &str) -> crate::Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "git" => Ok(Self::Git),
+            "cwd" => Ok(Self::Cwd),
+            "off" => Ok(Self::Off),
+            other => Err(crate::Error::Config(format!(
+                "unknown auto_scope '{other}' (expected git, cwd, or off)"
+            ))),
+        }
+    }
+}
+
+/// Which glyphs the TUI draws in its result icon column.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconStyle {
+    /// `unicode`, or `ascii` when the terminal cannot show it.
+    #[default]
+    Auto,
+    /// Nerd Font glyphs, which need a patched font.
+    Nerd,
+    /// Symbols every Unicode terminal font has.
+    Unicode,
+    /// One ASCII letter per kind, `ls -l` style.
+    Ascii,
+}
+
+impl std::str::FromStr for IconStyle {
+    type Err = crate::Error;
+
+    fn from_str(value: &str) -> crate::Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "nerd" => Ok(Self::Nerd),
+            "unicode" => Ok(Self::Unicode),
+            "ascii" => Ok(Self::Ascii),
+            other => Err(crate::Error::Config(format!(
+                "unknown icons '{other}' (expected auto, nerd, unicode, or ascii)"
+            ))),
+        }
+    }
+}
+
+/// What the index keeps per entry.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexProfile {
+    /// Files and directories with their full metadata.
+    #[default]
+    Full,
+    /// Files only, with size, device, and inode left at zero: no directory
+    /// results, size sorting, or inode-based rename tracking, in exchange for
+    /// a smaller index.
+    Lite,
+}
+
+impl IndexProfile {
+    /// Whether directories are indexed as entries of their own.
+    pub fn indexes_directories(self) -> bool {
+        self == Self::Full
+    }
+
+    /// Whether entries carry their size, device, and inode.
+    pub fn tracks_file_identity(self) -> bool {
+        self == Self::Full
+    }
+}
+
+impl std::str::FromStr for IndexProfile {
+    type Err = crate::Error;
+
+    fn from_str(value: &str) -> crate::Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "full" => Ok(Self::Full),
+            "lite" => Ok(Self::Lite),
+            other => Err(crate::Error::Config(format!(
+                "unknown index_profile '{other}' (expected full or lite)"
+            ))),
+        }
+    }
+}
+
+/// Local ranking telemetry configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// Log query, accepted rank, and time-to-select from the TUI to a local
+    /// file for `vicaya metrics ranking-report`. Nothing is uploaded.
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// Watcher journal configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    /// When appended journal entries are forced to disk: none, interval, or
+    /// every-batch.
+    #[serde(default = "default_journal_fsync")]
+    pub fsync: String,
+
+    /// Longest an appended entry may stay unsynced under the `interval` policy.
+    #[serde(default = "default_journal_fsync_interval_ms")]
+    pub fsync_interval_ms: u64,
+}
+
+/// How the daemon forces watcher journal appends to stable storage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "policy", rename_all = "kebab-case")]
+pub enum JournalFsync {
+    /// Leave flushing to the OS; a power loss can drop recent updates.
+    None,
+    /// Sync at most `interval_ms` after the first unsynced append.
+    Interval {
+        /// Deadline after the first unsynced append, in milliseconds.
+        interval_ms: u64,
+    },
+    /// Sync after every appended batch.
+    EveryBatch,
+}
+
+impl JournalFsync {
+    /// Parse a config policy name; `interval_ms` applies to `interval`.
+    pub fn parse(value: &str, interval_ms: u64) -> crate::Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "off" => Ok(Self::None),
+            "interval" => Ok(Self::Interval { interval_ms }),
+            "every-batch" | "every_batch" | "always" => Ok(Self::EveryBatch),
+            other => Err(crate::Error::Config(format!(
+                "unknown journal fsync policy '{other}'"
+            ))),
+        }
+    }
+}
+
+impl std::fmt::Display for JournalFsync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Interval { interval_ms } => write!(f, "interval ({interval_ms} ms)"),
+            Self::EveryBatch => f.write_str("every batch"),
+        }
+    }
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            fsync: default_journal_fsync(),
+            fsync_interval_ms: default_journal_fsync_interval_ms(),
+        }
+    }
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_http_port(),
+            token: None,
+        }
+    }
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            extensions: default_archive_extensions(),
+            max_archive_mb: default_archive_max_mb(),
+        }
+    }
+}
+
+impl Default for ContentHashConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            head_kb: default_content_hash_head_kb(),
+            max_file_mb: default_content_hash_max_file_mb(),
+        }
+    }
+}
+
+impl Default for PreviewConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_preview_max_bytes(),
+            max_lines: default_preview_max_lines(),
+        }
+    }
+}
+
 impl Default for ContentSearchConfig {
     fn default() -> Self {
         Self {
@@ -91,12 +746,36 @@ impl Default for ContentSearchConfig {
     }
 }
 
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            directory_weight: default_directory_weight(),
+            min_score: 0.0,
+            suggestions: default_ranking_suggestions(),
+            boost: Vec::new(),
+            recent_scope_boost: default_recent_scope_boost(),
+            recent_scope_half_life_mins: default_recent_scope_half_life_mins(),
+            auto_broaden: default_auto_broaden(),
+        }
+    }
+}
+
+impl Default for TrashConfig {
+    fn default() -> Self {
+        Self {
+            index: false,
+            recently_deleted: default_recently_deleted(),
+        }
+    }
+}
+
 impl Default for SmritiConfig {
     fn default() -> Self {
         Self {
             enabled: default_smriti_enabled(),
             max_entries: default_smriti_max_entries(),
             max_boost: default_smriti_max_boost(),
+            badges: default_smriti_badges(),
         }
     }
 }
@@ -115,14 +794,48 @@ impl Default for Config {
                 "target".to_string(),
             ],
             respect_ignore_files: true,
+            case_insensitive_exclusions: false,
+            exclusion_cache: ExclusionCache::default(),
             index_path: Self::default_index_path(),
             max_memory_mb: 512,
+            max_indexed_files: 0,
             performance: PerformanceConfig {
                 scanner_threads: num_cpus::get(),
                 reconcile_hour: 3,
+                query_timeout_ms: default_query_timeout_ms(),
+                query_max_candidates: 0,
+                warmup_trigrams: 0,
+                reconcile_schedule: Vec::new(),
+                reconcile_min_gap_minutes: default_reconcile_min_gap_minutes(),
+                reconcile_on_wake: default_reconcile_on_wake(),
+                initial_scan: Default::default(),
+                reconcile: Default::default(),
             },
             smriti: SmritiConfig::default(),
             content_search: ContentSearchConfig::default(),
+            preview: PreviewConfig::default(),
+            associations: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            http: HttpConfig::default(),
+            journal: JournalConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            ranking: RankingConfig::default(),
+            trash: TrashConfig::default(),
+            normalization: NormalizationConfig::default(),
+            hooks: Vec::new(),
+            root_max_depth: Default::default(),
+            show_hidden: true,
+            restore_tui_session: false,
+            offline_while_starting: false,
+            auto_scope: AutoScope::default(),
+            icons: IconStyle::default(),
+            language: Default::default(),
+            archives: ArchiveConfig::default(),
+            content_hash: ContentHashConfig::default(),
+            index_bundle_contents: false,
+            index_backup_volumes: false,
+            index_directory_paths: false,
+            index_profile: IndexProfile::default(),
         };
         config.normalize_exclusions();
         config
@@ -133,6 +846,46 @@ fn default_respect_ignore_files() -> bool {
     true
 }
 
+fn default_show_hidden() -> bool {
+    true
+}
+
+fn default_query_timeout_ms() -> u64 {
+    250
+}
+
+fn default_reconcile_min_gap_minutes() -> u64 {
+    60
+}
+
+fn default_reconcile_on_wake() -> bool {
+    true
+}
+
+fn default_directory_weight() -> i32 {
+    40
+}
+
+fn default_ranking_suggestions() -> usize {
+    3
+}
+
+fn default_recent_scope_boost() -> f32 {
+    0.1
+}
+
+fn default_auto_broaden() -> bool {
+    true
+}
+
+fn default_recent_scope_half_life_mins() -> u64 {
+    30
+}
+
+fn default_recently_deleted() -> usize {
+    200
+}
+
 fn default_smriti_enabled() -> bool {
     true
 }
@@ -145,6 +898,10 @@ fn default_smriti_max_boost() -> f32 {
     0.08
 }
 
+fn default_smriti_badges() -> bool {
+    true
+}
+
 fn default_content_search_enabled() -> bool {
     true
 }
@@ -153,18 +910,88 @@ fn default_content_search_engine() -> String {
     "auto".to_string()
 }
 
+fn default_preview_max_bytes() -> usize {
+    256 * 1024
+}
+
+fn default_preview_max_lines() -> usize {
+    4000
+}
+
+/// Archive suffixes looked inside when `[archives]` is enabled.
+pub fn default_archive_extensions() -> Vec<String> {
+    ["zip", "jar", "tar", "tar.gz", "tgz"]
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+fn default_archive_max_mb() -> u64 {
+    64
+}
+
+fn default_content_hash_head_kb() -> u64 {
+    64
+}
+
+fn default_content_hash_max_file_mb() -> u64 {
+    1024
+}
+
+fn default_hook_debounce_ms() -> u64 {
+    500
+}
+
+fn default_journal_fsync() -> String {
+    "interval".to_string()
+}
+
+fn default_journal_fsync_interval_ms() -> u64 {
+    1_000
+}
+
+fn default_http_port() -> u16 {
+    8347
+}
+
 impl Config {
     /// Load configuration from a TOML file.
+    ///
+    /// Validation errors are reported all at once with `path:line:col`
+    /// prefixes; warnings are dropped (see [`Config::load_with_report`]).
     pub fn load(path: &std::path::Path) -> crate::Result<Self> {
+        Self::load_with_report(path).map(|(config, _)| config)
+    }
+
+    /// Load configuration and return the validation report alongside it so
+    /// callers can surface warnings.
+    pub fn load_with_report(
+        path: &std::path::Path,
+    ) -> crate::Result<(Self, crate::config_validation::ConfigReport)> {
         let content = std::fs::read_to_string(path)?;
+        let report = crate::config_validation::validate_str(&content);
+        if report.has_errors() {
+            return Err(crate::Error::Config(format!(
+                "invalid config\n{}",
+                report.render(path)
+            )));
+        }
+
         let mut config: Self =
             toml::from_str(&content).map_err(|e| crate::Error::Config(e.to_string()))?;
+        config.normalize_paths();
 
-        // Expand tilde (~) and environment variables in paths using shellexpand
-        config.expand_paths();
-        config.normalize_exclusions();
+        Ok((config, report))
+    }
 
-        Ok(config)
+    /// Expand `~` and environment variables in every path-like field and
+    /// normalize exclusions.
+    ///
+    /// `Config::load` already does this; components that may receive a
+    /// hand-built `Config` (scanner, watcher setup) call it again.
+    pub fn normalize_paths(&mut self) {
+        self.expand_paths();
+        self.normalize_exclusions();
     }
 
     /// Expand tilde (~) and environment variables in all path fields.
@@ -176,31 +1003,85 @@ impl Config {
             .map(|p| Self::expand_path(p.as_ref()))
             .collect();
 
+        self.root_max_depth = std::mem::take(&mut self.root_max_depth)
+            .into_iter()
+            .map(|(root, depth)| (Self::expand_path(&root), depth))
+            .collect();
+
         // Expand in index_path
         self.index_path = Self::expand_path(&self.index_path);
 
         if let Some(path) = self.content_search.rg_path.as_mut() {
             *path = Self::expand_path(path);
         }
+
+        for hook in &mut self.hooks {
+            hook.pattern = Self::expand_path(Path::new(&hook.pattern))
+                .to_string_lossy()
+                .into_owned();
+            if let Some(cwd) = hook.cwd.as_mut() {
+                *cwd = Self::expand_path(cwd);
+            }
+        }
+
+        for boost in &mut self.ranking.boost {
+            if let Some(pattern) = boost.pattern.as_mut() {
+                *pattern = Self::expand_path(Path::new(pattern.as_str()))
+                    .to_string_lossy()
+                    .into_owned();
+            }
+        }
     }
 
     fn normalize_exclusions(&mut self) {
         self.exclusions = self
             .exclusions
             .iter()
-            .map(|exclusion| crate::filter::normalize_exclusion(exclusion).to_string())
+            .map(|exclusion| {
+                let expanded = crate::paths::expand_pattern(exclusion);
+                crate::filter::normalize_exclusion(&expanded).to_string()
+            })
             .collect();
     }
 
     /// Expand tilde and environment variables in a single path.
     fn expand_path(path: &Path) -> PathBuf {
-        let path_str = path.to_string_lossy();
+        crate::paths::expand_user_path(path)
+    }
 
-        // Use shellexpand to handle ~, ~user, and $VAR expansion
-        match shellexpand::full(&path_str) {
-            Ok(expanded) => PathBuf::from(expanded.as_ref()),
-            Err(_) => path.to_path_buf(), // Fallback to original path on error
-        }
+    /// `index_roots` without duplicates or roots nested inside another root,
+    /// in configured order.
+    ///
+    /// The scanner and watcher walk this set so overlapping roots (`~` and
+    /// `~/Projects`) are not scanned or watched twice; `vicaya doctor` warns
+    /// about the redundant entries.
+    pub fn effective_roots(&self) -> Vec<PathBuf> {
+        let roots = &self.index_roots;
+        roots
+            .iter()
+            .enumerate()
+            .filter(|&(idx, root)| {
+                !roots.iter().enumerate().any(|(other_idx, other)| {
+                    if other == root {
+                        other_idx < idx
+                    } else {
+                        root.starts_with(other)
+                    }
+                })
+            })
+            .map(|(_, root)| root.clone())
+            .collect()
+    }
+
+    /// The `root_max_depth` of `root`, when it has one.
+    pub fn root_max_depth(&self, root: &Path) -> Option<usize> {
+        self.root_max_depth.get(root).copied()
+    }
+
+    /// Compiled exclusion patterns, honouring `case_insensitive_exclusions`.
+    pub fn exclusion_set(&self) -> Arc<ExclusionSet> {
+        self.exclusion_cache
+            .get(&self.exclusions, self.case_insensitive_exclusions)
     }
 
     /// Save configuration to a TOML file.
@@ -211,6 +1092,98 @@ impl Config {
         Ok(())
     }
 
+    /// Append exclusion patterns to the config file at `path`, keeping its
+    /// comments and layout. Patterns already listed are skipped; the ones
+    /// actually added are returned.
+    pub fn append_exclusions(path: &Path, patterns: &[String]) -> crate::Result<Vec<String>> {
+        let mut doc = Self::edit_document(path)?;
+        let item = doc
+            .entry("exclusions")
+            .or_insert(toml_edit::value(toml_edit::Array::new()));
+        let Some(array) = item.as_array_mut() else {
+            return Err(crate::Error::Config(
+                "`exclusions` is not an array".to_string(),
+            ));
+        };
+
+        // Keep one pattern per line in the multi-line layout `vicaya init` writes.
+        let multiline = array.trailing().as_str().unwrap_or("").contains('\n')
+            || array.iter().any(|value| {
+                value
+                    .decor()
+                    .prefix()
+                    .and_then(|prefix| prefix.as_str())
+                    .is_some_and(|prefix| prefix.contains('\n'))
+            });
+        let mut added = Vec::new();
+        for pattern in patterns {
+            let listed = array
+                .iter()
+                .any(|value| value.as_str() == Some(pattern.as_str()));
+            if listed || added.contains(pattern) {
+                continue;
+            }
+            let mut value = toml_edit::Value::from(pattern.as_str());
+            if multiline {
+                value.decor_mut().set_prefix("\n    ");
+            }
+            array.push_formatted(value);
+            added.push(pattern.clone());
+        }
+        if multiline && !added.is_empty() {
+            array.set_trailing_comma(true);
+            array.set_trailing("\n");
+        }
+
+        if !added.is_empty() {
+            std::fs::write(path, doc.to_string())?;
+        }
+        Ok(added)
+    }
+
+    /// Define or replace the query alias `name` in the config file at `path`,
+    /// keeping its comments and layout. Returns the previous expansion.
+    pub fn set_alias(path: &Path, name: &str, expansion: &str) -> crate::Result<Option<String>> {
+        let mut doc = Self::edit_document(path)?;
+        let table = Self::aliases_table(&mut doc)?;
+        let previous = table
+            .get(name)
+            .and_then(|item| item.as_str())
+            .map(str::to_string);
+        table.insert(name, toml_edit::value(expansion));
+        std::fs::write(path, doc.to_string())?;
+        Ok(previous)
+    }
+
+    /// Remove the query alias `name` from the config file at `path`. Returns
+    /// its expansion, or `None` when it was not defined.
+    pub fn remove_alias(path: &Path, name: &str) -> crate::Result<Option<String>> {
+        let mut doc = Self::edit_document(path)?;
+        if doc.get("aliases").is_none() {
+            return Ok(None);
+        }
+        let table = Self::aliases_table(&mut doc)?;
+        let Some(removed) = table.remove(name) else {
+            return Ok(None);
+        };
+        std::fs::write(path, doc.to_string())?;
+        Ok(removed.as_str().map(str::to_string))
+    }
+
+    fn edit_document(path: &Path) -> crate::Result<toml_edit::DocumentMut> {
+        let content = std::fs::read_to_string(path)?;
+        content
+            .parse()
+            .map_err(|e: toml_edit::TomlError| crate::Error::Config(e.to_string()))
+    }
+
+    fn aliases_table(doc: &mut toml_edit::DocumentMut) -> crate::Result<&mut toml_edit::Table> {
+        doc.entry("aliases")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .ok_or_else(|| crate::Error::Config("`aliases` is not a table".to_string()))
+    }
+
     /// Get the default index path.
     fn default_index_path() -> PathBuf {
         crate::paths::vicaya_dir().join("index")
@@ -222,6 +1195,19 @@ impl Config {
         Ok(())
     }
 
+    /// Applications configured for `path`'s extension, matched without
+    /// regard to case or a leading dot in the config key.
+    pub fn associations_for(&self, path: &Path) -> &[String] {
+        let Some(ext) = path.extension().and_then(|ext| ext.to_str()) else {
+            return &[];
+        };
+        self.associations
+            .iter()
+            .find(|(key, _)| key.trim_start_matches('.').eq_ignore_ascii_case(ext))
+            .map(|(_, apps)| apps.as_slice())
+            .unwrap_or(&[])
+    }
+
     /// Whether Smriti is enabled after environment overrides.
     pub fn smriti_enabled(&self) -> bool {
         self.smriti.enabled && std::env::var_os("VICAYA_NO_SMRITI").is_none()
@@ -232,6 +1218,15 @@ impl Config {
         self.content_search.enabled && std::env::var_os("VICAYA_NO_CONTENT_SEARCH").is_none()
     }
 
+    /// HTTP bearer token after the `VICAYA_HTTP_TOKEN` override; empty tokens
+    /// count as unset.
+    pub fn http_token(&self) -> Option<String> {
+        std::env::var("VICAYA_HTTP_TOKEN")
+            .ok()
+            .or_else(|| self.http.token.clone())
+            .filter(|token| !token.trim().is_empty())
+    }
+
     /// Configured content-search engine preference after environment overrides.
     pub fn content_search_engine(
         &self,
@@ -241,6 +1236,11 @@ impl Config {
         crate::content_search::ContentSearchEngineChoice::parse(&engine)
     }
 
+    /// Configured journal fsync policy.
+    pub fn journal_fsync(&self) -> crate::Result<JournalFsync> {
+        JournalFsync::parse(&self.journal.fsync, self.journal.fsync_interval_ms)
+    }
+
     /// Whether slow recursive grep fallback is allowed after environment overrides.
     pub fn content_search_allow_slow_fallback(&self) -> bool {
         if let Ok(value) = std::env::var("VICAYA_CONTENT_SEARCH_ALLOW_SLOW_FALLBACK") {
@@ -259,6 +1259,47 @@ mod tests {
     use super::*;
     use std::env;
 
+    #[test]
+    fn effective_roots_drop_duplicate_and_nested_roots() {
+        let config = Config {
+            index_roots: vec![
+                PathBuf::from("/Users/me/Projects/app"),
+                PathBuf::from("/Users/me/Projects"),
+                PathBuf::from("/Volumes/Data"),
+                PathBuf::from("/Users/me/Projects"),
+                PathBuf::from("/Users/me/Projects-old"),
+            ],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.effective_roots(),
+            vec![
+                PathBuf::from("/Users/me/Projects"),
+                PathBuf::from("/Volumes/Data"),
+                PathBuf::from("/Users/me/Projects-old"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_profiles_fall_back_to_scanner_threads() {
+        let performance: PerformanceConfig = toml::from_str(
+            "scanner_threads = 8\nreconcile_hour = 3\n\n[reconcile]\nthreads = 1\nthrottle_ms = 20\n",
+        )
+        .unwrap();
+
+        let initial = performance.scan_settings(ScanProfile::InitialScan);
+        assert_eq!((initial.threads, initial.throttle_ms), (8, 0));
+        let reconcile = performance.scan_settings(ScanProfile::Reconcile);
+        assert_eq!((reconcile.threads, reconcile.throttle_ms), (1, 20));
+        assert_eq!(
+            reconcile.to_string(),
+            "reconcile profile, 1 thread, 20 ms throttle"
+        );
+        assert_eq!(initial.to_string(), "initial_scan profile, 8 threads");
+    }
+
     #[test]
     fn test_expand_path_with_tilde() {
         let home = env::var("HOME").unwrap();
@@ -389,6 +1430,24 @@ rg_path = "~/bin/rg"
         assert_eq!(config.performance.reconcile_hour, 3);
         assert!(config.smriti.enabled);
         assert_eq!(config.smriti.max_entries, 10_000);
+        assert_eq!(
+            config.journal_fsync().unwrap(),
+            JournalFsync::Interval { interval_ms: 1_000 }
+        );
+    }
+
+    #[test]
+    fn test_journal_fsync_policy_parses_names() {
+        assert_eq!(JournalFsync::parse("none", 5).unwrap(), JournalFsync::None);
+        assert_eq!(
+            JournalFsync::parse("Every-Batch", 5).unwrap(),
+            JournalFsync::EveryBatch
+        );
+        assert_eq!(
+            JournalFsync::parse("interval", 250).unwrap(),
+            JournalFsync::Interval { interval_ms: 250 }
+        );
+        assert!(JournalFsync::parse("sometimes", 5).is_err());
     }
 
     #[test]
@@ -418,6 +1477,7 @@ enabled = false
         assert!(!config.smriti.enabled);
         assert_eq!(config.smriti.max_entries, 10_000);
         assert_eq!(config.smriti.max_boost, 0.08);
+        assert!(config.smriti.badges);
     }
 
     #[test]
@@ -431,14 +1491,51 @@ enabled = false
             index_roots: vec![PathBuf::from("/test/root")],
             exclusions: vec![".git".to_string(), "target".to_string()],
             respect_ignore_files: true,
+            case_insensitive_exclusions: false,
+            exclusion_cache: ExclusionCache::default(),
             index_path: PathBuf::from("/test/index"),
             max_memory_mb: 256,
+            max_indexed_files: 0,
             performance: PerformanceConfig {
                 scanner_threads: 8,
                 reconcile_hour: 2,
+                query_timeout_ms: 250,
+                query_max_candidates: 0,
+                warmup_trigrams: 0,
+                reconcile_schedule: Vec::new(),
+                reconcile_min_gap_minutes: 60,
+                reconcile_on_wake: true,
+                initial_scan: Default::default(),
+                reconcile: Default::default(),
             },
             smriti: SmritiConfig::default(),
             content_search: ContentSearchConfig::default(),
+            preview: PreviewConfig {
+                max_bytes: 64 * 1024,
+                max_lines: 500,
+            },
+            associations: BTreeMap::from([("md".to_string(), vec!["Typora".to_string()])]),
+            aliases: BTreeMap::from([("docs".to_string(), "path:Documents".to_string())]),
+            http: HttpConfig::default(),
+            journal: JournalConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            ranking: RankingConfig::default(),
+            trash: TrashConfig::default(),
+            normalization: NormalizationConfig::default(),
+            hooks: Vec::new(),
+            root_max_depth: Default::default(),
+            show_hidden: true,
+            restore_tui_session: false,
+            offline_while_starting: false,
+            auto_scope: AutoScope::default(),
+            icons: IconStyle::default(),
+            language: Default::default(),
+            archives: ArchiveConfig::default(),
+            content_hash: ContentHashConfig::default(),
+            index_bundle_contents: false,
+            index_backup_volumes: false,
+            index_directory_paths: false,
+            index_profile: IndexProfile::default(),
         };
 
         // Save
@@ -459,6 +1556,30 @@ enabled = false
             loaded_config.performance.reconcile_hour,
             config.performance.reconcile_hour
         );
+        assert_eq!(loaded_config.preview.max_bytes, 64 * 1024);
+        assert_eq!(loaded_config.preview.max_lines, 500);
+        assert_eq!(loaded_config.associations, config.associations);
+        assert_eq!(loaded_config.aliases, config.aliases);
+    }
+
+    #[test]
+    fn test_associations_match_extension_case_insensitively() {
+        let mut config = Config::default();
+        config.associations.insert(
+            ".MD".to_string(),
+            vec!["Typora".to_string(), "code".to_string()],
+        );
+
+        assert_eq!(
+            config.associations_for(Path::new("/notes/todo.md")),
+            ["Typora", "code"]
+        );
+        assert!(config
+            .associations_for(Path::new("/notes/todo.txt"))
+            .is_empty());
+        assert!(config
+            .associations_for(Path::new("/notes/Makefile"))
+            .is_empty());
     }
 
     #[test]
@@ -484,4 +1605,152 @@ reconcile_hour = 3
         let config = Config::load(temp_file.path()).unwrap();
         assert_eq!(config.exclusions, vec!["target", "node_modules", "*.log"]);
     }
+
+    #[test]
+    fn test_load_expands_env_vars_in_exclusions_and_trims_root_slashes() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let _lock = crate::paths::test_env_lock();
+        let home = env::var("HOME").unwrap();
+        env::set_var("VICAYA_TEST_EXCLUDE_DIR", "build-cache");
+
+        let config_content = r#"
+index_roots = ["~/", "$HOME/Projects/"]
+exclusions = ["${VICAYA_TEST_EXCLUDE_DIR}", "/$VICAYA_TEST_EXCLUDE_DIR*", "$VICAYA_TEST_SURELY_UNSET.BIN"]
+index_path = "~/Library/Application Support/vicaya/"
+max_memory_mb = 512
+
+[performance]
+scanner_threads = 4
+reconcile_hour = 3
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let config = Config::load(temp_file.path()).unwrap();
+        env::remove_var("VICAYA_TEST_EXCLUDE_DIR");
+
+        assert_eq!(
+            config.index_roots,
+            vec![
+                PathBuf::from(&home),
+                PathBuf::from(format!("{home}/Projects"))
+            ]
+        );
+        assert_eq!(
+            config.exclusions,
+            vec![
+                "build-cache",
+                "build-cache*",
+                "$VICAYA_TEST_SURELY_UNSET.BIN"
+            ]
+        );
+        assert!(!config.index_path.to_string_lossy().ends_with('/'));
+    }
+
+    #[test]
+    fn append_exclusions_keeps_comments_and_skips_listed_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "index_roots = [\"~\"]\nexclusions = [\n    # Dependencies\n    \"node_modules\",\n]\nindex_path = \"/tmp/vicaya\"\nmax_memory_mb = 512\n\n[performance]\nscanner_threads = 4\nbatch_size = 1000\nfs_event_debounce_ms = 200\nreconcile_hour = 3\n",
+        )
+        .unwrap();
+
+        let added = Config::append_exclusions(
+            &path,
+            &[
+                "node_modules".to_string(),
+                "DerivedData".to_string(),
+                "Caches".to_string(),
+            ],
+        )
+        .unwrap();
+        assert_eq!(added, vec!["DerivedData", "Caches"]);
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("# Dependencies"), "{content}");
+        assert!(
+            content.contains("    \"DerivedData\",\n    \"Caches\",\n]"),
+            "{content}"
+        );
+        let config = Config::load(&path).unwrap();
+        assert_eq!(
+            config.exclusions,
+            vec!["node_modules", "DerivedData", "Caches"]
+        );
+        assert!(Config::append_exclusions(&path, &["Caches".to_string()])
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn aliases_are_added_replaced_and_removed_in_place() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "# my config\nindex_roots = [\"~\"]\nexclusions = []\nindex_path = \"/tmp/vicaya\"\nmax_memory_mb = 512\n\n[performance]\nscanner_threads = 4\nreconcile_hour = 3\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Config::set_alias(&path, "docs", "path:Documents").unwrap(),
+            None
+        );
+        assert_eq!(
+            Config::set_alias(&path, "docs", "path:Docs").unwrap(),
+            Some("path:Documents".to_string())
+        );
+        Config::set_alias(&path, "cfg", "ext:toml type:file").unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# my config"), "{content}");
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.aliases["docs"], "path:Docs");
+        assert_eq!(config.aliases["cfg"], "ext:toml type:file");
+
+        assert_eq!(
+            Config::remove_alias(&path, "docs").unwrap(),
+            Some("path:Docs".to_string())
+        );
+        assert_eq!(Config::remove_alias(&path, "docs").unwrap(), None);
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.aliases.keys().collect::<Vec<_>>(), ["cfg"]);
+    }
+
+    #[test]
+    fn test_load_reports_all_validation_errors_with_locations() {
+        use std::io::Write;
+        use tempfile::NamedTempFile;
+
+        let config_content = r#"
+index_roots = ["~"]
+exclusions = ["target"]
+index_path = "~/Library/Application Support/vicaya"
+max_memory_mb = 512
+
+[performance]
+scanner_threads = -4
+reconcile_hour = 30
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(config_content.as_bytes()).unwrap();
+        temp_file.flush().unwrap();
+
+        let err = Config::load(temp_file.path()).unwrap_err().to_string();
+        assert!(
+            err.contains(":9:18: error: performance.reconcile_hour"),
+            "{err}"
+        );
+        assert!(
+            err.contains(":8:19: error: performance.scanner_threads"),
+            "{err}"
+        );
+    }
 }
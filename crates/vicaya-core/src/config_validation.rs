@@ -0,0 +1,1469 @@
+//! Config file validation with line/column diagnostics.
+//!
+//! `toml::from_str` stops at the first problem and knows nothing about
+//! vicaya semantics (overlapping roots, exclusion globs that do not compile,
+//! out-of-range performance knobs). This module walks
+//! the raw TOML document instead, so every problem can be reported at once
+//! and pointed at the exact place in the file.
+
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use toml_edit::{ImDocument, Item, Table, Value};
+
+use crate::schedule::CronSchedule;
+
+/// How serious a validation finding is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The config cannot be used as written.
+    Error,
+    /// The config loads, but probably does not do what was intended.
+    Warning,
+}
+
+impl Severity {
+    /// Lowercase label used in rendered diagnostics.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// 1-based position inside the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// One validation finding.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// Dotted key path, e.g. `performance.reconcile_hour` or `exclusions[2]`.
+    pub key: String,
+    /// Where the offending key or value starts, when it exists in the file.
+    pub location: Option<Location>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = self.location {
+            write!(f, "{}:{}: ", location.line, location.column)?;
+        }
+        write!(f, "{}: ", self.severity.label())?;
+        if !self.key.is_empty() {
+            write!(f, "{}: ", self.key)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// All findings for one config file, in file order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    /// Whether any finding prevents the config from being used.
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Findings with [`Severity::Error`].
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    /// Findings with [`Severity::Warning`].
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    /// Render every finding as `path:line:col: severity: key: message`.
+    pub fn render(&self, path: &Path) -> String {
+        self.issues
+            .iter()
+            .map(|issue| format!("{}:{issue}", path.display()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Validate a config file on disk.
+pub fn validate_file(path: &Path) -> crate::Result<ConfigReport> {
+    let content = std::fs::read_to_string(path)?;
+    Ok(validate_str(&content))
+}
+
+/// Validate config file contents without touching the filesystem beyond
+/// checking whether configured roots exist.
+pub fn validate_str(content: &str) -> ConfigReport {
+    let mut validator = Validator {
+        source: content,
+        issues: Vec::new(),
+    };
+
+    match ImDocument::parse(content) {
+        Ok(doc) => validator.document(doc.as_table()),
+        Err(err) => validator.push(
+            Severity::Error,
+            String::new(),
+            err.span(),
+            format!("invalid TOML: {}", err.message().trim_end()),
+        ),
+    }
+
+    validator.issues.sort_by_key(|issue| {
+        issue
+            .location
+            .map(|loc| (loc.line, loc.column))
+            .unwrap_or((0, 0))
+    });
+    ConfigReport {
+        issues: validator.issues,
+    }
+}
+
+const TOP_LEVEL_KEYS: &[&str] = &[
+    "index_roots",
+    "exclusions",
+    "respect_ignore_files",
+    "case_insensitive_exclusions",
+    "index_bundle_contents",
+    "index_backup_volumes",
+    "index_directory_paths",
+    "index_profile",
+    "index_path",
+    "max_memory_mb",
+    "max_indexed_files",
+    "root_max_depth",
+    "show_hidden",
+    "auto_scope",
+    "icons",
+    "language",
+    "offline_while_starting",
+    "restore_tui_session",
+    "performance",
+    "smriti",
+    "content_search",
+    "preview",
+    "archives",
+    "content_hash",
+    "associations",
+    "aliases",
+    "http",
+    "journal",
+    "telemetry",
+    "ranking",
+    "trash",
+    "normalization",
+    "hooks",
+];
+const PERFORMANCE_KEYS: &[&str] = &[
+    "scanner_threads",
+    "reconcile_hour",
+    "query_timeout_ms",
+    "query_max_candidates",
+    "warmup_trigrams",
+    "reconcile_schedule",
+    "reconcile_min_gap_minutes",
+    "reconcile_on_wake",
+    "initial_scan",
+    "reconcile",
+];
+const SCAN_SETTINGS_KEYS: &[&str] = &["threads", "throttle_ms"];
+const SMRITI_KEYS: &[&str] = &["enabled", "max_entries", "max_boost", "badges"];
+const CONTENT_SEARCH_KEYS: &[&str] = &["enabled", "engine", "allow_slow_fallback", "rg_path"];
+const PREVIEW_KEYS: &[&str] = &["max_bytes", "max_lines"];
+const ARCHIVE_KEYS: &[&str] = &["enabled", "extensions", "max_archive_mb"];
+const CONTENT_HASH_KEYS: &[&str] = &["enabled", "head_kb", "max_file_mb"];
+const HTTP_KEYS: &[&str] = &["enabled", "port", "token"];
+const JOURNAL_KEYS: &[&str] = &["fsync", "fsync_interval_ms"];
+const TELEMETRY_KEYS: &[&str] = &["enabled"];
+const RANKING_KEYS: &[&str] = &[
+    "directory_weight",
+    "min_score",
+    "suggestions",
+    "boost",
+    "recent_scope_boost",
+    "recent_scope_half_life_mins",
+    "auto_broaden",
+];
+const RANKING_BOOST_KEYS: &[&str] = &["pattern", "ext", "factor"];
+const TRASH_KEYS: &[&str] = &["index", "recently_deleted"];
+const NORMALIZATION_KEYS: &[&str] = &["fold_diacritics", "fold_separators"];
+const HOOK_KEYS: &[&str] = &["pattern", "command", "debounce_ms", "cwd"];
+
+const MAX_SCANNER_THREADS: i64 = 1024;
+const MAX_SCAN_THROTTLE_MS: i64 = 60_000;
+const MIN_SENSIBLE_MEMORY_MB: i64 = 64;
+const MAX_QUERY_TIMEOUT_MS: i64 = 60_000;
+const MAX_PREVIEW_BYTES: i64 = 64 * 1024 * 1024;
+const MAX_PREVIEW_LINES: i64 = 1_000_000;
+const MAX_CONTENT_HASH_HEAD_KB: i64 = 64 * 1024;
+const MAX_JOURNAL_FSYNC_INTERVAL_MS: i64 = 600_000;
+const MAX_HOOK_DEBOUNCE_MS: i64 = 3_600_000;
+const MAX_DIRECTORY_WEIGHT: i64 = 1_000;
+const MAX_SUGGESTIONS: i64 = 50;
+const MAX_BOOST_FACTOR: f64 = 10.0;
+const MAX_RECENT_SCOPE_HALF_LIFE_MINS: i64 = 7 * 24 * 60;
+const MAX_RECENTLY_DELETED: i64 = 100_000;
+const MAX_RECONCILE_MIN_GAP_MINUTES: i64 = 7 * 24 * 60;
+
+struct Validator<'a> {
+    source: &'a str,
+    issues: Vec<ConfigIssue>,
+}
+
+impl Validator<'_> {
+    fn push(
+        &mut self,
+        severity: Severity,
+        key: impl Into<String>,
+        span: Option<Range<usize>>,
+        message: impl Into<String>,
+    ) {
+        let location = span.map(|span| self.location(span.start));
+        self.issues.push(ConfigIssue {
+            severity,
+            key: key.into(),
+            location,
+            message: message.into(),
+        });
+    }
+
+    fn location(&self, offset: usize) -> Location {
+        let before = &self.source[..offset.min(self.source.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|idx| idx + 1).unwrap_or(0);
+        Location {
+            line,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    fn document(&mut self, root: &Table) {
+        self.unknown_keys(root, "", TOP_LEVEL_KEYS);
+
+        if let Some(item) = self.required(root, "", "index_roots", None) {
+            self.index_roots(item);
+        }
+
+        if let Some(item) = self.required(root, "", "exclusions", None) {
+            self.exclusions(item);
+        }
+
+        if let Some(item) = root.get("respect_ignore_files") {
+            self.expect_bool("respect_ignore_files", item);
+        }
+
+        if let Some(item) = root.get("case_insensitive_exclusions") {
+            self.expect_bool("case_insensitive_exclusions", item);
+        }
+        if let Some(item) = root.get("index_bundle_contents") {
+            self.expect_bool("index_bundle_contents", item);
+        }
+        if let Some(item) = root.get("index_backup_volumes") {
+            self.expect_bool("index_backup_volumes", item);
+        }
+        if let Some(item) = root.get("index_directory_paths") {
+            self.expect_bool("index_directory_paths", item);
+        }
+
+        if let Some(item) = root.get("index_profile") {
+            if let Some(profile) = self.expect_str("index_profile", item) {
+                if let Err(err) = profile.parse::<crate::config::IndexProfile>() {
+                    self.push(
+                        Severity::Error,
+                        "index_profile",
+                        item.span(),
+                        config_message(err),
+                    );
+                }
+            }
+        }
+
+        if let Some(item) = self.required(root, "", "index_path", None) {
+            self.path("index_path", item);
+        }
+
+        if let Some(item) = self.required(root, "", "max_memory_mb", None) {
+            if let Some(mb) = self.expect_integer("max_memory_mb", item, 1, i64::MAX) {
+                if mb < MIN_SENSIBLE_MEMORY_MB {
+                    self.push(
+                        Severity::Warning,
+                        "max_memory_mb",
+                        item.span(),
+                        format!("{mb} MB is unusually low; the index alone may not fit"),
+                    );
+                }
+            }
+        }
+
+        if let Some(item) = root.get("max_indexed_files") {
+            self.expect_integer("max_indexed_files", item, 0, i64::MAX);
+        }
+
+        if let Some(item) = root.get("show_hidden") {
+            self.expect_bool("show_hidden", item);
+        }
+
+        if let Some(item) = root.get("offline_while_starting") {
+            self.expect_bool("offline_while_starting", item);
+        }
+
+        if let Some(item) = root.get("restore_tui_session") {
+            self.expect_bool("restore_tui_session", item);
+        }
+
+        if let Some(item) = root.get("root_max_depth") {
+            if let Some(table) = self.expect_table("root_max_depth", item) {
+                for (root, item) in table.iter() {
+                    self.expect_integer(&join_key("root_max_depth", root), item, 1, i64::MAX);
+                }
+            }
+        }
+
+        if let Some(item) = root.get("auto_scope") {
+            if let Some(mode) = self.expect_str("auto_scope", item) {
+                if let Err(err) = mode.parse::<crate::config::AutoScope>() {
+                    self.push(
+                        Severity::Error,
+                        "auto_scope",
+                        item.span(),
+                        config_message(err),
+                    );
+                }
+            }
+        }
+
+        if let Some(item) = root.get("icons") {
+            if let Some(style) = self.expect_str("icons", item) {
+                if let Err(err) = style.parse::<crate::config::IconStyle>() {
+                    self.push(Severity::Error, "icons", item.span(), config_message(err));
+                }
+            }
+        }
+
+        if let Some(item) = root.get("language") {
+            if let Some(language) = self.expect_str("language", item) {
+                if let Err(err) = language.parse::<crate::i18n::Language>() {
+                    self.push(
+                        Severity::Error,
+                        "language",
+                        item.span(),
+                        config_message(err),
+                    );
+                }
+            }
+        }
+
+        if let Some(item) = self.required(root, "", "performance", None) {
+            if let Some(table) = self.expect_table("performance", item) {
+                self.performance(table);
+            }
+        }
+
+        if let Some(item) = root.get("smriti") {
+            if let Some(table) = self.expect_table("smriti", item) {
+                self.smriti(table);
+            }
+        }
+
+        if let Some(item) = root.get("content_search") {
+            if let Some(table) = self.expect_table("content_search", item) {
+                self.content_search(table);
+            }
+        }
+
+        if let Some(item) = root.get("preview") {
+            if let Some(table) = self.expect_table("preview", item) {
+                self.preview(table);
+            }
+        }
+
+        if let Some(item) = root.get("archives") {
+            if let Some(table) = self.expect_table("archives", item) {
+                self.archives(table);
+            }
+        }
+
+        if let Some(item) = root.get("content_hash") {
+            if let Some(table) = self.expect_table("content_hash", item) {
+                self.content_hash(table);
+            }
+        }
+
+        if let Some(item) = root.get("http") {
+            if let Some(table) = self.expect_table("http", item) {
+                self.http(table);
+            }
+        }
+
+        if let Some(item) = root.get("journal") {
+            if let Some(table) = self.expect_table("journal", item) {
+                self.journal(table);
+            }
+        }
+
+        if let Some(item) = root.get("telemetry") {
+            if let Some(table) = self.expect_table("telemetry", item) {
+                self.unknown_keys(table, "telemetry", TELEMETRY_KEYS);
+                if let Some(item) = table.get("enabled") {
+                    self.expect_bool("telemetry.enabled", item);
+                }
+            }
+        }
+
+        if let Some(item) = root.get("ranking") {
+            if let Some(table) = self.expect_table("ranking", item) {
+                self.unknown_keys(table, "ranking", RANKING_KEYS);
+                if let Some(item) = table.get("directory_weight") {
+                    self.expect_integer("ranking.directory_weight", item, 0, MAX_DIRECTORY_WEIGHT);
+                }
+                if let Some(item) = table.get("min_score") {
+                    self.expect_float("ranking.min_score", item, 0.0, 1.0);
+                }
+                if let Some(item) = table.get("suggestions") {
+                    self.expect_integer("ranking.suggestions", item, 0, MAX_SUGGESTIONS);
+                }
+                if let Some(item) = table.get("boost") {
+                    self.ranking_boosts(item);
+                }
+                if let Some(item) = table.get("recent_scope_boost") {
+                    self.expect_float("ranking.recent_scope_boost", item, 0.0, 1.0);
+                }
+                if let Some(item) = table.get("recent_scope_half_life_mins") {
+                    self.expect_integer(
+                        "ranking.recent_scope_half_life_mins",
+                        item,
+                        1,
+                        MAX_RECENT_SCOPE_HALF_LIFE_MINS,
+                    );
+                }
+                if let Some(item) = table.get("auto_broaden") {
+                    self.expect_bool("ranking.auto_broaden", item);
+                }
+            }
+        }
+
+        if let Some(item) = root.get("trash") {
+            if let Some(table) = self.expect_table("trash", item) {
+                self.unknown_keys(table, "trash", TRASH_KEYS);
+                if let Some(item) = table.get("index") {
+                    self.expect_bool("trash.index", item);
+                }
+                if let Some(item) = table.get("recently_deleted") {
+                    self.expect_integer("trash.recently_deleted", item, 0, MAX_RECENTLY_DELETED);
+                }
+            }
+        }
+
+        if let Some(item) = root.get("normalization") {
+            if let Some(table) = self.expect_table("normalization", item) {
+                self.unknown_keys(table, "normalization", NORMALIZATION_KEYS);
+                for key in NORMALIZATION_KEYS {
+                    if let Some(item) = table.get(key) {
+                        self.expect_bool(&format!("normalization.{key}"), item);
+                    }
+                }
+            }
+        }
+
+        if let Some(item) = root.get("hooks") {
+            self.hooks(item);
+        }
+
+        if let Some(item) = root.get("associations") {
+            if let Some(table) = self.expect_table("associations", item) {
+                self.associations(table);
+            }
+        }
+
+        if let Some(item) = root.get("aliases") {
+            if let Some(table) = self.expect_table("aliases", item) {
+                self.aliases(table);
+            }
+        }
+    }
+
+    fn performance(&mut self, table: &Table) {
+        self.unknown_keys(table, "performance", PERFORMANCE_KEYS);
+        let header = table.span();
+
+        if let Some(item) = self.required(table, "performance", "scanner_threads", header.clone()) {
+            self.expect_integer("performance.scanner_threads", item, 1, MAX_SCANNER_THREADS);
+        }
+        if let Some(item) = self.required(table, "performance", "reconcile_hour", header) {
+            self.expect_integer("performance.reconcile_hour", item, 0, 23);
+        }
+        if let Some(item) = table.get("query_timeout_ms") {
+            self.expect_integer(
+                "performance.query_timeout_ms",
+                item,
+                0,
+                MAX_QUERY_TIMEOUT_MS,
+            );
+        }
+        if let Some(item) = table.get("query_max_candidates") {
+            self.expect_integer("performance.query_max_candidates", item, 0, i64::MAX);
+        }
+        if let Some(item) = table.get("warmup_trigrams") {
+            self.expect_integer("performance.warmup_trigrams", item, 0, i64::MAX);
+        }
+        if let Some(item) = table.get("reconcile_schedule") {
+            if let Some(array) = self.expect_array("performance.reconcile_schedule", item) {
+                for (idx, value) in array.iter().enumerate() {
+                    let key = format!("performance.reconcile_schedule[{idx}]");
+                    match value.as_str().map(str::parse::<CronSchedule>) {
+                        Some(Ok(_)) => {}
+                        Some(Err(reason)) => self.push(Severity::Error, key, value.span(), reason),
+                        None => self.push(
+                            Severity::Error,
+                            key,
+                            value.span(),
+                            format!("expected a string, found {}", value.type_name()),
+                        ),
+                    }
+                }
+            }
+        }
+        if let Some(item) = table.get("reconcile_min_gap_minutes") {
+            self.expect_integer(
+                "performance.reconcile_min_gap_minutes",
+                item,
+                0,
+                MAX_RECONCILE_MIN_GAP_MINUTES,
+            );
+        }
+        if let Some(item) = table.get("reconcile_on_wake") {
+            self.expect_bool("performance.reconcile_on_wake", item);
+        }
+        for section in ["initial_scan", "reconcile"] {
+            if let Some(item) = table.get(section) {
+                let name = format!("performance.{section}");
+                if let Some(table) = self.expect_table(&name, item) {
+                    self.scan_settings(&name, table);
+                }
+            }
+        }
+    }
+
+    fn scan_settings(&mut self, name: &str, table: &Table) {
+        self.unknown_keys(table, name, SCAN_SETTINGS_KEYS);
+
+        if let Some(item) = table.get("threads") {
+            self.expect_integer(&format!("{name}.threads"), item, 1, MAX_SCANNER_THREADS);
+        }
+        if let Some(item) = table.get("throttle_ms") {
+            self.expect_integer(
+                &format!("{name}.throttle_ms"),
+                item,
+                0,
+                MAX_SCAN_THROTTLE_MS,
+            );
+        }
+    }
+
+    fn smriti(&mut self, table: &Table) {
+        self.unknown_keys(table, "smriti", SMRITI_KEYS);
+
+        if let Some(item) = table.get("enabled") {
+            self.expect_bool("smriti.enabled", item);
+        }
+        if let Some(item) = table.get("max_entries") {
+            if self.expect_integer("smriti.max_entries", item, 0, i64::MAX) == Some(0) {
+                self.push(
+                    Severity::Warning,
+                    "smriti.max_entries",
+                    item.span(),
+                    "0 entries remembers nothing; set `enabled = false` instead",
+                );
+            }
+        }
+        if let Some(item) = table.get("max_boost") {
+            self.expect_float("smriti.max_boost", item, 0.0, 1.0);
+        }
+        if let Some(item) = table.get("badges") {
+            self.expect_bool("smriti.badges", item);
+        }
+    }
+
+    fn content_search(&mut self, table: &Table) {
+        self.unknown_keys(table, "content_search", CONTENT_SEARCH_KEYS);
+
+        if let Some(item) = table.get("enabled") {
+            self.expect_bool("content_search.enabled", item);
+        }
+        if let Some(item) = table.get("engine") {
+            if let Some(engine) = self.expect_str("content_search.engine", item) {
+                if let Err(err) = crate::content_search::ContentSearchEngineChoice::parse(engine) {
+                    let message = config_message(err);
+                    self.push(
+                        Severity::Error,
+                        "content_search.engine",
+                        item.span(),
+                        format!("{message} (expected auto, ripgrep, git-grep, or grep)"),
+                    );
+                }
+            }
+        }
+        if let Some(item) = table.get("allow_slow_fallback") {
+            self.expect_bool("content_search.allow_slow_fallback", item);
+        }
+        if let Some(item) = table.get("rg_path") {
+            self.path("content_search.rg_path", item);
+        }
+    }
+
+    fn preview(&mut self, table: &Table) {
+        self.unknown_keys(table, "preview", PREVIEW_KEYS);
+
+        if let Some(item) = table.get("max_bytes") {
+            self.expect_integer("preview.max_bytes", item, 1, MAX_PREVIEW_BYTES);
+        }
+        if let Some(item) = table.get("max_lines") {
+            self.expect_integer("preview.max_lines", item, 1, MAX_PREVIEW_LINES);
+        }
+    }
+
+    fn archives(&mut self, table: &Table) {
+        self.unknown_keys(table, "archives", ARCHIVE_KEYS);
+
+        if let Some(item) = table.get("enabled") {
+            self.expect_bool("archives.enabled", item);
+        }
+        if let Some(item) = table.get("extensions") {
+            if let Some(array) = self.expect_array("archives.extensions", item) {
+                for (idx, value) in array.iter().enumerate() {
+                    let key = format!("archives.extensions[{idx}]");
+                    match value.as_str() {
+                        Some(ext) if ext.trim().trim_start_matches('.').is_empty() => {
+                            self.push(Severity::Error, key, value.span(), "extension is empty");
+                        }
+                        Some(_) => {}
+                        None => self.push(
+                            Severity::Error,
+                            key,
+                            value.span(),
+                            format!("expected a string, found {}", value.type_name()),
+                        ),
+                    }
+                }
+            }
+        }
+        if let Some(item) = table.get("max_archive_mb") {
+            self.expect_integer("archives.max_archive_mb", item, 1, i64::MAX);
+        }
+    }
+
+    fn content_hash(&mut self, table: &Table) {
+        self.unknown_keys(table, "content_hash", CONTENT_HASH_KEYS);
+
+        if let Some(item) = table.get("enabled") {
+            self.expect_bool("content_hash.enabled", item);
+        }
+        if let Some(item) = table.get("head_kb") {
+            self.expect_integer("content_hash.head_kb", item, 1, MAX_CONTENT_HASH_HEAD_KB);
+        }
+        if let Some(item) = table.get("max_file_mb") {
+            self.expect_integer("content_hash.max_file_mb", item, 1, i64::MAX);
+        }
+    }
+
+    fn http(&mut self, table: &Table) {
+        self.unknown_keys(table, "http", HTTP_KEYS);
+
+        let enabled = table
+            .get("enabled")
+            .and_then(|item| self.expect_bool("http.enabled", item))
+            .unwrap_or(false);
+        if let Some(item) = table.get("port") {
+            self.expect_integer("http.port", item, 1, u16::MAX as i64);
+        }
+        let token = table.get("token").and_then(|item| {
+            self.expect_str("http.token", item)
+                .map(|token| (item, token))
+        });
+        match token {
+            Some((item, token)) if token.trim().is_empty() => {
+                self.push(Severity::Error, "http.token", item.span(), "token is empty");
+            }
+            Some(_) => {}
+            None if enabled => {
+                let span = table.get("enabled").and_then(|item| item.span());
+                self.push(
+                    Severity::Warning,
+                    "http.enabled",
+                    span,
+                    "no `token` set; the endpoint stays off unless VICAYA_HTTP_TOKEN is exported",
+                );
+            }
+            None => {}
+        }
+    }
+
+    fn journal(&mut self, table: &Table) {
+        self.unknown_keys(table, "journal", JOURNAL_KEYS);
+
+        if let Some(item) = table.get("fsync") {
+            if let Some(policy) = self.expect_str("journal.fsync", item) {
+                if let Err(err) = crate::config::JournalFsync::parse(policy, 0) {
+                    let message = config_message(err);
+                    self.push(
+                        Severity::Error,
+                        "journal.fsync",
+                        item.span(),
+                        format!("{message} (expected none, interval, or every-batch)"),
+                    );
+                }
+            }
+        }
+        if let Some(item) = table.get("fsync_interval_ms") {
+            self.expect_integer(
+                "journal.fsync_interval_ms",
+                item,
+                1,
+                MAX_JOURNAL_FSYNC_INTERVAL_MS,
+            );
+        }
+    }
+
+    fn hooks(&mut self, item: &Item) {
+        // `hooks = [{ ... }]` is the inline spelling of `[[hooks]]`.
+        let hooks = match item.clone().into_array_of_tables() {
+            Ok(hooks) => hooks,
+            Err(_) => {
+                self.type_mismatch("hooks", item, "an array of tables ([[hooks]])");
+                return;
+            }
+        };
+        for (idx, table) in hooks.iter().enumerate() {
+            let prefix = format!("hooks[{idx}]");
+            self.unknown_keys(table, &prefix, HOOK_KEYS);
+            let header = table.span();
+
+            if let Some(item) = self.required(table, &prefix, "pattern", header.clone()) {
+                let key = join_key(&prefix, "pattern");
+                if let Some(raw) = self.expect_str(&key, item) {
+                    let checked =
+                        crate::paths::try_expand_user_path(Path::new(raw)).and_then(|expanded| {
+                            crate::hooks::HookPattern::new(&expanded.to_string_lossy())
+                        });
+                    if let Err(err) = checked {
+                        self.push(Severity::Error, key, item.span(), config_message(err));
+                    }
+                }
+            }
+            if let Some(item) = self.required(table, &prefix, "command", header) {
+                let key = join_key(&prefix, "command");
+                if let Some(command) = self.expect_str(&key, item) {
+                    if command.trim().is_empty() {
+                        self.push(Severity::Error, key, item.span(), "command is empty");
+                    }
+                }
+            }
+            if let Some(item) = table.get("debounce_ms") {
+                self.expect_integer(
+                    &join_key(&prefix, "debounce_ms"),
+                    item,
+                    0,
+                    MAX_HOOK_DEBOUNCE_MS,
+                );
+            }
+            if let Some(item) = table.get("cwd") {
+                self.path(&join_key(&prefix, "cwd"), item);
+            }
+        }
+    }
+
+    fn ranking_boosts(&mut self, item: &Item) {
+        // `boost = [{ ... }]`, or `[[ranking.boost]]` tables.
+        let boosts = match item.clone().into_array_of_tables() {
+            Ok(boosts) => boosts,
+            Err(_) => {
+                self.type_mismatch("ranking.boost", item, "an array of tables");
+                return;
+            }
+        };
+        for (idx, table) in boosts.iter().enumerate() {
+            let prefix = format!("ranking.boost[{idx}]");
+            self.unknown_keys(table, &prefix, RANKING_BOOST_KEYS);
+            let header = table.span();
+
+            match (table.get("pattern"), table.get("ext")) {
+                (Some(item), None) => {
+                    let key = join_key(&prefix, "pattern");
+                    if let Some(raw) = self.expect_str(&key, item) {
+                        let checked = crate::paths::try_expand_user_path(Path::new(raw)).and_then(
+                            |expanded| crate::hooks::HookPattern::new(&expanded.to_string_lossy()),
+                        );
+                        if let Err(err) = checked {
+                            self.push(Severity::Error, key, item.span(), config_message(err));
+                        }
+                    }
+                }
+                (None, Some(item)) => {
+                    let key = join_key(&prefix, "ext");
+                    if let Some(ext) = self.expect_str(&key, item) {
+                        if ext.trim_start_matches('.').trim().is_empty() {
+                            self.push(Severity::Error, key, item.span(), "extension is empty");
+                        }
+                    }
+                }
+                (Some(_), Some(item)) => self.push(
+                    Severity::Error,
+                    join_key(&prefix, "ext"),
+                    item.span(),
+                    "set either `pattern` or `ext`, not both",
+                ),
+                (None, None) => self.push(
+                    Severity::Error,
+                    prefix.clone(),
+                    header.clone(),
+                    "needs a `pattern` or an `ext`",
+                ),
+            }
+            if let Some(item) = self.required(table, &prefix, "factor", header) {
+                self.expect_float(&join_key(&prefix, "factor"), item, 0.0, MAX_BOOST_FACTOR);
+            }
+        }
+    }
+
+    fn associations(&mut self, table: &Table) {
+        for (ext, item) in table.iter() {
+            let key = join_key("associations", ext);
+            let Some(array) = self.expect_array(&key, item) else {
+                continue;
+            };
+            for (idx, value) in array.iter().enumerate() {
+                let key = format!("{key}[{idx}]");
+                match value.as_str() {
+                    Some(app) if app.trim().is_empty() => {
+                        self.push(Severity::Error, key, value.span(), "application is empty");
+                    }
+                    Some(_) => {}
+                    None => self.push(
+                        Severity::Error,
+                        key,
+                        value.span(),
+                        format!("expected a string, found {}", value.type_name()),
+                    ),
+                }
+            }
+        }
+    }
+
+    fn aliases(&mut self, table: &Table) {
+        for (name, item) in table.iter() {
+            let key = join_key("aliases", name);
+            if !crate::aliases::is_valid_name(name) {
+                let span = table.key(name).and_then(|key| key.span());
+                self.push(
+                    Severity::Error,
+                    key,
+                    span,
+                    "alias names may only use letters, digits, `-` and `_`",
+                );
+                continue;
+            }
+            match item.as_str() {
+                Some(expansion) if expansion.trim().is_empty() => {
+                    self.push(Severity::Error, key, item.span(), "expansion is empty");
+                }
+                Some(_) => {}
+                None => self.push(
+                    Severity::Error,
+                    key,
+                    item.span(),
+                    format!("expected a string, found {}", item.type_name()),
+                ),
+            }
+        }
+    }
+
+    fn index_roots(&mut self, item: &Item) {
+        let Some(array) = self.expect_array("index_roots", item) else {
+            return;
+        };
+        if array.is_empty() {
+            self.push(
+                Severity::Error,
+                "index_roots",
+                item.span(),
+                "at least one root is required",
+            );
+            return;
+        }
+
+        let mut roots: Vec<(usize, PathBuf, Option<Range<usize>>)> = Vec::new();
+        for (idx, value) in array.iter().enumerate() {
+            let key = format!("index_roots[{idx}]");
+            let Some(expanded) = self.path_value(&key, value) else {
+                continue;
+            };
+            if !expanded.exists() {
+                self.push(
+                    Severity::Warning,
+                    key.clone(),
+                    value.span(),
+                    format!("root `{}` does not exist", expanded.display()),
+                );
+            }
+            roots.push((idx, expanded, value.span()));
+        }
+
+        for (idx, path, span) in &roots {
+            let covering = roots
+                .iter()
+                .filter(|(other_idx, _, _)| other_idx != idx)
+                .find(|(other_idx, other, _)| {
+                    if other == path {
+                        other_idx < idx
+                    } else {
+                        path.starts_with(other)
+                    }
+                });
+            if let Some((other_idx, other, _)) = covering {
+                let message = if other == path {
+                    format!("duplicate of index_roots[{other_idx}]")
+                } else {
+                    format!(
+                        "already covered by index_roots[{other_idx}] (`{}`); files would be indexed twice",
+                        other.display()
+                    )
+                };
+                self.push(
+                    Severity::Warning,
+                    format!("index_roots[{idx}]"),
+                    span.clone(),
+                    message,
+                );
+            }
+        }
+    }
+
+    fn exclusions(&mut self, item: &Item) {
+        let Some(array) = self.expect_array("exclusions", item) else {
+            return;
+        };
+        for (idx, value) in array.iter().enumerate() {
+            let key = format!("exclusions[{idx}]");
+            let Some(raw) = value.as_str() else {
+                self.push(
+                    Severity::Error,
+                    key,
+                    value.span(),
+                    format!("expected a string, found {}", value.type_name()),
+                );
+                continue;
+            };
+            // Exclusions may name literal `$` directories, so an unset
+            // variable is kept as written rather than refused.
+            if let Err(err) = crate::paths::try_expand_pattern(raw) {
+                self.push(
+                    Severity::Warning,
+                    key.clone(),
+                    value.span(),
+                    format!("{}; matching it literally", config_message(err)),
+                );
+            }
+            let expanded = crate::paths::expand_pattern(raw);
+            if let Some((severity, message)) = check_exclusion(&expanded) {
+                self.push(severity, key, value.span(), message);
+            }
+        }
+    }
+
+    fn path(&mut self, key: &str, item: &Item) {
+        match item.as_value() {
+            Some(value) => {
+                self.path_value(key, value);
+            }
+            None => self.type_mismatch(key, item, "a path string"),
+        }
+    }
+
+    /// Check a path value expands cleanly and return the expanded path.
+    fn path_value(&mut self, key: &str, value: &Value) -> Option<PathBuf> {
+        let Some(raw) = value.as_str() else {
+            self.push(
+                Severity::Error,
+                key,
+                value.span(),
+                format!("expected a path string, found {}", value.type_name()),
+            );
+            return None;
+        };
+        if raw.trim().is_empty() {
+            self.push(Severity::Error, key, value.span(), "path is empty");
+            return None;
+        }
+
+        let expanded = match crate::paths::try_expand_user_path(Path::new(raw)) {
+            Ok(expanded) => expanded,
+            Err(err) => {
+                self.push(Severity::Error, key, value.span(), config_message(err));
+                return None;
+            }
+        };
+        if expanded.is_relative() {
+            self.push(
+                Severity::Warning,
+                key,
+                value.span(),
+                format!(
+                    "`{raw}` is relative and will resolve against the daemon's working directory"
+                ),
+            );
+        }
+        Some(expanded)
+    }
+
+    fn unknown_keys(&mut self, table: &Table, prefix: &str, known: &[&str]) {
+        for (key, _) in table.iter() {
+            if known.contains(&key) {
+                continue;
+            }
+            let span = table.key(key).and_then(|key| key.span());
+            let dotted = join_key(prefix, key);
+            let message = match closest(key, known) {
+                Some(suggestion) => format!("unknown key (did you mean `{suggestion}`?)"),
+                None => "unknown key".to_string(),
+            };
+            // serde ignores unknown keys, so they are typos rather than
+            // load failures.
+            self.push(Severity::Warning, dotted, span, message);
+        }
+    }
+
+    fn required<'t>(
+        &mut self,
+        table: &'t Table,
+        prefix: &str,
+        key: &str,
+        header: Option<Range<usize>>,
+    ) -> Option<&'t Item> {
+        let item = table.get(key);
+        if item.is_none() {
+            self.push(
+                Severity::Error,
+                join_key(prefix, key),
+                header,
+                "missing required key",
+            );
+        }
+        item
+    }
+
+    fn expect_table<'t>(&mut self, key: &str, item: &'t Item) -> Option<&'t Table> {
+        let table = item.as_table();
+        if table.is_none() {
+            self.type_mismatch(key, item, "a table");
+        }
+        table
+    }
+
+    fn expect_array<'t>(&mut self, key: &str, item: &'t Item) -> Option<&'t toml_edit::Array> {
+        let array = item.as_array();
+        if array.is_none() {
+            self.type_mismatch(key, item, "an array");
+        }
+        array
+    }
+
+    fn expect_bool(&mut self, key: &str, item: &Item) -> Option<bool> {
+        let value = item.as_bool();
+        if value.is_none() {
+            self.type_mismatch(key, item, "a boolean");
+        }
+        value
+    }
+
+    fn expect_str<'t>(&mut self, key: &str, item: &'t Item) -> Option<&'t str> {
+        let value = item.as_str();
+        if value.is_none() {
+            self.type_mismatch(key, item, "a string");
+        }
+        value
+    }
+
+    fn expect_integer(&mut self, key: &str, item: &Item, min: i64, max: i64) -> Option<i64> {
+        let Some(value) = item.as_integer() else {
+            self.type_mismatch(key, item, "an integer");
+            return None;
+        };
+        if value < min || value > max {
+            let range = if max == i64::MAX {
+                format!("at least {min}")
+            } else {
+                format!("between {min} and {max}")
+            };
+            self.push(
+                Severity::Error,
+                key,
+                item.span(),
+                format!("{value} is out of range (must be {range})"),
+            );
+            return None;
+        }
+        Some(value)
+    }
+
+    fn expect_float(&mut self, key: &str, item: &Item, min: f64, max: f64) -> Option<f64> {
+        let value = item
+            .as_float()
+            .or_else(|| item.as_integer().map(|value| value as f64));
+        let Some(value) = value else {
+            self.type_mismatch(key, item, "a number");
+            return None;
+        };
+        if !(min..=max).contains(&value) {
+            self.push(
+                Severity::Error,
+                key,
+                item.span(),
+                format!("{value} is out of range (must be between {min} and {max})"),
+            );
+            return None;
+        }
+        Some(value)
+    }
+
+    fn type_mismatch(&mut self, key: &str, item: &Item, expected: &str) {
+        self.push(
+            Severity::Error,
+            key,
+            item.span(),
+            format!("expected {expected}, found {}", item.type_name()),
+        );
+    }
+}
+
+/// Check that one exclusion compiles in [`crate::filter`].
+///
+/// An empty pattern is harmless, so it only warns; a pattern that does not
+/// compile or would exclude everything is an error.
+fn check_exclusion(raw: &str) -> Option<(Severity, String)> {
+    match crate::filter::validate_exclusion(raw) {
+        Ok(()) => None,
+        Err(err @ crate::filter::PatternError::Empty) => Some((Severity::Warning, err.to_string())),
+        Err(err) => Some((Severity::Error, format!("`{raw}`: {err}"))),
+    }
+}
+
+/// Strip the "Configuration error:" prefix from errors raised by core helpers.
+fn config_message(err: crate::Error) -> String {
+    match err {
+        crate::Error::Config(message) => message,
+        other => other.to_string(),
+    }
+}
+
+fn join_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{prefix}.{key}")
+    }
+}
+
+/// Suggest the closest known key for a likely typo.
+fn closest<'k>(key: &str, known: &[&'k str]) -> Option<&'k str> {
+    known
+        .iter()
+        .map(|candidate| (edit_distance(key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(prev[j + 1] + 1).min(current[j] + 1);
+        }
+        prev = current;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"
+index_roots = ["/"]
+exclusions = [".git", "*.log", "tmp*"]
+index_path = "/tmp/vicaya-index"
+max_memory_mb = 512
+
+[performance]
+scanner_threads = 4
+reconcile_hour = 3
+"#;
+
+    fn keys(report: &ConfigReport, severity: Severity) -> Vec<&str> {
+        report
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .map(|issue| issue.key.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        let report = validate_str(VALID);
+        assert_eq!(report.issues, Vec::new());
+    }
+
+    #[test]
+    fn syntax_errors_point_at_the_offending_line() {
+        let report = validate_str("index_roots = [\"/\"]\nmax_memory_mb = = 5\n");
+        assert!(report.has_errors());
+        let issue = &report.issues[0];
+        assert_eq!(issue.location.map(|loc| loc.line), Some(2));
+        assert!(issue.message.starts_with("invalid TOML"), "{issue}");
+    }
+
+    #[test]
+    fn reports_every_problem_at_once_with_locations() {
+        let content = r#"index_roots = ["/", "/usr"]
+exclusions = ["", "[ab", "*.log"]
+index_path = "$VICAYA_TEST_SURELY_UNSET_VAR/index"
+max_memory_mb = "lots"
+max_indexed_files = -1
+respect_ignore_file = true
+auto_scope = "repo"
+icons = "emoji"
+language = "tlh"
+index_profile = "tiny"
+
+[performance]
+scanner_threads = 0
+reconcile_hour = 24
+reconcile_schedule = ["0 3 * * *", "0 25 * * *"]
+
+[performance.reconcile]
+threads = 0
+throttle_ms = -1
+
+[smriti]
+max_boost = 2.0
+badges = "yes"
+
+[content_search]
+engine = "ag"
+
+[preview]
+max_lines = 0
+
+[archives]
+extensions = ["zip", "."]
+max_archive_mb = 0
+
+[content_hash]
+head_kb = 0
+
+[root_max_depth]
+"/usr" = 0
+
+[associations]
+md = ["Typora", ""]
+
+[aliases]
+cfg = "ext:toml type:file"
+docs = ""
+
+[http]
+enabled = true
+port = 0
+
+[journal]
+fsync = "sometimes"
+
+[ranking]
+directory_weight = -5
+min_score = 1.5
+boost = [{ pattern = "*.md", ext = "md", factor = 2 }, { ext = "png", factor = 20 }]
+recent_scope_half_life_mins = 0
+auto_broaden = "yes"
+
+[trash]
+recently_deleted = -1
+
+[normalization]
+fold_diacritics = "yes"
+
+[[hooks]]
+pattern = "*.rs"
+command = "ctags -R ."
+
+[[hooks]]
+pattern = "src/[ab"
+commnd = "make"
+"#;
+        let report = validate_str(content);
+
+        assert_eq!(
+            keys(&report, Severity::Error),
+            vec![
+                "exclusions[1]",
+                "index_path",
+                "max_memory_mb",
+                "max_indexed_files",
+                "auto_scope",
+                "icons",
+                "language",
+                "index_profile",
+                "performance.scanner_threads",
+                "performance.reconcile_hour",
+                "performance.reconcile_schedule[1]",
+                "performance.reconcile.threads",
+                "performance.reconcile.throttle_ms",
+                "smriti.max_boost",
+                "smriti.badges",
+                "content_search.engine",
+                "preview.max_lines",
+                "archives.extensions[1]",
+                "archives.max_archive_mb",
+                "content_hash.head_kb",
+                "root_max_depth./usr",
+                "associations.md[1]",
+                "aliases.docs",
+                "http.port",
+                "journal.fsync",
+                "ranking.directory_weight",
+                "ranking.min_score",
+                "ranking.boost[0].ext",
+                "ranking.boost[1].factor",
+                "ranking.recent_scope_half_life_mins",
+                "ranking.auto_broaden",
+                "trash.recently_deleted",
+                "normalization.fold_diacritics",
+                "hooks[1].command",
+                "hooks[1].pattern",
+            ]
+        );
+        assert_eq!(
+            keys(&report, Severity::Warning),
+            vec![
+                "index_roots[1]",
+                "exclusions[0]",
+                "respect_ignore_file",
+                "http.enabled",
+                "hooks[1].commnd",
+            ]
+        );
+
+        let hour = report
+            .issues
+            .iter()
+            .find(|issue| issue.key == "performance.reconcile_hour")
+            .unwrap();
+        assert_eq!(
+            hour.location,
+            Some(Location {
+                line: 14,
+                column: 18
+            })
+        );
+        assert!(hour.message.contains("between 0 and 23"));
+
+        let typo = report
+            .issues
+            .iter()
+            .find(|issue| issue.key == "respect_ignore_file")
+            .unwrap();
+        assert!(typo.message.contains("did you mean"), "{typo}");
+        assert_eq!(typo.location, Some(Location { line: 6, column: 1 }));
+    }
+
+    #[test]
+    fn missing_required_keys_are_errors() {
+        let report = validate_str("index_roots = [\"/\"]\n[performance]\nscanner_threads = 2\n");
+        assert_eq!(
+            keys(&report, Severity::Error),
+            vec![
+                "exclusions",
+                "index_path",
+                "max_memory_mb",
+                "performance.reconcile_hour",
+            ]
+        );
+    }
+
+    #[test]
+    fn duplicate_and_nested_roots_are_warnings() {
+        let content = VALID.replace(r#"["/"]"#, r#"["/tmp", "/tmp/nested", "/tmp"]"#);
+        let report = validate_str(&content);
+        assert!(!report.has_errors(), "{}", report.render(Path::new("c")));
+        let messages: Vec<&str> = report
+            .warnings()
+            .filter(|issue| issue.message.contains("index_roots[0]"))
+            .map(|issue| issue.key.as_str())
+            .collect();
+        assert_eq!(messages, vec!["index_roots[1]", "index_roots[2]"]);
+    }
+
+    #[test]
+    fn unset_variables_in_exclusions_are_warnings() {
+        let content = VALID.replace(r#""tmp*""#, r#""$VICAYA_TEST_SURELY_UNSET.BIN""#);
+        let report = validate_str(&content);
+        assert!(!report.has_errors(), "{}", report.render(Path::new("c")));
+        let warning = report.warnings().next().unwrap();
+        assert_eq!(warning.key, "exclusions[2]");
+        assert!(warning.message.contains("literally"), "{}", warning.message);
+    }
+
+    #[test]
+    fn exclusion_checks_follow_the_glob_matcher() {
+        assert_eq!(check_exclusion("/target"), None);
+        assert_eq!(check_exclusion("*.tmp"), None);
+        assert_eq!(check_exclusion("**/build/**"), None);
+        assert_eq!(check_exclusion("src/*/generated"), None);
+        assert_eq!(check_exclusion("[Cc]ache"), None);
+        assert_eq!(check_exclusion("!target/doc"), None);
+
+        let (severity, message) = check_exclusion("/").unwrap();
+        assert_eq!(severity, Severity::Warning);
+        assert!(message.contains("never matches"), "{message}");
+
+        let (severity, message) = check_exclusion("*").unwrap();
+        assert_eq!(severity, Severity::Error);
+        assert!(message.contains("every path"), "{message}");
+
+        let (severity, message) = check_exclusion("cache[").unwrap();
+        assert_eq!(severity, Severity::Error);
+        assert!(message.starts_with("`cache[`: invalid glob"), "{message}");
+    }
+
+    #[test]
+    fn render_prefixes_each_issue_with_the_file_path() {
+        let report = validate_str("index_roots = 3\n");
+        let rendered = report.render(Path::new("config.toml"));
+        assert!(
+            rendered
+                .contains("config.toml:1:15: error: index_roots: expected an array, found integer"),
+            "{rendered}"
+        );
+    }
+}
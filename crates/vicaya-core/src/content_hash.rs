@@ -0,0 +1,126 @@
+//! Content fingerprints computed at scan time.
+//!
+//! With `[content_hash] enabled`, the scanner and the watcher hash the first
+//! `head_kb` KiB of every file up to `max_file_mb` MiB, together with its
+//! size, with BLAKE3. The first 64 bits of the digest are kept in the index,
+//! which is enough to find duplicates and to tell a changed file from one
+//! whose mtime was reset, without rereading the tree. Two files that agree
+//! in size and head but differ further in share a fingerprint.
+
+use std::io::Read;
+use std::num::NonZeroU64;
+use std::path::Path;
+
+/// Hex digits in a formatted fingerprint.
+pub const HEX_LEN: usize = 16;
+
+/// Fingerprint `path` of `size` bytes from its first `head_bytes` bytes.
+/// `None` when the file cannot be read.
+pub fn compute(path: &Path, size: u64, head_bytes: usize) -> Option<NonZeroU64> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut head = Vec::with_capacity(head_bytes.min(size as usize));
+    file.take(head_bytes as u64).read_to_end(&mut head).ok()?;
+    Some(fingerprint(&head, size))
+}
+
+/// Fingerprint of a file of `size` bytes starting with `head`.
+pub fn fingerprint(head: &[u8], size: u64) -> NonZeroU64 {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(head);
+    hasher.update(&size.to_le_bytes());
+    let digest = hasher.finalize();
+    let mut first = [0u8; 8];
+    first.copy_from_slice(&digest.as_bytes()[..8]);
+    // Zero is reserved for "not hashed".
+    NonZeroU64::new(u64::from_be_bytes(first)).unwrap_or(NonZeroU64::MIN)
+}
+
+/// A fingerprint as 16 lowercase hex digits.
+pub fn to_hex(hash: NonZeroU64) -> String {
+    format!("{:0width$x}", hash.get(), width = HEX_LEN)
+}
+
+/// Parse a fingerprint formatted by [`to_hex`].
+pub fn from_hex(hex: &str) -> Option<NonZeroU64> {
+    if hex.len() != HEX_LEN || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    NonZeroU64::new(u64::from_str_radix(hex, 16).ok()?)
+}
+
+/// The leading hex digits of a fingerprint, as typed in a `hash:` niyama.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HashPrefix {
+    value: u64,
+    digits: u32,
+}
+
+impl HashPrefix {
+    /// Parse 1 to 16 hex digits.
+    pub fn parse(prefix: &str) -> Option<Self> {
+        let prefix = prefix.trim();
+        if prefix.is_empty()
+            || prefix.len() > HEX_LEN
+            || !prefix.bytes().all(|b| b.is_ascii_hexdigit())
+        {
+            return None;
+        }
+        let value = u64::from_str_radix(prefix, 16).ok()?;
+        Some(Self {
+            value,
+            digits: prefix.len() as u32,
+        })
+    }
+
+    /// Whether `hash` starts with this prefix; unhashed entries never match.
+    pub fn matches(&self, hash: Option<NonZeroU64>) -> bool {
+        let Some(hash) = hash else {
+            return false;
+        };
+        let shift = 4 * (HEX_LEN as u32 - self.digits);
+        hash.get() >> shift == self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprints_cover_head_and_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        std::fs::write(&a, "same head, then more").unwrap();
+        std::fs::write(&b, "same head, then more").unwrap();
+
+        let hash_a = compute(&a, 20, 1024).unwrap();
+        assert_eq!(compute(&b, 20, 1024), Some(hash_a));
+        assert_eq!(compute(&a, 20, 9), compute(&b, 20, 9));
+        assert_ne!(compute(&a, 21, 1024), Some(hash_a), "size is mixed in");
+        assert_ne!(fingerprint(b"other", 20), hash_a);
+        assert!(compute(&dir.path().join("missing"), 0, 1024).is_none());
+
+        let hex = to_hex(hash_a);
+        assert_eq!(hex.len(), HEX_LEN);
+        assert_eq!(from_hex(&hex), Some(hash_a));
+        assert!(from_hex("0000000000000000").is_none());
+        assert!(HashPrefix::parse(&hex).unwrap().matches(Some(hash_a)));
+    }
+
+    #[test]
+    fn prefixes_match_leading_hex_digits() {
+        let hash = NonZeroU64::new(0x3fa9_0000_0000_0001).unwrap();
+        assert!(HashPrefix::parse("3").unwrap().matches(Some(hash)));
+        assert!(HashPrefix::parse("3FA9").unwrap().matches(Some(hash)));
+        assert!(!HashPrefix::parse("3fa8").unwrap().matches(Some(hash)));
+        assert!(HashPrefix::parse("3fa9000000000001")
+            .unwrap()
+            .matches(Some(hash)));
+        assert!(!HashPrefix::parse("3fa9").unwrap().matches(None));
+        assert!(HashPrefix::parse("").is_none());
+        assert!(HashPrefix::parse("xyz").is_none());
+        assert!(HashPrefix::parse("+3").is_none());
+        assert!(HashPrefix::parse("3fa90000000000012").is_none());
+    }
+}
@@ -1,4 +1,5 @@
-//! Content search powered by local grep-compatible tools.
+//! Content search powered by local grep-compatible tools, or by a built-in
+//! matcher over candidate files the index lists.
 
 use crate::{Error, Result};
 use serde::{Deserialize, Serialize};
@@ -46,6 +47,8 @@ pub enum ContentSearchEngine {
     GitGrep,
     /// Standard grep executed over Vicaya-controlled traversal.
     Grep,
+    /// Vicaya's own matcher over candidate files listed by the index.
+    Index,
 }
 
 impl ContentSearchEngine {
@@ -55,6 +58,7 @@ impl ContentSearchEngine {
             Self::Ripgrep => "ripgrep",
             Self::GitGrep => "git-grep",
             Self::Grep => "grep",
+            Self::Index => "index",
         }
     }
 }
@@ -130,6 +134,9 @@ pub fn search(options: &ContentSearchOptions) -> Result<ContentSearchReport> {
         ContentSearchEngine::Ripgrep => search_ripgrep(options, &resolved.command)?,
         ContentSearchEngine::GitGrep => search_git_grep(options, &resolved.command)?,
         ContentSearchEngine::Grep => search_grep(options, &resolved.command)?,
+        ContentSearchEngine::Index => {
+            unreachable!("the index engine needs candidates; see search_files")
+        }
     };
 
     Ok(ContentSearchReport {
@@ -305,6 +312,116 @@ fn search_grep(options: &ContentSearchOptions, grep: &Path) -> Result<Vec<Conten
     Ok(hits)
 }
 
+/// Candidate files read and matched together; hits keep this order, so a
+/// window is the most work done past the limit.
+const SEARCH_FILES_WINDOW: usize = 256;
+/// Leading bytes checked for a NUL, as ripgrep and grep detect binaries.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Search `files` for the literal `query` in parallel, returning at most
+/// `limit` hits in the order of `files`. This is the engine behind `vicaya
+/// grep --engine index`, whose candidates come from the index instead of a
+/// directory walk.
+///
+/// Like the ripgrep invocation above, matching is smart-case (case-sensitive
+/// only when the query has an uppercase letter), files larger than
+/// `max_filesize` bytes or with a NUL near the start are skipped, and each
+/// matching line is one hit whose column is its first match. Unreadable
+/// files are skipped.
+pub fn search_files(
+    query: &str,
+    files: &[PathBuf],
+    limit: usize,
+    max_filesize: u64,
+) -> Vec<ContentSearchHit> {
+    use rayon::prelude::*;
+
+    let query = query.trim();
+    let mut hits = Vec::new();
+    if query.is_empty() || limit == 0 {
+        return hits;
+    }
+    let matcher = LiteralMatcher::new(query);
+    for window in files.chunks(SEARCH_FILES_WINDOW) {
+        let found: Vec<Vec<ContentSearchHit>> = window
+            .par_iter()
+            .map(|path| search_file(&matcher, path, limit, max_filesize))
+            .collect();
+        for hit in found.into_iter().flatten() {
+            hits.push(hit);
+            if hits.len() >= limit {
+                return hits;
+            }
+        }
+    }
+    hits
+}
+
+/// A smart-case literal.
+struct LiteralMatcher {
+    needle: String,
+    ignore_case: bool,
+}
+
+impl LiteralMatcher {
+    fn new(query: &str) -> Self {
+        let ignore_case = !query.chars().any(char::is_uppercase);
+        Self {
+            needle: if ignore_case {
+                query.to_ascii_lowercase()
+            } else {
+                query.to_string()
+            },
+            ignore_case,
+        }
+    }
+
+    /// Byte offset of the first match in `line`. ASCII lowercasing keeps
+    /// offsets, so they index the original line.
+    fn find(&self, line: &str) -> Option<usize> {
+        if self.ignore_case {
+            line.to_ascii_lowercase().find(&self.needle)
+        } else {
+            line.find(&self.needle)
+        }
+    }
+}
+
+fn search_file(
+    matcher: &LiteralMatcher,
+    path: &Path,
+    limit: usize,
+    max_filesize: u64,
+) -> Vec<ContentSearchHit> {
+    let mut hits = Vec::new();
+    let too_large = std::fs::metadata(path).map_or(true, |meta| meta.len() > max_filesize);
+    if too_large {
+        return hits;
+    }
+    let Ok(bytes) = std::fs::read(path) else {
+        return hits;
+    };
+    if bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return hits;
+    }
+    let text = String::from_utf8_lossy(&bytes);
+    for (index, line) in text.lines().enumerate() {
+        let Some(offset) = matcher.find(line) else {
+            continue;
+        };
+        hits.push(ContentSearchHit {
+            path: path.to_path_buf(),
+            line_number: index + 1,
+            column: Some(offset + 1),
+            line: clean_match_line(line),
+        });
+        if hits.len() >= limit {
+            break;
+        }
+    }
+    hits
+}
+
 fn collect_json_rg(mut command: Command, limit: usize) -> Result<Vec<ContentSearchHit>> {
     let mut child = spawn_piped(&mut command)?;
     let stdout = child
@@ -728,6 +845,50 @@ mod tests {
         assert_eq!(hit.line, "fn main() {}");
     }
 
+    #[test]
+    fn search_files_matches_smart_case_in_file_order() {
+        let dir = tempdir().unwrap();
+        let notes = dir.path().join("notes.txt");
+        let code = dir.path().join("main.rs");
+        let blob = dir.path().join("image.bin");
+        let large = dir.path().join("large.log");
+        std::fs::write(&notes, "intro\n  Needle one\nnothing\nneedle two\r\n").unwrap();
+        std::fs::write(&code, "let needle = 1; // needle\n").unwrap();
+        std::fs::write(&blob, b"needle\0binary").unwrap();
+        std::fs::write(&large, "needle ".repeat(64)).unwrap();
+        let files = vec![
+            notes.clone(),
+            blob,
+            large,
+            code.clone(),
+            dir.path().join("gone.txt"),
+        ];
+
+        let hits = search_files("needle", &files, 10, 256);
+        let found: Vec<_> = hits
+            .iter()
+            .map(|hit| (hit.path.clone(), hit.line_number, hit.column))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (notes.clone(), 2, Some(3)),
+                (notes.clone(), 4, Some(1)),
+                (code.clone(), 1, Some(5)),
+            ]
+        );
+        assert_eq!(hits[0].line, "  Needle one");
+        assert_eq!(hits[1].line, "needle two");
+
+        // An uppercase letter makes the match case-sensitive.
+        let hits = search_files("Needle", &files, 10, 256);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].line_number, 2);
+
+        assert_eq!(search_files("needle", &files, 2, 256).len(), 2);
+        assert!(search_files("  ", &files, 10, 256).is_empty());
+    }
+
     #[test]
     fn returns_empty_hits_for_empty_query() {
         let mut options = ContentSearchOptions::new("", ".", 10);
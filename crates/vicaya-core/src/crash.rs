@@ -0,0 +1,153 @@
+//! Daemon crash reports.
+//!
+//! When a daemon thread panics, the daemon's panic hook writes one
+//! [`CrashReport`] as JSON under `crashes/` in the vicaya directory before
+//! aborting the process. Reports stay local; `vicaya daemon status` shows the
+//! latest one, and only the newest [`KEEP_REPORTS`] are kept.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Crash reports kept; writing another deletes the oldest.
+pub const KEEP_REPORTS: usize = 10;
+
+/// Everything the daemon could record about a panic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashReport {
+    /// Epoch seconds when the panic happened.
+    pub crashed_at: i64,
+    pub pid: u32,
+    /// `vicaya-daemon` version line of the binary that crashed.
+    pub version: String,
+    /// Name of the panicking thread (`<unnamed>` when it has none).
+    pub thread: String,
+    /// The panic message.
+    pub message: String,
+    /// `file:line:column` of the panic, when known.
+    #[serde(default)]
+    pub location: Option<String>,
+    /// Forced backtrace of the panicking thread.
+    pub backtrace: String,
+    /// The latest requests the daemon received, oldest first.
+    #[serde(default)]
+    pub recent_requests: Vec<RecentRequest>,
+    /// Index counters, unless the state lock was held elsewhere.
+    #[serde(default)]
+    pub index: Option<IndexStats>,
+}
+
+/// One request received before a crash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentRequest {
+    /// Epoch seconds when it arrived.
+    pub at: i64,
+    /// Its debug form, cut short.
+    pub request: String,
+}
+
+/// Index counters at crash time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexStats {
+    pub indexed_files: usize,
+    pub trigram_count: usize,
+    pub pending_watcher_events: usize,
+    pub journal_entries: usize,
+    pub reconciling: bool,
+}
+
+impl CrashReport {
+    /// Write the report to `dir` as `crash-<time>-<pid>.json` and delete
+    /// all but the newest [`KEEP_REPORTS`]. Returns the report's path.
+    pub fn write(&self, dir: &Path) -> crate::Result<PathBuf> {
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("crash-{}-{}.json", self.crashed_at, self.pid));
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| crate::Error::Serialization(e.to_string()))?;
+        std::fs::write(&path, json)?;
+        for old in report_paths(dir)?.into_iter().rev().skip(KEEP_REPORTS) {
+            let _ = std::fs::remove_file(old);
+        }
+        Ok(path)
+    }
+}
+
+/// The newest readable report in `dir`, with its path; a missing directory
+/// has none.
+pub fn latest(dir: &Path) -> crate::Result<Option<(PathBuf, CrashReport)>> {
+    for path in report_paths(dir)?.into_iter().rev() {
+        let Ok(json) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Ok(report) = serde_json::from_str(&json) {
+            return Ok(Some((path, report)));
+        }
+    }
+    Ok(None)
+}
+
+/// Report files in `dir`, oldest first.
+fn report_paths(dir: &Path) -> crate::Result<Vec<PathBuf>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut reports: Vec<(i64, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            let crashed_at = name
+                .to_str()?
+                .strip_prefix("crash-")?
+                .strip_suffix(".json")?
+                .split('-')
+                .next()?
+                .parse()
+                .ok()?;
+            Some((crashed_at, entry.path()))
+        })
+        .collect();
+    reports.sort();
+    Ok(reports.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn report(crashed_at: i64) -> CrashReport {
+        CrashReport {
+            crashed_at,
+            pid: 42,
+            version: "vicaya-daemon 1.2.0".to_string(),
+            thread: "watcher".to_string(),
+            message: "index out of bounds".to_string(),
+            location: Some("src/main.rs:1:1".to_string()),
+            backtrace: "0: main".to_string(),
+            recent_requests: vec![RecentRequest {
+                at: crashed_at - 1,
+                request: "Status".to_string(),
+            }],
+            index: None,
+        }
+    }
+
+    #[test]
+    fn latest_report_wins_and_old_ones_are_pruned() {
+        let dir = tempdir().unwrap();
+        let crashes = dir.path().join("crashes");
+        assert!(latest(&crashes).unwrap().is_none());
+
+        for at in 1..=(KEEP_REPORTS as i64 + 2) {
+            report(1_700_000_000 + at).write(&crashes).unwrap();
+        }
+        std::fs::write(crashes.join("notes.txt"), "not a report").unwrap();
+
+        let (path, latest) = latest(&crashes).unwrap().unwrap();
+        assert_eq!(latest, report(1_700_000_000 + KEEP_REPORTS as i64 + 2));
+        assert!(path.ends_with("crash-1700000012-42.json"));
+        assert_eq!(report_paths(&crashes).unwrap().len(), KEEP_REPORTS);
+        assert!(!crashes.join("crash-1700000001-42.json").exists());
+    }
+}
@@ -1,7 +1,7 @@
 //! Daemon lifecycle management utilities.
 
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 /// Get the PID file path for the daemon.
@@ -84,8 +84,18 @@ pub fn remove_pid_file() -> std::io::Result<()> {
     Ok(())
 }
 
-/// Start the daemon in the background.
+/// Start the daemon in the background and wait until its socket accepts
+/// connections.
 pub fn start_daemon() -> crate::Result<i32> {
+    let pid = spawn_daemon()?;
+    wait_for_daemon_ready(pid)?;
+    Ok(pid)
+}
+
+/// Start the daemon in the background without waiting for it to load its
+/// index. The PID file is written right away, so other clients see the
+/// daemon as running instead of starting a second one.
+pub fn spawn_daemon() -> crate::Result<i32> {
     if is_running() {
         return Err(crate::Error::Config(
             "Daemon is already running".to_string(),
@@ -111,13 +121,8 @@ pub fn start_daemon() -> crate::Result<i32> {
             .map_err(crate::Error::Io)?;
 
         let pid = child.id() as i32;
-
-        wait_for_daemon_ready(pid)?;
-
-        // Ensure PID file exists for consumers that rely on it.
-        if !pid_file_path().exists() {
-            write_pid(pid).map_err(crate::Error::Io)?;
-        }
+        // The daemon rewrites it once its socket is bound.
+        write_pid(pid).map_err(crate::Error::Io)?;
 
         Ok(pid)
     }
@@ -188,6 +193,31 @@ pub fn stop_daemon() -> crate::Result<()> {
     }
 }
 
+/// Hand the running daemon's index to `binary` (default: the daemon's own,
+/// possibly replaced, executable) and wait for the new binary to serve.
+///
+/// The daemon keeps its PID and skips the startup reconcile, so this is much
+/// faster than a stop and start. Returns the new binary's version line.
+pub fn upgrade_daemon(binary: Option<&Path>) -> crate::Result<String> {
+    let Some(pid) = get_pid().filter(|_| is_running()) else {
+        return Err(crate::Error::Config("Daemon is not running".to_string()));
+    };
+
+    let request = crate::ipc::Request::Upgrade {
+        binary: binary.map(|path| path.to_string_lossy().to_string()),
+    };
+    match request_via_ipc(&request)? {
+        Some(crate::ipc::Response::Upgrading { version, .. }) => {
+            wait_for_daemon_ready(pid)?;
+            Ok(version)
+        }
+        Some(crate::ipc::Response::Error { message }) => Err(crate::Error::Ipc(message)),
+        _ => Err(crate::Error::Ipc(
+            "Daemon does not support upgrades; restart it instead".to_string(),
+        )),
+    }
+}
+
 fn is_socket_connectable() -> bool {
     #[cfg(unix)]
     {
@@ -242,7 +272,35 @@ fn wait_for_daemon_ready(pid: i32) -> crate::Result<()> {
     }
 }
 
+/// Poll a freshly started daemon with `Request::Ping`, backing off from
+/// 10 ms to 250 ms, until it reports startup warm-up done or `timeout`
+/// passes. Returns whether it became ready; a daemon too old to know `Ping`
+/// counts as ready once it answers.
+pub fn wait_until_ready(timeout: std::time::Duration) -> bool {
+    use std::time::{Duration, Instant};
+
+    let deadline = Instant::now() + timeout;
+    let mut delay = Duration::from_millis(10);
+    loop {
+        match request_via_ipc(&crate::ipc::Request::Ping) {
+            Ok(Some(crate::ipc::Response::Pong { ready: false, .. })) | Ok(None) | Err(_) => {}
+            Ok(Some(_)) => return true,
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep(delay.min(deadline - now));
+        delay = (delay * 2).min(Duration::from_millis(250));
+    }
+}
+
 fn request_shutdown_via_ipc() -> crate::Result<()> {
+    request_via_ipc(&crate::ipc::Request::Shutdown).map(|_| ())
+}
+
+/// Send one request to the daemon and read its reply, if it sends one.
+fn request_via_ipc(request: &crate::ipc::Request) -> crate::Result<Option<crate::ipc::Response>> {
     #[cfg(unix)]
     {
         use std::io::{BufReader, Write};
@@ -251,7 +309,7 @@ fn request_shutdown_via_ipc() -> crate::Result<()> {
         let mut stream = UnixStream::connect(crate::ipc::socket_path())
             .map_err(|e| crate::Error::Ipc(format!("Failed to connect to daemon: {}", e)))?;
 
-        let mut request_json = crate::ipc::Request::Shutdown
+        let mut request_json = request
             .to_json()
             .map_err(|e| crate::Error::Ipc(format!("Failed to serialize request: {}", e)))?;
         request_json.push('\n');
@@ -261,14 +319,17 @@ fn request_shutdown_via_ipc() -> crate::Result<()> {
             .map_err(|e| crate::Error::Ipc(format!("Failed to send request: {}", e)))?;
 
         let mut reader = BufReader::new(stream);
-        let _ = crate::ipc::read_message(&mut reader);
-        Ok(())
+        Ok(crate::ipc::read_message(&mut reader)
+            .ok()
+            .flatten()
+            .and_then(|line| crate::ipc::Response::from_json(&line).ok()))
     }
 
     #[cfg(not(unix))]
     {
+        let _ = request;
         Err(crate::Error::Config(
-            "Daemon stop not supported on this platform".to_string(),
+            "Daemon IPC not supported on this platform".to_string(),
         ))
     }
 }
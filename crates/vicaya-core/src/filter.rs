@@ -1,44 +1,556 @@
 //! Path filtering helpers (exclusions, indexing decisions).
+//!
+//! Exclusions are globs compiled once into an [`ExclusionSet`]:
+//! - Patterns without `/` match any single path component: `node_modules`,
+//!   `*.pyc`, `._*`, `[Cc]ache`.
+//! - Patterns with `/` match a run of components anywhere in the path:
+//!   `src/*/generated`, `**/build/**`.
+//! - Patterns starting with `/` and naming more than one component are
+//!   anchored to the filesystem root: `/Users/me/scratch`. A lone `/target`
+//!   keeps meaning "any `target` component", as it always has.
+//! - A leading `!` re-includes what an earlier pattern excluded
+//!   (`target`, `!target/doc`); `\!name` matches a literal `!name`.
+//!
+//! `*` and `?` never cross a `/`; `**` does. A pattern applies to a path when
+//! it matches the path or any of its ancestors, and the last applicable
+//! pattern wins, as in `.gitignore`.
+//!
+//! Everything below an excluded directory stays excluded unless a later
+//! negation names a path inside it: `target` + `!target/doc` keeps the docs,
+//! while a component negation such as `!*.md` only re-includes files that
+//! were excluded by name, not ones inside an excluded directory.
 
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use std::borrow::Cow;
+use std::fmt;
 use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
 
-/// Normalize exclusion patterns to the component-oriented matching model.
-pub fn normalize_exclusion(exclusion: &str) -> &str {
-    exclusion.trim_start_matches('/')
+/// Normalize an exclusion pattern to the form the matcher compiles.
+///
+/// Trailing slashes are dropped, as is the leading `/` of single-component
+/// patterns. A leading `!` is kept.
+pub fn normalize_exclusion(exclusion: &str) -> Cow<'_, str> {
+    match exclusion.strip_prefix('!') {
+        Some(body) => Cow::Owned(format!("!{}", normalize_body(body))),
+        None => Cow::Borrowed(normalize_body(exclusion)),
+    }
 }
 
-/// Return `true` if a path should be indexed given the configured exclusions.
-///
-/// Exclusions are matched against individual path components. Supports:
-/// - Exact component matches (`node_modules`)
-/// - Simple globs:
-///   - `*.ext` (extension match)
-///   - `prefix*` (prefix match)
-pub fn should_index_path(path: &Path, exclusions: &[String]) -> bool {
-    for exclusion in exclusions {
-        let exclusion = normalize_exclusion(exclusion);
-        for component in path.components() {
-            if matches!(component, std::path::Component::RootDir) {
+fn normalize_body(pattern: &str) -> &str {
+    let trimmed = pattern.trim_end_matches('/');
+    match trimmed.strip_prefix('/') {
+        Some(rest) if !rest.contains('/') => rest,
+        _ => trimmed,
+    }
+}
+
+/// Why an exclusion pattern cannot be used.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    /// The pattern is empty and never matches.
+    Empty,
+    /// The pattern consists only of wildcards and would match every path.
+    MatchesEverything,
+    /// The glob syntax is invalid.
+    Invalid(String),
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "empty exclusion pattern never matches"),
+            Self::MatchesEverything => write!(f, "pattern would match every path"),
+            Self::Invalid(reason) => write!(f, "invalid glob: {reason}"),
+        }
+    }
+}
+
+/// Check that `raw` compiles to a usable exclusion pattern.
+pub fn validate_exclusion(raw: &str) -> Result<(), PatternError> {
+    compile_exclusion(raw, false).map(|_| ())
+}
+
+/// Which part of a path a compiled pattern is matched against.
+enum PatternScope {
+    /// The last component of the path or ancestor being checked.
+    Component,
+    /// The full path or ancestor being checked.
+    Path,
+}
+
+struct CompiledPattern {
+    scope: PatternScope,
+    glob: Glob,
+    negated: bool,
+    /// For negations: globs matching excluded directories the negation can
+    /// reach into, or `None` if it could reach into any of them.
+    parents: Option<Vec<Glob>>,
+}
+
+fn compile_exclusion(raw: &str, case_insensitive: bool) -> Result<CompiledPattern, PatternError> {
+    let (negated, body) = match raw.strip_prefix('!') {
+        Some(body) => (true, body),
+        None => (false, raw),
+    };
+    let pattern = normalize_body(body);
+    if pattern.is_empty() {
+        return Err(PatternError::Empty);
+    }
+    if pattern.chars().all(|c| c == '*' || c == '/') {
+        return Err(PatternError::MatchesEverything);
+    }
+
+    let build = |pattern: &str| {
+        GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .backslash_escape(true)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|err| PatternError::Invalid(err.kind().to_string()))
+    };
+
+    let (scope, pattern) = if !pattern.contains('/') {
+        (PatternScope::Component, pattern.to_string())
+    } else if pattern.starts_with('/') || pattern.starts_with("**/") {
+        (PatternScope::Path, pattern.to_string())
+    } else {
+        (PatternScope::Path, format!("**/{pattern}"))
+    };
+    let glob = build(&pattern)?;
+
+    // `**/target/doc` can only re-include paths under a directory matching
+    // `**/target`; a component negation never reaches into a directory.
+    let parents = match scope {
+        PatternScope::Component => Some(Vec::new()),
+        PatternScope::Path if negated => {
+            let parts: Vec<&str> = pattern.split('/').collect();
+            (2..parts.len())
+                .map(|len| build(&parts[..len].join("/")))
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+        }
+        _ => None,
+    };
+
+    Ok(CompiledPattern {
+        scope,
+        glob,
+        negated,
+        parents,
+    })
+}
+
+/// A negation and the directories it can re-include paths beneath.
+#[derive(Debug, Clone)]
+struct Reinclusion {
+    index: usize,
+    /// `None` when the negation can apply anywhere.
+    parents: Option<GlobSet>,
+}
+
+/// Compiled exclusion patterns.
+#[derive(Debug, Clone)]
+pub struct ExclusionSet {
+    /// Patterns as configured, indexed by declaration order.
+    patterns: Vec<String>,
+    /// Whether each pattern in `patterns` is a `!` negation.
+    negated: Vec<bool>,
+    components: GlobSet,
+    /// Maps `components` match indices back to `patterns`.
+    component_ids: Vec<usize>,
+    paths: GlobSet,
+    /// Maps `paths` match indices back to `patterns`.
+    path_ids: Vec<usize>,
+    reinclusions: Vec<Reinclusion>,
+}
+
+impl Default for ExclusionSet {
+    fn default() -> Self {
+        Self::new(&[], false)
+    }
+}
+
+impl ExclusionSet {
+    /// Compile `exclusions`; patterns that fail to compile are logged and
+    /// skipped (config validation rejects them before they get here).
+    pub fn new(exclusions: &[String], case_insensitive: bool) -> Self {
+        let mut components = GlobSetBuilder::new();
+        let mut component_ids = Vec::new();
+        let mut paths = GlobSetBuilder::new();
+        let mut path_ids = Vec::new();
+        let mut negated = vec![false; exclusions.len()];
+        let mut reinclusions = Vec::new();
+
+        for (idx, raw) in exclusions.iter().enumerate() {
+            let compiled = match compile_exclusion(raw, case_insensitive) {
+                Ok(compiled) => compiled,
+                Err(PatternError::Empty) => continue,
+                Err(err) => {
+                    tracing::warn!("Ignoring exclusion `{raw}`: {err}");
+                    continue;
+                }
+            };
+            match compiled.scope {
+                PatternScope::Component => {
+                    components.add(compiled.glob);
+                    component_ids.push(idx);
+                }
+                PatternScope::Path => {
+                    paths.add(compiled.glob);
+                    path_ids.push(idx);
+                }
+            }
+            if compiled.negated {
+                negated[idx] = true;
+                let parents = compiled.parents.and_then(|globs| {
+                    let mut set = GlobSetBuilder::new();
+                    for glob in globs {
+                        set.add(glob);
+                    }
+                    set.build().ok()
+                });
+                reinclusions.push(Reinclusion {
+                    index: idx,
+                    parents,
+                });
+            }
+        }
+
+        Self {
+            patterns: exclusions.to_vec(),
+            negated,
+            components: components.build().unwrap_or_else(|_| GlobSet::empty()),
+            component_ids,
+            paths: paths.build().unwrap_or_else(|_| GlobSet::empty()),
+            path_ids,
+            reinclusions,
+        }
+    }
+
+    /// Whether any pattern is a `!` negation.
+    pub fn has_negations(&self) -> bool {
+        !self.reinclusions.is_empty()
+    }
+
+    /// Return `true` if `path` is kept out of the index.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.matching(path).is_some()
+    }
+
+    /// Return `true` if `dir` and everything beneath it are excluded, so a
+    /// directory walk can skip it.
+    pub fn prunes(&self, dir: &Path) -> bool {
+        match self.decide(dir) {
+            Some(index) => !self.may_reinclude_below(dir, index),
+            None => false,
+        }
+    }
+
+    /// Return the exclusion pattern that keeps `path` out of the index.
+    pub fn matching(&self, path: &Path) -> Option<&str> {
+        self.decide(path).map(|index| self.patterns[index].as_str())
+    }
+
+    /// Index of the pattern excluding `path`, if any.
+    fn decide(&self, path: &Path) -> Option<usize> {
+        if self.components.is_empty() && self.paths.is_empty() {
+            return None;
+        }
+
+        let mut chain: Vec<&Path> = path
+            .ancestors()
+            .filter(|ancestor| ancestor.file_name().is_some())
+            .collect();
+        chain.reverse();
+
+        let mut hits = Vec::new();
+        let mut last: Option<usize> = None;
+        for (depth, ancestor) in chain.iter().enumerate() {
+            if let Some(index) = self.last_match(ancestor, &mut hits) {
+                last = last.max(Some(index));
+            }
+            let Some(index) = last.filter(|&index| !self.negated[index]) else {
                 continue;
+            };
+            // An excluded directory hides its subtree unless a later
+            // negation could re-include something inside it.
+            if depth + 1 < chain.len() && !self.may_reinclude_below(ancestor, index) {
+                return Some(index);
             }
+        }
+
+        last.filter(|&index| !self.negated[index])
+    }
 
-            let component_str = component.as_os_str().to_string_lossy();
+    /// Highest-index pattern matching `path` itself (not its ancestors).
+    fn last_match(&self, path: &Path, hits: &mut Vec<usize>) -> Option<usize> {
+        let mut last = None;
+        if let Some(name) = path.file_name() {
+            self.components.matches_into(name, hits);
+            last = hits.iter().map(|&hit| self.component_ids[hit]).max();
+        }
+        if !self.paths.is_empty() {
+            self.paths.matches_into(path, hits);
+            last = last.max(hits.iter().map(|&hit| self.path_ids[hit]).max());
+        }
+        last
+    }
 
-            if exclusion.contains('*') {
-                if let Some(ext) = exclusion.strip_prefix("*.") {
-                    if component_str.ends_with(&format!(".{}", ext)) {
-                        return false;
-                    }
-                } else if let Some(prefix) = exclusion.strip_suffix('*') {
-                    if !prefix.is_empty() && component_str.starts_with(prefix) {
-                        return false;
-                    }
-                }
-            } else if component_str == exclusion {
-                return false;
+    fn may_reinclude_below(&self, dir: &Path, excluded_by: usize) -> bool {
+        self.reinclusions
+            .iter()
+            .filter(|reinclusion| reinclusion.index > excluded_by)
+            .any(|reinclusion| match &reinclusion.parents {
+                None => true,
+                Some(parents) => parents.is_match(dir),
+            })
+    }
+}
+
+/// An [`ExclusionSet`] compiled on first use and rebuilt whenever the
+/// patterns it was built from change.
+#[derive(Default)]
+pub struct ExclusionCache {
+    slot: RwLock<Option<CachedSet>>,
+}
+
+struct CachedSet {
+    exclusions: Vec<String>,
+    case_insensitive: bool,
+    set: Arc<ExclusionSet>,
+}
+
+impl ExclusionCache {
+    /// Return the compiled set for `exclusions`, compiling it if needed.
+    pub fn get(&self, exclusions: &[String], case_insensitive: bool) -> Arc<ExclusionSet> {
+        if let Some(cached) = &*self.slot.read().unwrap_or_else(PoisonError::into_inner) {
+            if cached.case_insensitive == case_insensitive && cached.exclusions == exclusions {
+                return Arc::clone(&cached.set);
             }
         }
+
+        let set = Arc::new(ExclusionSet::new(exclusions, case_insensitive));
+        *self.slot.write().unwrap_or_else(PoisonError::into_inner) = Some(CachedSet {
+            exclusions: exclusions.to_vec(),
+            case_insensitive,
+            set: Arc::clone(&set),
+        });
+        set
     }
+}
+
+impl Clone for ExclusionCache {
+    fn clone(&self) -> Self {
+        let slot = self
+            .slot
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+            .map(|cached| CachedSet {
+                exclusions: cached.exclusions.clone(),
+                case_insensitive: cached.case_insensitive,
+                set: Arc::clone(&cached.set),
+            });
+        Self {
+            slot: RwLock::new(slot),
+        }
+    }
+}
+
+impl fmt::Debug for ExclusionCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExclusionCache").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(patterns: &[&str], case_insensitive: bool) -> ExclusionSet {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        ExclusionSet::new(&patterns, case_insensitive)
+    }
+
+    fn excluded(set: &ExclusionSet, path: &str) -> bool {
+        let excluded = set.is_excluded(Path::new(path));
+        assert_eq!(excluded, set.matching(Path::new(path)).is_some(), "{path}");
+        excluded
+    }
+
+    #[test]
+    fn case_insensitive_matching_covers_mixed_case_components() {
+        let exclusions = set(&["Build", "*.LOG", "Cache*"], true);
+        let paths = [
+            "/repo/build/out.o",
+            "/repo/BUILD/out.o",
+            "/repo/logs/server.log",
+            "/repo/cacheDir/blob",
+        ];
+
+        for path in paths {
+            assert!(excluded(&exclusions, path), "{path} should be excluded");
+        }
+        assert_eq!(
+            exclusions.matching(Path::new("/repo/BuIlD/x")),
+            Some("Build")
+        );
+        assert!(!excluded(&exclusions, "/repo/src/builder.rs"));
 
-    true
+        // Case-sensitive matching only honours the exact spelling.
+        let exclusions = set(&["Build", "*.LOG", "Cache*"], false);
+        assert!(!excluded(&exclusions, "/repo/build/out.o"));
+        assert!(excluded(&exclusions, "/repo/Build/out.o"));
+    }
+
+    #[test]
+    fn globs_support_doublestar_classes_and_multi_component_patterns() {
+        let exclusions = set(&["**/build/**", "src/*/generated", "[Cc]ache"], false);
+
+        assert!(excluded(&exclusions, "/repo/build/out.o"));
+        assert!(excluded(&exclusions, "/repo/nested/build/deep/out.o"));
+        assert!(excluded(&exclusions, "/repo/src/api/generated/types.rs"));
+        assert!(excluded(&exclusions, "/repo/src/api/generated"));
+        assert!(excluded(&exclusions, "/home/me/Cache/blob"));
+        assert!(excluded(&exclusions, "/home/me/cache/blob"));
+
+        // `*` stays within one component.
+        assert!(!excluded(&exclusions, "/repo/src/a/b/generated/types.rs"));
+        assert!(!excluded(&exclusions, "/repo/builder/out.o"));
+        assert!(!excluded(&exclusions, "/home/me/CACHE/blob"));
+    }
+
+    #[test]
+    fn absolute_patterns_are_anchored_at_the_filesystem_root() {
+        let exclusions = set(&["/Users/me/scratch", "/target"], false);
+
+        assert!(excluded(&exclusions, "/Users/me/scratch"));
+        assert!(excluded(&exclusions, "/Users/me/scratch/notes.txt"));
+        assert!(!excluded(
+            &exclusions,
+            "/Volumes/x/Users/me/scratch/notes.txt"
+        ));
+        assert!(!excluded(&exclusions, "/Users/me/scratchpad/notes.txt"));
+
+        // A single leading-slash component still matches anywhere.
+        assert!(excluded(&exclusions, "/repo/target/debug/app"));
+    }
+
+    #[test]
+    fn matching_reports_the_last_applicable_pattern() {
+        let exclusions = set(&["*.log", "**/logs/*.log"], false);
+        assert_eq!(
+            exclusions.matching(Path::new("/srv/logs/app.log")),
+            Some("**/logs/*.log")
+        );
+        assert_eq!(
+            exclusions.matching(Path::new("/srv/app.log")),
+            Some("*.log")
+        );
+
+        // An excluded directory decides for everything inside it.
+        let exclusions = set(&["*.log", "logs"], false);
+        assert_eq!(
+            exclusions.matching(Path::new("/srv/logs/app.log")),
+            Some("logs")
+        );
+    }
+
+    #[test]
+    fn negations_re_include_with_last_match_wins() {
+        let exclusions = set(&["target", "!target/doc", "*.log", "!important.log"], false);
+
+        assert!(excluded(&exclusions, "/repo/target/debug/app"));
+        assert!(!excluded(&exclusions, "/repo/target/doc"));
+        assert!(!excluded(&exclusions, "/repo/target/doc/index.html"));
+        assert!(excluded(&exclusions, "/repo/target/doc/build.log"));
+        assert!(!excluded(&exclusions, "/repo/important.log"));
+        assert!(excluded(&exclusions, "/repo/server.log"));
+
+        // The walk only descends into `target` for the sake of `target/doc`.
+        assert!(!exclusions.prunes(Path::new("/repo/target")));
+        assert!(exclusions.is_excluded(Path::new("/repo/target")));
+        assert!(exclusions.prunes(Path::new("/repo/target/debug")));
+        assert!(!exclusions.prunes(Path::new("/repo/src")));
+
+        // A negation declared before the exclusion it targets is overridden.
+        let exclusions = set(&["!target/doc", "target"], false);
+        assert!(excluded(&exclusions, "/repo/target/doc/index.html"));
+        assert!(exclusions.prunes(Path::new("/repo/target")));
+    }
+
+    #[test]
+    fn component_negations_do_not_reach_into_excluded_directories() {
+        let exclusions = set(&["Library", "*.md", "!README.md"], false);
+
+        assert!(!excluded(&exclusions, "/home/me/README.md"));
+        assert!(excluded(&exclusions, "/home/me/NOTES.md"));
+        assert!(excluded(&exclusions, "/home/me/Library/README.md"));
+        assert!(exclusions.prunes(Path::new("/home/me/Library")));
+    }
+
+    #[test]
+    fn anchored_negations_only_open_their_own_parents() {
+        let exclusions = set(&["node_modules", "!/srv/app/node_modules/local"], false);
+
+        assert!(!exclusions.prunes(Path::new("/srv/app/node_modules")));
+        assert!(exclusions.prunes(Path::new("/srv/other/node_modules")));
+        assert!(!excluded(
+            &exclusions,
+            "/srv/app/node_modules/local/index.js"
+        ));
+        assert!(excluded(
+            &exclusions,
+            "/srv/app/node_modules/left-pad/index.js"
+        ));
+        assert!(exclusions.has_negations());
+        assert!(!set(&["node_modules"], false).has_negations());
+    }
+
+    #[test]
+    fn unusable_patterns_are_reported_and_skipped() {
+        assert_eq!(validate_exclusion("src/*/generated"), Ok(()));
+        assert_eq!(validate_exclusion("!target/doc"), Ok(()));
+        assert_eq!(validate_exclusion("!"), Err(PatternError::Empty));
+        assert_eq!(
+            validate_exclusion("!**"),
+            Err(PatternError::MatchesEverything)
+        );
+        assert_eq!(validate_exclusion("/"), Err(PatternError::Empty));
+        assert_eq!(
+            validate_exclusion("*"),
+            Err(PatternError::MatchesEverything)
+        );
+        assert_eq!(
+            validate_exclusion("**/"),
+            Err(PatternError::MatchesEverything)
+        );
+        let err = validate_exclusion("[abc").unwrap_err();
+        assert!(matches!(err, PatternError::Invalid(_)), "{err:?}");
+        assert!(
+            err.to_string().contains("unclosed character class"),
+            "{err}"
+        );
+
+        let exclusions = set(&["*", "[abc", "node_modules"], false);
+        assert!(!excluded(&exclusions, "/repo/src/main.rs"));
+        assert!(excluded(&exclusions, "/repo/node_modules/x.js"));
+    }
+
+    #[test]
+    fn cache_recompiles_when_patterns_change() {
+        let cache = ExclusionCache::default();
+        let mut patterns = vec!["target".to_string()];
+
+        let first = cache.get(&patterns, false);
+        assert!(Arc::ptr_eq(&first, &cache.get(&patterns, false)));
+        assert!(!first.is_excluded(Path::new("/repo/dist/app.js")));
+
+        patterns.push("dist".to_string());
+        let second = cache.get(&patterns, false);
+        assert!(!Arc::ptr_eq(&first, &second));
+        assert!(second.is_excluded(Path::new("/repo/dist/app.js")));
+        assert!(!Arc::ptr_eq(&second, &cache.get(&patterns, true)));
+    }
 }
@@ -0,0 +1,170 @@
+//! User-defined commands run when indexed files change (`[[hooks]]`).
+//!
+//! The daemon matches every applied index update against each hook's
+//! [`HookPattern`]. Once a hook has seen no matching change for its
+//! `debounce_ms`, it runs the hook's command through `sh -c`, with
+//! [`render_command`] substituting the changed paths into the template.
+
+use globset::{GlobBuilder, GlobMatcher};
+use std::borrow::Cow;
+use std::path::Path;
+
+/// Compiled form of a hook's `pattern`.
+///
+/// Patterns without a `/` match the file name (`*.rs`); anything else is
+/// matched against the full path (`~/code/app/**/*.rs`). `*` never crosses a
+/// separator, `**` does.
+#[derive(Debug, Clone)]
+pub struct HookPattern {
+    matcher: GlobMatcher,
+    file_name_only: bool,
+}
+
+impl HookPattern {
+    /// Compile an already expanded pattern.
+    pub fn new(pattern: &str) -> crate::Result<Self> {
+        let pattern = pattern.trim();
+        if pattern.is_empty() {
+            return Err(crate::Error::Config("hook pattern is empty".to_string()));
+        }
+        let matcher = GlobBuilder::new(pattern)
+            .literal_separator(true)
+            .backslash_escape(true)
+            .build()
+            .map_err(|err| {
+                crate::Error::Config(format!("`{pattern}`: invalid glob: {}", err.kind()))
+            })?
+            .compile_matcher();
+        Ok(Self {
+            matcher,
+            file_name_only: !pattern.contains('/'),
+        })
+    }
+
+    /// Whether `path` is covered by this pattern.
+    pub fn matches(&self, path: &str) -> bool {
+        if self.file_name_only {
+            Path::new(path)
+                .file_name()
+                .is_some_and(|name| self.matcher.is_match(name))
+        } else {
+            self.matcher.is_match(path)
+        }
+    }
+}
+
+/// Substitute changed paths into a hook command template.
+///
+/// `{path}` becomes the most recent changed path, `{paths}` every distinct
+/// changed path separated by spaces, and `{count}` their number. Paths are
+/// shell-quoted, so templates must not quote the placeholders themselves.
+///
+/// The template is expanded in one pass, so placeholder text inside a
+/// substituted path is never expanded again.
+pub fn render_command(template: &str, paths: &[String]) -> String {
+    let last = paths
+        .last()
+        .map(|path| shell_quote(path))
+        .unwrap_or_default();
+    let all = paths
+        .iter()
+        .map(|path| shell_quote(path))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let count = paths.len().to_string();
+    let placeholders = [
+        ("{paths}", all.as_str()),
+        ("{path}", last.as_ref()),
+        ("{count}", count.as_str()),
+    ];
+
+    let mut command = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        command.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            Some((placeholder, value)) => {
+                command.push_str(value);
+                rest = &rest[placeholder.len()..];
+            }
+            None => {
+                command.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    command.push_str(rest);
+    command
+}
+
+/// Quote `value` for `sh` unless it consists only of safe characters.
+fn shell_quote(value: &str) -> Cow<'_, str> {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/._-+,:@%=".contains(c));
+    if safe {
+        Cow::Borrowed(value)
+    } else {
+        Cow::Owned(format!("'{}'", value.replace('\'', r"'\''")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_without_separator_match_file_names() {
+        let rust = HookPattern::new("*.rs").unwrap();
+        assert!(rust.matches("/code/app/src/main.rs"));
+        assert!(!rust.matches("/code/app/src/main.rs.orig"));
+
+        let scoped = HookPattern::new("/code/app/**/*.rs").unwrap();
+        assert!(scoped.matches("/code/app/src/deep/lib.rs"));
+        assert!(scoped.matches("/code/app/main.rs"));
+        assert!(!scoped.matches("/code/other/src/lib.rs"));
+
+        let shallow = HookPattern::new("/code/app/*.toml").unwrap();
+        assert!(shallow.matches("/code/app/Cargo.toml"));
+        assert!(!shallow.matches("/code/app/crates/x/Cargo.toml"));
+    }
+
+    #[test]
+    fn invalid_and_empty_patterns_are_rejected() {
+        assert!(HookPattern::new("  ").is_err());
+        let err = HookPattern::new("src/[ab").unwrap_err().to_string();
+        assert!(err.contains("invalid glob"), "{err}");
+    }
+
+    #[test]
+    fn commands_receive_quoted_paths_and_count() {
+        let paths = vec![
+            "/code/app/a.rs".to_string(),
+            "/code/my app/b's.rs".to_string(),
+        ];
+        assert_eq!(
+            render_command("ctags -a {paths} # {count}, last {path}", &paths),
+            r"ctags -a /code/app/a.rs '/code/my app/b'\''s.rs' # 2, last '/code/my app/b'\''s.rs'"
+        );
+        assert_eq!(render_command("make tags", &paths), "make tags");
+    }
+
+    #[test]
+    fn placeholders_inside_paths_are_not_expanded() {
+        let paths = vec!["/a/x{path}y".to_string(), "/b/$(touch pwned)".to_string()];
+        assert_eq!(
+            render_command("echo {paths}", &paths),
+            "echo '/a/x{path}y' '/b/$(touch pwned)'"
+        );
+        let paths = vec!["/a/{count}'{paths}".to_string()];
+        assert_eq!(
+            render_command("echo {path} {count} {{path}", &paths),
+            r"echo '/a/{count}'\''{paths}' 1 {'/a/{count}'\''{paths}'"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Translated user-facing text for the CLI and TUI.
+//!
+//! Strings live in TOML bundles under `locales/`, one per language, compiled
+//! into the binaries. Keys are dotted paths (`tui.footer.help`), and `{name}`
+//! placeholders are filled by [`format`]. A key missing from a translation
+//! falls back to English; a key missing from English shows as the key itself,
+//! so a typo is visible instead of blank.
+//!
+//! Each binary calls [`init`] once with the configured `language`; until then,
+//! and in tests, text is English.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+const EN: &str = include_str!("../locales/en.toml");
+const DE: &str = include_str!("../locales/de.toml");
+
+/// Language of the CLI and TUI text (`language` in the config).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Follow `LC_ALL`, `LC_MESSAGES`, or `LANG`; English when none names a
+    /// bundled language.
+    Auto,
+    #[default]
+    En,
+    De,
+}
+
+impl Language {
+    /// Bundled languages, English first.
+    pub const BUNDLED: [Language; 2] = [Language::En, Language::De];
+
+    /// The bundled language a locale such as `de_DE.UTF-8` names, if any.
+    pub fn from_locale(locale: &str) -> Option<Self> {
+        let code = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match code.as_str() {
+            "en" => Some(Self::En),
+            "de" => Some(Self::De),
+            _ => None,
+        }
+    }
+
+    /// This language, with `auto` resolved from the environment.
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .into_iter()
+                .filter_map(|var| std::env::var(var).ok())
+                .find(|value| !value.is_empty())
+                .and_then(|locale| Self::from_locale(&locale))
+                .unwrap_or(Self::En),
+            language => language,
+        }
+    }
+
+    fn bundle(self) -> &'static str {
+        match self {
+            Self::Auto | Self::En => EN,
+            Self::De => DE,
+        }
+    }
+}
+
+impl std::str::FromStr for Language {
+    type Err = crate::Error;
+
+    fn from_str(value: &str) -> crate::Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Ok(Self::Auto),
+            "en" => Ok(Self::En),
+            "de" => Ok(Self::De),
+            other => Err(crate::Error::Config(format!(
+                "unknown language '{other}' (expected auto, en, or de)"
+            ))),
+        }
+    }
+}
+
+/// The strings of one language, with English filling any gaps.
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    language: Language,
+    strings: HashMap<String, String>,
+}
+
+impl Catalog {
+    /// The catalog for `language`, resolving `auto`.
+    pub fn new(language: Language) -> Self {
+        let language = language.resolve();
+        let mut strings = parse_bundle(EN);
+        if language != Language::En {
+            strings.extend(parse_bundle(language.bundle()));
+        }
+        Self { language, strings }
+    }
+
+    /// The language the catalog resolved to.
+    pub fn language(&self) -> Language {
+        self.language
+    }
+
+    /// The text for `key`, or `key` itself when no bundle has it.
+    pub fn text<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings.get(key).map(String::as_str).unwrap_or(key)
+    }
+
+    /// The text for `key` with each `{name}` replaced by its value in `args`.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+        let mut text = self.text(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+}
+
+/// Flatten a bundle's nested tables into dotted keys. The bundles are
+/// compiled in and covered by tests, so a broken one is a build bug.
+fn parse_bundle(source: &str) -> HashMap<String, String> {
+    fn flatten(prefix: &str, table: &toml::Table, out: &mut HashMap<String, String>) {
+        for (key, value) in table {
+            let key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            match value {
+                toml::Value::Table(table) => flatten(&key, table, out),
+                toml::Value::String(text) => {
+                    out.insert(key, text.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let table: toml::Table = source.parse().expect("bundled locale is valid TOML");
+    let mut out = HashMap::new();
+    flatten("", &table, &mut out);
+    out
+}
+
+static CATALOG: OnceLock<Catalog> = OnceLock::new();
+
+/// Use `language` for this process's text. Only the first call has an
+/// effect, and only before any text was looked up.
+pub fn init(language: Language) {
+    let _ = CATALOG.set(Catalog::new(language));
+}
+
+/// The process-wide catalog; English when [`init`] was not called.
+pub fn catalog() -> &'static Catalog {
+    CATALOG.get_or_init(|| Catalog::new(Language::En))
+}
+
+/// The text for `key` in the process-wide catalog.
+pub fn t(key: &'static str) -> &'static str {
+    catalog().text(key)
+}
+
+/// [`Catalog::format`] on the process-wide catalog.
+pub fn format(key: &str, args: &[(&str, &dyn std::fmt::Display)]) -> String {
+    catalog().format(key, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn placeholders(text: &str) -> Vec<&str> {
+        let mut names: Vec<&str> = text
+            .split('{')
+            .skip(1)
+            .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn translations_cover_english_with_the_same_placeholders() {
+        let english = parse_bundle(EN);
+        assert!(english.contains_key("tui.footer.help"));
+        for language in Language::BUNDLED.into_iter().skip(1) {
+            let bundle = parse_bundle(language.bundle());
+            for (key, text) in &english {
+                let translated = bundle
+                    .get(key)
+                    .unwrap_or_else(|| panic!("{language:?} is missing {key}"));
+                assert_eq!(
+                    placeholders(translated),
+                    placeholders(text),
+                    "{language:?} {key}"
+                );
+            }
+            for key in bundle.keys() {
+                assert!(english.contains_key(key), "{language:?} has unknown {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn catalog_translates_formats_and_falls_back() {
+        let german = Catalog::new(Language::De);
+        assert_eq!(german.language(), Language::De);
+        assert_eq!(german.text("tui.footer.quit"), "beenden");
+        assert_eq!(
+            german.format("cli.status.root_files", &[("count", &"1,024")]),
+            "1,024 Dateien"
+        );
+        assert_eq!(german.text("no.such.key"), "no.such.key");
+
+        let english = Catalog::new(Language::En);
+        assert_eq!(english.text("tui.footer.quit"), "quit");
+        assert_eq!(
+            english.format("cli.status.running_progress", &[("progress", &42)]),
+            "running (42%)"
+        );
+        // Unset, the process-wide catalog is English.
+        assert_eq!(t("tui.footer.help"), "help");
+    }
+
+    #[test]
+    fn languages_parse_and_resolve_from_locales() {
+        assert_eq!("DE".parse::<Language>().unwrap(), Language::De);
+        assert_eq!("auto".parse::<Language>().unwrap(), Language::Auto);
+        assert!("fr".parse::<Language>().is_err());
+
+        assert_eq!(Language::from_locale("de_DE.UTF-8"), Some(Language::De));
+        assert_eq!(Language::from_locale("en_GB"), Some(Language::En));
+        assert_eq!(Language::from_locale("C"), None);
+        assert_eq!(Language::from_locale("fr_FR.UTF-8"), None);
+        assert_eq!(Language::De.resolve(), Language::De);
+    }
+}
@@ -1,9 +1,17 @@
 //! IPC protocol for daemon communication.
 
-use std::io::BufRead;
+use std::io::{BufRead, Write};
 
+use schemars::gen::SchemaSettings;
+use schemars::schema::{Metadata, RootSchema, SchemaObject, SubschemaValidation};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
+use crate::accounting::SubsystemUsage;
+use crate::config::{IndexProfile, JournalFsync, ResolvedScan};
+use crate::permissions::AttributeFilter;
+use crate::query::CmpU64;
 use crate::smriti::{SmritiAction, SmritiEntry};
 use crate::{Error, Result};
 
@@ -11,7 +19,7 @@ use crate::{Error, Result};
 pub const MAX_IPC_MESSAGE_BYTES: usize = 16 * 1024 * 1024;
 
 /// Build metadata for a running daemon or client.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
 pub struct BuildInfo {
     #[serde(default)]
     pub version: String,
@@ -23,28 +31,308 @@ pub struct BuildInfo {
     pub target: String,
 }
 
+/// Most queries one [`Request::SearchBatch`] may carry; clients split larger
+/// sets across requests.
+pub const MAX_SEARCH_BATCH: usize = 1_000;
+
+/// Current [`SearchOptions`] schema version.
+///
+/// Version 0 is the flat `query`/`filter_scope`/`recent_if_empty` layout that
+/// predates the options object; daemons still accept it.
+pub const SEARCH_OPTIONS_VERSION: u32 = 1;
+
+/// Options for [`Request::Search`].
+///
+/// Every field has a default, so clients may omit anything they do not set
+/// and options added later do not break older clients.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(from = "SearchOptionsWire")]
+#[schemars(default)]
+pub struct SearchOptions {
+    /// Schema version the client was built against.
+    pub version: u32,
+    /// The search term; empty lists recent files when `flags.recent_if_empty`.
+    pub term: String,
+    /// Maximum number of results.
+    pub limit: usize,
+    /// Number of ranked results to skip, for paging.
+    pub offset: usize,
+    /// Optional scope root (directory path) used to boost results "near" the user's context.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Constraints a result must satisfy.
+    #[serde(skip_serializing_if = "SearchFilters::is_empty")]
+    pub filters: SearchFilters,
+    /// Order of the returned page.
+    pub sort: SearchSort,
+    /// Whether abbreviation (fuzzy) matches are ranked alongside substring matches.
+    pub fuzzy: bool,
+    /// Boolean switches.
+    #[serde(skip_serializing_if = "SearchFlags::is_empty")]
+    pub flags: SearchFlags,
+    /// Ranking policy by name (`relevance`, `recency`, ...); `None` is relevance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scorer: Option<String>,
+    /// Whether directories rank above or below files with equal scores.
+    #[serde(skip_serializing_if = "DirectoryRank::is_neutral")]
+    pub directories: DirectoryRank,
+}
+
+impl Default for SearchOptions {
+    fn default() -> Self {
+        Self {
+            version: SEARCH_OPTIONS_VERSION,
+            term: String::new(),
+            limit: 20,
+            offset: 0,
+            scope: None,
+            filters: SearchFilters::default(),
+            sort: SearchSort::default(),
+            fuzzy: true,
+            flags: SearchFlags::default(),
+            scorer: None,
+            directories: DirectoryRank::default(),
+        }
+    }
+}
+
+impl SearchOptions {
+    /// Options for `term` with every other field at its default.
+    pub fn new(term: impl Into<String>, limit: usize) -> Self {
+        Self {
+            term: term.into(),
+            limit,
+            ..Self::default()
+        }
+    }
+}
+
+/// Constraints on [`SearchOptions`] results.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SearchFilters {
+    /// Optional scope root (directory path) used to strictly filter results to a subtree.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Ownership and permission Niyamas, evaluated against indexed metadata.
+    #[serde(skip_serializing_if = "AttributeFilter::is_empty")]
+    pub attributes: AttributeFilter,
+    /// Deepest result, in levels below `scope` (its entries are depth 1), or
+    /// below the index root holding it when unscoped.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_depth: Option<usize>,
+    /// Whether dotfiles and entries inside dot-directories (below `scope`
+    /// or the index root) are returned; `None` follows `show_hidden`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hidden: Option<bool>,
+    /// Only directories whose contents, at any depth, total this many bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir_size: Option<CmpU64>,
+}
+
+impl SearchFilters {
+    /// Whether no filter is set.
+    pub fn is_empty(&self) -> bool {
+        self.scope.is_none()
+            && self.attributes.is_empty()
+            && self.max_depth.is_none()
+            && self.hidden.is_none()
+            && self.dir_size.is_none()
+    }
+}
+
+/// How directories rank against files; by `ranking.directory_weight`
+/// tie-breaker points.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DirectoryRank {
+    #[default]
+    Neutral,
+    /// Directories first (the TUI's Sthana drishti).
+    Prefer,
+    /// Files first (Patra).
+    Demote,
+}
+
+impl DirectoryRank {
+    /// Whether directories rank like files.
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::Neutral
+    }
+
+    /// Parse a CLI/HTTP directory ranking name.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "neutral" => Ok(Self::Neutral),
+            "prefer" | "first" => Ok(Self::Prefer),
+            "demote" | "last" => Ok(Self::Demote),
+            other => Err(Error::Other(format!(
+                "unknown directory ranking '{other}' (expected neutral, prefer, or demote)"
+            ))),
+        }
+    }
+
+    /// Tie-breaker points for a directory, given the configured weight.
+    pub fn bias(self, weight: i32) -> i32 {
+        match self {
+            Self::Neutral => 0,
+            Self::Prefer => weight,
+            Self::Demote => -weight,
+        }
+    }
+}
+
+/// Order of a search result page.
+///
+/// Results are always ranked by relevance first; any other order rearranges
+/// the best matches (the daemon's sort window, or `offset + limit` if that is
+/// larger) rather than the whole index.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchSort {
+    #[default]
+    Relevance,
+    /// Basename, ascending.
+    Name,
+    /// Full path, ascending.
+    Path,
+    /// Modification time, newest first.
+    Mtime,
+    /// Size, largest first.
+    Size,
+}
+
+impl SearchSort {
+    /// Parse a CLI/HTTP sort name.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "relevance" | "score" => Ok(Self::Relevance),
+            "name" => Ok(Self::Name),
+            "path" => Ok(Self::Path),
+            "mtime" | "modified" => Ok(Self::Mtime),
+            "size" => Ok(Self::Size),
+            other => Err(Error::Other(format!(
+                "unknown sort '{other}' (expected relevance, name, path, mtime, or size)"
+            ))),
+        }
+    }
+}
+
+/// Boolean switches on [`SearchOptions`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct SearchFlags {
+    /// When the term is empty, return recent files instead of no results.
+    pub recent_if_empty: bool,
+    /// Attach a [`ScoreExplanation`] to every result.
+    pub explain: bool,
+    /// Keep a scoped search that finds nothing inside its scope, instead of
+    /// answering with matches from outside it (`[ranking] auto_broaden`).
+    pub no_broaden: bool,
+}
+
+impl SearchFlags {
+    /// Whether every flag is off.
+    pub fn is_empty(&self) -> bool {
+        !self.recent_if_empty && !self.explain && !self.no_broaden
+    }
+}
+
+/// What a search request may contain on the wire: the current options plus
+/// the version 0 top-level fields, which are folded in when present.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct SearchOptionsWire {
+    version: u32,
+    #[serde(alias = "query")]
+    term: String,
+    limit: Option<usize>,
+    offset: usize,
+    scope: Option<String>,
+    filters: SearchFilters,
+    sort: SearchSort,
+    fuzzy: Option<bool>,
+    flags: SearchFlags,
+    scorer: Option<String>,
+    directories: DirectoryRank,
+    filter_scope: Option<String>,
+    recent_if_empty: bool,
+    attributes: AttributeFilter,
+}
+
+impl From<SearchOptionsWire> for SearchOptions {
+    fn from(wire: SearchOptionsWire) -> Self {
+        let defaults = SearchOptions::default();
+        let mut filters = wire.filters;
+        if filters.scope.is_none() {
+            filters.scope = wire.filter_scope;
+        }
+        if filters.attributes.is_empty() {
+            filters.attributes = wire.attributes;
+        }
+        let mut flags = wire.flags;
+        flags.recent_if_empty |= wire.recent_if_empty;
+        Self {
+            version: wire.version,
+            term: wire.term,
+            limit: wire.limit.unwrap_or(defaults.limit),
+            offset: wire.offset,
+            scope: wire.scope,
+            filters,
+            sort: wire.sort,
+            fuzzy: wire.fuzzy.unwrap_or(defaults.fuzzy),
+            flags,
+            scorer: wire.scorer,
+            directories: wire.directories,
+        }
+    }
+}
+
 /// IPC request from client to daemon.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Request {
     /// Search for files.
-    Search {
-        query: String,
-        limit: usize,
-        /// Optional scope root (directory path) used to boost results "near" the user's context.
-        #[serde(default)]
-        scope: Option<String>,
-        /// Optional scope root (directory path) used to strictly filter results to a subtree.
-        #[serde(default)]
-        filter_scope: Option<String>,
-        /// When true and query is empty, return recent files instead of empty results.
+    Search(SearchOptions),
+    /// Run several searches in one round trip. The daemon answers them in
+    /// parallel and replies with one entry per query, in order. With
+    /// `total_limit`, results fill in query order until that many have been
+    /// returned across the batch.
+    SearchBatch {
+        queries: Vec<SearchOptions>,
         #[serde(default)]
-        recent_if_empty: bool,
+        total_limit: Option<usize>,
     },
     /// Get daemon status.
     Status,
+    /// Readiness check, answered with `Pong` as soon as the socket accepts
+    /// connections. A client that just started the daemon polls it until
+    /// startup warm-up is done instead of sleeping.
+    Ping,
+    /// Index statistics per index root, answered with `RootStats`. Walks the
+    /// whole index, so it is asked for on demand rather than polled.
+    RootStats,
+    /// Get cumulative CPU and I/O usage per daemon subsystem.
+    Metrics,
     /// Trigger index rebuild.
     Rebuild { dry_run: bool },
+    /// Start a full reconcile now, in the background, outside the
+    /// `reconcile_schedule`. Progress shows up in `Status`.
+    Reconcile,
+    /// Add exclusion patterns to the running daemon and drop the indexed
+    /// entries they match.
+    Exclude { patterns: Vec<String> },
+    /// A client failed to open or preview `path` because it no longer
+    /// exists. The daemon confirms that on disk and tombstones the entry
+    /// without waiting for a watcher event or reconcile.
+    ReportMissing { path: String },
+    /// List entries recently moved to the Trash, newest first, optionally
+    /// only those whose name or original path contains `query`.
+    RecentlyDeleted {
+        #[serde(default)]
+        query: Option<String>,
+        limit: usize,
+    },
     /// Record a best-effort Smriti usage event.
     SmritiRecord {
         path: String,
@@ -62,60 +350,422 @@ pub enum Request {
     },
     /// Forget one Smriti path.
     SmritiForget { path: String },
+    /// A client scoped its searches to the directory `path`. Global searches
+    /// rank results under recently used scopes higher for a while; the
+    /// daemon keeps this in memory only.
+    ScopeUsed { path: String },
+    /// Complete `prefix`, an absolute path whose last component may be
+    /// partial, from the index: entries under its directory that match that
+    /// component, most frecent first. `dirs_only` leaves out files.
+    CompletePath {
+        prefix: String,
+        limit: usize,
+        #[serde(default)]
+        dirs_only: bool,
+    },
+    /// Disk usage of the indexed directory `path`: its totals and its
+    /// largest immediate subdirectories, from sizes the daemon keeps current
+    /// as the index changes.
+    DirUsage { path: String, limit: usize },
+    /// Jump zoxide-style: the directory best matching `query`'s keywords,
+    /// most frecent first and ties to the most recent. The answer is
+    /// recorded as an `Enter`, so jumps teach later ones.
+    Jump { query: String },
+    /// File names starting with `prefix` (case-insensitive), for ghost-text
+    /// completion and launchers: names the user has used, most used and
+    /// then most recent first, topped up with indexed names that many
+    /// entries share.
+    Suggest { prefix: String, limit: usize },
+    /// Turn this connection into a live feed for a displayed search. The
+    /// daemon answers `Subscribed`, then pushes an `IndexChanged` whenever
+    /// watcher updates add results these options would return or take away
+    /// paths they may have returned. Sending another `Subscribe` replaces
+    /// the options; other requests are still answered in between.
+    Subscribe(SearchOptions),
     /// Clear all Smriti usage memory.
     SmritiClear,
+    /// Reveal a path in Finder / the file manager.
+    Reveal { path: String },
+    /// Open a path in the desktop's default editor; the daemon does not wait for it.
+    OpenEditor { path: String },
+    /// Copy a path to the clipboard.
+    CopyPath { path: String },
+    /// Hand the live index to a new daemon binary: the daemon saves a
+    /// handoff snapshot and execs `binary` (default: its own executable)
+    /// under the same PID, which resumes serving from the snapshot instead
+    /// of rescanning.
+    Upgrade {
+        #[serde(default)]
+        binary: Option<String>,
+    },
     /// Shutdown the daemon.
     Shutdown,
+    /// Switch this connection to another [`Framing`]. The daemon answers
+    /// with `Response::Framing` in the current framing and uses the new one
+    /// for every message after it, in both directions. A client may send
+    /// its next request right behind this one without waiting for the reply.
+    Framing { framing: Framing },
+}
+
+/// Daemon and index status, the answer to `Request::Status`.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct DaemonStatus {
+    /// Daemon process ID.
+    #[serde(default)]
+    pub pid: i32,
+    /// Daemon build metadata (useful to detect client/daemon mismatches).
+    #[serde(default)]
+    pub build: BuildInfo,
+    pub indexed_files: usize,
+    pub trigram_count: usize,
+    pub arena_size: usize,
+    /// Approximate heap bytes used by index structures.
+    #[serde(default)]
+    pub index_allocated_bytes: u64,
+    /// Approximate heap bytes used by daemon state (index + maps).
+    #[serde(default)]
+    pub state_allocated_bytes: u64,
+    pub last_updated: i64,
+    /// Whether the daemon is currently rebuilding/reconciling the index.
+    #[serde(default)]
+    pub reconciling: bool,
+    /// Watcher events received but not yet applied to the live index.
+    #[serde(default)]
+    pub pending_watcher_events: usize,
+    /// Watcher updates journaled since the last index checkpoint.
+    #[serde(default)]
+    pub journal_entries: usize,
+    /// Estimated reconcile progress (0-100) while `reconciling` is set.
+    #[serde(default)]
+    pub reconcile_progress: Option<u8>,
+    /// Scan profile, threads and throttle of the running reconcile.
+    #[serde(default)]
+    pub reconcile_scan: Option<ResolvedScan>,
+    /// Milliseconds the startup index warm-up took, once it has finished.
+    #[serde(default)]
+    pub warmup_ms: Option<u64>,
+    /// Journal durability policy the watcher applies to appends.
+    #[serde(default)]
+    pub journal_fsync: Option<JournalFsync>,
+    /// Set when `max_indexed_files` kept entries out of the index.
+    #[serde(default)]
+    pub index_cap: Option<IndexCap>,
+    /// When the last full reconcile finished (Unix timestamp).
+    #[serde(default)]
+    pub last_reconcile: Option<i64>,
+    /// When the next scheduled reconcile is due (Unix timestamp).
+    #[serde(default)]
+    pub next_reconcile: Option<i64>,
+    /// Active `index_profile`; lite indexes report no directories and
+    /// zero sizes.
+    #[serde(default)]
+    pub index_profile: IndexProfile,
 }
 
 /// IPC response from daemon to client.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+// One response is built per request, so `Status` being the largest variant
+// costs nothing worth boxing its fields for.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Response {
     /// Search results.
-    SearchResults { results: Vec<SearchResult> },
-    /// Status information.
-    Status {
-        /// Daemon process ID.
-        #[serde(default)]
-        pid: i32,
-        /// Daemon build metadata (useful to detect client/daemon mismatches).
+    SearchResults {
+        results: Vec<SearchResult>,
+        /// Whether the query budget ran out, so these are the best results
+        /// found so far rather than the full ranking.
         #[serde(default)]
-        build: BuildInfo,
+        truncated: bool,
+        /// Indexed names close to the term, offered when nothing matched.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        suggestions: Vec<String>,
+        /// Matches outside `filters.scope`, listed after `results` when a
+        /// scoped search found nothing inside it; see
+        /// [`SearchFlags::no_broaden`].
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        outside_scope: Vec<SearchResult>,
+    },
+    /// Status information.
+    Status(DaemonStatus),
+    /// Answer to `Ping`.
+    Pong {
+        /// Whether startup warm-up has finished (or is disabled), so
+        /// searches run at full speed.
+        ready: bool,
+        /// Live entries in the loaded index.
         indexed_files: usize,
-        trigram_count: usize,
-        arena_size: usize,
-        /// Approximate heap bytes used by index structures.
-        #[serde(default)]
-        index_allocated_bytes: u64,
-        /// Approximate heap bytes used by daemon state (index + maps).
-        #[serde(default)]
-        state_allocated_bytes: u64,
-        last_updated: i64,
-        /// Whether the daemon is currently rebuilding/reconciling the index.
+        /// Whether a reconcile is running. Searches are answered meanwhile
+        /// but may miss changes made while the daemon was down.
         #[serde(default)]
         reconciling: bool,
+        /// Estimated reconcile progress (0-100) while `reconciling` is set.
+        #[serde(default)]
+        reconcile_progress: Option<u8>,
+    },
+    /// Answer to `RootStats`, in `index_roots` order.
+    RootStats { roots: Vec<RootStats> },
+    /// Cumulative resource usage per subsystem.
+    Metrics {
+        /// Seconds since the daemon started.
+        uptime_secs: u64,
+        /// CPU time of the whole daemon process, attributed or not.
+        process_cpu_ns: u64,
+        subsystems: Vec<SubsystemUsage>,
     },
+    /// Results of a `SearchBatch`, one entry per query in request order.
+    SearchBatchResults { batches: Vec<BatchResults> },
     /// Rebuild completed.
     RebuildComplete { files_indexed: usize },
+    /// `Reconcile` accepted; `already_running` when one was in progress, in
+    /// which case no second one is queued.
+    ReconcileRequested { already_running: bool },
+    /// Exclusions added; `removed` entries were dropped from the index.
+    Excluded { removed: usize },
+    /// Result of `ReportMissing`; `removed` is false when the path still
+    /// exists or was not indexed.
+    MissingReported { removed: bool },
+    /// Entries recently moved to the Trash that are still there.
+    RecentlyDeleted { entries: Vec<DeletedEntry> },
+    /// `Upgrade` accepted; the daemon execs `binary` (reporting `version`)
+    /// once this reply is sent.
+    Upgrading { binary: String, version: String },
     /// Operation succeeded.
     Ok,
     /// Smriti usage entries.
     SmritiEntries { entries: Vec<SmritiEntry> },
     /// Result of forgetting one Smriti path.
     SmritiForgot { removed: bool },
+    /// Answer to `CompletePath`, best first.
+    PathCompletions { completions: Vec<PathCompletion> },
+    /// Answer to `DirUsage`: `total` covers everything below the directory,
+    /// `entries` its subdirectories, largest first.
+    DirUsage {
+        total: DirSize,
+        entries: Vec<DirUsageEntry>,
+    },
+    /// Answer to `Jump`; `None` when no directory matches.
+    JumpTarget { path: Option<String> },
+    /// Answer to `Suggest`, best first.
+    Suggestions { names: Vec<String> },
+    /// `Framing` accepted; later messages on the connection use `framing`.
+    Framing { framing: Framing },
+    /// `Subscribe` accepted; `IndexChanged` frames follow as the index does.
+    Subscribed,
+    /// Pushed to a `Subscribe` connection after watcher updates: `removed`
+    /// paths were deleted, moved away, or no longer match, and `added`
+    /// entries are new or changed matches, scored like search results.
+    IndexChanged {
+        removed: Vec<String>,
+        added: Vec<SearchResult>,
+    },
     /// Error occurred.
     Error { message: String },
 }
 
+/// Results of one query in a [`Request::SearchBatch`].
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BatchResults {
+    pub results: Vec<SearchResult>,
+    /// The query budget ran out, or the batch's `total_limit` cut this
+    /// query's results short.
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// An indexed entry the daemon saw moved to the Trash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DeletedEntry {
+    pub name: String,
+    /// Where the entry was indexed before it was trashed.
+    pub original_path: String,
+    /// Where it is now.
+    pub trash_path: String,
+    pub size: u64,
+    #[serde(default)]
+    pub is_dir: bool,
+    /// When the move was seen (Unix timestamp).
+    pub deleted_at: i64,
+}
+
+/// One [`Request::CompletePath`] completion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct PathCompletion {
+    /// Absolute path of the indexed entry.
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// What `max_indexed_files` kept out of the index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct IndexCap {
+    /// The configured cap.
+    pub max_indexed_files: usize,
+    /// Entries the last scan left out.
+    pub skipped: usize,
+    /// New entries the watcher saw while the index was full.
+    #[serde(default)]
+    pub skipped_live: usize,
+    /// How the last scan covered each root, in `index_roots` order.
+    #[serde(default)]
+    pub roots: Vec<RootCoverage>,
+}
+
+impl IndexCap {
+    /// Entries left out in total.
+    pub fn total_skipped(&self) -> usize {
+        self.skipped + self.skipped_live
+    }
+}
+
+/// How much of one index root a capped scan kept.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RootCoverage {
+    pub root: String,
+    pub indexed: usize,
+    pub skipped: usize,
+    /// Deepest level below the root (the root itself is 0) that was indexed
+    /// in full; `None` when not even the root fit.
+    pub complete_depth: Option<usize>,
+}
+
+/// What one index root contributes to the index.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct RootStats {
+    pub root: String,
+    /// Live entries below the root, the root itself included.
+    pub files: usize,
+    /// How many of `files` are directories.
+    pub dirs: usize,
+    /// Sum of the entries' sizes (0 with `index_profile = "lite"`).
+    pub total_bytes: u64,
+    /// Estimated index memory for these entries: their file table rows and
+    /// basenames. Shared directory paths and trigrams are not attributed.
+    pub memory_bytes: u64,
+    /// When the root was last scanned in full (Unix timestamp).
+    pub last_scan: Option<i64>,
+}
+
 /// A search result.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct SearchResult {
     pub path: String,
     pub name: String,
     pub score: f32,
+    /// Bytes: the entry's own `st_size`, for directories too.
     pub size: u64,
     pub mtime: i64,
+    #[serde(default)]
+    pub uid: u32,
+    #[serde(default)]
+    pub gid: u32,
+    /// Raw `st_mode`; 0 from daemons that predate ownership metadata.
+    #[serde(default)]
+    pub mode: u32,
+    /// When the daemon last read the entry's metadata from disk (Unix
+    /// timestamp); 0 from daemons that predate it.
+    #[serde(default)]
+    pub indexed_at: i64,
+    /// Content fingerprint as 16 hex digits; absent unless the daemon runs
+    /// with `[content_hash] enabled`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    /// Whether the entry is a directory.
+    #[serde(default)]
+    pub is_dir: bool,
+    /// For a directory, the total bytes of the indexed files below it;
+    /// absent for files and from daemons that predate directory totals.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_size: Option<u64>,
+    /// How the score was reached; only set when `SearchFlags::explain` was.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ScoreExplanation>,
+}
+
+/// Bytes and files below a directory, at any depth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DirSize {
+    pub bytes: u64,
+    pub files: u64,
+}
+
+/// One subdirectory in a [`Response::DirUsage`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DirUsageEntry {
+    /// Absolute path of the subdirectory.
+    pub path: String,
+    pub size: DirSize,
+}
+
+/// How a search term matched a result.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchStrategy {
+    /// The basename equals the term.
+    #[default]
+    Exact,
+    /// The basename starts with the term.
+    Prefix,
+    /// The term starts a word in the basename (after `_` or a space).
+    Word,
+    /// The term appears elsewhere in the basename.
+    Substring,
+    /// The term only appears in a parent directory.
+    Path,
+    /// Abbreviation match (`mr` → `main.rs`).
+    Abbreviation,
+    /// Exact basename lookup that skips ranking.
+    ExactName,
+    /// Empty term; recent files by mtime.
+    Recent,
+}
+
+impl MatchStrategy {
+    /// Name shown by `--explain` and the TUI inspector (the serde name).
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchStrategy::Exact => "exact",
+            MatchStrategy::Prefix => "prefix",
+            MatchStrategy::Word => "word",
+            MatchStrategy::Substring => "substring",
+            MatchStrategy::Path => "path",
+            MatchStrategy::Abbreviation => "abbreviation",
+            MatchStrategy::ExactName => "exact-name",
+            MatchStrategy::Recent => "recent",
+        }
+    }
+}
+
+/// Breakdown of one result's rank, for `SearchFlags::explain`.
+///
+/// Results are ordered by `score` (`base_score + smriti_boost +
+/// recent_scope_boost`, capped at 1.0), then by [`ScoreExplanation::context_rank`], then newer `mtime`,
+/// then smaller `path_depth`, then path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ScoreExplanation {
+    pub strategy: MatchStrategy,
+    /// Match score before boosts (0.0 to 1.0).
+    pub base_score: f32,
+    /// Smriti recency and frequency boost added by the daemon.
+    pub smriti_boost: f32,
+    /// Boost added by the daemon in global searches for results under a
+    /// recently used search scope, fading as the scope goes unused.
+    pub recent_scope_boost: f32,
+    /// Demotion for dependency, cache, build, and tool-state directories (≤ 0).
+    pub context_penalty: i32,
+    /// Promotion for results under the search scope, larger when shallower.
+    pub scope_boost: i32,
+    /// Directory promotion or demotion from `SearchOptions::directories`.
+    pub directory_bias: i32,
+    /// Path components; shallower results win remaining ties.
+    pub path_depth: usize,
+}
+
+impl ScoreExplanation {
+    /// The tie-breaker applied after `score`.
+    pub fn context_rank(&self) -> i32 {
+        self.context_penalty + self.scope_boost + self.directory_bias
+    }
 }
 
 impl Request {
@@ -142,6 +792,39 @@ impl Response {
     }
 }
 
+/// JSON Schema (draft-07) for the socket protocol: a message is a
+/// [`Request`] or a [`Response`], both described under `definitions`.
+///
+/// Generated from the Rust types, so it changes exactly when the wire format
+/// does; `vicaya ipc-schema` prints it.
+pub fn json_schema() -> RootSchema {
+    let mut generator = SchemaSettings::draft07().into_generator();
+    let request = generator.subschema_for::<Request>();
+    let response = generator.subschema_for::<Response>();
+    RootSchema {
+        meta_schema: generator.settings().meta_schema.clone(),
+        schema: SchemaObject {
+            metadata: Some(Box::new(Metadata {
+                title: Some("vicaya IPC message".to_string()),
+                description: Some(format!(
+                    "One newline-delimited JSON message on the daemon socket, at most \
+                     {MAX_IPC_MESSAGE_BYTES} bytes: a Request from a client or a Response \
+                     from the daemon. A connection may switch to length-prefixed MessagePack \
+                     of the same types with a framing request. Search options are at \
+                     version {SEARCH_OPTIONS_VERSION}."
+                )),
+                ..Default::default()
+            })),
+            subschemas: Some(Box::new(SubschemaValidation {
+                any_of: Some(vec![request, response]),
+                ..Default::default()
+            })),
+            ..Default::default()
+        },
+        definitions: generator.take_definitions(),
+    }
+}
+
 /// Read one newline-delimited IPC message without unbounded allocation.
 ///
 /// Returns `Ok(None)` on clean EOF before any bytes are read. If EOF arrives
@@ -187,6 +870,132 @@ pub fn read_message<R: BufRead>(reader: &mut R) -> Result<Option<String>> {
     }
 }
 
+/// How messages are encoded on one connection.
+///
+/// Every connection starts with newline-delimited JSON, which stays the
+/// default and the format to debug with (`socat`, `nc`). `Request::Framing`
+/// switches a connection to MessagePack frames: a 4-byte big-endian length,
+/// then that many bytes of MessagePack with named fields, so the same serde
+/// types travel in both encodings and skip JSON text entirely.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Framing {
+    /// One JSON message per line.
+    #[default]
+    Json,
+    /// Length-prefixed MessagePack.
+    Msgpack,
+}
+
+impl Framing {
+    /// Parse a CLI framing name.
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "msgpack" | "messagepack" => Ok(Self::Msgpack),
+            other => Err(Error::Other(format!(
+                "unknown framing '{other}' (expected json or msgpack)"
+            ))),
+        }
+    }
+
+    /// The name [`Framing::parse`] takes.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Msgpack => "msgpack",
+        }
+    }
+
+    /// Encode `message` and write it as one frame.
+    pub fn write<W: Write, T: Serialize>(self, writer: &mut W, message: &T) -> Result<()> {
+        let frame = match self {
+            Self::Json => {
+                let mut json =
+                    serde_json::to_vec(message).map_err(|e| Error::Serialization(e.to_string()))?;
+                json.push(b'\n');
+                json
+            }
+            Self::Msgpack => {
+                let body = rmp_serde::to_vec_named(message)
+                    .map_err(|e| Error::Serialization(e.to_string()))?;
+                if body.len() > MAX_IPC_MESSAGE_BYTES {
+                    return Err(Error::Ipc(format!(
+                        "IPC message exceeds {} bytes",
+                        MAX_IPC_MESSAGE_BYTES
+                    )));
+                }
+                let mut frame = Vec::with_capacity(4 + body.len());
+                frame.extend_from_slice(&(body.len() as u32).to_be_bytes());
+                frame.extend_from_slice(&body);
+                frame
+            }
+        };
+        writer
+            .write_all(&frame)
+            .map_err(|e| Error::Ipc(format!("Failed to write IPC message: {}", e)))
+    }
+
+    /// Read the bytes of one frame, without its length prefix or newline.
+    ///
+    /// Returns `Ok(None)` on clean EOF before any bytes are read.
+    pub fn read_frame<R: BufRead>(self, reader: &mut R) -> Result<Option<Vec<u8>>> {
+        match self {
+            Self::Json => Ok(read_message(reader)?.map(String::into_bytes)),
+            Self::Msgpack => {
+                let mut len = [0u8; 4];
+                let mut filled = 0;
+                while filled < len.len() {
+                    match reader.read(&mut len[filled..]) {
+                        Ok(0) if filled == 0 => return Ok(None),
+                        Ok(0) => {
+                            return Err(Error::Ipc(
+                                "IPC connection closed inside a frame header".to_string(),
+                            ))
+                        }
+                        Ok(read) => filled += read,
+                        Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                        Err(e) => {
+                            return Err(Error::Ipc(format!("Failed to read IPC message: {}", e)))
+                        }
+                    }
+                }
+                let len = u32::from_be_bytes(len) as usize;
+                if len > MAX_IPC_MESSAGE_BYTES {
+                    return Err(Error::Ipc(format!(
+                        "IPC message exceeds {} bytes",
+                        MAX_IPC_MESSAGE_BYTES
+                    )));
+                }
+                let mut body = vec![0u8; len];
+                reader
+                    .read_exact(&mut body)
+                    .map_err(|e| Error::Ipc(format!("Failed to read IPC message: {}", e)))?;
+                Ok(Some(body))
+            }
+        }
+    }
+
+    /// Decode a frame read with [`Framing::read_frame`].
+    pub fn decode<T: DeserializeOwned>(self, frame: &[u8]) -> Result<T> {
+        match self {
+            Self::Json => {
+                serde_json::from_slice(frame).map_err(|e| Error::Serialization(e.to_string()))
+            }
+            Self::Msgpack => {
+                rmp_serde::from_slice(frame).map_err(|e| Error::Serialization(e.to_string()))
+            }
+        }
+    }
+
+    /// Read and decode one message; `Ok(None)` on clean EOF.
+    pub fn read<R: BufRead, T: DeserializeOwned>(self, reader: &mut R) -> Result<Option<T>> {
+        self.read_frame(reader)?
+            .map(|frame| self.decode(&frame))
+            .transpose()
+    }
+}
+
 /// Get the socket path for IPC communication.
 pub fn socket_path() -> std::path::PathBuf {
     crate::paths::socket_path()
@@ -201,30 +1010,64 @@ mod tests {
     #[test]
     fn test_request_serialization() {
         // Test Search request
-        let search = Request::Search {
-            query: "test".to_string(),
-            limit: 10,
-            scope: None,
-            filter_scope: None,
-            recent_if_empty: false,
+        let mut options = SearchOptions::new("test", 10);
+        options.filters.attributes = AttributeFilter {
+            owner: Some("me".to_string()),
+            readonly: Some(true),
+            executable: None,
+            content_hash: None,
         };
+        let search = Request::Search(options.clone());
         let json = search.to_json().unwrap();
-        let decoded: Request = Request::from_json(&json).unwrap();
         assert!(
-            matches!(decoded, Request::Search { query, limit, scope, filter_scope, recent_if_empty } if query == "test" && limit == 10 && scope.is_none() && filter_scope.is_none() && !recent_if_empty)
-        );
-        let legacy_json =
-            r#"{"type":"search","query":"test","limit":10,"scope":null,"recent_if_empty":false}"#;
-        let decoded = Request::from_json(legacy_json).unwrap();
-        assert!(
-            matches!(decoded, Request::Search { query, limit, scope, filter_scope: None, recent_if_empty } if query == "test" && limit == 10 && scope.is_none() && !recent_if_empty)
+            json.starts_with(r#"{"type":"search","version":1,"term":"test""#),
+            "{json}"
         );
+        assert!(!json.contains("executable"), "{json}");
+        assert!(!json.contains("flags"), "{json}");
+        let decoded: Request = Request::from_json(&json).unwrap();
+        assert!(matches!(decoded, Request::Search(decoded) if decoded == options));
+
+        // Version 0 clients send flat fields.
+        let legacy_json = r#"{"type":"search","query":"test","limit":10,"scope":null,"filter_scope":"/w","recent_if_empty":true}"#;
+        let Request::Search(decoded) = Request::from_json(legacy_json).unwrap() else {
+            panic!("expected a search request");
+        };
+        assert_eq!(decoded.version, 0);
+        assert_eq!(decoded.term, "test");
+        assert_eq!(decoded.limit, 10);
+        assert_eq!(decoded.filters.scope.as_deref(), Some("/w"));
+        assert!(decoded.flags.recent_if_empty);
+        assert!(decoded.fuzzy);
+        assert_eq!(decoded.sort, SearchSort::Relevance);
+
+        // Fields a client leaves out take their defaults.
+        let Request::Search(decoded) =
+            Request::from_json(r#"{"type":"search","term":"x","sort":"mtime","fuzzy":false}"#)
+                .unwrap()
+        else {
+            panic!("expected a search request");
+        };
+        assert_eq!(decoded.limit, SearchOptions::default().limit);
+        assert_eq!(decoded.sort, SearchSort::Mtime);
+        assert!(!decoded.fuzzy);
+        assert_eq!(decoded.scorer, None);
+        let Request::Search(decoded) =
+            Request::from_json(r#"{"type":"search","term":"x","scorer":"recency"}"#).unwrap()
+        else {
+            panic!("expected a search request");
+        };
+        assert_eq!(decoded.scorer.as_deref(), Some("recency"));
+        assert_eq!(SearchSort::parse(" Modified").unwrap(), SearchSort::Mtime);
+        assert!(SearchSort::parse("random").is_err());
 
         // Test Status request
         let status = Request::Status;
         let json = status.to_json().unwrap();
         let decoded = Request::from_json(&json).unwrap();
         assert!(matches!(decoded, Request::Status));
+        let decoded = Request::from_json(r#"{"type":"metrics"}"#).unwrap();
+        assert!(matches!(decoded, Request::Metrics));
 
         // Test Rebuild request
         let rebuild = Request::Rebuild { dry_run: true };
@@ -232,6 +1075,60 @@ mod tests {
         let decoded = Request::from_json(&json).unwrap();
         assert!(matches!(decoded, Request::Rebuild { dry_run: true }));
 
+        let exclude = Request::Exclude {
+            patterns: vec!["DerivedData".to_string()],
+        };
+        let json = exclude.to_json().unwrap();
+        assert_eq!(json, r#"{"type":"exclude","patterns":["DerivedData"]}"#);
+        let decoded = Response::from_json(r#"{"type":"excluded","removed":3}"#).unwrap();
+        assert!(matches!(decoded, Response::Excluded { removed: 3 }));
+
+        let batch = Request::SearchBatch {
+            queries: vec![
+                SearchOptions::new("main.rs", 5),
+                SearchOptions::new("lib.rs", 5),
+            ],
+            total_limit: Some(6),
+        };
+        let json = batch.to_json().unwrap();
+        match Request::from_json(&json).unwrap() {
+            Request::SearchBatch {
+                queries,
+                total_limit,
+            } => {
+                assert_eq!(queries[1].term, "lib.rs");
+                assert_eq!(total_limit, Some(6));
+            }
+            other => panic!("unexpected request: {other:?}"),
+        }
+        let decoded = Request::from_json(
+            r#"{"type":"searchbatch","queries":[{"term":"a"},{"query":"b","limit":3}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            decoded,
+            Request::SearchBatch { queries, total_limit: None }
+                if queries.len() == 2 && queries[1].term == "b" && queries[1].limit == 3
+        ));
+        let decoded = Response::from_json(
+            r#"{"type":"searchbatchresults","batches":[{"results":[]},{"results":[],"truncated":true}]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            decoded,
+            Response::SearchBatchResults { batches }
+                if batches.len() == 2 && !batches[0].truncated && batches[1].truncated
+        ));
+
+        let report = Request::ReportMissing {
+            path: "/tmp/gone.txt".to_string(),
+        };
+        let json = report.to_json().unwrap();
+        assert_eq!(json, r#"{"type":"reportmissing","path":"/tmp/gone.txt"}"#);
+
+        let decoded = Request::from_json(r#"{"type":"upgrade"}"#).unwrap();
+        assert!(matches!(decoded, Request::Upgrade { binary: None }));
+
         // Test Shutdown request
         let shutdown = Request::Shutdown;
         let json = shutdown.to_json().unwrap();
@@ -253,6 +1150,14 @@ mod tests {
                 action: SmritiAction::Open
             } if path == "/tmp/file.rs" && query == "file"
         ));
+
+        let open = Request::OpenEditor {
+            path: "/tmp/file.rs".to_string(),
+        };
+        let json = open.to_json().unwrap();
+        assert_eq!(json, r#"{"type":"openeditor","path":"/tmp/file.rs"}"#);
+        let decoded = Request::from_json(r#"{"type":"copypath","path":"/tmp/a"}"#).unwrap();
+        assert!(matches!(decoded, Request::CopyPath { path } if path == "/tmp/a"));
     }
 
     #[test]
@@ -265,14 +1170,44 @@ mod tests {
                 score: 0.95,
                 size: 1024,
                 mtime: 1234567890,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+                total_size: None,
+                explain: None,
             }],
+            truncated: true,
+            suggestions: Vec::new(),
+            outside_scope: Vec::new(),
         };
         let json = results.to_json().unwrap();
+        assert!(!json.contains("suggestions"));
+        assert!(!json.contains("outside_scope"));
         let decoded = Response::from_json(&json).unwrap();
-        assert!(matches!(decoded, Response::SearchResults { .. }));
+        assert!(matches!(
+            decoded,
+            Response::SearchResults {
+                truncated: true,
+                ..
+            }
+        ));
+
+        let legacy = Response::from_json(r#"{"type":"searchresults","results":[]}"#).unwrap();
+        assert!(matches!(
+            legacy,
+            Response::SearchResults {
+                truncated: false,
+                ref suggestions,
+                ref outside_scope,
+                ..
+            } if suggestions.is_empty() && outside_scope.is_empty()
+        ));
 
         // Test Status response
-        let status = Response::Status {
+        let status = Response::Status(DaemonStatus {
             pid: 123,
             build: BuildInfo::default(),
             indexed_files: 100,
@@ -281,19 +1216,69 @@ mod tests {
             index_allocated_bytes: 0,
             state_allocated_bytes: 0,
             last_updated: 1234567890,
-            reconciling: false,
-        };
+            reconciling: true,
+            pending_watcher_events: 7,
+            journal_entries: 42,
+            reconcile_progress: Some(55),
+            reconcile_scan: None,
+            warmup_ms: Some(12),
+            journal_fsync: Some(JournalFsync::Interval { interval_ms: 1_000 }),
+            index_cap: None,
+            last_reconcile: None,
+            next_reconcile: None,
+            index_profile: IndexProfile::Lite,
+        });
         let json = status.to_json().unwrap();
         let decoded = Response::from_json(&json).unwrap();
         assert!(matches!(
             decoded,
-            Response::Status {
+            Response::Status(DaemonStatus {
                 pid: 123,
                 indexed_files: 100,
+                pending_watcher_events: 7,
+                journal_entries: 42,
+                reconcile_progress: Some(55),
+                warmup_ms: Some(12),
+                journal_fsync: Some(JournalFsync::Interval { interval_ms: 1_000 }),
+                index_profile: IndexProfile::Lite,
                 ..
-            }
+            })
+        ));
+
+        // Older daemons omit queue depths entirely.
+        let legacy_json = r#"{"type":"status","indexed_files":1,"trigram_count":2,"arena_size":3,"last_updated":4}"#;
+        let decoded = Response::from_json(legacy_json).unwrap();
+        assert!(matches!(
+            decoded,
+            Response::Status(DaemonStatus {
+                pending_watcher_events: 0,
+                journal_entries: 0,
+                reconcile_progress: None,
+                reconcile_scan: None,
+                warmup_ms: None,
+                journal_fsync: None,
+                index_profile: IndexProfile::Full,
+                ..
+            })
         ));
 
+        let metrics = Response::Metrics {
+            uptime_secs: 60,
+            process_cpu_ns: 9_000,
+            subsystems: vec![crate::accounting::SubsystemUsage {
+                subsystem: crate::accounting::Subsystem::Watcher,
+                cpu_ns: 4_000,
+                read_bytes: None,
+                spans: 3,
+            }],
+        };
+        let json = metrics.to_json().unwrap();
+        assert!(json.contains(r#""subsystem":"watcher""#), "{json}");
+        let decoded = Response::from_json(&json).unwrap();
+        assert!(
+            matches!(decoded, Response::Metrics { uptime_secs: 60, subsystems, .. } if subsystems[0].cpu_ns == 4_000 && subsystems[0].read_bytes.is_none())
+        );
+
         // Test Ok response
         let ok = Response::Ok;
         let json = ok.to_json().unwrap();
@@ -307,6 +1292,26 @@ mod tests {
         let decoded = Response::from_json(&json).unwrap();
         assert!(matches!(decoded, Response::SmritiEntries { entries } if entries.is_empty()));
 
+        let reported = Response::MissingReported { removed: true };
+        let json = reported.to_json().unwrap();
+        let decoded = Response::from_json(&json).unwrap();
+        assert!(matches!(
+            decoded,
+            Response::MissingReported { removed: true }
+        ));
+
+        let upgrading = Response::Upgrading {
+            binary: "/usr/local/bin/vicaya-daemon".to_string(),
+            version: "0.9.0".to_string(),
+        };
+        let json = upgrading.to_json().unwrap();
+        let decoded = Response::from_json(&json).unwrap();
+        assert!(matches!(
+            decoded,
+            Response::Upgrading { binary, version }
+                if binary == "/usr/local/bin/vicaya-daemon" && version == "0.9.0"
+        ));
+
         let forgot = Response::SmritiForgot { removed: true };
         let json = forgot.to_json().unwrap();
         let decoded = Response::from_json(&json).unwrap();
@@ -345,6 +1350,14 @@ mod tests {
             score: 1.0,
             size: 2048,
             mtime: 1234567890,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
         };
 
         assert_eq!(result.path, "/home/user/test.rs");
@@ -354,6 +1367,71 @@ mod tests {
         assert_eq!(result.mtime, 1234567890);
     }
 
+    #[test]
+    fn score_explanations_are_sent_only_when_requested() {
+        let mut result = SearchResult {
+            path: "/repo/target/config".to_string(),
+            name: "config".to_string(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("explain"));
+
+        result.explain = Some(ScoreExplanation {
+            strategy: MatchStrategy::ExactName,
+            base_score: 1.0,
+            smriti_boost: 0.25,
+            recent_scope_boost: 0.0,
+            context_penalty: -60,
+            scope_boost: 118,
+            directory_bias: 0,
+            path_depth: 3,
+        });
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["explain"]["strategy"], "exact-name");
+        let decoded: SearchResult = serde_json::from_value(json).unwrap();
+        assert_eq!(decoded.explain.unwrap().context_rank(), 58);
+
+        let options: SearchOptions =
+            serde_json::from_str(r#"{"term":"config","flags":{"explain":true}}"#).unwrap();
+        assert!(options.flags.explain && !options.flags.recent_if_empty);
+    }
+
+    #[test]
+    fn directory_rank_is_omitted_when_neutral() {
+        let options = SearchOptions::new("src", 10);
+        assert!(!serde_json::to_string(&options)
+            .unwrap()
+            .contains("directories"));
+
+        let options: SearchOptions =
+            serde_json::from_str(r#"{"term":"src","directories":"prefer"}"#).unwrap();
+        assert_eq!(options.directories, DirectoryRank::Prefer);
+        assert_eq!(options.directories.bias(40), 40);
+        assert_eq!(DirectoryRank::Demote.bias(40), -40);
+        assert_eq!(DirectoryRank::Neutral.bias(40), 0);
+        assert_eq!(
+            DirectoryRank::parse(" Last ").unwrap(),
+            DirectoryRank::Demote
+        );
+        assert!(DirectoryRank::parse("sideways").is_err());
+
+        let result: SearchResult =
+            serde_json::from_str(r#"{"path":"/a","name":"a","score":1.0,"size":0,"mtime":0}"#)
+                .unwrap();
+        assert!(!result.is_dir, "older daemons send no is_dir");
+    }
+
     #[test]
     fn test_read_message_under_limit_with_newline() {
         let mut reader = BufReader::new(&b"{\"type\":\"status\"}\n"[..]);
@@ -384,4 +1462,74 @@ mod tests {
         let err = read_message(&mut reader).unwrap_err();
         assert!(matches!(err, Error::Ipc(message) if message.contains("exceeds")));
     }
+
+    #[test]
+    fn both_framings_roundtrip_requests_and_responses_on_one_stream() {
+        let request = Request::Search(SearchOptions {
+            scope: Some("/repo".to_string()),
+            directories: DirectoryRank::Prefer,
+            ..SearchOptions::new("main.rs", 5)
+        });
+        let response = Response::SearchResults {
+            results: vec![SearchResult {
+                path: "/repo/src/main.rs".to_string(),
+                name: "main.rs".to_string(),
+                score: 0.5,
+                size: 12,
+                mtime: 34,
+                uid: 0,
+                gid: 0,
+                mode: 0o644,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+                total_size: None,
+                explain: None,
+            }],
+            truncated: true,
+            suggestions: Vec::new(),
+            outside_scope: Vec::new(),
+        };
+
+        for framing in [Framing::Json, Framing::Msgpack] {
+            let mut stream = Vec::new();
+            framing.write(&mut stream, &request).unwrap();
+            framing.write(&mut stream, &response).unwrap();
+            framing.write(&mut stream, &Request::Status).unwrap();
+
+            let mut reader = BufReader::new(stream.as_slice());
+            let decoded: Request = framing.read(&mut reader).unwrap().unwrap();
+            assert!(matches!(
+                decoded,
+                Request::Search(options) if options.term == "main.rs"
+                    && options.scope.as_deref() == Some("/repo")
+                    && options.directories == DirectoryRank::Prefer
+            ));
+            let decoded: Response = framing.read(&mut reader).unwrap().unwrap();
+            assert!(matches!(
+                decoded,
+                Response::SearchResults { results, truncated: true, suggestions, .. }
+                    if results[0].mode == 0o644 && suggestions.is_empty()
+            ));
+            let decoded: Request = framing.read(&mut reader).unwrap().unwrap();
+            assert!(matches!(decoded, Request::Status));
+            assert!(framing.read::<_, Request>(&mut reader).unwrap().is_none());
+        }
+
+        assert_eq!(Framing::parse(" MsgPack ").unwrap(), Framing::Msgpack);
+        assert!(Framing::parse("cbor").is_err());
+    }
+
+    #[test]
+    fn msgpack_frames_are_bounded_and_truncation_is_an_error() {
+        let oversized = ((MAX_IPC_MESSAGE_BYTES + 1) as u32).to_be_bytes();
+        let mut reader = BufReader::new(&oversized[..]);
+        let err = Framing::Msgpack.read_frame(&mut reader).unwrap_err();
+        assert!(matches!(err, Error::Ipc(message) if message.contains("exceeds")));
+
+        let mut reader = BufReader::new(&[0u8, 0][..]);
+        assert!(Framing::Msgpack.read_frame(&mut reader).is_err());
+        let mut reader = BufReader::new(&[0u8, 0, 0, 9, 1][..]);
+        assert!(Framing::Msgpack.read_frame(&mut reader).is_err());
+    }
 }
@@ -1,15 +1,33 @@
 //! vicaya-core: Core types, configuration, and logging for vicaya.
 
+pub mod accounting;
+pub mod actions;
+pub mod aliases;
+pub mod archive;
+pub mod audit;
+pub mod bookmarks;
 pub mod build_info;
 pub mod config;
+pub mod config_validation;
+pub mod content_hash;
 pub mod content_search;
+pub mod crash;
 pub mod daemon;
 pub mod error;
 pub mod filter;
+pub mod hooks;
+pub mod i18n;
 pub mod ipc;
 pub mod logging;
 pub mod paths;
+pub mod permissions;
+pub mod preview;
+pub mod query;
+pub mod schedule;
 pub mod smriti;
+pub mod telemetry;
+pub mod trash;
+pub mod volumes;
 
 pub use config::Config;
 pub use error::{Error, Result};
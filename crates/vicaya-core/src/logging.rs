@@ -16,6 +16,19 @@ pub fn init() {
         .init();
 }
 
+/// Initialize logging like [`init`], but write to stderr.
+///
+/// For command-line tools whose stdout is parsed (`--format json`, `plain`).
+pub fn init_stderr() {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("vicaya=info"));
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .init();
+}
+
 /// Initialize logging with a custom log level.
 pub fn init_with_level(level: &str) {
     let filter = EnvFilter::new(format!("vicaya={level}"));
@@ -4,11 +4,26 @@ use std::path::{Path, PathBuf};
 
 use crate::{Error, Result};
 
-/// Base directory for vicaya state (config, socket, pid, etc).
+/// Environment variable selecting a named profile.
+pub const PROFILE_ENV: &str = "VICAYA_PROFILE";
+
+/// Directory for vicaya state (config, index, socket, pid, etc).
+///
+/// This is [`base_dir`], or `<base>/profiles/<name>` when a named profile is
+/// active, so each profile runs its own daemon with its own config and index.
+pub fn vicaya_dir() -> PathBuf {
+    let base = base_dir();
+    match profile() {
+        Some(name) => base.join("profiles").join(name),
+        None => base,
+    }
+}
+
+/// Root of all vicaya state, shared by every profile.
 ///
 /// Defaults to `~/Library/Application Support/vicaya` on macOS, but can be
 /// overridden via `VICAYA_DIR` for testing or multi-instance setups.
-pub fn vicaya_dir() -> PathBuf {
+pub fn base_dir() -> PathBuf {
     if let Ok(dir) = std::env::var("VICAYA_DIR") {
         return PathBuf::from(dir);
     }
@@ -20,6 +35,57 @@ pub fn vicaya_dir() -> PathBuf {
         .join("vicaya")
 }
 
+/// The active named profile from `VICAYA_PROFILE`; unset, empty, or
+/// `default` selects the default (unnamed) profile.
+pub fn profile() -> Option<String> {
+    let name = std::env::var(PROFILE_ENV).ok()?;
+    let name = name.trim();
+    (!name.is_empty() && name != "default").then(|| name.to_string())
+}
+
+/// Activate profile `name` for this process and the daemon it spawns.
+///
+/// Names are limited to ASCII letters, digits, `-`, and `_` so they are safe
+/// as a directory name.
+pub fn set_profile(name: &str) -> Result<()> {
+    validate_profile_name(name)?;
+    std::env::set_var(PROFILE_ENV, name);
+    Ok(())
+}
+
+/// Apply a `--profile` flag, or validate the profile inherited through
+/// `VICAYA_PROFILE`. Returns the active profile.
+///
+/// Binaries call this before touching any path so a bad name fails loudly
+/// instead of being joined into the state directory.
+pub fn init_profile(flag: Option<&str>) -> Result<Option<String>> {
+    match flag {
+        Some(name) => set_profile(name)?,
+        None => {
+            if let Some(name) = profile() {
+                validate_profile_name(&name)?;
+            }
+        }
+    }
+    Ok(profile())
+}
+
+/// Check that `name` can be used as a profile name.
+pub fn validate_profile_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::Config(format!(
+            "invalid profile name '{name}': use 1-64 letters, digits, '-' or '_'"
+        )))
+    }
+}
+
 /// Path to the vicaya configuration file.
 pub fn config_path() -> PathBuf {
     vicaya_dir().join("config.toml")
@@ -40,12 +106,73 @@ pub fn smriti_path() -> PathBuf {
     vicaya_dir().join("smriti.json")
 }
 
+/// Path to the directory bookmark list.
+pub fn bookmarks_path() -> PathBuf {
+    vicaya_dir().join("bookmarks.json")
+}
+
+/// Path to the TUI's saved layout and display preferences.
+pub fn tui_state_path() -> PathBuf {
+    vicaya_dir().join("tui-state.json")
+}
+
+/// Path to the audit log of privileged daemon requests.
+pub fn audit_log_path() -> PathBuf {
+    vicaya_dir().join("audit.jsonl")
+}
+
+/// Directory of daemon crash reports.
+pub fn crash_dir() -> PathBuf {
+    vicaya_dir().join("crashes")
+}
+
+/// Path to the opt-in local ranking telemetry log.
+pub fn telemetry_path() -> PathBuf {
+    vicaya_dir().join("ranking-telemetry.jsonl")
+}
+
 /// Expand `~` and environment variables in a user-supplied path.
+///
+/// This is the single expansion routine for config roots, index paths, scope
+/// parameters, and watcher roots. Trailing separators are dropped so `~/` and
+/// `~` name the same root. Undefined variables leave the path unexpanded; use
+/// [`try_expand_user_path`] to surface them.
 pub fn expand_user_path(path: &Path) -> PathBuf {
+    try_expand_user_path(path).unwrap_or_else(|_| trim_trailing_separators(path))
+}
+
+/// Like [`expand_user_path`], but fails when a referenced variable is unset.
+pub fn try_expand_user_path(path: &Path) -> Result<PathBuf> {
     let path_str = path.to_string_lossy();
-    match shellexpand::full(&path_str) {
-        Ok(expanded) => PathBuf::from(expanded.as_ref()),
-        Err(_) => path.to_path_buf(),
+    let expanded = shellexpand::full(&path_str)
+        .map_err(|err| Error::Config(format!("cannot expand '{path_str}': {err}")))?;
+    Ok(trim_trailing_separators(Path::new(expanded.as_ref())))
+}
+
+/// Expand environment variables in an exclusion pattern.
+///
+/// `~` is left alone: exclusions match path components, so a home-relative
+/// absolute path could never match. A variable that is not set stays as
+/// written, since names such as `$RECYCLE.BIN` are literal.
+pub fn expand_pattern(pattern: &str) -> String {
+    shellexpand::env_with_context_no_errors(pattern, |name| std::env::var(name).ok()).into_owned()
+}
+
+/// Like [`expand_pattern`], but fails when a referenced variable is unset.
+pub fn try_expand_pattern(pattern: &str) -> Result<String> {
+    shellexpand::env(pattern)
+        .map(|expanded| expanded.into_owned())
+        .map_err(|err| Error::Config(format!("cannot expand '{pattern}': {err}")))
+}
+
+fn trim_trailing_separators(path: &Path) -> PathBuf {
+    let path_str = path.to_string_lossy();
+    if !path_str.ends_with('/') {
+        return path.to_path_buf();
+    }
+    match path_str.trim_end_matches('/') {
+        "" => PathBuf::from("/"),
+        trimmed => PathBuf::from(trimmed),
     }
 }
 
@@ -81,6 +208,85 @@ pub fn resolve_scope_dir(path: &Path) -> Result<PathBuf> {
     Ok(normalized)
 }
 
+/// Files whose presence marks a project directory.
+const PROJECT_MANIFESTS: &[&str] = &["Cargo.toml", "package.json", "go.mod", "pyproject.toml"];
+
+/// Find the project enclosing `start`: the nearest ancestor (or `start`
+/// itself) holding `.git`, falling back to the nearest one holding a project
+/// manifest such as `Cargo.toml` or `package.json`.
+///
+/// The search never reaches `$HOME` or `/`, so a dotfiles repository in the
+/// home directory does not count as a project.
+pub fn find_project_root(start: &Path) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    project_root_below(start, home.as_deref())
+}
+
+fn project_root_below(start: &Path, stop: Option<&Path>) -> Option<PathBuf> {
+    let mut manifest_dir = None;
+    for dir in start.ancestors() {
+        if dir.parent().is_none() || Some(dir) == stop {
+            break;
+        }
+        if dir.join(".git").exists() {
+            return Some(dir.to_path_buf());
+        }
+        if manifest_dir.is_none() && PROJECT_MANIFESTS.iter().any(|m| dir.join(m).is_file()) {
+            manifest_dir = Some(dir.to_path_buf());
+        }
+    }
+    manifest_dir
+}
+
+/// Directory suffixes macOS presents as a single item (applications,
+/// frameworks, plug-ins).
+pub const BUNDLE_EXTENSIONS: &[&str] = &[
+    "app",
+    "appex",
+    "bundle",
+    "framework",
+    "kext",
+    "plugin",
+    "prefpane",
+    "xpc",
+];
+
+/// Whether a directory named `name` is a macOS bundle.
+pub fn is_bundle_name(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            BUNDLE_EXTENSIONS
+                .iter()
+                .any(|bundle| ext.eq_ignore_ascii_case(bundle))
+        })
+}
+
+/// Whether a directory named `name` is an application bundle (`Foo.app`).
+pub fn is_application_name(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("app"))
+}
+
+/// The outermost bundle strictly between `root` and `path`, if any.
+///
+/// `path` itself never counts, so a bundle directory is not inside itself,
+/// and neither do bundles at or above `root`, so explicitly indexing
+/// `/Applications/Xcode.app` still descends into it.
+pub fn bundle_below<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    path.ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root) && *dir != root)
+        .filter(|dir| {
+            dir.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(is_bundle_name)
+        })
+        .last()
+}
+
 fn normalize_absolute_path(path: &Path) -> PathBuf {
     use std::path::Component;
 
@@ -118,6 +324,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn profiles_get_their_own_state_directory() {
+        let _lock = test_env_lock();
+        let base = tempfile::tempdir().unwrap();
+        std::env::set_var("VICAYA_DIR", base.path());
+        std::env::remove_var(PROFILE_ENV);
+        assert_eq!(profile(), None);
+        assert_eq!(socket_path(), base.path().join("daemon.sock"));
+
+        set_profile("work").unwrap();
+        assert_eq!(profile().as_deref(), Some("work"));
+        let dir = base.path().join("profiles").join("work");
+        assert_eq!(vicaya_dir(), dir);
+        assert_eq!(config_path(), dir.join("config.toml"));
+        assert_eq!(pid_file_path(), dir.join("daemon.pid"));
+        assert_eq!(base_dir(), base.path());
+
+        std::env::set_var(PROFILE_ENV, "default");
+        assert_eq!(vicaya_dir(), base.path());
+        assert!(set_profile("../etc").is_err());
+        assert!(set_profile("").is_err());
+
+        std::env::set_var(PROFILE_ENV, "a/b");
+        assert!(init_profile(None).is_err());
+        assert_eq!(
+            init_profile(Some("personal")).unwrap().as_deref(),
+            Some("personal")
+        );
+
+        std::env::remove_var(PROFILE_ENV);
+        std::env::remove_var("VICAYA_DIR");
+    }
+
     #[test]
     fn expand_user_path_preserves_relative_paths() {
         assert_eq!(expand_user_path(Path::new("./foo")), PathBuf::from("./foo"));
@@ -133,6 +372,55 @@ mod tests {
         );
     }
 
+    #[test]
+    fn expand_user_path_drops_trailing_separators_and_expands_braced_vars() {
+        let _lock = test_env_lock();
+        let home = std::env::var("HOME").expect("HOME should be set");
+        std::env::set_var("VICAYA_TEST_PROJECTS", "/work/projects/");
+
+        assert_eq!(expand_user_path(Path::new("~/")), PathBuf::from(&home));
+        assert_eq!(
+            expand_user_path(Path::new("${VICAYA_TEST_PROJECTS}")),
+            PathBuf::from("/work/projects")
+        );
+        assert_eq!(
+            expand_user_path(Path::new("$VICAYA_TEST_PROJECTS/app//")),
+            PathBuf::from("/work/projects/app")
+        );
+        assert_eq!(expand_user_path(Path::new("/")), PathBuf::from("/"));
+
+        std::env::remove_var("VICAYA_TEST_PROJECTS");
+    }
+
+    #[test]
+    fn try_expand_reports_undefined_variables() {
+        let err = try_expand_user_path(Path::new("$VICAYA_TEST_SURELY_UNSET/x")).unwrap_err();
+        assert!(
+            err.to_string().contains("VICAYA_TEST_SURELY_UNSET"),
+            "{err}"
+        );
+        assert_eq!(
+            expand_user_path(Path::new("$VICAYA_TEST_SURELY_UNSET/x/")),
+            PathBuf::from("$VICAYA_TEST_SURELY_UNSET/x")
+        );
+    }
+
+    #[test]
+    fn expand_pattern_expands_env_but_not_tilde() {
+        let _lock = test_env_lock();
+        std::env::set_var("VICAYA_TEST_CACHE", ".cache-dir");
+
+        assert_eq!(expand_pattern("${VICAYA_TEST_CACHE}"), ".cache-dir");
+        assert_eq!(expand_pattern("~backup"), "~backup");
+        assert!(try_expand_pattern("$VICAYA_TEST_SURELY_UNSET").is_err());
+        assert_eq!(
+            expand_pattern("$VICAYA_TEST_CACHE/$VICAYA_TEST_SURELY_UNSET.BIN"),
+            ".cache-dir/$VICAYA_TEST_SURELY_UNSET.BIN"
+        );
+
+        std::env::remove_var("VICAYA_TEST_CACHE");
+    }
+
     #[test]
     fn resolve_user_path_canonicalizes_relative_without_requiring_existence() {
         let _lock = test_env_lock();
@@ -162,6 +450,29 @@ mod tests {
         assert_eq!(resolved, expected_root.join("alpha/beta"));
     }
 
+    #[test]
+    fn project_root_prefers_git_then_nearest_manifest_below_home() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("home");
+        let repo = home.join("code").join("repo");
+        let crate_dir = repo.join("crates").join("app");
+        let src = crate_dir.join("src");
+        std::fs::create_dir_all(&src).unwrap();
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::write(crate_dir.join("Cargo.toml"), "").unwrap();
+
+        assert_eq!(project_root_below(&src, Some(&home)), Some(repo.clone()));
+
+        std::fs::remove_dir(repo.join(".git")).unwrap();
+        assert_eq!(project_root_below(&src, Some(&home)), Some(crate_dir));
+
+        // A repository at $HOME itself (dotfiles) is not a project.
+        std::fs::remove_file(repo.join("crates/app/Cargo.toml")).unwrap();
+        std::fs::create_dir_all(home.join(".git")).unwrap();
+        assert_eq!(project_root_below(&src, Some(&home)), None);
+        assert_eq!(project_root_below(&home, Some(&home)), None);
+    }
+
     #[test]
     fn resolve_scope_dir_rejects_files() {
         let dir = tempfile::tempdir().unwrap();
@@ -174,4 +485,35 @@ mod tests {
             "unexpected error: {err}"
         );
     }
+
+    #[test]
+    fn bundles_are_detected_below_the_root_only() {
+        assert!(is_bundle_name("Xcode.app"));
+        assert!(is_bundle_name("Sparkle.FRAMEWORK"));
+        assert!(!is_bundle_name("app"));
+        assert!(!is_bundle_name("notes.txt"));
+        assert!(is_application_name("Safari.app"));
+        assert!(!is_application_name("Sparkle.framework"));
+
+        let root = Path::new("/Applications");
+        let deep = Path::new("/Applications/Xcode.app/Contents/Frameworks/A.framework/A");
+        assert_eq!(
+            bundle_below(deep, root),
+            Some(Path::new("/Applications/Xcode.app"))
+        );
+        assert_eq!(
+            bundle_below(Path::new("/Applications/Xcode.app"), root),
+            None
+        );
+        assert_eq!(
+            bundle_below(deep, Path::new("/Applications/Xcode.app")),
+            Some(Path::new(
+                "/Applications/Xcode.app/Contents/Frameworks/A.framework"
+            ))
+        );
+        assert_eq!(
+            bundle_below(Path::new("/Users/me/src/main.rs"), Path::new("/Users/me")),
+            None
+        );
+    }
 }
@@ -0,0 +1,241 @@
+//! Ownership, permission, and fingerprint filters (`owner:`, `readonly:`,
+//! `executable:`, `hash:`).
+//!
+//! The index stores each entry's uid, gid, raw `st_mode`, and, with
+//! `[content_hash]` enabled, a content fingerprint. Clients send an
+//! [`AttributeFilter`] with a search; the daemon resolves it once into an
+//! [`AttributeMatcher`] and checks candidates against the stored bits. A mode
+//! of 0 means "unknown" (e.g. results from a daemon that predates these
+//! fields) and never fails a readability check.
+
+use std::num::NonZeroU64;
+use std::sync::OnceLock;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::content_hash::HashPrefix;
+
+const S_IFMT: u32 = 0o170_000;
+const S_IFREG: u32 = 0o100_000;
+
+/// Ownership and permission Niyamas as sent over IPC.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AttributeFilter {
+    /// Owning user, by name or numeric uid.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Keep only entries without (`true`) or with (`false`) any write bit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub readonly: Option<bool>,
+    /// Keep only files with (`true`) or without (`false`) an execute bit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub executable: Option<bool>,
+    /// Leading hex digits of the content fingerprint; unhashed entries
+    /// never match.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+}
+
+impl AttributeFilter {
+    /// Whether no attribute constraint is set.
+    pub fn is_empty(&self) -> bool {
+        self.owner.is_none()
+            && self.readonly.is_none()
+            && self.executable.is_none()
+            && self.content_hash.is_none()
+    }
+
+    /// Resolve owner names so candidates can be checked without lookups.
+    pub fn matcher(&self) -> AttributeMatcher {
+        AttributeMatcher {
+            owner: self.owner.as_deref().map(uid_for_owner),
+            readonly: self.readonly,
+            executable: self.executable,
+            content_hash: self.content_hash.as_deref().map(HashPrefix::parse),
+        }
+    }
+}
+
+/// An [`AttributeFilter`] with its owner resolved to a uid and its hash
+/// prefix parsed.
+///
+/// The default matches everything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AttributeMatcher {
+    /// `Some(None)` when the requested owner does not exist: nothing matches.
+    owner: Option<Option<u32>>,
+    readonly: Option<bool>,
+    executable: Option<bool>,
+    /// `Some(None)` when the prefix is not hex: nothing matches.
+    content_hash: Option<Option<HashPrefix>>,
+}
+
+impl AttributeMatcher {
+    /// Whether every entry matches.
+    pub fn is_empty(&self) -> bool {
+        self.owner.is_none()
+            && self.readonly.is_none()
+            && self.executable.is_none()
+            && self.content_hash.is_none()
+    }
+
+    /// Whether an entry owned by `uid` with `mode` and `content_hash`
+    /// passes the filter.
+    pub fn matches(&self, uid: u32, mode: u32, content_hash: Option<NonZeroU64>) -> bool {
+        self.owner.is_none_or(|owner| owner == Some(uid))
+            && self.readonly.is_none_or(|want| is_readonly(mode) == want)
+            && self
+                .executable
+                .is_none_or(|want| is_executable(mode) == want)
+            && self
+                .content_hash
+                .is_none_or(|prefix| prefix.is_some_and(|p| p.matches(content_hash)))
+    }
+}
+
+/// No write bit is set for anyone.
+pub fn is_readonly(mode: u32) -> bool {
+    mode != 0 && mode & 0o222 == 0
+}
+
+/// A regular file with an execute bit for anyone.
+pub fn is_executable(mode: u32) -> bool {
+    mode & S_IFMT == S_IFREG && mode & 0o111 != 0
+}
+
+/// The uid of `owner`, given as a user name or a numeric uid.
+pub fn uid_for_owner(owner: &str) -> Option<u32> {
+    let owner = owner.trim();
+    if let Ok(uid) = owner.parse::<u32>() {
+        return Some(uid);
+    }
+    let name = std::ffi::CString::new(owner).ok()?;
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut found: *mut libc::passwd = std::ptr::null_mut();
+    let rc = unsafe {
+        libc::getpwnam_r(
+            name.as_ptr(),
+            &mut pwd,
+            buf.as_mut_ptr(),
+            buf.len(),
+            &mut found,
+        )
+    };
+    (rc == 0 && !found.is_null()).then_some(pwd.pw_uid)
+}
+
+/// Effective uid plus every group the current process belongs to.
+struct Credentials {
+    uid: u32,
+    groups: Vec<u32>,
+}
+
+fn credentials() -> &'static Credentials {
+    static CREDENTIALS: OnceLock<Credentials> = OnceLock::new();
+    CREDENTIALS.get_or_init(|| {
+        let uid = unsafe { libc::geteuid() };
+        let mut groups = vec![unsafe { libc::getegid() }];
+        let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+        if count > 0 {
+            let mut extra = vec![0 as libc::gid_t; count as usize];
+            let count = unsafe { libc::getgroups(count, extra.as_mut_ptr()) };
+            extra.truncate(count.max(0) as usize);
+            groups.extend(extra);
+        }
+        Credentials { uid, groups }
+    })
+}
+
+/// Whether the current user may read an entry owned by `uid`:`gid` with
+/// `mode`. Unknown modes (0) are assumed readable.
+pub fn current_user_can_read(uid: u32, gid: u32, mode: u32) -> bool {
+    if mode == 0 {
+        return true;
+    }
+    let creds = credentials();
+    if creds.uid == 0 {
+        return true;
+    }
+    let bits = if uid == creds.uid {
+        mode >> 6
+    } else if creds.groups.contains(&gid) {
+        mode >> 3
+    } else {
+        mode
+    };
+    bits & 0o4 != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readonly_and_executable_follow_the_mode_bits() {
+        assert!(is_readonly(0o100_444));
+        assert!(!is_readonly(0o100_644));
+        assert!(!is_readonly(0));
+        assert!(is_executable(0o100_755));
+        assert!(!is_executable(0o100_644));
+        assert!(!is_executable(0o040_755), "directories are not executables");
+    }
+
+    #[test]
+    fn matcher_checks_owner_and_permissions() {
+        assert!(AttributeMatcher::default().matches(1, 0o100_644, None));
+
+        let filter = AttributeFilter {
+            owner: Some("501".to_string()),
+            executable: Some(true),
+            ..AttributeFilter::default()
+        };
+        let matcher = filter.matcher();
+        assert!(matcher.matches(501, 0o100_755, None));
+        assert!(!matcher.matches(501, 0o100_644, None));
+        assert!(!matcher.matches(0, 0o100_755, None));
+
+        let unknown = AttributeFilter {
+            owner: Some("no-such-user-vicaya".to_string()),
+            ..AttributeFilter::default()
+        };
+        assert!(!unknown.matcher().matches(0, 0o100_644, None));
+        assert_eq!(uid_for_owner("root"), Some(0));
+
+        let hash = NonZeroU64::new(0xab12_0000_0000_0000);
+        let by_hash = |prefix: &str| {
+            AttributeFilter {
+                content_hash: Some(prefix.to_string()),
+                ..AttributeFilter::default()
+            }
+            .matcher()
+        };
+        assert!(by_hash("ab1").matches(0, 0o100_644, hash));
+        assert!(!by_hash("ab1").matches(0, 0o100_644, None));
+        assert!(!by_hash("ac").matches(0, 0o100_644, hash));
+        assert!(!by_hash("zz").matches(0, 0o100_644, hash));
+    }
+
+    #[test]
+    fn readability_uses_the_matching_permission_class() {
+        let uid = credentials().uid;
+        assert!(current_user_can_read(uid, u32::MAX, 0));
+        if uid == 0 {
+            assert!(current_user_can_read(1, u32::MAX, 0o100_000));
+            return;
+        }
+        assert!(current_user_can_read(uid, u32::MAX, 0o100_400));
+        assert!(!current_user_can_read(uid, u32::MAX, 0o100_044));
+        assert!(current_user_can_read(
+            uid.wrapping_add(1),
+            u32::MAX,
+            0o100_004
+        ));
+        assert!(!current_user_can_read(
+            uid.wrapping_add(1),
+            u32::MAX,
+            0o100_440
+        ));
+    }
+}
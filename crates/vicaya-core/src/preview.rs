@@ -0,0 +1,154 @@
+//! The terminal-independent part of file previews.
+//!
+//! Reading a bounded head of a file or archive member, telling text from
+//! binary, and making lines safe to print. The TUI adds syntax highlighting,
+//! hex dumps and paging on top; `vicaya search --preview` prints a few
+//! picked lines as they are.
+
+use std::io::Read;
+use std::ops::Range;
+
+use crate::archive;
+
+/// Read at most `max_bytes` from the start of `path`, which may be an
+/// archive member's virtual path (`app.jar!/META-INF/MANIFEST.MF`). Returns
+/// the bytes and the full size of the file or member.
+pub fn read_head(path: &str, max_bytes: usize) -> crate::Result<(Vec<u8>, u64)> {
+    if let Some((archive, member)) = archive::split_virtual_path(path) {
+        return archive::read_member(archive, member, max_bytes);
+    }
+    let file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut buf = Vec::with_capacity(max_bytes.min(size as usize));
+    file.take(max_bytes as u64).read_to_end(&mut buf)?;
+    Ok((buf, size))
+}
+
+/// Whether a file head looks like binary data rather than text.
+pub fn is_binary(head: &[u8]) -> bool {
+    head.contains(&0)
+}
+
+/// Expand tabs, drop carriage returns, and replace other control characters
+/// so raw ANSI sequences in a file cannot change the terminal's state.
+pub fn sanitize_line(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '\t' => out.push_str("    "),
+            '\r' => {}
+            c if c.is_control() && c != '\n' => out.push('�'),
+            _ => out.push(ch),
+        }
+    }
+    out
+}
+
+/// One numbered, sanitized line of a text preview.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewLine {
+    /// 1-based line number in the file.
+    pub number: usize,
+    pub text: String,
+}
+
+/// Up to `count` lines of `text` that contain `term` (ignoring ASCII case),
+/// or its first `count` lines when none do. The flag says which it was.
+///
+/// `text` is a file head, so an unterminated last line may be cut short; it
+/// is still offered, since it is all there is of that line.
+pub fn pick_lines(text: &str, term: &str, count: usize) -> (Vec<PreviewLine>, bool) {
+    let numbered = || {
+        text.lines().enumerate().map(|(i, line)| PreviewLine {
+            number: i + 1,
+            text: sanitize_line(line),
+        })
+    };
+    let matching: Vec<PreviewLine> = numbered()
+        .filter(|line| !match_ranges(&line.text, term).is_empty())
+        .take(count)
+        .collect();
+    if !matching.is_empty() {
+        return (matching, true);
+    }
+    (numbered().take(count).collect(), false)
+}
+
+/// Byte ranges where `term` occurs in `line`, ignoring ASCII case, without
+/// overlaps. Matches always fall on character boundaries.
+pub fn match_ranges(line: &str, term: &str) -> Vec<Range<usize>> {
+    let (haystack, needle) = (line.as_bytes(), term.as_bytes());
+    let mut ranges = Vec::new();
+    if needle.is_empty() {
+        return ranges;
+    }
+    let mut start = 0;
+    while start + needle.len() <= haystack.len() {
+        if haystack[start..start + needle.len()].eq_ignore_ascii_case(needle) {
+            ranges.push(start..start + needle.len());
+            start += needle.len();
+        } else {
+            start += 1;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_lines_prefers_matches_and_falls_back_to_the_head() {
+        let text = "[package]\nname = \"vicaya\"\n\tversion = \"1\"\nNAME_ALIAS = 2\n";
+
+        let (lines, matched) = pick_lines(text, "name", 5);
+        assert!(matched);
+        assert_eq!(
+            lines,
+            vec![
+                PreviewLine {
+                    number: 2,
+                    text: "name = \"vicaya\"".to_string()
+                },
+                PreviewLine {
+                    number: 4,
+                    text: "NAME_ALIAS = 2".to_string()
+                },
+            ]
+        );
+
+        let (lines, matched) = pick_lines(text, "absent", 2);
+        assert!(!matched);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].text, "[package]");
+        assert_eq!(
+            pick_lines(text, "version", 1).0[0].text,
+            "    version = \"1\""
+        );
+    }
+
+    #[test]
+    fn match_ranges_ignore_ascii_case_and_keep_char_boundaries() {
+        assert_eq!(
+            match_ranges("Résumé RÉSUMÉ résumé", "résumé"),
+            [0..8, 18..26]
+        );
+        assert_eq!(match_ranges("aaaa", "aa"), [0..2, 2..4]);
+        assert!(match_ranges("abc", "").is_empty());
+        assert_eq!(sanitize_line("a\x1b[31mb\r"), "a�[31mb");
+    }
+
+    #[test]
+    fn read_head_caps_bytes_and_reports_the_full_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.txt");
+        std::fs::write(&path, "hello world").unwrap();
+
+        let (head, size) = read_head(&path.to_string_lossy(), 5).unwrap();
+        assert_eq!(head, b"hello");
+        assert_eq!(size, 11);
+        assert!(!is_binary(&head));
+        assert!(is_binary(b"\x7fELF\0"));
+    }
+}
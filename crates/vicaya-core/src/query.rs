@@ -0,0 +1,760 @@
+//! Query pre-parsing: a raw query into a search term and Niyama filters.
+//!
+//! The CLI, the TUI and the daemon all parse queries here, so `ext:rs`
+//! means the same thing everywhere. Tokens such as `ext:rs,md`,
+//! `size:>10mb` or `owner:alice` become [`Niyama`]s; everything else is the
+//! term. A token naming a known niyama with a value that does not parse
+//! (`size:>10xb`, `type:pipe`) is dropped from the term and reported in
+//! [`ParsedQuery::errors`] instead of being searched for as text.
+//!
+//! The ownership, permission, fingerprint, depth, hidden and directory size
+//! niyamas travel to the daemon as [`SearchFilters`](crate::ipc::SearchFilters);
+//! the rest are checked against each result with [`ParsedQuery::matches`].
+
+use crate::content_hash::HashPrefix;
+use crate::permissions::AttributeFilter;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// A query split into the text to search for and its Niyama filters.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ParsedQuery {
+    pub term: String,
+    pub niyamas: Vec<Niyama>,
+    /// Niyama tokens whose value did not parse, in query order.
+    pub errors: Vec<NiyamaError>,
+}
+
+/// A malformed niyama token and what was expected instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NiyamaError {
+    pub token: String,
+    pub message: &'static str,
+}
+
+impl fmt::Display for NiyamaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}`: {}", self.token, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NiyamaType {
+    File,
+    Dir,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CmpOp {
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+    Eq,
+}
+
+impl CmpOp {
+    pub fn matches_i64(self, left: i64, right: i64) -> bool {
+        match self {
+            CmpOp::Lt => left < right,
+            CmpOp::Lte => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Gte => left >= right,
+            CmpOp::Eq => left == right,
+        }
+    }
+
+    pub fn matches_u64(self, left: u64, right: u64) -> bool {
+        match self {
+            CmpOp::Lt => left < right,
+            CmpOp::Lte => left <= right,
+            CmpOp::Gt => left > right,
+            CmpOp::Gte => left >= right,
+            CmpOp::Eq => left == right,
+        }
+    }
+
+    pub fn invert(self) -> Self {
+        match self {
+            CmpOp::Lt => CmpOp::Gt,
+            CmpOp::Lte => CmpOp::Gte,
+            CmpOp::Gt => CmpOp::Lt,
+            CmpOp::Gte => CmpOp::Lte,
+            CmpOp::Eq => CmpOp::Eq,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CmpI64 {
+    pub op: CmpOp,
+    pub value: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CmpU64 {
+    pub op: CmpOp,
+    pub value: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Niyama {
+    Type {
+        kind: NiyamaType,
+        raw: String,
+    },
+    Ext {
+        exts: Vec<String>,
+        raw: String,
+    },
+    Path {
+        needle: String,
+        raw: String,
+    },
+    /// Inside a macOS bundle whose name contains `needle`.
+    InBundle {
+        needle: String,
+        raw: String,
+    },
+    Mtime {
+        cmp: CmpI64,
+        raw: String,
+    },
+    Size {
+        cmp: CmpU64,
+        raw: String,
+    },
+    /// Directories whose contents, at any depth, total `cmp` bytes;
+    /// evaluated by the daemon.
+    DirSize {
+        cmp: CmpU64,
+        raw: String,
+    },
+    /// Owned by `owner` (user name or uid); evaluated by the daemon.
+    Owner {
+        owner: String,
+        raw: String,
+    },
+    /// No write bit (`true`) or some write bit; evaluated by the daemon.
+    Readonly {
+        want: bool,
+        raw: String,
+    },
+    /// Files with (`true`) or without an execute bit; evaluated by the daemon.
+    Executable {
+        want: bool,
+        raw: String,
+    },
+    /// Content fingerprint starting with the hex digits in `prefix`;
+    /// evaluated by the daemon.
+    Hash {
+        prefix: String,
+        raw: String,
+    },
+    /// At most `max` levels below the search scope (or the index root
+    /// without one); evaluated by the daemon.
+    Depth {
+        max: usize,
+        raw: String,
+    },
+    /// Whether dotfiles and dot-directory contents are shown; evaluated by
+    /// the daemon.
+    Hidden {
+        show: bool,
+        raw: String,
+    },
+}
+
+impl Niyama {
+    pub fn raw(&self) -> &str {
+        match self {
+            Niyama::Type { raw, .. }
+            | Niyama::Ext { raw, .. }
+            | Niyama::Path { raw, .. }
+            | Niyama::InBundle { raw, .. }
+            | Niyama::Mtime { raw, .. }
+            | Niyama::Size { raw, .. }
+            | Niyama::DirSize { raw, .. }
+            | Niyama::Owner { raw, .. }
+            | Niyama::Readonly { raw, .. }
+            | Niyama::Executable { raw, .. }
+            | Niyama::Hash { raw, .. }
+            | Niyama::Depth { raw, .. }
+            | Niyama::Hidden { raw, .. } => raw,
+        }
+    }
+
+    /// Whether the daemon applies this niyama through `SearchFilters`.
+    pub fn is_daemon_side(&self) -> bool {
+        matches!(
+            self,
+            Niyama::Owner { .. }
+                | Niyama::Readonly { .. }
+                | Niyama::Executable { .. }
+                | Niyama::Hash { .. }
+                | Niyama::Depth { .. }
+                | Niyama::Hidden { .. }
+                | Niyama::DirSize { .. }
+        )
+    }
+
+    /// Whether an entry passes this niyama. Daemon-side niyamas always pass.
+    pub fn matches(&self, path: &str, is_dir: bool, size: u64, mtime: i64) -> bool {
+        match self {
+            Niyama::Type { kind, .. } => match kind {
+                NiyamaType::File => !is_dir,
+                NiyamaType::Dir => is_dir,
+            },
+            Niyama::Ext { exts, .. } => Path::new(path)
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase())
+                .is_some_and(|ext| exts.contains(&ext)),
+            Niyama::Path { needle, .. } => path.to_lowercase().contains(needle),
+            Niyama::InBundle { needle, .. } => Path::new(path).parent().is_some_and(|parent| {
+                parent.ancestors().any(|dir| {
+                    dir.file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| {
+                            crate::paths::is_bundle_name(name)
+                                && name.to_lowercase().contains(needle)
+                        })
+                })
+            }),
+            Niyama::Mtime { cmp, .. } => cmp.op.matches_i64(mtime, cmp.value),
+            Niyama::Size { cmp, .. } => cmp.op.matches_u64(size, cmp.value),
+            Niyama::Owner { .. }
+            | Niyama::Readonly { .. }
+            | Niyama::Executable { .. }
+            | Niyama::Hash { .. }
+            | Niyama::Depth { .. }
+            | Niyama::Hidden { .. }
+            | Niyama::DirSize { .. } => true,
+        }
+    }
+}
+
+impl ParsedQuery {
+    /// Whether an entry passes every niyama the daemon does not apply.
+    pub fn matches(&self, path: &str, is_dir: bool, size: u64, mtime: i64) -> bool {
+        self.niyamas
+            .iter()
+            .all(|niyama| niyama.matches(path, is_dir, size, mtime))
+    }
+
+    /// Whether any niyama has to be checked against results.
+    pub fn has_result_niyamas(&self) -> bool {
+        self.niyamas.iter().any(|niyama| !niyama.is_daemon_side())
+    }
+
+    /// The malformed niyamas as one message, or `None` when all parsed.
+    pub fn error_message(&self) -> Option<String> {
+        if self.errors.is_empty() {
+            return None;
+        }
+        let errors: Vec<String> = self.errors.iter().map(ToString::to_string).collect();
+        Some(format!("Invalid niyama {}", errors.join("; ")))
+    }
+}
+
+/// The ownership, permission and fingerprint Niyamas, sent to the daemon
+/// with a search.
+pub fn attribute_filter(niyamas: &[Niyama]) -> AttributeFilter {
+    let mut filter = AttributeFilter::default();
+    for niyama in niyamas {
+        match niyama {
+            Niyama::Owner { owner, .. } => filter.owner = Some(owner.clone()),
+            Niyama::Readonly { want, .. } => filter.readonly = Some(*want),
+            Niyama::Executable { want, .. } => filter.executable = Some(*want),
+            Niyama::Hash { prefix, .. } => filter.content_hash = Some(prefix.clone()),
+            _ => {}
+        }
+    }
+    filter
+}
+
+/// The `depth:` Niyama's limit, sent to the daemon with a search.
+pub fn max_depth(niyamas: &[Niyama]) -> Option<usize> {
+    niyamas.iter().find_map(|niyama| match niyama {
+        Niyama::Depth { max, .. } => Some(*max),
+        _ => None,
+    })
+}
+
+/// The `dirsize:` Niyama's bound, sent to the daemon with a search.
+pub fn dir_size(niyamas: &[Niyama]) -> Option<CmpU64> {
+    niyamas.iter().find_map(|niyama| match niyama {
+        Niyama::DirSize { cmp, .. } => Some(*cmp),
+        _ => None,
+    })
+}
+
+/// The `hidden:` Niyama's choice, sent to the daemon with a search.
+pub fn hidden(niyamas: &[Niyama]) -> Option<bool> {
+    niyamas.iter().find_map(|niyama| match niyama {
+        Niyama::Hidden { show, .. } => Some(*show),
+        _ => None,
+    })
+}
+
+const EXPECT_TYPE: &str = "expected file or dir";
+const EXPECT_EXT: &str = "expected extensions, as in ext:rs,md";
+const EXPECT_VALUE: &str = "expected a value after the colon";
+const EXPECT_MTIME: &str = "expected an age or date, as in mtime:<7d or mtime:>=2024-01-31";
+const EXPECT_SIZE: &str = "expected a size, as in size:>10mb";
+const EXPECT_DIR_SIZE: &str = "expected a size, as in dirsize:>1gb";
+const EXPECT_FLAG: &str = "expected yes or no";
+const EXPECT_DEPTH: &str = "expected a limit, as in depth:<=2";
+const EXPECT_HASH: &str = "expected 1 to 16 hex digits, as in hash:3fa9";
+
+pub fn parse_query(raw: &str) -> ParsedQuery {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+
+    let mut term_tokens: Vec<&str> = Vec::new();
+    let mut errors: Vec<NiyamaError> = Vec::new();
+
+    let mut type_filter: Option<NiyamaType> = None;
+    let mut exts: Vec<String> = Vec::new();
+    let mut path_filters: Vec<Niyama> = Vec::new();
+    let mut mtime: Option<CmpI64> = None;
+    let mut mtime_raw: Option<String> = None;
+    let mut size: Option<CmpU64> = None;
+    let mut size_raw: Option<String> = None;
+    let mut attributes: Vec<Niyama> = Vec::new();
+    let mut depth: Option<Niyama> = None;
+    let mut hidden: Option<Niyama> = None;
+    let mut dir_size: Option<Niyama> = None;
+
+    for token in raw.split_whitespace() {
+        let Some((key, value)) = token.split_once(':') else {
+            term_tokens.push(token);
+            continue;
+        };
+        let raw = token.to_string();
+
+        let parsed: Result<(), &'static str> = match key {
+            "type" => parse_type(value).ok_or(EXPECT_TYPE).map(|kind| {
+                type_filter = Some(kind);
+            }),
+            "ext" => {
+                let mut parsed = parse_exts(value);
+                if parsed.is_empty() {
+                    Err(EXPECT_EXT)
+                } else {
+                    exts.append(&mut parsed);
+                    Ok(())
+                }
+            }
+            "path" | "in-bundle" if value.is_empty() => Err(EXPECT_VALUE),
+            "path" => {
+                path_filters.push(Niyama::Path {
+                    needle: value.to_lowercase(),
+                    raw,
+                });
+                Ok(())
+            }
+            "in-bundle" => {
+                path_filters.push(Niyama::InBundle {
+                    needle: value.to_lowercase(),
+                    raw,
+                });
+                Ok(())
+            }
+            "mtime" => parse_mtime_expr(value, now).ok_or(EXPECT_MTIME).map(|cmp| {
+                mtime = Some(cmp);
+                mtime_raw = Some(raw);
+            }),
+            "size" => parse_size_expr(value).ok_or(EXPECT_SIZE).map(|cmp| {
+                size = Some(cmp);
+                size_raw = Some(raw);
+            }),
+            "dirsize" => parse_size_expr(value).ok_or(EXPECT_DIR_SIZE).map(|cmp| {
+                dir_size = Some(Niyama::DirSize { cmp, raw });
+            }),
+            "owner" if value.is_empty() => Err(EXPECT_VALUE),
+            "owner" => {
+                attributes.retain(|n| !matches!(n, Niyama::Owner { .. }));
+                attributes.push(Niyama::Owner {
+                    owner: value.to_string(),
+                    raw,
+                });
+                Ok(())
+            }
+            "readonly" => parse_flag(value).ok_or(EXPECT_FLAG).map(|want| {
+                attributes.retain(|n| !matches!(n, Niyama::Readonly { .. }));
+                attributes.push(Niyama::Readonly { want, raw });
+            }),
+            "executable" => parse_flag(value).ok_or(EXPECT_FLAG).map(|want| {
+                attributes.retain(|n| !matches!(n, Niyama::Executable { .. }));
+                attributes.push(Niyama::Executable { want, raw });
+            }),
+            "hash" => HashPrefix::parse(value).ok_or(EXPECT_HASH).map(|_| {
+                attributes.retain(|n| !matches!(n, Niyama::Hash { .. }));
+                attributes.push(Niyama::Hash {
+                    prefix: value.to_lowercase(),
+                    raw,
+                });
+            }),
+            "depth" => parse_depth_expr(value).ok_or(EXPECT_DEPTH).map(|max| {
+                depth = Some(Niyama::Depth { max, raw });
+            }),
+            "hidden" => parse_flag(value).ok_or(EXPECT_FLAG).map(|show| {
+                hidden = Some(Niyama::Hidden { show, raw });
+            }),
+            _ => {
+                term_tokens.push(token);
+                Ok(())
+            }
+        };
+
+        if let Err(message) = parsed {
+            errors.push(NiyamaError {
+                token: token.to_string(),
+                message,
+            });
+        }
+    }
+
+    exts.sort();
+    exts.dedup();
+
+    let mut niyamas: Vec<Niyama> = Vec::new();
+    if let Some(kind) = type_filter {
+        niyamas.push(Niyama::Type {
+            kind,
+            raw: format!(
+                "type:{}",
+                match kind {
+                    NiyamaType::File => "file",
+                    NiyamaType::Dir => "dir",
+                }
+            ),
+        });
+    }
+
+    if !exts.is_empty() {
+        niyamas.push(Niyama::Ext {
+            raw: format!("ext:{}", exts.join(",")),
+            exts,
+        });
+    }
+
+    niyamas.extend(path_filters);
+
+    if let (Some(cmp), Some(raw)) = (mtime, mtime_raw) {
+        niyamas.push(Niyama::Mtime { cmp, raw });
+    }
+
+    if let (Some(cmp), Some(raw)) = (size, size_raw) {
+        niyamas.push(Niyama::Size { cmp, raw });
+    }
+
+    niyamas.extend(attributes);
+    niyamas.extend(depth);
+    niyamas.extend(hidden);
+    niyamas.extend(dir_size);
+
+    ParsedQuery {
+        term: term_tokens.join(" "),
+        niyamas,
+        errors,
+    }
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "yes" | "y" | "true" | "1" => Some(true),
+        "no" | "n" | "false" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// `<=N` or `<N`, as the deepest level allowed.
+fn parse_depth_expr(value: &str) -> Option<usize> {
+    let (op, rest) = parse_op_and_value(value)?;
+    let n: usize = rest.trim().parse().ok()?;
+    match op {
+        CmpOp::Lte => Some(n),
+        CmpOp::Lt => n.checked_sub(1),
+        _ => None,
+    }
+}
+
+fn parse_type(value: &str) -> Option<NiyamaType> {
+    match value.trim().to_lowercase().as_str() {
+        "file" | "f" => Some(NiyamaType::File),
+        "dir" | "d" | "directory" => Some(NiyamaType::Dir),
+        _ => None,
+    }
+}
+
+fn parse_exts(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .filter_map(|ext| {
+            let ext = ext.trim().trim_start_matches('.').to_lowercase();
+            if ext.is_empty() {
+                None
+            } else {
+                Some(ext)
+            }
+        })
+        .collect()
+}
+
+fn parse_op_and_value(input: &str) -> Option<(CmpOp, &str)> {
+    let s = input.trim();
+    if let Some(rest) = s.strip_prefix(">=") {
+        return Some((CmpOp::Gte, rest));
+    }
+    if let Some(rest) = s.strip_prefix("<=") {
+        return Some((CmpOp::Lte, rest));
+    }
+    if let Some(rest) = s.strip_prefix('>') {
+        return Some((CmpOp::Gt, rest));
+    }
+    if let Some(rest) = s.strip_prefix('<') {
+        return Some((CmpOp::Lt, rest));
+    }
+    if let Some(rest) = s.strip_prefix('=') {
+        return Some((CmpOp::Eq, rest));
+    }
+    None
+}
+
+/// A size bound such as `>10mb` or `<=4k`, as `size:` and `dirsize:` take.
+pub fn parse_size_expr(input: &str) -> Option<CmpU64> {
+    let (op, value) = parse_op_and_value(input)?;
+    let value = value.trim().to_lowercase();
+    let (num_str, unit) = value
+        .trim()
+        .chars()
+        .position(|c| !c.is_ascii_digit())
+        .map(|idx| (&value[..idx], &value[idx..]))
+        .unwrap_or((value.as_str(), ""));
+
+    let n: u64 = num_str.parse().ok()?;
+    let multiplier: u64 = match unit {
+        "" | "b" => 1,
+        "k" | "kb" => 1024,
+        "m" | "mb" => 1024 * 1024,
+        "g" | "gb" => 1024 * 1024 * 1024,
+        "t" | "tb" => 1024 * 1024 * 1024 * 1024,
+        _ => return None,
+    };
+
+    Some(CmpU64 {
+        op,
+        value: n.saturating_mul(multiplier),
+    })
+}
+
+fn parse_mtime_expr(input: &str, now: i64) -> Option<CmpI64> {
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    let (op, value) = parse_op_and_value(input)?;
+    let value = value.trim();
+
+    if let Some((n, unit)) = parse_duration(value) {
+        let seconds = match unit {
+            's' => n,
+            'm' => n * 60,
+            'h' => n * 60 * 60,
+            'd' => n * 60 * 60 * 24,
+            'w' => n * 60 * 60 * 24 * 7,
+            _ => return None,
+        };
+
+        let threshold = now.saturating_sub(seconds);
+        return Some(CmpI64 {
+            op: op.invert(),
+            value: threshold,
+        });
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        let naive = date.and_hms_opt(0, 0, 0)?;
+        let timestamp = match Local.from_local_datetime(&naive) {
+            chrono::LocalResult::Single(dt) => dt.timestamp(),
+            chrono::LocalResult::Ambiguous(dt, _) => dt.timestamp(),
+            chrono::LocalResult::None => {
+                chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(naive, chrono::Utc)
+                    .timestamp()
+            }
+        };
+
+        return Some(CmpI64 {
+            op,
+            value: timestamp,
+        });
+    }
+
+    None
+}
+
+fn parse_duration(input: &str) -> Option<(i64, char)> {
+    let s = input.trim().to_lowercase();
+    let mut chars = s.chars();
+    let unit = chars.next_back()?;
+    let number = chars.as_str().parse::<i64>().ok()?;
+    Some((number, unit))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_query_extracts_term_and_filters() {
+        let parsed = parse_query("foo ext:rs,md type:file path:src/ std::fs");
+        assert_eq!(parsed.term, "foo std::fs");
+        assert_eq!(parsed.niyamas.len(), 3);
+        assert!(parsed.errors.is_empty());
+        assert!(matches!(
+            parsed.niyamas[0],
+            Niyama::Type {
+                kind: NiyamaType::File,
+                ..
+            }
+        ));
+        assert!(matches!(parsed.niyamas[1], Niyama::Ext { .. }));
+        assert!(matches!(parsed.niyamas[2], Niyama::Path { .. }));
+
+        let parsed = parse_query("Info in-bundle:Xcode");
+        assert_eq!(parsed.term, "Info");
+        assert_eq!(
+            parsed.niyamas,
+            vec![Niyama::InBundle {
+                needle: "xcode".to_string(),
+                raw: "in-bundle:Xcode".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn malformed_niyamas_are_reported_and_left_out_of_the_term() {
+        let parsed = parse_query("report size:>10xb type:pipe in-bundle: ext:, depth:>1");
+        assert_eq!(parsed.term, "report");
+        assert!(parsed.niyamas.is_empty());
+        assert_eq!(
+            parsed
+                .errors
+                .iter()
+                .map(|e| e.token.as_str())
+                .collect::<Vec<_>>(),
+            vec!["size:>10xb", "type:pipe", "in-bundle:", "ext:,", "depth:>1"]
+        );
+        assert_eq!(parsed.errors[0].message, EXPECT_SIZE);
+        let message = parsed.error_message().unwrap();
+        assert!(
+            message.starts_with("Invalid niyama `size:>10xb`: expected a size"),
+            "{message}"
+        );
+        assert!(parse_query("plain words").error_message().is_none());
+    }
+
+    #[test]
+    fn ownership_niyamas_become_a_daemon_attribute_filter() {
+        let parsed = parse_query("deploy owner:alice executable:yes readonly:maybe owner:bob");
+        assert_eq!(parsed.term, "deploy");
+        assert_eq!(parsed.errors.len(), 1);
+        assert_eq!(
+            parsed.niyamas.iter().map(Niyama::raw).collect::<Vec<_>>(),
+            vec!["executable:yes", "owner:bob"]
+        );
+        assert_eq!(
+            attribute_filter(&parsed.niyamas),
+            AttributeFilter {
+                owner: Some("bob".to_string()),
+                readonly: None,
+                executable: Some(true),
+                content_hash: None,
+            }
+        );
+        assert!(!parsed.has_result_niyamas());
+        assert!(attribute_filter(&parse_query("foo ext:rs").niyamas).is_empty());
+    }
+
+    #[test]
+    fn hash_niyama_becomes_a_daemon_fingerprint_prefix() {
+        let parsed = parse_query("hash:3FA9 hash:xyz");
+        assert_eq!(parsed.term, "");
+        assert_eq!(parsed.errors[0].token, "hash:xyz");
+        assert_eq!(parsed.errors[0].message, EXPECT_HASH);
+        assert_eq!(
+            attribute_filter(&parsed.niyamas).content_hash.as_deref(),
+            Some("3fa9")
+        );
+        assert!(!parsed.has_result_niyamas());
+    }
+
+    #[test]
+    fn depth_niyama_becomes_a_daemon_max_depth() {
+        let parsed = parse_query("readme depth:<=2 depth:<0");
+        assert_eq!(parsed.term, "readme");
+        assert_eq!(max_depth(&parsed.niyamas), Some(2));
+        assert_eq!(parsed.errors[0].token, "depth:<0");
+        assert_eq!(max_depth(&parse_query("readme depth:<3").niyamas), Some(2));
+        assert_eq!(max_depth(&parse_query("readme").niyamas), None);
+    }
+
+    #[test]
+    fn hidden_niyama_parses_a_flag() {
+        let parsed = parse_query("bashrc hidden:yes");
+        assert_eq!(parsed.term, "bashrc");
+        assert_eq!(hidden(&parsed.niyamas), Some(true));
+        assert_eq!(hidden(&parse_query("rc hidden:false").niyamas), Some(false));
+        assert_eq!(hidden(&parse_query("rc hidden:maybe").niyamas), None);
+    }
+
+    #[test]
+    fn dirsize_niyama_becomes_a_daemon_bound() {
+        let parsed = parse_query("node_modules dirsize:>1gb dirsize:>1xb");
+        assert_eq!(parsed.term, "node_modules");
+        assert_eq!(
+            dir_size(&parsed.niyamas),
+            Some(CmpU64 {
+                op: CmpOp::Gt,
+                value: 1024 * 1024 * 1024
+            })
+        );
+        assert_eq!(parsed.errors[0].token, "dirsize:>1xb");
+        assert!(!parsed.has_result_niyamas());
+        assert_eq!(dir_size(&parse_query("target").niyamas), None);
+    }
+
+    #[test]
+    fn result_niyamas_match_entry_facts() {
+        let parsed = parse_query("main ext:rs type:file path:src size:<1kb");
+        assert!(parsed.has_result_niyamas());
+        assert!(parsed.matches("/repo/src/main.rs", false, 512, 0));
+        assert!(!parsed.matches("/repo/src/main.rs", true, 512, 0));
+        assert!(!parsed.matches("/repo/src/main.py", false, 512, 0));
+        assert!(!parsed.matches("/repo/lib/main.rs", false, 512, 0));
+        assert!(!parsed.matches("/repo/src/main.rs", false, 4096, 0));
+
+        let bundle = parse_query("Info in-bundle:xcode");
+        assert!(bundle.matches("/Applications/Xcode.app/Contents/Info.plist", false, 0, 0));
+        assert!(!bundle.matches("/Applications/Xcode.app", true, 0, 0));
+    }
+
+    #[test]
+    fn parse_size_expr_parses_units() {
+        let cmp = parse_size_expr(">10mb").unwrap();
+        assert_eq!(cmp.op, CmpOp::Gt);
+        assert_eq!(cmp.value, 10 * 1024 * 1024);
+    }
+
+    #[test]
+    fn parse_mtime_expr_inverts_relative_age() {
+        let cmp = parse_mtime_expr("<7d", 1000).unwrap();
+        assert_eq!(cmp.op, CmpOp::Gt);
+        assert_eq!(cmp.value, 1000 - 7 * 60 * 60 * 24);
+    }
+}
@@ -0,0 +1,245 @@
+//! Cron-style reconcile schedules.
+//!
+//! `[performance] reconcile_schedule` lists times in the classic five-field
+//! form, `minute hour day-of-month month day-of-week`, in local time. Each
+//! field takes `*`, a number, a range (`1-5`), a list (`0,30`) and a step
+//! (`*/15`, `8-18/2`). Days of the week run 0-7, both 0 and 7 meaning Sunday.
+//! As in cron, when both day fields are restricted a day matching either one
+//! qualifies. `@hourly`, `@daily`, `@midnight` and `@weekly` are shorthands.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+
+/// Days searched for the next run before giving up; enough for a schedule
+/// that only matches on 29 February.
+const MAX_SEARCH_DAYS: i64 = 366 * 8;
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day-of-month field was anything but `*`.
+    dom_restricted: bool,
+    /// Whether the day-of-week field was anything but `*`.
+    dow_restricted: bool,
+    source: String,
+}
+
+impl CronSchedule {
+    /// Every day at `hour:00`, the schedule `reconcile_hour` stands for.
+    pub fn daily_at(hour: u8) -> Self {
+        format!("0 {} * * *", hour.min(23))
+            .parse()
+            .expect("a daily schedule is always valid")
+    }
+
+    /// The first matching minute strictly after `after`.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after
+            .with_second(0)
+            .and_then(|t| t.with_nanosecond(0))
+            .unwrap_or(after)
+            + Duration::minutes(1);
+
+        let mut date = start.date();
+        for day in 0..MAX_SEARCH_DAYS {
+            if self.matches_date(date) {
+                let (from_hour, from_minute) = if day == 0 {
+                    (start.hour(), start.minute())
+                } else {
+                    (0, 0)
+                };
+                for hour in from_hour..24 {
+                    if !has(self.hours, hour) {
+                        continue;
+                    }
+                    let first_minute = if hour == from_hour { from_minute } else { 0 };
+                    if let Some(minute) = (first_minute..60).find(|&m| has(self.minutes, m)) {
+                        return date.and_hms_opt(hour, minute, 0);
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+
+    fn matches_date(&self, date: chrono::NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+        let dom = has(self.days_of_month, date.day());
+        let dow = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.dom_restricted, self.dow_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day-of-month month day-of-week), found {}",
+                fields.len()
+            ));
+        };
+
+        let mut days_of_week = parse_field(dow, "day-of-week", 0, 7)?;
+        if has(days_of_week, 7) {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, "minute", 0, 59)?,
+            hours: parse_field(hour, "hour", 0, 23)?,
+            days_of_month: parse_field(dom, "day-of-month", 1, 31)?,
+            months: parse_field(month, "month", 1, 12)?,
+            days_of_week,
+            dom_restricted: dom != "*",
+            dow_restricted: dow != "*",
+            source: expression.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+/// The earliest run of any of `schedules` strictly after `after`.
+pub fn next_run(schedules: &[CronSchedule], after: NaiveDateTime) -> Option<NaiveDateTime> {
+    schedules.iter().filter_map(|s| s.next_after(after)).min()
+}
+
+fn has(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+/// Parse one field into a bit mask of the values it allows.
+fn parse_field(field: &str, name: &str, min: u32, max: u32) -> Result<u64, String> {
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 =
+                    step.parse().ok().filter(|&step| step > 0).ok_or_else(|| {
+                        format!("invalid step '{step}' in {name} field '{field}'")
+                    })?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let number = |value: &str| -> Result<u32, String> {
+            value
+                .parse()
+                .ok()
+                .filter(|n| (min..=max).contains(n))
+                .ok_or_else(|| format!("{name} '{value}' is not between {min} and {max}"))
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (number(start)?, number(end)?),
+                // `5/10` means from 5 to the end in steps of 10.
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if start > end {
+            return Err(format!("{name} range '{range}' runs backwards"));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn next(expression: &str, after: &str) -> NaiveDateTime {
+        expression
+            .parse::<CronSchedule>()
+            .unwrap()
+            .next_after(at(after))
+            .unwrap()
+    }
+
+    #[test]
+    fn next_after_walks_fields_and_days() {
+        // 2026-03-06 is a Friday.
+        assert_eq!(
+            next("0 3 * * *", "2026-03-06 02:59"),
+            at("2026-03-06 03:00")
+        );
+        assert_eq!(
+            next("0 3 * * *", "2026-03-06 03:00"),
+            at("2026-03-07 03:00")
+        );
+        assert_eq!(
+            next("*/15 9-17 * * 1-5", "2026-03-06 17:50"),
+            at("2026-03-09 09:00")
+        );
+        assert_eq!(
+            next("30 2 * * 7", "2026-03-06 00:00"),
+            at("2026-03-08 02:30")
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2026-03-01 00:00"),
+            at("2028-02-29 00:00")
+        );
+        // Either day field may match when both are restricted.
+        assert_eq!(
+            next("0 4 1 * 1", "2026-03-06 12:00"),
+            at("2026-03-09 04:00")
+        );
+        assert_eq!(next("@hourly", "2026-03-06 12:00"), at("2026-03-06 13:00"));
+
+        let schedules = ["0 3 * * *".parse().unwrap(), "0 13 * * *".parse().unwrap()];
+        assert_eq!(
+            next_run(&schedules, at("2026-03-06 04:00")),
+            Some(at("2026-03-06 13:00"))
+        );
+        assert_eq!(CronSchedule::daily_at(3), "0 3 * * *".parse().unwrap());
+    }
+
+    #[test]
+    fn malformed_expressions_are_rejected_with_a_reason() {
+        let err = |expression: &str| expression.parse::<CronSchedule>().unwrap_err();
+        assert!(err("0 3 * *").contains("expected 5 fields"));
+        assert!(err("60 3 * * *").contains("minute '60'"));
+        assert!(err("0 3 * * 8").contains("day-of-week '8'"));
+        assert!(err("0 5-2 * * *").contains("backwards"));
+        assert!(err("*/0 * * * *").contains("invalid step"));
+        assert!(err("0 3 L * *").contains("day-of-month 'L'"));
+    }
+}
@@ -1,5 +1,6 @@
 //! Local usage memory for Smriti (recent/frecency) ranking.
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Write;
@@ -8,7 +9,7 @@ use std::path::{Path, PathBuf};
 const CURRENT_VERSION: u16 = 1;
 
 /// A user action that can teach vicaya which paths are useful.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum SmritiAction {
     /// Opened a file in the configured editor.
@@ -37,7 +38,7 @@ impl SmritiAction {
 }
 
 /// One persisted Smriti path entry.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct SmritiEntry {
     /// Absolute path recorded for this entry.
     pub path: String,
@@ -102,6 +103,22 @@ impl SmritiEntry {
         self.last_query = query;
         self.last_action = action;
     }
+
+    /// Fold another entry's usage into this one, keeping the newer last use.
+    fn absorb(&mut self, other: SmritiEntry) {
+        self.total_count = self.total_count.saturating_add(other.total_count);
+        self.open_count = self.open_count.saturating_add(other.open_count);
+        self.copy_count = self.copy_count.saturating_add(other.copy_count);
+        self.reveal_count = self.reveal_count.saturating_add(other.reveal_count);
+        self.print_count = self.print_count.saturating_add(other.print_count);
+        self.enter_count = self.enter_count.saturating_add(other.enter_count);
+        self.first_used = self.first_used.min(other.first_used);
+        if other.last_used > self.last_used {
+            self.last_used = other.last_used;
+            self.last_query = other.last_query;
+            self.last_action = other.last_action;
+        }
+    }
 }
 
 /// Versioned on-disk Smriti document.
@@ -187,6 +204,49 @@ impl SmritiStore {
         self.entries.remove(path).is_some()
     }
 
+    /// Follow a rename: move the entry for `from`, and for a directory every
+    /// entry below it, to the same place under `to`.
+    ///
+    /// An entry already recorded at a destination absorbs the moved counts.
+    /// Returns the number of entries moved.
+    pub fn rename(&mut self, from: &str, to: &str) -> usize {
+        if from == to {
+            return 0;
+        }
+        let from = from.trim_end_matches('/');
+        let to = to.trim_end_matches('/');
+        let moved: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|path| {
+                path.strip_prefix(from)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .cloned()
+            .collect();
+
+        for old_path in &moved {
+            let Some(mut entry) = self.entries.remove(old_path) else {
+                continue;
+            };
+            let new_path = format!("{to}{}", &old_path[from.len()..]);
+            entry.name = Path::new(&new_path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| new_path.clone());
+            entry.path = new_path.clone();
+            match self.entries.entry(new_path) {
+                std::collections::hash_map::Entry::Occupied(mut existing) => {
+                    existing.get_mut().absorb(entry);
+                }
+                std::collections::hash_map::Entry::Vacant(slot) => {
+                    slot.insert(entry);
+                }
+            }
+        }
+        moved.len()
+    }
+
     /// Remove all usage memory entries.
     pub fn clear(&mut self) {
         self.entries.clear();
@@ -332,6 +392,37 @@ mod tests {
         assert_eq!(entry.last_action, SmritiAction::Copy);
     }
 
+    #[test]
+    fn rename_moves_entries_below_a_directory_and_merges_collisions() {
+        let mut store = SmritiStore::default();
+        for (path, now) in [
+            ("/tmp/repo/src/main.rs", 100),
+            ("/tmp/repo/src/lib.rs", 100),
+            ("/tmp/repo/srcs/other.rs", 100),
+            ("/tmp/repo/code/lib.rs", 300),
+        ] {
+            store.record(path.to_string(), "q".to_string(), SmritiAction::Open, now);
+        }
+
+        assert_eq!(store.rename("/tmp/repo/src", "/tmp/repo/code"), 2);
+
+        assert!(!store.entries.contains_key("/tmp/repo/src/main.rs"));
+        assert!(store.entries.contains_key("/tmp/repo/srcs/other.rs"));
+        let main = &store.entries["/tmp/repo/code/main.rs"];
+        assert_eq!(main.path, "/tmp/repo/code/main.rs");
+        assert_eq!(main.total_count, 1);
+        let lib = &store.entries["/tmp/repo/code/lib.rs"];
+        assert_eq!(lib.total_count, 2);
+        assert_eq!(lib.first_used, 100);
+        assert_eq!(lib.last_used, 300);
+
+        assert_eq!(
+            store.rename("/tmp/repo/code/main.rs", "/tmp/repo/app.rs"),
+            1
+        );
+        assert_eq!(store.entries["/tmp/repo/app.rs"].name, "app.rs");
+    }
+
     #[test]
     fn boost_is_bounded_and_decays_with_age() {
         let mut store = SmritiStore::default();
@@ -0,0 +1,268 @@
+//! Opt-in local ranking telemetry.
+//!
+//! When `[telemetry] enabled = true`, the TUI appends one [`RankingEvent`]
+//! per accepted result to a newline-delimited JSON log under the vicaya
+//! directory. Nothing is uploaded; `vicaya metrics ranking-report` aggregates
+//! the log into a [`RankingReport`] to show how often the wanted file is
+//! ranked first, and `vicaya metrics ranking-purge` deletes it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::io::{BufRead, Write};
+use std::path::Path;
+
+use crate::smriti::SmritiAction;
+
+/// One accepted search result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RankingEvent {
+    /// Epoch seconds when the result was accepted.
+    pub at: i64,
+    /// Query text the results were ranked for.
+    pub query: String,
+    /// 1-based position of the accepted result.
+    pub rank: usize,
+    /// Number of results shown for the query.
+    pub result_count: usize,
+    /// Milliseconds between the results appearing and the selection.
+    pub time_to_select_ms: u64,
+    /// Action that accepted the result.
+    pub action: SmritiAction,
+}
+
+/// Append `event` to the log at `path`, creating parent directories as needed.
+pub fn append_event(path: &Path, event: &RankingEvent) -> crate::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut line =
+        serde_json::to_string(event).map_err(|e| crate::Error::Serialization(e.to_string()))?;
+    line.push('\n');
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.write_all(line.as_bytes())?;
+    Ok(())
+}
+
+/// Read every event from the log at `path`; a missing log has none.
+///
+/// Lines that fail to parse (e.g. a torn final write) are skipped.
+pub fn load_events(path: &Path) -> crate::Result<Vec<RankingEvent>> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut events = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if let Ok(event) = serde_json::from_str(&line) {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+/// Delete the log at `path`. Returns `true` when a log existed.
+pub fn purge(path: &Path) -> crate::Result<bool> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Accepted results whose rank falls in `min..=max` (`max` of `None` is open).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankBucket {
+    /// Human-facing range label, e.g. `"4-5"` or `"11+"`.
+    pub label: String,
+    /// Events in this range.
+    pub count: usize,
+    /// Share of all events in this range (0.0-1.0).
+    pub share: f64,
+}
+
+/// Aggregate view of a ranking telemetry log.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RankingReport {
+    /// Accepted results in the log.
+    pub events: usize,
+    /// Distinct queries those results were accepted for.
+    pub distinct_queries: usize,
+    /// Mean reciprocal rank of accepted results (1.0 = always first).
+    pub mrr: f64,
+    /// Accepted results per rank range.
+    pub rank_distribution: Vec<RankBucket>,
+    /// Median time to select, if any events were recorded.
+    pub time_to_select_p50_ms: Option<u64>,
+    /// 90th percentile time to select, if any events were recorded.
+    pub time_to_select_p90_ms: Option<u64>,
+    /// Epoch seconds of the oldest event.
+    pub first_event_at: Option<i64>,
+    /// Epoch seconds of the newest event.
+    pub last_event_at: Option<i64>,
+}
+
+const RANK_BUCKETS: &[(usize, Option<usize>)] = &[
+    (1, Some(1)),
+    (2, Some(2)),
+    (3, Some(3)),
+    (4, Some(5)),
+    (6, Some(10)),
+    (11, None),
+];
+
+impl RankingReport {
+    /// Aggregate `events` into MRR, rank distribution, and selection latency.
+    pub fn from_events(events: &[RankingEvent]) -> Self {
+        let total = events.len();
+        let mrr = if total == 0 {
+            0.0
+        } else {
+            events
+                .iter()
+                .map(|event| 1.0 / event.rank.max(1) as f64)
+                .sum::<f64>()
+                / total as f64
+        };
+
+        let rank_distribution = RANK_BUCKETS
+            .iter()
+            .map(|&(min, max)| {
+                let count = events
+                    .iter()
+                    .filter(|event| event.rank >= min && max.is_none_or(|max| event.rank <= max))
+                    .count();
+                let label = match max {
+                    Some(max) if max == min => min.to_string(),
+                    Some(max) => format!("{min}-{max}"),
+                    None => format!("{min}+"),
+                };
+                RankBucket {
+                    label,
+                    count,
+                    share: if total == 0 {
+                        0.0
+                    } else {
+                        count as f64 / total as f64
+                    },
+                }
+            })
+            .collect();
+
+        let mut latencies: Vec<u64> = events.iter().map(|e| e.time_to_select_ms).collect();
+        latencies.sort_unstable();
+        let distinct_queries = events
+            .iter()
+            .map(|event| event.query.as_str())
+            .collect::<HashSet<_>>()
+            .len();
+
+        Self {
+            events: total,
+            distinct_queries,
+            mrr,
+            rank_distribution,
+            time_to_select_p50_ms: percentile(&latencies, 50),
+            time_to_select_p90_ms: percentile(&latencies, 90),
+            first_event_at: events.iter().map(|event| event.at).min(),
+            last_event_at: events.iter().map(|event| event.at).max(),
+        }
+    }
+}
+
+fn percentile(sorted: &[u64], pct: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let index = (pct * (sorted.len() - 1) + 50) / 100;
+    sorted.get(index).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(query: &str, rank: usize, time_to_select_ms: u64) -> RankingEvent {
+        RankingEvent {
+            at: 1_700_000_000 + rank as i64,
+            query: query.to_string(),
+            rank,
+            result_count: 20,
+            time_to_select_ms,
+            action: SmritiAction::Open,
+        }
+    }
+
+    #[test]
+    fn report_aggregates_mrr_and_rank_distribution() {
+        let events = vec![
+            event("main", 1, 400),
+            event("main", 2, 900),
+            event("readme", 4, 1_500),
+            event("cfg", 12, 3_000),
+        ];
+
+        let report = RankingReport::from_events(&events);
+
+        assert_eq!(report.events, 4);
+        assert_eq!(report.distinct_queries, 3);
+        let expected_mrr = (1.0 + 0.5 + 0.25 + 1.0 / 12.0) / 4.0;
+        assert!((report.mrr - expected_mrr).abs() < 1e-9);
+        let counts: Vec<(&str, usize)> = report
+            .rank_distribution
+            .iter()
+            .map(|bucket| (bucket.label.as_str(), bucket.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("1", 1),
+                ("2", 1),
+                ("3", 0),
+                ("4-5", 1),
+                ("6-10", 0),
+                ("11+", 1)
+            ]
+        );
+        assert_eq!(report.time_to_select_p50_ms, Some(1_500));
+        assert_eq!(report.time_to_select_p90_ms, Some(3_000));
+        assert_eq!(report.first_event_at, Some(1_700_000_001));
+        assert_eq!(report.last_event_at, Some(1_700_000_012));
+    }
+
+    #[test]
+    fn empty_log_reports_no_selections() {
+        let report = RankingReport::from_events(&[]);
+        assert_eq!(report.events, 0);
+        assert_eq!(report.mrr, 0.0);
+        assert!(report.rank_distribution.iter().all(|b| b.count == 0));
+        assert_eq!(report.time_to_select_p50_ms, None);
+    }
+
+    #[test]
+    fn log_round_trips_skips_torn_lines_and_purges() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nested").join("ranking-telemetry.jsonl");
+        assert!(load_events(&path).unwrap().is_empty());
+        assert!(!purge(&path).unwrap());
+
+        append_event(&path, &event("main", 1, 250)).unwrap();
+        append_event(&path, &event("lib", 3, 800)).unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"{\"at\":17").unwrap();
+
+        assert_eq!(
+            load_events(&path).unwrap(),
+            vec![event("main", 1, 250), event("lib", 3, 800)]
+        );
+        assert!(purge(&path).unwrap());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,76 @@
+//! Trash detection.
+//!
+//! Files moved to the Trash (`~/.Trash`, `.Trashes` on other volumes, the
+//! freedesktop `~/.local/share/Trash` and `.Trash-<uid>`) are deleted as far
+//! as search is concerned. The scanner prunes Trash directories and the daemon
+//! drops entries moved into them unless `[trash] index` is set.
+
+use std::path::Path;
+
+/// Directory names that only ever hold trashed files.
+const TRASH_DIR_NAMES: &[&str] = &[".trash", ".trashes"];
+
+/// Whether a directory named `name` is a Trash directory.
+pub fn is_trash_dir_name(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    TRASH_DIR_NAMES.contains(&lower.as_str())
+        || lower
+            .strip_prefix(".trash-")
+            .is_some_and(|uid| !uid.is_empty() && uid.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Whether the directory `path` is a Trash directory: one by name, or the
+/// freedesktop `.local/share/Trash`.
+pub fn is_trash_location(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return false;
+    };
+    is_trash_dir_name(name) || (name == "Trash" && path.ends_with(".local/share/Trash"))
+}
+
+/// The outermost Trash directory strictly below `root` that contains (or
+/// is) `path`. A root inside the Trash is indexed as asked.
+pub fn trash_below<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    path.ancestors()
+        .take_while(|dir| dir.starts_with(root) && *dir != root)
+        .filter(|dir| is_trash_location(dir))
+        .last()
+}
+
+/// Whether `path` is inside (or is) a Trash directory anywhere.
+pub fn is_in_trash(path: &Path) -> bool {
+    path.ancestors().any(is_trash_location)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trash_directories_are_recognised_by_name_and_location() {
+        assert!(is_trash_dir_name(".Trash"));
+        assert!(is_trash_dir_name(".Trashes"));
+        assert!(is_trash_dir_name(".Trash-1000"));
+        assert!(!is_trash_dir_name(".Trash-"));
+        assert!(!is_trash_dir_name("Trash"));
+        assert!(is_trash_location(Path::new("/home/me/.local/share/Trash")));
+        assert!(!is_trash_location(Path::new("/home/me/notes/Trash")));
+
+        assert!(is_in_trash(Path::new("/Users/me/.Trash/report.pdf")));
+        assert!(!is_in_trash(Path::new("/Users/me/Documents/report.pdf")));
+        assert_eq!(
+            trash_below(
+                Path::new("/Users/me/.Trash/old/a.txt"),
+                Path::new("/Users/me")
+            ),
+            Some(Path::new("/Users/me/.Trash"))
+        );
+        assert_eq!(
+            trash_below(
+                Path::new("/Users/me/.Trash/old/a.txt"),
+                Path::new("/Users/me/.Trash")
+            ),
+            None
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Backup and snapshot volume detection.
+//!
+//! Time Machine backups (`Backups.backupdb`, APFS `YYYY-MM-DD-HHMMSS.backup`
+//! snapshots) and local snapshot mounts repeat the whole disk many times over.
+//! The scanner prunes them from the walk and the daemon drops watcher updates
+//! under them unless `index_backup_volumes` is set.
+
+use std::path::Path;
+
+/// Directory names that only ever hold backups or snapshot mounts.
+const BACKUP_DIR_NAMES: &[&str] = &[
+    ".mobilebackups",
+    ".mobilebackups.trash",
+    ".timemachine",
+    "com.apple.timemachine.localsnapshots",
+];
+
+/// Suffixes of APFS Time Machine snapshot directories.
+const SNAPSHOT_SUFFIXES: &[&str] = &["backup", "inprogress", "interrupted", "previous"];
+
+/// Whether a directory named `name` holds backups or snapshots.
+pub fn is_backup_dir_name(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.ends_with(".backupdb")
+        || BACKUP_DIR_NAMES.contains(&lower.as_str())
+        || is_snapshot_dir_name(&lower)
+}
+
+/// `2024-01-15-093012.backup` and its in-progress variants.
+fn is_snapshot_dir_name(lower: &str) -> bool {
+    let Some((stamp, suffix)) = lower.rsplit_once('.') else {
+        return false;
+    };
+    SNAPSHOT_SUFFIXES.contains(&suffix)
+        && stamp.len() == 17
+        && stamp.char_indices().all(|(idx, c)| match idx {
+            4 | 7 | 10 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Whether `path` is the mount point of a snapshot or Time Machine volume,
+/// judged by the mount itself rather than its name.
+#[cfg(target_os = "macos")]
+pub fn is_snapshot_mount(path: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    /// `MNT_SNAPSHOT` from `<sys/mount.h>`.
+    const MNT_SNAPSHOT: u32 = 0x4000_0000;
+
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    let from = unsafe { std::ffi::CStr::from_ptr(stat.f_mntfromname.as_ptr()) };
+    stat.f_flags & MNT_SNAPSHOT != 0 || from.to_string_lossy().contains("com.apple.TimeMachine")
+}
+
+/// Whether `path` is the mount point of a snapshot or Time Machine volume.
+#[cfg(not(target_os = "macos"))]
+pub fn is_snapshot_mount(_path: &Path) -> bool {
+    false
+}
+
+/// Whether the directory `path` is a backup location: a backup directory
+/// by name, or a snapshot volume mounted under `/Volumes`.
+pub fn is_backup_location(path: &Path) -> bool {
+    let by_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(is_backup_dir_name);
+    by_name || (path.parent() == Some(Path::new("/Volumes")) && is_snapshot_mount(path))
+}
+
+/// The outermost backup location strictly below `root` that contains (or
+/// is) `path`. Locations at or above `root` do not count, so an explicitly
+/// indexed backup volume is still indexed.
+pub fn backup_below<'a>(path: &'a Path, root: &Path) -> Option<&'a Path> {
+    path.ancestors()
+        .take_while(|dir| dir.starts_with(root) && *dir != root)
+        .filter(|dir| is_backup_location(dir))
+        .last()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_and_snapshot_directories_are_recognised_by_name() {
+        assert!(is_backup_dir_name("Backups.backupdb"));
+        assert!(is_backup_dir_name(".MobileBackups"));
+        assert!(is_backup_dir_name("com.apple.TimeMachine.localsnapshots"));
+        assert!(is_backup_dir_name("2024-01-15-093012.backup"));
+        assert!(is_backup_dir_name("2024-01-15-093012.inProgress"));
+        assert!(!is_backup_dir_name("2024-01-15.backup"));
+        assert!(!is_backup_dir_name("notes.backup"));
+        assert!(!is_backup_dir_name("backups"));
+    }
+
+    #[test]
+    fn backups_are_found_below_the_root_only() {
+        let root = Path::new("/Volumes");
+        let inside = Path::new("/Volumes/TM/Backups.backupdb/mac/2024-01-15-093012/Users");
+        assert_eq!(
+            backup_below(inside, root),
+            Some(Path::new("/Volumes/TM/Backups.backupdb"))
+        );
+        assert_eq!(
+            backup_below(
+                Path::new("/Volumes/TM/Backups.backupdb"),
+                Path::new("/Volumes/TM")
+            ),
+            Some(Path::new("/Volumes/TM/Backups.backupdb"))
+        );
+        assert_eq!(
+            backup_below(inside, Path::new("/Volumes/TM/Backups.backupdb")),
+            None
+        );
+        assert_eq!(
+            backup_below(Path::new("/Users/me/src/main.rs"), Path::new("/Users")),
+            None
+        );
+    }
+}
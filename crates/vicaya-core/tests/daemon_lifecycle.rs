@@ -174,3 +174,13 @@ fn test_get_pid_corrupted_file() {
         let _ = daemon::remove_pid_file();
     });
 }
+
+/// Test that wait_until_ready() gives up when nothing answers.
+#[test]
+fn test_wait_until_ready_times_out_without_a_daemon() {
+    with_test_vicaya_dir(|_| {
+        let started = std::time::Instant::now();
+        assert!(!daemon::wait_until_ready(Duration::from_millis(100)));
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    });
+}
@@ -0,0 +1,440 @@
+//! Golden tests for the IPC wire format.
+//!
+//! `tests/golden/ipc-schema.json` is the published JSON Schema and
+//! `tests/golden/ipc-messages.jsonl` holds one serialized sample of every
+//! `Request` and `Response` variant. Both must stay byte-for-byte stable:
+//! third-party clients are written against them. After an intentional
+//! protocol change, regenerate them with
+//! `VICAYA_UPDATE_GOLDEN=1 cargo test -p vicaya-core --test ipc_golden`
+//! and review the diff.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+use vicaya_core::accounting::{Subsystem, SubsystemUsage};
+use vicaya_core::config::{IndexProfile, JournalFsync, ResolvedScan, ScanProfile};
+use vicaya_core::ipc::{
+    json_schema, BatchResults, BuildInfo, DaemonStatus, DeletedEntry, DirSize, DirUsageEntry,
+    DirectoryRank, Framing, IndexCap, MatchStrategy, PathCompletion, Request, Response,
+    RootCoverage, RootStats, ScoreExplanation, SearchFilters, SearchFlags, SearchOptions,
+    SearchResult, SearchSort,
+};
+use vicaya_core::permissions::AttributeFilter;
+use vicaya_core::query::{CmpOp, CmpU64};
+use vicaya_core::smriti::{SmritiAction, SmritiEntry};
+
+fn golden_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(name)
+}
+
+/// Compare `actual` with the golden file, or rewrite it when asked to.
+fn assert_golden(name: &str, actual: &str) {
+    let path = golden_path(name);
+    if std::env::var_os("VICAYA_UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {}: {e}", path.display()));
+    assert!(
+        expected == actual,
+        "{} is out of date; if the protocol change is intended, rerun with \
+         VICAYA_UPDATE_GOLDEN=1 and review the diff",
+        path.display()
+    );
+}
+
+fn result() -> SearchResult {
+    SearchResult {
+        path: "/Users/me/code/vicaya/src/main.rs".to_string(),
+        name: "main.rs".to_string(),
+        score: 0.95,
+        size: 1024,
+        mtime: 1_700_000_000,
+        uid: 501,
+        gid: 20,
+        mode: 0o100_644,
+        indexed_at: 1_700_000_100,
+        content_hash: Some("3fa9c2d41b7e8f06".to_string()),
+        is_dir: false,
+        total_size: None,
+        explain: Some(ScoreExplanation {
+            strategy: MatchStrategy::Prefix,
+            base_score: 0.9,
+            smriti_boost: 0.05,
+            recent_scope_boost: 0.0,
+            context_penalty: 0,
+            scope_boost: 30,
+            directory_bias: 0,
+            path_depth: 6,
+        }),
+    }
+}
+
+fn search_options() -> SearchOptions {
+    SearchOptions {
+        offset: 20,
+        scope: Some("/Users/me/code".to_string()),
+        filters: SearchFilters {
+            scope: Some("/Users/me/code/vicaya".to_string()),
+            attributes: AttributeFilter {
+                owner: Some("me".to_string()),
+                readonly: Some(false),
+                executable: None,
+                content_hash: Some("3fa9".to_string()),
+            },
+            max_depth: Some(3),
+            hidden: Some(false),
+            dir_size: Some(CmpU64 {
+                op: CmpOp::Gte,
+                value: 1 << 30,
+            }),
+        },
+        sort: SearchSort::Mtime,
+        fuzzy: false,
+        flags: SearchFlags {
+            recent_if_empty: true,
+            explain: true,
+            no_broaden: true,
+        },
+        scorer: Some("recency".to_string()),
+        directories: DirectoryRank::Prefer,
+        ..SearchOptions::new("main", 10)
+    }
+}
+
+fn requests() -> Vec<Request> {
+    vec![
+        Request::Search(SearchOptions::new("main", 10)),
+        Request::Search(search_options()),
+        Request::SearchBatch {
+            queries: vec![SearchOptions::new("a", 5), SearchOptions::new("b", 5)],
+            total_limit: Some(8),
+        },
+        Request::Status,
+        Request::Ping,
+        Request::RootStats,
+        Request::Metrics,
+        Request::Rebuild { dry_run: true },
+        Request::Reconcile,
+        Request::Exclude {
+            patterns: vec!["**/node_modules".to_string()],
+        },
+        Request::ReportMissing {
+            path: "/tmp/gone.txt".to_string(),
+        },
+        Request::RecentlyDeleted {
+            query: Some("report".to_string()),
+            limit: 50,
+        },
+        Request::SmritiRecord {
+            path: "/Users/me/notes.md".to_string(),
+            query: "notes".to_string(),
+            action: SmritiAction::Open,
+        },
+        Request::SmritiList {
+            query: None,
+            limit: 20,
+            filter_scope: Some("/Users/me".to_string()),
+        },
+        Request::SmritiForget {
+            path: "/Users/me/notes.md".to_string(),
+        },
+        Request::ScopeUsed {
+            path: "/Users/me/Projects/vicaya".to_string(),
+        },
+        Request::SmritiClear,
+        Request::CompletePath {
+            prefix: "/Users/me/Proj".to_string(),
+            limit: 20,
+            dirs_only: true,
+        },
+        Request::DirUsage {
+            path: "/Users/me/Projects".to_string(),
+            limit: 10,
+        },
+        Request::Jump {
+            query: "proj vic".to_string(),
+        },
+        Request::Suggest {
+            prefix: "Carg".to_string(),
+            limit: 5,
+        },
+        Request::Subscribe(SearchOptions {
+            filters: SearchFilters {
+                scope: Some("/Users/me/code".to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("main", 100)
+        }),
+        Request::Reveal {
+            path: "/Users/me/notes.md".to_string(),
+        },
+        Request::OpenEditor {
+            path: "/Users/me/notes.md".to_string(),
+        },
+        Request::CopyPath {
+            path: "/Users/me/notes.md".to_string(),
+        },
+        Request::Upgrade {
+            binary: Some("/usr/local/bin/vicaya-daemon".to_string()),
+        },
+        Request::Shutdown,
+        Request::Framing {
+            framing: Framing::Msgpack,
+        },
+    ]
+}
+
+fn responses() -> Vec<Response> {
+    vec![
+        Response::SearchResults {
+            results: vec![result()],
+            truncated: true,
+            suggestions: Vec::new(),
+            outside_scope: Vec::new(),
+        },
+        Response::SearchResults {
+            results: Vec::new(),
+            truncated: false,
+            suggestions: vec!["Cargo.toml".to_string()],
+            outside_scope: Vec::new(),
+        },
+        Response::SearchResults {
+            results: Vec::new(),
+            truncated: false,
+            suggestions: Vec::new(),
+            outside_scope: vec![result()],
+        },
+        Response::Status(DaemonStatus {
+            pid: 4242,
+            build: BuildInfo {
+                version: "1.5.1".to_string(),
+                git_sha: "abc1234".to_string(),
+                timestamp: "2026-01-01T00:00:00Z".to_string(),
+                target: "aarch64-apple-darwin".to_string(),
+            },
+            indexed_files: 1_000_000,
+            trigram_count: 50_000,
+            arena_size: 64_000_000,
+            index_allocated_bytes: 200_000_000,
+            state_allocated_bytes: 260_000_000,
+            last_updated: 1_700_000_000,
+            reconciling: true,
+            pending_watcher_events: 3,
+            journal_entries: 12,
+            reconcile_progress: Some(40),
+            reconcile_scan: Some(ResolvedScan {
+                profile: ScanProfile::Reconcile,
+                threads: 2,
+                throttle_ms: 5,
+            }),
+            last_reconcile: Some(1_699_990_000),
+            next_reconcile: Some(1_700_010_000),
+            index_profile: IndexProfile::Lite,
+            warmup_ms: Some(85),
+            journal_fsync: Some(JournalFsync::Interval { interval_ms: 1000 }),
+            index_cap: Some(IndexCap {
+                max_indexed_files: 1_000_000,
+                skipped: 5_000,
+                skipped_live: 2,
+                roots: vec![RootCoverage {
+                    root: "/Users/me".to_string(),
+                    indexed: 1_000_000,
+                    skipped: 5_000,
+                    complete_depth: Some(7),
+                }],
+            }),
+        }),
+        Response::Pong {
+            ready: false,
+            indexed_files: 1_000_000,
+            reconciling: true,
+            reconcile_progress: Some(40),
+        },
+        Response::RootStats {
+            roots: vec![RootStats {
+                root: "/Users/me".to_string(),
+                files: 1_000_000,
+                dirs: 90_000,
+                total_bytes: 250_000_000_000,
+                memory_bytes: 96_000_000,
+                last_scan: Some(1_700_000_000),
+            }],
+        },
+        Response::Metrics {
+            uptime_secs: 3600,
+            process_cpu_ns: 9_000_000_000,
+            subsystems: vec![SubsystemUsage {
+                subsystem: Subsystem::Scan,
+                cpu_ns: 8_000_000_000,
+                read_bytes: Some(1_048_576),
+                spans: 1,
+            }],
+        },
+        Response::SearchBatchResults {
+            batches: vec![
+                BatchResults {
+                    results: vec![result()],
+                    truncated: false,
+                },
+                BatchResults::default(),
+            ],
+        },
+        Response::RebuildComplete {
+            files_indexed: 1_000_000,
+        },
+        Response::ReconcileRequested {
+            already_running: false,
+        },
+        Response::Excluded { removed: 1200 },
+        Response::MissingReported { removed: true },
+        Response::RecentlyDeleted {
+            entries: vec![DeletedEntry {
+                name: "report.pdf".to_string(),
+                original_path: "/Users/me/Documents/report.pdf".to_string(),
+                trash_path: "/Users/me/.Trash/report.pdf".to_string(),
+                size: 2048,
+                is_dir: false,
+                deleted_at: 1_700_000_200,
+            }],
+        },
+        Response::Upgrading {
+            binary: "/usr/local/bin/vicaya-daemon".to_string(),
+            version: "1.5.2".to_string(),
+        },
+        Response::Ok,
+        Response::SmritiEntries {
+            entries: vec![SmritiEntry {
+                path: "/Users/me/notes.md".to_string(),
+                name: "notes.md".to_string(),
+                total_count: 3,
+                open_count: 2,
+                copy_count: 1,
+                reveal_count: 0,
+                print_count: 0,
+                enter_count: 0,
+                first_used: 1_699_000_000,
+                last_used: 1_700_000_000,
+                last_query: "notes".to_string(),
+                last_action: SmritiAction::Copy,
+            }],
+        },
+        Response::SmritiForgot { removed: false },
+        Response::PathCompletions {
+            completions: vec![PathCompletion {
+                path: "/Users/me/Projects".to_string(),
+                is_dir: true,
+            }],
+        },
+        Response::DirUsage {
+            total: DirSize {
+                bytes: 5_368_709_120,
+                files: 48_210,
+            },
+            entries: vec![DirUsageEntry {
+                path: "/Users/me/Projects/vicaya".to_string(),
+                size: DirSize {
+                    bytes: 2_147_483_648,
+                    files: 9_120,
+                },
+            }],
+        },
+        Response::JumpTarget {
+            path: Some("/Users/me/Projects/vicaya".to_string()),
+        },
+        Response::Suggestions {
+            names: vec!["Cargo.toml".to_string(), "Cargo.lock".to_string()],
+        },
+        Response::Framing {
+            framing: Framing::Msgpack,
+        },
+        Response::Subscribed,
+        Response::IndexChanged {
+            removed: vec!["/Users/me/code/vicaya/src/old_main.rs".to_string()],
+            added: vec![result()],
+        },
+        Response::Error {
+            message: "index not ready".to_string(),
+        },
+    ]
+}
+
+/// Wire `type` tags of every variant of a definition in the schema.
+fn schema_tags(schema: &serde_json::Value, definition: &str) -> BTreeSet<String> {
+    schema["definitions"][definition]["oneOf"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|variant| {
+            variant["properties"]["type"]["enum"][0]
+                .as_str()
+                .unwrap()
+                .to_string()
+        })
+        .collect()
+}
+
+fn message_tags(lines: &[String]) -> BTreeSet<String> {
+    lines
+        .iter()
+        .map(|line| {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            value["type"].as_str().unwrap().to_string()
+        })
+        .collect()
+}
+
+#[test]
+fn ipc_schema_matches_golden() {
+    let schema = serde_json::to_string_pretty(&json_schema()).unwrap() + "\n";
+    assert_golden("ipc-schema.json", &schema);
+}
+
+#[test]
+fn ipc_messages_match_golden_and_round_trip() {
+    let request_samples = requests();
+    let response_samples = responses();
+    let requests: Vec<String> = request_samples
+        .iter()
+        .map(|r| r.to_json().unwrap())
+        .collect();
+    let responses: Vec<String> = response_samples
+        .iter()
+        .map(|r| r.to_json().unwrap())
+        .collect();
+    let mut golden = String::new();
+    for line in requests.iter().chain(&responses) {
+        golden.push_str(line);
+        golden.push('\n');
+    }
+    assert_golden("ipc-messages.jsonl", &golden);
+
+    for line in &requests {
+        assert_eq!(&Request::from_json(line).unwrap().to_json().unwrap(), line);
+    }
+    for line in &responses {
+        assert_eq!(&Response::from_json(line).unwrap().to_json().unwrap(), line);
+    }
+
+    // MessagePack framing carries the same types: every sample survives it
+    // unchanged.
+    for (request, line) in request_samples.iter().zip(&requests) {
+        let mut frame = Vec::new();
+        Framing::Msgpack.write(&mut frame, request).unwrap();
+        let decoded: Request = Framing::Msgpack.read(&mut &frame[..]).unwrap().unwrap();
+        assert_eq!(&decoded.to_json().unwrap(), line);
+    }
+    for (response, line) in response_samples.iter().zip(&responses) {
+        let mut frame = Vec::new();
+        Framing::Msgpack.write(&mut frame, response).unwrap();
+        let decoded: Response = Framing::Msgpack.read(&mut &frame[..]).unwrap().unwrap();
+        assert_eq!(&decoded.to_json().unwrap(), line);
+    }
+
+    let schema = serde_json::to_value(json_schema()).unwrap();
+    assert_eq!(message_tags(&requests), schema_tags(&schema, "Request"));
+    assert_eq!(message_tags(&responses), schema_tags(&schema, "Response"));
+}
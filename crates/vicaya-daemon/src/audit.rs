@@ -0,0 +1,165 @@
+//! Audit records for privileged socket requests.
+//!
+//! Rebuilds, reconciles, exclusions, Smriti clears, upgrades and shutdowns
+//! used to leave no trace of who asked for them. [`PendingAudit`] captures the
+//! connected peer's pid and uid when such a request arrives and appends an
+//! [`AuditRecord`] with the daemon's answer once it is known.
+
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use tracing::warn;
+use vicaya_core::audit::{AuditOutcome, AuditRecord};
+use vicaya_core::ipc::{Request, Response};
+
+/// A privileged request waiting for its response to be audited.
+#[derive(Debug)]
+pub(crate) struct PendingAudit {
+    peer_pid: Option<i32>,
+    peer_uid: Option<u32>,
+    request: &'static str,
+    detail: Option<String>,
+}
+
+impl PendingAudit {
+    /// Start auditing `request` from the client on `stream`, or `None` when
+    /// the request is not privileged.
+    pub(crate) fn for_request(request: &Request, stream: &UnixStream) -> Option<Self> {
+        let (request, detail) = privileged(request)?;
+        let (peer_pid, peer_uid) = peer_credentials(stream);
+        Some(Self {
+            peer_pid,
+            peer_uid,
+            request,
+            detail,
+        })
+    }
+
+    /// Append the record for `response` to the log at `path`. A failed
+    /// write is logged, never returned: the request itself already ran.
+    pub(crate) fn finish(self, path: &Path, response: &Response) {
+        let (outcome, message) = match response {
+            Response::Error { message } => (AuditOutcome::Error, Some(message.clone())),
+            _ => (AuditOutcome::Ok, None),
+        };
+        let record = AuditRecord {
+            at: chrono::Utc::now().timestamp(),
+            peer_pid: self.peer_pid,
+            peer_uid: self.peer_uid,
+            request: self.request.to_string(),
+            detail: self.detail,
+            outcome,
+            message,
+        };
+        if let Err(e) = vicaya_core::audit::append(path, &record) {
+            warn!("Failed to append to audit log {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// The audit kind and detail of `request`, when it is privileged.
+fn privileged(request: &Request) -> Option<(&'static str, Option<String>)> {
+    match request {
+        Request::Rebuild { dry_run } => Some(("rebuild", dry_run.then(|| "dry_run".to_string()))),
+        Request::Reconcile => Some(("reconcile", None)),
+        Request::Exclude { patterns } => Some(("exclude", Some(patterns.join(", ")))),
+        Request::SmritiClear => Some(("smriti_clear", None)),
+        Request::Upgrade { binary } => Some(("upgrade", binary.clone())),
+        Request::Shutdown => Some(("shutdown", None)),
+        _ => None,
+    }
+}
+
+/// Process and user id of the peer on `stream`, as far as the platform says.
+#[cfg(target_os = "linux")]
+fn peer_credentials(stream: &UnixStream) -> (Option<i32>, Option<u32>) {
+    use std::os::fd::AsRawFd;
+
+    let mut cred = libc::ucred {
+        pid: 0,
+        uid: 0,
+        gid: 0,
+    };
+    let mut len = std::mem::size_of::<libc::ucred>() as libc::socklen_t;
+    // SAFETY: `cred` and `len` outlive the call and `len` is `cred`'s size.
+    let rc = unsafe {
+        libc::getsockopt(
+            stream.as_raw_fd(),
+            libc::SOL_SOCKET,
+            libc::SO_PEERCRED,
+            (&mut cred as *mut libc::ucred).cast(),
+            &mut len,
+        )
+    };
+    if rc != 0 {
+        return (None, None);
+    }
+    (Some(cred.pid), Some(cred.uid))
+}
+
+/// Process and user id of the peer on `stream`, as far as the platform says.
+#[cfg(target_os = "macos")]
+fn peer_credentials(stream: &UnixStream) -> (Option<i32>, Option<u32>) {
+    use std::os::fd::AsRawFd;
+
+    let fd = stream.as_raw_fd();
+    let mut uid: libc::uid_t = 0;
+    let mut gid: libc::gid_t = 0;
+    // SAFETY: both out-pointers are valid for the call.
+    let uid = (unsafe { libc::getpeereid(fd, &mut uid, &mut gid) } == 0).then_some(uid);
+    let mut pid: libc::pid_t = 0;
+    let mut len = std::mem::size_of::<libc::pid_t>() as libc::socklen_t;
+    // SAFETY: `pid` and `len` outlive the call and `len` is `pid`'s size.
+    let rc = unsafe {
+        libc::getsockopt(
+            fd,
+            libc::SOL_LOCAL,
+            libc::LOCAL_PEERPID,
+            (&mut pid as *mut libc::pid_t).cast(),
+            &mut len,
+        )
+    };
+    ((rc == 0).then_some(pid), uid)
+}
+
+/// Process and user id of the peer on `stream`, as far as the platform says.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn peer_credentials(_stream: &UnixStream) -> (Option<i32>, Option<u32>) {
+    (None, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_privileged_requests_are_audited() {
+        assert_eq!(
+            privileged(&Request::Rebuild { dry_run: true }),
+            Some(("rebuild", Some("dry_run".to_string())))
+        );
+        assert_eq!(
+            privileged(&Request::Exclude {
+                patterns: vec!["*.log".to_string(), "target".to_string()],
+            }),
+            Some(("exclude", Some("*.log, target".to_string())))
+        );
+        assert_eq!(privileged(&Request::Shutdown), Some(("shutdown", None)));
+        assert_eq!(privileged(&Request::Status), None);
+        assert_eq!(
+            privileged(&Request::Jump {
+                query: "notes".to_string(),
+            }),
+            None
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn peer_credentials_name_the_connected_process() {
+        let (client, _server) = UnixStream::pair().unwrap();
+        let (pid, uid) = peer_credentials(&client);
+        assert_eq!(pid, Some(std::process::id() as i32));
+        // SAFETY: getuid has no preconditions.
+        assert_eq!(uid, Some(unsafe { libc::getuid() }));
+    }
+}
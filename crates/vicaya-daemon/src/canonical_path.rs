@@ -0,0 +1,171 @@
+//! Canonical spelling for paths entering the daemon state.
+//!
+//! The index stores paths as the scanner saw them under the configured
+//! roots, but watcher events and journal entries can spell the same file
+//! differently: FSEvents reports `/private/var/...` for a root configured as
+//! `/var/...`, a symlinked root shows up under its target, and some events
+//! carry doubled or trailing slashes. Looked up verbatim, those miss
+//! `path_to_id` and get inserted a second time.
+//!
+//! [`PathCanonicalizer`] rewrites such paths to the indexed spelling. The
+//! prefix rules are derived once from the roots, so each event costs a
+//! string scan rather than a `canonicalize` syscall chain.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+/// macOS firmlinks whose targets FSEvents reports instead of the short form.
+#[cfg(target_os = "macos")]
+const PRIVATE_FIRMLINKS: &[&str] = &["/var", "/tmp", "/etc"];
+#[cfg(not(target_os = "macos"))]
+const PRIVATE_FIRMLINKS: &[&str] = &[];
+
+/// Rewrites alias spellings of indexed paths to the form stored in the index.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PathCanonicalizer {
+    /// `(alias, indexed)` prefix pairs, longest alias first.
+    rules: Vec<(String, String)>,
+}
+
+impl PathCanonicalizer {
+    /// Derive prefix rules for `roots`, resolving each root's symlinks once.
+    pub(crate) fn new(roots: &[PathBuf]) -> Self {
+        let mut rules = Vec::new();
+        for root in roots {
+            let indexed = lexically_normalize(&root.to_string_lossy()).into_owned();
+
+            if let Ok(real) = std::fs::canonicalize(root) {
+                let real = real.to_string_lossy().into_owned();
+                if real != indexed {
+                    rules.push((real, indexed.clone()));
+                }
+            }
+
+            for short in PRIVATE_FIRMLINKS {
+                let private = format!("/private{short}");
+                if let Some(rest) = strip_path_prefix(&indexed, short) {
+                    rules.push((format!("{private}{rest}"), indexed.clone()));
+                } else if let Some(rest) = strip_path_prefix(&indexed, &private) {
+                    rules.push((format!("{short}{rest}"), indexed.clone()));
+                }
+            }
+        }
+
+        rules.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.cmp(b)));
+        rules.dedup();
+        Self { rules }
+    }
+
+    /// Return the indexed spelling of `path`.
+    pub(crate) fn canonicalize<'a>(&self, path: &'a str) -> Cow<'a, str> {
+        let normalized = lexically_normalize(path);
+        for (alias, indexed) in &self.rules {
+            if let Some(rest) = strip_path_prefix(&normalized, alias) {
+                return Cow::Owned(format!("{indexed}{rest}"));
+            }
+        }
+        normalized
+    }
+
+    /// [`Self::canonicalize`] for an owned path.
+    pub(crate) fn canonicalize_owned(&self, path: String) -> String {
+        match self.canonicalize(&path) {
+            Cow::Borrowed(_) => path,
+            Cow::Owned(canonical) => canonical,
+        }
+    }
+}
+
+/// Return the remainder of `path` after `prefix` (starting with `/`, or
+/// empty), if `prefix` names `path` or one of its ancestors.
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = path.strip_prefix(prefix)?;
+    (rest.is_empty() || rest.starts_with('/') || prefix == "/").then_some(rest)
+}
+
+/// Drop `.` components, empty components, and trailing slashes, and resolve
+/// `..` against the preceding component, all without touching the disk.
+fn lexically_normalize(path: &str) -> Cow<'_, str> {
+    let clean = !path.contains("//")
+        && !path.contains("/./")
+        && !path.contains("/../")
+        && !path.ends_with("/.")
+        && !path.ends_with("/..")
+        && (path == "/" || !path.ends_with('/'));
+    if clean {
+        return Cow::Borrowed(path);
+    }
+
+    let mut components: Vec<&str> = Vec::new();
+    for component in path.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            other => components.push(other),
+        }
+    }
+    let joined = components.join("/");
+    if Path::new(path).is_absolute() {
+        Cow::Owned(format!("/{joined}"))
+    } else {
+        Cow::Owned(joined)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lexical_normalization_cleans_separators_and_dots() {
+        let paths = PathCanonicalizer::default();
+        assert_eq!(paths.canonicalize("/a/b/c.txt"), "/a/b/c.txt");
+        assert!(matches!(paths.canonicalize("/a/b"), Cow::Borrowed(_)));
+        assert_eq!(paths.canonicalize("/a//b/"), "/a/b");
+        assert_eq!(paths.canonicalize("/a/./b/../c"), "/a/c");
+        assert_eq!(paths.canonicalize("/"), "/");
+        assert_eq!(paths.canonicalize("//"), "/");
+    }
+
+    #[test]
+    fn symlinked_roots_map_their_target_back_to_the_configured_spelling() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let real = std::fs::canonicalize(&real).unwrap();
+        let link_str = link.to_string_lossy().to_string();
+
+        let paths = PathCanonicalizer::new(std::slice::from_ref(&link));
+        let event = format!("{}/src//main.rs", real.display());
+
+        assert_eq!(
+            paths.canonicalize(&event),
+            format!("{link_str}/src/main.rs")
+        );
+        assert_eq!(paths.canonicalize(&real.to_string_lossy()), link_str);
+        // A sibling sharing the textual prefix is left alone.
+        let sibling = format!("{}-other/file", real.display());
+        assert_eq!(paths.canonicalize(&sibling), sibling);
+    }
+
+    #[test]
+    fn prefix_matching_respects_component_boundaries() {
+        assert_eq!(strip_path_prefix("/var/db", "/var"), Some("/db"));
+        assert_eq!(strip_path_prefix("/var", "/var"), Some(""));
+        assert_eq!(strip_path_prefix("/various", "/var"), None);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn private_firmlinks_map_to_the_configured_spelling() {
+        let paths = PathCanonicalizer::new(&[PathBuf::from("/var/folders/xy")]);
+        assert_eq!(
+            paths.canonicalize("/private/var/folders/xy/T/a.txt"),
+            "/var/folders/xy/T/a.txt"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Panic hook that turns a panic on any daemon thread into a crash report.
+//!
+//! Without it a panicking watcher or reconcile thread left the daemon
+//! serving an index nobody updated, and a poisoned state lock failed later
+//! requests one at a time. [`install`] sets a hook that writes a
+//! [`CrashReport`] with the backtrace, the latest requests and the index
+//! counters to `crashes/` in the vicaya directory, then aborts the process
+//! when the panic is off the main thread, so the service manager restarts a
+//! whole daemon instead of a limping one.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock, TryLockError};
+use tracing::error;
+use vicaya_core::crash::{CrashReport, IndexStats, RecentRequest};
+use vicaya_core::ipc::Request;
+
+use crate::ipc_server::SharedState;
+
+/// Requests remembered for the report.
+const RECENT_REQUESTS: usize = 32;
+/// Characters kept of each request's debug form.
+const REQUEST_CHARS: usize = 240;
+
+static CONTEXT: OnceLock<CrashContext> = OnceLock::new();
+
+struct CrashContext {
+    state: SharedState,
+    requests: Mutex<VecDeque<RecentRequest>>,
+}
+
+/// Write a crash report for every panic, then abort on panics outside the
+/// main thread.
+pub(crate) fn install(state: &SharedState) {
+    let _ = CONTEXT.set(CrashContext::new(state));
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        let thread = std::thread::current();
+        let thread_name = thread.name().unwrap_or("<unnamed>");
+        if let Some(context) = CONTEXT.get() {
+            let report = context.report(
+                thread_name,
+                panic_message(info.payload()),
+                info.location().map(ToString::to_string),
+                std::backtrace::Backtrace::force_capture().to_string(),
+            );
+            match report.write(&vicaya_core::paths::crash_dir()) {
+                Ok(path) => error!("Daemon panicked; crash report at {}", path.display()),
+                Err(e) => error!("Daemon panicked; failed to write crash report: {}", e),
+            }
+        }
+        if thread_name != "main" {
+            std::process::abort();
+        }
+    }));
+}
+
+/// Remember `request` for a later crash report. Does nothing until
+/// [`install`] ran.
+pub(crate) fn record_request(request: &Request) {
+    let Some(context) = CONTEXT.get() else {
+        return;
+    };
+    context.record(request);
+}
+
+impl CrashContext {
+    fn new(state: &SharedState) -> Self {
+        Self {
+            state: SharedState::clone(state),
+            requests: Mutex::new(VecDeque::with_capacity(RECENT_REQUESTS)),
+        }
+    }
+
+    fn record(&self, request: &Request) {
+        let request = format!("{:?}", request);
+        let request = match request.char_indices().nth(REQUEST_CHARS) {
+            Some((end, _)) => format!("{}…", &request[..end]),
+            None => request,
+        };
+        let mut requests = self
+            .requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if requests.len() == RECENT_REQUESTS {
+            requests.pop_front();
+        }
+        requests.push_back(RecentRequest {
+            at: chrono::Utc::now().timestamp(),
+            request,
+        });
+    }
+
+    /// The report for a panic. Locks are only tried: the panicking thread
+    /// may hold them.
+    fn report(
+        &self,
+        thread: &str,
+        message: String,
+        location: Option<String>,
+        backtrace: String,
+    ) -> CrashReport {
+        let recent_requests = match self.requests.try_lock() {
+            Ok(requests) => requests.iter().cloned().collect(),
+            Err(TryLockError::Poisoned(poisoned)) => {
+                poisoned.into_inner().iter().cloned().collect()
+            }
+            Err(TryLockError::WouldBlock) => Vec::new(),
+        };
+        let state = match self.state.try_read() {
+            Ok(state) => Some(state),
+            Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => None,
+        };
+        let index = state.map(|state| IndexStats {
+            indexed_files: state.indexed_file_count(),
+            trigram_count: state.snapshot.trigram_index.trigram_count(),
+            pending_watcher_events: state.activity.pending_watcher_events(),
+            journal_entries: state.activity.journal_entries(),
+            reconciling: state.reconciling,
+        });
+        CrashReport {
+            crashed_at: chrono::Utc::now().timestamp(),
+            pid: std::process::id(),
+            version: vicaya_core::build_info::BUILD_INFO.version_line("vicaya-daemon"),
+            thread: thread.to_string(),
+            message,
+            location,
+            backtrace,
+            recent_requests,
+            index,
+        }
+    }
+}
+
+/// The text of a panic payload (`panic!` with a literal or a format).
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "<non-string panic payload>".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc_server::DaemonState;
+    use std::sync::{Arc, RwLock};
+    use tempfile::tempdir;
+    use vicaya_core::Config;
+    use vicaya_scanner::Scanner;
+
+    #[test]
+    fn reports_carry_recent_requests_and_index_counters() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("notes.md"), "x").unwrap();
+        let config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            index_path: vicaya_dir.path().join("index"),
+            exclusions: Vec::new(),
+            ..Config::default()
+        };
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let state: SharedState = Arc::new(RwLock::new(DaemonState::new(
+            config,
+            vicaya_dir.path().join("index.bin"),
+            vicaya_dir.path().join("journal.log"),
+            snapshot,
+        )));
+
+        let context = CrashContext::new(&state);
+        for i in 0..RECENT_REQUESTS + 2 {
+            context.record(&Request::Jump {
+                query: format!("q{i}"),
+            });
+        }
+        context.record(&Request::Jump {
+            query: "x".repeat(1000),
+        });
+
+        let report = context.report("watcher", "boom".to_string(), None, String::new());
+        assert_eq!(report.thread, "watcher");
+        assert_eq!(report.recent_requests.len(), RECENT_REQUESTS);
+        assert!(report.recent_requests[0].request.contains("q3"));
+        let last = &report.recent_requests.last().unwrap().request;
+        assert!(last.ends_with('…'));
+        assert_eq!(last.chars().count(), REQUEST_CHARS + 1);
+        // The note and its directory.
+        assert_eq!(report.index.as_ref().unwrap().indexed_files, 2);
+
+        // A writer holding the state lock leaves the counters out.
+        let _writer = state.write().unwrap();
+        let report = context.report("main", "boom".to_string(), None, String::new());
+        assert!(report.index.is_none());
+        assert_eq!(report.recent_requests.len(), RECENT_REQUESTS);
+
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&"owned".to_string()), "owned");
+    }
+}
@@ -0,0 +1,177 @@
+//! `--ephemeral`: a throwaway daemon for end-to-end tests.
+//!
+//! `vicaya-daemon --ephemeral --root DIR [--root DIR ...] [--state-dir DIR]`
+//! runs against its own state directory instead of the user's: a fresh
+//! directory under the system temp dir (removed on shutdown) unless
+//! `--state-dir` names one to keep, e.g. to test a restart on the same index.
+//! The directory gets a config that indexes the given roots, with no
+//! exclusions, and `VICAYA_DIR` points at it, so the socket, PID file, index,
+//! journal, and Smriti memory all live there and the normal startup path runs
+//! unchanged.
+//!
+//! Logs go to stderr. Once the socket accepts connections, one JSON line on
+//! stdout reports `socket`, `state_dir`, and `pid` for the test to connect to.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use vicaya_core::{Config, Error, Result};
+
+use crate::handoff::HANDOFF_ENV;
+
+/// Parsed `--ephemeral` flags.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct EphemeralArgs {
+    roots: Vec<PathBuf>,
+    state_dir: Option<PathBuf>,
+}
+
+impl EphemeralArgs {
+    /// The ephemeral flags in the daemon's arguments, or `None` without
+    /// `--ephemeral`. `--root` and `--state-dir` are only valid with it.
+    pub(crate) fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>> {
+        let mut ephemeral = false;
+        let mut parsed = Self::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| args.next())
+                    .ok_or_else(|| Error::Config(format!("{flag} needs a directory")))
+            };
+            match flag.as_str() {
+                "--ephemeral" => ephemeral = true,
+                "--root" => parsed.roots.push(PathBuf::from(value()?)),
+                "--state-dir" => parsed.state_dir = Some(PathBuf::from(value()?)),
+                _ => {}
+            }
+        }
+
+        if !ephemeral {
+            if parsed != Self::default() {
+                return Err(Error::Config(
+                    "--root and --state-dir require --ephemeral".to_string(),
+                ));
+            }
+            return Ok(None);
+        }
+        if parsed.roots.is_empty() {
+            return Err(Error::Config(
+                "--ephemeral needs at least one --root".to_string(),
+            ));
+        }
+        Ok(Some(parsed))
+    }
+}
+
+/// The state directory of a running ephemeral daemon.
+pub(crate) struct Ephemeral {
+    state_dir: PathBuf,
+    /// Whether the directory was created for this run and goes with it.
+    owned: bool,
+}
+
+impl Ephemeral {
+    /// Create the state directory and its config, and point `VICAYA_DIR` at
+    /// it. Call before anything reads a path.
+    ///
+    /// An upgrade re-execs with the same environment, so a handed-off daemon
+    /// keeps the directory its predecessor created instead of starting over.
+    pub(crate) fn activate(args: EphemeralArgs) -> Result<Self> {
+        let handed_off = std::env::var_os(HANDOFF_ENV).is_some();
+        let (state_dir, owned) = match args.state_dir {
+            Some(dir) => (vicaya_core::paths::resolve_user_path(&dir)?, false),
+            None => match std::env::var_os("VICAYA_DIR").filter(|_| handed_off) {
+                Some(dir) => (PathBuf::from(dir), true),
+                None => (fresh_dir(), true),
+            },
+        };
+        std::fs::create_dir_all(&state_dir)?;
+
+        let roots = args
+            .roots
+            .iter()
+            .map(|root| vicaya_core::paths::resolve_scope_dir(root))
+            .collect::<Result<Vec<_>>>()?;
+        // No default exclusions: fixtures under `target/` must be indexed.
+        let config = Config {
+            index_roots: roots,
+            exclusions: Vec::new(),
+            index_path: state_dir.join("index"),
+            ..Config::default()
+        };
+        config.save(&state_dir.join("config.toml"))?;
+
+        std::env::set_var("VICAYA_DIR", &state_dir);
+        std::env::remove_var(vicaya_core::paths::PROFILE_ENV);
+        Ok(Self { state_dir, owned })
+    }
+
+    /// Tell the test harness where to connect.
+    pub(crate) fn announce_ready(&self, socket: &std::path::Path) {
+        use std::io::Write;
+
+        let ready = serde_json::json!({
+            "socket": socket,
+            "state_dir": self.state_dir,
+            "pid": std::process::id(),
+        });
+        let mut stdout = std::io::stdout().lock();
+        let _ = writeln!(stdout, "{ready}");
+        let _ = stdout.flush();
+    }
+
+    /// Remove the state directory if this run created it.
+    pub(crate) fn cleanup(self) {
+        if self.owned {
+            let _ = std::fs::remove_dir_all(&self.state_dir);
+        }
+    }
+}
+
+/// A new, unused directory under the system temp dir. Kept short, since the
+/// socket inside it must fit in `sun_path` (104 bytes on macOS).
+fn fresh_dir() -> PathBuf {
+    static NEXT: AtomicU64 = AtomicU64::new(0);
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.subsec_nanos());
+    std::env::temp_dir().join(format!(
+        "vicaya-eph-{}-{:x}{}",
+        std::process::id(),
+        nanos,
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn parse_requires_ephemeral_for_its_flags_and_a_root_with_it() {
+        assert_eq!(EphemeralArgs::parse(args("vicaya-daemon")).unwrap(), None);
+        assert_eq!(
+            EphemeralArgs::parse(args(
+                "vicaya-daemon --ephemeral --root /a --root=/b --state-dir /s"
+            ))
+            .unwrap(),
+            Some(EphemeralArgs {
+                roots: vec![PathBuf::from("/a"), PathBuf::from("/b")],
+                state_dir: Some(PathBuf::from("/s")),
+            })
+        );
+        assert!(EphemeralArgs::parse(args("vicaya-daemon --root /a")).is_err());
+        assert!(EphemeralArgs::parse(args("vicaya-daemon --ephemeral")).is_err());
+        assert!(EphemeralArgs::parse(args("vicaya-daemon --ephemeral --root")).is_err());
+    }
+}
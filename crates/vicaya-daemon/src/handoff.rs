@@ -0,0 +1,145 @@
+//! Warm-standby upgrades: replace the daemon binary without losing the index.
+//!
+//! On `Request::Upgrade` the IPC server stops accepting connections and the
+//! main thread joins the watcher and reconcile threads, so every journaled
+//! update is already applied to the live index. [`exec_upgrade`] then saves
+//! that index over `index.bin`, truncates the journal, and execs the new
+//! binary under the same PID with [`HANDOFF_ENV`] set. The new process sees
+//! the marker through [`take_handoff`] and skips the startup reconcile, which
+//! is what makes a normal restart slow on large trees.
+
+use std::ffi::OsString;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+use tracing::info;
+use vicaya_core::{Error, Result};
+
+use crate::ipc_server::{truncate_journal, SharedState};
+
+/// Set to the handed-off index file when exec'ing the new binary.
+pub(crate) const HANDOFF_ENV: &str = "VICAYA_HANDOFF";
+
+/// Pick the binary to upgrade to and confirm it is a vicaya daemon.
+///
+/// Without `requested`, the daemon's own executable is used, which picks up
+/// a binary replaced in place (e.g. by `vicaya upgrade`). Returns the path
+/// and the version line the binary reports.
+pub(crate) fn resolve_binary(requested: Option<&str>) -> Result<(PathBuf, String)> {
+    let binary = match requested.filter(|path| !path.trim().is_empty()) {
+        Some(path) => vicaya_core::paths::expand_user_path(Path::new(path)),
+        None => current_exe()?,
+    };
+    let output = Command::new(&binary)
+        .arg("--version")
+        .output()
+        .map_err(|e| Error::Other(format!("Failed to run {}: {e}", binary.display())))?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || !version.starts_with("vicaya-daemon ") {
+        return Err(Error::Other(format!(
+            "{} is not a vicaya-daemon binary",
+            binary.display()
+        )));
+    }
+    Ok((binary, version))
+}
+
+/// The running executable. Linux reports a binary replaced by rename as
+/// "<path> (deleted)"; the new file lives at the original path.
+fn current_exe() -> Result<PathBuf> {
+    let exe = std::env::current_exe()?;
+    match exe
+        .to_str()
+        .and_then(|path| path.strip_suffix(" (deleted)"))
+    {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => Ok(exe),
+    }
+}
+
+/// Persist the live index and replace this process with `binary`.
+///
+/// Only returns on failure; the caller then shuts down normally.
+pub(crate) fn exec_upgrade(binary: &Path, state: &SharedState, journal_lock: &Mutex<()>) -> Error {
+    let index_file = match save_handoff(state, journal_lock) {
+        Ok(index_file) => index_file,
+        Err(e) => return e,
+    };
+    info!("Handing the index off to {}", binary.display());
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    let err = Command::new(binary)
+        .args(args)
+        .env(HANDOFF_ENV, &index_file)
+        .exec();
+    Error::Other(format!("Failed to exec {}: {err}", binary.display()))
+}
+
+/// Write the live index over `index.bin` and drop the journal it already
+/// contains. Returns the index file.
+fn save_handoff(state: &SharedState, journal_lock: &Mutex<()>) -> Result<PathBuf> {
+    let _journal_guard = journal_lock.lock().unwrap();
+    let state = state.read().unwrap();
+    state.snapshot.save(&state.index_file)?;
+    truncate_journal(&state.journal_file)?;
+    state.activity.set_journal_entries(0);
+    Ok(state.index_file.clone())
+}
+
+/// Whether this process was exec'd by [`exec_upgrade`] with `index_file`
+/// current. Clears the marker so daemons started by this one don't inherit it.
+pub(crate) fn take_handoff(index_file: &Path) -> bool {
+    let Some(handed_off) = std::env::var_os(HANDOFF_ENV) else {
+        return false;
+    };
+    std::env::remove_var(HANDOFF_ENV);
+    Path::new(&handed_off) == index_file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, RwLock};
+    use vicaya_core::Config;
+    use vicaya_scanner::Scanner;
+
+    use crate::ipc_server::DaemonState;
+
+    #[test]
+    fn handoff_saves_the_live_index_and_drops_the_applied_journal() {
+        let root = tempfile::tempdir().unwrap();
+        let index_dir = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("kept.txt"), "").unwrap();
+        let config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            exclusions: Vec::new(),
+            respect_ignore_files: false,
+            index_path: index_dir.path().to_path_buf(),
+            ..Config::default()
+        };
+        let index_file = index_dir.path().join("index.bin");
+        let journal_file = index_dir.path().join("index.journal");
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let files = snapshot.file_table.len();
+        std::fs::write(&journal_file, "stale\n").unwrap();
+        let state: SharedState = Arc::new(RwLock::new(DaemonState::new(
+            config,
+            index_file.clone(),
+            journal_file.clone(),
+            snapshot,
+        )));
+
+        let saved = save_handoff(&state, &Mutex::new(())).unwrap();
+        assert_eq!(saved, index_file);
+        let reloaded = vicaya_scanner::IndexSnapshot::load(&index_file).unwrap();
+        assert_eq!(reloaded.file_table.len(), files);
+        assert_eq!(std::fs::metadata(&journal_file).unwrap().len(), 0);
+
+        assert!(!take_handoff(&index_file));
+        std::env::set_var(HANDOFF_ENV, &index_file);
+        assert!(take_handoff(&index_file));
+        assert!(std::env::var_os(HANDOFF_ENV).is_none());
+        std::env::set_var(HANDOFF_ENV, index_dir.path().join("other.bin"));
+        assert!(!take_handoff(&index_file));
+    }
+}
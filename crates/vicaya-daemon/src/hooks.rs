@@ -0,0 +1,321 @@
+//! Runs `[[hooks]]` commands for applied index updates.
+//!
+//! The watcher thread feeds every prepared batch to [`HookRunner::observe`],
+//! which records the changed paths each hook's pattern matches. A hook fires
+//! once no matching change has arrived for its `debounce_ms`, so a `git
+//! checkout` touching hundreds of files runs the command once. At most
+//! [`MAX_RUNNING_HOOKS`] commands run at a time and a hook never overlaps
+//! itself; changes arriving meanwhile wait for the next run.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+use vicaya_core::config::HookConfig;
+use vicaya_core::hooks::{render_command, HookPattern};
+use vicaya_core::Config;
+
+use crate::ipc_server::PreparedIndexUpdate;
+
+/// Hook commands allowed to run concurrently across all hooks.
+pub(crate) const MAX_RUNNING_HOOKS: usize = 4;
+
+/// Changed paths remembered per pending run; older ones are dropped.
+const MAX_PENDING_PATHS: usize = 1024;
+
+struct Hook {
+    pattern: HookPattern,
+    command: String,
+    debounce: Duration,
+    cwd: Option<PathBuf>,
+    /// Distinct changed paths since the last run, oldest first.
+    pending: Vec<String>,
+    pending_set: HashSet<String>,
+    last_change: Option<Instant>,
+    running: Option<Child>,
+}
+
+impl Hook {
+    fn record(&mut self, path: &str, now: Instant) {
+        if self.pending_set.insert(path.to_string()) {
+            self.pending.push(path.to_string());
+            if self.pending.len() > MAX_PENDING_PATHS {
+                let dropped = self.pending.remove(0);
+                self.pending_set.remove(&dropped);
+            }
+        }
+        self.last_change = Some(now);
+    }
+
+    fn is_due(&self, now: Instant) -> bool {
+        self.last_change
+            .is_some_and(|changed| now.duration_since(changed) >= self.debounce)
+    }
+
+    /// Collect a finished run, logging failures.
+    fn reap(&mut self) {
+        let Some(child) = self.running.as_mut() else {
+            return;
+        };
+        match child.try_wait() {
+            Ok(None) => {}
+            Ok(Some(status)) => {
+                if !status.success() {
+                    warn!("Hook `{}` exited with {}", self.command, status);
+                }
+                self.running = None;
+            }
+            Err(e) => {
+                warn!("Failed to wait for hook `{}`: {}", self.command, e);
+                self.running = None;
+            }
+        }
+    }
+
+    fn start(&mut self) {
+        let paths = std::mem::take(&mut self.pending);
+        self.pending_set.clear();
+        self.last_change = None;
+
+        let rendered = render_command(&self.command, &paths);
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(&rendered)
+            .env("VICAYA_HOOK_PATHS", paths.join("\n"))
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        match command.spawn() {
+            Ok(child) => {
+                info!(
+                    "Running hook for {} changed path(s): {}",
+                    paths.len(),
+                    rendered
+                );
+                self.running = Some(child);
+            }
+            Err(e) => warn!("Failed to start hook `{}`: {}", rendered, e),
+        }
+    }
+}
+
+/// Debounces and runs the configured hooks.
+pub(crate) struct HookRunner {
+    hooks: Vec<Hook>,
+    max_running: usize,
+}
+
+impl HookRunner {
+    /// Compile `configs`; hooks whose pattern does not compile are skipped
+    /// with a warning.
+    pub(crate) fn new(configs: &[HookConfig]) -> Self {
+        let hooks = configs
+            .iter()
+            .filter_map(|config| match HookPattern::new(&config.pattern) {
+                Ok(pattern) => Some(Hook {
+                    pattern,
+                    command: config.command.clone(),
+                    debounce: Duration::from_millis(config.debounce_ms),
+                    cwd: config.cwd.clone(),
+                    pending: Vec::new(),
+                    pending_set: HashSet::new(),
+                    last_change: None,
+                    running: None,
+                }),
+                Err(e) => {
+                    warn!("Skipping hook `{}`: {}", config.command, e);
+                    None
+                }
+            })
+            .collect();
+        Self {
+            hooks,
+            max_running: MAX_RUNNING_HOOKS,
+        }
+    }
+
+    /// Record the paths in `updates` that match each hook. Removed paths
+    /// only count when the index would have held them.
+    pub(crate) fn observe(&mut self, config: &Config, updates: &[PreparedIndexUpdate]) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let now = Instant::now();
+        for (path, present) in updates.iter().flat_map(|update| update.touched_paths()) {
+            let mut indexed = None;
+            for hook in &mut self.hooks {
+                if !hook.pattern.matches(path) {
+                    continue;
+                }
+                let indexed = *indexed.get_or_insert_with(|| {
+                    present || vicaya_scanner::should_index_path(config, Path::new(path), false)
+                });
+                if indexed {
+                    hook.record(path, now);
+                }
+            }
+        }
+    }
+
+    /// Start every hook whose debounce window has passed, up to the
+    /// concurrency cap.
+    pub(crate) fn run_due(&mut self, now: Instant) {
+        let mut running = 0;
+        for hook in &mut self.hooks {
+            hook.reap();
+            running += usize::from(hook.running.is_some());
+        }
+        for hook in &mut self.hooks {
+            if running >= self.max_running {
+                break;
+            }
+            if hook.running.is_none() && hook.is_due(now) {
+                hook.start();
+                running += usize::from(hook.running.is_some());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::canonical_path::PathCanonicalizer;
+    use tempfile::tempdir;
+    use vicaya_watcher::IndexUpdate;
+
+    fn hook(pattern: &str, command: String, debounce_ms: u64) -> HookConfig {
+        HookConfig {
+            pattern: pattern.to_string(),
+            command,
+            debounce_ms,
+            cwd: None,
+        }
+    }
+
+    fn prepared(config: &Config, updates: Vec<IndexUpdate>) -> Vec<PreparedIndexUpdate> {
+        let canonical_paths = PathCanonicalizer::default();
+        updates
+            .into_iter()
+            .map(|update| crate::ipc_server::prepare_index_update(config, &canonical_paths, update))
+            .collect()
+    }
+
+    fn wait_for_hooks(runner: &mut HookRunner) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while runner.hooks.iter().any(|hook| hook.running.is_some()) {
+            assert!(Instant::now() < deadline, "hook did not finish");
+            std::thread::sleep(Duration::from_millis(10));
+            runner.run_due(Instant::now());
+        }
+    }
+
+    #[test]
+    fn matching_changes_run_the_command_once_after_the_debounce() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("root");
+        std::fs::create_dir_all(&root).unwrap();
+        let a = root.join("a.rs");
+        let b = root.join("b.rs");
+        std::fs::write(&a, "").unwrap();
+        std::fs::write(&b, "").unwrap();
+        let out = dir.path().join("out.txt");
+        let config = Config {
+            index_roots: vec![root.clone()],
+            exclusions: Vec::new(),
+            ..Config::default()
+        };
+
+        let mut runner = HookRunner::new(&[
+            hook(
+                "*.rs",
+                format!("echo {{count}} {{paths}} >> '{}'", out.display()),
+                50,
+            ),
+            hook("*.md", "exit 1".to_string(), 0),
+        ]);
+        let path = |p: &Path| p.to_string_lossy().to_string();
+        runner.observe(
+            &config,
+            &prepared(
+                &config,
+                vec![
+                    IndexUpdate::Modify { path: path(&a) },
+                    IndexUpdate::Modify { path: path(&b) },
+                    IndexUpdate::Modify { path: path(&a) },
+                    IndexUpdate::Delete {
+                        path: path(&root.join("gone.rs")),
+                    },
+                ],
+            ),
+        );
+
+        runner.run_due(Instant::now());
+        assert!(runner.hooks[0].running.is_none(), "still debouncing");
+        assert!(runner.hooks[1].running.is_none(), "no markdown changed");
+
+        std::thread::sleep(Duration::from_millis(60));
+        runner.run_due(Instant::now());
+        wait_for_hooks(&mut runner);
+
+        let output = std::fs::read_to_string(&out).unwrap();
+        assert_eq!(
+            output.trim(),
+            format!("3 {} {} {}/gone.rs", path(&a), path(&b), root.display())
+        );
+        assert!(runner.hooks[0].pending.is_empty());
+    }
+
+    #[test]
+    fn running_hooks_are_capped_and_never_overlap_themselves() {
+        let config = Config::default();
+        let mut runner = HookRunner::new(&[
+            hook("*.rs", "sleep 0.2".to_string(), 0),
+            hook("*.rs", "sleep 0.2".to_string(), 0),
+        ]);
+        runner.max_running = 1;
+        let updates = prepared(
+            &config,
+            vec![IndexUpdate::Delete {
+                path: "/tmp/vicaya-hook-test/lib.rs".to_string(),
+            }],
+        );
+
+        runner.observe(&config, &updates);
+        runner.run_due(Instant::now());
+        assert!(runner.hooks[0].running.is_some());
+        assert!(runner.hooks[1].running.is_none(), "capped at one");
+
+        // A change while the first hook runs waits for its next run.
+        runner.observe(&config, &updates);
+        runner.run_due(Instant::now());
+        assert_eq!(runner.hooks[0].pending.len(), 1);
+
+        wait_for_hooks(&mut runner);
+        assert!(runner.hooks.iter().all(|hook| hook.pending.is_empty()));
+    }
+
+    #[test]
+    fn excluded_removals_do_not_trigger_hooks() {
+        let config = Config {
+            exclusions: vec!["target".to_string()],
+            ..Config::default()
+        };
+        let mut runner = HookRunner::new(&[hook("*.rs", "true".to_string(), 0)]);
+        runner.observe(
+            &config,
+            &prepared(
+                &config,
+                vec![IndexUpdate::Delete {
+                    path: "/tmp/app/target/debug/build/out/gen.rs".to_string(),
+                }],
+            ),
+        );
+        assert!(runner.hooks[0].pending.is_empty());
+    }
+}
@@ -0,0 +1,447 @@
+//! Optional localhost HTTP endpoint (`[http]` config section).
+//!
+//! A deliberately small HTTP/1.1 server: one `GET` per connection, answered
+//! with the same JSON the IPC socket returns, produced by the same handler.
+//! It binds to 127.0.0.1 only, requires a bearer token, and rejects requests
+//! whose `Host` is not a loopback name so web pages cannot reach it through
+//! DNS rebinding.
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, info};
+use vicaya_core::ipc::{
+    DirectoryRank, Request, Response, SearchFilters, SearchFlags, SearchOptions, SearchSort,
+};
+use vicaya_core::Result;
+
+use crate::ipc_server::IpcHandler;
+
+/// Largest request head (request line + headers) accepted.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const MAX_HEADERS: usize = 32;
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 1000;
+
+pub struct HttpServer {
+    listener: TcpListener,
+    handler: IpcHandler,
+    token: Arc<str>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl HttpServer {
+    /// Bind `127.0.0.1:port`.
+    pub(crate) fn bind(
+        port: u16,
+        token: String,
+        handler: IpcHandler,
+        shutdown: Arc<AtomicBool>,
+    ) -> Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|e| {
+            vicaya_core::Error::Ipc(format!("Failed to bind HTTP port {}: {}", port, e))
+        })?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| vicaya_core::Error::Ipc(format!("Failed to set nonblocking: {}", e)))?;
+        if let Ok(addr) = listener.local_addr() {
+            info!("HTTP endpoint listening on http://{}", addr);
+        }
+
+        Ok(Self {
+            listener,
+            handler,
+            token: token.into(),
+            shutdown,
+        })
+    }
+
+    #[cfg(test)]
+    fn local_addr(&self) -> SocketAddr {
+        self.listener.local_addr().unwrap()
+    }
+
+    /// Run the accept loop until shutdown.
+    pub fn run(&self) {
+        while !self.shutdown.load(Ordering::Relaxed) {
+            match self.listener.accept() {
+                Ok((stream, addr)) => {
+                    let handler = self.handler.clone();
+                    let token = Arc::clone(&self.token);
+                    std::thread::spawn(move || handle_connection(stream, addr, &handler, &token));
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    std::thread::sleep(Duration::from_millis(5));
+                }
+                Err(e) => {
+                    error!("Failed to accept HTTP connection: {}", e);
+                    std::thread::sleep(Duration::from_millis(100));
+                }
+            }
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, addr: SocketAddr, handler: &IpcHandler, token: &str) {
+    debug!("HTTP client connected: {}", addr);
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+
+    let (status, body) = match read_head(&mut stream) {
+        Ok(head) => match route(&head, token) {
+            Ok(request) => {
+                let response = handler.handle_request(request);
+                let status = if matches!(response, Response::Error { .. }) {
+                    500
+                } else {
+                    200
+                };
+                (status, response)
+            }
+            Err((status, message)) => (status, Response::Error { message }),
+        },
+        Err(message) => (400, Response::Error { message }),
+    };
+
+    let body = response_json(&body);
+    let reply = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason_phrase(status),
+        body.len()
+    );
+    if let Err(e) = stream
+        .write_all(reply.as_bytes())
+        .and_then(|()| stream.write_all(body.as_bytes()))
+    {
+        debug!("Failed to write HTTP response: {}", e);
+    }
+}
+
+/// Read up to the blank line that ends the request head.
+fn read_head(stream: &mut TcpStream) -> std::result::Result<Vec<u8>, String> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_HEAD_BYTES {
+            return Err("request head too large".to_string());
+        }
+        let read = stream
+            .read(&mut buf)
+            .map_err(|e| format!("failed to read request: {}", e))?;
+        if read == 0 {
+            return Err("connection closed before request head".to_string());
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    Ok(head)
+}
+
+/// Authenticate and translate an HTTP request head into an IPC request.
+fn route(head: &[u8], token: &str) -> std::result::Result<Request, (u16, String)> {
+    let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+    let mut parsed = httparse::Request::new(&mut headers);
+    match parsed.parse(head) {
+        Ok(httparse::Status::Complete(_)) => {}
+        Ok(httparse::Status::Partial) => return Err((400, "incomplete request".to_string())),
+        Err(e) => return Err((400, format!("malformed request: {}", e))),
+    }
+
+    let header = |name: &str| {
+        parsed
+            .headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case(name))
+            .and_then(|header| std::str::from_utf8(header.value).ok())
+    };
+    if !header("host").is_some_and(is_loopback_host) {
+        return Err((403, "Host must be localhost".to_string()));
+    }
+    if parsed.method != Some("GET") {
+        return Err((405, "only GET is supported".to_string()));
+    }
+
+    let target = parsed.path.unwrap_or("/");
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let params: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    let param = |name: &str| {
+        params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    let presented = header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| param("token"));
+    if !presented.is_some_and(|presented| tokens_match(presented.trim(), token)) {
+        return Err((401, "missing or invalid token".to_string()));
+    }
+
+    let limit = || match param("limit") {
+        Some(raw) => raw
+            .parse::<usize>()
+            .map(|limit| limit.min(MAX_LIMIT))
+            .map_err(|_| (400, format!("invalid limit `{}`", raw))),
+        None => Ok(DEFAULT_LIMIT),
+    };
+
+    match path {
+        "/search" => {
+            let limit = limit()?;
+            let non_empty = |name: &str| param(name).filter(|v| !v.is_empty()).map(str::to_string);
+            let flag = |name: &str| match param(name) {
+                Some("1" | "true") => Some(true),
+                Some("0" | "false") => Some(false),
+                _ => None,
+            };
+            let offset = match param("offset") {
+                Some(raw) => raw
+                    .parse::<usize>()
+                    .map_err(|_| (400, format!("invalid offset `{}`", raw)))?,
+                None => 0,
+            };
+            let sort = match param("sort") {
+                Some(raw) => SearchSort::parse(raw).map_err(|e| (400, e.to_string()))?,
+                None => SearchSort::default(),
+            };
+            let directories = match param("directories") {
+                Some(raw) => DirectoryRank::parse(raw).map_err(|e| (400, e.to_string()))?,
+                None => DirectoryRank::default(),
+            };
+            let max_depth = match param("max_depth") {
+                Some(raw) => Some(
+                    raw.parse::<usize>()
+                        .map_err(|_| (400, format!("invalid max_depth `{}`", raw)))?,
+                ),
+                None => None,
+            };
+            let dir_size = match param("dir_size") {
+                Some(raw) => Some(
+                    vicaya_core::query::parse_size_expr(raw)
+                        .ok_or_else(|| (400, format!("invalid dir_size `{}`", raw)))?,
+                ),
+                None => None,
+            };
+            Ok(Request::Search(SearchOptions {
+                term: param("q").unwrap_or_default().to_string(),
+                limit,
+                offset,
+                scope: non_empty("scope"),
+                filters: SearchFilters {
+                    scope: non_empty("filter_scope"),
+                    attributes: vicaya_core::permissions::AttributeFilter {
+                        owner: non_empty("owner"),
+                        readonly: flag("readonly"),
+                        executable: flag("executable"),
+                        content_hash: non_empty("hash"),
+                    },
+                    max_depth,
+                    hidden: flag("hidden"),
+                    dir_size,
+                },
+                sort,
+                fuzzy: flag("fuzzy").unwrap_or(true),
+                flags: SearchFlags {
+                    recent_if_empty: matches!(param("recent"), Some("1" | "true")),
+                    explain: matches!(param("explain"), Some("1" | "true")),
+                    no_broaden: flag("broaden") == Some(false),
+                },
+                scorer: non_empty("scorer"),
+                directories,
+                ..SearchOptions::default()
+            }))
+        }
+        "/suggest" => Ok(Request::Suggest {
+            prefix: param("prefix").unwrap_or_default().to_string(),
+            limit: limit()?,
+        }),
+        "/status" => Ok(Request::Status),
+        _ => Err((404, format!("unknown endpoint `{}`", path))),
+    }
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    matches!(
+        name.to_ascii_lowercase().as_str(),
+        "localhost" | "127.0.0.1" | "::1"
+    )
+}
+
+/// Compare tokens without short-circuiting on the first differing byte.
+fn tokens_match(presented: &str, expected: &str) -> bool {
+    presented.len() == expected.len()
+        && presented
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn response_json(response: &Response) -> String {
+    response.to_json().unwrap_or_else(|e| {
+        format!(
+            r#"{{"type":"error","message":"failed to serialize response: {}"}}"#,
+            e
+        )
+    })
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc_server::{DaemonState, IpcServer};
+    use std::sync::{Mutex, RwLock};
+    use tempfile::tempdir;
+
+    fn head(target: &str, extra: &str) -> Vec<u8> {
+        format!("GET {target} HTTP/1.1\r\nHost: 127.0.0.1:8347\r\n{extra}\r\n").into_bytes()
+    }
+
+    #[test]
+    fn route_maps_search_params_onto_the_ipc_request() {
+        let request = route(
+            &head(
+                "/search?q=main%20rs&limit=5000&offset=20&sort=mtime&fuzzy=0&filter_scope=%2Ftmp%2Frepo&recent=1&executable=1&max_depth=2&hidden=0&dir_size=%3E%3D1mb",
+                "Authorization: Bearer s3cret\r\n",
+            ),
+            "s3cret",
+        )
+        .unwrap();
+
+        match request {
+            Request::Search(options) => {
+                assert_eq!(options.term, "main rs");
+                assert_eq!(options.limit, MAX_LIMIT);
+                assert_eq!(options.offset, 20);
+                assert_eq!(options.sort, SearchSort::Mtime);
+                assert!(!options.fuzzy);
+                assert_eq!(options.scope, None);
+                assert_eq!(options.filters.scope.as_deref(), Some("/tmp/repo"));
+                assert_eq!(options.filters.max_depth, Some(2));
+                assert_eq!(options.filters.hidden, Some(false));
+                assert_eq!(
+                    options.filters.dir_size,
+                    Some(vicaya_core::query::CmpU64 {
+                        op: vicaya_core::query::CmpOp::Gte,
+                        value: 1024 * 1024,
+                    })
+                );
+                assert!(options.flags.recent_if_empty);
+                let attributes = options.filters.attributes;
+                assert_eq!(attributes.executable, Some(true));
+                assert_eq!((attributes.owner, attributes.readonly), (None, None));
+            }
+            other => panic!("unexpected request: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn route_rejects_bad_tokens_foreign_hosts_and_unknown_paths() {
+        let status = |head: Vec<u8>| route(&head, "s3cret").map(|_| 200).unwrap_or_else(|e| e.0);
+
+        assert_eq!(status(head("/status", "")), 401);
+        assert_eq!(
+            status(head("/status", "Authorization: Bearer wrong!\r\n")),
+            401
+        );
+        assert_eq!(status(head("/status?token=s3cret", "")), 200);
+        assert_eq!(status(head("/nope?token=s3cret", "")), 404);
+        assert!(matches!(
+            route(&head("/suggest?token=s3cret&prefix=Carg&limit=3", ""), "s3cret"),
+            Ok(Request::Suggest { prefix, limit: 3 }) if prefix == "Carg"
+        ));
+        assert_eq!(status(head("/suggest?token=s3cret&limit=x", "")), 400);
+        assert_eq!(
+            status(
+                b"GET /status?token=s3cret HTTP/1.1\r\nHost: evil.example:8347\r\n\r\n".to_vec()
+            ),
+            403
+        );
+        assert_eq!(
+            status(b"POST /status?token=s3cret HTTP/1.1\r\nHost: [::1]:8347\r\n\r\n".to_vec()),
+            405
+        );
+    }
+
+    #[test]
+    fn serves_status_over_http() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("hello.txt"), "").unwrap();
+
+        let config = vicaya_core::Config {
+            index_roots: vec![root.path().to_path_buf()],
+            exclusions: Vec::new(),
+            index_path: vicaya_dir.path().join("index"),
+            ..vicaya_core::Config::default()
+        };
+        std::fs::create_dir_all(&config.index_path).unwrap();
+        let snapshot = vicaya_scanner::Scanner::new(config.clone()).scan().unwrap();
+        let state = Arc::new(RwLock::new(DaemonState::new(
+            config.clone(),
+            config.index_path.join("index.bin"),
+            config.index_path.join("index.journal"),
+            snapshot,
+        )));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let ipc = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            state,
+            Arc::clone(&shutdown),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+
+        let server = HttpServer::bind(
+            0,
+            "s3cret".to_string(),
+            ipc.handler(),
+            Arc::clone(&shutdown),
+        )
+        .unwrap();
+        let addr = server.local_addr();
+        let serve = std::thread::spawn(move || server.run());
+
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "GET /status HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer s3cret\r\n\r\n"
+        )
+        .unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+
+        shutdown.store(true, Ordering::Relaxed);
+        serve.join().unwrap();
+
+        let (head, body) = reply.split_once("\r\n\r\n").unwrap();
+        assert!(head.starts_with("HTTP/1.1 200 OK"), "{head}");
+        match Response::from_json(body).unwrap() {
+            Response::Status(status) => assert!(status.indexed_files > 0),
+            other => panic!("unexpected response: {other:?}"),
+        }
+    }
+}
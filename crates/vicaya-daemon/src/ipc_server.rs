@@ -1,23 +1,54 @@
 //! IPC server for daemon communication.
 
 use std::collections::hash_map::RandomState;
+use std::collections::VecDeque;
 use std::hash::BuildHasher;
-use std::io::{BufReader, Write};
+use std::io::BufReader;
+use std::num::NonZeroU64;
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering};
 use std::sync::Mutex;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 use tracing::{debug, error, info, warn};
-use vicaya_core::ipc::{Request, Response};
-use vicaya_core::smriti::SmritiStore;
+use vicaya_core::accounting::{Subsystem, ThreadUsage, UsageCounters};
+use vicaya_core::config::{IndexProfile, JournalFsync, ResolvedScan, ScanProfile};
+use vicaya_core::ipc::{
+    BatchResults, DaemonStatus, DeletedEntry, DirSize, DirUsageEntry, DirectoryRank, Framing,
+    IndexCap, MatchStrategy, PathCompletion, Request, Response, RootStats, ScoreExplanation,
+    SearchFilters, SearchFlags, SearchOptions, SearchSort, MAX_SEARCH_BATCH,
+};
+use vicaya_core::permissions::AttributeMatcher;
+use vicaya_core::query::CmpU64;
+use vicaya_core::smriti::{SmritiAction, SmritiStore};
 use vicaya_core::{Config, Result};
-use vicaya_index::{FileId, FileMeta, Query, QueryEngine};
+use vicaya_index::scorer::RELEVANCE_SCORER;
+use vicaya_index::{
+    DirId, DirSizes, DirTotals, FileId, FileMeta, Query, QueryBudget, QueryEngine, ScorerRegistry,
+};
 use vicaya_scanner::{IndexSnapshot, Scanner};
 use vicaya_watcher::IndexUpdate;
 
+use crate::audit::PendingAudit;
+use crate::canonical_path::PathCanonicalizer;
+use crate::recent_scopes::RecentScopes;
+use crate::subscriptions::{ChangeFeed, ChangedPaths};
+use crate::suggest::NameTrie;
+
 pub type SharedState = Arc<RwLock<DaemonState>>;
 const RECENT_UPDATE_LIMIT: usize = 4096;
+/// How long an entry removed by a watcher delete waits for a create of the
+/// same inode, which is then read as the other half of a rename.
+const RENAME_PAIRING_WINDOW: std::time::Duration = std::time::Duration::from_secs(5);
+/// Deleted entries held for rename pairing at once.
+const RENAME_PAIRING_LIMIT: usize = 1024;
+/// Matches ranked for a path completion before frecency reorders them.
+const COMPLETION_CANDIDATES: usize = 500;
+/// Matches searched for indexed names when Smriti has too few suggestions.
+const SUGGEST_CANDIDATES: usize = 200;
+/// How long a `Subscribe` connection waits for a request before forwarding
+/// watcher batches.
+const SUBSCRIPTION_POLL: std::time::Duration = std::time::Duration::from_millis(100);
 
 /// Shared daemon state.
 pub struct DaemonState {
@@ -35,13 +66,217 @@ pub struct DaemonState {
     pub recent_updates: Vec<FileId>,
     pub smriti_file: PathBuf,
     pub smriti: SmritiStore,
+    /// File names in `smriti`, by prefix, for `Request::Suggest`.
+    pub(crate) name_trie: NameTrie,
+    /// Scopes clients searched in this session, for `Request::ScopeUsed`.
+    pub(crate) recent_scopes: RecentScopes,
+    /// Recursive size and file count of each directory, kept in step with
+    /// every entry change.
+    pub(crate) dir_sizes: DirSizes,
     pub inode_to_id: std::collections::HashMap<(u64, u64), FileId>,
     pub last_updated: i64,
+    /// When each effective index root was last scanned in full (Unix
+    /// timestamp). Reconciles scan roots one after another; full rebuilds
+    /// give them all one time.
+    pub(crate) root_scans: std::collections::BTreeMap<PathBuf, i64>,
     pub reconciling: bool,
+    pub activity: Arc<DaemonActivity>,
+    /// Entries seen moving to the Trash, newest first, capped at
+    /// `[trash] recently_deleted`.
+    pub recently_deleted: VecDeque<DeletedEntry>,
+    /// What `max_indexed_files` kept out of the index, from the scan that
+    /// built it and watcher updates since.
+    pub index_cap: Option<IndexCap>,
+    /// Rewrites watcher and journal paths to the spelling used in the index.
+    pub(crate) canonical_paths: PathCanonicalizer,
+    /// Set while `apply_prepared_batch` runs.
+    batching: bool,
+    /// Entries watcher deletes removed within [`RENAME_PAIRING_WINDOW`],
+    /// oldest first.
+    vanished: VecDeque<VanishedEntry>,
+    /// Set when a rename moved Smriti entries that are not saved yet.
+    smriti_renamed: bool,
     #[cfg(test)]
     retirement_probe: Option<Arc<std::sync::atomic::AtomicUsize>>,
 }
 
+/// Queue depths reported through `Status` and resource usage reported through
+/// `Metrics`, shared across state swaps so the watcher and reconcile threads
+/// can update them without the state lock.
+#[derive(Debug)]
+pub struct DaemonActivity {
+    pending_watcher_events: AtomicUsize,
+    journal_entries: AtomicUsize,
+    reconcile_scanned: AtomicUsize,
+    reconcile_expected: AtomicUsize,
+    /// Settings of the running reconcile's scan.
+    reconcile_scan: Mutex<Option<ResolvedScan>>,
+    warmup_ms: OnceLock<u64>,
+    journal_fsync: OnceLock<JournalFsync>,
+    /// Set by `Request::Reconcile` until the reconcile thread picks it up.
+    reconcile_requested: AtomicBool,
+    /// When the last full reconcile finished (Unix timestamp, 0 if none yet).
+    pub(crate) last_reconcile: AtomicI64,
+    /// When the next scheduled reconcile is due (Unix timestamp, 0 if none).
+    next_reconcile: AtomicI64,
+    /// CPU time and bytes read per subsystem.
+    pub usage: UsageCounters,
+    /// Applied watcher batches, for `Subscribe` connections.
+    pub(crate) changes: ChangeFeed,
+    /// Serializes writes of the Smriti store.
+    pub(crate) smriti_persist_lock: Arc<Mutex<()>>,
+    started: std::time::Instant,
+}
+
+/// An entry a watcher delete removed, held briefly in case a create of the
+/// same inode shows it was renamed.
+struct VanishedEntry {
+    inode: (u64, u64),
+    path: String,
+    is_dir: bool,
+    mtime: i64,
+    at: std::time::Instant,
+}
+
+impl Default for DaemonActivity {
+    fn default() -> Self {
+        Self {
+            pending_watcher_events: AtomicUsize::new(0),
+            journal_entries: AtomicUsize::new(0),
+            reconcile_scanned: AtomicUsize::new(0),
+            reconcile_expected: AtomicUsize::new(0),
+            reconcile_scan: Mutex::new(None),
+            warmup_ms: OnceLock::new(),
+            journal_fsync: OnceLock::new(),
+            reconcile_requested: AtomicBool::new(false),
+            last_reconcile: AtomicI64::new(0),
+            next_reconcile: AtomicI64::new(0),
+            usage: UsageCounters::default(),
+            changes: ChangeFeed::default(),
+            smriti_persist_lock: Arc::new(Mutex::new(())),
+            started: std::time::Instant::now(),
+        }
+    }
+}
+
+impl DaemonActivity {
+    pub fn watcher_events_queued(&self, count: usize) {
+        self.pending_watcher_events
+            .fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn watcher_events_applied(&self, count: usize) {
+        let _ = self.pending_watcher_events.fetch_update(
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+            |pending| Some(pending.saturating_sub(count)),
+        );
+    }
+
+    pub fn pending_watcher_events(&self) -> usize {
+        self.pending_watcher_events.load(Ordering::Relaxed)
+    }
+
+    pub fn journal_appended(&self, count: usize) {
+        self.journal_entries.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn set_journal_entries(&self, count: usize) {
+        self.journal_entries.store(count, Ordering::Relaxed);
+    }
+
+    pub fn journal_entries(&self) -> usize {
+        self.journal_entries.load(Ordering::Relaxed)
+    }
+
+    pub fn warmup_finished(&self, elapsed_ms: u64) {
+        let _ = self.warmup_ms.set(elapsed_ms);
+    }
+
+    pub fn warmup_ms(&self) -> Option<u64> {
+        self.warmup_ms.get().copied()
+    }
+
+    pub fn set_journal_fsync(&self, policy: JournalFsync) {
+        let _ = self.journal_fsync.set(policy);
+    }
+
+    pub fn journal_fsync(&self) -> Option<JournalFsync> {
+        self.journal_fsync.get().copied()
+    }
+
+    /// Ask the reconcile thread to run a reconcile as soon as it can.
+    pub fn request_reconcile(&self) {
+        self.reconcile_requested.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether a reconcile was requested since the last call.
+    pub fn take_reconcile_request(&self) -> bool {
+        self.reconcile_requested.swap(false, Ordering::Relaxed)
+    }
+
+    pub fn last_reconcile(&self) -> Option<i64> {
+        Some(self.last_reconcile.load(Ordering::Relaxed)).filter(|&at| at > 0)
+    }
+
+    pub fn set_next_reconcile(&self, at: Option<i64>) {
+        self.next_reconcile
+            .store(at.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn next_reconcile(&self) -> Option<i64> {
+        Some(self.next_reconcile.load(Ordering::Relaxed)).filter(|&at| at > 0)
+    }
+
+    pub(crate) fn begin_reconcile(&self, expected_entries: usize, scan: ResolvedScan) {
+        self.reconcile_scanned.store(0, Ordering::Relaxed);
+        self.reconcile_expected
+            .store(expected_entries, Ordering::Relaxed);
+        *self.reconcile_scan.lock().unwrap() = Some(scan);
+    }
+
+    pub(crate) fn end_reconcile(&self) {
+        self.reconcile_scanned.store(0, Ordering::Relaxed);
+        self.reconcile_expected.store(0, Ordering::Relaxed);
+        *self.reconcile_scan.lock().unwrap() = None;
+    }
+
+    /// Profile, threads and throttle of the running reconcile's scan.
+    pub fn reconcile_scan(&self) -> Option<ResolvedScan> {
+        *self.reconcile_scan.lock().unwrap()
+    }
+
+    pub(crate) fn reconcile_scanned(&self, scanned: usize) {
+        self.reconcile_scanned.store(scanned, Ordering::Relaxed);
+    }
+
+    pub fn uptime_secs(&self) -> u64 {
+        self.started.elapsed().as_secs()
+    }
+
+    /// Estimated reconcile progress, using the previous index size as the
+    /// expected scan total. Capped at 99% until the new state is swapped in.
+    pub fn reconcile_progress(&self) -> Option<u8> {
+        let expected = self.reconcile_expected.load(Ordering::Relaxed);
+        if expected == 0 {
+            return None;
+        }
+        let scanned = self.reconcile_scanned.load(Ordering::Relaxed);
+        Some((scanned.saturating_mul(100) / expected).min(99) as u8)
+    }
+}
+
+/// One page of search results.
+struct SearchPage {
+    results: Vec<vicaya_core::ipc::SearchResult>,
+    /// Whether the query budget ran out before the full ranking.
+    truncated: bool,
+    /// Near-miss names, when the search found nothing.
+    suggestions: Vec<String>,
+    /// Matches outside the scope, from [`IndexState::search_broadening`].
+    outside_scope: Vec<vicaya_core::ipc::SearchResult>,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) enum PreparedIndexUpdate {
     CreateOrModify {
@@ -54,6 +289,32 @@ pub(crate) enum PreparedIndexUpdate {
         from: PathBuf,
         file: Option<PreparedFileMeta>,
     },
+    /// An entry landed in the (unindexed) Trash at `to`. `from` is its old
+    /// path when the watcher reported both ends; otherwise the entry is found
+    /// by `to`'s inode.
+    Trash {
+        from: Option<PathBuf>,
+        to: PathBuf,
+        inode: Option<(u64, u64)>,
+    },
+}
+
+impl PreparedIndexUpdate {
+    /// Paths this update touches, each flagged with whether it still exists
+    /// and passed the exclusion checks during preparation.
+    pub(crate) fn touched_paths(&self) -> impl Iterator<Item = (&str, bool)> {
+        let (removed, present) = match self {
+            Self::CreateOrModify { file } => (None, file.as_ref()),
+            Self::Delete { path } => (Some(path), None),
+            Self::Move { from, file } => (Some(from), file.as_ref()),
+            Self::Trash { from, .. } => (from.as_ref(), None),
+        };
+        removed
+            .and_then(|path| path.to_str())
+            .map(|path| (path, false))
+            .into_iter()
+            .chain(present.map(|file| (file.path.as_str(), true)))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -64,34 +325,65 @@ pub(crate) struct PreparedFileMeta {
     mtime: i64,
     dev: u64,
     ino: u64,
+    uid: u32,
+    gid: u32,
+    mode: u32,
+    indexed_at: i64,
+    content_hash: Option<NonZeroU64>,
+    is_dir: bool,
 }
 
-pub(crate) fn prepare_index_update(config: &Config, update: IndexUpdate) -> PreparedIndexUpdate {
+pub(crate) fn prepare_index_update(
+    config: &Config,
+    canonical_paths: &PathCanonicalizer,
+    update: IndexUpdate,
+) -> PreparedIndexUpdate {
+    let canonical = |path: String| PathBuf::from(canonical_paths.canonicalize_owned(path));
+    let trashed = |path: &Path| !config.trash.index && vicaya_core::trash::is_in_trash(path);
     match update {
         IndexUpdate::Create { path } | IndexUpdate::Modify { path } => {
-            let path = PathBuf::from(path);
+            let path = canonical(path);
+            if trashed(&path) {
+                return prepare_trash(None, path);
+            }
             PreparedIndexUpdate::CreateOrModify {
                 file: prepare_file_meta(config, &path),
             }
         }
         IndexUpdate::Delete { path } => PreparedIndexUpdate::Delete {
-            path: PathBuf::from(path),
+            path: canonical(path),
         },
         IndexUpdate::Move { from, to } => {
-            let to = PathBuf::from(to);
+            let to = canonical(to);
+            if trashed(&to) {
+                return prepare_trash(Some(canonical(from)), to);
+            }
             PreparedIndexUpdate::Move {
-                from: PathBuf::from(from),
+                from: canonical(from),
                 file: prepare_file_meta(config, &to),
             }
         }
     }
 }
 
+fn prepare_trash(from: Option<PathBuf>, to: PathBuf) -> PreparedIndexUpdate {
+    #[cfg(unix)]
+    use std::os::unix::fs::MetadataExt;
+
+    let inode = std::fs::symlink_metadata(&to)
+        .ok()
+        .map(|metadata| (metadata.dev(), metadata.ino()));
+    PreparedIndexUpdate::Trash { from, to, inode }
+}
+
 fn prepare_file_meta(config: &Config, path: &Path) -> Option<PreparedFileMeta> {
     let metadata = std::fs::metadata(path).ok()?;
     if !(metadata.is_file() || metadata.is_dir()) {
         return None;
     }
+    if metadata.is_dir() && !config.index_profile.indexes_directories() {
+        return None;
+    }
 
     if !vicaya_scanner::should_index_path(config, path, metadata.is_dir()) {
         return None;
@@ -105,18 +397,27 @@ fn prepare_file_meta(config: &Config, path: &Path) -> Option<PreparedFileMeta> {
         return None;
     }
 
+    let identity = config.index_profile.tracks_file_identity();
     Some(PreparedFileMeta {
         path: path.to_string_lossy().to_string(),
         name,
-        size: metadata.len(),
+        size: if identity { metadata.len() } else { 0 },
         mtime: metadata
             .modified()
             .ok()
             .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0),
-        dev: metadata.dev(),
-        ino: metadata.ino(),
+        dev: if identity { metadata.dev() } else { 0 },
+        ino: if identity { metadata.ino() } else { 0 },
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+        mode: metadata.mode(),
+        indexed_at: now_epoch_seconds(),
+        content_hash: config
+            .content_hash
+            .compute(path, metadata.len(), metadata.is_dir()),
+        is_dir: metadata.is_dir(),
     })
 }
 
@@ -127,11 +428,6 @@ impl DaemonState {
         journal_file: PathBuf,
         snapshot: IndexSnapshot,
     ) -> Self {
-        let path_hasher = RandomState::new();
-        let (path_to_id, path_hash_collisions) = build_path_map(&snapshot, &path_hasher);
-        let path_order = build_path_order(&snapshot);
-        let name_to_ids = build_name_map(&snapshot);
-        let recent_order = build_recent_order(&snapshot);
         let smriti_file = smriti_file_for_index(&index_file);
         let smriti = if config.smriti_enabled() {
             match SmritiStore::load(&smriti_file) {
@@ -148,7 +444,48 @@ impl DaemonState {
         } else {
             SmritiStore::default()
         };
+        Self::with_smriti(config, index_file, journal_file, snapshot, smriti)
+    }
+
+    /// A state for a rebuild to swap in. It starts without Smriti memory:
+    /// `replace_state` moves the live store over, so loading a second copy
+    /// from disk would only add to the rebuild's peak.
+    fn rebuilt(
+        config: Config,
+        index_file: PathBuf,
+        journal_file: PathBuf,
+        snapshot: IndexSnapshot,
+    ) -> Self {
+        Self::with_smriti(
+            config,
+            index_file,
+            journal_file,
+            snapshot,
+            SmritiStore::default(),
+        )
+    }
+
+    fn with_smriti(
+        config: Config,
+        index_file: PathBuf,
+        journal_file: PathBuf,
+        mut snapshot: IndexSnapshot,
+        smriti: SmritiStore,
+    ) -> Self {
+        let index_cap = snapshot.cap.take();
+        let path_hasher = RandomState::new();
+        let (path_to_id, path_hash_collisions) = build_path_map(&snapshot, &path_hasher);
+        let path_order = build_path_order(&snapshot);
+        let name_to_ids = build_name_map(&snapshot);
+        let recent_order = build_recent_order(&snapshot);
+        let smriti_file = smriti_file_for_index(&index_file);
+        let name_trie = NameTrie::from_smriti(&smriti);
         let inode_to_id = build_inode_map(&snapshot);
+        snapshot
+            .file_table
+            .ensure_dir_lookup(&snapshot.string_arena);
+        let dir_sizes = DirSizes::build(&snapshot.file_table);
+        let canonical_paths = PathCanonicalizer::new(&config.effective_roots());
         let last_updated = index_file
             .metadata()
             .and_then(|m| m.modified())
@@ -156,6 +493,12 @@ impl DaemonState {
             .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
             .map(|d| d.as_secs() as i64)
             .unwrap_or(0);
+        let root_scans = config
+            .effective_roots()
+            .into_iter()
+            .filter(|_| last_updated > 0)
+            .map(|root| (root, last_updated))
+            .collect();
 
         Self {
             config,
@@ -172,16 +515,39 @@ impl DaemonState {
             recent_updates: Vec::new(),
             smriti_file,
             smriti,
+            name_trie,
+            recent_scopes: RecentScopes::default(),
+            dir_sizes,
             inode_to_id,
             last_updated,
+            root_scans,
             reconciling: false,
+            activity: Arc::new(DaemonActivity::default()),
+            recently_deleted: VecDeque::new(),
+            index_cap,
+            canonical_paths,
+            batching: false,
+            vanished: VecDeque::new(),
+            smriti_renamed: false,
             #[cfg(test)]
             retirement_probe: None,
         }
     }
 
+    /// Fault in the hot parts of the index; see [`QueryEngine::warm_up`].
+    pub fn warm_up(&self, top_trigrams: usize) -> usize {
+        QueryEngine::new(
+            &self.snapshot.file_table,
+            &self.snapshot.string_arena,
+            &self.snapshot.trigram_index,
+        )
+        .with_path_index(self.snapshot.path_index.as_ref())
+        .warm_up(top_trigrams)
+    }
+
+    #[cfg(test)]
     pub fn apply_update(&mut self, update: IndexUpdate) {
-        let update = prepare_index_update(&self.config, update);
+        let update = prepare_index_update(&self.config, &self.canonical_paths, update);
         self.apply_prepared_update(update);
     }
 
@@ -193,15 +559,574 @@ impl DaemonState {
                 }
             }
             PreparedIndexUpdate::Delete { path } => {
-                self.remove_path(&path);
+                self.delete_path(&path);
             }
             PreparedIndexUpdate::Move { from, file } => {
                 self.move_prepared(&from, file);
             }
+            PreparedIndexUpdate::Trash { from, to, inode } => {
+                self.trash_entry(from.as_deref(), &to, inode);
+            }
+        }
+    }
+
+    /// Apply a batch of updates, deferring recency bookkeeping to the end so
+    /// large replays don't rescan `recent_updates` once per entry.
+    pub(crate) fn apply_prepared_batch(&mut self, updates: Vec<PreparedIndexUpdate>) {
+        self.batching = true;
+        for update in updates {
+            self.apply_prepared_update(update);
+        }
+        self.batching = false;
+
+        // Keep the last touch of each live file, newest last.
+        let mut seen = std::collections::HashSet::new();
+        let snapshot = &self.snapshot;
+        let mut recent: Vec<FileId> = self
+            .recent_updates
+            .iter()
+            .rev()
+            .copied()
+            .filter(|&id| {
+                snapshot
+                    .file_table
+                    .get(id)
+                    .is_some_and(|meta| !meta.is_tombstone())
+                    && seen.insert(id)
+            })
+            .take(RECENT_UPDATE_LIMIT)
+            .collect();
+        recent.reverse();
+        self.recent_updates = recent;
+    }
+
+    /// Run one search against the live index: rank, apply Smriti boosts,
+    /// sort, and cut out the requested page. Unknown scorer names fall back
+    /// to relevance; callers reject them first with [`unknown_scorer`].
+    fn search(&self, options: SearchOptions, scorers: &ScorerRegistry) -> SearchPage {
+        self.search_among(options, scorers, None)
+    }
+
+    /// [`Self::search`], then, when a scoped search found nothing, the same
+    /// search over every root for [`SearchPage::outside_scope`].
+    fn search_broadening(&self, options: SearchOptions, scorers: &ScorerRegistry) -> SearchPage {
+        let broadened = self.broadened(&options);
+        let scope = options.filters.scope.clone().unwrap_or_default();
+        let mut page = self.search(options, scorers);
+        if let Some(options) = broadened.filter(|_| page.results.is_empty()) {
+            page.outside_scope = self.search(options, scorers).results;
+            // Matches under the scope were cut by its depth limit, not outside it.
+            page.outside_scope
+                .retain(|result| !Path::new(&result.path).starts_with(&scope));
+            if !page.outside_scope.is_empty() {
+                page.suggestions.clear();
+            }
+        }
+        page
+    }
+
+    /// The unscoped retry of a first-page scoped search, unless
+    /// `[ranking] auto_broaden` or the request turns it off. The depth limit
+    /// counts from the scope, so it goes too; the scope stays as a ranking
+    /// boost so matches near it come first, and matches under it are dropped
+    /// by [`Self::search_broadening`].
+    fn broadened(&self, options: &SearchOptions) -> Option<SearchOptions> {
+        let scope = options
+            .filters
+            .scope
+            .as_deref()
+            .filter(|scope| !scope.trim().is_empty())?;
+        if !self.config.ranking.auto_broaden
+            || options.flags.no_broaden
+            || options.offset > 0
+            || options.term.trim().is_empty()
+        {
+            return None;
+        }
+        let mut broadened = options.clone();
+        broadened.scope.get_or_insert_with(|| scope.to_string());
+        broadened.filters.scope = None;
+        broadened.filters.max_depth = None;
+        Some(broadened)
+    }
+
+    /// [`Self::search`] limited to `candidates` when given, as subscriptions
+    /// rank just the entries a watcher batch touched. Candidates skip the
+    /// exact-name shortcut and near-miss suggestions.
+    fn search_among(
+        &self,
+        options: SearchOptions,
+        scorers: &ScorerRegistry,
+        candidates: Option<Vec<FileId>>,
+    ) -> SearchPage {
+        let SearchOptions {
+            term: query,
+            limit: page_limit,
+            offset,
+            scope,
+            filters,
+            sort,
+            fuzzy,
+            flags,
+            scorer,
+            directories,
+            ..
+        } = options;
+        let candidates = match filters.dir_size {
+            Some(bound) => Some(self.directories_sized(bound, candidates)),
+            None => candidates,
+        };
+        let scorer = scorers.get(scorer.as_deref().unwrap_or(RELEVANCE_SCORER));
+        // Smriti boosts re-rank by score, which only fits the relevance order.
+        let relevance = scorer
+            .as_ref()
+            .is_none_or(|scorer| scorer.name() == RELEVANCE_SCORER);
+        // Rank enough results to cover the page, then cut it out below.
+        let limit = vicaya_index::query::ranked_window(offset, page_limit, sort);
+        let filter_scope = filters.scope;
+        let attributes = filters.attributes.matcher();
+        let hide_hidden = !filters.hidden.unwrap_or(self.config.show_hidden);
+        // Unscoped depth limits and hidden checks look below the index roots.
+        let roots = if filters.max_depth.is_some() || hide_hidden {
+            self.config.effective_roots()
+        } else {
+            Vec::new()
+        };
+        let engine = QueryEngine::new(
+            &self.snapshot.file_table,
+            &self.snapshot.string_arena,
+            &self.snapshot.trigram_index,
+        )
+        .with_path_index(self.snapshot.path_index.as_ref())
+        .with_roots(&roots);
+
+        let scope_path = scope
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| vicaya_core::paths::expand_user_path(Path::new(&s)));
+        let filter_scope_path = filter_scope
+            .filter(|s| !s.trim().is_empty())
+            .map(|s| vicaya_core::paths::expand_user_path(Path::new(&s)));
+        let placement = (filters.max_depth.is_some() || hide_hidden).then(|| Placement {
+            max_depth: filters.max_depth,
+            hide_hidden,
+            scope: filter_scope_path.as_deref(),
+            roots: &roots,
+        });
+        const SCOPED_LINEAR_SEARCH_LIMIT: usize = 100_000;
+        let restricted = candidates.is_some();
+        let scoped_file_ids = match candidates {
+            Some(ids) => {
+                let mut ids = match filter_scope_path.as_deref() {
+                    Some(scope) => self
+                        .filter_file_ids_in_scope(&ids, scope)
+                        .unwrap_or_default(),
+                    None => ids,
+                };
+                self.retain_matching_attributes(&mut ids, &attributes);
+                self.retain_placed(&mut ids, placement.as_ref());
+                Some((ids, true))
+            }
+            None => filter_scope_path
+                .as_deref()
+                .and_then(|scope| self.scoped_file_ids_up_to(scope, SCOPED_LINEAR_SEARCH_LIMIT))
+                .map(|(mut ids, complete)| {
+                    self.retain_matching_attributes(&mut ids, &attributes);
+                    self.retain_placed(&mut ids, placement.as_ref());
+                    (ids, complete)
+                }),
+        };
+        let exact_name_file_ids = if restricted {
+            None
+        } else {
+            self.exact_name_file_ids(&query)
+        };
+        let exact_name_file_ids = exact_name_file_ids.map(|ids| {
+            let mut ids = if let Some(scope) = filter_scope_path.as_deref() {
+                self.filter_file_ids_in_scope(&ids, scope)
+                    .unwrap_or_default()
+            } else {
+                ids
+            };
+            self.retain_matching_attributes(&mut ids, &attributes);
+            self.retain_placed(&mut ids, placement.as_ref());
+            ids
+        });
+        let trimmed_query_is_empty = query.trim().is_empty();
+        // Global searches also favor scopes used this session.
+        let recent_scopes = filter_scope_path.is_none()
+            && self.config.ranking.recent_scope_boost > 0.0
+            && !self.recent_scopes.is_empty();
+        let search_limit = if relevance
+            && (recent_scopes || self.config.smriti_enabled() && !self.smriti.entries.is_empty())
+            && !trimmed_query_is_empty
+        {
+            limit
+                .saturating_mul(4)
+                .max(limit)
+                .min(limit.saturating_add(300))
+        } else {
+            limit
+        };
+
+        // If query is empty and recent_if_empty is true, return recent files
+        let mut truncated = false;
+        let mut suggestions = Vec::new();
+        let mut results = if trimmed_query_is_empty && flags.recent_if_empty {
+            if let Some((file_ids, true)) = scoped_file_ids.as_ref() {
+                engine.recent_file_ids(limit, file_ids)
+            } else {
+                let file_ids = self
+                    .recent_file_ids(
+                        limit,
+                        filter_scope_path.as_deref(),
+                        &attributes,
+                        placement.as_ref(),
+                    )
+                    .unwrap_or_default();
+                engine.recent_file_ids(limit, &file_ids)
+            }
+        } else if let Some(file_ids) = exact_name_file_ids.as_deref() {
+            engine.exact_name_file_ids(search_limit, file_ids)
+        } else {
+            let query_obj = Query {
+                term: query,
+                limit: search_limit,
+                scope: scope_path,
+                filter_scope: filter_scope_path,
+                budget: query_budget(&self.config),
+                attributes,
+                fuzzy,
+                explain: flags.explain,
+                scorer,
+                directory_bias: directories.bias(self.config.ranking.directory_weight),
+                // Other orders list every match, as `vicaya find` relies on.
+                min_score: if sort == SearchSort::Relevance {
+                    self.config.ranking.min_score
+                } else {
+                    0.0
+                },
+                max_depth: filters.max_depth,
+                hide_hidden,
+            };
+            let outcome = if let Some((file_ids, true)) = scoped_file_ids.as_ref() {
+                engine.search_file_ids_outcome(&query_obj, file_ids)
+            } else {
+                engine.search_outcome(&query_obj)
+            };
+            truncated = outcome.truncated;
+            if outcome.results.is_empty() && offset == 0 && !trimmed_query_is_empty && !restricted {
+                suggestions = engine.suggest(&query_obj, self.config.ranking.suggestions);
+            }
+            outcome.results
+        };
+        if flags.explain {
+            // The recent-files and exact-name paths skip ranking.
+            let strategy = if trimmed_query_is_empty {
+                MatchStrategy::Recent
+            } else {
+                MatchStrategy::ExactName
+            };
+            for result in results.iter_mut().filter(|r| r.explain.is_none()) {
+                result.explain = Some(ScoreExplanation {
+                    strategy,
+                    base_score: result.score,
+                    path_depth: Path::new(&result.path).components().count(),
+                    ..ScoreExplanation::default()
+                });
+            }
+        }
+        if relevance && !trimmed_query_is_empty {
+            apply_usage_boosts(self, &mut results, limit, recent_scopes);
+        }
+        // Size sorts and the du view rank directories by what they hold.
+        for result in results.iter_mut().filter(|r| r.is_dir) {
+            result.total_size = Some(self.dir_totals(&result.path).bytes);
+        }
+        vicaya_index::query::sort_results(&mut results, sort);
+
+        let ipc_results = results
+            .into_iter()
+            .skip(offset)
+            .take(page_limit)
+            .map(|r| vicaya_core::ipc::SearchResult {
+                path: r.path,
+                name: r.name,
+                score: r.score,
+                size: r.size,
+                mtime: r.mtime,
+                uid: r.uid,
+                gid: r.gid,
+                mode: r.mode,
+                indexed_at: r.indexed_at,
+                content_hash: r.content_hash.map(vicaya_core::content_hash::to_hex),
+                is_dir: r.is_dir,
+                total_size: r.total_size,
+                explain: r.explain,
+            })
+            .collect();
+
+        SearchPage {
+            results: ipc_results,
+            truncated,
+            suggestions,
+            outside_scope: Vec::new(),
+        }
+    }
+
+    /// Answer a batch of searches in parallel. With `total_limit`, each query
+    /// ranks at most that many results, then results are kept in query order
+    /// until the total is spent; queries cut short are flagged truncated.
+    fn search_batch(
+        &self,
+        mut queries: Vec<SearchOptions>,
+        total_limit: Option<usize>,
+        scorers: &ScorerRegistry,
+    ) -> Vec<BatchResults> {
+        use rayon::prelude::*;
+
+        if let Some(total) = total_limit {
+            for options in &mut queries {
+                options.limit = options.limit.min(total);
+            }
+        }
+        let mut batches: Vec<BatchResults> = queries
+            .into_par_iter()
+            .map(|options| {
+                let page = self.search(options, scorers);
+                BatchResults {
+                    results: page.results,
+                    truncated: page.truncated,
+                }
+            })
+            .collect();
+
+        if let Some(mut remaining) = total_limit {
+            for batch in &mut batches {
+                if batch.results.len() > remaining {
+                    batch.results.truncate(remaining);
+                    batch.truncated = true;
+                }
+                remaining -= batch.results.len();
+            }
+        }
+        batches
+    }
+
+    /// Completions for `prefix`, an absolute path whose last component may
+    /// be partial: indexed entries below its directory whose name matches
+    /// that component. The most frecent come first, a directory counting the
+    /// Smriti use of everything inside it, then shallower entries, names
+    /// starting with the component, and better matches.
+    fn complete_path(
+        &self,
+        prefix: &str,
+        limit: usize,
+        dirs_only: bool,
+        scorers: &ScorerRegistry,
+    ) -> Vec<PathCompletion> {
+        let (dir, partial) = match prefix.rsplit_once('/') {
+            Some(("", partial)) => ("/", partial),
+            Some(split) => split,
+            None => return Vec::new(),
+        };
+        let dir_path = Path::new(dir);
+        let page = self.search(
+            SearchOptions {
+                filters: SearchFilters {
+                    scope: Some(dir.to_string()),
+                    ..SearchFilters::default()
+                },
+                flags: SearchFlags {
+                    recent_if_empty: true,
+                    ..SearchFlags::default()
+                },
+                directories: if dirs_only {
+                    DirectoryRank::Prefer
+                } else {
+                    DirectoryRank::Neutral
+                },
+                ..SearchOptions::new(partial, COMPLETION_CANDIDATES)
+            },
+            scorers,
+        );
+        let mut candidates: Vec<(String, bool, f32)> = page
+            .results
+            .into_iter()
+            .map(|result| (result.path, result.is_dir, result.score))
+            .collect();
+
+        let now = now_epoch_seconds();
+        let mut frecency: std::collections::HashMap<String, f32> = Default::default();
+        if self.config.smriti_enabled() {
+            for entry in self.smriti.list(None, usize::MAX, Some(dir_path), now) {
+                let score = self.smriti.boost_for_path(&entry.path, now, 1.0);
+                for ancestor in Path::new(&entry.path)
+                    .ancestors()
+                    .take_while(|ancestor| *ancestor != dir_path)
+                {
+                    let best = frecency
+                        .entry(ancestor.to_string_lossy().into_owned())
+                        .or_default();
+                    *best = best.max(score);
+                }
+            }
+        }
+        // Frecent paths matching the partial name may rank below the cut.
+        let partial = partial.to_lowercase();
+        let name_of = |path: &str| {
+            Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        };
+        for path in frecency.keys() {
+            if !name_of(path).contains(&partial) {
+                continue;
+            }
+            let Some(meta) = self
+                .get_file_id_for_path(path)
+                .and_then(|id| self.snapshot.file_table.get(id))
+            else {
+                continue;
+            };
+            candidates.push((path.clone(), meta.is_dir, 0.0));
+        }
+
+        // The search also matches the partial against parent directories.
+        let mut seen = std::collections::HashSet::new();
+        candidates.retain(|(path, is_dir, _)| {
+            (*is_dir || !dirs_only)
+                && path != dir
+                && name_of(path).contains(&partial)
+                && seen.insert(path.clone())
+        });
+        let key = |path: &str| {
+            let depth = Path::new(path)
+                .strip_prefix(dir_path)
+                .map_or(usize::MAX, |rel| rel.components().count());
+            let frecency = frecency.get(path).copied().unwrap_or(0.0);
+            (frecency, depth, name_of(path).starts_with(&partial))
+        };
+        candidates.sort_by(|a, b| {
+            let (a_frecency, a_depth, a_prefix) = key(&a.0);
+            let (b_frecency, b_depth, b_prefix) = key(&b.0);
+            b_frecency
+                .total_cmp(&a_frecency)
+                .then(a_depth.cmp(&b_depth))
+                .then(b_prefix.cmp(&a_prefix))
+                .then(b.2.total_cmp(&a.2))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        candidates
+            .into_iter()
+            .take(limit)
+            .map(|(path, is_dir, _)| PathCompletion { path, is_dir })
+            .collect()
+    }
+
+    /// File names starting with `prefix`, ignoring case: used names from
+    /// the [`NameTrie`] that are still indexed, most used first, then
+    /// indexed names from a prefix search, those that the most entries share
+    /// first.
+    fn suggest(&self, prefix: &str, limit: usize, scorers: &ScorerRegistry) -> Vec<String> {
+        let prefix = prefix.trim().to_lowercase();
+        let mut seen = std::collections::HashSet::new();
+        let mut names: Vec<String> = self
+            .name_trie
+            .complete(&prefix)
+            .into_iter()
+            .filter(|used| {
+                let key = used.name.to_lowercase();
+                self.name_to_ids.contains_key(&key) && seen.insert(key)
+            })
+            .take(limit)
+            .map(|used| used.name.clone())
+            .collect();
+        if names.len() >= limit || prefix.is_empty() {
+            return names;
+        }
+
+        let page = self.search(
+            SearchOptions {
+                fuzzy: false,
+                ..SearchOptions::new(&prefix, SUGGEST_CANDIDATES)
+            },
+            scorers,
+        );
+        let mut indexed: Vec<(String, usize)> = Vec::new();
+        for result in page.results {
+            let key = result.name.to_lowercase();
+            if !key.starts_with(&prefix) || !seen.insert(key.clone()) {
+                continue;
+            }
+            let shared = self.name_to_ids.get(&key).map_or(1, Vec::len);
+            indexed.push((result.name, shared));
+        }
+        indexed.sort_by(|a, b| {
+            b.1.cmp(&a.1)
+                .then(a.0.len().cmp(&b.0.len()))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        names.extend(
+            indexed
+                .into_iter()
+                .take(limit - names.len())
+                .map(|(name, _)| name),
+        );
+        names
+    }
+
+    /// Remembered entries that may lead to a directory `keywords` jump to,
+    /// zoxide-style: the keywords appear in the path in order, the last one
+    /// in the directory's own name. Only path strings are compared here; the
+    /// index says which entries are directories, and [`best_jump_dir`] asks
+    /// the filesystem about the rest once the state lock is released.
+    fn jump_candidates(&self, keywords: &[String]) -> Vec<JumpCandidate> {
+        if !self.config.smriti_enabled() {
+            return Vec::new();
         }
+        let now = now_epoch_seconds();
+        let last = keywords.last().map(String::as_str);
+        self.smriti
+            .list(last, usize::MAX, None, now)
+            .into_iter()
+            .filter(|entry| {
+                let path = Path::new(&entry.path);
+                jump_keywords_match(&entry.path, keywords)
+                    || path.parent().is_some_and(|parent| {
+                        jump_keywords_match(&parent.to_string_lossy(), keywords)
+                    })
+            })
+            .map(|entry| JumpCandidate {
+                score: self.smriti.boost_for_path(&entry.path, now, 1.0),
+                last_used: entry.last_used,
+                is_dir: self
+                    .get_file_id_for_path(&entry.path)
+                    .and_then(|file_id| self.snapshot.file_table.get(file_id))
+                    .map(|meta| meta.is_dir),
+                path: entry.path,
+            })
+            .collect()
+    }
+
+    /// The best indexed directory `keywords` jump to, for when no remembered
+    /// one matches.
+    fn indexed_jump_dir(&self, keywords: &[String], scorers: &ScorerRegistry) -> Option<String> {
+        let last = keywords.last()?;
+        let page = self.search(
+            SearchOptions {
+                directories: DirectoryRank::Prefer,
+                ..SearchOptions::new(last, COMPLETION_CANDIDATES)
+            },
+            scorers,
+        );
+        page.results
+            .into_iter()
+            .find(|result| result.is_dir && jump_keywords_match(&result.path, keywords))
+            .map(|result| result.path)
     }
 
-    fn indexed_file_count(&self) -> usize {
+    pub(crate) fn indexed_file_count(&self) -> usize {
         self.path_to_id.len()
             + self
                 .path_hash_collisions
@@ -210,10 +1135,70 @@ impl DaemonState {
                 .sum::<usize>()
     }
 
+    /// What each effective index root contributes, for `Request::RootStats`.
+    /// Entries are matched to roots through their parent directory, looked
+    /// up once per directory.
+    pub(crate) fn root_stats(&self) -> Vec<RootStats> {
+        let roots = self.config.effective_roots();
+        let mut stats: Vec<RootStats> = roots
+            .iter()
+            .map(|root| RootStats {
+                root: root.to_string_lossy().to_string(),
+                last_scan: self.root_scans.get(root).copied(),
+                ..RootStats::default()
+            })
+            .collect();
+        let root_of = |path: &str| {
+            roots
+                .iter()
+                .position(|root| Path::new(path).starts_with(root))
+        };
+
+        let table = &self.snapshot.file_table;
+        let arena = &self.snapshot.string_arena;
+        let mut dir_roots: std::collections::HashMap<DirId, Option<usize>> =
+            std::collections::HashMap::new();
+        let mut path = String::new();
+        for (_, meta) in table.iter() {
+            if meta.is_tombstone() {
+                continue;
+            }
+            let mut root = meta.parent.and_then(|dir| {
+                *dir_roots.entry(dir).or_insert_with(|| {
+                    path.clear();
+                    table
+                        .write_dir_path(dir, arena, &mut path)
+                        .then(|| root_of(&path))
+                        .flatten()
+                })
+            });
+            // A root's own entry lives in a directory outside every root.
+            if root.is_none() {
+                path.clear();
+                if table.write_path(meta, arena, &mut path) {
+                    root = root_of(&path);
+                }
+            }
+            let Some(stats) = root.and_then(|idx| stats.get_mut(idx)) else {
+                continue;
+            };
+            stats.files += 1;
+            stats.dirs += usize::from(meta.is_dir);
+            stats.total_bytes += meta.size;
+            stats.memory_bytes += (std::mem::size_of::<FileMeta>() + meta.name_len) as u64;
+        }
+        stats
+    }
+
     fn estimated_index_allocated_bytes(&self) -> u64 {
         (self.snapshot.file_table.allocated_bytes()
             + self.snapshot.string_arena.allocated_bytes()
-            + self.snapshot.trigram_index.allocated_bytes()) as u64
+            + self.snapshot.trigram_index.allocated_bytes()
+            + self
+                .snapshot
+                .path_index
+                .as_ref()
+                .map_or(0, |path_index| path_index.allocated_bytes())) as u64
     }
 
     fn estimated_state_allocated_bytes(&self) -> u64 {
@@ -228,6 +1213,7 @@ impl DaemonState {
             + hash_map_allocated_bytes(&self.path_hash_collisions)
             + collisions_vec_bytes
             + self.path_order.capacity() * std::mem::size_of::<FileId>()
+            + self.dir_sizes.allocated_bytes()
             + hash_map_allocated_bytes(&self.name_to_ids)
             + self
                 .name_to_ids
@@ -236,6 +1222,7 @@ impl DaemonState {
                 .sum::<usize>()
             + self.recent_order.capacity() * std::mem::size_of::<FileId>()
             + self.recent_updates.capacity() * std::mem::size_of::<FileId>()
+            + self.name_trie.allocated_bytes()
             + hash_map_allocated_bytes(&self.inode_to_id)) as u64
     }
 
@@ -247,17 +1234,12 @@ impl DaemonState {
         let Some(meta) = self.snapshot.file_table.get(file_id) else {
             return false;
         };
-        let Some(existing_path) = self
-            .snapshot
-            .string_arena
-            .get(meta.path_offset, meta.path_len)
-        else {
-            return false;
-        };
-        existing_path == path
+        self.snapshot
+            .file_table
+            .path_eq(meta, &self.snapshot.string_arena, path)
     }
 
-    fn get_file_id_for_path(&self, path: &str) -> Option<FileId> {
+    pub(crate) fn get_file_id_for_path(&self, path: &str) -> Option<FileId> {
         let hash = self.path_hash(path);
 
         if let Some(ids) = self.path_hash_collisions.get(&hash) {
@@ -298,29 +1280,116 @@ impl DaemonState {
         self.path_order_dirty = true;
     }
 
-    fn scoped_file_ids_up_to(&self, scope: &Path, max_ids: usize) -> Option<(Vec<FileId>, bool)> {
-        if self.path_order_dirty {
-            return None;
+    /// Keep the directory-path index, when enabled, in step with `file_id`
+    /// moving from directory `from` to `to` (`None` when not indexed).
+    fn move_in_path_index(&mut self, file_id: FileId, from: Option<DirId>, to: Option<DirId>) {
+        let snapshot = &mut self.snapshot;
+        let Some(path_index) = snapshot.path_index.as_mut() else {
+            return;
+        };
+        if from == to {
+            return;
         }
+        path_index.remove(file_id, from, &snapshot.file_table, &snapshot.string_arena);
+        path_index.add(file_id, to, &snapshot.file_table, &snapshot.string_arena);
+    }
 
-        let (scope, scope_child_prefix) = normalized_scope_parts(scope)?;
-        let start = self.path_order.partition_point(|&id| {
-            snapshot_path_for_id(&self.snapshot, id).is_some_and(|path| path < scope.as_str())
-        });
+    /// Add `file_id` to the totals of its directories; pairs with
+    /// [`Self::uncount_dir_size`] around every change to an entry.
+    fn count_dir_size(&mut self, file_id: FileId) {
+        if let Some(meta) = self.snapshot.file_table.get(file_id) {
+            self.dir_sizes.add(&self.snapshot.file_table, meta);
+        }
+    }
 
-        let mut ids = Vec::new();
-        for &file_id in &self.path_order[start..] {
-            let Some(path) = snapshot_path_for_id(&self.snapshot, file_id) else {
-                continue;
+    /// Take `file_id` out of the totals of its directories.
+    fn uncount_dir_size(&mut self, file_id: FileId) {
+        if let Some(meta) = self.snapshot.file_table.get(file_id) {
+            self.dir_sizes.remove(&self.snapshot.file_table, meta);
+        }
+    }
+
+    /// Recursive totals of the indexed directory at `path`; zero when it is
+    /// not indexed or holds no files.
+    fn dir_totals(&self, path: &str) -> DirTotals {
+        self.snapshot
+            .file_table
+            .find_dir(path, &self.snapshot.string_arena)
+            .map(|dir| self.dir_sizes.of_dir(dir))
+            .unwrap_or_default()
+    }
+
+    /// Live directory entries, among `candidates` when given, whose
+    /// recursive size passes `bound`.
+    fn directories_sized(&self, bound: CmpU64, candidates: Option<Vec<FileId>>) -> Vec<FileId> {
+        let file_table = &self.snapshot.file_table;
+        let passes = |meta: &FileMeta| {
+            meta.is_dir
+                && !meta.is_tombstone()
+                && bound.op.matches_u64(
+                    self.dir_sizes
+                        .of_entry(file_table, &self.snapshot.string_arena, meta)
+                        .bytes,
+                    bound.value,
+                )
+        };
+        match candidates {
+            Some(ids) => ids
+                .into_iter()
+                .filter(|&file_id| file_table.get(file_id).is_some_and(passes))
+                .collect(),
+            None => file_table
+                .iter()
+                .filter(|(_, meta)| passes(meta))
+                .map(|(file_id, _)| file_id)
+                .collect(),
+        }
+    }
+
+    /// Totals of the indexed directory `path` and its `limit` largest
+    /// subdirectories; `None` when nothing below `path` is indexed.
+    fn dir_usage(&self, path: &str, limit: usize) -> Option<(DirTotals, Vec<(String, DirTotals)>)> {
+        let file_table = &self.snapshot.file_table;
+        let arena = &self.snapshot.string_arena;
+        let dir = file_table.find_dir(path, arena)?;
+        let mut entries: Vec<(String, DirTotals)> = file_table
+            .iter()
+            .filter(|(_, meta)| meta.parent == Some(dir) && meta.is_dir && !meta.is_tombstone())
+            .filter_map(|(_, meta)| {
+                let path = file_table.path_of(meta, arena)?;
+                Some((path, self.dir_sizes.of_entry(file_table, arena, meta)))
+            })
+            .collect();
+        entries.sort_by(|(a_path, a), (b_path, b)| {
+            b.bytes.cmp(&a.bytes).then_with(|| a_path.cmp(b_path))
+        });
+        entries.truncate(limit);
+        Some((self.dir_sizes.of_dir(dir), entries))
+    }
+
+    fn scoped_file_ids_up_to(&self, scope: &Path, max_ids: usize) -> Option<(Vec<FileId>, bool)> {
+        if self.path_order_dirty {
+            return None;
+        }
+
+        let (scope, scope_child_prefix) = normalized_scope_parts(scope)?;
+        let start = self.path_order.partition_point(|&id| {
+            snapshot_path_for_id(&self.snapshot, id).is_some_and(|path| path < scope)
+        });
+
+        let mut ids = Vec::new();
+        for &file_id in &self.path_order[start..] {
+            let Some(path) = snapshot_path_for_id(&self.snapshot, file_id) else {
+                continue;
             };
-            if path_is_in_normalized_scope(path, &scope, &scope_child_prefix) {
+            if path_is_in_normalized_scope(&path, &scope, &scope_child_prefix) {
                 ids.push(file_id);
                 if ids.len() > max_ids {
                     return Some((ids, false));
                 }
                 continue;
             }
-            if path > scope.as_str() && !path.starts_with(&scope_child_prefix) {
+            if path > scope && !path.starts_with(&scope_child_prefix) {
                 break;
             }
         }
@@ -328,7 +1397,13 @@ impl DaemonState {
         Some((ids, true))
     }
 
-    fn recent_file_ids(&self, limit: usize, scope: Option<&Path>) -> Option<Vec<FileId>> {
+    fn recent_file_ids(
+        &self,
+        limit: usize,
+        scope: Option<&Path>,
+        attributes: &AttributeMatcher,
+        placement: Option<&Placement>,
+    ) -> Option<Vec<FileId>> {
         let scope = scope.and_then(normalized_scope_parts);
         let mut seen = std::collections::HashSet::with_capacity(limit.saturating_mul(2));
         let mut ids = Vec::with_capacity(limit);
@@ -349,17 +1424,20 @@ impl DaemonState {
             let Some(meta) = self.snapshot.file_table.get(file_id) else {
                 continue;
             };
-            if meta.path_len == 0 || meta.name_len == 0 {
+            if meta.name_len == 0 || !attributes.matches(meta.uid, meta.mode, meta.content_hash) {
                 continue;
             }
             let Some(path) = snapshot_path_for_id(&self.snapshot, file_id) else {
                 continue;
             };
             if let Some((scope, scope_child_prefix)) = scope.as_ref() {
-                if !path_is_in_normalized_scope(path, scope, scope_child_prefix) {
+                if !path_is_in_normalized_scope(&path, scope, scope_child_prefix) {
                     continue;
                 }
             }
+            if placement.is_some_and(|placement| !placement.allows(&path)) {
+                continue;
+            }
 
             ids.push(file_id);
         }
@@ -367,6 +1445,34 @@ impl DaemonState {
         Some(ids)
     }
 
+    /// Keep only the IDs whose owner, mode, and fingerprint pass `attributes`.
+    fn retain_matching_attributes(
+        &self,
+        file_ids: &mut Vec<FileId>,
+        attributes: &AttributeMatcher,
+    ) {
+        if attributes.is_empty() {
+            return;
+        }
+        file_ids.retain(|&file_id| {
+            self.snapshot
+                .file_table
+                .get(file_id)
+                .is_some_and(|meta| attributes.matches(meta.uid, meta.mode, meta.content_hash))
+        });
+    }
+
+    /// Keep only the IDs `placement` allows, when the search has one.
+    fn retain_placed(&self, file_ids: &mut Vec<FileId>, placement: Option<&Placement>) {
+        let Some(placement) = placement else {
+            return;
+        };
+        file_ids.retain(|&file_id| {
+            snapshot_path_for_id(&self.snapshot, file_id)
+                .is_some_and(|path| placement.allows(&path))
+        });
+    }
+
     fn filter_file_ids_in_scope(&self, file_ids: &[FileId], scope: &Path) -> Option<Vec<FileId>> {
         let (scope, scope_child_prefix) = normalized_scope_parts(scope)?;
         Some(
@@ -375,7 +1481,7 @@ impl DaemonState {
                 .copied()
                 .filter(|&file_id| {
                     snapshot_path_for_id(&self.snapshot, file_id).is_some_and(|path| {
-                        path_is_in_normalized_scope(path, &scope, &scope_child_prefix)
+                        path_is_in_normalized_scope(&path, &scope, &scope_child_prefix)
                     })
                 })
                 .collect(),
@@ -425,7 +1531,12 @@ impl DaemonState {
         let Some(meta) = self.snapshot.file_table.get(file_id) else {
             return;
         };
-        if meta.path_len == 0 || meta.name_len == 0 {
+        if meta.name_len == 0 {
+            return;
+        }
+        if self.batching {
+            // Deduplicated and trimmed once by `apply_prepared_batch`.
+            self.recent_updates.push(file_id);
             return;
         }
         self.recent_updates.retain(|&id| id != file_id);
@@ -437,6 +1548,9 @@ impl DaemonState {
     }
 
     fn remove_recent_update(&mut self, file_id: FileId) {
+        if self.batching {
+            return;
+        }
         self.recent_updates.retain(|&id| id != file_id);
     }
 
@@ -446,15 +1560,11 @@ impl DaemonState {
 
         if let Some(ids) = self.path_hash_collisions.get_mut(&hash) {
             let pos = ids.iter().position(|&file_id| {
-                let Some(meta) = snapshot.file_table.get(file_id) else {
-                    return false;
-                };
-                let Some(existing_path) =
-                    snapshot.string_arena.get(meta.path_offset, meta.path_len)
-                else {
-                    return false;
-                };
-                existing_path == path
+                snapshot.file_table.get(file_id).is_some_and(|meta| {
+                    snapshot
+                        .file_table
+                        .path_eq(meta, &snapshot.string_arena, path)
+                })
             })?;
             let file_id = ids.remove(pos);
 
@@ -505,6 +1615,7 @@ impl DaemonState {
                 self.remove_name_mapping(file_id, &old_name);
             }
 
+            self.uncount_dir_size(file_id);
             let Some(meta) = self.snapshot.file_table.get_mut(file_id) else {
                 return;
             };
@@ -513,7 +1624,7 @@ impl DaemonState {
                 if self.inode_to_id.get(&old_inode_key) == Some(&file_id) {
                     self.inode_to_id.remove(&old_inode_key);
                 }
-                self.inode_to_id.insert(inode_key, file_id);
+                map_inode(&mut self.inode_to_id, inode_key, file_id);
             }
 
             if old_name != name_str {
@@ -529,25 +1640,37 @@ impl DaemonState {
             meta.mtime = file.mtime;
             meta.dev = file.dev;
             meta.ino = file.ino;
+            meta.uid = file.uid;
+            meta.gid = file.gid;
+            meta.mode = file.mode;
+            meta.indexed_at = file.indexed_at;
+            meta.content_hash = file.content_hash;
+            meta.is_dir = file.is_dir;
+            self.count_dir_size(file_id);
 
             if old_name != name_str {
                 self.insert_name_mapping(file_id);
             }
             self.mark_recent_update(file_id);
-        } else if let Some(&file_id) = self.inode_to_id.get(&inode_key) {
+        } else if let Some(file_id) = self
+            .inode_to_id
+            .get(&inode_key)
+            .copied()
+            .filter(|&file_id| !self.still_linked(file_id, inode_key))
+        {
             // Same inode (dev+ino) already exists in the index under a different path; treat this
-            // as a move/rename even if the watcher didn't report the old path.
-            let (old_path, old_name) = {
+            // as a move/rename even if the watcher didn't report the old path. A hard link leaves
+            // the old path in place and is indexed as a new entry below.
+            let (old_path, old_name, old_parent) = {
                 let Some(meta) = self.snapshot.file_table.get(file_id) else {
                     return;
                 };
 
                 let old_path = self
                     .snapshot
-                    .string_arena
-                    .get(meta.path_offset, meta.path_len)
-                    .unwrap_or("")
-                    .to_string();
+                    .file_table
+                    .path_of(meta, &self.snapshot.string_arena)
+                    .unwrap_or_default();
 
                 let old_name = self
                     .snapshot
@@ -556,37 +1679,46 @@ impl DaemonState {
                     .unwrap_or("")
                     .to_string();
 
-                (old_path, old_name)
+                (old_path, old_name, meta.parent)
             };
 
             if !old_path.is_empty() {
                 let _ = self.remove_path_mapping(&old_path);
+                self.follow_rename(&old_path, path_str);
             }
             if old_name != name_str {
                 self.remove_name_mapping(file_id, &old_name);
             }
 
-            let Some(meta) = self.snapshot.file_table.get_mut(file_id) else {
-                return;
-            };
-
             if old_name != name_str {
                 self.snapshot.trigram_index.remove_text(file_id, &old_name);
                 self.snapshot.trigram_index.add(file_id, name_str);
-
-                let (name_offset, name_len) = self.snapshot.string_arena.add(name_str);
-                meta.name_offset = name_offset;
-                meta.name_len = name_len;
             }
 
-            let (path_offset, path_len) = self.snapshot.string_arena.add(path_str);
-            meta.path_offset = path_offset;
-            meta.path_len = path_len;
+            let (parent, name_offset, name_len) = self
+                .snapshot
+                .file_table
+                .intern_path(path_str, &mut self.snapshot.string_arena);
+            self.uncount_dir_size(file_id);
+            let Some(meta) = self.snapshot.file_table.get_mut(file_id) else {
+                return;
+            };
+            meta.parent = parent;
+            meta.name_offset = name_offset;
+            meta.name_len = name_len;
             meta.size = file.size;
             meta.mtime = file.mtime;
             meta.dev = file.dev;
             meta.ino = file.ino;
-
+            meta.uid = file.uid;
+            meta.gid = file.gid;
+            meta.mode = file.mode;
+            meta.indexed_at = file.indexed_at;
+            meta.content_hash = file.content_hash;
+            meta.is_dir = file.is_dir;
+            self.count_dir_size(file_id);
+
+            self.move_in_path_index(file_id, old_parent, parent);
             self.insert_path_mapping(path_str, file_id);
             self.mark_path_order_dirty();
             if old_name != name_str {
@@ -594,43 +1726,319 @@ impl DaemonState {
             }
             self.mark_recent_update(file_id);
         } else {
-            let (path_offset, path_len) = self.snapshot.string_arena.add(path_str);
-            let (name_offset, name_len) = self.snapshot.string_arena.add(name_str);
+            let limit = self.config.max_indexed_files;
+            if limit > 0 && self.indexed_file_count() >= limit {
+                debug!("Index full (max_indexed_files); skipping {}", path_str);
+                self.index_cap
+                    .get_or_insert_with(|| IndexCap {
+                        max_indexed_files: limit,
+                        ..IndexCap::default()
+                    })
+                    .skipped_live += 1;
+                return;
+            }
+
+            let (parent, name_offset, name_len) = self
+                .snapshot
+                .file_table
+                .intern_path(path_str, &mut self.snapshot.string_arena);
 
             let new_meta = FileMeta {
-                path_offset,
-                path_len,
+                parent,
                 name_offset,
                 name_len,
                 size: file.size,
                 mtime: file.mtime,
                 dev: file.dev,
                 ino: file.ino,
+                uid: file.uid,
+                gid: file.gid,
+                mode: file.mode,
+                indexed_at: file.indexed_at,
+                content_hash: file.content_hash,
+                is_dir: file.is_dir,
             };
 
             let file_id = self.snapshot.file_table.insert(new_meta);
+            self.count_dir_size(file_id);
             self.snapshot.trigram_index.add(file_id, name_str);
+            self.move_in_path_index(file_id, None, parent);
             self.insert_path_mapping(path_str, file_id);
             self.mark_path_order_dirty();
             self.insert_name_mapping(file_id);
             self.mark_recent_update(file_id);
-            self.inode_to_id.insert(inode_key, file_id);
+            map_inode(&mut self.inode_to_id, inode_key, file_id);
+
+            // The delete half of a rename the watcher reported first.
+            if let Some(old_path) = self.take_vanished(&file) {
+                self.follow_rename(&old_path, path_str);
+            }
+        }
+
+        self.last_updated = now_epoch_seconds();
+    }
+
+    /// Whether the indexed path of `file_id` still holds `inode_key` on disk,
+    /// so another path with that inode is a hard link, not a rename.
+    fn still_linked(&self, file_id: FileId, inode_key: (u64, u64)) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        let Some(path) = self.snapshot.file_table.get(file_id).and_then(|meta| {
+            self.snapshot
+                .file_table
+                .path_of(meta, &self.snapshot.string_arena)
+        }) else {
+            return false;
+        };
+        std::fs::symlink_metadata(path)
+            .is_ok_and(|metadata| (metadata.dev(), metadata.ino()) == inode_key)
+    }
+
+    /// [`Self::remove_path`] for a watcher delete, holding on to the entry for
+    /// [`RENAME_PAIRING_WINDOW`] in case a create of the same inode follows.
+    fn delete_path(&mut self, path: &Path) {
+        let path_str = path.to_string_lossy();
+        let Some(file_id) = self.remove_path_mapping(path_str.as_ref()) else {
+            self.tombstone_below_path(&path_str);
+            return;
+        };
+
+        if let Some(meta) = self.snapshot.file_table.get(file_id) {
+            if (meta.dev, meta.ino) != (0, 0) {
+                let entry = VanishedEntry {
+                    inode: (meta.dev, meta.ino),
+                    path: path_str.into_owned(),
+                    is_dir: meta.is_dir,
+                    mtime: meta.mtime,
+                    at: std::time::Instant::now(),
+                };
+                self.expire_vanished();
+                if self.vanished.len() >= RENAME_PAIRING_LIMIT {
+                    self.vanished.pop_front();
+                }
+                self.vanished.push_back(entry);
+            }
+        }
+
+        self.tombstone_file(file_id);
+    }
+
+    /// The path a recent watcher delete removed for `file`'s inode, when the
+    /// entry looks the same. Renames keep a file's mtime, which tells them
+    /// apart from a new file that reused a freed inode.
+    fn take_vanished(&mut self, file: &PreparedFileMeta) -> Option<String> {
+        self.expire_vanished();
+        let pos = self.vanished.iter().rposition(|entry| {
+            entry.inode == (file.dev, file.ino)
+                && entry.is_dir == file.is_dir
+                && (entry.is_dir || entry.mtime == file.mtime)
+        })?;
+        self.vanished.remove(pos).map(|entry| entry.path)
+    }
+
+    fn expire_vanished(&mut self) {
+        while self
+            .vanished
+            .front()
+            .is_some_and(|entry| entry.at.elapsed() >= RENAME_PAIRING_WINDOW)
+        {
+            self.vanished.pop_front();
+        }
+    }
+
+    /// Carry Smriti usage from `from` (and below it) to `to` after a rename.
+    fn follow_rename(&mut self, from: &str, to: &str) {
+        if self.smriti.rename(from, to) > 0 {
+            self.name_trie = NameTrie::from_smriti(&self.smriti);
+            self.smriti_renamed = true;
+        }
+    }
+
+    /// Add exclusion patterns and tombstone every indexed entry they now
+    /// exclude. Returns the number of entries removed.
+    pub(crate) fn exclude(&mut self, patterns: &[String]) -> usize {
+        let mut added = false;
+        for pattern in patterns {
+            let pattern = vicaya_core::filter::normalize_exclusion(pattern).into_owned();
+            if !self.config.exclusions.contains(&pattern) {
+                self.config.exclusions.push(pattern);
+                added = true;
+            }
+        }
+        if !added {
+            return 0;
+        }
+
+        let exclusions = self.config.exclusion_set();
+        let excluded: Vec<String> = self
+            .snapshot
+            .file_table
+            .iter()
+            .filter(|(_, meta)| !meta.is_tombstone())
+            .filter_map(|(_, meta)| {
+                self.snapshot
+                    .file_table
+                    .path_of(meta, &self.snapshot.string_arena)
+            })
+            .filter(|path| exclusions.is_excluded(Path::new(path)))
+            .collect();
+        for path in &excluded {
+            self.remove_path(Path::new(path));
+        }
+        excluded.len()
+    }
+
+    /// Drop an entry (and, for a directory, everything below it) that was
+    /// moved to the Trash, remembering it for the recently deleted list.
+    fn trash_entry(&mut self, from: Option<&Path>, to: &Path, inode: Option<(u64, u64)>) {
+        let file_id = from
+            .and_then(|from| self.get_file_id_for_path(&from.to_string_lossy()))
+            .or_else(|| inode.and_then(|inode| self.inode_to_id.get(&inode).copied()));
+        let Some(meta) = file_id.and_then(|id| self.snapshot.file_table.get(id)) else {
+            return;
+        };
+        if meta.is_tombstone() {
+            return;
         }
+        let Some(original_path) = self
+            .snapshot
+            .file_table
+            .path_of(meta, &self.snapshot.string_arena)
+        else {
+            return;
+        };
+        let entry = DeletedEntry {
+            name: self
+                .snapshot
+                .string_arena
+                .get(meta.name_offset, meta.name_len)
+                .unwrap_or("")
+                .to_string(),
+            original_path,
+            trash_path: to.to_string_lossy().to_string(),
+            size: meta.size,
+            is_dir: meta.is_dir,
+            deleted_at: now_epoch_seconds(),
+        };
+        debug!("Moved to trash: {}", entry.original_path);
 
+        self.remove_path(Path::new(&entry.original_path));
         self.last_updated = now_epoch_seconds();
+
+        let limit = self.config.trash.recently_deleted;
+        if limit > 0 {
+            self.recently_deleted
+                .retain(|known| known.original_path != entry.original_path);
+            self.recently_deleted.push_front(entry);
+            self.recently_deleted.truncate(limit);
+        }
+    }
+
+    /// Recently deleted entries still in the Trash, newest first, whose name
+    /// or original path contains `query` (case-insensitively).
+    pub(crate) fn recently_deleted(&self, query: Option<&str>, limit: usize) -> Vec<DeletedEntry> {
+        let query = query
+            .map(str::trim)
+            .filter(|query| !query.is_empty())
+            .map(str::to_lowercase);
+        self.recently_deleted
+            .iter()
+            .filter(|entry| {
+                query.as_deref().is_none_or(|query| {
+                    entry.name.to_lowercase().contains(query)
+                        || entry.original_path.to_lowercase().contains(query)
+                })
+            })
+            .filter(|entry| std::fs::symlink_metadata(&entry.trash_path).is_ok())
+            .take(limit)
+            .cloned()
+            .collect()
     }
 
     fn remove_path(&mut self, path: &Path) {
         let path_str = path.to_string_lossy();
         let Some(file_id) = self.remove_path_mapping(path_str.as_ref()) else {
+            self.tombstone_below_path(&path_str);
             return;
         };
 
         self.tombstone_file(file_id);
     }
 
+    /// Tombstone `file_id` and, for a directory, every entry below it: a
+    /// directory removed as a whole is reported as one event.
     fn tombstone_file(&mut self, file_id: FileId) {
-        let (inode_key, old_name) = {
+        let dir = self
+            .snapshot
+            .file_table
+            .get(file_id)
+            .filter(|meta| meta.is_dir && !meta.is_tombstone())
+            .and_then(|meta| {
+                self.snapshot
+                    .file_table
+                    .dir_of_entry(meta, &self.snapshot.string_arena)
+            });
+        self.tombstone_entry(file_id);
+        if let Some(dir) = dir {
+            self.tombstone_below(dir);
+        }
+    }
+
+    /// Tombstone what is left below `path` when it has no entry of its own,
+    /// as a directory in a lite index.
+    fn tombstone_below_path(&mut self, path: &str) {
+        if let Some(dir) = self
+            .snapshot
+            .file_table
+            .find_dir(path, &self.snapshot.string_arena)
+        {
+            self.tombstone_below(dir);
+        }
+    }
+
+    /// Tombstone every live entry below the interned directory `dir`.
+    fn tombstone_below(&mut self, dir: DirId) {
+        if self.dir_sizes.of_dir(dir).entries == 0 {
+            return;
+        }
+        let file_table = &self.snapshot.file_table;
+        // A directory is interned after its parent, so one pass in ID order
+        // from `dir` marks every directory below it.
+        let first = dir.0 as usize;
+        let mut below = vec![false; file_table.dir_count().saturating_sub(first)];
+        let is_below = |below: &[bool], parent: Option<DirId>| {
+            parent.is_some_and(|parent| {
+                (parent.0 as usize)
+                    .checked_sub(first)
+                    .is_some_and(|index| below[index])
+            })
+        };
+        if let Some(slot) = below.first_mut() {
+            *slot = true;
+        }
+        for index in 1..below.len() {
+            below[index] = is_below(&below, file_table.dir_parent(DirId((first + index) as u32)));
+        }
+        let ids: Vec<FileId> = file_table
+            .iter()
+            .filter(|(_, meta)| !meta.is_tombstone() && is_below(&below, meta.parent))
+            .map(|(file_id, _)| file_id)
+            .collect();
+
+        for file_id in ids {
+            if let Some(path) = self
+                .snapshot
+                .file_table
+                .path(file_id, &self.snapshot.string_arena)
+            {
+                self.remove_path_mapping(&path);
+            }
+            self.tombstone_entry(file_id);
+        }
+    }
+
+    fn tombstone_entry(&mut self, file_id: FileId) {
+        let (inode_key, old_name, old_parent) = {
             let Some(meta) = self.snapshot.file_table.get(file_id) else {
                 return;
             };
@@ -640,7 +2048,7 @@ impl DaemonState {
                 .get(meta.name_offset, meta.name_len)
                 .unwrap_or("")
                 .to_string();
-            ((meta.dev, meta.ino), old_name)
+            ((meta.dev, meta.ino), old_name, meta.parent)
         };
 
         if inode_key != (0, 0) && self.inode_to_id.get(&inode_key) == Some(&file_id) {
@@ -650,17 +2058,16 @@ impl DaemonState {
         self.mark_path_order_dirty();
         self.remove_recent_update(file_id);
         self.snapshot.trigram_index.remove_text(file_id, &old_name);
+        self.move_in_path_index(file_id, old_parent, None);
         self.remove_name_mapping(file_id, &old_name);
+        self.uncount_dir_size(file_id);
 
         let Some(meta) = self.snapshot.file_table.get_mut(file_id) else {
             return;
         };
 
         // Tombstone the entry (keeps IDs stable).
-        meta.path_len = 0;
-        meta.name_len = 0;
-        meta.size = 0;
-        meta.mtime = 0;
+        meta.tombstone();
 
         self.last_updated = now_epoch_seconds();
     }
@@ -682,6 +2089,7 @@ impl DaemonState {
 
         let to_str = file.path.as_str();
         let name_str = file.name.as_str();
+        self.follow_rename(&from_str, to_str);
 
         if let Some(overwritten_id) = self
             .get_file_id_for_path(to_str)
@@ -692,7 +2100,7 @@ impl DaemonState {
             self.tombstone_file(overwritten_id);
         }
 
-        let (old_inode_key, old_name) = {
+        let (old_inode_key, old_name, old_parent) = {
             let Some(meta) = self.snapshot.file_table.get(file_id) else {
                 return;
             };
@@ -702,7 +2110,7 @@ impl DaemonState {
                 .get(meta.name_offset, meta.name_len)
                 .unwrap_or("")
                 .to_string();
-            ((meta.dev, meta.ino), old_name)
+            ((meta.dev, meta.ino), old_name, meta.parent)
         };
 
         if old_name != name_str {
@@ -711,33 +2119,36 @@ impl DaemonState {
             self.snapshot.trigram_index.add(file_id, name_str);
         }
 
+        let (parent, name_offset, name_len) = self
+            .snapshot
+            .file_table
+            .intern_path(to_str, &mut self.snapshot.string_arena);
+        self.uncount_dir_size(file_id);
         let Some(meta) = self.snapshot.file_table.get_mut(file_id) else {
             return;
         };
 
-        if old_name != name_str {
-            let (name_offset, name_len) = self.snapshot.string_arena.add(name_str);
-            meta.name_offset = name_offset;
-            meta.name_len = name_len;
-        }
-
-        let (path_offset, path_len) = self.snapshot.string_arena.add(to_str);
-
-        meta.path_offset = path_offset;
-        meta.path_len = path_len;
+        meta.parent = parent;
+        meta.name_offset = name_offset;
+        meta.name_len = name_len;
         meta.size = file.size;
         meta.mtime = file.mtime;
         meta.dev = file.dev;
         meta.ino = file.ino;
+        meta.uid = file.uid;
+        meta.gid = file.gid;
+        meta.mode = file.mode;
+        self.count_dir_size(file_id);
+        self.move_in_path_index(file_id, old_parent, parent);
 
         let new_inode_key = (file.dev, file.ino);
         if old_inode_key != new_inode_key {
             if self.inode_to_id.get(&old_inode_key) == Some(&file_id) {
                 self.inode_to_id.remove(&old_inode_key);
             }
-            self.inode_to_id.insert(new_inode_key, file_id);
+            map_inode(&mut self.inode_to_id, new_inode_key, file_id);
         } else {
-            self.inode_to_id.insert(new_inode_key, file_id);
+            map_inode(&mut self.inode_to_id, new_inode_key, file_id);
         }
 
         self.insert_path_mapping(to_str, file_id);
@@ -750,6 +2161,17 @@ impl DaemonState {
     }
 }
 
+/// Per-search work limits from `[performance]`; 0 disables a limit.
+fn query_budget(config: &Config) -> QueryBudget {
+    let performance = &config.performance;
+    QueryBudget {
+        max_duration: (performance.query_timeout_ms > 0)
+            .then(|| std::time::Duration::from_millis(performance.query_timeout_ms)),
+        max_candidates: (performance.query_max_candidates > 0)
+            .then_some(performance.query_max_candidates),
+    }
+}
+
 fn normalized_scope_parts(scope: &Path) -> Option<(String, String)> {
     let scope = scope.to_str()?.trim_end_matches('/');
     let scope = if scope.is_empty() {
@@ -775,13 +2197,113 @@ fn is_exact_basename_query(query: &str) -> bool {
         && !query.bytes().any(|b| matches!(b, b'/' | b'\\'))
 }
 
-fn now_epoch_seconds() -> i64 {
+/// Whether lowercase `keywords` appear in `path` in order, case-insensitively,
+/// with the last one in its final component.
+/// A Smriti entry [`DaemonState::jump_candidates`] found for a jump.
+struct JumpCandidate {
+    path: String,
+    score: f32,
+    last_used: i64,
+    /// Whether the index holds the entry as a directory; `None` when it is
+    /// not indexed.
+    is_dir: Option<bool>,
+}
+
+/// The remembered directory a jump lands on. Candidates vote for themselves
+/// when they are directories and for their parent otherwise; the most
+/// frecent wins, ties going to the most recently used. Only candidates the
+/// index does not hold are looked up on disk.
+fn best_jump_dir(candidates: Vec<JumpCandidate>, keywords: &[String]) -> Option<String> {
+    let mut remembered: std::collections::HashMap<String, (f32, i64)> = Default::default();
+    for candidate in candidates {
+        let path = Path::new(&candidate.path);
+        let dir = match candidate.is_dir {
+            Some(true) => path,
+            Some(false) => match path.parent() {
+                Some(parent) => parent,
+                None => continue,
+            },
+            None if path.is_dir() => path,
+            None => match path.parent() {
+                Some(parent) if parent.is_dir() => parent,
+                _ => continue,
+            },
+        };
+        let dir = dir.to_string_lossy().into_owned();
+        if !jump_keywords_match(&dir, keywords) {
+            continue;
+        }
+        let best = remembered
+            .entry(dir)
+            .or_insert((candidate.score, candidate.last_used));
+        *best = (best.0.max(candidate.score), best.1.max(candidate.last_used));
+    }
+    let depth = |path: &str| Path::new(path).components().count();
+    remembered
+        .into_iter()
+        .max_by(|a, b| {
+            a.1 .0
+                .total_cmp(&b.1 .0)
+                .then(a.1 .1.cmp(&b.1 .1))
+                .then(depth(&b.0).cmp(&depth(&a.0)))
+                .then_with(|| b.0.cmp(&a.0))
+        })
+        .map(|(path, _)| path)
+}
+
+fn jump_keywords_match(path: &str, keywords: &[String]) -> bool {
+    let path = path.to_lowercase();
+    let name_start = path
+        .trim_end_matches('/')
+        .rfind('/')
+        .map_or(0, |slash| slash + 1);
+    let mut cursor = 0;
+    for (i, keyword) in keywords.iter().enumerate() {
+        let rest = &path[cursor..];
+        let found = if i + 1 == keywords.len() {
+            rest.rfind(keyword.as_str())
+                .filter(|at| cursor + at >= name_start)
+        } else {
+            rest.find(keyword.as_str())
+        };
+        match found {
+            Some(at) => cursor += at + keyword.len(),
+            None => return false,
+        }
+    }
+    true
+}
+
+pub(crate) fn now_epoch_seconds() -> i64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0)
 }
 
+/// A search's `max_depth` and hidden-entry rule, with the bases they look
+/// below.
+struct Placement<'a> {
+    max_depth: Option<usize>,
+    hide_hidden: bool,
+    /// The filter scope; without one, paths are placed below `roots`.
+    scope: Option<&'a Path>,
+    roots: &'a [PathBuf],
+}
+
+impl Placement<'_> {
+    fn allows(&self, path: &str) -> bool {
+        let path = Path::new(path);
+        let base = match self.scope {
+            Some(scope) => scope,
+            None => vicaya_index::query::root_of(path, self.roots),
+        };
+        self.max_depth
+            .is_none_or(|max_depth| vicaya_index::query::depth_below(path, base) <= max_depth)
+            && !(self.hide_hidden && vicaya_index::query::hidden_below(path, base))
+    }
+}
+
 fn smriti_file_for_index(index_file: &Path) -> PathBuf {
     let Some(parent) = index_file.parent() else {
         return vicaya_core::paths::smriti_path();
@@ -795,24 +2317,84 @@ fn smriti_file_for_index(index_file: &Path) -> PathBuf {
     parent.join("smriti.json")
 }
 
-fn apply_smriti_boosts(
+/// Error message for the first search naming a scorer that is not registered.
+fn unknown_scorer<'a>(
+    scorers: &ScorerRegistry,
+    queries: impl IntoIterator<Item = &'a SearchOptions>,
+) -> Option<String> {
+    let name = queries
+        .into_iter()
+        .filter_map(|options| options.scorer.as_deref())
+        .find(|name| scorers.get(name).is_none())?;
+    Some(format!(
+        "Unknown scorer '{name}' (expected one of: {})",
+        scorers.names().join(", ")
+    ))
+}
+
+/// Error message for the first search term holding a malformed niyama
+/// (`size:>10xb`). Clients strip niyamas before sending the term, so any
+/// left over were typed into a raw request.
+fn malformed_niyama<'a>(queries: impl IntoIterator<Item = &'a SearchOptions>) -> Option<String> {
+    queries
+        .into_iter()
+        .find_map(|options| vicaya_core::query::parse_query(&options.term).error_message())
+}
+
+/// Lite indexes keep no sizes to sort by.
+fn unsupported_sort<'a>(
+    profile: IndexProfile,
+    queries: impl IntoIterator<Item = &'a SearchOptions>,
+) -> Option<String> {
+    let sorts_by_size = queries
+        .into_iter()
+        .any(|options| options.sort == SearchSort::Size);
+    (sorts_by_size && !profile.tracks_file_identity()).then(|| {
+        "Sorting by size needs index_profile = \"full\"; this daemon keeps a lite index".to_string()
+    })
+}
+
+/// Add Smriti boosts and, when `recent_scopes` is set, boosts for scopes used
+/// this session, then re-rank by the boosted score.
+fn apply_usage_boosts(
     state: &DaemonState,
     results: &mut Vec<vicaya_index::SearchResult>,
     limit: usize,
+    recent_scopes: bool,
 ) {
-    if !state.config.smriti_enabled() || results.is_empty() || state.smriti.entries.is_empty() {
+    let smriti = state.config.smriti_enabled() && !state.smriti.entries.is_empty();
+    if results.is_empty() || !smriti && !recent_scopes {
         results.truncate(limit);
         return;
     }
 
     let now = now_epoch_seconds();
     let max_boost = state.config.smriti.max_boost;
+    let ranking = &state.config.ranking;
+    let half_life_secs = ranking.recent_scope_half_life_mins.saturating_mul(60) as i64;
 
-    let boosts: Vec<f32> = results
+    let boosts: Vec<(f32, f32)> = results
         .iter()
-        .map(|result| state.smriti.boost_for_path(&result.path, now, max_boost))
+        .map(|result| {
+            let smriti_boost = if smriti {
+                state.smriti.boost_for_path(&result.path, now, max_boost)
+            } else {
+                0.0
+            };
+            let scope_boost = if recent_scopes {
+                state.recent_scopes.boost_for_path(
+                    &result.path,
+                    now,
+                    ranking.recent_scope_boost,
+                    half_life_secs,
+                )
+            } else {
+                0.0
+            };
+            (smriti_boost, scope_boost)
+        })
         .collect();
-    if boosts.iter().all(|boost| *boost <= 0.0) {
+    if boosts.iter().all(|(smriti, scope)| *smriti + *scope <= 0.0) {
         results.truncate(limit);
         return;
     }
@@ -821,7 +2403,7 @@ fn apply_smriti_boosts(
         .drain(..)
         .enumerate()
         .map(|(idx, result)| {
-            let boosted_score = result.score + boosts[idx];
+            let boosted_score = result.score + boosts[idx].0 + boosts[idx].1;
             (idx, boosted_score, result)
         })
         .collect();
@@ -835,8 +2417,11 @@ fn apply_smriti_boosts(
         ranked
             .into_iter()
             .take(limit)
-            .map(|(_, score, mut result)| {
+            .map(|(idx, score, mut result)| {
                 result.score = score.min(1.0);
+                if let Some(explain) = result.explain.as_mut() {
+                    (explain.smriti_boost, explain.recent_scope_boost) = boosts[idx];
+                }
                 result
             }),
     );
@@ -851,9 +2436,8 @@ fn hash_map_allocated_bytes<K, V>(map: &std::collections::HashMap<K, V>) -> usiz
     map.capacity() * std::mem::size_of::<(K, V)>() + map.capacity()
 }
 
-fn snapshot_path_for_id(snapshot: &IndexSnapshot, file_id: FileId) -> Option<&str> {
-    let meta = snapshot.file_table.get(file_id)?;
-    snapshot.string_arena.get(meta.path_offset, meta.path_len)
+fn snapshot_path_for_id(snapshot: &IndexSnapshot, file_id: FileId) -> Option<String> {
+    snapshot.file_table.path(file_id, &snapshot.string_arena)
 }
 
 fn build_path_map(
@@ -866,16 +2450,21 @@ fn build_path_map(
     let mut map = std::collections::HashMap::with_capacity(snapshot.file_table.len());
     let mut collisions = std::collections::HashMap::<u64, Vec<FileId>>::new();
 
+    let mut path = String::new();
     for (file_id, meta) in snapshot.file_table.iter() {
-        if meta.path_len == 0 {
+        if meta.is_tombstone() {
             continue;
         }
 
-        let Some(path) = snapshot.string_arena.get(meta.path_offset, meta.path_len) else {
+        path.clear();
+        if !snapshot
+            .file_table
+            .write_path(meta, &snapshot.string_arena, &mut path)
+        {
             continue;
-        };
+        }
 
-        let hash = hasher.hash_one(path);
+        let hash = hasher.hash_one(path.as_str());
 
         if let Some(ids) = collisions.get_mut(&hash) {
             ids.push(file_id);
@@ -887,8 +2476,12 @@ fn build_path_map(
             continue;
         };
 
-        let existing_path = snapshot_path_for_id(snapshot, existing).unwrap_or("");
-        if existing_path == path {
+        let existing_matches = snapshot.file_table.get(existing).is_some_and(|existing| {
+            snapshot
+                .file_table
+                .path_eq(existing, &snapshot.string_arena, &path)
+        });
+        if existing_matches {
             // Duplicate path (unexpected); prefer the latest ID deterministically.
             map.insert(hash, file_id);
             continue;
@@ -904,7 +2497,7 @@ fn build_path_map(
 fn build_inode_map(snapshot: &IndexSnapshot) -> std::collections::HashMap<(u64, u64), FileId> {
     let mut map = std::collections::HashMap::with_capacity(snapshot.file_table.len());
     for (file_id, meta) in snapshot.file_table.iter() {
-        if meta.path_len == 0 {
+        if meta.is_tombstone() {
             continue;
         }
         if meta.dev == 0 && meta.ino == 0 {
@@ -915,24 +2508,34 @@ fn build_inode_map(snapshot: &IndexSnapshot) -> std::collections::HashMap<(u64,
     map
 }
 
+/// Track `file_id` under its inode. Entries without one (archive members,
+/// lite indexes) are never mapped, so none looks like a rename of another.
+fn map_inode(
+    inode_to_id: &mut std::collections::HashMap<(u64, u64), FileId>,
+    inode_key: (u64, u64),
+    file_id: FileId,
+) {
+    if inode_key != (0, 0) {
+        inode_to_id.insert(inode_key, file_id);
+    }
+}
+
 fn build_path_order(snapshot: &IndexSnapshot) -> Vec<FileId> {
     let mut ids: Vec<FileId> = snapshot
         .file_table
         .iter()
-        .filter_map(|(file_id, meta)| (meta.path_len > 0).then_some(file_id))
+        .filter_map(|(file_id, meta)| (!meta.is_tombstone()).then_some(file_id))
         .collect();
-    ids.sort_unstable_by(|&a, &b| {
-        let a_path = snapshot_path_for_id(snapshot, a).unwrap_or("");
-        let b_path = snapshot_path_for_id(snapshot, b).unwrap_or("");
-        a_path.cmp(b_path).then_with(|| a.cmp(&b))
-    });
+    // Paths are rebuilt from the directory table, so build each key once;
+    // the stable sort keeps equal paths in ID order.
+    ids.sort_by_cached_key(|&id| snapshot_path_for_id(snapshot, id).unwrap_or_default());
     ids
 }
 
 fn build_name_map(snapshot: &IndexSnapshot) -> std::collections::HashMap<String, Vec<FileId>> {
     let mut map = std::collections::HashMap::<String, Vec<FileId>>::new();
     for (file_id, meta) in snapshot.file_table.iter() {
-        if meta.name_len == 0 || meta.path_len == 0 {
+        if meta.name_len == 0 {
             continue;
         }
         let Some(name) = snapshot.string_arena.get(meta.name_offset, meta.name_len) else {
@@ -947,7 +2550,7 @@ fn build_recent_order(snapshot: &IndexSnapshot) -> Vec<FileId> {
     let mut ids: Vec<FileId> = snapshot
         .file_table
         .iter()
-        .filter_map(|(file_id, meta)| (meta.path_len > 0 && meta.name_len > 0).then_some(file_id))
+        .filter_map(|(file_id, meta)| (meta.name_len > 0).then_some(file_id))
         .collect();
     ids.sort_unstable_by(|&a, &b| {
         let a_meta = snapshot.file_table.get(a);
@@ -959,57 +2562,162 @@ fn build_recent_order(snapshot: &IndexSnapshot) -> Vec<FileId> {
     ids
 }
 
+/// Append `updates` to the journal, one JSON line each. Callers hold the
+/// journal lock.
+pub(crate) fn append_journal(path: &Path, updates: &[IndexUpdate]) -> std::io::Result<()> {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    for update in updates {
+        let line = serde_json::to_string(update).unwrap_or_default();
+        if line.is_empty() {
+            continue;
+        }
+        file.write_all(line.as_bytes())?;
+        file.write_all(b"\n")?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
 fn journal_len(path: &Path) -> u64 {
     std::fs::metadata(path).map(|m| m.len()).unwrap_or(0)
 }
 
-fn apply_journal_from_offset<F>(path: &Path, offset: u64, mut apply: F) -> usize
-where
-    F: FnMut(IndexUpdate),
-{
-    use std::io::{BufRead, Seek};
-
-    let Ok(file) = std::fs::File::open(path) else {
+/// Journal entries read and applied per replay batch, so replaying a long
+/// journal holds a bounded slice of it in memory rather than all of it.
+const JOURNAL_REPLAY_CHUNK: usize = 4096;
+
+/// Read the journal entries in `[start, end)`, in order, handing them to `f`
+/// at most `chunk` at a time; `None` reads to the end of file. Lines that
+/// fail to parse are logged and skipped. Returns the number of entries read.
+///
+/// Callers take `end` from `journal_len` under the journal lock so it always
+/// lands on a line boundary.
+fn for_each_journal_chunk(
+    path: &Path,
+    start: u64,
+    end: Option<u64>,
+    chunk: usize,
+    mut f: impl FnMut(Vec<IndexUpdate>),
+) -> usize {
+    use std::io::{BufRead, Read, Seek};
+
+    let Ok(mut file) = std::fs::File::open(path) else {
         return 0;
     };
-
-    let mut reader = BufReader::new(file);
-    if offset > 0 {
-        let _ = reader.seek(std::io::SeekFrom::Start(offset));
+    if start > 0 && file.seek(std::io::SeekFrom::Start(start)).is_err() {
+        return 0;
     }
 
-    let mut applied = 0usize;
-    let mut line = String::new();
-
+    let chunk = chunk.max(1);
+    let limit = end.map_or(u64::MAX, |end| end.saturating_sub(start));
+    let mut reader = std::io::BufReader::new(file.take(limit));
+    let mut line = Vec::new();
+    let mut updates = Vec::new();
+    let mut read = 0usize;
+    let mut invalid = 0usize;
     loop {
         line.clear();
-        match reader.read_line(&mut line) {
+        match reader.read_until(b'\n', &mut line) {
             Ok(0) => break,
             Ok(_) => {}
             Err(e) => {
-                error!("Failed to read journal line: {}", e);
-                continue;
+                error!("Failed to read journal: {}", e);
+                break;
             }
         }
-
-        let trimmed = line.trim_end();
-        if trimmed.is_empty() {
+        let entry = line.trim_ascii();
+        if entry.is_empty() {
             continue;
         }
-
-        match serde_json::from_str::<IndexUpdate>(trimmed) {
-            Ok(update) => {
-                apply(update);
-                applied += 1;
+        match serde_json::from_slice::<IndexUpdate>(entry) {
+            Ok(update) => updates.push(update),
+            Err(e) => {
+                debug!("Skipping invalid journal entry: {}", e);
+                invalid += 1;
+                continue;
             }
-            Err(e) => error!("Skipping invalid journal entry: {}", e),
         }
+        if updates.len() == chunk {
+            read += chunk;
+            f(std::mem::take(&mut updates));
+        }
+    }
+    if !updates.is_empty() {
+        read += updates.len();
+        f(updates);
+    }
+    if invalid > 0 {
+        error!("Skipped {} invalid journal entries", invalid);
+    }
+    read
+}
+
+/// Read the journal entries in `[start, end)` at once.
+#[cfg(test)]
+fn read_journal_range(path: &Path, start: u64, end: Option<u64>) -> Vec<IndexUpdate> {
+    let mut all = Vec::new();
+    for_each_journal_chunk(path, start, end, usize::MAX, |updates| all.extend(updates));
+    all
+}
+
+/// Replay the journal entries in `[start, end)` into `state`, streaming it in
+/// batches of [`JOURNAL_REPLAY_CHUNK`] entries.
+///
+/// Each batch is collapsed per path, prepared (stat + exclusion checks) in
+/// parallel, and applied with recency bookkeeping deferred to the end of the
+/// batch. Returns the number of journal entries read.
+fn replay_journal_range(
+    state: &mut DaemonState,
+    path: &Path,
+    start: u64,
+    end: Option<u64>,
+) -> usize {
+    replay_journal_chunks(state, path, start, end, JOURNAL_REPLAY_CHUNK)
+}
+
+fn replay_journal_chunks(
+    state: &mut DaemonState,
+    path: &Path,
+    start: u64,
+    end: Option<u64>,
+    chunk: usize,
+) -> usize {
+    use rayon::prelude::*;
+
+    let started = std::time::Instant::now();
+    let mut collapsed = 0usize;
+    let read = for_each_journal_chunk(path, start, end, chunk, |updates| {
+        let updates = vicaya_watcher::collapse_updates(updates);
+        collapsed += updates.len();
+        let config = &state.config;
+        let canonical_paths = &state.canonical_paths;
+        let prepared: Vec<PreparedIndexUpdate> = updates
+            .into_par_iter()
+            .map(|update| prepare_index_update(config, canonical_paths, update))
+            .collect();
+        state.apply_prepared_batch(prepared);
+    });
+    if read == 0 {
+        return 0;
     }
 
-    applied
+    debug!(
+        "Replayed {} journal entries ({} after collapsing) in {} ms",
+        read,
+        collapsed,
+        started.elapsed().as_millis()
+    );
+    read
 }
 
-fn truncate_journal(path: &Path) -> std::io::Result<()> {
+pub(crate) fn truncate_journal(path: &Path) -> std::io::Result<()> {
     use std::fs::OpenOptions;
     use std::io::Write;
 
@@ -1026,12 +2734,65 @@ fn truncate_journal(path: &Path) -> std::io::Result<()> {
     Ok(())
 }
 
-fn replace_state(state: &SharedState, mut rebuilt: DaemonState) {
-    let old_state = {
-        let mut state = state.write().unwrap();
-        rebuilt.smriti = std::mem::take(&mut state.smriti);
-        std::mem::replace(&mut *state, rebuilt)
-    };
+/// Drop the first `len` bytes of the journal, keeping what was appended after
+/// them. Callers hold the journal lock.
+fn drop_journal_prefix(path: &Path, len: u64) -> std::io::Result<()> {
+    use std::io::Seek;
+
+    if journal_len(path) <= len {
+        return truncate_journal(path);
+    }
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let write = || -> std::io::Result<()> {
+        let mut source = std::fs::File::open(path)?;
+        source.seek(std::io::SeekFrom::Start(len))?;
+        let mut target = std::fs::File::create(&tmp_path)?;
+        std::io::copy(&mut source, &mut target)?;
+        std::fs::rename(&tmp_path, path)
+    };
+    write().inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp_path);
+    })
+}
+
+/// Save the Smriti store if renames moved entries since it was last saved.
+pub(crate) fn persist_renamed_smriti(state: &SharedState) {
+    let persist_lock = Arc::clone(&state.read().unwrap().activity.smriti_persist_lock);
+    let _persist_guard = persist_lock.lock().unwrap();
+    let (store, smriti_file) = {
+        let mut state = state.write().unwrap();
+        if !std::mem::take(&mut state.smriti_renamed) {
+            return;
+        }
+        (state.smriti.clone(), state.smriti_file.clone())
+    };
+    if let Err(err) = store.save_atomic(&smriti_file) {
+        warn!(
+            "Failed to save Smriti usage memory to {}: {}",
+            smriti_file.display(),
+            err
+        );
+    }
+}
+
+fn replace_state(state: &SharedState, mut rebuilt: DaemonState) {
+    let old_state = {
+        let mut state = state.write().unwrap();
+        rebuilt.smriti = std::mem::take(&mut state.smriti);
+        rebuilt.name_trie = std::mem::take(&mut state.name_trie);
+        rebuilt.recent_scopes = std::mem::take(&mut state.recent_scopes);
+        rebuilt.smriti_renamed = state.smriti_renamed;
+        rebuilt.activity = Arc::clone(&state.activity);
+        let recently_deleted = std::mem::take(&mut state.recently_deleted);
+        rebuilt.recently_deleted.extend(recently_deleted);
+        rebuilt
+            .recently_deleted
+            .truncate(rebuilt.config.trash.recently_deleted);
+        std::mem::replace(&mut *state, rebuilt)
+    };
 
     // Dropping a multi-GB old state can monopolize allocator locks and stall
     // foreground IPC threads. Retire it outside the shared-state lock so
@@ -1086,18 +2847,58 @@ fn relieve_malloc_pressure() -> usize {
     0
 }
 
+/// Swap in `rebuilt`, whose snapshot was saved covering the journal up to
+/// `caught_up_to`. Replays the journal tail written since, then drops only the
+/// saved prefix so the tail is still replayed onto `index.bin` after a
+/// restart. Returns the number of tail entries replayed.
+fn finish_rebuild(
+    state: &SharedState,
+    journal_lock: &Mutex<()>,
+    activity: &DaemonActivity,
+    mut rebuilt: DaemonState,
+    caught_up_to: u64,
+) -> Result<usize> {
+    let _journal_guard = journal_lock.lock().unwrap();
+    let journal_file = rebuilt.journal_file.clone();
+    let tail = replay_journal_range(&mut rebuilt, &journal_file, caught_up_to, None);
+    drop_journal_prefix(&journal_file, caught_up_to)?;
+    activity.set_journal_entries(tail);
+    rebuilt.last_updated = now_epoch_seconds();
+    for scanned_at in rebuilt.root_scans.values_mut() {
+        *scanned_at = rebuilt.last_updated;
+    }
+    rebuilt.reconciling = false;
+    activity
+        .last_reconcile
+        .store(rebuilt.last_updated, Ordering::Relaxed);
+
+    // The swap itself is a single move under the state write lock.
+    replace_state(state, rebuilt);
+    Ok(tail)
+}
+
+/// Rescan every root with the `profile` scan settings and swap the result
+/// in, replaying watcher updates journaled meanwhile.
 pub fn full_rebuild_from_disk(
     state: &SharedState,
     journal_lock: &Arc<Mutex<()>>,
     rebuild_lock: &Arc<Mutex<()>>,
+    profile: ScanProfile,
 ) -> Result<usize> {
     let _rebuild_guard = rebuild_lock.lock().unwrap();
 
-    {
+    let activity = {
         let mut state = state.write().unwrap();
         state.reconciling = true;
-    }
+        let scan = state.config.performance.scan_settings(profile);
+        state
+            .activity
+            .begin_reconcile(state.indexed_file_count(), scan);
+        Arc::clone(&state.activity)
+    };
 
+    let reconcile_started = ThreadUsage::now();
+    let mut scan_usage = ThreadUsage::default();
     let result = (|| {
         let (config, index_file, journal_file) = {
             let state = state.read().unwrap();
@@ -1114,38 +2915,39 @@ pub fn full_rebuild_from_disk(
         };
 
         info!("Starting full index rebuild from disk...");
-        let scanner = Scanner::new(config.clone());
-        let snapshot = scanner.scan()?;
+        let scanner = Scanner::new(config.clone()).with_profile(profile);
+        let scan_started = ThreadUsage::now();
+        let snapshot = scanner.scan_with_progress(|scanned| activity.reconcile_scanned(scanned));
+        scan_usage = scan_started.elapsed();
+        let snapshot = snapshot?;
         let files_indexed = snapshot.file_table.len();
 
-        // Finalize without holding the shared state write lock for expensive work.
-        // Holding the journal lock blocks watcher writes, but search/status can keep
-        // reading the previous hot snapshot until the final pointer-sized swap.
-        let _journal_guard = journal_lock.lock().unwrap();
-        let applied_updates = {
-            let mut rebuilt =
-                DaemonState::new(config, index_file.clone(), journal_file.clone(), snapshot);
-            let applied_updates = apply_journal_from_offset(&journal_file, journal_offset, |u| {
-                rebuilt.apply_update(u);
-            });
-            if applied_updates > 0 {
-                debug!("Applied {} journal updates after rebuild", applied_updates);
-            }
-
-            rebuilt.snapshot.save(&index_file)?;
-            truncate_journal(&journal_file)?;
-            rebuilt.last_updated = now_epoch_seconds();
-            rebuilt.reconciling = false;
-
-            replace_state(state, rebuilt);
-            applied_updates
+        // Build and persist the replacement off to the side. Neither the shared
+        // state lock nor the journal lock is held here, so search/status keep
+        // reading the previous snapshot and the watcher keeps journaling.
+        let mut rebuilt =
+            DaemonState::rebuilt(config, index_file.clone(), journal_file.clone(), snapshot);
+        let caught_up_to = {
+            let _guard = journal_lock.lock().unwrap();
+            journal_len(&journal_file)
         };
-
-        info!("Full rebuild complete: {} files indexed", files_indexed);
+        let mut applied_updates = replay_journal_range(
+            &mut rebuilt,
+            &journal_file,
+            journal_offset,
+            Some(caught_up_to),
+        );
+        // `index.bin` now covers the journal up to `caught_up_to`; entries
+        // appended while it was written stay in the journal for the next
+        // load to replay.
+        rebuilt.snapshot.save(&index_file)?;
+        applied_updates += finish_rebuild(state, journal_lock, &activity, rebuilt, caught_up_to)?;
         if applied_updates > 0 {
-            debug!("Full rebuild included {} journal updates", applied_updates);
+            debug!("Applied {} journal updates after rebuild", applied_updates);
         }
 
+        info!("Full rebuild complete: {} files indexed", files_indexed);
+
         Ok(files_indexed)
     })();
 
@@ -1153,10 +2955,31 @@ pub fn full_rebuild_from_disk(
         let mut state = state.write().unwrap();
         state.reconciling = false;
     }
+    activity.end_reconcile();
+    record_reconcile_usage(&activity, reconcile_started.elapsed(), scan_usage);
 
     result
 }
 
+/// Charge a reconcile that used `total` to the `Reconcile` subsystem, less
+/// the `scan` part of it, which is charged to `Scan` on its own.
+pub(crate) fn record_reconcile_usage(
+    activity: &DaemonActivity,
+    total: ThreadUsage,
+    scan: ThreadUsage,
+) {
+    activity.usage.record(Subsystem::Scan, scan);
+    activity.usage.record(
+        Subsystem::Reconcile,
+        ThreadUsage {
+            cpu_ns: total.cpu_ns.saturating_sub(scan.cpu_ns),
+            read_bytes: total
+                .read_bytes
+                .map(|bytes| bytes.saturating_sub(scan.read_bytes.unwrap_or(0))),
+        },
+    );
+}
+
 /// IPC server that handles client connections.
 pub struct IpcServer {
     listener: UnixListener,
@@ -1164,14 +2987,21 @@ pub struct IpcServer {
     socket_path: PathBuf,
 }
 
+/// Request handler shared by the IPC socket and the HTTP endpoint.
 #[derive(Clone)]
-struct IpcHandler {
+pub(crate) struct IpcHandler {
     state: SharedState,
     shutdown: Arc<AtomicBool>,
     socket_path: PathBuf,
     journal_lock: Arc<Mutex<()>>,
     rebuild_lock: Arc<Mutex<()>>,
     smriti_persist_lock: Arc<Mutex<()>>,
+    /// Binary accepted by `Request::Upgrade`, exec'd once the server stops.
+    upgrade: Arc<Mutex<Option<PathBuf>>>,
+    /// Ranking policies `SearchOptions::scorer` may name.
+    scorers: Arc<ScorerRegistry>,
+    /// Audit log of privileged requests, next to the socket.
+    audit_file: PathBuf,
 }
 
 impl IpcServer {
@@ -1211,6 +3041,13 @@ impl IpcServer {
             .map_err(|e| vicaya_core::Error::Ipc(format!("Failed to set nonblocking: {}", e)))?;
         info!("IPC server listening on {}", socket_path.display());
 
+        let scorers = ScorerRegistry::from_config(&state.read().unwrap().config.ranking)
+            .unwrap_or_else(|e| {
+                warn!("Ignoring [ranking] boosts: {e}");
+                ScorerRegistry::default()
+            });
+        let smriti_persist_lock = Arc::clone(&state.read().unwrap().activity.smriti_persist_lock);
+
         Ok(Self {
             listener,
             socket_path: socket_path.to_path_buf(),
@@ -1220,11 +3057,24 @@ impl IpcServer {
                 socket_path: socket_path.to_path_buf(),
                 journal_lock,
                 rebuild_lock,
-                smriti_persist_lock: Arc::new(Mutex::new(())),
+                smriti_persist_lock,
+                upgrade: Arc::new(Mutex::new(None)),
+                scorers: Arc::new(scorers),
+                audit_file: socket_path.with_file_name("audit.jsonl"),
             },
         })
     }
 
+    /// Binary to exec after `run` returns, when an upgrade was requested.
+    pub fn upgrade_binary(&self) -> Option<PathBuf> {
+        self.handler.upgrade.lock().unwrap().clone()
+    }
+
+    /// Handler for serving the same requests over another transport.
+    pub(crate) fn handler(&self) -> IpcHandler {
+        self.handler.clone()
+    }
+
     /// Run the server loop.
     pub fn run(&self) -> Result<()> {
         while !self.handler.shutdown.load(Ordering::Relaxed) {
@@ -1257,29 +3107,64 @@ impl IpcHandler {
         debug!("Client connected: {:?}", peer_addr);
 
         let mut reader = BufReader::new(stream.try_clone().unwrap());
+        // Every connection starts as JSON; `Request::Framing` switches it.
+        let mut framing = Framing::Json;
 
         loop {
-            match vicaya_core::ipc::read_message(&mut reader) {
+            match framing.read_frame(&mut reader) {
                 Ok(None) => {
                     debug!("Client disconnected");
                     return;
                 }
-                Ok(Some(line)) => {
-                    let request = match Request::from_json(&line) {
+                Ok(Some(frame)) => {
+                    let request = match framing.decode::<Request>(&frame) {
                         Ok(req) => req,
                         Err(e) => {
                             error!("Failed to parse request: {}", e);
                             let response = Response::Error {
                                 message: format!("Invalid request: {}", e),
                             };
-                            self.send_response(&mut stream, &response);
+                            self.send_response(&mut stream, framing, &response);
                             return;
                         }
                     };
 
                     debug!("Received request: {:?}", request);
+                    if let Request::Framing { framing: next } = request {
+                        self.send_response(
+                            &mut stream,
+                            framing,
+                            &Response::Framing { framing: next },
+                        );
+                        framing = next;
+                        continue;
+                    }
+                    if let Request::Subscribe(options) = request {
+                        if let Some(message) = self.rejected_search(&options) {
+                            self.send_response(&mut stream, framing, &Response::Error { message });
+                            continue;
+                        }
+                        self.serve_subscription(&mut stream, &mut reader, framing, options);
+                        return;
+                    }
+                    let mut audit = PendingAudit::for_request(&request, &stream);
+                    if matches!(request, Request::Shutdown) {
+                        // Always granted, and the daemon may exit before
+                        // the reply is out, so it is recorded up front.
+                        if let Some(audit) = audit.take() {
+                            audit.finish(&self.audit_file, &Response::Ok);
+                        }
+                    }
                     let response = self.handle_request(request);
-                    self.send_response(&mut stream, &response);
+                    if let Some(audit) = audit {
+                        audit.finish(&self.audit_file, &response);
+                    }
+                    self.send_response(&mut stream, framing, &response);
+                    if matches!(response, Response::Upgrading { .. }) {
+                        // Stop only after the reply is out: the exec that
+                        // follows ends every connection thread mid-write.
+                        self.shutdown.store(true, Ordering::Relaxed);
+                    }
 
                     if self.shutdown.load(Ordering::Relaxed) {
                         return;
@@ -1290,113 +3175,232 @@ impl IpcHandler {
                     let response = Response::Error {
                         message: e.to_string(),
                     };
-                    self.send_response(&mut stream, &response);
+                    self.send_response(&mut stream, framing, &response);
                     return;
                 }
             }
         }
     }
 
-    /// Handle a request and generate a response.
-    fn handle_request(&self, request: Request) -> Response {
-        match request {
-            Request::Search {
-                query,
-                limit,
-                scope,
-                filter_scope,
-                recent_if_empty,
-            } => {
-                let state = self.state.read().unwrap();
-                let engine = QueryEngine::new(
-                    &state.snapshot.file_table,
-                    &state.snapshot.string_arena,
-                    &state.snapshot.trigram_index,
-                );
+    /// Why `options` cannot be searched, if they cannot.
+    fn rejected_search(&self, options: &SearchOptions) -> Option<String> {
+        let profile = self.state.read().unwrap().config.index_profile;
+        unknown_scorer(&self.scorers, [options])
+            .or_else(|| unsupported_sort(profile, [options]))
+            .or_else(|| malformed_niyama([options]))
+    }
 
-                let scope_path = scope
-                    .filter(|s| !s.trim().is_empty())
-                    .map(std::path::PathBuf::from);
-                let filter_scope_path = filter_scope
-                    .filter(|s| !s.trim().is_empty())
-                    .map(std::path::PathBuf::from);
-                const SCOPED_LINEAR_SEARCH_LIMIT: usize = 100_000;
-                let scoped_file_ids = filter_scope_path.as_deref().and_then(|scope| {
-                    state.scoped_file_ids_up_to(scope, SCOPED_LINEAR_SEARCH_LIMIT)
-                });
-                let exact_name_file_ids = state.exact_name_file_ids(&query).map(|ids| {
-                    if let Some(scope) = filter_scope_path.as_deref() {
-                        state
-                            .filter_file_ids_in_scope(&ids, scope)
-                            .unwrap_or_default()
-                    } else {
-                        ids
-                    }
-                });
-                let trimmed_query_is_empty = query.trim().is_empty();
-                let search_limit = if state.config.smriti_enabled()
-                    && !state.smriti.entries.is_empty()
-                    && !trimmed_query_is_empty
-                {
-                    limit
-                        .saturating_mul(4)
-                        .max(limit)
-                        .min(limit.saturating_add(300))
-                } else {
-                    limit
-                };
+    /// Serve a `Subscribe` connection until the client leaves or the daemon
+    /// stops: push an `IndexChanged` for the watcher batches that affect
+    /// `options`, and answer requests as they arrive, a new `Subscribe`
+    /// replacing the options.
+    fn serve_subscription(
+        &self,
+        stream: &mut UnixStream,
+        reader: &mut BufReader<UnixStream>,
+        framing: Framing,
+        mut options: SearchOptions,
+    ) {
+        use std::io::BufRead;
+
+        let changes = self.state.read().unwrap().activity.changes.subscribe();
+        self.send_response(stream, framing, &Response::Subscribed);
+        // Wake up regularly to forward batches and notice shutdown.
+        if let Err(e) = reader.get_ref().set_read_timeout(Some(SUBSCRIPTION_POLL)) {
+            error!("Failed to set subscription read timeout: {}", e);
+            return;
+        }
 
-                // If query is empty and recent_if_empty is true, return recent files
-                let mut results = if trimmed_query_is_empty && recent_if_empty {
-                    if let Some((file_ids, true)) = scoped_file_ids.as_ref() {
-                        engine.recent_file_ids(limit, file_ids)
-                    } else {
-                        let file_ids = state
-                            .recent_file_ids(limit, filter_scope_path.as_deref())
-                            .unwrap_or_default();
-                        engine.recent_file_ids(limit, &file_ids)
-                    }
-                } else if let Some(file_ids) = exact_name_file_ids.as_deref() {
-                    engine.exact_name_file_ids(search_limit, file_ids)
-                } else if let Some((file_ids, true)) = scoped_file_ids.as_ref() {
-                    let query_obj = Query {
-                        term: query,
-                        limit: search_limit,
-                        scope: scope_path,
-                        filter_scope: filter_scope_path,
+        while !self.shutdown.load(Ordering::Relaxed) {
+            match reader.fill_buf() {
+                Ok([]) => {
+                    debug!("Subscriber disconnected");
+                    return;
+                }
+                Ok(_) => {
+                    let request = match framing.read_frame(reader) {
+                        Ok(Some(frame)) => framing.decode::<Request>(&frame),
+                        Ok(None) => return,
+                        Err(e) => {
+                            error!("Failed to read from subscriber: {}", e);
+                            return;
+                        }
                     };
-                    engine.search_file_ids(&query_obj, file_ids)
-                } else {
-                    let query_obj = Query {
-                        term: query,
-                        limit: search_limit,
-                        scope: scope_path,
-                        filter_scope: filter_scope_path,
+                    let response = match request {
+                        Ok(Request::Subscribe(next)) => match self.rejected_search(&next) {
+                            Some(message) => Response::Error { message },
+                            None => {
+                                options = next;
+                                Response::Subscribed
+                            }
+                        },
+                        Ok(request) => self.handle_request(request),
+                        Err(e) => Response::Error {
+                            message: format!("Invalid request: {}", e),
+                        },
                     };
-                    engine.search(&query_obj)
-                };
-                if !trimmed_query_is_empty {
-                    apply_smriti_boosts(&state, &mut results, limit);
+                    self.send_response(stream, framing, &response);
                 }
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) => {}
+                Err(e) => {
+                    error!("Failed to read from subscriber: {}", e);
+                    return;
+                }
+            }
 
-                let ipc_results = results
-                    .into_iter()
-                    .map(|r| vicaya_core::ipc::SearchResult {
-                        path: r.path,
-                        name: r.name,
-                        score: r.score,
-                        size: r.size,
-                        mtime: r.mtime,
-                    })
-                    .collect();
+            let batches: Vec<ChangedPaths> = changes.try_iter().collect();
+            if batches.is_empty() {
+                continue;
+            }
+            let response = self.index_changed(&options, &batches);
+            if matches!(&response, Response::IndexChanged { removed, added } if removed.is_empty() && added.is_empty())
+            {
+                continue;
+            }
+            if let Err(e) = framing.write(stream, &response) {
+                debug!("Subscriber went away: {}", e);
+                return;
+            }
+        }
+    }
+
+    /// What `batches` change about the results of `options`: touched paths
+    /// that still match are ranked as `added`, and every other touched path
+    /// inside the filter scope is `removed`, as it is gone or no longer
+    /// matches.
+    fn index_changed(&self, options: &SearchOptions, batches: &[ChangedPaths]) -> Response {
+        let mut seen = std::collections::HashSet::new();
+        let changed: Vec<(&str, bool)> = batches
+            .iter()
+            .flat_map(|batch| batch.iter().rev())
+            .filter(|(path, _)| seen.insert(path.as_str()))
+            .map(|(path, present)| (path.as_str(), *present))
+            .collect();
+
+        let state = self.state.read().unwrap();
+        let activity = Arc::clone(&state.activity);
+        let candidates: Vec<FileId> = changed
+            .iter()
+            .filter(|(_, present)| *present)
+            .filter_map(|(path, _)| state.get_file_id_for_path(path))
+            .collect();
+        let added = if candidates.is_empty() {
+            Vec::new()
+        } else {
+            let options = SearchOptions {
+                limit: candidates.len(),
+                offset: 0,
+                ..options.clone()
+            };
+            activity.usage.measure(Subsystem::Query, || {
+                state
+                    .search_among(options, &self.scorers, Some(candidates))
+                    .results
+            })
+        };
+        drop(state);
+
+        let scope = options
+            .filters
+            .scope
+            .as_deref()
+            .filter(|scope| !scope.trim().is_empty())
+            .map(|scope| vicaya_core::paths::expand_user_path(Path::new(scope)));
+        let matched: std::collections::HashSet<&str> =
+            added.iter().map(|result| result.path.as_str()).collect();
+        let removed = changed
+            .iter()
+            .map(|(path, _)| *path)
+            .filter(|path| !matched.contains(path))
+            .filter(|path| {
+                scope
+                    .as_ref()
+                    .is_none_or(|scope| Path::new(path).starts_with(scope))
+            })
+            .map(str::to_string)
+            .collect();
+        Response::IndexChanged { removed, added }
+    }
+
+    /// Handle a request and generate a response.
+    pub(crate) fn handle_request(&self, request: Request) -> Response {
+        crate::crash::record_request(&request);
+        if matches!(
+            request,
+            Request::Search(_)
+                | Request::SearchBatch { .. }
+                | Request::SmritiList { .. }
+                | Request::CompletePath { .. }
+                | Request::DirUsage { .. }
+                | Request::Jump { .. }
+                | Request::Suggest { .. }
+        ) {
+            let activity = Arc::clone(&self.state.read().unwrap().activity);
+            return activity
+                .usage
+                .measure(Subsystem::Query, || self.dispatch_request(request));
+        }
+        self.dispatch_request(request)
+    }
 
+    fn dispatch_request(&self, request: Request) -> Response {
+        match request {
+            Request::Search(options) => {
+                if let Some(message) = self.rejected_search(&options) {
+                    return Response::Error { message };
+                }
+                let SearchPage {
+                    results,
+                    truncated,
+                    suggestions,
+                    outside_scope,
+                } = self
+                    .state
+                    .read()
+                    .unwrap()
+                    .search_broadening(options, &self.scorers);
                 Response::SearchResults {
-                    results: ipc_results,
+                    results,
+                    truncated,
+                    suggestions,
+                    outside_scope,
                 }
             }
+            Request::SearchBatch {
+                queries,
+                total_limit,
+            } => {
+                if queries.len() > MAX_SEARCH_BATCH {
+                    return Response::Error {
+                        message: format!(
+                            "Batch of {} queries exceeds the limit of {MAX_SEARCH_BATCH}",
+                            queries.len()
+                        ),
+                    };
+                }
+                if let Some(message) =
+                    unknown_scorer(&self.scorers, &queries).or_else(|| malformed_niyama(&queries))
+                {
+                    return Response::Error { message };
+                }
+                let profile = self.state.read().unwrap().config.index_profile;
+                if let Some(message) = unsupported_sort(profile, &queries) {
+                    return Response::Error { message };
+                }
+                let batches =
+                    self.state
+                        .read()
+                        .unwrap()
+                        .search_batch(queries, total_limit, &self.scorers);
+                Response::SearchBatchResults { batches }
+            }
             Request::Status => {
                 let state = self.state.read().unwrap();
-                Response::Status {
+                Response::Status(DaemonStatus {
                     pid: std::process::id() as i32,
                     build: vicaya_core::ipc::BuildInfo {
                         version: vicaya_core::build_info::BUILD_INFO.version.to_string(),
@@ -1411,13 +3415,85 @@ impl IpcHandler {
                     state_allocated_bytes: state.estimated_state_allocated_bytes(),
                     last_updated: state.last_updated,
                     reconciling: state.reconciling,
+                    pending_watcher_events: state.activity.pending_watcher_events(),
+                    journal_entries: state.activity.journal_entries(),
+                    reconcile_progress: if state.reconciling {
+                        state.activity.reconcile_progress()
+                    } else {
+                        None
+                    },
+                    reconcile_scan: if state.reconciling {
+                        state.activity.reconcile_scan()
+                    } else {
+                        None
+                    },
+                    warmup_ms: state.activity.warmup_ms(),
+                    journal_fsync: state.activity.journal_fsync(),
+                    index_cap: state.index_cap.clone(),
+                    last_reconcile: state.activity.last_reconcile(),
+                    next_reconcile: state.activity.next_reconcile(),
+                    index_profile: state.config.index_profile,
+                })
+            }
+            Request::Ping => {
+                let state = self.state.read().unwrap();
+                Response::Pong {
+                    ready: state.activity.warmup_ms().is_some()
+                        || state.config.performance.warmup_trigrams == 0,
+                    indexed_files: state.indexed_file_count(),
+                    reconciling: state.reconciling,
+                    reconcile_progress: if state.reconciling {
+                        state.activity.reconcile_progress()
+                    } else {
+                        None
+                    },
+                }
+            }
+            Request::RootStats => Response::RootStats {
+                roots: self.state.read().unwrap().root_stats(),
+            },
+            Request::Metrics => {
+                let activity = Arc::clone(&self.state.read().unwrap().activity);
+                Response::Metrics {
+                    uptime_secs: activity.uptime_secs(),
+                    process_cpu_ns: vicaya_core::accounting::process_cpu_ns(),
+                    subsystems: activity.usage.snapshot(),
                 }
             }
+            Request::Exclude { patterns } => {
+                let invalid: Vec<String> = patterns
+                    .iter()
+                    .filter_map(|pattern| {
+                        vicaya_core::filter::validate_exclusion(pattern)
+                            .err()
+                            .map(|err| format!("`{pattern}`: {err}"))
+                    })
+                    .collect();
+                if !invalid.is_empty() {
+                    return Response::Error {
+                        message: format!("Invalid exclusion patterns: {}", invalid.join("; ")),
+                    };
+                }
+                let removed = self.state.write().unwrap().exclude(&patterns);
+                Response::Excluded { removed }
+            }
+            Request::ReportMissing { path } => self.report_missing(&path),
+            Request::RecentlyDeleted { query, limit } => Response::RecentlyDeleted {
+                entries: self
+                    .state
+                    .read()
+                    .unwrap()
+                    .recently_deleted(query.as_deref(), limit),
+            },
             Request::Rebuild { dry_run } => {
                 if dry_run {
-                    let config = { self.state.read().unwrap().config.clone() };
+                    let (config, activity) = {
+                        let state = self.state.read().unwrap();
+                        (state.config.clone(), Arc::clone(&state.activity))
+                    };
                     let scanner = Scanner::new(config);
-                    let snapshot = match scanner.scan() {
+                    let snapshot = match activity.usage.measure(Subsystem::Scan, || scanner.scan())
+                    {
                         Ok(s) => s,
                         Err(e) => {
                             error!("Rebuild failed: {}", e);
@@ -1431,40 +3507,39 @@ impl IpcHandler {
                     return Response::RebuildComplete { files_indexed };
                 }
 
-                match full_rebuild_from_disk(&self.state, &self.journal_lock, &self.rebuild_lock) {
+                // Someone is waiting on an explicit rebuild, so it runs
+                // with the faster `initial_scan` settings.
+                match full_rebuild_from_disk(
+                    &self.state,
+                    &self.journal_lock,
+                    &self.rebuild_lock,
+                    ScanProfile::InitialScan,
+                ) {
                     Ok(files_indexed) => Response::RebuildComplete { files_indexed },
                     Err(e) => Response::Error {
                         message: format!("Rebuild failed: {}", e),
                     },
                 }
             }
+            Request::Reconcile => {
+                let state = self.state.read().unwrap();
+                if !state.reconciling {
+                    state.activity.request_reconcile();
+                }
+                Response::ReconcileRequested {
+                    already_running: state.reconciling,
+                }
+            }
             Request::SmritiRecord {
                 path,
                 query,
                 action,
-            } => {
-                let _persist_guard = self.smriti_persist_lock.lock().unwrap();
-                let Some((store, smriti_file)) = ({
-                    let mut state = self.state.write().unwrap();
-                    if !state.config.smriti_enabled() {
-                        None
-                    } else {
-                        let now = now_epoch_seconds();
-                        state.smriti.record(path, query, action, now);
-                        let max_entries = state.config.smriti.max_entries;
-                        state.smriti.prune_to_limit(max_entries);
-                        Some((state.smriti.clone(), state.smriti_file.clone()))
-                    }
-                }) else {
-                    return Response::Ok;
-                };
-                match store.save_atomic(&smriti_file) {
-                    Ok(()) => Response::Ok,
-                    Err(e) => Response::Error {
-                        message: format!("Failed to save Smriti usage memory: {}", e),
-                    },
-                }
-            }
+            } => match self.record_smriti(path, query, action) {
+                Ok(()) => Response::Ok,
+                Err(e) => Response::Error {
+                    message: format!("Failed to save Smriti usage memory: {}", e),
+                },
+            },
             Request::SmritiList {
                 query,
                 limit,
@@ -1479,13 +3554,78 @@ impl IpcHandler {
                 let scope = filter_scope
                     .as_deref()
                     .filter(|s| !s.trim().is_empty())
-                    .map(Path::new);
-                let entries =
-                    state
-                        .smriti
-                        .list(query.as_deref(), limit, scope, now_epoch_seconds());
+                    .map(|s| vicaya_core::paths::expand_user_path(Path::new(s)));
+                let entries = state.smriti.list(
+                    query.as_deref(),
+                    limit,
+                    scope.as_deref(),
+                    now_epoch_seconds(),
+                );
                 Response::SmritiEntries { entries }
             }
+            Request::CompletePath {
+                prefix,
+                limit,
+                dirs_only,
+            } => Response::PathCompletions {
+                completions: self.state.read().unwrap().complete_path(
+                    &prefix,
+                    limit,
+                    dirs_only,
+                    &self.scorers,
+                ),
+            },
+            Request::DirUsage { path, limit } => {
+                let state = self.state.read().unwrap();
+                let path = vicaya_core::paths::expand_user_path(Path::new(&path));
+                let path = state
+                    .canonical_paths
+                    .canonicalize_owned(path.to_string_lossy().into_owned());
+                let size = |totals: DirTotals| DirSize {
+                    bytes: totals.bytes,
+                    files: totals.files,
+                };
+                match state.dir_usage(&path, limit) {
+                    Some((total, entries)) => Response::DirUsage {
+                        total: size(total),
+                        entries: entries
+                            .into_iter()
+                            .map(|(path, totals)| DirUsageEntry {
+                                path,
+                                size: size(totals),
+                            })
+                            .collect(),
+                    },
+                    None => Response::Error {
+                        message: format!("{path} is not an indexed directory"),
+                    },
+                }
+            }
+            Request::Jump { query } => {
+                let keywords: Vec<String> =
+                    query.split_whitespace().map(str::to_lowercase).collect();
+                // Remembered entries are checked on disk outside the lock.
+                let candidates = self.state.read().unwrap().jump_candidates(&keywords);
+                let target = best_jump_dir(candidates, &keywords).or_else(|| {
+                    self.state
+                        .read()
+                        .unwrap()
+                        .indexed_jump_dir(&keywords, &self.scorers)
+                });
+                if let Some(path) = &target {
+                    if let Err(e) = self.record_smriti(path.clone(), query, SmritiAction::Enter) {
+                        warn!("Failed to save Smriti usage memory: {}", e);
+                    }
+                }
+                Response::JumpTarget { path: target }
+            }
+            Request::Suggest { prefix, limit } => Response::Suggestions {
+                names: self
+                    .state
+                    .read()
+                    .unwrap()
+                    .suggest(&prefix, limit, &self.scorers),
+            },
             Request::SmritiForget { path } => {
                 let _persist_guard = self.smriti_persist_lock.lock().unwrap();
                 let Some((removed, store, smriti_file)) = ({
@@ -1494,6 +3634,9 @@ impl IpcHandler {
                         None
                     } else {
                         let removed = state.smriti.forget(&path);
+                        if removed {
+                            state.name_trie = NameTrie::from_smriti(&state.smriti);
+                        }
                         Some((removed, state.smriti.clone(), state.smriti_file.clone()))
                     }
                 }) else {
@@ -1509,6 +3652,17 @@ impl IpcHandler {
                     },
                 }
             }
+            Request::ScopeUsed { path } => {
+                let mut state = self.state.write().unwrap();
+                let path = vicaya_core::paths::expand_user_path(Path::new(&path));
+                let path = state
+                    .canonical_paths
+                    .canonicalize_owned(path.to_string_lossy().into_owned());
+                state
+                    .recent_scopes
+                    .record(PathBuf::from(path), now_epoch_seconds());
+                Response::Ok
+            }
             Request::SmritiClear => {
                 let _persist_guard = self.smriti_persist_lock.lock().unwrap();
                 let Some((store, smriti_file)) = ({
@@ -1517,6 +3671,7 @@ impl IpcHandler {
                         None
                     } else {
                         state.smriti.clear();
+                        state.name_trie = NameTrie::default();
                         Some((state.smriti.clone(), state.smriti_file.clone()))
                     }
                 }) else {
@@ -1529,29 +3684,155 @@ impl IpcHandler {
                     },
                 }
             }
+            Request::Reveal { path } => {
+                self.run_action(&path, SmritiAction::Reveal, vicaya_core::actions::reveal)
+            }
+            Request::OpenEditor { path } => self.run_action(
+                &path,
+                SmritiAction::Open,
+                vicaya_core::actions::open_in_editor,
+            ),
+            Request::CopyPath { path } => self.run_action(&path, SmritiAction::Copy, |path| {
+                vicaya_core::actions::copy_to_clipboard(&path.to_string_lossy())
+            }),
+            Request::Upgrade { binary } => self.accept_upgrade(binary.as_deref()),
             Request::Shutdown => {
                 info!("Shutdown requested");
                 self.shutdown.store(true, Ordering::Relaxed);
                 let _ = UnixStream::connect(&self.socket_path);
                 Response::Ok
             }
+            // Answered by `handle_client`, which owns the connection's framing.
+            Request::Framing { .. } => Response::Error {
+                message: "Framing can only be negotiated on a socket connection".to_string(),
+            },
+            Request::Subscribe(_) => Response::Error {
+                message: "Subscribe is only served on a socket connection".to_string(),
+            },
         }
     }
 
-    /// Send a response to the client.
-    fn send_response(&self, stream: &mut UnixStream, response: &Response) {
-        match response.to_json() {
-            Ok(json) => {
-                let mut data = json;
-                data.push('\n');
-                if let Err(e) = stream.write_all(data.as_bytes()) {
-                    error!("Failed to send response: {}", e);
+    /// Check the upgrade target and remember it for the main thread. The
+    /// socket is removed right away so clients wait for the new daemon
+    /// instead of reaching this one while it winds down.
+    fn accept_upgrade(&self, binary: Option<&str>) -> Response {
+        let (binary, version) = match crate::handoff::resolve_binary(binary) {
+            Ok(target) => target,
+            Err(e) => {
+                return Response::Error {
+                    message: e.to_string(),
                 }
             }
-            Err(e) => {
-                error!("Failed to serialize response: {}", e);
+        };
+        info!("Upgrade requested: {} ({})", binary.display(), version);
+        *self.upgrade.lock().unwrap() = Some(binary.clone());
+        let _ = std::fs::remove_file(&self.socket_path);
+        Response::Upgrading {
+            binary: binary.to_string_lossy().to_string(),
+            version,
+        }
+    }
+
+    /// Record a Smriti usage event and persist the store, unless Smriti is
+    /// disabled.
+    fn record_smriti(
+        &self,
+        path: String,
+        query: String,
+        action: SmritiAction,
+    ) -> vicaya_core::Result<()> {
+        let _persist_guard = self.smriti_persist_lock.lock().unwrap();
+        let Some((store, smriti_file)) = ({
+            let mut state = self.state.write().unwrap();
+            if !state.config.smriti_enabled() {
+                None
+            } else {
+                let now = now_epoch_seconds();
+                let name = Path::new(&path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned());
+                state.smriti.record(path, query, action, now);
+                let before = state.smriti.entries.len();
+                let max_entries = state.config.smriti.max_entries;
+                state.smriti.prune_to_limit(max_entries);
+                if state.smriti.entries.len() < before {
+                    state.name_trie = NameTrie::from_smriti(&state.smriti);
+                } else if let Some(name) = name {
+                    state.name_trie.record(&name, 1, now);
+                }
+                Some((state.smriti.clone(), state.smriti_file.clone()))
+            }
+        }) else {
+            return Ok(());
+        };
+        store.save_atomic(&smriti_file)
+    }
+
+    /// Tombstone `path` after a client reports it gone, once the filesystem
+    /// agrees. The delete is journaled like a watcher event so it survives a
+    /// restart before the next snapshot.
+    fn report_missing(&self, path: &str) -> Response {
+        let path = vicaya_core::paths::expand_user_path(Path::new(path))
+            .to_string_lossy()
+            .to_string();
+        // Archive members are gone only when their archive is.
+        let on_disk = vicaya_core::archive::split_virtual_path(&path)
+            .map_or(Path::new(&path), |(archive, _)| archive);
+        match std::fs::symlink_metadata(on_disk) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            _ => return Response::MissingReported { removed: false },
+        }
+
+        let (journal_file, activity) = {
+            let state = self.state.read().unwrap();
+            if state.get_file_id_for_path(&path).is_none() {
+                return Response::MissingReported { removed: false };
+            }
+            (state.journal_file.clone(), Arc::clone(&state.activity))
+        };
+        info!("Client reported missing path: {}", path);
+        let update = IndexUpdate::Delete { path };
+        {
+            let _guard = self.journal_lock.lock().unwrap();
+            match append_journal(&journal_file, std::slice::from_ref(&update)) {
+                Ok(()) => activity.journal_appended(1),
+                Err(e) => warn!("Failed to append journal: {}", e),
             }
         }
+        activity.usage.measure(Subsystem::Watcher, || {
+            let mut state = self.state.write().unwrap();
+            let update = prepare_index_update(&state.config, &state.canonical_paths, update);
+            state.apply_prepared_update(update);
+        });
+        Response::MissingReported { removed: true }
+    }
+
+    /// Run a result action for an IPC client, recording it in Smriti the same
+    /// way the TUI does when it succeeds.
+    fn run_action(
+        &self,
+        path: &str,
+        action: SmritiAction,
+        run: impl FnOnce(&Path) -> vicaya_core::Result<()>,
+    ) -> Response {
+        let path = vicaya_core::paths::expand_user_path(Path::new(path));
+        if let Err(e) = run(&path) {
+            return Response::Error {
+                message: format!("{} failed: {}", action.as_str(), e),
+            };
+        }
+        let path = path.to_string_lossy().to_string();
+        if let Err(e) = self.record_smriti(path, String::new(), action) {
+            warn!("Failed to save Smriti usage memory: {}", e);
+        }
+        Response::Ok
+    }
+
+    /// Send a response to the client.
+    fn send_response(&self, stream: &mut UnixStream, framing: Framing, response: &Response) {
+        if let Err(e) = framing.write(stream, response) {
+            error!("Failed to send response: {}", e);
+        }
     }
 }
 
@@ -1572,7 +3853,9 @@ impl Drop for IpcServer {
 mod tests {
     use super::*;
     use tempfile::tempdir;
-    use vicaya_core::config::{ContentSearchConfig, PerformanceConfig, SmritiConfig};
+    use vicaya_core::config::{
+        ContentSearchConfig, PerformanceConfig, PreviewConfig, SmritiConfig,
+    };
     use vicaya_scanner::Scanner;
 
     fn test_config(root: &Path, vicaya_dir: &Path) -> Config {
@@ -1580,14 +3863,48 @@ mod tests {
             index_roots: vec![root.to_path_buf()],
             exclusions: vec![],
             respect_ignore_files: true,
+            case_insensitive_exclusions: false,
+            exclusion_cache: Default::default(),
             index_path: vicaya_dir.join("index"),
             max_memory_mb: 128,
+            max_indexed_files: 0,
             performance: PerformanceConfig {
                 scanner_threads: 2,
                 reconcile_hour: 3,
+                query_timeout_ms: 250,
+                query_max_candidates: 0,
+                warmup_trigrams: 0,
+                reconcile_schedule: Vec::new(),
+                reconcile_min_gap_minutes: 60,
+                reconcile_on_wake: true,
+                initial_scan: Default::default(),
+                reconcile: Default::default(),
             },
             smriti: SmritiConfig::default(),
             content_search: ContentSearchConfig::default(),
+            preview: PreviewConfig::default(),
+            associations: Default::default(),
+            aliases: Default::default(),
+            http: vicaya_core::config::HttpConfig::default(),
+            journal: vicaya_core::config::JournalConfig::default(),
+            telemetry: vicaya_core::config::TelemetryConfig::default(),
+            ranking: vicaya_core::config::RankingConfig::default(),
+            trash: vicaya_core::config::TrashConfig::default(),
+            normalization: Default::default(),
+            hooks: Vec::new(),
+            root_max_depth: Default::default(),
+            show_hidden: true,
+            restore_tui_session: false,
+            offline_while_starting: false,
+            auto_scope: vicaya_core::config::AutoScope::default(),
+            icons: vicaya_core::config::IconStyle::default(),
+            language: Default::default(),
+            archives: vicaya_core::config::ArchiveConfig::default(),
+            content_hash: vicaya_core::config::ContentHashConfig::default(),
+            index_bundle_contents: false,
+            index_backup_volumes: false,
+            index_directory_paths: false,
+            index_profile: Default::default(),
         }
     }
 
@@ -1620,7 +3937,7 @@ mod tests {
     }
 
     #[test]
-    fn smriti_boosts_preserve_engine_order_without_memory() {
+    fn usage_boosts_preserve_engine_order_without_memory() {
         let vicaya_dir = tempdir().unwrap();
         let root = tempdir().unwrap();
         std::fs::write(root.path().join("server.go"), "package main\n").unwrap();
@@ -1632,6 +3949,14 @@ mod tests {
                 score: 0.9,
                 size: 1,
                 mtime: 10,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+                total_size: None,
+                explain: None,
             },
             vicaya_index::SearchResult {
                 path: "/tmp/project/node_modules/server.go".to_string(),
@@ -1639,10 +3964,18 @@ mod tests {
                 score: 0.9,
                 size: 1,
                 mtime: 20,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+                total_size: None,
+                explain: None,
             },
         ];
 
-        apply_smriti_boosts(&state, &mut results, 2);
+        apply_usage_boosts(&state, &mut results, 2, false);
 
         assert_eq!(results[0].path, "/tmp/project/src/server.go");
         assert_eq!(results[1].path, "/tmp/project/node_modules/server.go");
@@ -1707,7 +4040,7 @@ mod tests {
         );
         assert_eq!(
             snapshot_path_for_id(&state.snapshot, file_id),
-            Some(to.to_string_lossy().as_ref())
+            Some(to.to_string_lossy().to_string())
         );
         assert_eq!(
             state.inode_to_id.get(&inode_key_for(&state, file_id)),
@@ -1716,44 +4049,415 @@ mod tests {
     }
 
     #[test]
-    fn move_path_tombstones_overwritten_destination_and_clears_inode_mapping() {
+    fn delete_then_create_of_the_same_inode_carries_smriti_usage() {
         let vicaya_dir = tempdir().unwrap();
         let root = tempdir().unwrap();
-
-        let from = root.path().join("from.txt");
-        let to = root.path().join("to.txt");
-        std::fs::write(&from, "from").unwrap();
-        std::fs::write(&to, "to").unwrap();
+        let from = root.path().join("draft.md");
+        let to = root.path().join("final.md");
+        std::fs::write(&from, "draft").unwrap();
 
         let mut state = build_state(root.path(), vicaya_dir.path());
-        let from_id = state.get_file_id_for_path(&from.to_string_lossy()).unwrap();
-        let overwritten_id = state.get_file_id_for_path(&to.to_string_lossy()).unwrap();
-        let overwritten_inode = inode_key_for(&state, overwritten_id);
+        state.smriti.record(
+            from.to_string_lossy().to_string(),
+            "draft".to_string(),
+            vicaya_core::smriti::SmritiAction::Open,
+            123,
+        );
+        state.name_trie = NameTrie::from_smriti(&state.smriti);
 
+        // The watcher reports the rename as a delete and, later, a create.
         std::fs::rename(&from, &to).unwrap();
-        state.apply_update(IndexUpdate::Move {
-            from: from.to_string_lossy().to_string(),
-            to: to.to_string_lossy().to_string(),
+        state.apply_update(IndexUpdate::Delete {
+            path: from.to_string_lossy().to_string(),
+        });
+        state.apply_update(IndexUpdate::Create {
+            path: to.to_string_lossy().to_string(),
         });
 
         assert!(state
             .get_file_id_for_path(&from.to_string_lossy())
             .is_none());
+        assert!(state.get_file_id_for_path(&to.to_string_lossy()).is_some());
+        assert!(!state
+            .smriti
+            .entries
+            .contains_key(from.to_string_lossy().as_ref()));
         assert_eq!(
-            state.get_file_id_for_path(&to.to_string_lossy()),
-            Some(from_id)
+            state.smriti.entries[to.to_string_lossy().as_ref()].total_count,
+            1
         );
-        assert_eq!(state.inode_to_id.get(&overwritten_inode), None);
+        assert!(state.vanished.is_empty());
+
+        let smriti_file = state.smriti_file.clone();
+        let state = Arc::new(RwLock::new(state));
+        persist_renamed_smriti(&state);
+        let saved = SmritiStore::load(&smriti_file).unwrap();
+        assert!(saved.entries.contains_key(to.to_string_lossy().as_ref()));
+        assert!(!state.read().unwrap().smriti_renamed);
+    }
 
-        let tombstoned = state.snapshot.file_table.get(overwritten_id).unwrap();
-        assert_eq!(tombstoned.path_len, 0);
-        assert_eq!(tombstoned.name_len, 0);
-        assert!(
-            !state.inode_to_id.values().any(|&id| id == overwritten_id),
+    #[test]
+    fn create_of_a_hard_link_keeps_the_original_entry() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let original = root.path().join("original.txt");
+        let link = root.path().join("link.txt");
+        std::fs::write(&original, "shared").unwrap();
+
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        let original_id = state
+            .get_file_id_for_path(&original.to_string_lossy())
+            .unwrap();
+
+        std::fs::hard_link(&original, &link).unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: link.to_string_lossy().to_string(),
+        });
+
+        assert_eq!(
+            state.get_file_id_for_path(&original.to_string_lossy()),
+            Some(original_id)
+        );
+        let link_id = state.get_file_id_for_path(&link.to_string_lossy()).unwrap();
+        assert_ne!(link_id, original_id);
+    }
+
+    #[test]
+    fn path_index_follows_moves_and_deletes() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let spartan = root.path().join("spartan");
+        let athens = root.path().join("athens");
+        std::fs::create_dir(&spartan).unwrap();
+        std::fs::create_dir(&athens).unwrap();
+        std::fs::write(spartan.join("plan.md"), "plan").unwrap();
+        std::fs::write(athens.join("notes.md"), "notes").unwrap();
+
+        let mut config = test_config(root.path(), vicaya_dir.path());
+        config.index_directory_paths = true;
+        std::fs::create_dir_all(&config.index_path).unwrap();
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        assert!(snapshot.path_index.is_some());
+        let mut state = DaemonState::new(
+            config,
+            vicaya_dir.path().join("index.bin"),
+            vicaya_dir.path().join("journal.log"),
+            snapshot,
+        );
+        let search = |state: &DaemonState, term: &str| -> Vec<String> {
+            let page = state.search(SearchOptions::new(term, 10), &ScorerRegistry::default());
+            page.results.into_iter().map(|result| result.path).collect()
+        };
+
+        assert_eq!(
+            search(&state, "spartan/plan"),
+            [spartan.join("plan.md").to_string_lossy()]
+        );
+        assert!(search(&state, "spartan/notes").is_empty());
+
+        std::fs::rename(athens.join("notes.md"), spartan.join("notes.md")).unwrap();
+        state.apply_update(IndexUpdate::Move {
+            from: athens.join("notes.md").to_string_lossy().to_string(),
+            to: spartan.join("notes.md").to_string_lossy().to_string(),
+        });
+        assert_eq!(
+            search(&state, "spartan/notes"),
+            [spartan.join("notes.md").to_string_lossy()]
+        );
+        assert!(search(&state, "athens/notes").is_empty());
+
+        std::fs::remove_file(spartan.join("plan.md")).unwrap();
+        state.apply_update(IndexUpdate::Delete {
+            path: spartan.join("plan.md").to_string_lossy().to_string(),
+        });
+        assert!(search(&state, "spartan/plan").is_empty());
+    }
+
+    #[test]
+    fn deleting_a_directory_drops_every_entry_below_it() {
+        for profile in [IndexProfile::Full, IndexProfile::Lite] {
+            let vicaya_dir = tempdir().unwrap();
+            let root = tempdir().unwrap();
+            let build = root.path().join("build");
+            std::fs::create_dir_all(build.join("out/empty")).unwrap();
+            std::fs::write(build.join("out/app.o"), "o").unwrap();
+            std::fs::write(build.join("log.txt"), "log").unwrap();
+            std::fs::write(root.path().join("build.rs"), "fn main() {}").unwrap();
+            let mut config = test_config(root.path(), vicaya_dir.path());
+            config.index_profile = profile;
+            std::fs::create_dir_all(&config.index_path).unwrap();
+            let snapshot = Scanner::new(config.clone()).scan().unwrap();
+            let mut state = DaemonState::new(
+                config,
+                vicaya_dir.path().join("index.bin"),
+                vicaya_dir.path().join("journal.log"),
+                snapshot,
+            );
+            let indexed = |state: &DaemonState, path: &Path| {
+                state
+                    .get_file_id_for_path(&path.to_string_lossy())
+                    .is_some()
+            };
+            assert!(indexed(&state, &build.join("out/app.o")));
+            let before = state.indexed_file_count();
+
+            // Only the directory itself is reported.
+            std::fs::remove_dir_all(&build).unwrap();
+            state.apply_update(IndexUpdate::Delete {
+                path: build.to_string_lossy().to_string(),
+            });
+            for path in ["build", "build/out", "build/out/empty", "build/out/app.o"] {
+                assert!(!indexed(&state, &root.path().join(path)), "{path}");
+            }
+            let removed = if profile == IndexProfile::Lite { 2 } else { 5 };
+            assert_eq!(state.indexed_file_count(), before - removed);
+            assert_eq!(state.dir_totals(&root.path().to_string_lossy()).files, 1);
+            let results = state
+                .search(SearchOptions::new("build", 10), &ScorerRegistry::default())
+                .results;
+            assert_eq!(
+                results
+                    .iter()
+                    .map(|result| result.name.as_str())
+                    .collect::<Vec<_>>(),
+                ["build.rs"]
+            );
+        }
+    }
+
+    #[test]
+    fn directory_sizes_follow_updates_and_serve_du_and_dirsize() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let big = root.path().join("big");
+        let small = root.path().join("small");
+        std::fs::create_dir_all(big.join("sub")).unwrap();
+        std::fs::create_dir(&small).unwrap();
+        std::fs::write(big.join("a.bin"), vec![0u8; 3_000]).unwrap();
+        std::fs::write(big.join("sub/b.bin"), vec![0u8; 1_000]).unwrap();
+        std::fs::write(small.join("c.txt"), "tiny").unwrap();
+
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        let totals = |state: &DaemonState, dir: &Path| {
+            let totals = state.dir_totals(&dir.to_string_lossy());
+            (totals.bytes, totals.files)
+        };
+        // The running totals always match a fresh sum of the table.
+        let assert_consistent = |state: &DaemonState| {
+            let fresh = DirSizes::build(&state.snapshot.file_table);
+            for dir in [root.path(), &big, &big.join("sub"), &small] {
+                let expected = state
+                    .snapshot
+                    .file_table
+                    .find_dir(&dir.to_string_lossy(), &state.snapshot.string_arena)
+                    .map(|id| fresh.of_dir(id))
+                    .unwrap_or_default();
+                assert_eq!(state.dir_totals(&dir.to_string_lossy()), expected);
+            }
+        };
+        assert_eq!(totals(&state, &big), (4_000, 2));
+        assert_eq!(totals(&state, root.path()), (4_004, 3));
+        assert_consistent(&state);
+
+        std::fs::rename(big.join("sub/b.bin"), small.join("b.bin")).unwrap();
+        state.apply_update(IndexUpdate::Move {
+            from: big.join("sub/b.bin").to_string_lossy().to_string(),
+            to: small.join("b.bin").to_string_lossy().to_string(),
+        });
+        std::fs::write(small.join("c.txt"), vec![0u8; 500]).unwrap();
+        state.apply_update(IndexUpdate::Modify {
+            path: small.join("c.txt").to_string_lossy().to_string(),
+        });
+        std::fs::write(small.join("new.bin"), vec![0u8; 2_500]).unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: small.join("new.bin").to_string_lossy().to_string(),
+        });
+        std::fs::remove_file(big.join("a.bin")).unwrap();
+        state.apply_update(IndexUpdate::Delete {
+            path: big.join("a.bin").to_string_lossy().to_string(),
+        });
+        assert_eq!(totals(&state, &big), (0, 0));
+        assert_eq!(totals(&state, &small), (4_000, 3));
+        assert_eq!(totals(&state, root.path()), (4_000, 3));
+        assert_consistent(&state);
+
+        let (total, entries) = state.dir_usage(&root.path().to_string_lossy(), 10).unwrap();
+        assert_eq!(total.bytes, 4_000);
+        let listed: Vec<(String, u64)> = entries
+            .into_iter()
+            .map(|(path, totals)| (path, totals.bytes))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                (small.to_string_lossy().to_string(), 4_000),
+                (big.to_string_lossy().to_string(), 0)
+            ]
+        );
+        assert!(state.dir_usage("/no/such/dir", 10).is_none());
+
+        // Directory results carry their totals, and `dirsize:` selects by them.
+        let sized = |bound: &str| -> Vec<(String, Option<u64>)> {
+            let page = state.search(
+                SearchOptions {
+                    filters: SearchFilters {
+                        dir_size: vicaya_core::query::parse_size_expr(bound),
+                        ..SearchFilters::default()
+                    },
+                    flags: SearchFlags {
+                        recent_if_empty: true,
+                        ..SearchFlags::default()
+                    },
+                    sort: SearchSort::Size,
+                    ..SearchOptions::new("", 10)
+                },
+                &ScorerRegistry::default(),
+            );
+            page.results
+                .into_iter()
+                .filter(|result| Path::new(&result.path) != root.path())
+                .map(|result| (result.name, result.total_size))
+                .collect()
+        };
+        assert_eq!(sized(">=1kb"), [("small".to_string(), Some(4_000))]);
+        let mut empty = sized("<1kb");
+        empty.sort();
+        assert_eq!(
+            empty,
+            [("big".to_string(), Some(0)), ("sub".to_string(), Some(0))]
+        );
+    }
+
+    #[test]
+    fn move_path_tombstones_overwritten_destination_and_clears_inode_mapping() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+
+        let from = root.path().join("from.txt");
+        let to = root.path().join("to.txt");
+        std::fs::write(&from, "from").unwrap();
+        std::fs::write(&to, "to").unwrap();
+
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        let from_id = state.get_file_id_for_path(&from.to_string_lossy()).unwrap();
+        let overwritten_id = state.get_file_id_for_path(&to.to_string_lossy()).unwrap();
+        let overwritten_inode = inode_key_for(&state, overwritten_id);
+
+        std::fs::rename(&from, &to).unwrap();
+        state.apply_update(IndexUpdate::Move {
+            from: from.to_string_lossy().to_string(),
+            to: to.to_string_lossy().to_string(),
+        });
+
+        assert!(state
+            .get_file_id_for_path(&from.to_string_lossy())
+            .is_none());
+        assert_eq!(
+            state.get_file_id_for_path(&to.to_string_lossy()),
+            Some(from_id)
+        );
+        assert_eq!(state.inode_to_id.get(&overwritten_inode), None);
+
+        let tombstoned = state.snapshot.file_table.get(overwritten_id).unwrap();
+        assert!(tombstoned.is_tombstone());
+        assert!(
+            !state.inode_to_id.values().any(|&id| id == overwritten_id),
             "overwritten destination should not survive in inode map"
         );
     }
 
+    #[test]
+    fn moves_into_the_trash_drop_entries_and_list_them_as_recently_deleted() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let trash = root.path().join(".Trash");
+        std::fs::create_dir_all(&trash).unwrap();
+        std::fs::create_dir_all(root.path().join("old/deep")).unwrap();
+        std::fs::write(root.path().join("old/deep/a.rs"), "a").unwrap();
+        std::fs::write(root.path().join("report.pdf"), "pdf").unwrap();
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        let indexed = |state: &DaemonState, path: &Path| {
+            state
+                .get_file_id_for_path(&path.to_string_lossy())
+                .is_some()
+        };
+        assert!(indexed(&state, &root.path().join("old/deep/a.rs")));
+
+        // Both ends reported: the directory and everything below it go.
+        std::fs::rename(root.path().join("old"), trash.join("old")).unwrap();
+        state.apply_update(IndexUpdate::Move {
+            from: root.path().join("old").to_string_lossy().to_string(),
+            to: trash.join("old").to_string_lossy().to_string(),
+        });
+        assert!(!indexed(&state, &root.path().join("old")));
+        assert!(!indexed(&state, &root.path().join("old/deep/a.rs")));
+        assert!(!indexed(&state, &trash.join("old")));
+
+        // Only the new path reported: the entry is found by inode.
+        std::fs::rename(root.path().join("report.pdf"), trash.join("report.pdf")).unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: trash.join("report.pdf").to_string_lossy().to_string(),
+        });
+        assert!(!indexed(&state, &root.path().join("report.pdf")));
+
+        let deleted = state.recently_deleted(None, 10);
+        let names: Vec<&str> = deleted.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["report.pdf", "old"]);
+        assert!(deleted[1].is_dir);
+        assert_eq!(
+            deleted[0].original_path,
+            root.path().join("report.pdf").to_string_lossy()
+        );
+        assert_eq!(state.recently_deleted(Some("OLD"), 10).len(), 1);
+
+        // Emptying the Trash empties the list.
+        std::fs::remove_file(trash.join("report.pdf")).unwrap();
+        assert_eq!(state.recently_deleted(None, 10).len(), 1);
+
+        state.config.trash.recently_deleted = 0;
+        std::fs::write(root.path().join("b.rs"), "b").unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: root.path().join("b.rs").to_string_lossy().to_string(),
+        });
+        std::fs::rename(root.path().join("b.rs"), trash.join("b.rs")).unwrap();
+        state.apply_update(IndexUpdate::Move {
+            from: root.path().join("b.rs").to_string_lossy().to_string(),
+            to: trash.join("b.rs").to_string_lossy().to_string(),
+        });
+        assert!(!indexed(&state, &root.path().join("b.rs")));
+        assert_eq!(state.recently_deleted(None, 10).len(), 1, "list disabled");
+    }
+
+    #[test]
+    fn creates_beyond_max_indexed_files_are_skipped_and_reported() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("kept.txt"), "").unwrap();
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        assert!(state.index_cap.is_none());
+        state.config.max_indexed_files = state.indexed_file_count();
+
+        let file = root.path().join("late.txt");
+        std::fs::write(&file, "").unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: file.to_string_lossy().to_string(),
+        });
+        assert!(state
+            .get_file_id_for_path(&file.to_string_lossy())
+            .is_none());
+        let cap = state.index_cap.as_ref().unwrap();
+        assert_eq!(cap.skipped_live, 1);
+
+        // Updates to entries already indexed still apply.
+        let kept = root.path().join("kept.txt");
+        std::fs::write(&kept, "grown").unwrap();
+        state.apply_update(IndexUpdate::Modify {
+            path: kept.to_string_lossy().to_string(),
+        });
+        let id = state.get_file_id_for_path(&kept.to_string_lossy()).unwrap();
+        assert_eq!(state.snapshot.file_table.get(id).unwrap().size, 5);
+    }
+
     #[test]
     fn apply_update_create_modify_delete_and_exclusions_keep_maps_consistent() {
         let vicaya_dir = tempdir().unwrap();
@@ -1792,7 +4496,110 @@ mod tests {
         assert!(state
             .get_file_id_for_path(&file.to_string_lossy())
             .is_none());
-        assert_eq!(state.snapshot.file_table.get(file_id).unwrap().path_len, 0);
+        assert!(state
+            .snapshot
+            .file_table
+            .get(file_id)
+            .unwrap()
+            .is_tombstone());
+    }
+
+    #[test]
+    fn watcher_paths_through_a_symlinked_root_reuse_indexed_entries() {
+        let vicaya_dir = tempdir().unwrap();
+        let dir = tempdir().unwrap();
+        let real = dir.path().join("real");
+        std::fs::create_dir(&real).unwrap();
+        std::fs::write(real.join("note.txt"), "one").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink(&real, &link).unwrap();
+        let real = std::fs::canonicalize(&real).unwrap();
+
+        let mut state = build_state(&link, vicaya_dir.path());
+        let indexed = link.join("note.txt");
+        let file_id = state
+            .get_file_id_for_path(&indexed.to_string_lossy())
+            .unwrap();
+        let count = state.indexed_file_count();
+
+        std::fs::write(real.join("note.txt"), "updated").unwrap();
+        state.apply_update(IndexUpdate::Modify {
+            path: format!("{}//note.txt", real.display()),
+        });
+        assert_eq!(state.indexed_file_count(), count);
+        assert_eq!(state.snapshot.file_table.get(file_id).unwrap().size, 7);
+
+        std::fs::write(real.join("new.txt"), "new").unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: real.join("new.txt").to_string_lossy().to_string(),
+        });
+        assert!(state
+            .get_file_id_for_path(&link.join("new.txt").to_string_lossy())
+            .is_some());
+        assert!(state
+            .get_file_id_for_path(&real.join("new.txt").to_string_lossy())
+            .is_none());
+
+        state.apply_update(IndexUpdate::Delete {
+            path: format!("{}/note.txt/", real.display()),
+        });
+        assert!(state
+            .get_file_id_for_path(&indexed.to_string_lossy())
+            .is_none());
+    }
+
+    #[test]
+    fn apply_update_honors_negated_exclusions() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        state.config.exclusions = vec!["target".to_string(), "!target/doc".to_string()];
+
+        let kept = root.path().join("target").join("doc").join("index.html");
+        let dropped = root.path().join("target").join("debug").join("app");
+        for path in [&kept, &dropped] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "x").unwrap();
+            state.apply_update(IndexUpdate::Create {
+                path: path.to_string_lossy().to_string(),
+            });
+        }
+
+        assert!(state
+            .get_file_id_for_path(&kept.to_string_lossy())
+            .is_some());
+        assert!(state
+            .get_file_id_for_path(&dropped.to_string_lossy())
+            .is_none());
+    }
+
+    #[test]
+    fn apply_update_honors_case_insensitive_exclusions() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let mut state = build_state(root.path(), vicaya_dir.path());
+        state.config.exclusions.push("build".to_string());
+
+        let upper = root.path().join("Build").join("out.o");
+        std::fs::create_dir_all(upper.parent().unwrap()).unwrap();
+        std::fs::write(&upper, "obj").unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: upper.to_string_lossy().to_string(),
+        });
+        assert!(state
+            .get_file_id_for_path(&upper.to_string_lossy())
+            .is_some());
+
+        state.config.case_insensitive_exclusions = true;
+        let shouting = root.path().join("BUILD").join("out.o");
+        std::fs::create_dir_all(shouting.parent().unwrap()).unwrap();
+        std::fs::write(&shouting, "obj").unwrap();
+        state.apply_update(IndexUpdate::Create {
+            path: shouting.to_string_lossy().to_string(),
+        });
+        assert!(state
+            .get_file_id_for_path(&shouting.to_string_lossy())
+            .is_none());
     }
 
     #[test]
@@ -1827,10 +4634,12 @@ mod tests {
         assert!(state
             .get_file_id_for_path(&excluded.to_string_lossy())
             .is_none());
-        assert_eq!(
-            state.snapshot.file_table.get(created_id).unwrap().path_len,
-            0
-        );
+        assert!(state
+            .snapshot
+            .file_table
+            .get(created_id)
+            .unwrap()
+            .is_tombstone());
     }
 
     #[test]
@@ -1854,119 +4663,1623 @@ mod tests {
         )
         .unwrap();
 
-        let mut applied = Vec::new();
-        let count = apply_journal_from_offset(&journal, offset, |update| applied.push(update));
-        assert_eq!(count, 1);
-        assert!(matches!(applied[0], IndexUpdate::Delete { .. }));
-
-        truncate_journal(&journal).unwrap();
-        assert_eq!(std::fs::metadata(&journal).unwrap().len(), 0);
+        let applied = read_journal_range(&journal, offset, None);
+        assert_eq!(applied.len(), 1);
+        assert!(matches!(applied[0], IndexUpdate::Delete { .. }));
+
+        truncate_journal(&journal).unwrap();
+        assert_eq!(std::fs::metadata(&journal).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn journal_range_replay_stops_at_end_offset() {
+        let dir = tempdir().unwrap();
+        let journal = dir.path().join("index.journal");
+        let lines: Vec<String> = ["one", "two", "three"]
+            .iter()
+            .map(|name| {
+                serde_json::to_string(&IndexUpdate::Create {
+                    path: format!("/tmp/{name}.txt"),
+                })
+                .unwrap()
+            })
+            .collect();
+        std::fs::write(&journal, format!("{}\n", lines.join("\n"))).unwrap();
+        let start = lines[0].len() as u64 + 1;
+        let end = start + lines[1].len() as u64 + 1;
+
+        let applied = read_journal_range(&journal, start, Some(end));
+        assert_eq!(applied.len(), 1);
+        assert!(matches!(&applied[0], IndexUpdate::Create { path } if path == "/tmp/two.txt"));
+
+        let tail = read_journal_range(&journal, end, None);
+        assert_eq!(tail.len(), 1);
+    }
+
+    #[test]
+    fn batched_journal_replay_matches_one_by_one_application() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let journal = vicaya_dir.path().join("replay.journal");
+        let path_of = |name: &str| root.path().join(name).to_string_lossy().to_string();
+
+        let mut updates = Vec::new();
+        for round in 0..50 {
+            for name in ["keep.txt", "churn.txt", "gone.txt"] {
+                updates.push(IndexUpdate::Modify {
+                    path: path_of(name),
+                });
+            }
+            if round % 10 == 0 {
+                updates.push(IndexUpdate::Delete {
+                    path: path_of("churn.txt"),
+                });
+            }
+        }
+        updates.push(IndexUpdate::Move {
+            from: path_of("keep.txt"),
+            to: path_of("kept.txt"),
+        });
+        updates.push(IndexUpdate::Delete {
+            path: path_of("gone.txt"),
+        });
+        let lines: Vec<String> = updates
+            .iter()
+            .map(|update| serde_json::to_string(update).unwrap())
+            .collect();
+        std::fs::write(&journal, format!("{}\n", lines.join("\n"))).unwrap();
+
+        let mut sequential = build_state(root.path(), vicaya_dir.path());
+        let mut batched = build_state(root.path(), vicaya_dir.path());
+        let mut streamed = build_state(root.path(), vicaya_dir.path());
+        std::fs::write(root.path().join("churn.txt"), "churn").unwrap();
+        std::fs::write(root.path().join("kept.txt"), "kept").unwrap();
+
+        for update in read_journal_range(&journal, 0, None) {
+            sequential.apply_update(update);
+        }
+        let read = replay_journal_range(&mut batched, &journal, 0, None);
+        assert_eq!(read, updates.len());
+        // A journal longer than one replay chunk streams through in pieces.
+        let mut chunks = Vec::new();
+        for_each_journal_chunk(&journal, 0, None, 7, |chunk| chunks.push(chunk.len()));
+        assert!(chunks.iter().all(|&len| len <= 7));
+        assert_eq!(chunks.iter().sum::<usize>(), updates.len());
+        let read = replay_journal_chunks(&mut streamed, &journal, 0, None, 7);
+        assert_eq!(read, updates.len());
+
+        let live_paths = |state: &DaemonState| {
+            let mut paths: Vec<String> = state
+                .snapshot
+                .file_table
+                .iter()
+                .filter(|(_, meta)| !meta.is_tombstone())
+                .filter_map(|(_, meta)| {
+                    state
+                        .snapshot
+                        .file_table
+                        .path_of(meta, &state.snapshot.string_arena)
+                })
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(live_paths(&batched), live_paths(&sequential));
+        assert_eq!(live_paths(&streamed), live_paths(&sequential));
+        assert!(batched
+            .get_file_id_for_path(&path_of("churn.txt"))
+            .is_some());
+        assert!(batched.get_file_id_for_path(&path_of("kept.txt")).is_some());
+        assert!(batched.get_file_id_for_path(&path_of("gone.txt")).is_none());
+
+        let recent_paths = |state: &DaemonState| -> Vec<String> {
+            state
+                .recent_updates
+                .iter()
+                .filter_map(|&id| snapshot_path_for_id(&state.snapshot, id))
+                .collect()
+        };
+        assert_eq!(recent_paths(&batched), recent_paths(&sequential));
+        assert_eq!(recent_paths(&streamed), recent_paths(&sequential));
+    }
+
+    #[test]
+    fn full_rebuild_persists_snapshot_and_replays_pending_journal() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let (index_file, journal_file) = {
+            let state = state.read().unwrap();
+            (state.index_file.clone(), state.journal_file.clone())
+        };
+        let late = root.path().join("late.rs");
+        std::fs::write(&late, "").unwrap();
+        let entry = serde_json::to_string(&IndexUpdate::Create {
+            path: late.to_string_lossy().to_string(),
+        })
+        .unwrap();
+        std::fs::write(&journal_file, format!("{entry}\n")).unwrap();
+
+        full_rebuild_from_disk(
+            &state,
+            &Arc::new(Mutex::new(())),
+            &Arc::new(Mutex::new(())),
+            ScanProfile::Reconcile,
+        )
+        .unwrap();
+
+        assert_eq!(std::fs::metadata(&journal_file).unwrap().len(), 0);
+        let persisted = IndexSnapshot::load(&index_file).unwrap();
+        assert!(persisted.file_table.len() >= 2);
+        let state = state.read().unwrap();
+        assert!(state
+            .get_file_id_for_path(&late.to_string_lossy())
+            .is_some());
+    }
+
+    #[test]
+    fn full_rebuild_keeps_journal_appended_after_the_snapshot_was_saved() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let (config, index_file, journal_file, activity) = {
+            let state = state.read().unwrap();
+            (
+                state.config.clone(),
+                state.index_file.clone(),
+                state.journal_file.clone(),
+                Arc::clone(&state.activity),
+            )
+        };
+        let create = |name: &str| {
+            let path = root.path().join(name);
+            std::fs::write(&path, "").unwrap();
+            IndexUpdate::Create {
+                path: path.to_string_lossy().to_string(),
+            }
+        };
+        let journal_lock = Arc::new(Mutex::new(()));
+
+        // Journaled during the scan: replayed before the snapshot is saved.
+        append_journal(&journal_file, &[create("early.rs")]).unwrap();
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let mut rebuilt = DaemonState::rebuilt(
+            config.clone(),
+            index_file.clone(),
+            journal_file.clone(),
+            snapshot,
+        );
+        let caught_up_to = journal_len(&journal_file);
+        replay_journal_range(&mut rebuilt, &journal_file, 0, Some(caught_up_to));
+        rebuilt.snapshot.save(&index_file).unwrap();
+
+        // Journaled while the snapshot was being written.
+        append_journal(&journal_file, &[create("late.rs")]).unwrap();
+        let tail = finish_rebuild(&state, &journal_lock, &activity, rebuilt, caught_up_to).unwrap();
+        assert_eq!(tail, 1);
+        assert_eq!(activity.journal_entries(), 1);
+        assert!(state
+            .read()
+            .unwrap()
+            .get_file_id_for_path(&root.path().join("late.rs").to_string_lossy())
+            .is_some());
+
+        // A restart loads index.bin and replays what is left of the journal.
+        let mut reloaded = DaemonState::new(
+            config,
+            index_file.clone(),
+            journal_file.clone(),
+            IndexSnapshot::load(&index_file).unwrap(),
+        );
+        assert_eq!(
+            replay_journal_range(&mut reloaded, &journal_file, 0, None),
+            1
+        );
+        for name in ["early.rs", "late.rs"] {
+            assert!(
+                reloaded
+                    .get_file_id_for_path(&root.path().join(name).to_string_lossy())
+                    .is_some(),
+                "{name} lost across the restart"
+            );
+        }
+    }
+
+    #[test]
+    fn full_rebuild_refreshes_last_updated_after_swap() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        state.write().unwrap().last_updated = 0;
+        let retirement_probe = retirement_probe();
+        state.write().unwrap().retirement_probe = Some(Arc::clone(&retirement_probe));
+        let retired_before = retirement_probe.load(std::sync::atomic::Ordering::SeqCst);
+
+        let files_indexed = full_rebuild_from_disk(
+            &state,
+            &Arc::new(Mutex::new(())),
+            &Arc::new(Mutex::new(())),
+            ScanProfile::Reconcile,
+        )
+        .unwrap();
+
+        assert!(files_indexed >= 1);
+        let state = state.read().unwrap();
+        assert!(state.last_updated > 0);
+        assert!(!state.reconciling);
+        drop(state);
+        wait_for_retired_state_drop(&retirement_probe, retired_before);
+    }
+
+    #[test]
+    fn full_rebuild_keeps_activity_and_resets_journal_depth() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let activity = Arc::clone(&state.read().unwrap().activity);
+        activity.journal_appended(12);
+        activity.watcher_events_queued(3);
+
+        full_rebuild_from_disk(
+            &state,
+            &Arc::new(Mutex::new(())),
+            &Arc::new(Mutex::new(())),
+            ScanProfile::Reconcile,
+        )
+        .unwrap();
+
+        let state = state.read().unwrap();
+        assert!(Arc::ptr_eq(&state.activity, &activity));
+        assert_eq!(activity.journal_entries(), 0);
+        assert_eq!(activity.pending_watcher_events(), 3);
+        assert_eq!(activity.reconcile_progress(), None);
+    }
+
+    #[test]
+    fn daemon_activity_progress_is_capped_and_pending_never_underflows() {
+        let activity = DaemonActivity::default();
+        assert_eq!(activity.reconcile_progress(), None);
+
+        let scan = ResolvedScan {
+            profile: ScanProfile::Reconcile,
+            threads: 1,
+            throttle_ms: 5,
+        };
+        activity.begin_reconcile(200, scan);
+        activity.reconcile_scanned(84);
+        assert_eq!(activity.reconcile_progress(), Some(42));
+        activity.reconcile_scanned(500);
+        assert_eq!(activity.reconcile_progress(), Some(99));
+        assert_eq!(activity.reconcile_scan(), Some(scan));
+        activity.end_reconcile();
+        assert_eq!(activity.reconcile_progress(), None);
+        assert_eq!(activity.reconcile_scan(), None);
+
+        activity.watcher_events_queued(2);
+        activity.watcher_events_applied(5);
+        assert_eq!(activity.pending_watcher_events(), 0);
+    }
+
+    #[test]
+    fn warm_up_visits_common_postings_and_reports_duration_in_status() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        assert!(state.read().unwrap().warm_up(8) > 0);
+        assert_eq!(state.read().unwrap().warm_up(0), 0);
+
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let warmup_ms = || match server.handle_request(Request::Status) {
+            Response::Status(DaemonStatus { warmup_ms, .. }) => warmup_ms,
+            other => panic!("unexpected status response: {other:?}"),
+        };
+        assert_eq!(warmup_ms(), None);
+        state.read().unwrap().activity.warmup_finished(7);
+        state.read().unwrap().activity.warmup_finished(9);
+        assert_eq!(warmup_ms(), Some(7));
+    }
+
+    #[test]
+    fn ping_is_ready_once_warm_up_finishes_or_when_it_is_disabled() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let ping = || match server.handle_request(Request::Ping) {
+            Response::Pong {
+                ready,
+                indexed_files,
+                ..
+            } => (ready, indexed_files),
+            other => panic!("unexpected ping response: {other:?}"),
+        };
+
+        state.write().unwrap().config.performance.warmup_trigrams = 0;
+        assert_eq!(ping(), (true, 2));
+        state.write().unwrap().config.performance.warmup_trigrams = 8;
+        assert_eq!(ping(), (false, 2));
+        state.read().unwrap().activity.warmup_finished(3);
+        assert_eq!(ping(), (true, 2));
+    }
+
+    #[test]
+    fn root_stats_split_entries_between_index_roots() {
+        let vicaya_dir = tempdir().unwrap();
+        let parent = tempdir().unwrap();
+        let (code, docs) = (parent.path().join("code"), parent.path().join("docs"));
+        std::fs::create_dir_all(code.join("src")).unwrap();
+        std::fs::create_dir_all(&docs).unwrap();
+        std::fs::write(code.join("src/main.rs"), vec![b'x'; 30]).unwrap();
+        std::fs::write(code.join("Cargo.toml"), vec![b'x'; 10]).unwrap();
+        std::fs::write(docs.join("notes.md"), vec![b'x'; 5]).unwrap();
+
+        let mut config = test_config(&code, vicaya_dir.path());
+        config.index_roots = vec![code.clone(), docs.clone()];
+        std::fs::create_dir_all(&config.index_path).unwrap();
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let mut state = DaemonState::new(
+            config,
+            vicaya_dir.path().join("index.bin"),
+            vicaya_dir.path().join("journal.log"),
+            snapshot,
+        );
+        state.root_scans.insert(code.clone(), 1_700_000_000);
+
+        let stats = state.root_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].root, code.to_string_lossy());
+        assert_eq!((stats[0].files, stats[0].dirs), (4, 2));
+        assert_eq!(stats[0].last_scan, Some(1_700_000_000));
+        assert_eq!(stats[1].root, docs.to_string_lossy());
+        assert_eq!((stats[1].files, stats[1].dirs), (2, 1));
+        assert_eq!(stats[1].last_scan, None);
+        assert!(stats[0].memory_bytes > stats[1].memory_bytes);
+        assert_eq!(
+            stats.iter().map(|root| root.files).sum::<usize>(),
+            state.indexed_file_count()
+        );
+
+        let main_rs = code.join("src/main.rs").to_string_lossy().to_string();
+        state.apply_update(IndexUpdate::Delete { path: main_rs });
+        assert_eq!(state.root_stats()[0].files, 3);
+    }
+
+    #[test]
+    fn replace_state_retires_old_state_without_leaking_it() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let rebuilt = build_state(root.path(), vicaya_dir.path());
+        let retirement_probe = retirement_probe();
+        state.write().unwrap().retirement_probe = Some(Arc::clone(&retirement_probe));
+        let before = retirement_probe.load(std::sync::atomic::Ordering::SeqCst);
+
+        replace_state(&state, rebuilt);
+        wait_for_retired_state_drop(&retirement_probe, before);
+    }
+
+    #[test]
+    fn ipc_server_handle_request_covers_status_search_rebuild_and_shutdown() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let cargo = root.path().join("Cargo.toml");
+        std::fs::write(&cargo, "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let journal_lock = Arc::new(Mutex::new(()));
+        let rebuild_lock = Arc::new(Mutex::new(()));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server =
+            IpcServer::new(&socket, state, shutdown.clone(), journal_lock, rebuild_lock).unwrap();
+
+        match server.handle_request(Request::Status) {
+            Response::Status(DaemonStatus {
+                indexed_files,
+                trigram_count,
+                ..
+            }) => {
+                assert!(indexed_files >= 1);
+                assert!(trigram_count > 0);
+            }
+            other => panic!("unexpected status response: {other:?}"),
+        }
+
+        match server.handle_request(Request::Search(SearchOptions {
+            scope: Some(root.path().to_string_lossy().to_string()),
+            filters: SearchFilters {
+                scope: Some(root.path().to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("Cargo", 10)
+        })) {
+            Response::SearchResults { results, .. } => {
+                assert_eq!(results.len(), 1);
+                assert_eq!(results[0].path, cargo.to_string_lossy());
+            }
+            other => panic!("unexpected search response: {other:?}"),
+        }
+
+        match server.handle_request(Request::Search(SearchOptions {
+            filters: SearchFilters {
+                scope: Some(root.path().to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            flags: SearchFlags {
+                recent_if_empty: true,
+                ..SearchFlags::default()
+            },
+            ..SearchOptions::new("", 10)
+        })) {
+            Response::SearchResults { results, .. } => {
+                assert!(results.iter().any(|r| r.path == cargo.to_string_lossy()))
+            }
+            other => panic!("unexpected recent response: {other:?}"),
+        }
+
+        match server.handle_request(Request::Rebuild { dry_run: true }) {
+            Response::RebuildComplete { files_indexed } => assert!(files_indexed >= 1),
+            other => panic!("unexpected rebuild response: {other:?}"),
+        }
+
+        match server.handle_request(Request::Metrics) {
+            Response::Metrics {
+                process_cpu_ns,
+                subsystems,
+                ..
+            } => {
+                let spans = |subsystem| {
+                    subsystems
+                        .iter()
+                        .find(|usage| usage.subsystem == subsystem)
+                        .map(|usage| usage.spans)
+                };
+                assert_eq!(spans(Subsystem::Query), Some(2));
+                assert_eq!(spans(Subsystem::Scan), Some(1), "dry-run rebuild scans");
+                assert_eq!(spans(Subsystem::Watcher), Some(0));
+                let attributed: u64 = subsystems.iter().map(|usage| usage.cpu_ns).sum();
+                assert!(process_cpu_ns >= attributed);
+            }
+            other => panic!("unexpected metrics response: {other:?}"),
+        }
+
+        assert!(matches!(
+            server.handle_request(Request::Shutdown),
+            Response::Ok
+        ));
+        assert!(shutdown.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn upgrade_request_accepts_only_daemon_binaries_and_releases_the_socket() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            state,
+            shutdown.clone(),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+
+        let impostor = vicaya_dir.path().join("impostor");
+        std::fs::write(&impostor, "#!/bin/sh\necho impostor 1.0\n").unwrap();
+        std::fs::set_permissions(&impostor, std::fs::Permissions::from_mode(0o755)).unwrap();
+        assert!(matches!(
+            server.handle_request(Request::Upgrade {
+                binary: Some(impostor.to_string_lossy().to_string()),
+            }),
+            Response::Error { .. }
+        ));
+        assert!(server.upgrade_binary().is_none());
+        assert!(socket.exists());
+
+        let daemon = vicaya_dir.path().join("vicaya-daemon");
+        std::fs::write(
+            &daemon,
+            "#!/bin/sh\necho 'vicaya-daemon 9.9.9 (rev test)'\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&daemon, std::fs::Permissions::from_mode(0o755)).unwrap();
+        match server.handle_request(Request::Upgrade {
+            binary: Some(daemon.to_string_lossy().to_string()),
+        }) {
+            Response::Upgrading { binary, version } => {
+                assert_eq!(binary, daemon.to_string_lossy());
+                assert_eq!(version, "vicaya-daemon 9.9.9 (rev test)");
+            }
+            other => panic!("unexpected upgrade response: {other:?}"),
+        }
+        assert_eq!(server.upgrade_binary(), Some(daemon));
+        assert!(!socket.exists(), "new clients wait for the new daemon");
+        // Shutdown starts once the reply has been written to the client.
+        assert!(!shutdown.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn exclude_request_drops_matching_entries_and_future_updates() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let derived = root.path().join("DerivedData");
+        std::fs::create_dir_all(derived.join("Build")).unwrap();
+        std::fs::write(derived.join("Build/app.o"), "").unwrap();
+        std::fs::write(root.path().join("app.swift"), "").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+
+        assert!(matches!(
+            server.handle_request(Request::Exclude {
+                patterns: vec!["**".to_string()],
+            }),
+            Response::Error { .. }
+        ));
+        match server.handle_request(Request::Exclude {
+            patterns: vec!["DerivedData/".to_string()],
+        }) {
+            Response::Excluded { removed } => assert_eq!(removed, 3),
+            other => panic!("unexpected exclude response: {other:?}"),
+        }
+        assert!(matches!(
+            server.handle_request(Request::Exclude {
+                patterns: vec!["DerivedData".to_string()],
+            }),
+            Response::Excluded { removed: 0 }
+        ));
+
+        let state = state.read().unwrap();
+        assert!(state
+            .get_file_id_for_path(&derived.join("Build/app.o").to_string_lossy())
+            .is_none());
+        assert!(state
+            .get_file_id_for_path(&root.path().join("app.swift").to_string_lossy())
+            .is_some());
+        assert!(!vicaya_scanner::should_index_path(
+            &state.config,
+            &derived.join("Build/new.o"),
+            false
+        ));
+    }
+
+    #[test]
+    fn reconcile_requests_queue_once_and_status_reports_the_last_run() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("a.txt"), "a").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let activity = Arc::clone(&state.read().unwrap().activity);
+
+        assert!(matches!(
+            server.handle_request(Request::Reconcile),
+            Response::ReconcileRequested {
+                already_running: false
+            }
+        ));
+        assert!(activity.take_reconcile_request());
+        assert!(!activity.take_reconcile_request());
+
+        state.write().unwrap().reconciling = true;
+        assert!(matches!(
+            server.handle_request(Request::Reconcile),
+            Response::ReconcileRequested {
+                already_running: true
+            }
+        ));
+        assert!(!activity.take_reconcile_request());
+        state.write().unwrap().reconciling = false;
+
+        full_rebuild_from_disk(
+            &state,
+            &Arc::new(Mutex::new(())),
+            &Arc::new(Mutex::new(())),
+            ScanProfile::Reconcile,
+        )
+        .unwrap();
+        activity.set_next_reconcile(Some(4_000_000_000));
+        match server.handle_request(Request::Status) {
+            Response::Status(DaemonStatus {
+                last_reconcile,
+                next_reconcile,
+                ..
+            }) => {
+                assert!(last_reconcile.is_some_and(|at| at > 0));
+                assert_eq!(next_reconcile, Some(4_000_000_000));
+            }
+            other => panic!("unexpected status response: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn report_missing_tombstones_only_entries_gone_from_disk() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let ghost = root.path().join("ghost.txt");
+        let kept = root.path().join("kept.txt");
+        std::fs::write(&ghost, "boo").unwrap();
+        std::fs::write(&kept, "here").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let report = |path: &Path| {
+            server.handle_request(Request::ReportMissing {
+                path: path.to_string_lossy().to_string(),
+            })
+        };
+
+        // A client cannot drop entries that still exist.
+        assert!(matches!(
+            report(&kept),
+            Response::MissingReported { removed: false }
+        ));
+        std::fs::remove_file(&ghost).unwrap();
+        assert!(matches!(
+            report(&ghost),
+            Response::MissingReported { removed: true }
+        ));
+        assert!(matches!(
+            report(&ghost),
+            Response::MissingReported { removed: false }
+        ));
+
+        match server.handle_request(Request::Search(SearchOptions::new("txt", 10))) {
+            Response::SearchResults { results, .. } => {
+                let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+                assert_eq!(names, vec!["kept.txt"]);
+            }
+            other => panic!("unexpected search response: {other:?}"),
+        }
+        let state = state.read().unwrap();
+        assert_eq!(state.activity.journal_entries(), 1);
+        let journaled = read_journal_range(&state.journal_file, 0, None);
+        assert!(matches!(
+            journaled.as_slice(),
+            [IndexUpdate::Delete { path }] if *path == ghost.to_string_lossy()
+        ));
+    }
+
+    #[test]
+    fn search_attributes_filter_on_indexed_owner_and_mode() {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+        use vicaya_core::permissions::AttributeFilter;
+
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        for (name, mode) in [
+            ("deploy.sh", 0o755),
+            ("deploy.txt", 0o444),
+            ("deploy.md", 0o644),
+        ] {
+            let path = root.path().join(name);
+            std::fs::write(&path, "x").unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+        }
+        let me = std::fs::metadata(root.path()).unwrap().uid().to_string();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let names = |query: &str, attributes: AttributeFilter| -> Vec<String> {
+            match server.handle_request(Request::Search(SearchOptions {
+                filters: SearchFilters {
+                    scope: Some(root.path().to_string_lossy().to_string()),
+                    attributes,
+                    max_depth: None,
+                    hidden: None,
+                    dir_size: None,
+                },
+                flags: SearchFlags {
+                    recent_if_empty: true,
+                    ..SearchFlags::default()
+                },
+                ..SearchOptions::new(query, 10)
+            })) {
+                Response::SearchResults { results, .. } => {
+                    let mut names: Vec<String> = results.into_iter().map(|r| r.name).collect();
+                    names.sort();
+                    names
+                }
+                other => panic!("unexpected search response: {other:?}"),
+            }
+        };
+
+        let executable = AttributeFilter {
+            executable: Some(true),
+            ..AttributeFilter::default()
+        };
+        let readonly = AttributeFilter {
+            readonly: Some(true),
+            ..AttributeFilter::default()
+        };
+        assert_eq!(names("deploy", executable.clone()), vec!["deploy.sh"]);
+        assert_eq!(names("", executable), vec!["deploy.sh"]);
+        assert_eq!(names("deploy", readonly.clone()), vec!["deploy.txt"]);
+        assert!(names("deploy.md", readonly).is_empty());
+
+        let owner = |owner: &str| AttributeFilter {
+            owner: Some(owner.to_string()),
+            ..AttributeFilter::default()
+        };
+        assert_eq!(names("deploy", owner(&me)).len(), 3);
+        assert!(names("deploy", owner("no-such-user-vicaya")).is_empty());
+    }
+
+    #[test]
+    fn content_fingerprints_find_duplicates_and_follow_updates() {
+        use vicaya_core::permissions::AttributeFilter;
+
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("a.txt"), "same").unwrap();
+        std::fs::write(root.path().join("b.txt"), "same").unwrap();
+        std::fs::write(root.path().join("c.txt"), "other").unwrap();
+
+        let config = Config {
+            content_hash: vicaya_core::config::ContentHashConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            ..test_config(root.path(), vicaya_dir.path())
+        };
+        std::fs::create_dir_all(&config.index_path).unwrap();
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let state = Arc::new(RwLock::new(DaemonState::new(
+            config,
+            vicaya_dir.path().join("index.bin"),
+            vicaya_dir.path().join("journal.log"),
+            snapshot,
+        )));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            state.clone(),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let search = |query: &str, content_hash: Option<String>| -> Vec<(String, String)> {
+            match server.handle_request(Request::Search(SearchOptions {
+                filters: SearchFilters {
+                    attributes: AttributeFilter {
+                        content_hash,
+                        ..AttributeFilter::default()
+                    },
+                    ..SearchFilters::default()
+                },
+                flags: SearchFlags {
+                    recent_if_empty: true,
+                    ..SearchFlags::default()
+                },
+                ..SearchOptions::new(query, 10)
+            })) {
+                Response::SearchResults { results, .. } => {
+                    let mut found: Vec<(String, String)> = results
+                        .into_iter()
+                        .filter(|r| !r.is_dir)
+                        .map(|r| (r.name, r.content_hash.unwrap_or_default()))
+                        .collect();
+                    found.sort();
+                    found
+                }
+                other => panic!("unexpected search response: {other:?}"),
+            }
+        };
+
+        let all = search(".txt", None);
+        assert_eq!(all.len(), 3);
+        let same = all[0].1.clone();
+        assert_eq!(same.len(), vicaya_core::content_hash::HEX_LEN);
+        assert_eq!(all[1].1, same);
+        assert_ne!(all[2].1, same);
+
+        let duplicates = |prefix: &str| -> Vec<String> {
+            search("", Some(prefix.to_string()))
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+        assert_eq!(duplicates(&same[..6]), vec!["a.txt", "b.txt"]);
+
+        let c = root.path().join("c.txt");
+        std::fs::write(&c, "same").unwrap();
+        state.write().unwrap().apply_update(IndexUpdate::Modify {
+            path: c.to_string_lossy().to_string(),
+        });
+        assert_eq!(duplicates(&same), vec!["a.txt", "b.txt", "c.txt"]);
+        assert!(duplicates("zz").is_empty());
+    }
+
+    #[test]
+    fn searches_rank_with_the_named_scorer() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let old = root.path().join("notes.md");
+        let new = root.path().join("meeting_notes_archive.txt");
+        std::fs::write(&old, "old").unwrap();
+        std::fs::write(&new, "new").unwrap();
+        let hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&old)
+            .unwrap()
+            .set_modified(hour_ago)
+            .unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let names =
+            |scorer: Option<&str>| match server.handle_request(Request::Search(SearchOptions {
+                scorer: scorer.map(str::to_string),
+                ..SearchOptions::new("notes", 10)
+            })) {
+                Response::SearchResults { results, .. } => {
+                    results.into_iter().map(|r| r.name).collect::<Vec<String>>()
+                }
+                other => panic!("unexpected search response: {other:?}"),
+            };
+
+        assert_eq!(names(None), ["notes.md", "meeting_notes_archive.txt"]);
+        assert_eq!(
+            names(Some("recency")),
+            ["meeting_notes_archive.txt", "notes.md"]
+        );
+        match server.handle_request(Request::SearchBatch {
+            queries: vec![
+                SearchOptions::new("notes", 10),
+                SearchOptions {
+                    scorer: Some("frecency".to_string()),
+                    ..SearchOptions::new("notes", 10)
+                },
+            ],
+            total_limit: None,
+        }) {
+            Response::Error { message } => {
+                assert!(message.contains("'frecency'"), "{message}");
+                assert!(message.contains("relevance, recency"), "{message}");
+            }
+            other => panic!("unexpected batch response: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn searches_with_malformed_niyamas_are_rejected() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("report.txt"), "r").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+
+        match server.handle_request(Request::Search(SearchOptions::new("report size:>10xb", 10))) {
+            Response::Error { message } => {
+                assert!(message.contains("`size:>10xb`"), "{message}");
+            }
+            other => panic!("unexpected search response: {other:?}"),
+        }
+        match server.handle_request(Request::SearchBatch {
+            queries: vec![
+                SearchOptions::new("report", 10),
+                SearchOptions::new("report type:pipe", 10),
+            ],
+            total_limit: None,
+        }) {
+            Response::Error { message } => {
+                assert!(message.contains("`type:pipe`"), "{message}");
+            }
+            other => panic!("unexpected batch response: {other:?}"),
+        }
+        assert!(matches!(
+            server.handle_request(Request::Search(SearchOptions::new("report", 10))),
+            Response::SearchResults { .. }
+        ));
+    }
+
+    #[test]
+    fn search_batch_answers_each_query_in_order_within_the_total_limit() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        for name in [
+            "alpha_1.rs",
+            "alpha_2.rs",
+            "alpha_3.rs",
+            "beta_1.rs",
+            "beta_2.rs",
+        ] {
+            std::fs::write(root.path().join(name), "").unwrap();
+        }
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let batch = |queries: &[&str], total_limit: Option<usize>| match server.handle_request(
+            Request::SearchBatch {
+                queries: queries
+                    .iter()
+                    .map(|term| SearchOptions::new(*term, 10))
+                    .collect(),
+                total_limit,
+            },
+        ) {
+            Response::SearchBatchResults { batches } => batches
+                .into_iter()
+                .map(|batch| (batch.results.len(), batch.truncated))
+                .collect::<Vec<_>>(),
+            other => panic!("unexpected batch response: {other:?}"),
+        };
+
+        assert_eq!(
+            batch(&["alpha_", "beta_", "gamma_"], None),
+            vec![(3, false), (2, false), (0, false)]
+        );
+        // The total is spent in query order.
+        assert_eq!(
+            batch(&["beta_", "alpha_", "beta_"], Some(4)),
+            vec![(2, false), (2, true), (0, true)]
+        );
+
+        let too_many = vec![SearchOptions::new("alpha", 1); MAX_SEARCH_BATCH + 1];
+        assert!(matches!(
+            server.handle_request(Request::SearchBatch {
+                queries: too_many,
+                total_limit: None,
+            }),
+            Response::Error { .. }
+        ));
+    }
+
+    #[test]
+    fn search_options_page_sort_and_disable_fuzzy_matching() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        for (name, size) in [
+            ("report_a.txt", 30),
+            ("report_b.txt", 10),
+            ("report_c.txt", 20),
+        ] {
+            std::fs::write(root.path().join(name), "x".repeat(size)).unwrap();
+        }
+        std::fs::write(root.path().join("rpt.md"), "").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let names = |options: SearchOptions| -> Vec<String> {
+            match server.handle_request(Request::Search(options)) {
+                Response::SearchResults { results, .. } => {
+                    results.into_iter().map(|r| r.name).collect()
+                }
+                other => panic!("unexpected search response: {other:?}"),
+            }
+        };
+
+        let by_size = |offset: usize, limit: usize| SearchOptions {
+            offset,
+            sort: SearchSort::Size,
+            ..SearchOptions::new("report_", limit)
+        };
+        assert_eq!(
+            names(by_size(0, 3)),
+            vec!["report_a.txt", "report_c.txt", "report_b.txt"]
+        );
+        assert_eq!(names(by_size(1, 1)), vec!["report_c.txt"]);
+        assert!(names(by_size(3, 10)).is_empty());
+        assert_eq!(
+            names(SearchOptions {
+                sort: SearchSort::Name,
+                ..SearchOptions::new("report_", 10)
+            }),
+            vec!["report_a.txt", "report_b.txt", "report_c.txt"]
+        );
+
+        // `rp` only abbreviates `report_*`; `rpt.md` is the one substring match.
+        assert!(names(SearchOptions::new("rp", 10)).len() > 1);
+        assert_eq!(
+            names(SearchOptions {
+                fuzzy: false,
+                ..SearchOptions::new("rp", 10)
+            }),
+            vec!["rpt.md"]
+        );
+    }
+
+    #[test]
+    fn watcher_updates_during_reconcile_are_searchable_before_finalize() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let fresh = root.path().join("fresh_during_reconcile.rs");
+        std::fs::write(&fresh, "").unwrap();
+        let search = || Request::Search(SearchOptions::new("fresh_during", 10));
+
+        // The watcher keeps applying to the live state while a rebuild scans.
+        {
+            let mut state = state.write().unwrap();
+            state.reconciling = true;
+            state.apply_update(IndexUpdate::Create {
+                path: fresh.to_string_lossy().to_string(),
+            });
+        }
+        match server.handle_request(search()) {
+            Response::SearchResults { results, .. } => {
+                assert_eq!(results.len(), 1);
+                assert_eq!(results[0].path, fresh.to_string_lossy());
+            }
+            other => panic!("unexpected search response: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn failed_result_actions_report_errors_without_recording_smriti() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+
+        let missing = root.path().join("gone.txt").to_string_lossy().to_string();
+        for request in [
+            Request::Reveal {
+                path: missing.clone(),
+            },
+            Request::OpenEditor {
+                path: missing.clone(),
+            },
+        ] {
+            match server.handle_request(request) {
+                Response::Error { message } => {
+                    assert!(message.contains("does not exist"), "{message}");
+                }
+                other => panic!("unexpected action response: {other:?}"),
+            }
+        }
+
+        match server.handle_request(Request::SmritiList {
+            query: None,
+            limit: 10,
+            filter_scope: None,
+        }) {
+            Response::SmritiEntries { entries } => assert!(entries.is_empty()),
+            other => panic!("unexpected smriti list response: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn search_reports_truncation_when_query_budget_is_exhausted() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        for idx in 0..4 {
+            std::fs::write(root.path().join(format!("budget_note_{idx}.txt")), "").unwrap();
+        }
+
+        let mut daemon_state = build_state(root.path(), vicaya_dir.path());
+        daemon_state.config.performance.query_max_candidates = 1;
+        let state = Arc::new(RwLock::new(daemon_state));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let search = || Request::Search(SearchOptions::new("budget_note", 10));
+
+        match server.handle_request(search()) {
+            Response::SearchResults {
+                results, truncated, ..
+            } => {
+                assert!(truncated);
+                assert_eq!(results.len(), 1);
+            }
+            other => panic!("unexpected search response: {other:?}"),
+        }
+
+        state
+            .write()
+            .unwrap()
+            .config
+            .performance
+            .query_max_candidates = 0;
+        match server.handle_request(search()) {
+            Response::SearchResults {
+                results, truncated, ..
+            } => {
+                assert!(!truncated);
+                assert_eq!(results.len(), 4);
+            }
+            other => panic!("unexpected search response: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn empty_search_offers_suggestions_and_min_score_drops_path_only_matches() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let notes = root.path().join("notes");
+        std::fs::create_dir_all(&notes).unwrap();
+        std::fs::write(notes.join("meeting.md"), "").unwrap();
+        std::fs::write(root.path().join("config.toml"), "").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let search = |term: &str| match server.handle_request(Request::Search(SearchOptions {
+            filters: SearchFilters {
+                scope: Some(root.path().to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new(term, 10)
+        })) {
+            Response::SearchResults {
+                results,
+                suggestions,
+                ..
+            } => (
+                results.into_iter().map(|r| r.name).collect::<Vec<_>>(),
+                suggestions,
+            ),
+            other => panic!("unexpected search response: {other:?}"),
+        };
+
+        // Swapped at the end, so no path under the random tempdir holds the
+        // typo as a subsequence and it cannot match fuzzily.
+        assert_eq!(
+            search("confgi"),
+            (Vec::new(), vec!["config.toml".to_string()])
+        );
+        assert!(search("config").1.is_empty());
+        // "notes" only matches the directory of meeting.md by path.
+        assert!(search("notes").0.contains(&"meeting.md".to_string()));
+
+        state.write().unwrap().config.ranking.min_score = 0.4;
+        assert!(!search("notes").0.contains(&"meeting.md".to_string()));
+        state.write().unwrap().config.ranking.suggestions = 0;
+        assert_eq!(search("confgi"), (Vec::new(), Vec::new()));
+    }
+
+    #[test]
+    fn scoped_search_without_matches_lists_results_outside_the_scope() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let project = root.path().join("project");
+        std::fs::create_dir_all(project.join("src")).unwrap();
+        std::fs::create_dir_all(root.path().join("docs")).unwrap();
+        std::fs::write(project.join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(root.path().join("docs").join("guide.md"), "").unwrap();
+        let deep = project.join("src").join("a").join("b");
+        std::fs::create_dir_all(&deep).unwrap();
+        std::fs::write(deep.join("deepfile.rs"), "").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::clone(&state),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let search = |term: &str, no_broaden: bool| match server.handle_request(Request::Search(
+            SearchOptions {
+                filters: SearchFilters {
+                    scope: Some(project.to_string_lossy().to_string()),
+                    max_depth: Some(2),
+                    ..SearchFilters::default()
+                },
+                flags: SearchFlags {
+                    no_broaden,
+                    ..SearchFlags::default()
+                },
+                ..SearchOptions::new(term, 10)
+            },
+        )) {
+            Response::SearchResults {
+                results,
+                outside_scope,
+                ..
+            } => (
+                results.into_iter().map(|r| r.name).collect::<Vec<_>>(),
+                outside_scope
+                    .into_iter()
+                    .map(|r| r.name)
+                    .collect::<Vec<_>>(),
+            ),
+            other => panic!("unexpected search response: {other:?}"),
+        };
+
+        assert_eq!(
+            search("lib.rs", false),
+            (vec!["lib.rs".to_string()], Vec::new())
+        );
+        assert_eq!(
+            search("guide", false),
+            (Vec::new(), vec!["guide.md".to_string()])
+        );
+        assert_eq!(search("guide", true), (Vec::new(), Vec::new()));
+        // Below the depth limit but inside the scope: not outside it.
+        assert_eq!(search("deepfile", false), (Vec::new(), Vec::new()));
+        state.write().unwrap().config.ranking.auto_broaden = false;
+        assert_eq!(search("guide", false), (Vec::new(), Vec::new()));
     }
 
     #[test]
-    fn full_rebuild_refreshes_last_updated_after_swap() {
+    fn path_completions_rank_frecent_directories_then_shallow_prefix_matches() {
         let vicaya_dir = tempdir().unwrap();
         let root = tempdir().unwrap();
-        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let photo = root.path().join("Pictures").join("beach.png");
+        std::fs::create_dir_all(photo.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(root.path().join("Projects").join("pipeline")).unwrap();
+        std::fs::write(&photo, "").unwrap();
+        std::fs::write(root.path().join("paper.pdf"), "").unwrap();
 
         let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
-        state.write().unwrap().last_updated = 0;
-        let retirement_probe = retirement_probe();
-        state.write().unwrap().retirement_probe = Some(Arc::clone(&retirement_probe));
-        let retired_before = retirement_probe.load(std::sync::atomic::Ordering::SeqCst);
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let complete =
+            |partial: &str, dirs_only: bool| match server.handle_request(Request::CompletePath {
+                prefix: format!("{}/{partial}", root.path().display()),
+                limit: 10,
+                dirs_only,
+            }) {
+                Response::PathCompletions { completions } => completions
+                    .into_iter()
+                    .map(|c| {
+                        let rel = Path::new(&c.path).strip_prefix(root.path()).unwrap();
+                        format!("{}{}", rel.display(), if c.is_dir { "/" } else { "" })
+                    })
+                    .collect::<Vec<_>>(),
+                other => panic!("unexpected completion response: {other:?}"),
+            };
 
-        let files_indexed =
-            full_rebuild_from_disk(&state, &Arc::new(Mutex::new(())), &Arc::new(Mutex::new(())))
-                .unwrap();
+        // Shallow first; within a depth, the order follows the match score.
+        let mut shallow_first = complete("P", false);
+        shallow_first[..3].sort();
+        shallow_first[3..].sort();
+        assert_eq!(
+            shallow_first,
+            [
+                "Pictures/",
+                "Projects/",
+                "paper.pdf",
+                "Pictures/beach.png",
+                "Projects/pipeline/"
+            ]
+        );
+        // Only names count: `beach.png` is under `Pictures/` but does not match.
+        assert_eq!(complete("pic", false), ["Pictures/"]);
 
-        assert!(files_indexed >= 1);
-        let state = state.read().unwrap();
-        assert!(state.last_updated > 0);
-        assert!(!state.reconciling);
-        drop(state);
-        wait_for_retired_state_drop(&retirement_probe, retired_before);
+        // Entering a directory makes it and its ancestors frecent.
+        assert!(matches!(
+            server.handle_request(Request::SmritiRecord {
+                path: root
+                    .path()
+                    .join("Projects/pipeline")
+                    .to_string_lossy()
+                    .to_string(),
+                query: String::new(),
+                action: vicaya_core::smriti::SmritiAction::Enter,
+            }),
+            Response::Ok
+        ));
+        assert_eq!(
+            complete("P", true),
+            ["Projects/", "Projects/pipeline/", "Pictures/"]
+        );
     }
 
     #[test]
-    fn replace_state_retires_old_state_without_leaking_it() {
+    fn jumps_pick_the_most_frecent_matching_directory_and_learn_from_themselves() {
         let vicaya_dir = tempdir().unwrap();
         let root = tempdir().unwrap();
-        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let old = root.path().join("old").join("proj");
+        let new = root.path().join("new").join("proj");
+        std::fs::create_dir_all(&old).unwrap();
+        std::fs::create_dir_all(&new).unwrap();
+        std::fs::write(new.join("readme.md"), "").unwrap();
 
         let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
-        let rebuilt = build_state(root.path(), vicaya_dir.path());
-        let retirement_probe = retirement_probe();
-        state.write().unwrap().retirement_probe = Some(Arc::clone(&retirement_probe));
-        let before = retirement_probe.load(std::sync::atomic::Ordering::SeqCst);
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let jump = |query: &str| match server.handle_request(Request::Jump {
+            query: query.to_string(),
+        }) {
+            Response::JumpTarget { path } => path.map(PathBuf::from),
+            other => panic!("unexpected jump response: {other:?}"),
+        };
+        let record = |path: &Path, action| {
+            server.handle_request(Request::SmritiRecord {
+                path: path.to_string_lossy().to_string(),
+                query: String::new(),
+                action,
+            })
+        };
 
-        replace_state(&state, rebuilt);
-        wait_for_retired_state_drop(&retirement_probe, before);
+        assert_eq!(jump("nothing-here"), None);
+        // Opening a file votes for its directory, and the jump is remembered.
+        record(&new.join("readme.md"), SmritiAction::Open);
+        assert_eq!(jump("proj"), Some(new.clone()));
+        record(&old, SmritiAction::Enter);
+        record(&old, SmritiAction::Enter);
+        record(&old, SmritiAction::Enter);
+        assert_eq!(jump("PROJ"), Some(old.clone()));
+        assert_eq!(jump("new proj"), Some(new.clone()));
+        // Keywords match in order, the last one in the directory's name;
+        // without a remembered match the index answers.
+        assert_eq!(jump("proj new"), None);
+        assert_eq!(jump("new"), Some(root.path().join("new")));
+
+        // Remembered directories outside the index are found on disk, and
+        // ones gone from disk are passed over.
+        let elsewhere = tempdir().unwrap();
+        let outside = elsewhere.path().join("vendored");
+        std::fs::create_dir_all(&outside).unwrap();
+        record(&outside, SmritiAction::Enter);
+        assert_eq!(jump("vendored"), Some(outside.clone()));
+        std::fs::remove_dir(&outside).unwrap();
+        assert_eq!(jump("vendored"), None);
+
+        assert!(jump_keywords_match(
+            "/a/foo/barbaz",
+            &["foo".into(), "baz".into()]
+        ));
+        assert!(!jump_keywords_match("/a/foo/bar", &["foo".into()]));
+        assert!(jump_keywords_match("/a/foo/", &["foo".into()]));
     }
 
     #[test]
-    fn ipc_server_handle_request_covers_status_search_rebuild_and_shutdown() {
+    fn suggestions_prefer_used_names_then_names_many_entries_share() {
         let vicaya_dir = tempdir().unwrap();
         let root = tempdir().unwrap();
-        let cargo = root.path().join("Cargo.toml");
-        std::fs::write(&cargo, "[package]\n").unwrap();
-
-        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
-        let shutdown = Arc::new(AtomicBool::new(false));
-        let journal_lock = Arc::new(Mutex::new(()));
-        let rebuild_lock = Arc::new(Mutex::new(()));
-        let socket = vicaya_dir.path().join("daemon.sock");
-        let server =
-            IpcServer::new(&socket, state, shutdown.clone(), journal_lock, rebuild_lock).unwrap();
-
-        match server.handle_request(Request::Status) {
-            Response::Status {
-                indexed_files,
-                trigram_count,
-                ..
-            } => {
-                assert!(indexed_files >= 1);
-                assert!(trigram_count > 0);
-            }
-            other => panic!("unexpected status response: {other:?}"),
+        for dir in ["a", "b", "c"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+            std::fs::write(root.path().join(dir).join("Makefile"), "").unwrap();
         }
+        std::fs::write(root.path().join("a").join("makeup.txt"), "").unwrap();
+        std::fs::write(root.path().join("a").join("maker.rs"), "").unwrap();
+        std::fs::write(root.path().join("b").join("maker.rs"), "").unwrap();
 
-        match server.handle_request(Request::Search {
-            query: "Cargo".to_string(),
-            limit: 10,
-            scope: Some(root.path().to_string_lossy().to_string()),
-            filter_scope: Some(root.path().to_string_lossy().to_string()),
-            recent_if_empty: false,
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let suggest = |prefix: &str, limit| match server.handle_request(Request::Suggest {
+            prefix: prefix.to_string(),
+            limit,
         }) {
-            Response::SearchResults { results } => {
-                assert_eq!(results.len(), 1);
-                assert_eq!(results[0].path, cargo.to_string_lossy());
-            }
-            other => panic!("unexpected search response: {other:?}"),
-        }
+            Response::Suggestions { names } => names,
+            other => panic!("unexpected suggest response: {other:?}"),
+        };
+        let record = |path: PathBuf| {
+            server.handle_request(Request::SmritiRecord {
+                path: path.to_string_lossy().to_string(),
+                query: String::new(),
+                action: SmritiAction::Open,
+            })
+        };
 
-        match server.handle_request(Request::Search {
-            query: String::new(),
-            limit: 10,
-            scope: None,
-            filter_scope: Some(root.path().to_string_lossy().to_string()),
-            recent_if_empty: true,
-        }) {
-            Response::SearchResults { results } => {
-                assert!(results.iter().any(|r| r.path == cargo.to_string_lossy()))
-            }
-            other => panic!("unexpected recent response: {other:?}"),
-        }
+        // Nothing used yet: indexed names, the most shared first.
+        assert_eq!(suggest("MAK", 5), ["Makefile", "maker.rs", "makeup.txt"]);
+        assert_eq!(suggest("", 5), Vec::<String>::new());
+
+        // A used name leads, and an empty prefix lists used names.
+        record(root.path().join("a").join("makeup.txt"));
+        assert_eq!(suggest("mak", 2), ["makeup.txt", "Makefile"]);
+        assert_eq!(suggest("", 5), ["makeup.txt"]);
+
+        // Used names no longer indexed are not offered.
+        record(root.path().join("gone.txt"));
+        assert_eq!(suggest("gon", 5), Vec::<String>::new());
+
+        // Forgetting a path forgets its name.
+        server.handle_request(Request::SmritiForget {
+            path: root
+                .path()
+                .join("a")
+                .join("makeup.txt")
+                .to_string_lossy()
+                .to_string(),
+        });
+        assert_eq!(suggest("", 5), Vec::<String>::new());
+    }
 
-        match server.handle_request(Request::Rebuild { dry_run: true }) {
-            Response::RebuildComplete { files_indexed } => assert!(files_indexed >= 1),
-            other => panic!("unexpected rebuild response: {other:?}"),
+    #[test]
+    fn lite_profile_indexes_files_only_and_refuses_size_sorting() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("docs")).unwrap();
+        std::fs::write(root.path().join("docs").join("a.txt"), "aaaa").unwrap();
+        let mut config = test_config(root.path(), vicaya_dir.path());
+        config.index_profile = IndexProfile::Lite;
+        std::fs::create_dir_all(&config.index_path).unwrap();
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let mut state = DaemonState::new(
+            config,
+            vicaya_dir.path().join("index.bin"),
+            vicaya_dir.path().join("journal.log"),
+            snapshot,
+        );
+
+        // Watcher updates follow the profile, and entries without inodes
+        // are never taken for renames of each other.
+        std::fs::create_dir_all(root.path().join("notes")).unwrap();
+        std::fs::write(root.path().join("notes").join("b.txt"), "bb").unwrap();
+        for path in ["notes", "notes/b.txt"] {
+            state.apply_update(IndexUpdate::Create {
+                path: root.path().join(path).to_string_lossy().to_string(),
+            });
         }
+        assert!(state.inode_to_id.is_empty());
+        assert_eq!(state.indexed_file_count(), 2);
+        let mut entries: Vec<_> = state
+            .search(SearchOptions::new("txt", 10), &ScorerRegistry::default())
+            .results
+            .into_iter()
+            .map(|result| (result.name, result.is_dir, result.size))
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                ("a.txt".to_string(), false, 0),
+                ("b.txt".to_string(), false, 0)
+            ]
+        );
 
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            Arc::new(RwLock::new(state)),
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
         assert!(matches!(
-            server.handle_request(Request::Shutdown),
-            Response::Ok
+            server.handle_request(Request::Status),
+            Response::Status(DaemonStatus {
+                index_profile: IndexProfile::Lite,
+                ..
+            })
         ));
-        assert!(shutdown.load(Ordering::Relaxed));
+        let by_size = SearchOptions {
+            sort: SearchSort::Size,
+            ..SearchOptions::new("txt", 10)
+        };
+        match server.handle_request(Request::Search(by_size)) {
+            Response::Error { message } => assert!(message.contains("index_profile"), "{message}"),
+            other => panic!("expected a size-sort error, got {other:?}"),
+        }
     }
 
     #[test]
@@ -1998,14 +6311,15 @@ mod tests {
             Response::Ok
         ));
 
-        match server.handle_request(Request::Search {
-            query: "config".to_string(),
-            limit: 2,
+        match server.handle_request(Request::Search(SearchOptions {
             scope: Some(root.path().to_string_lossy().to_string()),
-            filter_scope: Some(root.path().to_string_lossy().to_string()),
-            recent_if_empty: false,
-        }) {
-            Response::SearchResults { results } => {
+            filters: SearchFilters {
+                scope: Some(root.path().to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("config", 2)
+        })) {
+            Response::SearchResults { results, .. } => {
                 assert_eq!(
                     results.first().map(|r| r.path.as_str()),
                     Some(preferred.to_string_lossy().as_ref())
@@ -2014,14 +6328,15 @@ mod tests {
             other => panic!("unexpected search response: {other:?}"),
         }
 
-        match server.handle_request(Request::Search {
-            query: "config.toml".to_string(),
-            limit: 2,
+        match server.handle_request(Request::Search(SearchOptions {
             scope: Some(root.path().to_string_lossy().to_string()),
-            filter_scope: Some(root.path().to_string_lossy().to_string()),
-            recent_if_empty: false,
-        }) {
-            Response::SearchResults { results } => {
+            filters: SearchFilters {
+                scope: Some(root.path().to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("config.toml", 2)
+        })) {
+            Response::SearchResults { results, .. } => {
                 assert_eq!(
                     results.first().map(|r| r.path.as_str()),
                     Some(preferred.to_string_lossy().as_ref())
@@ -2065,6 +6380,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn global_searches_favor_recently_used_scopes() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let scoped = root.path().join("old").join("notes.md");
+        let newer = root.path().join("new").join("notes.md");
+        std::fs::create_dir_all(scoped.parent().unwrap()).unwrap();
+        std::fs::create_dir_all(newer.parent().unwrap()).unwrap();
+        std::fs::write(&scoped, "").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        std::fs::write(&newer, "").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let server = IpcServer::new(
+            &vicaya_dir.path().join("daemon.sock"),
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let search = |filter_scope: Option<&Path>| match server.handle_request(Request::Search(
+            SearchOptions {
+                filters: SearchFilters {
+                    scope: filter_scope.map(|p| p.to_string_lossy().to_string()),
+                    ..SearchFilters::default()
+                },
+                flags: SearchFlags {
+                    explain: true,
+                    ..SearchFlags::default()
+                },
+                ..SearchOptions::new("notes", 10)
+            },
+        )) {
+            Response::SearchResults { results, .. } => results
+                .into_iter()
+                .map(|r| (PathBuf::from(r.path), r.explain.unwrap().recent_scope_boost))
+                .collect::<Vec<_>>(),
+            other => panic!("unexpected search response: {other:?}"),
+        };
+
+        assert_eq!(search(None)[0], (newer.clone(), 0.0));
+
+        assert!(matches!(
+            server.handle_request(Request::ScopeUsed {
+                path: scoped.parent().unwrap().to_string_lossy().to_string(),
+            }),
+            Response::Ok
+        ));
+        let global = search(None);
+        assert_eq!(global[0].0, scoped);
+        assert!(global[0].1 > 0.09, "{global:?}");
+        assert_eq!(global[1], (newer.clone(), 0.0));
+
+        // Scoped searches rank on their own.
+        let scoped_search = search(Some(root.path()));
+        assert_eq!(scoped_search[0], (newer, 0.0));
+        assert_eq!(scoped_search[1], (scoped, 0.0));
+    }
+
     #[test]
     fn scoped_file_ids_up_to_reports_incomplete_large_scope() {
         let vicaya_dir = tempdir().unwrap();
@@ -2116,14 +6491,15 @@ mod tests {
         let socket = vicaya_dir.path().join("daemon.sock");
         let server = IpcServer::new(&socket, state, shutdown, journal_lock, rebuild_lock).unwrap();
 
-        match server.handle_request(Request::Search {
-            query: "main.go".to_string(),
-            limit: 10,
+        match server.handle_request(Request::Search(SearchOptions {
             scope: Some(inside_dir.to_string_lossy().to_string()),
-            filter_scope: Some(inside_dir.to_string_lossy().to_string()),
-            recent_if_empty: false,
-        }) {
-            Response::SearchResults { results } => {
+            filters: SearchFilters {
+                scope: Some(inside_dir.to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("main.go", 10)
+        })) {
+            Response::SearchResults { results, .. } => {
                 assert_eq!(results.len(), 1);
                 assert_eq!(results[0].path, inside.to_string_lossy());
             }
@@ -2167,24 +6543,25 @@ mod tests {
             .unwrap();
         assert!(matches!(
             Response::from_json(&line).unwrap(),
-            Response::Status { .. }
+            Response::Status(DaemonStatus { .. })
         ));
 
         send(
             &mut stream,
-            Request::Search {
-                query: "Cargo".to_string(),
-                limit: 10,
+            Request::Search(SearchOptions {
                 scope: Some(root.path().to_string_lossy().to_string()),
-                filter_scope: Some(root.path().to_string_lossy().to_string()),
-                recent_if_empty: false,
-            },
+                filters: SearchFilters {
+                    scope: Some(root.path().to_string_lossy().to_string()),
+                    ..SearchFilters::default()
+                },
+                ..SearchOptions::new("Cargo", 10)
+            }),
         );
         let line = vicaya_core::ipc::read_message(&mut reader)
             .unwrap()
             .unwrap();
         match Response::from_json(&line).unwrap() {
-            Response::SearchResults { results } => assert_eq!(results.len(), 1),
+            Response::SearchResults { results, .. } => assert_eq!(results.len(), 1),
             other => panic!("unexpected persistent search response: {other:?}"),
         }
 
@@ -2200,6 +6577,228 @@ mod tests {
         assert!(shutdown.load(Ordering::Relaxed));
     }
 
+    #[test]
+    fn ipc_server_switches_a_connection_to_msgpack_framing() {
+        use std::os::unix::net::UnixStream;
+
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let journal_lock = Arc::new(Mutex::new(()));
+        let rebuild_lock = Arc::new(Mutex::new(()));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server =
+            IpcServer::new(&socket, state, shutdown.clone(), journal_lock, rebuild_lock).unwrap();
+        let server_thread = std::thread::spawn(move || server.run().unwrap());
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        // The first search goes out right behind the switch, before its reply.
+        let scope = root.path().to_string_lossy().to_string();
+        let search = Request::Search(SearchOptions {
+            scope: Some(scope.clone()),
+            filters: SearchFilters {
+                scope: Some(scope),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("Cargo", 10)
+        });
+        Framing::Json
+            .write(
+                &mut stream,
+                &Request::Framing {
+                    framing: Framing::Msgpack,
+                },
+            )
+            .unwrap();
+        Framing::Msgpack.write(&mut stream, &search).unwrap();
+
+        let ack: Response = Framing::Json.read(&mut reader).unwrap().unwrap();
+        assert!(matches!(
+            ack,
+            Response::Framing {
+                framing: Framing::Msgpack
+            }
+        ));
+        match Framing::Msgpack.read(&mut reader).unwrap().unwrap() {
+            Response::SearchResults { results, .. } => assert_eq!(results.len(), 1),
+            other => panic!("unexpected msgpack search response: {other:?}"),
+        }
+
+        Framing::Msgpack
+            .write(&mut stream, &Request::Shutdown)
+            .unwrap();
+        let response: Response = Framing::Msgpack.read(&mut reader).unwrap().unwrap();
+        assert!(matches!(response, Response::Ok));
+
+        drop(reader);
+        drop(stream);
+        server_thread.join().unwrap();
+        assert!(shutdown.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn subscribe_pushes_watcher_changes_to_the_subscribed_search() {
+        use std::os::unix::net::UnixStream;
+
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("notes.md"), "old").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            Arc::clone(&state),
+            shutdown.clone(),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let server_thread = std::thread::spawn(move || server.run().unwrap());
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let scope = root.path().to_string_lossy().to_string();
+        let subscribe = |term: &str| {
+            Request::Subscribe(SearchOptions {
+                filters: SearchFilters {
+                    scope: Some(scope.clone()),
+                    ..SearchFilters::default()
+                },
+                ..SearchOptions::new(term, 10)
+            })
+        };
+        Framing::Json
+            .write(&mut stream, &subscribe("notes"))
+            .unwrap();
+        let ack: Response = Framing::Json.read(&mut reader).unwrap().unwrap();
+        assert!(matches!(ack, Response::Subscribed));
+
+        let watch = |updates: Vec<IndexUpdate>| {
+            let mut state = state.write().unwrap();
+            let prepared: Vec<_> = updates
+                .into_iter()
+                .map(|update| prepare_index_update(&state.config, &state.canonical_paths, update))
+                .collect();
+            for update in prepared.iter().cloned() {
+                state.apply_prepared_update(update);
+            }
+            state.activity.changes.publish(&prepared);
+        };
+        let path = |name: &str| root.path().join(name).to_string_lossy().to_string();
+        std::fs::remove_file(root.path().join("notes.md")).unwrap();
+        std::fs::write(root.path().join("notes-2.md"), "new").unwrap();
+        std::fs::write(root.path().join("todo.txt"), "other").unwrap();
+        watch(vec![
+            IndexUpdate::Delete {
+                path: path("notes.md"),
+            },
+            IndexUpdate::Create {
+                path: path("notes-2.md"),
+            },
+            IndexUpdate::Create {
+                path: path("todo.txt"),
+            },
+        ]);
+
+        match Framing::Json.read(&mut reader).unwrap().unwrap() {
+            Response::IndexChanged { removed, added } => {
+                let added: Vec<_> = added.iter().map(|r| r.path.clone()).collect();
+                assert_eq!(added, [path("notes-2.md")]);
+                assert!(removed.contains(&path("notes.md")));
+                assert!(!removed.contains(&path("notes-2.md")));
+            }
+            other => panic!("unexpected push: {other:?}"),
+        }
+
+        // Requests are still answered, and a new Subscribe swaps the search.
+        Framing::Json
+            .write(&mut stream, &subscribe("todo"))
+            .unwrap();
+        let ack: Response = Framing::Json.read(&mut reader).unwrap().unwrap();
+        assert!(matches!(ack, Response::Subscribed));
+        std::fs::write(root.path().join("todo.txt"), "changed").unwrap();
+        watch(vec![IndexUpdate::Modify {
+            path: path("todo.txt"),
+        }]);
+        match Framing::Json.read(&mut reader).unwrap().unwrap() {
+            Response::IndexChanged { removed, added } => {
+                assert!(removed.is_empty());
+                assert_eq!(added.len(), 1);
+                assert_eq!(added[0].path, path("todo.txt"));
+            }
+            other => panic!("unexpected push: {other:?}"),
+        }
+
+        Framing::Json
+            .write(&mut stream, &Request::Shutdown)
+            .unwrap();
+        let response: Response = Framing::Json.read(&mut reader).unwrap().unwrap();
+        assert!(matches!(response, Response::Ok));
+        drop(reader);
+        drop(stream);
+        server_thread.join().unwrap();
+    }
+
+    #[test]
+    fn privileged_socket_requests_are_audited_with_peer_and_outcome() {
+        use std::os::unix::net::UnixStream;
+        use vicaya_core::audit::AuditOutcome;
+
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        std::fs::write(root.path().join("notes.md"), "x").unwrap();
+
+        let state = Arc::new(RwLock::new(build_state(root.path(), vicaya_dir.path())));
+        let socket = vicaya_dir.path().join("daemon.sock");
+        let server = IpcServer::new(
+            &socket,
+            state,
+            Arc::new(AtomicBool::new(false)),
+            Arc::new(Mutex::new(())),
+            Arc::new(Mutex::new(())),
+        )
+        .unwrap();
+        let server_thread = std::thread::spawn(move || server.run().unwrap());
+
+        let mut stream = UnixStream::connect(&socket).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let missing = vicaya_dir.path().join("no-such-daemon");
+        for request in [
+            Request::Rebuild { dry_run: true },
+            Request::Status,
+            Request::Upgrade {
+                binary: Some(missing.to_string_lossy().to_string()),
+            },
+            Request::Shutdown,
+        ] {
+            Framing::Json.write(&mut stream, &request).unwrap();
+            let _: Response = Framing::Json.read(&mut reader).unwrap().unwrap();
+        }
+        drop(reader);
+        drop(stream);
+        server_thread.join().unwrap();
+
+        let records = vicaya_core::audit::load(&vicaya_dir.path().join("audit.jsonl")).unwrap();
+        let kinds: Vec<_> = records.iter().map(|r| r.request.as_str()).collect();
+        assert_eq!(kinds, ["rebuild", "upgrade", "shutdown"]);
+        assert_eq!(records[0].detail.as_deref(), Some("dry_run"));
+        assert_eq!(records[0].outcome, AuditOutcome::Ok);
+        assert_eq!(records[1].outcome, AuditOutcome::Error);
+        assert!(records[1].message.is_some());
+        assert_eq!(records[2].outcome, AuditOutcome::Ok);
+        #[cfg(target_os = "linux")]
+        for record in &records {
+            assert_eq!(record.peer_pid, Some(std::process::id() as i32));
+        }
+    }
+
     #[test]
     fn ipc_server_handles_multiple_tui_clients_concurrently() {
         use std::io::Write as _;
@@ -2239,24 +6838,25 @@ mod tests {
                     .unwrap();
                 assert!(matches!(
                     Response::from_json(&line).unwrap(),
-                    Response::Status { .. }
+                    Response::Status(DaemonStatus { .. })
                 ));
 
                 send(
                     &mut stream,
-                    Request::Search {
-                        query: "Cargo".to_string(),
-                        limit: 10,
+                    Request::Search(SearchOptions {
                         scope: Some(scope.clone()),
-                        filter_scope: Some(scope),
-                        recent_if_empty: false,
-                    },
+                        filters: SearchFilters {
+                            scope: Some(scope),
+                            ..SearchFilters::default()
+                        },
+                        ..SearchOptions::new("Cargo", 10)
+                    }),
                 );
                 let line = vicaya_core::ipc::read_message(&mut reader)
                     .unwrap()
                     .unwrap();
                 match Response::from_json(&line).unwrap() {
-                    Response::SearchResults { results } => assert_eq!(results.len(), 1),
+                    Response::SearchResults { results, .. } => assert_eq!(results.len(), 1),
                     other => panic!("unexpected concurrent search response: {other:?}"),
                 }
             }));
@@ -1,23 +1,57 @@
 //! vicaya-daemon: Background service for vicaya.
 
+mod audit;
+mod canonical_path;
+mod crash;
+mod ephemeral;
+mod handoff;
+mod hooks;
+mod http_server;
 mod ipc_server;
+mod recent_scopes;
+mod reconcile;
+mod subscriptions;
+mod subtree_scan;
+mod suggest;
 
 use std::path::Path;
 use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 use tracing::{info, warn};
+use vicaya_core::accounting::{Subsystem, ThreadUsage};
+use vicaya_core::config::{JournalFsync, ScanProfile};
+use vicaya_core::schedule::CronSchedule;
 use vicaya_core::{Config, Result};
+use vicaya_index::Normalization;
 use vicaya_scanner::{IndexSnapshot, Scanner};
-use vicaya_watcher::{FileWatcher, IndexUpdate};
+use vicaya_watcher::{FileWatcher, IndexUpdate, ResumeCheckpoint, ResumePoint};
 
+use crate::canonical_path::PathCanonicalizer;
+use crate::ephemeral::{Ephemeral, EphemeralArgs};
+use crate::hooks::HookRunner;
+use crate::http_server::HttpServer;
 use crate::ipc_server::{
-    prepare_index_update, DaemonState, IpcServer, PreparedIndexUpdate, SharedState,
+    append_journal, prepare_index_update, DaemonState, IpcServer, PreparedIndexUpdate, SharedState,
 };
+use crate::subtree_scan::SubtreeScans;
 
 const WATCHER_APPLY_CHUNK_SIZE: usize = 256;
 
+/// FSEvents resume point, next to `index.bin`.
+const RESUME_FILE: &str = "watcher.resume";
+
+/// A startup replay slower than this is abandoned for a reconcile.
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(60);
+
 fn main() -> Result<()> {
-    vicaya_core::logging::init();
+    let ephemeral_args = EphemeralArgs::parse(std::env::args().skip(1))?;
+    // An ephemeral daemon reports where it listens on stdout.
+    if ephemeral_args.is_some() {
+        vicaya_core::logging::init_stderr();
+    } else {
+        vicaya_core::logging::init();
+    }
 
     if std::env::args().any(|arg| arg == "--version" || arg == "-V") {
         println!(
@@ -27,7 +61,20 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    info!("vicaya daemon starting...");
+    let ephemeral = match ephemeral_args {
+        Some(_) if profile_arg(std::env::args()).is_some() => {
+            return Err(vicaya_core::Error::Config(
+                "--ephemeral cannot be combined with --profile".to_string(),
+            ));
+        }
+        Some(args) => Some(Ephemeral::activate(args)?),
+        None => None,
+    };
+    let profile = vicaya_core::paths::init_profile(profile_arg(std::env::args()).as_deref())?;
+    match profile.as_deref() {
+        Some(name) => info!("vicaya daemon starting (profile {})...", name),
+        None => info!("vicaya daemon starting..."),
+    }
 
     // Load or create default config
     let config = load_config()?;
@@ -36,17 +83,54 @@ fn main() -> Result<()> {
     let index_file = config.index_path.join("index.bin");
     let journal_file = config.index_path.join("index.journal");
 
-    // Check if index exists, otherwise build it
-    let had_index = index_file.exists();
-    let snapshot = if had_index {
+    // Load the existing index; build a new one if it is missing or was
+    // written in an incompatible format.
+    let existing = if index_file.exists() {
         info!("Loading existing index...");
-        IndexSnapshot::load(&index_file)?
+        IndexSnapshot::load(&index_file)
+            .map_err(|e| warn!("Discarding unreadable index ({}); rebuilding", e))
+            .ok()
+            .filter(|snapshot| {
+                let fits = snapshot.fits_profile(config.index_profile);
+                if !fits {
+                    info!("index_profile changed; rebuilding");
+                }
+                fits
+            })
     } else {
-        info!("Building new index...");
-        let scanner = Scanner::new(config.clone());
-        let snapshot = scanner.scan()?;
-        snapshot.save(&index_file)?;
-        snapshot
+        None
+    };
+    let had_index = existing.is_some();
+    // An upgrading daemon saved the live index just before exec'ing this
+    // binary, so there is no downtime to reconcile.
+    let resumed = handoff::take_handoff(&index_file) && had_index;
+    if resumed {
+        info!("Resuming from upgrade handoff");
+    }
+    // A cleanly stopped daemon on macOS left the FSEvents event ID it had
+    // applied everything up to; replaying from it replaces the startup
+    // reconcile. A fresh scan has nothing to replay onto.
+    let replay_from = ResumePoint::take(&config.index_path.join(RESUME_FILE)).filter(|_| had_index);
+    let mut initial_scan = ThreadUsage::default();
+    let snapshot = match existing {
+        Some(mut snapshot) => {
+            if snapshot.set_normalization(Normalization::from(&config.normalization)) {
+                info!("Name normalization changed; re-derived trigram index");
+            }
+            if snapshot.set_path_index(config.index_directory_paths) {
+                info!("Built directory path index");
+            }
+            snapshot
+        }
+        None => {
+            info!("Building new index...");
+            let scanner = Scanner::new(config.clone());
+            let started = ThreadUsage::now();
+            let snapshot = scanner.scan()?;
+            initial_scan = started.elapsed();
+            snapshot.save(&index_file)?;
+            snapshot
+        }
     };
 
     info!("Index ready: {} files indexed", snapshot.file_table.len());
@@ -58,11 +142,21 @@ fn main() -> Result<()> {
         snapshot,
     )));
 
+    // From here on a panic on any thread leaves a crash report and takes
+    // the whole daemon down.
+    crash::install(&state);
+
     // Fresh scans are authoritative. Existing snapshots become live immediately;
     // startup reconcile catches downtime changes and truncates any stale journal
     // after the IPC socket is ready.
     if !had_index {
+        let state = state.read().unwrap();
+        state.activity.usage.record(Subsystem::Scan, initial_scan);
+        drop(state);
         clear_stale_journal(&journal_file)?;
+    } else {
+        let pending = count_journal_entries(&journal_file);
+        state.read().unwrap().activity.set_journal_entries(pending);
     }
 
     let shutdown = Arc::new(AtomicBool::new(false));
@@ -81,6 +175,13 @@ fn main() -> Result<()> {
 
     // Record PID once we're successfully bound.
     vicaya_core::daemon::write_pid(std::process::id() as i32)?;
+    if let Some(ephemeral) = &ephemeral {
+        ephemeral.announce_ready(&socket_path);
+    }
+
+    start_warmup_thread(&config, Arc::clone(&state));
+
+    let http_thread = start_http_thread(&config, &server, Arc::clone(&shutdown));
 
     // Start watcher thread
     let watcher_thread = start_watcher_thread(
@@ -88,6 +189,7 @@ fn main() -> Result<()> {
         Arc::clone(&state),
         Arc::clone(&shutdown),
         Arc::clone(&journal_lock),
+        replay_from,
     )?;
 
     // Start reconciliation thread to catch up on missed updates during downtime.
@@ -97,7 +199,7 @@ fn main() -> Result<()> {
         Arc::clone(&shutdown),
         Arc::clone(&journal_lock),
         Arc::clone(&rebuild_lock),
-        had_index,
+        had_index && !resumed && replay_from.is_none(),
     )?;
 
     info!("vicaya daemon running. Press Ctrl+C to stop.");
@@ -112,10 +214,25 @@ fn main() -> Result<()> {
     if let Err(e) = reconcile_thread.join() {
         warn!("Reconcile thread did not shut down cleanly: {:?}", e);
     }
+    if let Some(Err(e)) = http_thread.map(|thread| thread.join()) {
+        warn!("HTTP thread did not shut down cleanly: {:?}", e);
+    }
+
+    if let Some(binary) = server.upgrade_binary() {
+        // Keeps the PID file: the new binary runs under this PID.
+        let e = handoff::exec_upgrade(&binary, &state, &journal_lock);
+        warn!("Upgrade failed: {}", e);
+        let _ = vicaya_core::daemon::remove_pid_file();
+        let _ = std::fs::remove_file(&socket_path);
+        return Err(e);
+    }
 
     // Best-effort cleanup.
     let _ = vicaya_core::daemon::remove_pid_file();
     let _ = std::fs::remove_file(&socket_path);
+    if let Some(ephemeral) = ephemeral {
+        ephemeral.cleanup();
+    }
 
     server_result
 }
@@ -124,7 +241,11 @@ fn load_config() -> Result<Config> {
     let config_path = vicaya_core::paths::config_path();
 
     if config_path.exists() {
-        Config::load(&config_path)
+        let (config, report) = Config::load_with_report(&config_path)?;
+        for issue in report.warnings() {
+            warn!("{}:{}", config_path.display(), issue);
+        }
+        Ok(config)
     } else {
         let config = Config::default();
         std::fs::create_dir_all(config_path.parent().unwrap())?;
@@ -151,63 +272,218 @@ fn clear_stale_journal(journal_file: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Touch the most common trigram postings in the background so the first
+/// searches after startup do not wait on the freshly loaded snapshot.
+fn start_warmup_thread(config: &Config, state: SharedState) {
+    let top_trigrams = config.performance.warmup_trigrams;
+    if top_trigrams == 0 {
+        return;
+    }
+    std::thread::spawn(move || {
+        let started = std::time::Instant::now();
+        let state = state.read().unwrap();
+        let postings = state.warm_up(top_trigrams);
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        state.activity.warmup_finished(elapsed_ms);
+        info!(
+            "Index warm-up touched {} postings across {} trigrams in {} ms",
+            postings, top_trigrams, elapsed_ms
+        );
+    });
+}
+
+/// Serve the optional `[http]` endpoint with the IPC request handler. A
+/// missing token or a busy port disables the endpoint but not the daemon.
+fn start_http_thread(
+    config: &Config,
+    server: &IpcServer,
+    shutdown: Arc<AtomicBool>,
+) -> Option<std::thread::JoinHandle<()>> {
+    if !config.http.enabled {
+        return None;
+    }
+    let Some(token) = config.http_token() else {
+        warn!("[http] is enabled but no token is set; HTTP endpoint not started");
+        return None;
+    };
+    match HttpServer::bind(config.http.port, token, server.handler(), shutdown) {
+        Ok(http) => Some(std::thread::spawn(move || http.run())),
+        Err(e) => {
+            warn!("HTTP endpoint not started: {}", e);
+            None
+        }
+    }
+}
+
 fn start_watcher_thread(
     config: Config,
     state: SharedState,
     shutdown: Arc<AtomicBool>,
     journal_lock: Arc<Mutex<()>>,
+    replay_from: Option<ResumePoint>,
 ) -> Result<std::thread::JoinHandle<()>> {
-    let watcher = FileWatcher::new(&config.index_roots)?;
-    let internal_dir = vicaya_core::paths::vicaya_dir();
+    // Start watching before replaying, so nothing falls between the two.
+    let mut watcher = FileWatcher::new(&config.effective_roots())?;
+    // Every profile's state lives under the base dir; none of it is user data.
+    let internal_dir = vicaya_core::paths::base_dir();
     let index_dir = config.index_path.clone();
     let journal_file = config.index_path.join("index.journal");
+    let resume_file = config.index_path.join(RESUME_FILE);
+    let fsync = config.journal_fsync().unwrap_or_else(|e| {
+        warn!("{}; using the default journal fsync policy", e);
+        JournalFsync::Interval {
+            interval_ms: config.journal.fsync_interval_ms,
+        }
+    });
+    state.read().unwrap().activity.set_journal_fsync(fsync);
+
+    let mut hooks = HookRunner::new(&config.hooks);
+    let subtrees = SubtreeScans::start(config.clone(), Arc::clone(&state.read().unwrap().activity));
 
     let handle = std::thread::spawn(move || {
+        let mut journal_sync = JournalSync::new(fsync);
+        let mut replayed = replay_from.map(|point| replay_changes(&config, &state, point));
+        let mut checkpoint = ResumeCheckpoint::new(
+            replay_from
+                .map(|point| point.event_id)
+                .or_else(vicaya_watcher::current_event_id),
+        );
         while !shutdown.load(std::sync::atomic::Ordering::Relaxed) {
-            let mut updates = watcher.poll_updates();
+            checkpoint.observe(Instant::now(), vicaya_watcher::current_event_id);
+            let mut updates = replayed.take().unwrap_or_else(|| watcher.poll_updates());
 
             // Avoid feedback loops and indexing internal state.
             updates.retain(|u| !is_internal_update(u, &internal_dir, &index_dir));
+            // Contents of directories moved in, listed by the subtree
+            // scanner; only raw updates are observed, or every listed
+            // directory would be walked again.
+            subtrees.observe(&updates);
+            updates.extend(subtrees.take_ready().unwrap_or_default());
 
             if updates.is_empty() {
+                hooks.run_due(Instant::now());
+                if journal_sync.due(Instant::now()) {
+                    let _guard = journal_lock.lock().unwrap();
+                    sync_journal(&journal_file, &mut journal_sync);
+                }
                 std::thread::sleep(std::time::Duration::from_millis(50));
                 continue;
             }
 
-            {
-                let _guard = journal_lock.lock().unwrap();
-                if let Err(e) = append_journal(&journal_file, &updates) {
-                    warn!("Failed to append journal: {}", e);
+            let activity = Arc::clone(&state.read().unwrap().activity);
+            activity.watcher_events_queued(updates.len());
+
+            activity.usage.measure(Subsystem::Watcher, || {
+                {
+                    let _guard = journal_lock.lock().unwrap();
+                    match append_journal(&journal_file, &updates) {
+                        Ok(()) => {
+                            activity.journal_appended(updates.len());
+                            if journal_sync.appended(Instant::now()) {
+                                sync_journal(&journal_file, &mut journal_sync);
+                            }
+                        }
+                        Err(e) => warn!("Failed to append journal: {}", e),
+                    }
                 }
-            }
 
-            apply_watcher_updates(&state, updates);
+                apply_watcher_updates(&state, updates, &mut hooks);
+            });
+            hooks.run_due(Instant::now());
         }
 
+        if journal_sync.is_dirty() {
+            let _guard = journal_lock.lock().unwrap();
+            sync_journal(&journal_file, &mut journal_sync);
+        }
+        if let Some(event_id) = checkpoint.settled() {
+            if let Err(e) = (ResumePoint { event_id }).save(&resume_file) {
+                warn!("Failed to save the FSEvents resume point: {}", e);
+            }
+        }
         info!("Watcher thread exiting");
     });
 
     Ok(handle)
 }
 
-fn apply_watcher_updates(state: &SharedState, updates: Vec<IndexUpdate>) {
-    let config = { state.read().unwrap().config.clone() };
-    let updates = prepare_watcher_updates(&config, updates);
-    apply_watcher_updates_chunked(state, updates, WATCHER_APPLY_CHUNK_SIZE, |_| {
+/// Changes made while the daemon was stopped, from FSEvents history. When the
+/// history has gaps, the replayed paths are still applied and a reconcile is
+/// queued to cover the rest.
+fn replay_changes(config: &Config, state: &SharedState, point: ResumePoint) -> Vec<IndexUpdate> {
+    let activity = Arc::clone(&state.read().unwrap().activity);
+    let started = Instant::now();
+    match vicaya_watcher::replay_since(&config.effective_roots(), point.event_id, REPLAY_TIMEOUT) {
+        Ok(replay) if replay.complete => {
+            info!(
+                "Replayed {} paths changed while stopped in {:?}",
+                replay.updates.len(),
+                started.elapsed()
+            );
+            replay.updates
+        }
+        Ok(replay) => {
+            warn!(
+                "FSEvents history since event {} is incomplete; reconciling",
+                point.event_id
+            );
+            activity.request_reconcile();
+            replay.updates
+        }
+        Err(e) => {
+            warn!("FSEvents replay failed ({}); reconciling", e);
+            activity.request_reconcile();
+            Vec::new()
+        }
+    }
+}
+
+/// `--profile NAME` or `--profile=NAME` from the daemon's arguments.
+fn profile_arg(args: impl IntoIterator<Item = String>) -> Option<String> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--profile" {
+            return args.next();
+        }
+        if let Some(name) = arg.strip_prefix("--profile=") {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+fn apply_watcher_updates(state: &SharedState, updates: Vec<IndexUpdate>, hooks: &mut HookRunner) {
+    let (config, canonical_paths, activity) = {
+        let state = state.read().unwrap();
+        (
+            state.config.clone(),
+            state.canonical_paths.clone(),
+            Arc::clone(&state.activity),
+        )
+    };
+    let updates = prepare_watcher_updates(&config, &canonical_paths, updates);
+    hooks.observe(&config, &updates);
+    apply_watcher_updates_chunked(state, &updates, WATCHER_APPLY_CHUNK_SIZE, |_| {
         std::thread::yield_now();
     });
+    crate::ipc_server::persist_renamed_smriti(state);
+    activity.changes.publish(&updates);
 }
 
-fn prepare_watcher_updates(config: &Config, updates: Vec<IndexUpdate>) -> Vec<PreparedIndexUpdate> {
+fn prepare_watcher_updates(
+    config: &Config,
+    canonical_paths: &PathCanonicalizer,
+    updates: Vec<IndexUpdate>,
+) -> Vec<PreparedIndexUpdate> {
     updates
         .into_iter()
-        .map(|update| prepare_index_update(config, update))
+        .map(|update| prepare_index_update(config, canonical_paths, update))
         .collect()
 }
 
 fn apply_watcher_updates_chunked<F>(
     state: &SharedState,
-    updates: Vec<PreparedIndexUpdate>,
+    updates: &[PreparedIndexUpdate],
     chunk_size: usize,
     mut after_chunk: F,
 ) where
@@ -222,6 +498,7 @@ fn apply_watcher_updates_chunked<F>(
             for update in chunk {
                 state.apply_prepared_update(update.clone());
             }
+            state.activity.watcher_events_applied(chunk.len());
         }
 
         if idx + 1 < chunk_count {
@@ -236,45 +513,93 @@ fn start_reconcile_thread(
     shutdown: Arc<AtomicBool>,
     journal_lock: Arc<Mutex<()>>,
     rebuild_lock: Arc<Mutex<()>>,
-    had_index: bool,
+    startup_reconcile: bool,
 ) -> Result<std::thread::JoinHandle<()>> {
     let handle = std::thread::spawn(move || {
-        if had_index && !shutdown.load(std::sync::atomic::Ordering::Relaxed) {
+        if startup_reconcile && !shutdown.load(std::sync::atomic::Ordering::Relaxed) {
             // Initial reconcile: discover pre-existing files that won't emit watcher events.
-            if let Err(e) =
-                crate::ipc_server::full_rebuild_from_disk(&state, &journal_lock, &rebuild_lock)
-            {
+            if let Err(e) = crate::reconcile::reconcile_from_disk(
+                &state,
+                &journal_lock,
+                &rebuild_lock,
+                ScanProfile::Reconcile,
+            ) {
                 warn!("Initial reconcile failed: {}", e);
                 let mut state = state.write().unwrap();
                 state.reconciling = false;
             }
         }
 
-        // Scheduled daily reconciliation for resilience against missed watcher events.
+        // Scheduled reconciliation for resilience against missed watcher
+        // events, plus reconciles requested over IPC and catch-ups after the
+        // machine wakes, when FSEvents may have dropped what changed.
+        let schedules = config.performance.reconcile_schedules();
+        let min_gap_secs = config.performance.reconcile_min_gap_minutes as i64 * 60;
+        let activity = Arc::clone(&state.read().unwrap().activity);
+        let mut wake = WakeDetector::new(SystemTime::now(), Instant::now());
+        let mut wake_catch_up: Option<Instant> = None;
         loop {
-            if shutdown.load(std::sync::atomic::Ordering::Relaxed) {
-                break;
-            }
+            let due = next_reconcile_time(&schedules, chrono::Local::now());
+            activity.set_next_reconcile(due.map(|due| due.timestamp()));
 
-            let sleep_for = next_reconcile_sleep(config.performance.reconcile_hour);
-            let mut slept = std::time::Duration::from_secs(0);
-            while slept < sleep_for {
+            let trigger = loop {
                 if shutdown.load(std::sync::atomic::Ordering::Relaxed) {
                     return;
                 }
-                let step = std::cmp::min(std::time::Duration::from_millis(250), sleep_for - slept);
-                std::thread::sleep(step);
-                slept += step;
-            }
-
-            if shutdown.load(std::sync::atomic::Ordering::Relaxed) {
-                break;
+                if activity.take_reconcile_request() {
+                    break ReconcileTrigger::Requested;
+                }
+                if let Some(due) = due.filter(|&due| chrono::Local::now() >= due) {
+                    break ReconcileTrigger::Scheduled(due);
+                }
+                if let Some(slept) = wake.poll(SystemTime::now(), Instant::now()) {
+                    if config.performance.reconcile_on_wake {
+                        info!(
+                            "Woke after about {} min asleep; catch-up reconcile in {}s",
+                            slept.as_secs() / 60,
+                            WAKE_RECONCILE_DELAY.as_secs()
+                        );
+                        wake_catch_up = Some(Instant::now() + WAKE_RECONCILE_DELAY);
+                    }
+                }
+                if wake_catch_up.is_some_and(|at| Instant::now() >= at) {
+                    break ReconcileTrigger::Wake;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(250));
+            };
+
+            let now = chrono::Local::now().timestamp();
+            let skip = match trigger {
+                ReconcileTrigger::Requested => None,
+                ReconcileTrigger::Scheduled(due) => reconcile_skip_reason(
+                    due.timestamp(),
+                    now,
+                    activity.last_reconcile(),
+                    min_gap_secs,
+                ),
+                ReconcileTrigger::Wake => {
+                    reconcile_skip_reason(now, now, activity.last_reconcile(), min_gap_secs)
+                }
+            };
+            if let Some(reason) = skip {
+                info!("Skipping {}: {}", trigger.describe(), reason);
+                if let ReconcileTrigger::Wake = trigger {
+                    wake_catch_up = None;
+                }
+                continue;
             }
 
-            if let Err(e) =
-                crate::ipc_server::full_rebuild_from_disk(&state, &journal_lock, &rebuild_lock)
-            {
-                warn!("Scheduled reconcile failed: {}", e);
+            // Whatever runs now also covers a pending wake catch-up.
+            wake_catch_up = None;
+            let kind = trigger.label();
+            info!("{} reconcile starting", kind);
+            if let Err(e) = crate::reconcile::reconcile_from_disk(
+                &state,
+                &journal_lock,
+                &rebuild_lock,
+                ScanProfile::Reconcile,
+            ) {
+                warn!("{} reconcile failed: {}", kind, e);
                 let mut state = state.write().unwrap();
                 state.reconciling = false;
             }
@@ -284,56 +609,187 @@ fn start_reconcile_thread(
     Ok(handle)
 }
 
-fn next_reconcile_sleep(reconcile_hour: u8) -> std::time::Duration {
-    use chrono::{Local, TimeZone};
-
-    let now = Local::now();
-    let today = now.date_naive();
-    let hour = reconcile_hour as u32;
-
-    let naive_today = today
-        .and_hms_opt(hour, 0, 0)
-        .unwrap_or_else(|| today.and_hms_opt(3, 0, 0).unwrap());
-
-    let mut target = Local
-        .from_local_datetime(&naive_today)
-        .earliest()
-        .unwrap_or(now);
-
-    if target <= now {
-        let tomorrow = today + chrono::Duration::days(1);
-        let naive_tomorrow = tomorrow
-            .and_hms_opt(hour, 0, 0)
-            .unwrap_or_else(|| tomorrow.and_hms_opt(3, 0, 0).unwrap());
-        target = Local
-            .from_local_datetime(&naive_tomorrow)
-            .earliest()
-            .unwrap_or(now + chrono::Duration::hours(24));
+/// What started a reconcile.
+#[derive(Debug, Clone, Copy)]
+enum ReconcileTrigger {
+    /// `Request::Reconcile` over IPC; never skipped.
+    Requested,
+    /// A `reconcile_schedule` entry fell due at this time.
+    Scheduled(chrono::DateTime<chrono::Local>),
+    /// The machine woke from sleep.
+    Wake,
+}
+
+impl ReconcileTrigger {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Requested => "Requested",
+            Self::Scheduled(_) => "Scheduled",
+            Self::Wake => "Wake catch-up",
+        }
+    }
+
+    fn describe(self) -> String {
+        match self {
+            Self::Requested => "requested reconcile".to_string(),
+            Self::Scheduled(due) => {
+                format!("reconcile scheduled for {}", due.format("%Y-%m-%d %H:%M"))
+            }
+            Self::Wake => "wake catch-up reconcile".to_string(),
+        }
     }
+}
 
-    let delta = target - now;
-    delta.to_std().unwrap_or(std::time::Duration::from_secs(0))
+/// Wait this long after a wake before the catch-up reconcile, so network
+/// volumes can remount and the watcher's backlog drains first.
+const WAKE_RECONCILE_DELAY: Duration = Duration::from_secs(30);
+
+/// A wall-clock jump at least this far past the monotonic clock between two
+/// polls counts as sleep.
+const WAKE_MIN_SLEEP: Duration = Duration::from_secs(60);
+
+/// Notices system sleep from the reconcile loop's polls: `Instant` stops
+/// while the machine is suspended (on macOS and Linux) but the wall clock
+/// keeps going. A wall clock set forward by hand looks the same, which costs
+/// at most one extra, rate-limited reconcile.
+struct WakeDetector {
+    wall: SystemTime,
+    mono: Instant,
 }
 
-fn append_journal(path: &Path, updates: &[vicaya_watcher::IndexUpdate]) -> std::io::Result<()> {
-    use std::fs::OpenOptions;
-    use std::io::Write;
+impl WakeDetector {
+    fn new(wall: SystemTime, mono: Instant) -> Self {
+        Self { wall, mono }
+    }
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
+    /// How long the machine slept since the previous poll, if it did.
+    fn poll(&mut self, wall: SystemTime, mono: Instant) -> Option<Duration> {
+        let wall_elapsed = wall.duration_since(self.wall).unwrap_or_default();
+        let mono_elapsed = mono.saturating_duration_since(self.mono);
+        self.wall = wall;
+        self.mono = mono;
+        let slept = wall_elapsed.saturating_sub(mono_elapsed);
+        (slept >= WAKE_MIN_SLEEP).then_some(slept)
     }
+}
 
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-    for update in updates {
-        let line = serde_json::to_string(update).unwrap_or_default();
-        if line.is_empty() {
-            continue;
+/// Scheduled reconciles this many seconds late were slept through and are
+/// skipped instead of starting when the machine wakes.
+const MISSED_RECONCILE_GRACE_SECS: i64 = 30 * 60;
+
+/// The next time any of `schedules` fires after `now`, skipping local times
+/// a DST change leaves out.
+fn next_reconcile_time(
+    schedules: &[CronSchedule],
+    now: chrono::DateTime<chrono::Local>,
+) -> Option<chrono::DateTime<chrono::Local>> {
+    use chrono::TimeZone;
+
+    let mut after = now.naive_local();
+    for _ in 0..4 {
+        let next = vicaya_core::schedule::next_run(schedules, after)?;
+        if let Some(due) = chrono::Local.from_local_datetime(&next).earliest() {
+            return Some(due);
         }
-        file.write_all(line.as_bytes())?;
-        file.write_all(b"\n")?;
+        after = next;
     }
-    file.flush()?;
-    Ok(())
+    None
+}
+
+/// Why a scheduled reconcile due at `due` should not run at `now`: the
+/// machine was asleep when it was due, or one finished less than
+/// `min_gap_secs` ago. Timestamps are Unix seconds.
+fn reconcile_skip_reason(
+    due: i64,
+    now: i64,
+    last_reconcile: Option<i64>,
+    min_gap_secs: i64,
+) -> Option<String> {
+    let late = now - due;
+    if late > MISSED_RECONCILE_GRACE_SECS {
+        return Some(format!(
+            "missed by {} min (was the machine asleep?)",
+            late / 60
+        ));
+    }
+    let since = now - last_reconcile?;
+    (min_gap_secs > 0 && since < min_gap_secs)
+        .then(|| format!("the last reconcile finished {} min ago", since / 60))
+}
+
+fn count_journal_entries(path: &Path) -> usize {
+    use std::io::BufRead;
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return 0;
+    };
+    std::io::BufReader::new(file)
+        .lines()
+        .map_while(std::result::Result::ok)
+        .filter(|line| !line.trim().is_empty())
+        .count()
+}
+
+/// Tracks appends not yet forced to disk under the `[journal] fsync` policy.
+#[derive(Debug)]
+struct JournalSync {
+    policy: JournalFsync,
+    /// When the oldest unsynced append was written.
+    dirty_since: Option<Instant>,
+}
+
+impl JournalSync {
+    fn new(policy: JournalFsync) -> Self {
+        Self {
+            policy,
+            dirty_since: None,
+        }
+    }
+
+    /// Record an appended batch; returns whether it must be synced now.
+    fn appended(&mut self, now: Instant) -> bool {
+        match self.policy {
+            JournalFsync::None => false,
+            JournalFsync::EveryBatch => true,
+            JournalFsync::Interval { .. } => {
+                self.dirty_since.get_or_insert(now);
+                self.due(now)
+            }
+        }
+    }
+
+    /// Whether unsynced appends have reached their interval deadline.
+    fn due(&self, now: Instant) -> bool {
+        match (self.policy, self.dirty_since) {
+            (JournalFsync::Interval { interval_ms }, Some(since)) => {
+                now.duration_since(since) >= Duration::from_millis(interval_ms)
+            }
+            _ => false,
+        }
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.dirty_since.is_some()
+    }
+
+    fn synced(&mut self) {
+        self.dirty_since = None;
+    }
+}
+
+/// Force appended journal entries to stable storage. The caller holds the
+/// journal lock. A journal truncated or removed by a rebuild has nothing
+/// left to sync.
+fn sync_journal(path: &Path, journal_sync: &mut JournalSync) {
+    match std::fs::File::open(path).and_then(|file| file.sync_data()) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => {
+            warn!("Failed to sync journal: {}", e);
+            return;
+        }
+    }
+    journal_sync.synced();
 }
 
 fn is_internal_update(
@@ -370,14 +826,48 @@ mod tests {
             index_roots: vec![root.to_path_buf()],
             exclusions: vec![],
             respect_ignore_files: true,
+            case_insensitive_exclusions: false,
+            exclusion_cache: Default::default(),
             index_path: vicaya_dir.join("index"),
             max_memory_mb: 128,
+            max_indexed_files: 0,
             performance: PerformanceConfig {
                 scanner_threads: 2,
                 reconcile_hour: 3,
+                query_timeout_ms: 250,
+                query_max_candidates: 0,
+                warmup_trigrams: 0,
+                reconcile_schedule: Vec::new(),
+                reconcile_min_gap_minutes: 60,
+                reconcile_on_wake: true,
+                initial_scan: Default::default(),
+                reconcile: Default::default(),
             },
             smriti: vicaya_core::config::SmritiConfig::default(),
             content_search: vicaya_core::config::ContentSearchConfig::default(),
+            preview: vicaya_core::config::PreviewConfig::default(),
+            associations: Default::default(),
+            aliases: Default::default(),
+            http: vicaya_core::config::HttpConfig::default(),
+            journal: vicaya_core::config::JournalConfig::default(),
+            telemetry: vicaya_core::config::TelemetryConfig::default(),
+            ranking: vicaya_core::config::RankingConfig::default(),
+            trash: vicaya_core::config::TrashConfig::default(),
+            normalization: Default::default(),
+            hooks: Vec::new(),
+            root_max_depth: Default::default(),
+            show_hidden: true,
+            restore_tui_session: false,
+            offline_while_starting: false,
+            auto_scope: vicaya_core::config::AutoScope::default(),
+            icons: vicaya_core::config::IconStyle::default(),
+            language: Default::default(),
+            archives: vicaya_core::config::ArchiveConfig::default(),
+            content_hash: vicaya_core::config::ContentHashConfig::default(),
+            index_bundle_contents: false,
+            index_backup_volumes: false,
+            index_directory_paths: false,
+            index_profile: Default::default(),
         }
     }
 
@@ -396,15 +886,11 @@ mod tests {
     fn state_contains_path(state: &DaemonState, path: &Path) -> bool {
         let needle = path.to_string_lossy();
         state.snapshot.file_table.iter().any(|(_, meta)| {
-            if meta.path_len == 0 {
-                return false;
-            }
-
-            state
-                .snapshot
-                .string_arena
-                .get(meta.path_offset, meta.path_len)
-                .is_some_and(|indexed| indexed == needle)
+            !meta.is_tombstone()
+                && state
+                    .snapshot
+                    .file_table
+                    .path_eq(meta, &state.snapshot.string_arena, &needle)
         })
     }
 
@@ -428,8 +914,10 @@ mod tests {
         std::fs::write(&first, "one").unwrap();
         std::fs::write(&second, "two").unwrap();
 
+        let guard = state.read().unwrap();
         let updates = prepare_watcher_updates(
-            &state.read().unwrap().config,
+            &guard.config,
+            &guard.canonical_paths,
             vec![
                 IndexUpdate::Create {
                     path: first.to_string_lossy().to_string(),
@@ -439,12 +927,14 @@ mod tests {
                 },
             ],
         );
+        guard.activity.watcher_events_queued(updates.len());
+        drop(guard);
         let (ready_tx, ready_rx) = std::sync::mpsc::channel();
         let (resume_tx, resume_rx) = std::sync::mpsc::channel();
         let worker_state = Arc::clone(&state);
 
         let worker = std::thread::spawn(move || {
-            apply_watcher_updates_chunked(&worker_state, updates, 1, |chunk| {
+            apply_watcher_updates_chunked(&worker_state, &updates, 1, |chunk| {
                 if chunk == 1 {
                     ready_tx.send(()).unwrap();
                     resume_rx.recv().unwrap();
@@ -458,15 +948,86 @@ mod tests {
         {
             let state = state.read().unwrap();
             assert!(public_indexed_count(&state) >= 1);
+            assert_eq!(state.activity.pending_watcher_events(), 1);
         }
         resume_tx.send(()).unwrap();
         worker.join().unwrap();
 
         let state = state.read().unwrap();
+        assert_eq!(state.activity.pending_watcher_events(), 0);
         assert!(state_contains_path(&state, &first));
         assert!(state_contains_path(&state, &second));
     }
 
+    #[test]
+    fn journal_entry_count_matches_appended_updates() {
+        let dir = tempdir().unwrap();
+        let journal = dir.path().join("index.journal");
+        assert_eq!(count_journal_entries(&journal), 0);
+
+        append_journal(
+            &journal,
+            &[
+                IndexUpdate::Create {
+                    path: "/tmp/repo/a.rs".to_string(),
+                },
+                IndexUpdate::Delete {
+                    path: "/tmp/repo/b.rs".to_string(),
+                },
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(count_journal_entries(&journal), 2);
+    }
+
+    #[test]
+    fn journal_sync_follows_the_configured_policy() {
+        let start = Instant::now();
+        let later = |ms| start + Duration::from_millis(ms);
+
+        let mut none = JournalSync::new(JournalFsync::None);
+        assert!(!none.appended(start));
+        assert!(!none.is_dirty());
+        assert!(!none.due(later(60_000)));
+
+        let mut every = JournalSync::new(JournalFsync::EveryBatch);
+        assert!(every.appended(start));
+
+        let mut interval = JournalSync::new(JournalFsync::Interval { interval_ms: 500 });
+        assert!(!interval.appended(start));
+        // Later appends do not push the deadline back.
+        assert!(!interval.appended(later(400)));
+        assert!(!interval.due(later(499)));
+        assert!(interval.due(later(500)));
+        assert!(interval.appended(later(600)));
+        interval.synced();
+        assert!(!interval.is_dirty());
+        assert!(!interval.due(later(5_000)));
+    }
+
+    #[test]
+    fn journal_sync_tolerates_a_missing_journal() {
+        let dir = tempdir().unwrap();
+        let journal = dir.path().join("index.journal");
+        let mut journal_sync = JournalSync::new(JournalFsync::Interval { interval_ms: 0 });
+
+        assert!(journal_sync.appended(Instant::now()));
+        sync_journal(&journal, &mut journal_sync);
+        assert!(!journal_sync.is_dirty());
+
+        append_journal(
+            &journal,
+            &[IndexUpdate::Create {
+                path: "/tmp/repo/a.rs".to_string(),
+            }],
+        )
+        .unwrap();
+        assert!(journal_sync.appended(Instant::now()));
+        sync_journal(&journal, &mut journal_sync);
+        assert!(!journal_sync.is_dirty());
+    }
+
     #[test]
     fn internal_update_filter_rejects_vicaya_state_paths() {
         let internal_dir = Path::new("/tmp/vicaya");
@@ -501,4 +1062,107 @@ mod tests {
             index_dir
         ));
     }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn replay_without_fsevents_history_falls_back_to_a_reconcile() {
+        let vicaya_dir = tempdir().unwrap();
+        let root = tempdir().unwrap();
+        let config = test_config(root.path(), vicaya_dir.path());
+        let state = build_state(root.path(), vicaya_dir.path());
+
+        let updates = replay_changes(&config, &state, ResumePoint { event_id: 1 });
+        assert!(updates.is_empty());
+        assert!(state.read().unwrap().activity.take_reconcile_request());
+    }
+
+    #[test]
+    fn scheduled_reconciles_skip_missed_and_recent_runs() {
+        let due = 1_700_000_000;
+        assert_eq!(reconcile_skip_reason(due, due + 5, None, 3600), None);
+        assert_eq!(
+            reconcile_skip_reason(due, due + 2 * 3600, None, 3600).as_deref(),
+            Some("missed by 120 min (was the machine asleep?)")
+        );
+        assert_eq!(
+            reconcile_skip_reason(due, due + 5, Some(due - 600), 3600).as_deref(),
+            Some("the last reconcile finished 10 min ago")
+        );
+        assert_eq!(
+            reconcile_skip_reason(due, due + 5, Some(due - 7200), 3600),
+            None
+        );
+        assert_eq!(
+            reconcile_skip_reason(due, due + 5, Some(due - 600), 0),
+            None
+        );
+    }
+
+    #[test]
+    fn wake_detector_reports_wall_clock_time_the_monotonic_clock_missed() {
+        let wall = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mono = Instant::now();
+        let mut wake = WakeDetector::new(wall, mono);
+
+        let (wall, mono) = (wall + Duration::from_secs(1), mono + Duration::from_secs(1));
+        assert_eq!(wake.poll(wall, mono), None);
+
+        let (wall, mono) = (
+            wall + Duration::from_secs(7201),
+            mono + Duration::from_secs(1),
+        );
+        assert_eq!(wake.poll(wall, mono), Some(Duration::from_secs(7200)));
+
+        // A busy poll that took 30s of both clocks is not sleep, nor is the
+        // wall clock stepping backwards.
+        let (wall, mono) = (
+            wall + Duration::from_secs(30),
+            mono + Duration::from_secs(30),
+        );
+        assert_eq!(wake.poll(wall, mono), None);
+        let (wall, mono) = (
+            wall - Duration::from_secs(600),
+            mono + Duration::from_secs(1),
+        );
+        assert_eq!(wake.poll(wall, mono), None);
+    }
+
+    #[test]
+    fn next_reconcile_time_picks_the_earliest_schedule() {
+        use chrono::TimeZone;
+
+        let schedules = vec![
+            "0 3 * * *".parse().unwrap(),
+            "30 13 * * 1-5".parse().unwrap(),
+        ];
+        // 2026-03-06 is a Friday.
+        let now = chrono::Local
+            .with_ymd_and_hms(2026, 3, 6, 12, 0, 0)
+            .earliest()
+            .unwrap();
+        let due = next_reconcile_time(&schedules, now).unwrap();
+        assert_eq!(
+            due.naive_local().format("%Y-%m-%d %H:%M").to_string(),
+            "2026-03-06 13:30"
+        );
+        let due = next_reconcile_time(&schedules, due).unwrap();
+        assert_eq!(
+            due.naive_local().format("%Y-%m-%d %H:%M").to_string(),
+            "2026-03-07 03:00"
+        );
+    }
+
+    #[test]
+    fn profile_arg_accepts_separate_and_inline_values() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            profile_arg(args(&["vicaya-daemon", "--profile", "work"])).as_deref(),
+            Some("work")
+        );
+        assert_eq!(
+            profile_arg(args(&["vicaya-daemon", "--profile=home"])).as_deref(),
+            Some("home")
+        );
+        assert_eq!(profile_arg(args(&["vicaya-daemon"])), None);
+    }
 }
@@ -0,0 +1,109 @@
+//! Search scopes used this session, behind `Request::ScopeUsed`.
+//!
+//! Someone who works inside a directory in the TUI and then goes back to
+//! global search usually still cares about that directory. Clients report
+//! each scope they enter, and global searches add a boost to results under
+//! the recently used ones: `[ranking] recent_scope_boost` right after use,
+//! halving every `recent_scope_half_life_mins` the scope goes unused. The
+//! list lives in memory only, so a daemon restart starts a fresh session.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// How many scopes are remembered; older ones have faded anyway.
+const MAX_SCOPES: usize = 16;
+
+/// A scope and when it was last used (epoch seconds).
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RecentScope {
+    path: PathBuf,
+    last_used: i64,
+}
+
+/// Recently used search scopes, most recent first.
+#[derive(Debug, Default)]
+pub(crate) struct RecentScopes {
+    scopes: VecDeque<RecentScope>,
+}
+
+impl RecentScopes {
+    /// Note that `path` was used as a scope at `now`, moving it to the front.
+    pub(crate) fn record(&mut self, path: PathBuf, now: i64) {
+        self.scopes.retain(|scope| scope.path != path);
+        self.scopes.push_front(RecentScope {
+            path,
+            last_used: now,
+        });
+        self.scopes.truncate(MAX_SCOPES);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scopes.is_empty()
+    }
+
+    /// The boost for a result at `path`: `max_boost` halved once per
+    /// `half_life_secs` since the most recent use of a scope containing it,
+    /// or 0 when none does.
+    pub(crate) fn boost_for_path(
+        &self,
+        path: &str,
+        now: i64,
+        max_boost: f32,
+        half_life_secs: i64,
+    ) -> f32 {
+        if max_boost <= 0.0 {
+            return 0.0;
+        }
+        let path = Path::new(path);
+        self.scopes
+            .iter()
+            .find(|scope| path.starts_with(&scope.path))
+            .map(|scope| {
+                let age = now.saturating_sub(scope.last_used).max(0) as f32;
+                max_boost * 0.5f32.powf(age / half_life_secs.max(1) as f32)
+            })
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boost_fades_with_the_most_recent_use_of_a_containing_scope() {
+        let mut scopes = RecentScopes::default();
+        assert!(scopes.is_empty());
+        scopes.record(PathBuf::from("/home/me/work"), 1_000);
+        scopes.record(PathBuf::from("/home/me/work/api"), 1_600);
+
+        let boost = |scopes: &RecentScopes, path: &str, now: i64| {
+            scopes.boost_for_path(path, now, 0.2, 600)
+        };
+        assert_eq!(boost(&scopes, "/home/me/work/api/main.rs", 1_600), 0.2);
+        assert!((boost(&scopes, "/home/me/work/README.md", 1_600) - 0.1).abs() < 1e-6);
+        assert!((boost(&scopes, "/home/me/work/api/main.rs", 2_800) - 0.05).abs() < 1e-6);
+        assert_eq!(boost(&scopes, "/home/me/workshop/notes.md", 1_600), 0.0);
+        assert_eq!(boost(&scopes, "/etc/hosts", 1_600), 0.0);
+        assert_eq!(
+            scopes.boost_for_path("/home/me/work/a", 1_600, 0.0, 600),
+            0.0
+        );
+
+        // Using a scope again refreshes it instead of listing it twice.
+        scopes.record(PathBuf::from("/home/me/work"), 2_800);
+        assert_eq!(scopes.scopes.len(), 2);
+        assert_eq!(boost(&scopes, "/home/me/work/README.md", 2_800), 0.2);
+    }
+
+    #[test]
+    fn only_the_latest_scopes_are_kept() {
+        let mut scopes = RecentScopes::default();
+        for n in 0..MAX_SCOPES as i64 + 4 {
+            scopes.record(PathBuf::from(format!("/scope{n}")), n);
+        }
+        assert_eq!(scopes.scopes.len(), MAX_SCOPES);
+        assert_eq!(scopes.boost_for_path("/scope0/file", 100, 0.1, 60), 0.0);
+        assert!(scopes.boost_for_path("/scope19/file", 100, 0.1, 60) > 0.0);
+    }
+}
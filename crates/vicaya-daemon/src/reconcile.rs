@@ -0,0 +1,539 @@
+//! Reconciles the live index with disk one index root at a time.
+//!
+//! [`full_rebuild_from_disk`] builds a complete replacement index next to
+//! the live one, so its peak is two indexes. [`reconcile_from_disk`] instead
+//! scans each effective root into a shard of its own with
+//! [`Scanner::scan_shard`], compares it with the live entries of that root,
+//! and journals and applies the differences as `Create`, `Modify` and
+//! `Delete` updates, [`UPDATES_PER_BATCH`] at a time, like a watcher batch.
+//! The shard is dropped before the next root is scanned, so the peak is the
+//! live index plus the largest root's shard. Entries outside every root,
+//! left over from a root dropped from the config, are deleted at the end.
+//!
+//! Applying updates adds entries and tombstones but never compacts, and
+//! `index.bin` is left as it was. Once tombstones or journal entries pass
+//! `1 / COMPACT_SHARE` of the live entries, the next reconcile rebuilds the
+//! whole index instead, which compacts it, saves it and trims the journal.
+//! Indexes with a `max_indexed_files` cap or with archive members always
+//! rebuild whole: the cap is planned across every root, and members cannot
+//! be re-read through updates.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info, warn};
+use vicaya_core::accounting::ThreadUsage;
+use vicaya_core::config::ScanProfile;
+use vicaya_core::Result;
+use vicaya_index::FileMeta;
+use vicaya_scanner::{IndexSnapshot, Scanner};
+use vicaya_watcher::IndexUpdate;
+
+use crate::ipc_server::{
+    append_journal, full_rebuild_from_disk, now_epoch_seconds, persist_renamed_smriti,
+    prepare_index_update, record_reconcile_usage, DaemonActivity, DaemonState, PreparedIndexUpdate,
+    SharedState,
+};
+
+/// Updates journaled and applied under one acquisition of each lock.
+const UPDATES_PER_BATCH: usize = 4096;
+
+/// A reconcile rebuilds the whole index once tombstones or journal entries
+/// pass this fraction (1 / `COMPACT_SHARE`) of the live entries.
+const COMPACT_SHARE: usize = 4;
+
+/// Bring the index in line with disk with the `profile` scan settings, root
+/// by root, or through [`full_rebuild_from_disk`] when the index is due for
+/// compaction or cannot be reconciled in shards. Returns the number of
+/// indexed entries.
+pub fn reconcile_from_disk(
+    state: &SharedState,
+    journal_lock: &Arc<Mutex<()>>,
+    rebuild_lock: &Arc<Mutex<()>>,
+    profile: ScanProfile,
+) -> Result<usize> {
+    let rebuild_reason = full_rebuild_reason(&state.read().unwrap());
+    if let Some(reason) = rebuild_reason {
+        info!("Rebuilding the whole index: {}", reason);
+        return full_rebuild_from_disk(state, journal_lock, rebuild_lock, profile);
+    }
+    let _rebuild_guard = rebuild_lock.lock().unwrap();
+
+    let activity = {
+        let mut state = state.write().unwrap();
+        state.reconciling = true;
+        let scan = state.config.performance.scan_settings(profile);
+        state
+            .activity
+            .begin_reconcile(state.indexed_file_count(), scan);
+        Arc::clone(&state.activity)
+    };
+
+    let reconcile_started = ThreadUsage::now();
+    let mut scan_usage = ThreadUsage::default();
+    let result = (|| {
+        let config = state.read().unwrap().config.clone();
+        let roots = config.effective_roots();
+        let scanner = Scanner::new(config).with_profile(profile);
+
+        info!("Starting reconcile of {} index roots...", roots.len());
+        let mut scanned_before = 0usize;
+        let mut changed = 0usize;
+        for (root_idx, root) in roots.iter().enumerate() {
+            let scan_started = ThreadUsage::now();
+            let mut scanned_root = 0usize;
+            let shard = scanner.scan_shard(root, |scanned| {
+                scanned_root = scanned;
+                activity.reconcile_scanned(scanned_before + scanned);
+            });
+            add_usage(&mut scan_usage, scan_started.elapsed());
+            scanned_before += scanned_root;
+            let shard = shard_entries(shard?);
+
+            let live = live_entries(&state.read().unwrap(), &roots, Some(root_idx));
+            let updates = diff_entries(live, shard);
+            debug!("{} changes under {}", updates.len(), root.display());
+            changed += apply_updates(state, journal_lock, &activity, updates, true);
+            state
+                .write()
+                .unwrap()
+                .root_scans
+                .insert(root.clone(), now_epoch_seconds());
+        }
+
+        let orphans = diff_entries(
+            live_entries(&state.read().unwrap(), &roots, None),
+            Vec::new(),
+        );
+        if !orphans.is_empty() {
+            info!("Dropping {} entries outside the index roots", orphans.len());
+        }
+        // These are gone from the index even while they exist on disk.
+        changed += apply_updates(state, journal_lock, &activity, orphans, false);
+        persist_renamed_smriti(state);
+
+        let mut state = state.write().unwrap();
+        state.last_updated = now_epoch_seconds();
+        state.reconciling = false;
+        activity
+            .last_reconcile
+            .store(state.last_updated, std::sync::atomic::Ordering::Relaxed);
+        let files_indexed = state.indexed_file_count();
+        info!(
+            "Reconcile complete: {} changes applied, {} files indexed",
+            changed, files_indexed
+        );
+        Ok(files_indexed)
+    })();
+
+    if result.is_err() {
+        let mut state = state.write().unwrap();
+        state.reconciling = false;
+    }
+    activity.end_reconcile();
+    record_reconcile_usage(&activity, reconcile_started.elapsed(), scan_usage);
+
+    result
+}
+
+/// Why the index must be rebuilt whole rather than reconciled root by root.
+fn full_rebuild_reason(state: &DaemonState) -> Option<&'static str> {
+    let live = state.indexed_file_count();
+    let tombstones = state.snapshot.file_table.len().saturating_sub(live);
+    if state.config.max_indexed_files > 0 {
+        Some("max_indexed_files caps it across roots")
+    } else if state.config.archives.enabled {
+        Some("archive members are indexed")
+    } else if tombstones * COMPACT_SHARE > live {
+        Some("compacting removed entries")
+    } else if state.activity.journal_entries() * COMPACT_SHARE > live {
+        Some("trimming the journal")
+    } else {
+        None
+    }
+}
+
+fn add_usage(total: &mut ThreadUsage, usage: ThreadUsage) {
+    total.cpu_ns += usage.cpu_ns;
+    total.read_bytes = match (total.read_bytes, usage.read_bytes) {
+        (Some(total), Some(bytes)) => Some(total + bytes),
+        (total, bytes) => total.or(bytes),
+    };
+}
+
+/// The entries of a shard by path, sorted, keeping only their metadata so
+/// the shard's trigram index is freed before the comparison.
+fn shard_entries(shard: IndexSnapshot) -> Vec<(String, FileMeta)> {
+    let mut entries: Vec<(String, FileMeta)> = shard
+        .file_table
+        .iter()
+        .filter(|(_, meta)| !meta.is_tombstone())
+        .filter_map(|(_, meta)| {
+            let path = shard.file_table.path_of(meta, &shard.string_arena)?;
+            Some((path, meta.clone()))
+        })
+        .collect();
+    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+/// Live entries of effective root `root` (an index into `roots`), or of no
+/// root for `None`, by path, sorted. Entries are matched to roots the way
+/// `DaemonState::root_stats` matches them: through their parent directory,
+/// looked up once per directory.
+fn live_entries(
+    state: &DaemonState,
+    roots: &[PathBuf],
+    root: Option<usize>,
+) -> Vec<(String, FileMeta)> {
+    let root_of = |path: &str| {
+        roots
+            .iter()
+            .position(|root| Path::new(path).starts_with(root))
+    };
+
+    let table = &state.snapshot.file_table;
+    let arena = &state.snapshot.string_arena;
+    let mut dir_roots = std::collections::HashMap::new();
+    let mut dir_path = String::new();
+    let mut entries = Vec::new();
+    for (_, meta) in table.iter() {
+        if meta.is_tombstone() {
+            continue;
+        }
+        let mut entry_root = meta.parent.and_then(|dir| {
+            *dir_roots.entry(dir).or_insert_with(|| {
+                dir_path.clear();
+                table
+                    .write_dir_path(dir, arena, &mut dir_path)
+                    .then(|| root_of(&dir_path))
+                    .flatten()
+            })
+        });
+        let path = if entry_root.is_none() {
+            // A root's own entry lives in a directory outside every root.
+            let Some(path) = table.path_of(meta, arena) else {
+                continue;
+            };
+            entry_root = root_of(&path);
+            (entry_root == root).then_some(path)
+        } else if entry_root == root {
+            table.path_of(meta, arena)
+        } else {
+            None
+        };
+        if let Some(path) = path {
+            entries.push((path, meta.clone()));
+        }
+    }
+    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    entries
+}
+
+/// The updates that turn the `live` entries into the `scanned` ones, both
+/// sorted by path: deletes first, then creates and modifies in path order.
+/// Deletes below a deleted directory are left out, since deleting the
+/// directory removes them.
+fn diff_entries(
+    live: Vec<(String, FileMeta)>,
+    scanned: Vec<(String, FileMeta)>,
+) -> Vec<IndexUpdate> {
+    let mut deletes = Vec::new();
+    let mut upserts = Vec::new();
+    let mut deleted_dir: Option<String> = None;
+    let mut delete = |path: String, meta: &FileMeta| {
+        if deleted_dir
+            .as_deref()
+            .is_some_and(|dir| Path::new(&path).starts_with(dir))
+        {
+            return;
+        }
+        if meta.is_dir {
+            deleted_dir = Some(path.clone());
+        }
+        deletes.push(IndexUpdate::Delete { path });
+    };
+
+    let mut live = live.into_iter().peekable();
+    let mut scanned = scanned.into_iter().peekable();
+    loop {
+        let order = match (live.peek(), scanned.peek()) {
+            (None, None) => break,
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (Some((live_path, _)), Some((scanned_path, _))) => live_path.cmp(scanned_path),
+        };
+        match order {
+            std::cmp::Ordering::Less => {
+                let (path, meta) = live.next().unwrap();
+                delete(path, &meta);
+            }
+            std::cmp::Ordering::Greater => {
+                let (path, _) = scanned.next().unwrap();
+                upserts.push(IndexUpdate::Create { path });
+            }
+            std::cmp::Ordering::Equal => {
+                let (_, old) = live.next().unwrap();
+                let (path, new) = scanned.next().unwrap();
+                if !same_metadata(&old, &new) {
+                    upserts.push(IndexUpdate::Modify { path });
+                }
+            }
+        }
+    }
+    deletes.extend(upserts);
+    deletes
+}
+
+/// Whether a rescan found `new` as the index has `old`. `indexed_at` only
+/// says when each was read.
+fn same_metadata(old: &FileMeta, new: &FileMeta) -> bool {
+    old.size == new.size
+        && old.mtime == new.mtime
+        && old.dev == new.dev
+        && old.ino == new.ino
+        && old.uid == new.uid
+        && old.gid == new.gid
+        && old.mode == new.mode
+        && old.content_hash == new.content_hash
+        && old.is_dir == new.is_dir
+}
+
+/// Journal and apply `updates` in batches, as the watcher does its own.
+/// With `recheck_deletes`, a delete is dropped when its path exists again by
+/// the time the journal lock is held, so a watcher create journaled after
+/// the scan is not undone. Returns the number of updates applied.
+fn apply_updates(
+    state: &SharedState,
+    journal_lock: &Mutex<()>,
+    activity: &DaemonActivity,
+    updates: Vec<IndexUpdate>,
+    recheck_deletes: bool,
+) -> usize {
+    use rayon::prelude::*;
+
+    if updates.is_empty() {
+        return 0;
+    }
+    let (config, canonical_paths, journal_file) = {
+        let state = state.read().unwrap();
+        (
+            state.config.clone(),
+            state.canonical_paths.clone(),
+            state.journal_file.clone(),
+        )
+    };
+
+    let mut applied = 0usize;
+    let mut updates = updates.into_iter();
+    loop {
+        let batch: Vec<IndexUpdate> = updates.by_ref().take(UPDATES_PER_BATCH).collect();
+        if batch.is_empty() {
+            break;
+        }
+        let batch: Vec<IndexUpdate> = {
+            let _guard = journal_lock.lock().unwrap();
+            let batch: Vec<IndexUpdate> = batch
+                .into_iter()
+                .filter(|update| match update {
+                    IndexUpdate::Delete { path } if recheck_deletes => {
+                        std::fs::symlink_metadata(path).is_err()
+                    }
+                    _ => true,
+                })
+                .collect();
+            match append_journal(&journal_file, &batch) {
+                Ok(()) => activity.journal_appended(batch.len()),
+                Err(e) => warn!("Failed to append journal: {}", e),
+            }
+            batch
+        };
+        applied += batch.len();
+        let prepared: Vec<PreparedIndexUpdate> = batch
+            .into_par_iter()
+            .map(|update| prepare_index_update(&config, &canonical_paths, update))
+            .collect();
+        state.write().unwrap().apply_prepared_batch(prepared);
+    }
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::RwLock;
+    use vicaya_core::Config;
+
+    fn shared_state(roots: &[&Path], vicaya_dir: &Path) -> SharedState {
+        let config = Config {
+            index_roots: roots.iter().map(|root| root.to_path_buf()).collect(),
+            index_path: vicaya_dir.to_path_buf(),
+            ..Config::default()
+        };
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        Arc::new(RwLock::new(DaemonState::new(
+            config,
+            vicaya_dir.join("index.bin"),
+            vicaya_dir.join("index.journal"),
+            snapshot,
+        )))
+    }
+
+    fn reconcile(state: &SharedState) -> usize {
+        reconcile_from_disk(
+            state,
+            &Arc::new(Mutex::new(())),
+            &Arc::new(Mutex::new(())),
+            ScanProfile::Reconcile,
+        )
+        .unwrap()
+    }
+
+    fn indexed(state: &SharedState, path: &Path) -> bool {
+        state
+            .read()
+            .unwrap()
+            .get_file_id_for_path(&path.to_string_lossy())
+            .is_some()
+    }
+
+    #[test]
+    fn reconciles_each_root_in_place_through_the_journal() {
+        let vicaya_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let path = |relative: &str| root.path().join(relative);
+        std::fs::create_dir_all(path("gone/nested")).unwrap();
+        for file in ["kept.rs", "edited.rs", "gone/a.rs", "gone/nested/b.rs"] {
+            std::fs::write(path(file), "").unwrap();
+        }
+        let state = shared_state(&[root.path()], vicaya_dir.path());
+        let kept_id = state
+            .read()
+            .unwrap()
+            .get_file_id_for_path(&path("kept.rs").to_string_lossy());
+        state.write().unwrap().root_scans.clear();
+
+        std::fs::remove_dir_all(path("gone")).unwrap();
+        std::fs::write(path("edited.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(path("new.rs"), "").unwrap();
+        let files_indexed = reconcile(&state);
+
+        for (file, present) in [
+            ("kept.rs", true),
+            ("edited.rs", true),
+            ("new.rs", true),
+            ("gone", false),
+            ("gone/a.rs", false),
+            ("gone/nested/b.rs", false),
+        ] {
+            assert_eq!(indexed(&state, &path(file)), present, "{file}");
+        }
+        let state = state.read().unwrap();
+        assert_eq!(files_indexed, state.indexed_file_count());
+        // Updated in place rather than swapped for a rebuilt index.
+        assert_eq!(
+            state.get_file_id_for_path(&path("kept.rs").to_string_lossy()),
+            kept_id
+        );
+        let edited = state
+            .get_file_id_for_path(&path("edited.rs").to_string_lossy())
+            .and_then(|id| state.snapshot.file_table.get(id))
+            .unwrap();
+        assert_eq!(edited.size, 13);
+        assert!(state.root_scans.contains_key(root.path()));
+        assert!(!state.reconciling);
+        assert!(state.activity.last_reconcile().is_some());
+
+        // Only the differences were journaled, with one delete for the
+        // removed directory.
+        let journal = std::fs::read_to_string(&state.journal_file).unwrap();
+        let journaled: Vec<IndexUpdate> = journal
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let path_string = |file: &str| path(file).to_string_lossy().to_string();
+        assert_eq!(
+            journaled,
+            [
+                IndexUpdate::Delete {
+                    path: path_string("gone")
+                },
+                IndexUpdate::Modify {
+                    path: path_string("edited.rs")
+                },
+                IndexUpdate::Create {
+                    path: path_string("new.rs")
+                },
+            ]
+        );
+        assert_eq!(state.activity.journal_entries(), 3);
+    }
+
+    #[test]
+    fn entries_of_a_dropped_root_are_deleted() {
+        let vicaya_dir = tempfile::tempdir().unwrap();
+        let kept = tempfile::tempdir().unwrap();
+        let dropped = tempfile::tempdir().unwrap();
+        std::fs::write(kept.path().join("kept.rs"), "").unwrap();
+        std::fs::write(dropped.path().join("dropped.rs"), "").unwrap();
+        let state = shared_state(&[kept.path(), dropped.path()], vicaya_dir.path());
+        state.write().unwrap().config.index_roots = vec![kept.path().to_path_buf()];
+
+        reconcile(&state);
+
+        assert!(indexed(&state, &kept.path().join("kept.rs")));
+        assert!(!indexed(&state, &dropped.path().join("dropped.rs")));
+        assert!(!indexed(&state, dropped.path()));
+    }
+
+    #[test]
+    fn heavy_churn_or_a_cap_rebuilds_the_whole_index() {
+        let vicaya_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        for idx in 0..8 {
+            std::fs::write(root.path().join(format!("{idx}.rs")), "").unwrap();
+        }
+        let state = shared_state(&[root.path()], vicaya_dir.path());
+        assert_eq!(full_rebuild_reason(&state.read().unwrap()), None);
+
+        state.write().unwrap().config.max_indexed_files = 100;
+        assert!(full_rebuild_reason(&state.read().unwrap()).is_some());
+        state.write().unwrap().config.max_indexed_files = 0;
+
+        state.read().unwrap().activity.journal_appended(1_000);
+        assert!(full_rebuild_reason(&state.read().unwrap()).is_some());
+        // The whole rebuild saves index.bin and trims the journal.
+        reconcile(&state);
+        let state = state.read().unwrap();
+        assert!(state.index_file.exists());
+        assert_eq!(state.activity.journal_entries(), 0);
+        assert_eq!(full_rebuild_reason(&state), None);
+    }
+
+    #[test]
+    fn deletes_of_paths_that_exist_again_are_dropped() {
+        let vicaya_dir = tempfile::tempdir().unwrap();
+        let root = tempfile::tempdir().unwrap();
+        let back = root.path().join("back.rs");
+        std::fs::write(&back, "").unwrap();
+        let state = shared_state(&[root.path()], vicaya_dir.path());
+        let activity = Arc::clone(&state.read().unwrap().activity);
+
+        let applied = apply_updates(
+            &state,
+            &Mutex::new(()),
+            &activity,
+            vec![
+                IndexUpdate::Delete {
+                    path: back.to_string_lossy().to_string(),
+                },
+                IndexUpdate::Delete {
+                    path: root.path().join("missing.rs").to_string_lossy().to_string(),
+                },
+            ],
+            true,
+        );
+
+        assert_eq!(applied, 1);
+        assert!(indexed(&state, &back));
+    }
+}
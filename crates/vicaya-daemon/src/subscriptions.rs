@@ -0,0 +1,50 @@
+//! Live result feeds behind `Request::Subscribe`.
+//!
+//! The watcher thread publishes the paths each applied batch touched to the
+//! [`ChangeFeed`]. Every subscribed connection receives its own copy and
+//! matches it against its search options on its own thread, so subscribers
+//! never slow down index updates, and batches are only collected while
+//! someone is listening.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+use crate::ipc_server::PreparedIndexUpdate;
+
+/// Paths touched by one applied batch, each flagged with whether it is
+/// still indexed.
+pub(crate) type ChangedPaths = Arc<Vec<(String, bool)>>;
+
+/// Fan-out of applied watcher batches to subscribed connections.
+#[derive(Debug, Default)]
+pub(crate) struct ChangeFeed {
+    subscribers: Mutex<Vec<Sender<ChangedPaths>>>,
+}
+
+impl ChangeFeed {
+    /// Receive every batch published from now on. Dropping the receiver
+    /// unsubscribes at the next publish.
+    pub(crate) fn subscribe(&self) -> Receiver<ChangedPaths> {
+        let (tx, rx) = channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Hand the paths `updates` touched to every subscriber.
+    pub(crate) fn publish(&self, updates: &[PreparedIndexUpdate]) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if subscribers.is_empty() {
+            return;
+        }
+        let changed: Vec<(String, bool)> = updates
+            .iter()
+            .flat_map(|update| update.touched_paths())
+            .map(|(path, present)| (path.to_string(), present))
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+        let changed = Arc::new(changed);
+        subscribers.retain(|tx| tx.send(Arc::clone(&changed)).is_ok());
+    }
+}
@@ -0,0 +1,232 @@
+//! Indexes the contents of directories that arrive whole under a root.
+//!
+//! Moving a directory into an index root is a single rename, so the watcher
+//! reports one `Create` (or a `Move` from an unindexed path) for the top
+//! directory and nothing for what is inside it. The watcher thread passes
+//! each raw batch to [`SubtreeScans::observe`], which queues such
+//! directories for a worker thread. The worker lists each subtree with
+//! [`Scanner::walk_subtree`] at the reconcile profile's threads and
+//! throttle, and [`SubtreeScans::take_ready`] hands the entries back as
+//! synthetic `Create`s, which are journaled and applied like any other
+//! update. A subtree over [`MAX_SUBTREE_ENTRIES`], or a full queue, is
+//! left partly unindexed and asks for a reconcile instead.
+
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+use tracing::{debug, warn};
+use vicaya_core::config::ScanProfile;
+use vicaya_core::Config;
+use vicaya_scanner::Scanner;
+use vicaya_watcher::IndexUpdate;
+
+use crate::ipc_server::DaemonActivity;
+
+/// Entries indexed from one subtree before the rest is left to a reconcile.
+const MAX_SUBTREE_ENTRIES: usize = 50_000;
+
+/// Directories waiting for the worker.
+const QUEUED_SUBTREES: usize = 256;
+
+/// Synthetic `Create`s handed to the watcher thread per poll.
+const CREATES_PER_POLL: usize = 4096;
+
+pub(crate) struct SubtreeScans {
+    config: Config,
+    roots: Vec<PathBuf>,
+    queue: SyncSender<PathBuf>,
+    found: Receiver<Vec<IndexUpdate>>,
+    activity: Arc<DaemonActivity>,
+}
+
+impl SubtreeScans {
+    /// Start the worker thread. It exits once this is dropped.
+    pub(crate) fn start(config: Config, activity: Arc<DaemonActivity>) -> Self {
+        let (queue, queued) = mpsc::sync_channel(QUEUED_SUBTREES);
+        let (found_tx, found) = mpsc::sync_channel(4);
+        let scanner = Scanner::new(config.clone()).with_profile(ScanProfile::Reconcile);
+        let worker_activity = Arc::clone(&activity);
+        std::thread::spawn(move || {
+            scan_subtrees(&scanner, &queued, &found_tx, &worker_activity);
+        });
+        Self {
+            roots: config.effective_roots(),
+            config,
+            queue,
+            found,
+            activity,
+        }
+    }
+
+    /// Queue the directories in `updates` whose contents the index has not
+    /// seen: created ones, and ones moved in from outside the index.
+    pub(crate) fn observe(&self, updates: &[IndexUpdate]) {
+        let mut dirs: Vec<&Path> = updates
+            .iter()
+            .filter_map(|update| match update {
+                IndexUpdate::Create { path } => Some(Path::new(path)),
+                IndexUpdate::Move { from, to } if !self.was_indexed(Path::new(from)) => {
+                    Some(Path::new(to))
+                }
+                _ => None,
+            })
+            .filter(|path| std::fs::symlink_metadata(path).is_ok_and(|meta| meta.is_dir()))
+            .collect();
+        dirs.sort();
+        dirs.dedup();
+        let mut last: Option<&Path> = None;
+        for dir in dirs {
+            // Sorted, so a directory follows any ancestor also queued here.
+            if last.is_some_and(|ancestor| dir.starts_with(ancestor)) {
+                continue;
+            }
+            last = Some(dir);
+            match self.queue.try_send(dir.to_path_buf()) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    warn!(
+                        "Too many new directories to index at once ({}); reconciling",
+                        dir.display()
+                    );
+                    self.activity.request_reconcile();
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    warn!("Subtree scanner stopped; reconciling");
+                    self.activity.request_reconcile();
+                }
+            }
+        }
+    }
+
+    /// The next synthetic `Create`s found by the worker, if any.
+    pub(crate) fn take_ready(&self) -> Option<Vec<IndexUpdate>> {
+        self.found.try_recv().ok()
+    }
+
+    /// Whether the index followed `path` before it moved, so its contents
+    /// moved along with it.
+    fn was_indexed(&self, path: &Path) -> bool {
+        self.roots.iter().any(|root| path.starts_with(root))
+            && vicaya_scanner::should_index_path(&self.config, path, true)
+    }
+}
+
+fn scan_subtrees(
+    scanner: &Scanner,
+    queued: &Receiver<PathBuf>,
+    found: &SyncSender<Vec<IndexUpdate>>,
+    activity: &DaemonActivity,
+) {
+    while let Ok(first) = queued.recv() {
+        // Take whatever else queued meanwhile, so a directory moved in
+        // together with its parent is walked once.
+        let mut dirs: Vec<PathBuf> = std::iter::once(first).chain(queued.try_iter()).collect();
+        dirs.sort();
+        dirs.dedup();
+        let mut last: Option<PathBuf> = None;
+        for dir in dirs {
+            if last
+                .as_ref()
+                .is_some_and(|ancestor| dir.starts_with(ancestor))
+            {
+                continue;
+            }
+            let walk = scanner.walk_subtree(&dir, MAX_SUBTREE_ENTRIES);
+            if !walk.complete {
+                warn!(
+                    "{} holds more than {} entries; indexing the first ones and reconciling",
+                    dir.display(),
+                    MAX_SUBTREE_ENTRIES
+                );
+                activity.request_reconcile();
+            }
+            debug!(
+                "Found {} entries under new directory {}",
+                walk.paths.len(),
+                dir.display()
+            );
+            let mut paths = walk.paths.into_iter();
+            loop {
+                let creates: Vec<IndexUpdate> = paths
+                    .by_ref()
+                    .take(CREATES_PER_POLL)
+                    .map(|path| IndexUpdate::Create {
+                        path: path.to_string_lossy().to_string(),
+                    })
+                    .collect();
+                if creates.is_empty() {
+                    break;
+                }
+                if found.send(creates).is_err() {
+                    return;
+                }
+            }
+            last = Some(dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn created(scans: &SubtreeScans, count: usize) -> Vec<String> {
+        let mut paths = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while paths.len() < count && Instant::now() < deadline {
+            match scans.take_ready() {
+                Some(creates) => paths.extend(creates.into_iter().map(|update| match update {
+                    IndexUpdate::Create { path } => path,
+                    other => panic!("unexpected update: {other:?}"),
+                })),
+                None => std::thread::sleep(Duration::from_millis(10)),
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn directories_arriving_from_outside_the_index_are_walked() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        for dir in ["moved/src", "copied"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        std::fs::write(root.path().join("moved/src/lib.rs"), "").unwrap();
+        std::fs::write(root.path().join("copied/notes.md"), "").unwrap();
+        let config = Config {
+            index_roots: vec![root.path().to_path_buf()],
+            ..Config::default()
+        };
+        let scans = SubtreeScans::start(config, Arc::new(DaemonActivity::default()));
+        let path = |relative: &str| root.path().join(relative).to_string_lossy().to_string();
+
+        scans.observe(&[
+            IndexUpdate::Move {
+                from: outside.path().join("moved").to_string_lossy().to_string(),
+                to: path("moved"),
+            },
+            // Already covered by its parent's walk.
+            IndexUpdate::Create {
+                path: path("moved/src"),
+            },
+            IndexUpdate::Create {
+                path: path("copied/notes.md"),
+            },
+            // Renamed within the root: its contents are indexed already.
+            IndexUpdate::Move {
+                from: path("old-copied"),
+                to: path("copied"),
+            },
+        ]);
+
+        assert_eq!(
+            created(&scans, 2),
+            [path("moved/src"), path("moved/src/lib.rs")]
+        );
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(scans.take_ready().is_none());
+    }
+}
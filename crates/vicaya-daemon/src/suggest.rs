@@ -0,0 +1,178 @@
+//! Basename prefix trie behind `Request::Suggest`.
+//!
+//! Instant-search UIs want a completion after every keystroke, so the daemon
+//! keeps the file names of the paths in Smriti usage memory in a trie keyed
+//! by their lowercase characters, with how often and how recently each name
+//! was used. It is only as large as Smriti (`[smriti] max_entries`) and
+//! follows it: uses are added as they are recorded, and the trie is rebuilt
+//! when Smriti forgets, prunes, or clears paths.
+
+use vicaya_core::smriti::SmritiStore;
+
+/// How much one file name has been used, over every path that carries it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct NameUse {
+    /// The name as most recently used.
+    pub(crate) name: String,
+    /// Accepted actions on paths with this name.
+    pub(crate) uses: u64,
+    /// Epoch seconds of the latest of them.
+    pub(crate) last_used: i64,
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    /// Child nodes by character, sorted.
+    children: Vec<(char, u32)>,
+    name: Option<NameUse>,
+}
+
+/// Used file names by lowercase prefix.
+#[derive(Debug)]
+pub(crate) struct NameTrie {
+    /// `nodes[0]` is the root.
+    nodes: Vec<Node>,
+}
+
+impl Default for NameTrie {
+    fn default() -> Self {
+        Self {
+            nodes: vec![Node::default()],
+        }
+    }
+}
+
+impl NameTrie {
+    /// The names of every path in `store`.
+    pub(crate) fn from_smriti(store: &SmritiStore) -> Self {
+        let mut trie = Self::default();
+        for entry in store.entries.values() {
+            trie.record(&entry.name, entry.total_count, entry.last_used);
+        }
+        trie
+    }
+
+    /// Approximate heap bytes held, for the state memory estimate.
+    pub(crate) fn allocated_bytes(&self) -> usize {
+        self.nodes.capacity() * std::mem::size_of::<Node>()
+            + self
+                .nodes
+                .iter()
+                .map(|node| {
+                    node.children.capacity() * std::mem::size_of::<(char, u32)>()
+                        + node.name.as_ref().map_or(0, |used| used.name.capacity())
+                })
+                .sum::<usize>()
+    }
+
+    /// Add `uses` uses of `name`, the latest at `last_used`.
+    pub(crate) fn record(&mut self, name: &str, uses: u64, last_used: i64) {
+        let mut node = 0;
+        for c in name.chars().flat_map(char::to_lowercase) {
+            node = match self.child(node, c) {
+                Ok(child) => child,
+                Err(slot) => {
+                    let child = self.nodes.len() as u32;
+                    self.nodes.push(Node::default());
+                    self.nodes[node].children.insert(slot, (c, child));
+                    child as usize
+                }
+            };
+        }
+        match &mut self.nodes[node].name {
+            Some(used) => {
+                used.uses = used.uses.saturating_add(uses);
+                if last_used >= used.last_used {
+                    used.last_used = last_used;
+                    used.name = name.to_string();
+                }
+            }
+            slot @ None => {
+                *slot = Some(NameUse {
+                    name: name.to_string(),
+                    uses,
+                    last_used,
+                });
+            }
+        }
+    }
+
+    /// Names starting with `prefix` (lowercase), most used first and ties
+    /// to the most recent.
+    pub(crate) fn complete(&self, prefix: &str) -> Vec<&NameUse> {
+        let mut node = 0;
+        for c in prefix.chars() {
+            match self.child(node, c) {
+                Ok(child) => node = child,
+                Err(_) => return Vec::new(),
+            }
+        }
+        let mut found = Vec::new();
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            found.extend(node.name.as_ref());
+            stack.extend(node.children.iter().map(|&(_, child)| child as usize));
+        }
+        found.sort_by(|a, b| {
+            b.uses
+                .cmp(&a.uses)
+                .then(b.last_used.cmp(&a.last_used))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        found
+    }
+
+    /// The child of `node` for `c`, or where to insert it.
+    fn child(&self, node: usize, c: char) -> Result<usize, usize> {
+        let children = &self.nodes[node].children;
+        children
+            .binary_search_by_key(&c, |&(key, _)| key)
+            .map(|i| children[i].1 as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vicaya_core::smriti::SmritiAction;
+
+    fn names(trie: &NameTrie, prefix: &str) -> Vec<String> {
+        trie.complete(prefix)
+            .into_iter()
+            .map(|used| used.name.clone())
+            .collect()
+    }
+
+    #[test]
+    fn completions_rank_by_uses_then_recency_across_paths_and_case() {
+        let mut store = SmritiStore::default();
+        for (path, uses, at) in [
+            ("/a/Cargo.toml", 2, 10),
+            ("/b/Cargo.toml", 2, 20),
+            ("/a/cargo.lock", 3, 5),
+            ("/a/Carousel.swift", 1, 30),
+            ("/a/main.rs", 9, 40),
+        ] {
+            for _ in 0..uses {
+                store.record(path.to_string(), String::new(), SmritiAction::Open, at);
+            }
+        }
+
+        let mut trie = NameTrie::from_smriti(&store);
+        assert_eq!(trie.complete("").len(), 4);
+        // Both Cargo.toml paths count towards one name.
+        assert_eq!(
+            names(&trie, "car"),
+            ["Cargo.toml", "cargo.lock", "Carousel.swift"]
+        );
+        assert_eq!(trie.complete("cargo.t")[0].uses, 4);
+        assert_eq!(names(&trie, "")[0], "main.rs");
+        assert!(trie.complete("cart").is_empty());
+
+        // A tie on uses goes to the most recent; its casing wins.
+        trie.record("CARGO.LOCK", 1, 50);
+        assert_eq!(names(&trie, "cargo"), ["CARGO.LOCK", "Cargo.toml"]);
+        assert_eq!(trie.complete("").len(), 4);
+    }
+}
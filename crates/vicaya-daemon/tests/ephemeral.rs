@@ -0,0 +1,166 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdout, Command, Stdio};
+use std::time::{Duration, Instant};
+
+use tempfile::tempdir;
+use vicaya_core::ipc::{DaemonStatus, Request, Response, SearchOptions};
+
+/// A daemon started with `--ephemeral`, killed if the test fails first.
+struct EphemeralDaemon {
+    child: Child,
+    socket: PathBuf,
+    state_dir: PathBuf,
+    // Held open so the daemon never writes to a closed pipe.
+    _stdout: BufReader<ChildStdout>,
+}
+
+impl EphemeralDaemon {
+    fn start(extra_args: &[&Path]) -> Self {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_vicaya-daemon"));
+        command.arg("--ephemeral");
+        for pair in extra_args.chunks(2) {
+            command.arg(pair[0]).arg(pair[1]);
+        }
+        let mut child = command
+            .env_remove("VICAYA_DIR")
+            .env_remove("VICAYA_PROFILE")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout
+            .read_line(&mut line)
+            .expect("Should read the ready line");
+        let ready: serde_json::Value =
+            serde_json::from_str(&line).unwrap_or_else(|e| panic!("bad ready line {line:?}: {e}"));
+        let path = |key: &str| PathBuf::from(ready[key].as_str().unwrap());
+
+        Self {
+            socket: path("socket"),
+            state_dir: path("state_dir"),
+            child,
+            _stdout: stdout,
+        }
+    }
+
+    fn request(&self, req: &Request) -> Response {
+        let mut stream = UnixStream::connect(&self.socket).expect("Should connect to socket");
+        let mut json = req.to_json().expect("Should serialize request");
+        json.push('\n');
+        stream
+            .write_all(json.as_bytes())
+            .expect("Should write request");
+
+        let mut reader = BufReader::new(stream);
+        let line = vicaya_core::ipc::read_message(&mut reader)
+            .expect("Should read response")
+            .expect("Should receive response");
+        Response::from_json(&line).expect("Should parse response")
+    }
+
+    fn finds(&self, term: &str) -> bool {
+        match self.request(&Request::Search(SearchOptions::new(term, 20))) {
+            Response::SearchResults { results, .. } => {
+                results.iter().any(|result| result.path.ends_with(term))
+            }
+            other => panic!("unexpected response: {other:?}"),
+        }
+    }
+
+    fn wait_until(&self, what: &str, mut done: impl FnMut(&Self) -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while !done(self) {
+            if Instant::now() >= deadline {
+                panic!("Timed out waiting for {what}");
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    fn shutdown(mut self) {
+        let _ = self.request(&Request::Shutdown);
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(25));
+        }
+        panic!("Daemon did not shut down within timeout");
+    }
+}
+
+impl Drop for EphemeralDaemon {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[test]
+fn ephemeral_daemon_serves_its_roots_and_removes_its_state_on_shutdown() {
+    let root = tempdir().unwrap();
+    std::fs::write(root.path().join("scanned.txt"), "").unwrap();
+
+    let daemon = EphemeralDaemon::start(&[Path::new("--root"), root.path()]);
+    let state_dir = daemon.state_dir.clone();
+    assert!(daemon.socket.starts_with(&state_dir));
+    assert!(state_dir.join("config.toml").exists());
+    assert!(daemon.finds("scanned.txt"));
+
+    // Live watcher events reach the index.
+    std::fs::write(root.path().join("watched.txt"), "").unwrap();
+    daemon.wait_until("the watcher to index watched.txt", |daemon| {
+        daemon.finds("watched.txt")
+    });
+
+    daemon.shutdown();
+    assert!(!state_dir.exists(), "ephemeral state should be removed");
+}
+
+#[test]
+fn kept_state_dir_survives_a_restart_and_reconciles_offline_changes() {
+    let root = tempdir().unwrap();
+    let state = tempdir().unwrap();
+    std::fs::write(root.path().join("before.txt"), "").unwrap();
+    let args = [
+        Path::new("--root"),
+        root.path(),
+        Path::new("--state-dir"),
+        state.path(),
+    ];
+
+    let daemon = EphemeralDaemon::start(&args);
+    assert!(daemon.finds("before.txt"));
+    daemon.shutdown();
+    assert!(state.path().join("index").join("index.bin").exists());
+
+    // Changed while stopped: only the startup reconcile can find it.
+    std::fs::write(root.path().join("offline.txt"), "").unwrap();
+    let daemon = EphemeralDaemon::start(&args);
+    daemon.wait_until("the startup reconcile to index offline.txt", |daemon| {
+        daemon.finds("offline.txt")
+    });
+
+    assert!(matches!(
+        daemon.request(&Request::Reconcile),
+        Response::ReconcileRequested { .. }
+    ));
+    daemon.wait_until("a reconcile to be recorded", |daemon| {
+        matches!(
+            daemon.request(&Request::Status),
+            Response::Status(DaemonStatus {
+                last_reconcile: Some(_),
+                ..
+            })
+        )
+    });
+    daemon.shutdown();
+    assert!(state.path().exists(), "a given state dir is kept");
+}
@@ -6,7 +6,7 @@ use std::time::{Duration, Instant};
 
 use tempfile::tempdir;
 use vicaya_core::config::PerformanceConfig;
-use vicaya_core::ipc::{Request, Response};
+use vicaya_core::ipc::{Request, Response, SearchFilters, SearchOptions};
 use vicaya_core::Config;
 
 struct DaemonChild(Child);
@@ -55,19 +55,56 @@ fn daemon_search_filter_scope_restricts_results_before_limit() {
     std::fs::create_dir_all(&repo_b).unwrap();
     std::fs::write(repo_a.join("query.rs"), "").unwrap();
     std::fs::write(repo_b.join("query.rs"), "").unwrap();
+    let cache = root.path().join("workspace/.cache");
+    std::fs::create_dir_all(&cache).unwrap();
+    std::fs::write(cache.join("query.rs"), "").unwrap();
 
     let config = Config {
         index_roots: vec![root.path().to_path_buf()],
         exclusions: vec![],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
@@ -90,17 +127,18 @@ fn daemon_search_filter_scope_restricts_results_before_limit() {
 
     let response = ipc_request(
         &socket,
-        &Request::Search {
-            query: "query.rs".to_string(),
-            limit: 10,
+        &Request::Search(SearchOptions {
             scope: Some(repo_a.parent().unwrap().to_string_lossy().to_string()),
-            filter_scope: Some(repo_a.parent().unwrap().to_string_lossy().to_string()),
-            recent_if_empty: false,
-        },
+            filters: SearchFilters {
+                scope: Some(repo_a.parent().unwrap().to_string_lossy().to_string()),
+                ..SearchFilters::default()
+            },
+            ..SearchOptions::new("query.rs", 10)
+        }),
     );
 
     match response {
-        Response::SearchResults { results } => {
+        Response::SearchResults { results, .. } => {
             assert_eq!(results.len(), 1, "expected a single scoped result");
             assert_eq!(
                 results[0].path,
@@ -110,6 +148,58 @@ fn daemon_search_filter_scope_restricts_results_before_limit() {
         other => panic!("unexpected response: {:?}", other),
     }
 
+    // `workspace/repo-*/src/query.rs` is three levels below `workspace` and
+    // four below the index root; `workspace/.cache/query.rs` one less.
+    let workspace = root.path().join("workspace").to_string_lossy().to_string();
+    for (scope, max_depth, expected) in [
+        (Some(workspace.clone()), 1, 0),
+        (Some(workspace.clone()), 2, 1),
+        (Some(workspace.clone()), 3, 3),
+        (None, 3, 1),
+        (None, 4, 3),
+    ] {
+        let response = ipc_request(
+            &socket,
+            &Request::Search(SearchOptions {
+                filters: SearchFilters {
+                    scope: scope.clone(),
+                    max_depth: Some(max_depth),
+                    ..SearchFilters::default()
+                },
+                ..SearchOptions::new("query.rs", 10)
+            }),
+        );
+        match response {
+            Response::SearchResults { results, .. } => assert_eq!(
+                results.len(),
+                expected,
+                "scope {scope:?} with max_depth {max_depth}"
+            ),
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
+    // Dotfiles follow `show_hidden` (on) unless the search says otherwise.
+    for (hidden, expected) in [(None, 3), (Some(false), 2), (Some(true), 3)] {
+        let response = ipc_request(
+            &socket,
+            &Request::Search(SearchOptions {
+                filters: SearchFilters {
+                    scope: Some(workspace.clone()),
+                    hidden,
+                    ..SearchFilters::default()
+                },
+                ..SearchOptions::new("query.rs", 10)
+            }),
+        );
+        match response {
+            Response::SearchResults { results, .. } => {
+                assert_eq!(results.len(), expected, "hidden {hidden:?}")
+            }
+            other => panic!("unexpected response: {:?}", other),
+        }
+    }
+
     let _ = ipc_request(&socket, &Request::Shutdown);
 
     let deadline = Instant::now() + Duration::from_secs(5);
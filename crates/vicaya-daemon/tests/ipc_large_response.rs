@@ -7,7 +7,7 @@ use std::time::{Duration, Instant};
 
 use tempfile::tempdir;
 use vicaya_core::config::PerformanceConfig;
-use vicaya_core::ipc::{Request, Response, MAX_IPC_MESSAGE_BYTES};
+use vicaya_core::ipc::{Request, Response, SearchOptions, MAX_IPC_MESSAGE_BYTES};
 use vicaya_core::Config;
 
 struct DaemonChild(Child);
@@ -62,14 +62,48 @@ fn it_handles_large_search_responses_without_truncation() {
         index_roots: vec![root.path().to_path_buf()],
         exclusions: vec![],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
@@ -92,17 +126,11 @@ fn it_handles_large_search_responses_without_truncation() {
 
     let response = ipc_request(
         &socket,
-        &Request::Search {
-            query: "LargeResponseTest".to_string(),
-            limit: 500,
-            scope: None,
-            filter_scope: None,
-            recent_if_empty: false,
-        },
+        &Request::Search(SearchOptions::new("LargeResponseTest", 500)),
     );
 
     match response {
-        Response::SearchResults { results } => {
+        Response::SearchResults { results, .. } => {
             assert!(
                 results.len() >= 300,
                 "expected many results, got {}",
@@ -136,14 +164,48 @@ fn it_rejects_oversized_requests_and_stays_responsive() {
         index_roots: vec![root.path().to_path_buf()],
         exclusions: vec![],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
@@ -190,17 +252,11 @@ fn it_rejects_oversized_requests_and_stays_responsive() {
 
     let healthy = ipc_request(
         &socket,
-        &Request::Search {
-            query: "healthy.txt".to_string(),
-            limit: 10,
-            scope: None,
-            filter_scope: None,
-            recent_if_empty: false,
-        },
+        &Request::Search(SearchOptions::new("healthy.txt", 10)),
     );
 
     match healthy {
-        Response::SearchResults { results } => {
+        Response::SearchResults { results, .. } => {
             assert!(
                 results.iter().any(|r| r.path.ends_with("healthy.txt")),
                 "expected daemon to remain responsive after malformed client"
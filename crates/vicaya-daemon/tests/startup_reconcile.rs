@@ -6,7 +6,7 @@ use std::time::{Duration, Instant};
 
 use tempfile::tempdir;
 use vicaya_core::config::PerformanceConfig;
-use vicaya_core::ipc::{Request, Response};
+use vicaya_core::ipc::{Request, Response, SearchOptions};
 use vicaya_core::Config;
 use vicaya_scanner::Scanner;
 use vicaya_watcher::IndexUpdate;
@@ -81,14 +81,48 @@ fn it_indexes_offline_changes_via_startup_reconcile() {
         index_roots: vec![root.path().to_path_buf()],
         exclusions: vec![],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
@@ -123,16 +157,10 @@ fn it_indexes_offline_changes_via_startup_reconcile() {
     loop {
         let response = ipc_request(
             &socket,
-            &Request::Search {
-                query: "after.txt".to_string(),
-                limit: 20,
-                scope: None,
-                filter_scope: None,
-                recent_if_empty: false,
-            },
+            &Request::Search(SearchOptions::new("after.txt", 20)),
         );
 
-        if let Response::SearchResults { results } = response {
+        if let Response::SearchResults { results, .. } = response {
             if results.iter().any(|r| r.path.ends_with("after.txt")) {
                 break;
             }
@@ -158,14 +186,48 @@ fn it_replays_journal_when_starting_from_existing_index() {
         index_roots: vec![root.path().to_path_buf()],
         exclusions: vec![],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
@@ -204,16 +266,10 @@ fn it_replays_journal_when_starting_from_existing_index() {
     loop {
         let response = ipc_request(
             &socket,
-            &Request::Search {
-                query: "after.txt".to_string(),
-                limit: 20,
-                scope: None,
-                filter_scope: None,
-                recent_if_empty: false,
-            },
+            &Request::Search(SearchOptions::new("after.txt", 20)),
         );
 
-        if let Response::SearchResults { results } = response {
+        if let Response::SearchResults { results, .. } = response {
             if results.iter().any(|r| r.path.ends_with("after.txt")) {
                 break;
             }
@@ -239,14 +295,48 @@ fn it_discards_stale_journal_when_starting_from_fresh_build() {
         index_roots: vec![root.path().to_path_buf()],
         exclusions: vec![],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: vicaya_dir.path().join("index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     };
 
     std::fs::create_dir_all(vicaya_dir.path()).unwrap();
@@ -279,17 +369,11 @@ fn it_discards_stale_journal_when_starting_from_fresh_build() {
 
     let response = ipc_request(
         &socket,
-        &Request::Search {
-            query: "live.txt".to_string(),
-            limit: 20,
-            scope: None,
-            filter_scope: None,
-            recent_if_empty: false,
-        },
+        &Request::Search(SearchOptions::new("live.txt", 20)),
     );
 
     match response {
-        Response::SearchResults { results } => {
+        Response::SearchResults { results, .. } => {
             assert!(results.iter().any(|r| r.path.ends_with("live.txt")));
         }
         other => panic!("unexpected response: {:?}", other),
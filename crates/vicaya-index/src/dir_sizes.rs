@@ -0,0 +1,178 @@
+//! Recursive size and file count of every indexed directory.
+//!
+//! Summing a directory's contents on demand means walking every entry below
+//! it. [`DirSizes`] keeps the sums instead, one slot per interned directory
+//! of the [`FileTable`], and is kept current by applying each entry's change
+//! as a delta to its parent and every ancestor: a file update costs one step
+//! per path component. Only live files count towards the size; a
+//! directory's own `st_size` does not. Live entries of either kind are
+//! counted too, so a deleted directory can tell whether anything below it
+//! is left to drop. Like the [`PathIndex`](crate::PathIndex) it is derived
+//! from the file table and never persisted.
+
+use crate::{DirId, FileMeta, FileTable, StringArena};
+
+/// Bytes and files below one directory, at any depth.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirTotals {
+    pub bytes: u64,
+    pub files: u64,
+    /// Live entries, directories included.
+    pub entries: u64,
+}
+
+/// Per-directory totals of the live files in a [`FileTable`].
+#[derive(Debug, Clone, Default)]
+pub struct DirSizes {
+    /// Indexed by `DirId`; directories interned since the last change to a
+    /// file below them have no slot yet and total zero.
+    totals: Vec<DirTotals>,
+}
+
+impl DirSizes {
+    /// Sum every live file in `file_table`.
+    pub fn build(file_table: &FileTable) -> Self {
+        let mut sizes = Self::default();
+        for (_, meta) in file_table.iter() {
+            sizes.add(file_table, meta);
+        }
+        sizes
+    }
+
+    /// Count `meta` in its parent and every ancestor. Tombstones are
+    /// ignored, and directories only add to `entries`.
+    pub fn add(&mut self, file_table: &FileTable, meta: &FileMeta) {
+        if meta.is_tombstone() {
+            return;
+        }
+        self.apply(file_table, meta.parent, |totals| {
+            totals.entries += 1;
+            if !meta.is_dir {
+                totals.bytes = totals.bytes.saturating_add(meta.size);
+                totals.files += 1;
+            }
+        });
+    }
+
+    /// Undo [`add`](Self::add) for `meta`, which must be unchanged since.
+    pub fn remove(&mut self, file_table: &FileTable, meta: &FileMeta) {
+        if meta.is_tombstone() {
+            return;
+        }
+        self.apply(file_table, meta.parent, |totals| {
+            totals.entries = totals.entries.saturating_sub(1);
+            if !meta.is_dir {
+                totals.bytes = totals.bytes.saturating_sub(meta.size);
+                totals.files = totals.files.saturating_sub(1);
+            }
+        });
+    }
+
+    /// Totals below the interned directory `dir`.
+    pub fn of_dir(&self, dir: DirId) -> DirTotals {
+        self.totals.get(dir.0 as usize).copied().unwrap_or_default()
+    }
+
+    /// Totals below the directory entry `meta`; zero for an empty directory.
+    pub fn of_entry(
+        &self,
+        file_table: &FileTable,
+        arena: &StringArena,
+        meta: &FileMeta,
+    ) -> DirTotals {
+        file_table
+            .dir_of_entry(meta, arena)
+            .map(|dir| self.of_dir(dir))
+            .unwrap_or_default()
+    }
+
+    /// Approximate heap bytes held, for the state memory estimate.
+    pub fn allocated_bytes(&self) -> usize {
+        self.totals.capacity() * std::mem::size_of::<DirTotals>()
+    }
+
+    fn apply(&mut self, file_table: &FileTable, parent: Option<DirId>, f: impl Fn(&mut DirTotals)) {
+        let mut current = parent;
+        while let Some(dir) = current {
+            let index = dir.0 as usize;
+            if index >= self.totals.len() {
+                self.totals.resize(index + 1, DirTotals::default());
+            }
+            f(&mut self.totals[index]);
+            current = file_table.dir_parent(dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(
+        table: &mut FileTable,
+        arena: &mut StringArena,
+        path: &str,
+        size: u64,
+        is_dir: bool,
+    ) -> crate::FileId {
+        let (parent, name_offset, name_len) = table.intern_path(path, arena);
+        table.insert(FileMeta {
+            parent,
+            name_offset,
+            name_len,
+            size,
+            mtime: 0,
+            dev: 0,
+            ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir,
+        })
+    }
+
+    #[test]
+    fn totals_roll_up_and_follow_updates() {
+        let mut table = FileTable::new();
+        let mut arena = StringArena::new();
+        let repo = insert(&mut table, &mut arena, "/repo", 4096, true);
+        let src = insert(&mut table, &mut arena, "/repo/src", 4096, true);
+        insert(&mut table, &mut arena, "/repo/src/main.rs", 100, false);
+        let lib = insert(&mut table, &mut arena, "/repo/src/lib.rs", 50, false);
+        insert(&mut table, &mut arena, "/repo/README.md", 10, false);
+        let empty = insert(&mut table, &mut arena, "/repo/empty", 4096, true);
+
+        let mut sizes = DirSizes::build(&table);
+        let of = |sizes: &DirSizes, id| {
+            let totals = sizes.of_entry(&table, &arena, table.get(id).unwrap());
+            (totals.bytes, totals.files)
+        };
+        assert_eq!(of(&sizes, repo), (160, 3));
+        assert_eq!(of(&sizes, src), (150, 2));
+        assert_eq!(of(&sizes, empty), (0, 0));
+        let repo_dir = table
+            .dir_of_entry(table.get(repo).unwrap(), &arena)
+            .unwrap();
+        assert_eq!(sizes.of_dir(repo_dir).entries, 5);
+
+        // An update is a remove of the old metadata and an add of the new.
+        let mut meta = table.get(lib).unwrap().clone();
+        sizes.remove(&table, &meta);
+        meta.size = 1_000;
+        sizes.add(&table, &meta);
+        assert_eq!(of(&sizes, repo), (1_110, 3));
+
+        sizes.remove(&table, &meta);
+        assert_eq!(of(&sizes, src), (100, 1));
+        assert_eq!(of(&sizes, repo), (110, 2));
+
+        // A file in a directory that had no files gives it a slot.
+        let new = insert(&mut table, &mut arena, "/repo/empty/new.bin", 7, false);
+        sizes.add(&table, table.get(new).unwrap());
+        let empty = sizes.of_entry(&table, &arena, table.get(empty).unwrap());
+        assert_eq!((empty.bytes, empty.files), (7, 1));
+        assert_eq!(sizes.of_dir(DirId(u32::MAX)), DirTotals::default());
+    }
+}
@@ -0,0 +1,299 @@
+//! Interned directory table shared by all file entries.
+//!
+//! Each directory is stored once as a (parent, component) pair whose name
+//! lives in the string arena, so a million files under the same deep prefix
+//! cost one copy of that prefix instead of a million. Full paths are rebuilt
+//! on demand by walking the parent chain.
+
+use crate::StringArena;
+use hashbrown::hash_map::DefaultHashBuilder;
+use hashbrown::HashTable;
+use serde::{Deserialize, Serialize};
+use std::hash::BuildHasher;
+
+/// Identifier of an interned directory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct DirId(pub u32);
+
+/// One path component and the directory that contains it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DirEntry {
+    parent: Option<DirId>,
+    name_offset: usize,
+    name_len: usize,
+}
+
+/// Table of interned directories.
+///
+/// A directory path is split on `/` and each component is interned under its
+/// parent, so joining the components of a chain with `/` reproduces the
+/// original string exactly (`"/a/b"` is `""` → `"a"` → `"b"`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DirTable {
+    entries: Vec<DirEntry>,
+    /// Interning lookup keyed by (parent, component); rebuilt lazily after
+    /// loading a snapshot since only updates need it.
+    #[serde(skip)]
+    lookup: HashTable<DirId>,
+    #[serde(skip)]
+    hasher: DefaultHashBuilder,
+    /// Last interned directory; consecutive files usually share a parent.
+    #[serde(skip)]
+    last: Option<(String, DirId)>,
+}
+
+impl DirTable {
+    /// Create a new empty directory table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Intern the directory `dir` (no trailing file name), returning its ID.
+    ///
+    /// # Panics
+    /// Panics if the table exceeds u32::MAX directories.
+    pub fn intern(&mut self, dir: &str, arena: &mut StringArena) -> DirId {
+        if let Some((last_dir, id)) = &self.last {
+            if last_dir == dir {
+                return *id;
+            }
+        }
+        self.ensure_lookup(arena);
+
+        let mut parent = None;
+        for component in dir.split('/') {
+            parent = Some(self.intern_component(parent, component, arena));
+        }
+        let id = parent.expect("split yields at least one component");
+        self.last = Some((dir.to_string(), id));
+        id
+    }
+
+    /// Append the full path of `id` to `out`.
+    pub fn write_path(&self, id: DirId, arena: &StringArena, out: &mut String) -> bool {
+        let mut chain = Vec::new();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let Some(entry) = self.entries.get(id.0 as usize) else {
+                return false;
+            };
+            chain.push(entry);
+            current = entry.parent;
+        }
+
+        for (i, entry) in chain.iter().rev().enumerate() {
+            let Some(name) = arena.get(entry.name_offset, entry.name_len) else {
+                return false;
+            };
+            if i > 0 {
+                out.push('/');
+            }
+            out.push_str(name);
+        }
+        true
+    }
+
+    /// Whether the full path of `id` equals `dir`, without allocating.
+    pub fn path_eq(&self, id: DirId, arena: &StringArena, dir: &str) -> bool {
+        let mut rest = dir;
+        let mut current = id;
+        loop {
+            let Some(entry) = self.entries.get(current.0 as usize) else {
+                return false;
+            };
+            let Some(name) = arena.get(entry.name_offset, entry.name_len) else {
+                return false;
+            };
+            let Some(parent) = entry.parent else {
+                return rest == name;
+            };
+            let Some(prefix) = rest
+                .strip_suffix(name)
+                .and_then(|prefix| prefix.strip_suffix('/'))
+            else {
+                return false;
+            };
+            rest = prefix;
+            current = parent;
+        }
+    }
+
+    /// The directory containing `id`, or `None` for a top-level component.
+    pub fn parent(&self, id: DirId) -> Option<DirId> {
+        self.entries.get(id.0 as usize)?.parent
+    }
+
+    /// The interned directory named `component` inside `parent`, if any.
+    /// Uses the interning lookup once [`DirTable::ensure_lookup`] built it,
+    /// and scans the table otherwise.
+    pub fn find(
+        &self,
+        parent: Option<DirId>,
+        component: &str,
+        arena: &StringArena,
+    ) -> Option<DirId> {
+        let is_match = |entry: &DirEntry| {
+            entry.parent == parent
+                && arena.get(entry.name_offset, entry.name_len) == Some(component)
+        };
+        if self.lookup.len() == self.entries.len() {
+            let hash = self.hasher.hash_one((parent, component));
+            return self
+                .lookup
+                .find(hash, |&id| is_match(&self.entries[id.0 as usize]))
+                .copied();
+        }
+        self.entries
+            .iter()
+            .position(is_match)
+            .map(|index| DirId(index as u32))
+    }
+
+    /// Number of interned directories.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the table is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Approximate heap bytes used by the table and its lookup.
+    pub fn allocated_bytes(&self) -> usize {
+        self.entries.capacity() * std::mem::size_of::<DirEntry>()
+            + self.lookup.capacity() * (std::mem::size_of::<DirId>() + 1)
+    }
+
+    fn intern_component(
+        &mut self,
+        parent: Option<DirId>,
+        component: &str,
+        arena: &mut StringArena,
+    ) -> DirId {
+        let hash = self.hasher.hash_one((parent, component));
+        let entries = &self.entries;
+        let found = self.lookup.find(hash, |&id| {
+            let entry = &entries[id.0 as usize];
+            entry.parent == parent
+                && arena.get(entry.name_offset, entry.name_len) == Some(component)
+        });
+        if let Some(&id) = found {
+            return id;
+        }
+
+        let index = self.entries.len();
+        assert!(
+            index < u32::MAX as usize,
+            "Directory table exceeded u32::MAX capacity"
+        );
+        let id = DirId(index as u32);
+        let (name_offset, name_len) = arena.add(component);
+        self.entries.push(DirEntry {
+            parent,
+            name_offset,
+            name_len,
+        });
+
+        let (entries, hasher) = (&self.entries, &self.hasher);
+        self.lookup
+            .insert_unique(hash, id, |&id| Self::entry_hash(hasher, entries, arena, id));
+        id
+    }
+
+    /// Build the interning lookup if loading a snapshot left it empty.
+    pub fn ensure_lookup(&mut self, arena: &StringArena) {
+        if self.lookup.len() == self.entries.len() {
+            return;
+        }
+        self.lookup = HashTable::with_capacity(self.entries.len());
+        let (entries, hasher) = (&self.entries, &self.hasher);
+        for index in 0..entries.len() {
+            let id = DirId(index as u32);
+            let hash = Self::entry_hash(hasher, entries, arena, id);
+            self.lookup
+                .insert_unique(hash, id, |&id| Self::entry_hash(hasher, entries, arena, id));
+        }
+    }
+
+    fn entry_hash(
+        hasher: &DefaultHashBuilder,
+        entries: &[DirEntry],
+        arena: &StringArena,
+        id: DirId,
+    ) -> u64 {
+        let entry = &entries[id.0 as usize];
+        let name = arena.get(entry.name_offset, entry.name_len).unwrap_or("");
+        hasher.hash_one((entry.parent, name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_of(table: &DirTable, id: DirId, arena: &StringArena) -> String {
+        let mut out = String::new();
+        assert!(table.write_path(id, arena, &mut out));
+        out
+    }
+
+    #[test]
+    fn interning_shares_common_prefixes_and_round_trips() {
+        let mut arena = StringArena::new();
+        let mut table = DirTable::new();
+
+        let src = table.intern("/home/user/project/src", &mut arena);
+        let tests = table.intern("/home/user/project/tests", &mut arena);
+        let again = table.intern("/home/user/project/src", &mut arena);
+        let relative = table.intern("docs/api", &mut arena);
+        let root = table.intern("", &mut arena);
+
+        assert_eq!(src, again);
+        // "", home, user, project, src, tests, docs, api
+        assert_eq!(table.len(), 8);
+        assert_eq!(path_of(&table, src, &arena), "/home/user/project/src");
+        assert_eq!(path_of(&table, tests, &arena), "/home/user/project/tests");
+        assert_eq!(path_of(&table, relative, &arena), "docs/api");
+        assert_eq!(path_of(&table, root, &arena), "");
+
+        assert!(table.path_eq(src, &arena, "/home/user/project/src"));
+        assert!(!table.path_eq(src, &arena, "/home/user/project/tests"));
+        assert!(!table.path_eq(src, &arena, "/x/home/user/project/src"));
+        assert!(!table.path_eq(src, &arena, "home/user/project/src"));
+    }
+
+    #[test]
+    fn lookup_is_rebuilt_after_deserialization() {
+        let mut arena = StringArena::new();
+        let mut table = DirTable::new();
+        let src = table.intern("/repo/src", &mut arena);
+
+        let bytes = bincode::serialize(&table).unwrap();
+        let mut loaded: DirTable = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(loaded.intern("/repo/src", &mut arena), src);
+        assert_eq!(loaded.len(), 3);
+        let docs = loaded.intern("/repo/docs", &mut arena);
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(path_of(&loaded, docs, &arena), "/repo/docs");
+    }
+
+    #[test]
+    fn find_walks_components_with_or_without_the_lookup() {
+        let mut arena = StringArena::new();
+        let mut table = DirTable::new();
+        let src = table.intern("/repo/src", &mut arena);
+        let repo = table.parent(src).unwrap();
+
+        let bytes = bincode::serialize(&table).unwrap();
+        let mut loaded: DirTable = bincode::deserialize(&bytes).unwrap();
+        for table in [&table, &loaded] {
+            assert_eq!(table.find(Some(repo), "src", &arena), Some(src));
+            assert_eq!(table.find(Some(repo), "docs", &arena), None);
+            assert_eq!(table.find(None, "", &arena), table.parent(repo));
+        }
+        loaded.ensure_lookup(&arena);
+        assert_eq!(loaded.find(Some(repo), "src", &arena), Some(src));
+    }
+}
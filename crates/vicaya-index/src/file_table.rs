@@ -1,6 +1,8 @@
 //! File table and metadata types.
 
+use crate::{DirId, DirTable, StringArena};
 use serde::{Deserialize, Serialize};
+use std::num::NonZeroU64;
 
 /// Unique identifier for a file entry.
 ///
@@ -12,13 +14,11 @@ pub struct FileId(pub u32);
 /// Metadata for a single file entry.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMeta {
-    /// Offset into the string arena for the full path.
-    pub path_offset: usize,
-    /// Length of the full path string.
-    pub path_len: usize,
-    /// Offset into the string arena for just the basename.
+    /// Interned directory containing the entry (`None` for a bare name).
+    pub parent: Option<DirId>,
+    /// Offset into the string arena for the basename.
     pub name_offset: usize,
-    /// Length of the basename string.
+    /// Length of the basename string; 0 marks a tombstone.
     pub name_len: usize,
     /// File size in bytes.
     pub size: u64,
@@ -28,12 +28,48 @@ pub struct FileMeta {
     pub dev: u64,
     /// Inode number.
     pub ino: u64,
+    /// Owning user ID.
+    pub uid: u32,
+    /// Owning group ID.
+    pub gid: u32,
+    /// Raw `st_mode`: file type and permission bits (0 when unknown).
+    pub mode: u32,
+    /// When the metadata above was last read from disk (Unix timestamp);
+    /// refreshed by watcher updates, reconciles that find it changed, and
+    /// full rebuilds.
+    pub indexed_at: i64,
+    /// Fingerprint of the file's head and size (`[content_hash]`); `None`
+    /// when hashing is off or the file was skipped.
+    pub content_hash: Option<NonZeroU64>,
+    /// Whether the entry is a directory.
+    pub is_dir: bool,
+}
+
+impl FileMeta {
+    /// Whether this entry was removed (IDs stay stable, so removed entries
+    /// are blanked rather than dropped).
+    pub fn is_tombstone(&self) -> bool {
+        self.parent.is_none() && self.name_len == 0
+    }
+
+    /// Blank this entry in place; see [`FileMeta::is_tombstone`].
+    pub fn tombstone(&mut self) {
+        self.parent = None;
+        self.name_len = 0;
+        self.size = 0;
+        self.mtime = 0;
+        self.content_hash = None;
+    }
 }
 
 /// File table: collection of all indexed files.
+///
+/// Entries store a parent directory ID plus basename; full paths are rebuilt
+/// from the shared [`DirTable`] on demand.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileTable {
     entries: Vec<FileMeta>,
+    dirs: DirTable,
 }
 
 impl FileTable {
@@ -41,9 +77,108 @@ impl FileTable {
     pub fn new() -> Self {
         Self {
             entries: Vec::new(),
+            dirs: DirTable::new(),
+        }
+    }
+
+    /// Split `path` at its last `/`, interning the directory part and adding
+    /// the basename to `arena`. Returns the parent and the basename's
+    /// `(offset, len)` for a [`FileMeta`].
+    pub fn intern_path(
+        &mut self,
+        path: &str,
+        arena: &mut StringArena,
+    ) -> (Option<DirId>, usize, usize) {
+        let (parent, name) = match path.rsplit_once('/') {
+            Some((dir, name)) => (Some(self.dirs.intern(dir, arena)), name),
+            None => (None, path),
+        };
+        let (name_offset, name_len) = arena.add(name);
+        (parent, name_offset, name_len)
+    }
+
+    /// Full path of `meta`, rebuilt from its parent chain and basename.
+    pub fn path_of(&self, meta: &FileMeta, arena: &StringArena) -> Option<String> {
+        let mut path = String::new();
+        self.write_path(meta, arena, &mut path).then_some(path)
+    }
+
+    /// Full path of the entry `id`; see [`FileTable::path_of`].
+    pub fn path(&self, id: FileId, arena: &StringArena) -> Option<String> {
+        self.path_of(self.get(id)?, arena)
+    }
+
+    /// Append the full path of `meta` to `out`, reusing its allocation.
+    pub fn write_path(&self, meta: &FileMeta, arena: &StringArena, out: &mut String) -> bool {
+        let Some(name) = arena.get(meta.name_offset, meta.name_len) else {
+            return false;
+        };
+        if let Some(parent) = meta.parent {
+            if !self.dirs.write_path(parent, arena, out) {
+                return false;
+            }
+            out.push('/');
+        }
+        out.push_str(name);
+        true
+    }
+
+    /// Append the full path of the interned directory `id` to `out`.
+    pub fn write_dir_path(&self, id: DirId, arena: &StringArena, out: &mut String) -> bool {
+        self.dirs.write_path(id, arena, out)
+    }
+
+    /// Whether `meta`'s full path equals `path`, without allocating.
+    pub fn path_eq(&self, meta: &FileMeta, arena: &StringArena, path: &str) -> bool {
+        let Some(name) = arena.get(meta.name_offset, meta.name_len) else {
+            return false;
+        };
+        match meta.parent {
+            Some(parent) => path
+                .strip_suffix(name)
+                .and_then(|dir| dir.strip_suffix('/'))
+                .is_some_and(|dir| self.dirs.path_eq(parent, arena, dir)),
+            None => path == name,
         }
     }
 
+    /// Number of interned directories backing the entries' paths.
+    pub fn dir_count(&self) -> usize {
+        self.dirs.len()
+    }
+
+    /// The interned directory containing the interned directory `id`.
+    pub fn dir_parent(&self, id: DirId) -> Option<DirId> {
+        self.dirs.parent(id)
+    }
+
+    /// The interned directory a directory entry's own path names; `None`
+    /// when nothing was ever interned below it.
+    pub fn dir_of_entry(&self, meta: &FileMeta, arena: &StringArena) -> Option<DirId> {
+        let name = arena.get(meta.name_offset, meta.name_len)?;
+        self.dirs.find(meta.parent, name, arena)
+    }
+
+    /// The interned directory whose path is `dir`, if any.
+    pub fn find_dir(&self, dir: &str, arena: &StringArena) -> Option<DirId> {
+        let dir = if dir.len() > 1 {
+            dir.trim_end_matches('/')
+        } else {
+            dir
+        };
+        let dir = if dir == "/" { "" } else { dir };
+        dir.split('/')
+            .try_fold(None, |parent, component| {
+                self.dirs.find(parent, component, arena).map(Some)
+            })
+            .flatten()
+    }
+
+    /// See [`DirTable::ensure_lookup`].
+    pub fn ensure_dir_lookup(&mut self, arena: &StringArena) {
+        self.dirs.ensure_lookup(arena);
+    }
+
     /// Insert a new file entry.
     ///
     /// # Panics
@@ -85,7 +220,7 @@ impl FileTable {
 
     /// Approximate heap bytes used by the table storage.
     pub fn allocated_bytes(&self) -> usize {
-        self.entries.capacity() * std::mem::size_of::<FileMeta>()
+        self.entries.capacity() * std::mem::size_of::<FileMeta>() + self.dirs.allocated_bytes()
     }
 
     /// Check if the table is empty.
@@ -104,16 +239,21 @@ impl Default for FileTable {
 mod tests {
     use super::*;
 
-    fn create_test_meta(path_offset: usize, name_offset: usize) -> FileMeta {
+    fn create_test_meta(parent: u32, name_offset: usize) -> FileMeta {
         FileMeta {
-            path_offset,
-            path_len: 10,
+            parent: Some(DirId(parent)),
             name_offset,
             name_len: 5,
             size: 1024,
             mtime: 1234567890,
             dev: 1,
             ino: 100,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         }
     }
 
@@ -135,7 +275,7 @@ mod tests {
         assert!(!table.is_empty());
 
         let retrieved = table.get(id).unwrap();
-        assert_eq!(retrieved.path_offset, 0);
+        assert_eq!(retrieved.parent, Some(DirId(0)));
         assert_eq!(retrieved.name_offset, 10);
         assert_eq!(retrieved.size, 1024);
     }
@@ -153,9 +293,9 @@ mod tests {
         assert_eq!(id3, FileId(2));
         assert_eq!(table.len(), 3);
 
-        assert_eq!(table.get(id1).unwrap().path_offset, 0);
-        assert_eq!(table.get(id2).unwrap().path_offset, 20);
-        assert_eq!(table.get(id3).unwrap().path_offset, 40);
+        assert_eq!(table.get(id1).unwrap().parent, Some(DirId(0)));
+        assert_eq!(table.get(id2).unwrap().parent, Some(DirId(20)));
+        assert_eq!(table.get(id3).unwrap().parent, Some(DirId(40)));
     }
 
     #[test]
@@ -192,9 +332,9 @@ mod tests {
         assert_eq!(entries[1].0, FileId(1));
         assert_eq!(entries[2].0, FileId(2));
 
-        assert_eq!(entries[0].1.path_offset, 0);
-        assert_eq!(entries[1].1.path_offset, 20);
-        assert_eq!(entries[2].1.path_offset, 40);
+        assert_eq!(entries[0].1.parent, Some(DirId(0)));
+        assert_eq!(entries[1].1.parent, Some(DirId(20)));
+        assert_eq!(entries[2].1.parent, Some(DirId(40)));
     }
 
     #[test]
@@ -212,4 +352,53 @@ mod tests {
         assert_eq!(id1, id2);
         assert_ne!(id1, id3);
     }
+
+    #[test]
+    fn paths_are_rebuilt_from_shared_parent_directories() {
+        let mut table = FileTable::new();
+        let mut arena = StringArena::new();
+
+        let mut insert = |path: &str| {
+            let (parent, name_offset, name_len) = table.intern_path(path, &mut arena);
+            let mut meta = create_test_meta(0, name_offset);
+            meta.parent = parent;
+            meta.name_len = name_len;
+            table.insert(meta)
+        };
+        let main = insert("/home/user/project/src/main.rs");
+        let lib = insert("/home/user/project/src/lib.rs");
+        let top = insert("/etc");
+        let bare = insert("notes.txt");
+
+        for (id, expected) in [
+            (main, "/home/user/project/src/main.rs"),
+            (lib, "/home/user/project/src/lib.rs"),
+            (top, "/etc"),
+            (bare, "notes.txt"),
+        ] {
+            assert_eq!(table.path(id, &arena).as_deref(), Some(expected));
+            assert!(table.path_eq(table.get(id).unwrap(), &arena, expected));
+        }
+        assert!(!table.path_eq(
+            table.get(main).unwrap(),
+            &arena,
+            "/home/user/project/src/lib.rs"
+        ));
+        assert_eq!(
+            table.get(main).unwrap().parent,
+            table.get(lib).unwrap().parent
+        );
+        // "", home, user, project, src
+        assert_eq!(table.dir_count(), 5);
+        let src = table.get(main).unwrap().parent;
+        assert_eq!(table.find_dir("/home/user/project/src", &arena), src);
+        assert_eq!(table.find_dir("/home/user/project/src/", &arena), src);
+        assert_eq!(table.find_dir("/", &arena), Some(DirId(0)));
+        assert_eq!(table.find_dir("/home/user/other", &arena), None);
+
+        let meta = table.get_mut(lib).unwrap();
+        assert!(!meta.is_tombstone());
+        meta.tombstone();
+        assert!(meta.is_tombstone());
+    }
 }
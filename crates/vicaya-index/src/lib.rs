@@ -1,13 +1,25 @@
 //! vicaya-index: File table, string arena, trigram index, and query engine.
 
 pub mod abbreviation;
+pub mod dir_sizes;
+pub mod dir_table;
 pub mod file_table;
+pub mod normalize;
+pub mod path_index;
 pub mod query;
+pub mod scorer;
 pub mod string_arena;
 pub mod trigram;
 
 pub use abbreviation::{AbbreviationMatch, AbbreviationMatcher, MatchStrategy};
+pub use dir_sizes::{DirSizes, DirTotals};
+pub use dir_table::{DirId, DirTable};
 pub use file_table::{FileId, FileMeta, FileTable};
-pub use query::{Query, QueryEngine, SearchResult};
+pub use normalize::Normalization;
+pub use path_index::PathIndex;
+pub use query::{Query, QueryBudget, QueryEngine, SearchOutcome, SearchResult};
+pub use scorer::{
+    Boost, Candidate, Ranked, RecencyScorer, RelevanceScorer, Scorer, ScorerRegistry,
+};
 pub use string_arena::StringArena;
 pub use trigram::{Trigram, TrigramIndex};
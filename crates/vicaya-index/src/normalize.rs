@@ -0,0 +1,137 @@
+//! Name normalization shared by indexing and querying.
+//!
+//! The trigram index stores the [`Normalization`] it was built with, and the
+//! query engine applies the same one to search terms and candidate names, so
+//! `resume` finds `résumé.pdf` and `foo bar` finds `foo_bar.rs` only when the
+//! index was built to allow it.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+/// Characters treated as interchangeable word separators.
+const SEPARATORS: &[char] = &['-', '_', '.', ' '];
+
+/// What a name is folded to before trigram extraction and scoring.
+///
+/// The default folds nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Normalization {
+    /// Strip diacritics: `é` → `e`, `ø` → `o`, `ß` → `ss`.
+    pub fold_diacritics: bool,
+    /// Treat `-`, `_`, `.` and space as the same character.
+    pub fold_separators: bool,
+}
+
+impl From<&vicaya_core::config::NormalizationConfig> for Normalization {
+    fn from(config: &vicaya_core::config::NormalizationConfig) -> Self {
+        Self {
+            fold_diacritics: config.fold_diacritics,
+            fold_separators: config.fold_separators,
+        }
+    }
+}
+
+impl Normalization {
+    /// Whether any folding is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.fold_diacritics || self.fold_separators
+    }
+
+    /// Fold `text`. Non-ASCII letters come back lowercased when diacritics
+    /// are folded; ASCII case is left alone.
+    pub fn apply<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let needs_folding = (self.fold_diacritics && !text.is_ascii())
+            || (self.fold_separators && text.contains(|c| c != ' ' && SEPARATORS.contains(&c)));
+        if !needs_folding {
+            return Cow::Borrowed(text);
+        }
+
+        let mut folded = String::with_capacity(text.len());
+        for c in text.chars() {
+            if self.fold_separators && SEPARATORS.contains(&c) {
+                folded.push(' ');
+            } else if self.fold_diacritics && !c.is_ascii() {
+                if is_combining_mark(c) {
+                    continue;
+                }
+                for lower in c.to_lowercase() {
+                    match fold_letter(lower) {
+                        Some(base) => folded.push_str(base),
+                        None => folded.push(lower),
+                    }
+                }
+            } else {
+                folded.push(c);
+            }
+        }
+        Cow::Owned(folded)
+    }
+}
+
+/// Combining diacritical marks, as left behind by decomposed (NFD) names.
+fn is_combining_mark(c: char) -> bool {
+    matches!(c, '\u{0300}'..='\u{036F}')
+}
+
+/// The unaccented spelling of a lowercase Latin letter.
+fn fold_letter(c: char) -> Option<&'static str> {
+    Some(match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => "e",
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => "g",
+        'ĥ' | 'ħ' => "h",
+        'ì' | 'í' | 'î' | 'ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => "i",
+        'ĳ' => "ij",
+        'ĵ' => "j",
+        'ķ' => "k",
+        'ĺ' | 'ļ' | 'ľ' | 'ŀ' | 'ł' => "l",
+        'ñ' | 'ń' | 'ņ' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => "o",
+        'œ' => "oe",
+        'ŕ' | 'ŗ' | 'ř' => "r",
+        'ś' | 'ŝ' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'ţ' | 'ť' | 'ŧ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => "u",
+        'ŵ' => "w",
+        'ý' | 'ÿ' | 'ŷ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: Normalization = Normalization {
+        fold_diacritics: true,
+        fold_separators: true,
+    };
+
+    #[test]
+    fn folds_precomposed_and_decomposed_accents_and_separators() {
+        assert_eq!(ALL.apply("Résumé.pdf"), "Resume pdf");
+        assert_eq!(ALL.apply("re\u{301}sume\u{301}"), "resume");
+        assert_eq!(ALL.apply("Straße_Ærø-notes"), "Strasse aero notes");
+        assert_eq!(ALL.apply("日本語.txt"), "日本語 txt");
+        assert!(matches!(ALL.apply("plain words"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn disabled_options_leave_names_alone() {
+        assert!(matches!(
+            Normalization::default().apply("résumé_v2.pdf"),
+            Cow::Borrowed("résumé_v2.pdf")
+        ));
+        let separators_only = Normalization {
+            fold_diacritics: false,
+            fold_separators: true,
+        };
+        assert_eq!(separators_only.apply("résumé_v2.pdf"), "résumé v2 pdf");
+    }
+}
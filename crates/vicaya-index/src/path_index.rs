@@ -0,0 +1,234 @@
+//! Optional trigram index over directory paths.
+//!
+//! The main [`TrigramIndex`] only covers basenames, so a term that spans
+//! directories (`projects/spartan`) or names a directory cannot produce
+//! candidates from it. A [`PathIndex`] indexes the full path of every
+//! directory that directly holds an entry, plus which entries each one holds,
+//! so those files are reached through the directories that match.
+//!
+//! Only directories are indexed, not every file's path: the cost grows with
+//! the number of directories (roughly one in ten entries) rather than with
+//! the number of files. It is derived from the file table, never persisted.
+
+use crate::{DirId, FileId, FileTable, Normalization, StringArena, Trigram, TrigramIndex};
+use hashbrown::HashMap;
+
+/// Directory-path trigrams and directory membership.
+#[derive(Debug, Clone, Default)]
+pub struct PathIndex {
+    /// Trigrams of each directory's full path. Postings hold `DirId` numbers
+    /// wrapped as `FileId`, reusing the sorted posting lists and intersection.
+    dirs: TrigramIndex,
+    /// Live entries directly inside each indexed directory.
+    members: HashMap<DirId, Vec<FileId>>,
+}
+
+impl PathIndex {
+    /// Index the directories of every live entry in `file_table`.
+    pub fn build(
+        file_table: &FileTable,
+        arena: &StringArena,
+        normalization: Normalization,
+    ) -> Self {
+        let mut index = Self {
+            dirs: TrigramIndex::with_normalization(normalization),
+            members: HashMap::new(),
+        };
+        for (file_id, meta) in file_table.iter() {
+            if !meta.is_tombstone() {
+                index.add(file_id, meta.parent, file_table, arena);
+            }
+        }
+        index
+    }
+
+    /// The folding directory paths (and the fragments queried) go through.
+    pub fn normalization(&self) -> Normalization {
+        self.dirs.normalization()
+    }
+
+    /// Record `file_id` as an entry of `parent`, indexing the directory's path
+    /// when it gains its first entry.
+    pub fn add(
+        &mut self,
+        file_id: FileId,
+        parent: Option<DirId>,
+        file_table: &FileTable,
+        arena: &StringArena,
+    ) {
+        let Some(parent) = parent else {
+            return;
+        };
+        let members = self.members.entry(parent).or_default();
+        if members.contains(&file_id) {
+            return;
+        }
+        members.push(file_id);
+        if members.len() == 1 {
+            let mut path = String::new();
+            if file_table.write_dir_path(parent, arena, &mut path) {
+                self.dirs.add(FileId(parent.0), &path);
+            }
+        }
+    }
+
+    /// Forget `file_id` as an entry of `parent`, dropping the directory's path
+    /// once it has no entries left.
+    pub fn remove(
+        &mut self,
+        file_id: FileId,
+        parent: Option<DirId>,
+        file_table: &FileTable,
+        arena: &StringArena,
+    ) {
+        let Some(parent) = parent else {
+            return;
+        };
+        let Some(members) = self.members.get_mut(&parent) else {
+            return;
+        };
+        members.retain(|&id| id != file_id);
+        if members.is_empty() {
+            self.members.remove(&parent);
+            let mut path = String::new();
+            if file_table.write_dir_path(parent, arena, &mut path) {
+                self.dirs.remove_text(FileId(parent.0), &path);
+            }
+        }
+    }
+
+    /// Entries directly inside directories whose path contains `fragment`
+    /// (lowercased and normalized like a query term), stopping after
+    /// `max_results` accepted entries.
+    pub fn candidates<F>(
+        &self,
+        fragment: &str,
+        max_results: usize,
+        file_table: &FileTable,
+        arena: &StringArena,
+        mut accept: F,
+    ) -> Vec<FileId>
+    where
+        F: FnMut(FileId) -> bool,
+    {
+        let trigrams = Trigram::extract(fragment);
+        let mut candidates = Vec::new();
+        if trigrams.is_empty() || max_results == 0 {
+            return candidates;
+        }
+
+        let normalization = self.normalization();
+        let mut path = String::new();
+        for dir in self.dirs.query(&trigrams) {
+            let dir = DirId(dir.0);
+            path.clear();
+            if !file_table.write_dir_path(dir, arena, &mut path) {
+                continue;
+            }
+            if !normalization.apply(&path.to_lowercase()).contains(fragment) {
+                continue;
+            }
+            for &file_id in self.members.get(&dir).into_iter().flatten() {
+                if accept(file_id) {
+                    candidates.push(file_id);
+                    if candidates.len() >= max_results {
+                        return candidates;
+                    }
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Number of directories indexed.
+    pub fn dir_count(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Approximate heap bytes used by the path index.
+    pub fn allocated_bytes(&self) -> usize {
+        let members_bytes: usize = self
+            .members
+            .values()
+            .map(|members| members.capacity() * std::mem::size_of::<FileId>())
+            .sum();
+        self.dirs.allocated_bytes()
+            + self.members.capacity() * (std::mem::size_of::<(DirId, Vec<FileId>)>() + 1)
+            + members_bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileMeta;
+
+    fn table(paths: &[&str]) -> (FileTable, StringArena) {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        for path in paths {
+            let (parent, name_offset, name_len) = file_table.intern_path(path, &mut arena);
+            file_table.insert(FileMeta {
+                parent,
+                name_offset,
+                name_len,
+                size: 0,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+        }
+        (file_table, arena)
+    }
+
+    #[test]
+    fn candidates_come_from_directories_whose_path_matches() {
+        let (file_table, arena) = table(&[
+            "/home/me/Projects/spartan/src/main.rs",
+            "/home/me/Projects/spartan/README.md",
+            "/home/me/Projects/athens/spartan.txt",
+            "/home/me/notes/projects.md",
+        ]);
+        let index = PathIndex::build(&file_table, &arena, Normalization::default());
+        assert_eq!(index.dir_count(), 4);
+
+        let found = |fragment: &str| {
+            let mut ids = index.candidates(fragment, usize::MAX, &file_table, &arena, |_| true);
+            ids.sort_unstable();
+            ids
+        };
+        assert_eq!(found("projects/spartan"), vec![FileId(0), FileId(1)]);
+        assert_eq!(found("projects"), vec![FileId(0), FileId(1), FileId(2)]);
+        assert!(found("notes/projects").is_empty());
+        assert_eq!(
+            index.candidates("projects", 1, &file_table, &arena, |id| id != FileId(0)),
+            vec![FileId(1)]
+        );
+    }
+
+    #[test]
+    fn directories_leave_the_index_with_their_last_entry() {
+        let (file_table, arena) = table(&["/srv/spartan/a.txt", "/srv/spartan/b.txt"]);
+        let mut index = PathIndex::build(&file_table, &arena, Normalization::default());
+        let parent = file_table.get(FileId(0)).unwrap().parent;
+
+        index.remove(FileId(0), parent, &file_table, &arena);
+        let remaining = index.candidates("spartan", usize::MAX, &file_table, &arena, |_| true);
+        assert_eq!(remaining, vec![FileId(1)]);
+
+        index.remove(FileId(1), parent, &file_table, &arena);
+        assert_eq!(index.dir_count(), 0);
+        assert_eq!(index.dirs.trigram_count(), 0);
+
+        index.add(FileId(1), parent, &file_table, &arena);
+        index.add(FileId(1), parent, &file_table, &arena);
+        let restored = index.candidates("spartan", usize::MAX, &file_table, &arena, |_| true);
+        assert_eq!(restored, vec![FileId(1)]);
+    }
+}
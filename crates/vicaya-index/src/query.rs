@@ -1,14 +1,35 @@
 //! Query engine for searching the index.
 
-use crate::{AbbreviationMatcher, FileId, FileTable, StringArena, Trigram, TrigramIndex};
+use crate::scorer::{Candidate, Ranked, RelevanceScorer, Scorer};
+use crate::{
+    AbbreviationMatcher, FileId, FileTable, Normalization, PathIndex, StringArena, Trigram,
+    TrigramIndex,
+};
+use hashbrown::{HashMap, HashSet};
 use serde::{Deserialize, Serialize};
 use std::cmp::{Ordering, Reverse};
 use std::collections::BinaryHeap;
+use std::num::NonZeroU64;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use vicaya_core::ipc::{MatchStrategy, ScoreExplanation, SearchSort};
+use vicaya_core::permissions::AttributeMatcher;
 
 const SHORT_QUERY_MAX_SCAN: usize = 50_000;
 const SHORT_QUERY_MIN_SCAN_AFTER_LIMIT: usize = 10_000;
 const INDEXED_QUERY_CANDIDATE_LIMIT: usize = 10_000;
+/// Scored candidates between wall-clock checks, so `Instant::now` stays off the hot path.
+const BUDGET_CLOCK_CHECK_INTERVAL: usize = 256;
+/// Postings read to find names near a term that matched nothing.
+const SUGGESTION_POSTING_BUDGET: usize = 200_000;
+/// Names with the most shared trigrams compared by edit distance.
+const SUGGESTION_CANDIDATES: usize = 1_000;
+/// Ranking for queries that name no scorer.
+static DEFAULT_SCORER: RelevanceScorer = RelevanceScorer::new();
+/// Best matches a non-relevance [`SearchSort`] orders, unless the requested
+/// page reaches further.
+pub const SORTED_SEARCH_WINDOW: usize = 1_000;
 
 /// A search query.
 #[derive(Debug, Clone)]
@@ -21,6 +42,98 @@ pub struct Query {
     pub scope: Option<std::path::PathBuf>,
     /// Optional scope root used to strictly filter results to a subtree.
     pub filter_scope: Option<std::path::PathBuf>,
+    /// Work limits; when exhausted the best results so far are returned.
+    pub budget: QueryBudget,
+    /// Ownership and permission constraints (`owner:`, `readonly:`, ...).
+    pub attributes: AttributeMatcher,
+    /// Also rank abbreviation matches (`mr` → `main.rs`), not just substrings.
+    pub fuzzy: bool,
+    /// Attach a [`ScoreExplanation`] to each result.
+    pub explain: bool,
+    /// Ranking policy; `None` uses [`RelevanceScorer`].
+    pub scorer: Option<Arc<dyn Scorer>>,
+    /// Added to the context score of directory results (negative demotes).
+    pub directory_bias: i32,
+    /// Candidates whose match score (0.0 to 1.0, before the scorer) is below
+    /// this are dropped; 0.0 keeps every match.
+    pub min_score: f32,
+    /// Deepest result kept, in levels below `filter_scope` (its entries are
+    /// depth 1), or below the engine's roots without a filter scope; see
+    /// [`QueryEngine::with_roots`].
+    pub max_depth: Option<usize>,
+    /// Drop dotfiles and everything inside dot-directories, looking below
+    /// `filter_scope` or the engine's roots like `max_depth`.
+    pub hide_hidden: bool,
+}
+
+/// Limits on how much work a single query may do before returning early.
+///
+/// The default is unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueryBudget {
+    /// Stop scoring candidates once this much wall-clock time has passed.
+    pub max_duration: Option<Duration>,
+    /// Stop after scoring this many candidates.
+    pub max_candidates: Option<usize>,
+}
+
+impl QueryBudget {
+    fn start(self) -> BudgetTracker {
+        BudgetTracker {
+            deadline: self.max_duration.map(|duration| Instant::now() + duration),
+            max_candidates: self.max_candidates,
+            charged: 0,
+            exhausted: false,
+        }
+    }
+}
+
+/// Results plus whether the query stopped early because its budget ran out.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOutcome {
+    pub results: Vec<SearchResult>,
+    pub truncated: bool,
+    /// Near-miss names for an empty result set. The engine's searches leave
+    /// this empty; callers that want hints fill it from
+    /// [`QueryEngine::suggest`].
+    pub suggestions: Vec<String>,
+    /// Matches outside the filter scope, for a scoped search that found
+    /// nothing inside it. The engine's searches leave this empty too; the
+    /// daemon fills it by searching again without the scope.
+    pub outside_scope: Vec<SearchResult>,
+}
+
+struct BudgetTracker {
+    deadline: Option<Instant>,
+    max_candidates: Option<usize>,
+    charged: usize,
+    exhausted: bool,
+}
+
+impl BudgetTracker {
+    /// Account for one more candidate; returns `false` once the budget is spent.
+    fn charge(&mut self) -> bool {
+        if self.exhausted {
+            return false;
+        }
+        if self
+            .max_candidates
+            .is_some_and(|max_candidates| self.charged >= max_candidates)
+        {
+            self.exhausted = true;
+            return false;
+        }
+        if let Some(deadline) = self.deadline {
+            if self.charged.is_multiple_of(BUDGET_CLOCK_CHECK_INTERVAL)
+                && Instant::now() >= deadline
+            {
+                self.exhausted = true;
+                return false;
+            }
+        }
+        self.charged += 1;
+        true
+    }
 }
 
 /// A search result.
@@ -36,6 +149,90 @@ pub struct SearchResult {
     pub size: u64,
     /// Modification time.
     pub mtime: i64,
+    /// Owning user ID.
+    #[serde(default)]
+    pub uid: u32,
+    /// Owning group ID.
+    #[serde(default)]
+    pub gid: u32,
+    /// Raw `st_mode` (0 when unknown).
+    #[serde(default)]
+    pub mode: u32,
+    /// When the entry's metadata was last read from disk (Unix timestamp).
+    #[serde(default)]
+    pub indexed_at: i64,
+    /// Content fingerprint, when `[content_hash]` recorded one.
+    #[serde(default)]
+    pub content_hash: Option<NonZeroU64>,
+    /// Whether the entry is a directory.
+    #[serde(default)]
+    pub is_dir: bool,
+    /// For a directory, the total size of the indexed files below it.
+    #[serde(default)]
+    pub total_size: Option<u64>,
+    /// Score breakdown, when the query asked for one.
+    #[serde(default)]
+    pub explain: Option<ScoreExplanation>,
+}
+
+/// How many ranked results a page at `offset` of `limit` needs.
+///
+/// Relevance pages only need the results up to their end; other orders sort a
+/// fixed window so successive pages do not overlap.
+pub fn ranked_window(offset: usize, limit: usize, sort: SearchSort) -> usize {
+    let end = offset.saturating_add(limit);
+    if sort == SearchSort::Relevance {
+        end
+    } else {
+        end.max(SORTED_SEARCH_WINDOW)
+    }
+}
+
+/// Reorder ranked results for a non-relevance [`SearchSort`]; ties keep
+/// their relevance order.
+pub fn sort_results(results: &mut [SearchResult], sort: SearchSort) {
+    match sort {
+        SearchSort::Relevance => {}
+        SearchSort::Name => results.sort_by(|a, b| a.name.cmp(&b.name)),
+        SearchSort::Path => results.sort_by(|a, b| a.path.cmp(&b.path)),
+        SearchSort::Mtime => results.sort_by(|a, b| b.mtime.cmp(&a.mtime)),
+        // Directories rank by what they hold.
+        SearchSort::Size => results.sort_by(|a, b| {
+            let size = |r: &SearchResult| r.total_size.unwrap_or(r.size);
+            size(b).cmp(&size(a))
+        }),
+    }
+}
+
+/// Levels `path` lies below `base`: 1 for `base`'s direct entries, 0 for
+/// `base` itself. `path` is assumed to be inside `base`.
+pub fn depth_below(path: &Path, base: &Path) -> usize {
+    path.components()
+        .count()
+        .saturating_sub(base.components().count())
+}
+
+/// The deepest of `roots` holding `path`, or the filesystem root when none
+/// does.
+pub fn root_of<'r>(path: &Path, roots: &'r [PathBuf]) -> &'r Path {
+    roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .max_by_key(|root| root.components().count())
+        .map_or(Path::new("/"), PathBuf::as_path)
+}
+
+/// Levels `path` lies below the deepest of `roots` holding it, or below the
+/// filesystem root when none does.
+pub fn depth_in(path: &Path, roots: &[PathBuf]) -> usize {
+    depth_below(path, root_of(path, roots))
+}
+
+/// Whether `path` or a directory between it and `base` is dot-named.
+pub fn hidden_below(path: &Path, base: &Path) -> bool {
+    path.components()
+        .skip(base.components().count())
+        .any(|component| component.as_os_str().as_encoded_bytes().starts_with(b"."))
 }
 
 /// Query engine that searches the index.
@@ -43,6 +240,25 @@ pub struct QueryEngine<'a> {
     file_table: &'a FileTable,
     string_arena: &'a StringArena,
     trigram_index: &'a TrigramIndex,
+    path_index: Option<&'a PathIndex>,
+    roots: &'a [PathBuf],
+}
+
+/// Where a term's candidates come from; see [`QueryEngine::plan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CandidatePlan<'t> {
+    /// Scan entries in order; the term is too short for trigrams.
+    Linear,
+    /// Basename trigrams of the whole term.
+    Names,
+    /// Basename trigrams, topped up with entries of directories whose path
+    /// contains the term.
+    NamesThenPaths,
+    /// Entries of directories whose path contains the part of the term before
+    /// its last `/`, kept when their full path contains the whole term.
+    Paths(&'t str),
+    /// Basename trigrams of the part of the term after its last `/`.
+    NamesOf(&'t str),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -54,8 +270,33 @@ struct RankFeatures {
 struct QueryContext<'b> {
     boost_scope: Option<&'b Path>,
     filter_scope: Option<&'b Path>,
+    attributes: AttributeMatcher,
     cwd: Option<&'b Path>,
     abbr_matcher: AbbreviationMatcher,
+    normalization: Normalization,
+    fuzzy: bool,
+    explain: bool,
+    scorer: &'b dyn Scorer,
+    directory_bias: i32,
+    min_score: f32,
+    max_depth: Option<usize>,
+    hide_hidden: bool,
+    roots: &'b [PathBuf],
+}
+
+impl QueryContext<'_> {
+    /// Whether candidates must pass [`QueryEngine::passes_filters`].
+    fn filtered(&self) -> bool {
+        self.filter_scope.is_some()
+            || self.max_depth.is_some()
+            || self.hide_hidden
+            || !self.attributes.is_empty()
+    }
+
+    /// Whether [`QueryEngine::in_scope`] has anything to check.
+    fn placed(&self) -> bool {
+        self.filter_scope.is_some() || self.max_depth.is_some() || self.hide_hidden
+    }
 }
 
 impl<'a> QueryEngine<'a> {
@@ -69,54 +310,250 @@ impl<'a> QueryEngine<'a> {
             file_table,
             string_arena,
             trigram_index,
+            path_index: None,
+            roots: &[],
+        }
+    }
+
+    /// Index roots that unscoped `max_depth` and `hide_hidden` queries look
+    /// below. A
+    /// path under none of them counts from the filesystem root.
+    pub fn with_roots(mut self, roots: &'a [PathBuf]) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// Also generate candidates from directory paths; see [`PathIndex`].
+    pub fn with_path_index(mut self, path_index: Option<&'a PathIndex>) -> Self {
+        self.path_index = path_index;
+        self
+    }
+
+    /// Touch the posting lists of the `top_trigrams` most common trigrams and
+    /// the file metadata and strings they reference, so the first searches
+    /// after startup do not pay to fault those pages in. Returns the number of
+    /// postings visited.
+    pub fn warm_up(&self, top_trigrams: usize) -> usize {
+        let mut visited = 0;
+        let mut checksum = 0usize;
+        let mut path = String::new();
+        for trigram in self.trigram_index.most_common(top_trigrams) {
+            for &file_id in self.trigram_index.postings(trigram) {
+                visited += 1;
+                let Some(meta) = self.file_table.get(file_id) else {
+                    continue;
+                };
+                path.clear();
+                self.file_table
+                    .write_path(meta, self.string_arena, &mut path);
+                checksum = checksum
+                    .wrapping_add(path.len())
+                    .wrapping_add(meta.mtime as usize);
+            }
         }
+        std::hint::black_box(checksum);
+        visited
     }
 
     /// Execute a search query.
     pub fn search(&self, query: &Query) -> Vec<SearchResult> {
-        let normalized = query.term.to_lowercase();
+        self.search_outcome(query).results
+    }
+
+    /// Execute a search query, reporting whether the budget cut it short.
+    pub fn search_outcome(&self, query: &Query) -> SearchOutcome {
+        let mut budget = query.budget.start();
+        let normalization = self.trigram_index.normalization();
+        let normalized = normalization.apply(&query.term.to_lowercase()).into_owned();
         let cwd = std::env::current_dir().ok();
-        let context = QueryContext {
+        let context = self.context(query, normalization, cwd.as_deref());
+
+        let plan = self.plan(&normalized);
+        if plan == CandidatePlan::Linear {
+            let results = self.linear_search(&normalized, query.limit, &context, &mut budget);
+            return SearchOutcome {
+                results,
+                truncated: budget.exhausted,
+                suggestions: Vec::new(),
+                outside_scope: Vec::new(),
+            };
+        }
+
+        let candidates = self.plan_candidates(plan, &normalized, &context);
+
+        let results =
+            self.rank_file_ids(&normalized, query.limit, &candidates, &context, &mut budget);
+        SearchOutcome {
+            results,
+            truncated: budget.exhausted,
+            suggestions: Vec::new(),
+            outside_scope: Vec::new(),
+        }
+    }
+
+    fn context<'q>(
+        &self,
+        query: &'q Query,
+        normalization: Normalization,
+        cwd: Option<&'q Path>,
+    ) -> QueryContext<'q>
+    where
+        'a: 'q,
+    {
+        QueryContext {
             boost_scope: query.scope.as_deref(),
             filter_scope: query.filter_scope.as_deref(),
-            cwd: cwd.as_deref(),
+            attributes: query.attributes,
+            cwd,
             abbr_matcher: AbbreviationMatcher::new(),
-        };
+            normalization,
+            fuzzy: query.fuzzy,
+            explain: query.explain,
+            scorer: query.scorer.as_deref().unwrap_or(&DEFAULT_SCORER),
+            directory_bias: query.directory_bias,
+            min_score: query.min_score,
+            max_depth: query.max_depth,
+            hide_hidden: query.hide_hidden,
+            roots: self.roots,
+        }
+    }
 
-        // For short queries, do a linear scan
-        if normalized.len() < 3 {
-            return self.linear_search(&normalized, query.limit, &context);
-        }
-
-        // Extract trigrams and query the index
-        let trigrams = Trigram::extract(&normalized);
-        let candidates = if let Some(filter_scope) = context.filter_scope {
-            self.trigram_index.query_filtered_limited(
-                &trigrams,
-                INDEXED_QUERY_CANDIDATE_LIMIT,
-                |file_id| {
-                    let Some(meta) = self.file_table.get(file_id) else {
-                        return false;
-                    };
-                    let Some(path) = self.string_arena.get(meta.path_offset, meta.path_len) else {
-                        return false;
-                    };
-                    Self::scope_contains(Path::new(path), filter_scope, context.cwd)
-                },
-            )
+    /// Pick where the candidates for `term` (lowercased and folded) come from.
+    ///
+    /// A `/` never appears in a basename, so a term spanning directories gets
+    /// nothing from the name index: with a path index its directory part
+    /// selects candidates, otherwise its last component does.
+    fn plan<'t>(&self, term: &'t str) -> CandidatePlan<'t> {
+        if term.len() < 3 {
+            return CandidatePlan::Linear;
+        }
+        let Some((dir, name)) = term.rsplit_once('/') else {
+            return if self.path_index.is_some() {
+                CandidatePlan::NamesThenPaths
+            } else {
+                CandidatePlan::Names
+            };
+        };
+        if self.path_index.is_some() && dir.len() >= 3 {
+            CandidatePlan::Paths(dir)
+        } else if name.len() >= 3 {
+            CandidatePlan::NamesOf(name)
         } else {
-            self.trigram_index
-                .query_limited(&trigrams, INDEXED_QUERY_CANDIDATE_LIMIT)
+            CandidatePlan::Linear
+        }
+    }
+
+    /// Candidate file IDs for `term` as `plan` says, capped at
+    /// `INDEXED_QUERY_CANDIDATE_LIMIT` after the scope and attribute filters.
+    fn plan_candidates(
+        &self,
+        plan: CandidatePlan<'_>,
+        term: &str,
+        context: &QueryContext<'_>,
+    ) -> Vec<FileId> {
+        let filtered = context.filtered();
+        let names = |fragment: &str| {
+            let trigrams = Trigram::extract(fragment);
+            if filtered {
+                self.trigram_index.query_filtered_limited(
+                    &trigrams,
+                    INDEXED_QUERY_CANDIDATE_LIMIT,
+                    |file_id| self.passes_filters(file_id, context),
+                )
+            } else {
+                self.trigram_index
+                    .query_limited(&trigrams, INDEXED_QUERY_CANDIDATE_LIMIT)
+            }
         };
 
-        let mut ranked: Vec<(SearchResult, RankFeatures)> = Vec::with_capacity(query.limit);
-        for file_id in candidates {
-            if let Some(result) = self.score_candidate(file_id, &normalized, &context) {
-                self.push_ranked_candidate(&mut ranked, result, query.limit);
+        match (plan, self.path_index) {
+            (CandidatePlan::Linear, _) => Vec::new(),
+            (CandidatePlan::NamesOf(name), _) => names(name),
+            (CandidatePlan::NamesThenPaths, Some(path_index)) => {
+                let mut candidates = names(term);
+                let remaining = INDEXED_QUERY_CANDIDATE_LIMIT.saturating_sub(candidates.len());
+                if remaining > 0 {
+                    let seen: HashSet<FileId> = candidates.iter().copied().collect();
+                    candidates.extend(path_index.candidates(
+                        term,
+                        remaining,
+                        self.file_table,
+                        self.string_arena,
+                        |file_id| {
+                            !seen.contains(&file_id)
+                                && (!filtered || self.passes_filters(file_id, context))
+                        },
+                    ));
+                }
+                candidates
             }
+            (CandidatePlan::Paths(dir), Some(path_index)) => {
+                let mut path = String::new();
+                path_index.candidates(
+                    dir,
+                    INDEXED_QUERY_CANDIDATE_LIMIT,
+                    self.file_table,
+                    self.string_arena,
+                    |file_id| {
+                        let Some(meta) = self.file_table.get(file_id) else {
+                            return false;
+                        };
+                        path.clear();
+                        self.file_table
+                            .write_path(meta, self.string_arena, &mut path)
+                            && context
+                                .normalization
+                                .apply(&path.to_lowercase())
+                                .contains(term)
+                            && (!filtered || self.passes_filters(file_id, context))
+                    },
+                )
+            }
+            _ => names(term),
         }
-        self.sort_ranked_results(&mut ranked);
-        ranked.into_iter().map(|(r, _)| r).collect()
+    }
+
+    /// Whether `file_id` passes the query's attribute, filter-scope and depth
+    /// checks.
+    fn passes_filters(&self, file_id: FileId, context: &QueryContext<'_>) -> bool {
+        let Some(meta) = self.file_table.get(file_id) else {
+            return false;
+        };
+        if !context
+            .attributes
+            .matches(meta.uid, meta.mode, meta.content_hash)
+        {
+            return false;
+        }
+        if !context.placed() {
+            return true;
+        }
+        let Some(path) = self.file_table.path_of(meta, self.string_arena) else {
+            return false;
+        };
+        Self::in_scope(Path::new(&path), context)
+    }
+
+    /// Whether `path` lies inside the query's filter scope and depth limit,
+    /// and is not hidden when hidden entries are dropped.
+    fn in_scope(path: &Path, context: &QueryContext<'_>) -> bool {
+        if !context.placed() {
+            return true;
+        }
+        let (path, base) = match context.filter_scope {
+            Some(scope) => match Self::scope_pair(path, scope, context.cwd) {
+                Some(pair) => pair,
+                None => return false,
+            },
+            None => (
+                path.to_path_buf(),
+                root_of(path, context.roots).to_path_buf(),
+            ),
+        };
+        context
+            .max_depth
+            .is_none_or(|max_depth| depth_below(&path, &base) <= max_depth)
+            && !(context.hide_hidden && hidden_below(&path, &base))
     }
 
     /// Execute a query against a pre-filtered set of file IDs.
@@ -124,16 +561,93 @@ impl<'a> QueryEngine<'a> {
     /// This is intended for daemon-side scope accelerators where enumerating a small
     /// subtree is cheaper than probing global posting lists and filtering afterward.
     pub fn search_file_ids(&self, query: &Query, file_ids: &[FileId]) -> Vec<SearchResult> {
-        let normalized = query.term.to_lowercase();
+        self.search_file_ids_outcome(query, file_ids).results
+    }
+
+    /// [`QueryEngine::search_file_ids`], reporting whether the budget cut it short.
+    pub fn search_file_ids_outcome(&self, query: &Query, file_ids: &[FileId]) -> SearchOutcome {
+        let mut budget = query.budget.start();
+        let normalization = self.trigram_index.normalization();
+        let normalized = normalization.apply(&query.term.to_lowercase()).into_owned();
         let cwd = std::env::current_dir().ok();
-        let context = QueryContext {
-            boost_scope: query.scope.as_deref(),
-            filter_scope: query.filter_scope.as_deref(),
-            cwd: cwd.as_deref(),
-            abbr_matcher: AbbreviationMatcher::new(),
-        };
+        let context = self.context(query, normalization, cwd.as_deref());
+
+        let results = self.rank_file_ids(&normalized, query.limit, file_ids, &context, &mut budget);
+        SearchOutcome {
+            results,
+            truncated: budget.exhausted,
+            suggestions: Vec::new(),
+            outside_scope: Vec::new(),
+        }
+    }
 
-        self.search_file_ids_normalized(&normalized, query.limit, file_ids, &context)
+    /// Up to `max` basenames close to the query's term, closest first, for a
+    /// "did you mean" hint when the search found nothing.
+    ///
+    /// Names sharing the most trigrams with the term (rarest posting lists
+    /// first, within a budget) are compared by edit distance, with an adjacent
+    /// transposition counting as one edit, against the whole name and its
+    /// stem. Those within `max(1, len / 4)` edits of the term and inside the
+    /// query's filter scope and attributes qualify.
+    pub fn suggest(&self, query: &Query, max: usize) -> Vec<String> {
+        let normalization = self.trigram_index.normalization();
+        let term = normalization
+            .apply(&query.term.trim().to_lowercase())
+            .into_owned();
+        let term_chars: Vec<char> = term.chars().collect();
+        if max == 0 || term_chars.len() < 3 || term.contains('/') {
+            return Vec::new();
+        }
+        let max_edits = (term_chars.len() / 4).max(1);
+
+        let mut overlap: HashMap<FileId, u32> = HashMap::new();
+        let mut budget = SUGGESTION_POSTING_BUDGET;
+        for (trigram, postings) in self.trigram_index.selectivity(&Trigram::extract(&term)) {
+            // Rarest first, so every later list is at least as long.
+            if postings > budget {
+                break;
+            }
+            budget -= postings;
+            for &file_id in self.trigram_index.postings(trigram) {
+                *overlap.entry(file_id).or_default() += 1;
+            }
+        }
+        let mut candidates: Vec<(FileId, u32)> = overlap.into_iter().collect();
+        candidates.sort_unstable_by_key(|&(file_id, shared)| (Reverse(shared), file_id));
+
+        let cwd = std::env::current_dir().ok();
+        let context = self.context(query, normalization, cwd.as_deref());
+        let filtered = context.filtered();
+        let mut seen = HashSet::new();
+        let mut close: Vec<(usize, Reverse<u32>, String)> = Vec::new();
+        for (file_id, shared) in candidates.into_iter().take(SUGGESTION_CANDIDATES) {
+            let Some(meta) = self.file_table.get(file_id) else {
+                continue;
+            };
+            let Some(name) = self.string_arena.get(meta.name_offset, meta.name_len) else {
+                continue;
+            };
+            if meta.is_tombstone() || seen.contains(name) {
+                continue;
+            }
+            let folded = normalization.apply(&name.to_lowercase()).into_owned();
+            let stem = folded.rsplit_once('.').map_or("", |(stem, _)| stem);
+            let edits = edit_distance(&term_chars, &folded).min(edit_distance(&term_chars, stem));
+            if edits == 0 || edits > max_edits {
+                continue;
+            }
+            if filtered && !self.passes_filters(file_id, &context) {
+                continue;
+            }
+            seen.insert(name);
+            close.push((edits, Reverse(shared), name.to_string()));
+        }
+        close.sort_unstable();
+        close
+            .into_iter()
+            .map(|(_, _, name)| name)
+            .take(max)
+            .collect()
     }
 
     /// Score a candidate file.
@@ -144,51 +658,96 @@ impl<'a> QueryEngine<'a> {
         context: &QueryContext<'_>,
     ) -> Option<(SearchResult, RankFeatures)> {
         let meta = self.file_table.get(file_id)?;
+        if !context
+            .attributes
+            .matches(meta.uid, meta.mode, meta.content_hash)
+        {
+            return None;
+        }
 
-        let path = self.string_arena.get(meta.path_offset, meta.path_len)?;
+        let path = self.file_table.path_of(meta, self.string_arena)?;
+        let path = path.as_str();
         let name = self.string_arena.get(meta.name_offset, meta.name_len)?;
         let path_buf = Path::new(path);
 
-        if let Some(filter_scope) = context.filter_scope {
-            if !Self::scope_contains(path_buf, filter_scope, context.cwd) {
-                return None;
-            }
+        if !Self::in_scope(path_buf, context) {
+            return None;
         }
 
         let name_lower = lower_if_needed(name);
         let path_lower = lower_if_needed(path);
+        let name_folded = context.normalization.apply(name_lower.as_ref());
+        let path_folded = context.normalization.apply(path_lower.as_ref());
 
         // Try traditional substring matching
         let substring_score =
-            if name_lower.as_ref().contains(query) || path_lower.as_ref().contains(query) {
-                Some(self.calculate_score(name_lower.as_ref(), path_lower.as_ref(), query))
+            if name_folded.as_ref().contains(query) || path_folded.as_ref().contains(query) {
+                Some(self.calculate_score(name_folded.as_ref(), path_folded.as_ref(), query))
             } else {
                 None
             };
 
-        let abbr_score = if substring_score.is_some() || is_literal_filename_query(query) {
-            None
-        } else {
-            context
-                .abbr_matcher
-                .match_path(query, path)
-                .map(|abbr_match| abbr_match.score)
-        };
+        let abbr_score =
+            if !context.fuzzy || substring_score.is_some() || is_literal_filename_query(query) {
+                None
+            } else {
+                context
+                    .abbr_matcher
+                    .match_path(query, path)
+                    .map(|abbr_match| (abbr_match.score, MatchStrategy::Abbreviation))
+            };
 
         // Use the best score from either method
-        let score = match (abbr_score, substring_score) {
-            (Some(a), Some(s)) => a.max(s),
+        let (match_score, strategy) = match (abbr_score, substring_score) {
+            (Some(a), Some(s)) => {
+                if a.0 > s.0 {
+                    a
+                } else {
+                    s
+                }
+            }
             (Some(a), None) => a,
             (None, Some(s)) => s,
             (None, None) => return None,
         };
+        if match_score < context.min_score {
+            return None;
+        }
 
         let path_depth = Self::path_depth(path);
+        let context_penalty = Self::context_score(path_lower.as_ref());
+        let scope_boost = Self::scope_boost(path_buf, context.boost_scope, context.cwd);
+        let directory_bias = if meta.is_dir {
+            context.directory_bias
+        } else {
+            0
+        };
+        let score = context.scorer.score_candidate(&Candidate {
+            path,
+            name,
+            size: meta.size,
+            mtime: meta.mtime,
+            is_dir: meta.is_dir,
+            match_score,
+            strategy,
+            context_penalty,
+            scope_boost,
+            path_depth,
+        })?;
         let features = RankFeatures {
-            context_score: Self::context_score(path_lower.as_ref())
-                + Self::scope_boost(path_buf, context.boost_scope, context.cwd),
+            context_score: context_penalty + scope_boost + directory_bias,
             path_depth,
         };
+        let explain = context.explain.then_some(ScoreExplanation {
+            strategy,
+            base_score: score,
+            smriti_boost: 0.0,
+            recent_scope_boost: 0.0,
+            context_penalty,
+            scope_boost,
+            directory_bias,
+            path_depth,
+        });
 
         Some((
             SearchResult {
@@ -197,16 +756,24 @@ impl<'a> QueryEngine<'a> {
                 score,
                 size: meta.size,
                 mtime: meta.mtime,
+                uid: meta.uid,
+                gid: meta.gid,
+                mode: meta.mode,
+                indexed_at: meta.indexed_at,
+                content_hash: meta.content_hash,
+                is_dir: meta.is_dir,
+                total_size: None,
+                explain,
             },
             features,
         ))
     }
 
-    /// Calculate match score (0.0 to 1.0).
-    fn calculate_score(&self, name: &str, _path: &str, query: &str) -> f32 {
+    /// Calculate match score (0.0 to 1.0) and how the term matched.
+    fn calculate_score(&self, name: &str, _path: &str, query: &str) -> (f32, MatchStrategy) {
         // Exact match of entire basename (highest score)
         if name == query {
-            return 1.0;
+            return (1.0, MatchStrategy::Exact);
         }
 
         // Check for prefix match
@@ -214,21 +781,21 @@ impl<'a> QueryEngine<'a> {
             // Prefer shorter suffixes - use ratio of query length to total length
             // This makes "main.rs" score higher than "main_test.rs"
             let ratio = query.len() as f32 / name.len() as f32;
-            return 0.9 + (ratio * 0.09); // Range: 0.9 to 0.99
+            return (0.9 + (ratio * 0.09), MatchStrategy::Prefix); // Range: 0.9 to 0.99
         }
 
         // Contains as whole word (after underscore or space)
         if name.contains(&format!(" {}", query)) || name.contains(&format!("_{}", query)) {
-            return 0.7;
+            return (0.7, MatchStrategy::Word);
         }
 
         // Contains as substring
         if name.contains(query) {
-            return 0.5;
+            return (0.5, MatchStrategy::Substring);
         }
 
         // Default score for trigram matches
-        0.3
+        (0.3, MatchStrategy::Path)
     }
 
     /// Linear search for short queries.
@@ -237,6 +804,7 @@ impl<'a> QueryEngine<'a> {
         query: &str,
         limit: usize,
         context: &QueryContext<'_>,
+        budget: &mut BudgetTracker,
     ) -> Vec<SearchResult> {
         if limit == 0 {
             return Vec::new();
@@ -249,7 +817,11 @@ impl<'a> QueryEngine<'a> {
 
         for (scanned, (file_id, _meta)) in self.file_table.iter().enumerate() {
             // Early termination for non-matching queries
-            if context.filter_scope.is_none() && ranked.is_empty() && scanned >= MAX_EMPTY_SCAN {
+            if context.filter_scope.is_none()
+                && context.attributes.is_empty()
+                && ranked.is_empty()
+                && scanned >= MAX_EMPTY_SCAN
+            {
                 break;
             }
             // One- and two-character queries are inherently broad over large home
@@ -260,22 +832,26 @@ impl<'a> QueryEngine<'a> {
             {
                 break;
             }
+            if !budget.charge() {
+                break;
+            }
 
             if let Some(result) = self.score_candidate(file_id, query, context) {
-                self.push_ranked_candidate(&mut ranked, result, limit);
+                Self::push_ranked_candidate(context.scorer, &mut ranked, result, limit);
             }
         }
 
-        self.sort_ranked_results(&mut ranked);
+        Self::sort_ranked_results(context.scorer, &mut ranked);
         ranked.into_iter().map(|(r, _)| r).collect()
     }
 
-    fn search_file_ids_normalized(
+    fn rank_file_ids(
         &self,
         query: &str,
         limit: usize,
         file_ids: &[FileId],
         context: &QueryContext<'_>,
+        budget: &mut BudgetTracker,
     ) -> Vec<SearchResult> {
         if limit == 0 {
             return Vec::new();
@@ -283,21 +859,24 @@ impl<'a> QueryEngine<'a> {
 
         let mut ranked: Vec<(SearchResult, RankFeatures)> = Vec::with_capacity(limit);
         for &file_id in file_ids {
+            if !budget.charge() {
+                break;
+            }
             if let Some(result) = self.score_candidate(file_id, query, context) {
-                self.push_ranked_candidate(&mut ranked, result, limit);
+                Self::push_ranked_candidate(context.scorer, &mut ranked, result, limit);
             }
         }
 
-        self.sort_ranked_results(&mut ranked);
+        Self::sort_ranked_results(context.scorer, &mut ranked);
         ranked.into_iter().map(|(r, _)| r).collect()
     }
 
-    fn sort_ranked_results(&self, ranked: &mut [(SearchResult, RankFeatures)]) {
-        ranked.sort_by(Self::compare_ranked);
+    fn sort_ranked_results(scorer: &dyn Scorer, ranked: &mut [(SearchResult, RankFeatures)]) {
+        ranked.sort_by(|a, b| Self::compare_ranked(scorer, a, b));
     }
 
     fn push_ranked_candidate(
-        &self,
+        scorer: &dyn Scorer,
         ranked: &mut Vec<(SearchResult, RankFeatures)>,
         candidate: (SearchResult, RankFeatures),
         limit: usize,
@@ -310,25 +889,25 @@ impl<'a> QueryEngine<'a> {
         if let Some((worst_index, worst)) = ranked
             .iter()
             .enumerate()
-            .max_by(|(_, a), (_, b)| Self::compare_ranked(a, b))
+            .max_by(|(_, a), (_, b)| Self::compare_ranked(scorer, a, b))
         {
-            if Self::compare_ranked(&candidate, worst) == Ordering::Less {
+            if Self::compare_ranked(scorer, &candidate, worst) == Ordering::Less {
                 ranked[worst_index] = candidate;
             }
         }
     }
 
     fn compare_ranked(
+        scorer: &dyn Scorer,
         (a, af): &(SearchResult, RankFeatures),
         (b, bf): &(SearchResult, RankFeatures),
     ) -> Ordering {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(Ordering::Equal)
-            .then_with(|| bf.context_score.cmp(&af.context_score))
-            .then_with(|| b.mtime.cmp(&a.mtime))
-            .then_with(|| af.path_depth.cmp(&bf.path_depth))
-            .then_with(|| a.path.cmp(&b.path))
+        let ranked = |result, features: &RankFeatures| Ranked {
+            result,
+            context_score: features.context_score,
+            path_depth: features.path_depth,
+        };
+        scorer.compare(&ranked(a, af), &ranked(b, bf))
     }
 
     fn path_depth(path: &str) -> usize {
@@ -438,9 +1017,6 @@ impl<'a> QueryEngine<'a> {
         let mut heap: BinaryHeap<Reverse<RecentCandidate>> = BinaryHeap::with_capacity(limit + 1);
 
         for (file_id, meta) in self.file_table.iter() {
-            let Some(path) = self.string_arena.get(meta.path_offset, meta.path_len) else {
-                continue;
-            };
             let Some(name) = self.string_arena.get(meta.name_offset, meta.name_len) else {
                 continue;
             };
@@ -451,7 +1027,10 @@ impl<'a> QueryEngine<'a> {
             }
 
             if let Some(scope_path) = filter_scope {
-                if !Self::scope_contains(Path::new(path), scope_path, cwd) {
+                let Some(path) = self.file_table.path_of(meta, self.string_arena) else {
+                    continue;
+                };
+                if !Self::scope_contains(Path::new(&path), scope_path, cwd) {
                     continue;
                 }
             }
@@ -479,14 +1058,22 @@ impl<'a> QueryEngine<'a> {
             .into_iter()
             .filter_map(|candidate| {
                 let meta = self.file_table.get(candidate.file_id)?;
-                let path = self.string_arena.get(meta.path_offset, meta.path_len)?;
+                let path = self.file_table.path_of(meta, self.string_arena)?;
                 let name = self.string_arena.get(meta.name_offset, meta.name_len)?;
                 Some(SearchResult {
-                    path: path.to_string(),
+                    path,
                     name: name.to_string(),
                     score: 0.0,
                     size: meta.size,
                     mtime: meta.mtime,
+                    uid: meta.uid,
+                    gid: meta.gid,
+                    mode: meta.mode,
+                    indexed_at: meta.indexed_at,
+                    content_hash: meta.content_hash,
+                    is_dir: meta.is_dir,
+                    total_size: None,
+                    explain: None,
                 })
             })
             .collect()
@@ -534,14 +1121,22 @@ impl<'a> QueryEngine<'a> {
             .into_iter()
             .filter_map(|candidate| {
                 let meta = self.file_table.get(candidate.file_id)?;
-                let path = self.string_arena.get(meta.path_offset, meta.path_len)?;
+                let path = self.file_table.path_of(meta, self.string_arena)?;
                 let name = self.string_arena.get(meta.name_offset, meta.name_len)?;
                 Some(SearchResult {
-                    path: path.to_string(),
+                    path,
                     name: name.to_string(),
                     score: 0.0,
                     size: meta.size,
                     mtime: meta.mtime,
+                    uid: meta.uid,
+                    gid: meta.gid,
+                    mode: meta.mode,
+                    indexed_at: meta.indexed_at,
+                    content_hash: meta.content_hash,
+                    is_dir: meta.is_dir,
+                    total_size: None,
+                    explain: None,
                 })
             })
             .collect()
@@ -553,17 +1148,25 @@ impl<'a> QueryEngine<'a> {
             .iter()
             .filter_map(|&file_id| {
                 let meta = self.file_table.get(file_id)?;
-                let path = self.string_arena.get(meta.path_offset, meta.path_len)?;
+                let path = self.file_table.path_of(meta, self.string_arena)?;
                 let name = self.string_arena.get(meta.name_offset, meta.name_len)?;
                 if name.is_empty() {
                     return None;
                 }
                 Some(SearchResult {
-                    path: path.to_string(),
+                    path,
                     name: name.to_string(),
                     score: 1.0,
                     size: meta.size,
                     mtime: meta.mtime,
+                    uid: meta.uid,
+                    gid: meta.gid,
+                    mode: meta.mode,
+                    indexed_at: meta.indexed_at,
+                    content_hash: meta.content_hash,
+                    is_dir: meta.is_dir,
+                    total_size: None,
+                    explain: None,
                 })
             })
             .collect();
@@ -621,6 +1224,27 @@ impl<'a> QueryEngine<'a> {
     }
 }
 
+/// Edits (insert, delete, substitute, or swap two adjacent characters)
+/// between `a` and `b`: the optimal string alignment distance.
+fn edit_distance(a: &[char], b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Rows i-2, i-1 and i of the DP table over prefixes of `a` and `b`.
+    let mut before: Vec<usize> = Vec::new();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, &ca) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitute.min(previous[j + 1] + 1).min(current[j] + 1);
+            if i > 0 && j > 0 && ca == b[j - 1] && a[i - 1] == cb {
+                current[j + 1] = current[j + 1].min(before[j - 1] + 1);
+            }
+        }
+        before = std::mem::replace(&mut previous, current);
+    }
+    previous[b.len()]
+}
+
 fn lower_if_needed(text: &str) -> std::borrow::Cow<'_, str> {
     if text.is_ascii() {
         if text.bytes().any(|b| b.is_ascii_uppercase()) {
@@ -677,18 +1301,23 @@ mod tests {
         let mut index = TrigramIndex::new();
 
         // Add some test files
-        let (path_off, path_len) = arena.add("/home/user/test.txt");
+        let parent = file_table.intern_path("/home/user/test.txt", &mut arena).0;
         let (name_off, name_len) = arena.add("test.txt");
 
         let meta = FileMeta {
-            path_offset: path_off,
-            path_len,
+            parent,
             name_offset: name_off,
             name_len,
             size: 1024,
             mtime: 0,
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         };
 
         let file_id = file_table.insert(meta);
@@ -700,6 +1329,15 @@ mod tests {
             limit: 10,
             scope: None,
             filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let results = engine.search(&query);
@@ -707,23 +1345,159 @@ mod tests {
         assert_eq!(results[0].name, "test.txt");
     }
 
+    #[test]
+    fn explain_breaks_down_strategy_penalties_and_scope_boost() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+        for path in [
+            "/repo/src/config.rs",
+            "/repo/target/debug/config",
+            "/repo/docs/app_config.md",
+        ] {
+            let name = path.rsplit('/').next().unwrap();
+            let parent = file_table.intern_path(path, &mut arena).0;
+            let (name_offset, name_len) = arena.add(name);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset,
+                name_len,
+                size: 1,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, name);
+        }
+
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        let mut query = Query {
+            term: "config".to_string(),
+            limit: 10,
+            scope: Some(PathBuf::from("/repo/src")),
+            filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: true,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
+        };
+        let results = engine.search(&query);
+        let explain = |name: &str| {
+            results
+                .iter()
+                .find(|result| result.name == name)
+                .and_then(|result| result.explain)
+                .unwrap()
+        };
+
+        let exact = explain("config");
+        assert_eq!(exact.strategy, MatchStrategy::Exact);
+        assert_eq!(exact.base_score, 1.0);
+        assert_eq!(exact.context_penalty, -60, "build output is demoted");
+        assert_eq!(exact.scope_boost, 0, "outside the scope");
+        let prefix = explain("config.rs");
+        assert_eq!(prefix.strategy, MatchStrategy::Prefix);
+        assert_eq!((prefix.context_penalty, prefix.scope_boost), (0, 119));
+        assert_eq!(prefix.path_depth, 4);
+        assert_eq!(explain("app_config.md").strategy, MatchStrategy::Word);
+
+        query.explain = false;
+        assert!(engine.search(&query).iter().all(|r| r.explain.is_none()));
+    }
+
+    #[test]
+    fn directory_bias_breaks_ties_between_directories_and_files() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+        for (path, is_dir) in [("/repo/a/notes", true), ("/repo/b/notes", false)] {
+            let name = path.rsplit('/').next().unwrap();
+            let parent = file_table.intern_path(path, &mut arena).0;
+            let (name_offset, name_len) = arena.add(name);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset,
+                name_len,
+                size: 0,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir,
+            });
+            index.add(file_id, name);
+        }
+
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        let search = |directory_bias: i32| {
+            engine.search(&Query {
+                term: "notes".to_string(),
+                limit: 10,
+                scope: None,
+                filter_scope: None,
+                budget: Default::default(),
+                attributes: Default::default(),
+                fuzzy: true,
+                explain: true,
+                scorer: None,
+                directory_bias,
+                min_score: 0.0,
+                max_depth: None,
+                hide_hidden: false,
+            })
+        };
+
+        let preferred = search(40);
+        assert!(preferred[0].is_dir);
+        assert_eq!(preferred[0].explain.unwrap().directory_bias, 40);
+        assert_eq!(
+            preferred[1].explain.unwrap().directory_bias,
+            0,
+            "files keep 0"
+        );
+
+        let demoted = search(-40);
+        assert_eq!(demoted[0].path, "/repo/b/notes");
+        assert!(!demoted[0].is_dir && demoted[1].is_dir);
+    }
+
     #[test]
     fn unicode_uppercase_filename_matches_lowercase_query() {
         let mut file_table = FileTable::new();
         let mut arena = StringArena::new();
         let mut index = TrigramIndex::new();
 
-        let (path_off, path_len) = arena.add("/repo/Überblick.md");
+        let parent = file_table.intern_path("/repo/Überblick.md", &mut arena).0;
         let (name_off, name_len) = arena.add("Überblick.md");
         let file_id = file_table.insert(FileMeta {
-            path_offset: path_off,
-            path_len,
+            parent,
             name_offset: name_off,
             name_len,
             size: 1,
             mtime: 0,
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         });
         index.add(file_id, "Überblick.md");
 
@@ -733,12 +1507,322 @@ mod tests {
             limit: 10,
             scope: None,
             filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         });
 
         assert_eq!(results.len(), 1);
         assert_eq!(results[0].name, "Überblick.md");
     }
 
+    #[test]
+    fn normalization_folds_names_and_queries_alike() {
+        let names = ["résumé.pdf", "foo_bar.rs", "foo-bar.md", "foobar.txt"];
+        let search = |normalization: Normalization, term: &str| {
+            let mut file_table = FileTable::new();
+            let mut arena = StringArena::new();
+            let mut index = TrigramIndex::with_normalization(normalization);
+            for name in names {
+                let (parent, name_offset, name_len) =
+                    file_table.intern_path(&format!("/docs/{name}"), &mut arena);
+                let file_id = file_table.insert(FileMeta {
+                    parent,
+                    name_offset,
+                    name_len,
+                    size: 1,
+                    mtime: 0,
+                    dev: 0,
+                    ino: 0,
+                    uid: 0,
+                    gid: 0,
+                    mode: 0,
+                    indexed_at: 0,
+                    content_hash: None,
+                    is_dir: false,
+                });
+                index.add(file_id, name);
+            }
+            let engine = QueryEngine::new(&file_table, &arena, &index);
+            let mut names: Vec<String> = engine
+                .search(&Query {
+                    term: term.to_string(),
+                    limit: 10,
+                    scope: None,
+                    filter_scope: None,
+                    budget: Default::default(),
+                    attributes: Default::default(),
+                    fuzzy: false,
+                    explain: false,
+                    scorer: None,
+                    directory_bias: 0,
+                    min_score: 0.0,
+                    max_depth: None,
+                    hide_hidden: false,
+                })
+                .into_iter()
+                .map(|result| result.name)
+                .collect();
+            names.sort();
+            names
+        };
+
+        let folded = Normalization {
+            fold_diacritics: true,
+            fold_separators: true,
+        };
+        assert_eq!(search(folded, "resume"), vec!["résumé.pdf"]);
+        assert_eq!(search(folded, "RÉSUMÉ"), vec!["résumé.pdf"]);
+        assert_eq!(search(folded, "foo bar"), vec!["foo-bar.md", "foo_bar.rs"]);
+        assert_eq!(search(folded, "foo-b"), vec!["foo-bar.md", "foo_bar.rs"]);
+
+        assert!(search(Normalization::default(), "resume").is_empty());
+        assert_eq!(
+            search(Normalization::default(), "foo_bar"),
+            vec!["foo_bar.rs"]
+        );
+    }
+
+    #[test]
+    fn path_index_plans_candidates_for_terms_spanning_directories() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+        for path in [
+            "/home/me/Projects/spartan/src/main.rs",
+            "/home/me/Projects/spartan",
+            "/home/me/Projects/athens/notes.md",
+            "/home/me/spartan-notes.md",
+        ] {
+            let (parent, name_offset, name_len) = file_table.intern_path(path, &mut arena);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset,
+                name_len,
+                size: 1,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, path.rsplit('/').next().unwrap());
+        }
+        let path_index = PathIndex::build(&file_table, &arena, index.normalization());
+
+        let search = |path_index: Option<&PathIndex>, term: &str| {
+            let engine = QueryEngine::new(&file_table, &arena, &index).with_path_index(path_index);
+            let mut paths: Vec<String> = engine
+                .search(&Query {
+                    term: term.to_string(),
+                    limit: 10,
+                    scope: None,
+                    filter_scope: None,
+                    budget: Default::default(),
+                    attributes: Default::default(),
+                    fuzzy: false,
+                    explain: false,
+                    scorer: None,
+                    directory_bias: 0,
+                    min_score: 0.0,
+                    max_depth: None,
+                    hide_hidden: false,
+                })
+                .into_iter()
+                .map(|result| result.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        assert_eq!(engine.plan("src/main"), CandidatePlan::NamesOf("main"));
+        assert_eq!(engine.plan("spartan"), CandidatePlan::Names);
+        let engine = engine.with_path_index(Some(&path_index));
+        assert_eq!(
+            engine.plan("projects/spartan"),
+            CandidatePlan::Paths("projects")
+        );
+        assert_eq!(engine.plan("spartan"), CandidatePlan::NamesThenPaths);
+        assert_eq!(engine.plan("ab/cd"), CandidatePlan::Linear);
+
+        // Without the path index only the last component reaches the name
+        // index, so the directory's contents are missed.
+        assert_eq!(
+            search(None, "projects/spartan"),
+            ["/home/me/Projects/spartan"]
+        );
+        assert_eq!(
+            search(Some(&path_index), "projects/spartan"),
+            [
+                "/home/me/Projects/spartan",
+                "/home/me/Projects/spartan/src/main.rs"
+            ]
+        );
+        assert_eq!(
+            search(None, "spartan"),
+            ["/home/me/Projects/spartan", "/home/me/spartan-notes.md"]
+        );
+        assert_eq!(
+            search(Some(&path_index), "spartan"),
+            [
+                "/home/me/Projects/spartan",
+                "/home/me/Projects/spartan/src/main.rs",
+                "/home/me/spartan-notes.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn min_score_drops_weak_matches_and_suggest_offers_near_misses() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+        let mut file_ids = Vec::new();
+        for path in [
+            "/w/config.toml",
+            "/w/contig.rs",
+            "/w/src/main.rs",
+            "/w/src/mainline.rs",
+            "/w/docs/main.rs",
+        ] {
+            let (parent, name_offset, name_len) = file_table.intern_path(path, &mut arena);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset,
+                name_len,
+                size: 1,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, path.rsplit('/').next().unwrap());
+            file_ids.push(file_id);
+        }
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        let query = |term: &str, min_score: f32, filter_scope: Option<&str>| Query {
+            term: term.to_string(),
+            limit: 10,
+            scope: None,
+            filter_scope: filter_scope.map(PathBuf::from),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: false,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score,
+            max_depth: None,
+            hide_hidden: false,
+        };
+
+        // `docs` only matches a directory in the path (0.3).
+        assert_eq!(
+            engine
+                .search_file_ids(&query("docs", 0.0, None), &file_ids)
+                .len(),
+            1
+        );
+        assert!(engine
+            .search_file_ids(&query("docs", 0.4, None), &file_ids)
+            .is_empty());
+        assert_eq!(engine.search(&query("main", 0.4, None)).len(), 3);
+
+        // Stems count, a transposition is one edit, and names are listed once.
+        assert_eq!(
+            engine.suggest(&query("confg", 0.0, None), 5),
+            ["config.toml"]
+        );
+        assert_eq!(engine.suggest(&query("mian.rs", 0.0, None), 5), ["main.rs"]);
+        assert!(engine
+            .suggest(&query("confg", 0.0, Some("/w/docs")), 5)
+            .is_empty());
+        assert_eq!(
+            engine.suggest(&query("mian.rs", 0.0, Some("/w/docs")), 5),
+            ["main.rs"]
+        );
+        assert!(engine.suggest(&query("zzzzzz", 0.0, None), 5).is_empty());
+        assert!(engine.suggest(&query("confg", 0.0, None), 0).is_empty());
+        assert_eq!(edit_distance(&['a', 'b', 'c'], "acb"), 1);
+        assert_eq!(edit_distance(&['k', 'i', 't'], "sitting"), 5);
+    }
+
+    #[test]
+    fn test_fuzzy_off_keeps_only_substring_matches() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+
+        let mut ids = Vec::new();
+        for path in ["/repo/src/main.rs", "/repo/src/mr.txt"] {
+            let parent = file_table.intern_path(path, &mut arena).0;
+            let name = Path::new(path).file_name().unwrap().to_str().unwrap();
+            let (name_offset, name_len) = arena.add(name);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset,
+                name_len,
+                size: 1,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, name);
+            ids.push(file_id);
+        }
+
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        let mut query = Query {
+            term: "mr".to_string(),
+            limit: 10,
+            scope: None,
+            filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
+        };
+        let names = |results: Vec<SearchResult>| {
+            let mut names: Vec<String> = results.into_iter().map(|r| r.name).collect();
+            names.sort();
+            names
+        };
+        assert_eq!(
+            names(engine.search_file_ids(&query, &ids)),
+            vec!["main.rs", "mr.txt"]
+        );
+
+        query.fuzzy = false;
+        assert_eq!(names(engine.search_file_ids(&query, &ids)), vec!["mr.txt"]);
+    }
+
     #[test]
     fn test_early_termination_for_non_matching() {
         let mut file_table = FileTable::new();
@@ -750,18 +1834,23 @@ mod tests {
             let path = format!("/home/user/file_{}.txt", i);
             let name = format!("file_{}.txt", i);
 
-            let (path_off, path_len) = arena.add(&path);
+            let parent = file_table.intern_path(&path, &mut arena).0;
             let (name_off, name_len) = arena.add(&name);
 
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1024,
                 mtime: 0,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
 
             let file_id = file_table.insert(meta);
@@ -776,6 +1865,15 @@ mod tests {
             limit: 100,
             scope: None,
             filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let start = std::time::Instant::now();
@@ -806,18 +1904,23 @@ mod tests {
             let path = format!("/home/user/file_{}.txt", i);
             let name = format!("file_{}.txt", i);
 
-            let (path_off, path_len) = arena.add(&path);
+            let parent = file_table.intern_path(&path, &mut arena).0;
             let (name_off, name_len) = arena.add(&name);
 
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1024,
                 mtime: 0,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
 
             let file_id = file_table.insert(meta);
@@ -832,6 +1935,15 @@ mod tests {
             limit: 50,
             scope: None,
             filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let results = engine.search(&query);
@@ -863,18 +1975,23 @@ mod tests {
             "session.rs",
         ] {
             let path = format!("/repo/{name}");
-            let (path_off, path_len) = arena.add(&path);
+            let parent = file_table.intern_path(&path, &mut arena).0;
             let (name_off, name_len) = arena.add(name);
 
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1024,
                 mtime: 0,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
 
             let file_id = file_table.insert(meta);
@@ -887,6 +2004,15 @@ mod tests {
             limit: 2,
             scope: None,
             filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let results = engine.search(&query);
@@ -903,17 +2029,22 @@ mod tests {
         for i in 0..15_000 {
             let path = format!("/home/user/site-packages/pkg_{i}/RECORD");
             let name = "RECORD";
-            let (path_off, path_len) = arena.add(&path);
+            let parent = file_table.intern_path(&path, &mut arena).0;
             let (name_off, name_len) = arena.add(name);
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1024,
                 mtime: i,
                 dev: 0,
                 ino: i as u64,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
 
             let file_id = file_table.insert(meta);
@@ -926,12 +2057,124 @@ mod tests {
             limit: 10,
             scope: Some(PathBuf::from("/home/user")),
             filter_scope: None,
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         });
 
         assert_eq!(results.len(), 10);
         assert!(results.iter().all(|result| result.name == "RECORD"));
     }
 
+    fn record_index(count: usize) -> (FileTable, StringArena, TrigramIndex) {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+
+        for i in 0..count {
+            let path = format!("/data/batch_{i}/record_{i}.csv");
+            let name = format!("record_{i}.csv");
+            let parent = file_table.intern_path(&path, &mut arena).0;
+            let (name_off, name_len) = arena.add(&name);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset: name_off,
+                name_len,
+                size: 1,
+                mtime: i as i64,
+                dev: 0,
+                ino: i as u64,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, &name);
+        }
+
+        (file_table, arena, index)
+    }
+
+    #[test]
+    fn test_candidate_budget_truncates_and_keeps_best_so_far() {
+        let (file_table, arena, index) = record_index(1_000);
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        let mut query = Query {
+            term: "record".to_string(),
+            limit: 5,
+            scope: None,
+            filter_scope: None,
+            budget: QueryBudget {
+                max_duration: None,
+                max_candidates: Some(100),
+            },
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
+        };
+
+        let outcome = engine.search_outcome(&query);
+        assert!(outcome.truncated);
+        assert_eq!(outcome.results.len(), 5);
+
+        query.budget = QueryBudget::default();
+        let outcome = engine.search_outcome(&query);
+        assert!(!outcome.truncated);
+        assert_eq!(outcome.results.len(), 5);
+
+        // Short queries take the linear path and honour the same budget.
+        query.term = "re".to_string();
+        query.budget.max_candidates = Some(10);
+        let outcome = engine.search_outcome(&query);
+        assert!(outcome.truncated);
+        assert_eq!(outcome.results.len(), 5);
+    }
+
+    #[test]
+    fn test_expired_time_budget_returns_partial_results() {
+        let (file_table, arena, index) = record_index(1_000);
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        let query = Query {
+            term: "record".to_string(),
+            limit: 5,
+            scope: None,
+            filter_scope: None,
+            budget: QueryBudget {
+                max_duration: Some(Duration::ZERO),
+                max_candidates: None,
+            },
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
+        };
+
+        let outcome = engine.search_outcome(&query);
+        assert!(outcome.truncated);
+        assert!(outcome.results.is_empty());
+
+        let file_ids: Vec<FileId> = file_table.iter().map(|(id, _)| id).collect();
+        let outcome = engine.search_file_ids_outcome(&query, &file_ids);
+        assert!(outcome.truncated);
+    }
+
     #[test]
     fn test_scoped_indexed_search_filters_before_effective_limit() {
         let mut file_table = FileTable::new();
@@ -941,17 +2184,22 @@ mod tests {
         for i in 0..(INDEXED_QUERY_CANDIDATE_LIMIT + 10) {
             let path = format!("/outside/site-packages/pkg_{i}/RECORD");
             let name = "RECORD";
-            let (path_off, path_len) = arena.add(&path);
+            let parent = file_table.intern_path(&path, &mut arena).0;
             let (name_off, name_len) = arena.add(name);
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1024,
                 mtime: i as i64,
                 dev: 0,
                 ino: i as u64,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
 
             let file_id = file_table.insert(meta);
@@ -959,17 +2207,22 @@ mod tests {
         }
 
         let inside_path = "/inside/notes/recording.md";
-        let (path_off, path_len) = arena.add(inside_path);
+        let parent = file_table.intern_path(inside_path, &mut arena).0;
         let (name_off, name_len) = arena.add("recording.md");
         let file_id = file_table.insert(FileMeta {
-            path_offset: path_off,
-            path_len,
+            parent,
             name_offset: name_off,
             name_len,
             size: 512,
             mtime: 99_999,
             dev: 0,
             ino: 99_999,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         });
         index.add(file_id, "recording.md");
 
@@ -979,6 +2232,15 @@ mod tests {
             limit: 10,
             scope: Some(PathBuf::from("/inside")),
             filter_scope: Some(PathBuf::from("/inside")),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         });
 
         assert_eq!(results.len(), 1);
@@ -992,47 +2254,62 @@ mod tests {
         let index = TrigramIndex::new();
 
         // Add a normal file
-        let (path_off1, path_len1) = arena.add("/home/user/test.txt");
+        let parent1 = file_table.intern_path("/home/user/test.txt", &mut arena).0;
         let (name_off1, name_len1) = arena.add("test.txt");
         let meta1 = FileMeta {
-            path_offset: path_off1,
-            path_len: path_len1,
+            parent: parent1,
             name_offset: name_off1,
             name_len: name_len1,
             size: 1024,
             mtime: 100,
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         };
         file_table.insert(meta1);
 
         // Add an entry with an empty name (simulating a root directory or corrupted entry)
-        let (path_off2, path_len2) = arena.add("/");
+        let parent2 = file_table.intern_path("/", &mut arena).0;
         let (name_off2, name_len2) = arena.add("");
         let meta2 = FileMeta {
-            path_offset: path_off2,
-            path_len: path_len2,
+            parent: parent2,
             name_offset: name_off2,
             name_len: name_len2,
             size: 0,
             mtime: 200, // More recent mtime
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         };
         file_table.insert(meta2);
 
         // Add another normal file
-        let (path_off3, path_len3) = arena.add("/home/user/other.rs");
+        let parent3 = file_table.intern_path("/home/user/other.rs", &mut arena).0;
         let (name_off3, name_len3) = arena.add("other.rs");
         let meta3 = FileMeta {
-            path_offset: path_off3,
-            path_len: path_len3,
+            parent: parent3,
             name_offset: name_off3,
             name_len: name_len3,
             size: 2048,
             mtime: 50,
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         };
         file_table.insert(meta3);
 
@@ -1060,17 +2337,22 @@ mod tests {
 
         for path in ["/repo-a/query.rs", "/repo-b/query.rs"] {
             let name = "query.rs";
-            let (path_off, path_len) = arena.add(path);
+            let parent = file_table.intern_path(path, &mut arena).0;
             let (name_off, name_len) = arena.add(name);
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 128,
                 mtime: 0,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
             let file_id = file_table.insert(meta);
             index.add(file_id, name);
@@ -1082,6 +2364,15 @@ mod tests {
             limit: 10,
             scope: Some(std::path::PathBuf::from("/repo-a")),
             filter_scope: Some(std::path::PathBuf::from("/repo-a")),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let results = engine.search(&query);
@@ -1089,6 +2380,138 @@ mod tests {
         assert_eq!(results[0].path, "/repo-a/query.rs");
     }
 
+    #[test]
+    fn max_depth_counts_levels_below_the_filter_scope_or_the_roots() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+
+        for path in ["/w/notes.md", "/w/a/notes.md", "/w/a/b/notes.md"] {
+            let name = "notes.md";
+            let parent = file_table.intern_path(path, &mut arena).0;
+            let (name_off, name_len) = arena.add(name);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset: name_off,
+                name_len,
+                size: 1,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, name);
+        }
+
+        let query = |filter_scope: Option<&str>, max_depth: usize| Query {
+            term: "notes".to_string(),
+            limit: 10,
+            scope: None,
+            filter_scope: filter_scope.map(PathBuf::from),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: false,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: Some(max_depth),
+            hide_hidden: false,
+        };
+        let paths = |engine: &QueryEngine, query: &Query| {
+            let mut paths: Vec<_> = engine.search(query).into_iter().map(|r| r.path).collect();
+            paths.sort();
+            paths
+        };
+
+        let engine = QueryEngine::new(&file_table, &arena, &index);
+        assert_eq!(paths(&engine, &query(Some("/w/a"), 1)), ["/w/a/notes.md"]);
+        assert_eq!(
+            paths(&engine, &query(Some("/w"), 2)),
+            ["/w/a/notes.md", "/w/notes.md"]
+        );
+        // Without roots, levels count from the filesystem root.
+        assert_eq!(paths(&engine, &query(None, 2)), ["/w/notes.md"]);
+
+        let roots = [PathBuf::from("/w/a"), PathBuf::from("/w")];
+        let engine = QueryEngine::new(&file_table, &arena, &index).with_roots(&roots);
+        // The deepest root holding a path is its base.
+        assert_eq!(
+            paths(&engine, &query(None, 1)),
+            ["/w/a/notes.md", "/w/notes.md"]
+        );
+    }
+
+    #[test]
+    fn hide_hidden_drops_dot_entries_below_the_scope_or_the_roots() {
+        let mut file_table = FileTable::new();
+        let mut arena = StringArena::new();
+        let mut index = TrigramIndex::new();
+
+        for path in [
+            "/home/.dots/notes.md",
+            "/home/.dots/.git/notes.md",
+            "/home/w/.notes.md",
+            "/home/w/notes.md",
+        ] {
+            let name = Path::new(path).file_name().unwrap().to_str().unwrap();
+            let parent = file_table.intern_path(path, &mut arena).0;
+            let (name_off, name_len) = arena.add(name);
+            let file_id = file_table.insert(FileMeta {
+                parent,
+                name_offset: name_off,
+                name_len,
+                size: 1,
+                mtime: 0,
+                dev: 0,
+                ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+            });
+            index.add(file_id, name);
+        }
+
+        let query = |filter_scope: Option<&str>, hide_hidden: bool| Query {
+            term: "notes".to_string(),
+            limit: 10,
+            scope: None,
+            filter_scope: filter_scope.map(PathBuf::from),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: false,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden,
+        };
+        let roots = [PathBuf::from("/home")];
+        let engine = QueryEngine::new(&file_table, &arena, &index).with_roots(&roots);
+        let paths = |query: &Query| {
+            let mut paths: Vec<_> = engine.search(query).into_iter().map(|r| r.path).collect();
+            paths.sort();
+            paths
+        };
+
+        assert_eq!(paths(&query(None, false)).len(), 4);
+        assert_eq!(paths(&query(None, true)), ["/home/w/notes.md"]);
+        // A hidden scope is not itself a reason to hide its entries.
+        assert_eq!(
+            paths(&query(Some("/home/.dots"), true)),
+            ["/home/.dots/notes.md"]
+        );
+    }
+
     #[test]
     fn test_filter_scope_matches_relative_indexed_paths_against_absolute_scope() {
         use std::sync::{Mutex, OnceLock};
@@ -1102,17 +2525,22 @@ mod tests {
 
         for path in ["workspace/repo-a/query.rs", "workspace/repo-b/query.rs"] {
             let name = "query.rs";
-            let (path_off, path_len) = arena.add(path);
+            let parent = file_table.intern_path(path, &mut arena).0;
             let (name_off, name_len) = arena.add(name);
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 128,
                 mtime: 0,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
             let file_id = file_table.insert(meta);
             index.add(file_id, name);
@@ -1137,6 +2565,15 @@ mod tests {
             limit: 10,
             scope: Some(resolved_cwd.join("workspace/repo-a")),
             filter_scope: Some(resolved_cwd.join("workspace/repo-a")),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let results = engine.search(&query);
@@ -1156,33 +2593,43 @@ mod tests {
         for i in 0..1200 {
             let path = format!("/outside/outside_{i}.txt");
             let name = format!("outside_{i}.txt");
-            let (path_off, path_len) = arena.add(&path);
+            let parent = file_table.intern_path(&path, &mut arena).0;
             let (name_off, name_len) = arena.add(&name);
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1,
                 mtime: 0,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
             let file_id = file_table.insert(meta);
             index.add(file_id, &name);
         }
 
-        let (path_off, path_len) = arena.add("/repo-a/src/qa.rs");
+        let parent = file_table.intern_path("/repo-a/src/qa.rs", &mut arena).0;
         let (name_off, name_len) = arena.add("qa.rs");
         let file_id = file_table.insert(FileMeta {
-            path_offset: path_off,
-            path_len,
+            parent,
             name_offset: name_off,
             name_len,
             size: 1,
             mtime: 1,
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         });
         index.add(file_id, "qa.rs");
 
@@ -1192,6 +2639,15 @@ mod tests {
             limit: 10,
             scope: Some(PathBuf::from("/repo-a")),
             filter_scope: Some(PathBuf::from("/repo-a")),
+            budget: Default::default(),
+            attributes: Default::default(),
+            fuzzy: true,
+            explain: false,
+            scorer: None,
+            directory_bias: 0,
+            min_score: 0.0,
+            max_depth: None,
+            hide_hidden: false,
         };
 
         let results = engine.search(&query);
@@ -1210,17 +2666,22 @@ mod tests {
             ("/repo-b/other.rs", "other.rs", 200),
             ("/repo-a/older.rs", "older.rs", 100),
         ] {
-            let (path_off, path_len) = arena.add(path);
+            let parent = file_table.intern_path(path, &mut arena).0;
             let (name_off, name_len) = arena.add(name);
             let meta = FileMeta {
-                path_offset: path_off,
-                path_len,
+                parent,
                 name_offset: name_off,
                 name_len,
                 size: 1,
                 mtime,
                 dev: 0,
                 ino: 0,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
             };
             file_table.insert(meta);
         }
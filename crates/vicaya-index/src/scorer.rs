@@ -0,0 +1,326 @@
+//! Ranking policies for the query engine.
+//!
+//! The engine finds candidates and measures how the term matched; a
+//! [`Scorer`] turns that into a score and decides the order. Queries pick a
+//! scorer, so views can rank the same matches differently.
+
+use crate::SearchResult;
+use std::cmp::Ordering;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use vicaya_core::config::{RankingBoost, RankingConfig};
+use vicaya_core::hooks::HookPattern;
+use vicaya_core::ipc::MatchStrategy;
+
+/// Name of the default scorer.
+pub const RELEVANCE_SCORER: &str = "relevance";
+/// Name of the built-in most-recently-modified-first scorer.
+pub const RECENCY_SCORER: &str = "recency";
+
+/// A candidate that matched the search term, as seen by a [`Scorer`].
+#[derive(Debug, Clone, Copy)]
+pub struct Candidate<'c> {
+    pub path: &'c str,
+    pub name: &'c str,
+    pub size: u64,
+    pub mtime: i64,
+    pub is_dir: bool,
+    /// How well the term matched (0.0 to 1.0).
+    pub match_score: f32,
+    pub strategy: MatchStrategy,
+    /// Demotion for dependency, cache, build, and tool-state directories (≤ 0).
+    pub context_penalty: i32,
+    /// Promotion for results under the boost scope, larger when shallower.
+    pub scope_boost: i32,
+    pub path_depth: usize,
+}
+
+/// A scored result and the features the engine ranks it by.
+#[derive(Debug, Clone, Copy)]
+pub struct Ranked<'r> {
+    pub result: &'r SearchResult,
+    /// `context_penalty + scope_boost`, plus the query's directory bias for
+    /// directories.
+    pub context_score: i32,
+    pub path_depth: usize,
+}
+
+/// Ranking policy for [`crate::QueryEngine`].
+pub trait Scorer: fmt::Debug + Send + Sync {
+    /// Name used to select the scorer (see [`ScorerRegistry`]).
+    fn name(&self) -> &str;
+
+    /// Score a matched candidate (0.0 to 1.0, higher is better), or drop it
+    /// with `None`.
+    fn score_candidate(&self, candidate: &Candidate<'_>) -> Option<f32>;
+
+    /// Order two scored results; `Ordering::Less` ranks `a` first.
+    fn compare(&self, a: &Ranked<'_>, b: &Ranked<'_>) -> Ordering;
+}
+
+/// Default ranking: match score, then context and scope, then newer
+/// `mtime`, then shallower paths, then path.
+///
+/// With [`Boost`]s, the match score of each candidate is multiplied by the
+/// factor of every boost that covers it first.
+#[derive(Debug, Clone, Default)]
+pub struct RelevanceScorer {
+    boosts: Vec<Boost>,
+}
+
+impl RelevanceScorer {
+    /// Relevance ranking with no boosts.
+    pub const fn new() -> Self {
+        Self { boosts: Vec::new() }
+    }
+
+    pub fn with_boosts(boosts: Vec<Boost>) -> Self {
+        Self { boosts }
+    }
+}
+
+impl Scorer for RelevanceScorer {
+    fn name(&self) -> &str {
+        RELEVANCE_SCORER
+    }
+
+    fn score_candidate(&self, candidate: &Candidate<'_>) -> Option<f32> {
+        let factor: f32 = self
+            .boosts
+            .iter()
+            .filter(|boost| boost.covers(candidate))
+            .map(|boost| boost.factor)
+            .product();
+        Some(candidate.match_score * factor)
+    }
+
+    fn compare(&self, a: &Ranked<'_>, b: &Ranked<'_>) -> Ordering {
+        b.result
+            .score
+            .partial_cmp(&a.result.score)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| b.context_score.cmp(&a.context_score))
+            .then_with(|| b.result.mtime.cmp(&a.result.mtime))
+            .then_with(|| a.path_depth.cmp(&b.path_depth))
+            .then_with(|| a.result.path.cmp(&b.result.path))
+    }
+}
+
+/// A score multiplier for candidates under a path or with an extension
+/// (`[ranking] boost`).
+#[derive(Debug, Clone)]
+pub struct Boost {
+    target: BoostTarget,
+    factor: f32,
+}
+
+#[derive(Debug, Clone)]
+enum BoostTarget {
+    Pattern(HookPattern),
+    /// Lowercase, without the dot.
+    Extension(String),
+}
+
+impl Boost {
+    /// Boost paths matching `pattern`, a glob over the full path or, without
+    /// a `/`, the file name (see [`HookPattern`]).
+    pub fn pattern(pattern: &str, factor: f32) -> vicaya_core::Result<Self> {
+        Ok(Self {
+            target: BoostTarget::Pattern(HookPattern::new(pattern)?),
+            factor,
+        })
+    }
+
+    /// Boost names ending in `.ext`, ignoring case.
+    pub fn extension(ext: &str, factor: f32) -> Self {
+        Self {
+            target: BoostTarget::Extension(ext.trim().trim_start_matches('.').to_lowercase()),
+            factor,
+        }
+    }
+
+    /// Compile a `[ranking] boost` entry; its pattern is already expanded.
+    pub fn from_config(config: &RankingBoost) -> vicaya_core::Result<Self> {
+        match (&config.pattern, &config.ext) {
+            (Some(pattern), None) => Self::pattern(pattern, config.factor),
+            (None, Some(ext)) => Ok(Self::extension(ext, config.factor)),
+            _ => Err(vicaya_core::Error::Config(
+                "ranking boost needs either `pattern` or `ext`".to_string(),
+            )),
+        }
+    }
+
+    fn covers(&self, candidate: &Candidate<'_>) -> bool {
+        match &self.target {
+            BoostTarget::Pattern(pattern) => pattern.matches(candidate.path),
+            BoostTarget::Extension(ext) => Path::new(candidate.name)
+                .extension()
+                .is_some_and(|candidate_ext| candidate_ext.eq_ignore_ascii_case(ext)),
+        }
+    }
+}
+
+/// Newest `mtime` first regardless of match quality; the match score only
+/// breaks ties.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RecencyScorer;
+
+impl Scorer for RecencyScorer {
+    fn name(&self) -> &str {
+        RECENCY_SCORER
+    }
+
+    fn score_candidate(&self, candidate: &Candidate<'_>) -> Option<f32> {
+        Some(candidate.match_score)
+    }
+
+    fn compare(&self, a: &Ranked<'_>, b: &Ranked<'_>) -> Ordering {
+        b.result
+            .mtime
+            .cmp(&a.result.mtime)
+            .then_with(|| {
+                b.result
+                    .score
+                    .partial_cmp(&a.result.score)
+                    .unwrap_or(Ordering::Equal)
+            })
+            .then_with(|| a.path_depth.cmp(&b.path_depth))
+            .then_with(|| a.result.path.cmp(&b.result.path))
+    }
+}
+
+/// Scorers selectable by name. Starts with the built-ins; callers may
+/// register more (or replace one) with [`ScorerRegistry::register`].
+#[derive(Debug, Clone)]
+pub struct ScorerRegistry {
+    scorers: Vec<Arc<dyn Scorer>>,
+}
+
+impl Default for ScorerRegistry {
+    fn default() -> Self {
+        Self {
+            scorers: vec![Arc::new(RelevanceScorer::new()), Arc::new(RecencyScorer)],
+        }
+    }
+}
+
+impl ScorerRegistry {
+    /// The built-ins, with `relevance` applying the configured boosts.
+    pub fn from_config(ranking: &RankingConfig) -> vicaya_core::Result<Self> {
+        let boosts = ranking
+            .boost
+            .iter()
+            .map(Boost::from_config)
+            .collect::<vicaya_core::Result<Vec<_>>>()?;
+        Ok(Self {
+            scorers: vec![
+                Arc::new(RelevanceScorer::with_boosts(boosts)),
+                Arc::new(RecencyScorer),
+            ],
+        })
+    }
+
+    /// Add `scorer`, replacing any registered under the same name.
+    pub fn register(&mut self, scorer: Arc<dyn Scorer>) {
+        self.scorers
+            .retain(|existing| existing.name() != scorer.name());
+        self.scorers.push(scorer);
+    }
+
+    /// The scorer registered as `name`.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Scorer>> {
+        self.scorers
+            .iter()
+            .find(|scorer| scorer.name() == name)
+            .cloned()
+    }
+
+    /// Registered scorer names, in registration order.
+    pub fn names(&self) -> Vec<&str> {
+        self.scorers.iter().map(|scorer| scorer.name()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    struct ShortestName;
+
+    impl Scorer for ShortestName {
+        fn name(&self) -> &str {
+            "shortest"
+        }
+
+        fn score_candidate(&self, candidate: &Candidate<'_>) -> Option<f32> {
+            (candidate.strategy != MatchStrategy::Path).then_some(1.0)
+        }
+
+        fn compare(&self, a: &Ranked<'_>, b: &Ranked<'_>) -> Ordering {
+            a.result.name.len().cmp(&b.result.name.len())
+        }
+    }
+
+    #[test]
+    fn registry_resolves_builtins_and_registered_scorers() {
+        let mut registry = ScorerRegistry::default();
+        assert_eq!(registry.names(), vec![RELEVANCE_SCORER, RECENCY_SCORER]);
+        assert!(registry.get("shortest").is_none());
+
+        registry.register(Arc::new(ShortestName));
+        registry.register(Arc::new(ShortestName));
+        assert_eq!(registry.names().len(), 3);
+        assert_eq!(registry.get("shortest").unwrap().name(), "shortest");
+    }
+
+    fn candidate(path: &'static str) -> Candidate<'static> {
+        Candidate {
+            path,
+            name: path.rsplit('/').next().unwrap(),
+            size: 0,
+            mtime: 0,
+            is_dir: false,
+            match_score: 0.5,
+            strategy: MatchStrategy::Substring,
+            context_penalty: 0,
+            scope_boost: 0,
+            path_depth: 3,
+        }
+    }
+
+    #[test]
+    fn configured_boosts_multiply_relevance_scores() {
+        let ranking = RankingConfig {
+            boost: vec![
+                RankingBoost {
+                    pattern: Some("/home/me/Projects/**".to_string()),
+                    ext: None,
+                    factor: 1.2,
+                },
+                RankingBoost {
+                    pattern: None,
+                    ext: Some(".PNG".to_string()),
+                    factor: 0.5,
+                },
+            ],
+            ..RankingConfig::default()
+        };
+        let registry = ScorerRegistry::from_config(&ranking).unwrap();
+        assert_eq!(registry.names(), vec![RELEVANCE_SCORER, RECENCY_SCORER]);
+        let relevance = registry.get(RELEVANCE_SCORER).unwrap();
+        let score = |path| relevance.score_candidate(&candidate(path)).unwrap();
+
+        assert_eq!(score("/home/me/notes/plan.md"), 0.5);
+        assert_eq!(score("/home/me/Projects/app/main.rs"), 0.6);
+        assert_eq!(score("/home/me/Desktop/shot.png"), 0.25);
+        assert_eq!(score("/home/me/Projects/app/logo.Png"), 0.3);
+
+        let unboosted = ScorerRegistry::default().get(RELEVANCE_SCORER).unwrap();
+        assert_eq!(
+            unboosted.score_candidate(&candidate("/home/me/Projects/a.rs")),
+            Some(0.5)
+        );
+    }
+}
@@ -1,9 +1,16 @@
 //! Trigram index for fast substring search.
 
-use crate::FileId;
+use crate::{FileId, Normalization};
 use hashbrown::HashMap;
 use serde::{Deserialize, Serialize};
 
+/// Posting lists at least this long may be left out of an intersection; see
+/// [`TrigramIndex::query_filtered_limited`].
+const UBIQUITOUS_MIN_POSTINGS: usize = 65_536;
+/// How many times longer than the rarest list a posting list must be before
+/// it is left out of an intersection.
+const UBIQUITOUS_RATIO: usize = 64;
+
 /// A trigram: 3 consecutive characters encoded as a u32.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Trigram(pub u32);
@@ -33,19 +40,32 @@ impl Trigram {
 pub struct TrigramIndex {
     /// Map from trigram to list of file IDs containing that trigram.
     index: HashMap<Trigram, Vec<FileId>>,
+    /// Folding applied to names before their trigrams are extracted.
+    normalization: Normalization,
 }
 
 impl TrigramIndex {
     /// Create a new empty trigram index.
     pub fn new() -> Self {
+        Self::with_normalization(Normalization::default())
+    }
+
+    /// Create an empty trigram index that folds names as `normalization` says.
+    pub fn with_normalization(normalization: Normalization) -> Self {
         Self {
             index: HashMap::new(),
+            normalization,
         }
     }
 
+    /// The folding names (and so queries against this index) go through.
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
     /// Add a file to the index with its trigrams.
     pub fn add(&mut self, file_id: FileId, text: &str) {
-        let trigrams = Trigram::extract(text);
+        let trigrams = Trigram::extract(&self.normalization.apply(text));
         // Deduplicate trigrams to avoid adding the same file multiple times
         let mut unique_trigrams: Vec<Trigram> = trigrams;
         unique_trigrams.sort_unstable();
@@ -76,7 +96,7 @@ impl TrigramIndex {
     /// This is much cheaper than `remove()` for incremental updates because it
     /// only touches posting lists the file could have been added to.
     pub fn remove_text(&mut self, file_id: FileId, text: &str) {
-        let mut trigrams = Trigram::extract(text);
+        let mut trigrams = Trigram::extract(&self.normalization.apply(text));
         trigrams.sort_unstable();
         trigrams.dedup();
 
@@ -96,7 +116,8 @@ impl TrigramIndex {
         }
     }
 
-    /// Query the index for files containing all given trigrams.
+    /// Query the index for files containing all given trigrams (bar
+    /// ubiquitous ones; see [`TrigramIndex::query_filtered_limited`]).
     pub fn query(&self, trigrams: &[Trigram]) -> Vec<FileId> {
         self.query_limited(trigrams, usize::MAX)
     }
@@ -109,6 +130,13 @@ impl TrigramIndex {
 
     /// Query the index for files containing all given trigrams, applying `accept`
     /// before counting a candidate against `max_results`.
+    ///
+    /// Posting lists are intersected rarest first, walking each longer list
+    /// with a galloping cursor. When the rarest list already fits within
+    /// `max_results`, lists that are both ubiquitous and far longer than it
+    /// are skipped: they barely narrow the candidates, and the extra false
+    /// positives cannot crowd out a true match. Candidates can therefore miss
+    /// a very common trigram, so callers verify them against the term.
     pub fn query_filtered_limited<F>(
         &self,
         trigrams: &[Trigram],
@@ -122,36 +150,68 @@ impl TrigramIndex {
             return Vec::new();
         }
 
-        if trigrams.is_empty() {
+        let selectivity = self.selectivity(trigrams);
+        let Some(&(rarest, rarest_len)) = selectivity.first() else {
+            return Vec::new();
+        };
+        if rarest_len == 0 {
             return Vec::new();
         }
 
+        let smallest = self.postings(rarest);
+        let skip_ubiquitous = smallest.len() <= max_results;
+        let mut rest: Vec<Gallop<'_>> = selectivity[1..]
+            .iter()
+            .filter(|&&(_, len)| {
+                !(skip_ubiquitous
+                    && len >= UBIQUITOUS_MIN_POSTINGS
+                    && len >= smallest.len().saturating_mul(UBIQUITOUS_RATIO))
+            })
+            .map(|&(trigram, _)| Gallop::new(self.postings(trigram)))
+            .collect();
+
+        let mut candidates = Vec::new();
+        for &file_id in smallest {
+            if rest.iter_mut().all(|list| list.seek(file_id)) && accept(file_id) {
+                candidates.push(file_id);
+                if candidates.len() >= max_results {
+                    break;
+                }
+            }
+        }
+        candidates
+    }
+
+    /// Posting-list length of each distinct trigram in `trigrams`, rarest
+    /// first. A length of 0 means no indexed name contains that trigram.
+    pub fn selectivity(&self, trigrams: &[Trigram]) -> Vec<(Trigram, usize)> {
         let mut unique_trigrams = trigrams.to_vec();
         unique_trigrams.sort_unstable();
         unique_trigrams.dedup();
 
-        let mut posting_lists: Vec<&Vec<FileId>> = Vec::with_capacity(unique_trigrams.len());
-        for trigram in &unique_trigrams {
-            let Some(list) = self.index.get(trigram) else {
-                return Vec::new();
-            };
-            posting_lists.push(list);
-        }
-        posting_lists.sort_unstable_by_key(|list| list.len());
-
-        let Some((smallest, rest)) = posting_lists.split_first() else {
-            return Vec::new();
-        };
+        let mut selectivity: Vec<(Trigram, usize)> = unique_trigrams
+            .into_iter()
+            .map(|trigram| (trigram, self.postings(trigram).len()))
+            .collect();
+        selectivity.sort_by_key(|&(trigram, len)| (len, trigram));
+        selectivity
+    }
 
-        // Filter candidates that contain all trigrams. Posting lists are kept sorted by FileId,
-        // so membership checks stay logarithmic even for very common filename trigrams.
-        smallest
+    /// The `n` trigrams with the longest posting lists, most common first.
+    pub fn most_common(&self, n: usize) -> Vec<Trigram> {
+        let mut trigrams: Vec<(usize, Trigram)> = self
+            .index
             .iter()
-            .filter(|&&file_id| rest.iter().all(|list| list.binary_search(&file_id).is_ok()))
-            .filter(|&&file_id| accept(file_id))
-            .take(max_results)
-            .copied()
-            .collect()
+            .map(|(&trigram, posting_list)| (posting_list.len(), trigram))
+            .collect();
+        trigrams.sort_unstable_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        trigrams.truncate(n);
+        trigrams.into_iter().map(|(_, trigram)| trigram).collect()
+    }
+
+    /// Posting list for one trigram (empty if the trigram is not indexed).
+    pub fn postings(&self, trigram: Trigram) -> &[FileId] {
+        self.index.get(&trigram).map(Vec::as_slice).unwrap_or(&[])
     }
 
     /// Number of unique trigrams in the index.
@@ -173,6 +233,43 @@ impl TrigramIndex {
     }
 }
 
+/// A cursor over a sorted posting list, probed with ascending file IDs.
+///
+/// Each probe gallops forward from the previous position (1, 2, 4, ...
+/// entries) and then binary searches the last step, so probes close together
+/// cost about as much as a merge step and far-apart ones stay logarithmic.
+struct Gallop<'a> {
+    list: &'a [FileId],
+    pos: usize,
+}
+
+impl<'a> Gallop<'a> {
+    fn new(list: &'a [FileId]) -> Self {
+        Self { list, pos: 0 }
+    }
+
+    /// Whether `file_id` is in the list. Probes must not decrease.
+    fn seek(&mut self, file_id: FileId) -> bool {
+        let rest = &self.list[self.pos..];
+        let mut bound = 1;
+        while bound < rest.len() && rest[bound] < file_id {
+            bound *= 2;
+        }
+        let low = bound / 2;
+        let high = (bound + 1).min(rest.len());
+        match rest[low..high].binary_search(&file_id) {
+            Ok(offset) => {
+                self.pos += low + offset;
+                true
+            }
+            Err(offset) => {
+                self.pos += low + offset;
+                false
+            }
+        }
+    }
+}
+
 impl Default for TrigramIndex {
     fn default() -> Self {
         Self::new()
@@ -218,6 +315,23 @@ mod tests {
         assert_eq!(results, vec![FileId(1), FileId(2), FileId(3)]);
     }
 
+    #[test]
+    fn most_common_orders_trigrams_by_posting_list_length() {
+        let mut index = TrigramIndex::new();
+        index.add(FileId(1), "abcd");
+        index.add(FileId(2), "abcx");
+        index.add(FileId(3), "zabc");
+
+        let abc = Trigram::from_bytes(b'a', b'b', b'c');
+        let top = index.most_common(2);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0], abc);
+        assert_eq!(index.postings(abc), &[FileId(1), FileId(2), FileId(3)]);
+        assert!(index
+            .postings(Trigram::from_bytes(b'q', b'q', b'q'))
+            .is_empty());
+    }
+
     #[test]
     fn query_limited_caps_common_posting_lists() {
         let mut index = TrigramIndex::new();
@@ -229,4 +343,53 @@ mod tests {
         let results = index.query_limited(&Trigram::extract("record"), 3);
         assert_eq!(results, vec![FileId(0), FileId(1), FileId(2)]);
     }
+
+    #[test]
+    fn galloping_matches_binary_search_for_ascending_probes() {
+        let list: Vec<FileId> = (0..2_000).map(|id| FileId(id * 3)).collect();
+        for step in [1, 2, 7, 64, 1_000] {
+            let mut cursor = Gallop::new(&list);
+            for id in (0..6_100).step_by(step) {
+                assert_eq!(
+                    cursor.seek(FileId(id)),
+                    list.binary_search(&FileId(id)).is_ok(),
+                    "id {id}, step {step}"
+                );
+            }
+        }
+        assert!(!Gallop::new(&[]).seek(FileId(0)));
+    }
+
+    #[test]
+    fn intersection_starts_rare_and_skips_ubiquitous_trigrams() {
+        let mut index = TrigramIndex::new();
+        for id in 0..(UBIQUITOUS_MIN_POSTINGS + 1_000) as u32 {
+            let name = match id {
+                5_000 => "rare",
+                id if id % 1_000 == 0 => "rare common",
+                _ => "common",
+            };
+            index.add(FileId(id), name);
+        }
+        let rar = Trigram::from_bytes(b'r', b'a', b'r');
+        let com = Trigram::from_bytes(b'c', b'o', b'm');
+        let zzz = Trigram::from_bytes(b'z', b'z', b'z');
+
+        assert_eq!(
+            index.selectivity(&[com, rar, rar]),
+            vec![(rar, 67), (com, UBIQUITOUS_MIN_POSTINGS + 999)]
+        );
+        assert!(index.query(&[com, zzz]).is_empty());
+
+        // "com" is nearly everywhere and 1000x longer than "rar": with room
+        // for every "rar" file it is not checked, so file 5000 slips through
+        // for the caller to reject.
+        let loose = index.query_limited(&[rar, com], 100);
+        assert_eq!(loose.len(), 67);
+        assert!(loose.contains(&FileId(5_000)));
+
+        let exact = index.query_limited(&[rar, com], 10);
+        assert_eq!(exact.len(), 10);
+        assert!(!exact.contains(&FileId(5_000)));
+    }
 }
@@ -29,18 +29,22 @@ pub fn build_snapshot(files: &[TestFile]) -> (FileTable, StringArena, TrigramInd
     let mut trigram_index = TrigramIndex::new();
 
     for file in files {
-        let (path_offset, path_len) = arena.add(file.path);
-        let (name_offset, name_len) = arena.add(file.name);
+        let (parent, name_offset, name_len) = file_table.intern_path(file.path, &mut arena);
 
         let meta = FileMeta {
-            path_offset,
-            path_len,
+            parent,
             name_offset,
             name_len,
             size: file.size,
             mtime: file.mtime,
             dev: 0,
             ino: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
         };
 
         let file_id = file_table.insert(meta);
@@ -289,5 +293,14 @@ pub fn run_query(
         limit,
         scope: scope.map(std::path::PathBuf::from),
         filter_scope: None,
+        budget: Default::default(),
+        attributes: Default::default(),
+        fuzzy: true,
+        explain: false,
+        scorer: None,
+        directory_bias: 0,
+        min_score: 0.0,
+        max_depth: None,
+        hide_hidden: false,
     })
 }
@@ -1,76 +1,326 @@
 //! vicaya-scanner: Parallel filesystem scanner.
 
 use ignore::gitignore::GitignoreBuilder;
-use std::path::Path;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tracing::{debug, info, warn};
-use vicaya_core::{Config, Result};
-use vicaya_index::{FileMeta, FileTable, StringArena, TrigramIndex};
+use vicaya_core::archive::{self, ArchiveFormat};
+use vicaya_core::config::{IndexProfile, ResolvedScan, ScanProfile};
+use vicaya_core::ipc::{IndexCap, RootCoverage};
+use vicaya_core::{paths, trash, volumes, Config, Result};
+use vicaya_index::{FileMeta, FileTable, Normalization, PathIndex, StringArena, TrigramIndex};
 
 /// Scanned file information.
+#[derive(Debug, Clone, Copy)]
 pub struct ScannedFile {
     pub size: u64,
     pub mtime: i64,
     pub dev: u64,
     pub ino: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    /// When the metadata was read (Unix timestamp).
+    pub indexed_at: i64,
+    /// Content fingerprint, when `[content_hash]` is enabled.
+    pub content_hash: Option<NonZeroU64>,
+    pub is_dir: bool,
 }
 
+/// Entries listed by [`Scanner::walk_subtree`].
+#[derive(Debug, Default)]
+pub struct SubtreeWalk {
+    pub paths: Vec<PathBuf>,
+    /// Whether the walk saw the whole subtree rather than stopping at its
+    /// limit.
+    pub complete: bool,
+}
+
+/// Walked entries whose metadata is read together, on the scan's threads.
+const SCAN_BATCH: usize = 512;
+
 /// Scanner for building the initial index.
 pub struct Scanner {
     config: Config,
+    settings: ResolvedScan,
+    /// Threads for metadata reads, built on first use; `None` reads on the
+    /// calling thread.
+    pool: OnceLock<Option<rayon::ThreadPool>>,
 }
 
 impl Scanner {
-    /// Create a new scanner with the given configuration.
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    /// Create a new scanner with the given configuration, running with the
+    /// `initial_scan` settings.
+    ///
+    /// Roots and exclusions are expanded (`~`, `$VAR`) here as well as in
+    /// `Config::load`, so hand-built configs behave the same.
+    pub fn new(mut config: Config) -> Self {
+        config.normalize_paths();
+        let settings = config.performance.scan_settings(ScanProfile::InitialScan);
+        Self {
+            config,
+            settings,
+            pool: OnceLock::new(),
+        }
+    }
+
+    /// Run with the threads and throttle of `profile` instead.
+    pub fn with_profile(mut self, profile: ScanProfile) -> Self {
+        self.settings = self.config.performance.scan_settings(profile);
+        self.pool = OnceLock::new();
+        self
+    }
+
+    /// The settings scans run with.
+    pub fn settings(&self) -> ResolvedScan {
+        self.settings
     }
 
     /// Scan all configured roots and build an index.
     pub fn scan(&self) -> Result<IndexSnapshot> {
-        info!("Starting filesystem scan");
+        self.scan_with_progress(|_| {})
+    }
 
-        let mut file_table = FileTable::new();
-        let mut string_arena = StringArena::new();
-        let mut trigram_index = TrigramIndex::new();
+    /// Scan all configured roots, reporting the running count of visited
+    /// entries (files and directories) to `on_progress`.
+    ///
+    /// When the roots hold more than `max_indexed_files` entries, they are
+    /// walked a second time keeping whole levels, earlier roots and shallower
+    /// paths first, and [`IndexSnapshot::cap`] reports what was left out.
+    pub fn scan_with_progress<F>(&self, mut on_progress: F) -> Result<IndexSnapshot>
+    where
+        F: FnMut(usize),
+    {
+        info!(
+            "Starting filesystem scan ({} profile, {} threads)",
+            self.settings.profile.as_str(),
+            self.settings.threads
+        );
 
-        for root in &self.config.index_roots {
-            info!("Scanning root: {}", root.display());
-            self.scan_root(root, &mut file_table, &mut string_arena, &mut trigram_index)?;
+        let roots = self.config.effective_roots();
+        if roots.len() < self.config.index_roots.len() {
+            info!(
+                "Skipping {} duplicate or nested index roots",
+                self.config.index_roots.len() - roots.len()
+            );
+        }
+
+        let limit = self.config.max_indexed_files;
+        let mut scanned_total = 0usize;
+        let mut admission = Admission::UpTo {
+            limit,
+            levels: vec![Vec::new(); roots.len()],
+            members_skipped: 0,
+        };
+        let mut snapshot =
+            self.scan_roots(&roots, &mut admission, &mut scanned_total, &mut on_progress)?;
+
+        let capped = if let Some(plans) = admission.over_cap() {
+            warn!(
+                "Index roots hold more than max_indexed_files = {}; rescanning shallowest first",
+                limit
+            );
+            let mut admission = Admission::Planned(plans);
+            snapshot =
+                self.scan_roots(&roots, &mut admission, &mut scanned_total, &mut on_progress)?;
+            Some(admission)
+        } else {
+            admission.skipped_members().then_some(admission)
+        };
+
+        if let Some(admission) = capped {
+            let cap = admission.report(&roots, limit);
+            for root in cap.roots.iter().filter(|root| root.skipped > 0) {
+                warn!(
+                    "Skipped {} entries under {} ({})",
+                    root.skipped,
+                    root.root,
+                    match root.complete_depth {
+                        Some(depth) => format!("complete to depth {depth}"),
+                        None => "root not indexed".to_string(),
+                    }
+                );
+            }
+            snapshot.cap = Some(cap);
         }
+        snapshot.set_path_index(self.config.index_directory_paths);
 
-        info!("Scan complete: {} files indexed", file_table.len());
+        info!("Scan complete: {} files indexed", snapshot.file_table.len());
 
-        Ok(IndexSnapshot {
-            file_table,
-            string_arena,
-            trigram_index,
-        })
+        Ok(snapshot)
+    }
+
+    /// Scan the effective root `root` alone into an index of its own,
+    /// reporting the running count of visited entries to `on_progress`.
+    ///
+    /// `max_indexed_files` is not applied: a cap is planned across every
+    /// root, so capped indexes are only built by [`Scanner::scan`].
+    pub fn scan_shard<F>(&self, root: &Path, mut on_progress: F) -> Result<IndexSnapshot>
+    where
+        F: FnMut(usize),
+    {
+        let mut admission = Admission::UpTo {
+            limit: 0,
+            levels: vec![Vec::new()],
+            members_skipped: 0,
+        };
+        let mut scanned_total = 0usize;
+        self.scan_roots(
+            &[root.to_path_buf()],
+            &mut admission,
+            &mut scanned_total,
+            &mut on_progress,
+        )
+    }
+
+    /// Entries below `dir`, a directory that arrived whole under an index
+    /// root (moved or copied in), in walk order. The walk prunes like a full
+    /// scan and stops at the root's `root_max_depth`; reading metadata is
+    /// left to the caller. At most `limit` entries are listed, with the
+    /// profile's `throttle_ms` pause after each batch of them.
+    pub fn walk_subtree(&self, dir: &Path, limit: usize) -> SubtreeWalk {
+        let mut walk = SubtreeWalk {
+            paths: Vec::new(),
+            complete: true,
+        };
+        let bundle = !self.config.index_bundle_contents
+            && dir
+                .file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(paths::is_bundle_name);
+        if bundle
+            || matching_index_root(&self.config, dir).is_none()
+            || !should_index_path(&self.config, dir, true)
+        {
+            return walk;
+        }
+        // Depth limits count from the outermost root, as in a full scan.
+        let remaining = self
+            .config
+            .index_roots
+            .iter()
+            .filter(|root| dir.starts_with(root))
+            .min_by_key(|root| root.components().count())
+            .and_then(|root| {
+                let max_depth = self.config.root_max_depth(root)?;
+                Some(max_depth.saturating_sub(vicaya_index::query::depth_below(dir, root)))
+            });
+        if remaining == Some(0) {
+            return walk;
+        }
+
+        let exclusions = self.config.exclusion_set();
+        let mut walker = self.walk_builder(dir);
+        walker.max_depth(remaining);
+        for entry in walker.build() {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let Some(file_type) = entry.file_type() else {
+                continue;
+            };
+            if entry.depth() == 0 || !(file_type.is_file() || file_type.is_dir()) {
+                continue;
+            }
+            if file_type.is_dir()
+                && (!self.config.index_profile.indexes_directories()
+                    || exclusions.has_negations() && exclusions.is_excluded(entry.path()))
+            {
+                continue;
+            }
+            if walk.paths.len() == limit {
+                walk.complete = false;
+                break;
+            }
+            walk.paths.push(entry.into_path());
+            if walk.paths.len().is_multiple_of(SCAN_BATCH) && self.settings.throttle_ms > 0 {
+                std::thread::sleep(std::time::Duration::from_millis(self.settings.throttle_ms));
+            }
+        }
+        walk
     }
 
-    /// Scan a single root directory.
+    fn thread_pool(&self) -> Option<&rayon::ThreadPool> {
+        self.pool
+            .get_or_init(|| {
+                if self.settings.threads <= 1 {
+                    return None;
+                }
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(self.settings.threads)
+                    .thread_name(|idx| format!("vicaya-scan-{idx}"))
+                    .build()
+                    .map_err(|e| warn!("Scanning on one thread: {}", e))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    fn scan_roots(
+        &self,
+        roots: &[PathBuf],
+        admission: &mut Admission,
+        scanned_total: &mut usize,
+        on_progress: &mut dyn FnMut(usize),
+    ) -> Result<IndexSnapshot> {
+        let mut index = IndexSnapshot {
+            file_table: FileTable::new(),
+            string_arena: StringArena::new(),
+            trigram_index: TrigramIndex::with_normalization(Normalization::from(
+                &self.config.normalization,
+            )),
+            path_index: None,
+            cap: None,
+        };
+
+        for (root_idx, root) in roots.iter().enumerate() {
+            if admission.skips_root(root_idx) {
+                info!("Skipping root (max_indexed_files): {}", root.display());
+                continue;
+            }
+            info!("Scanning root: {}", root.display());
+            self.scan_root(
+                root,
+                root_idx,
+                admission,
+                &mut index,
+                scanned_total,
+                on_progress,
+            )?;
+        }
+
+        Ok(index)
+    }
+
+    /// Scan a single root directory into `index`.
     fn scan_root(
         &self,
         root: &Path,
-        file_table: &mut FileTable,
-        string_arena: &mut StringArena,
-        trigram_index: &mut TrigramIndex,
+        root_idx: usize,
+        admission: &mut Admission,
+        index: &mut IndexSnapshot,
+        scanned_total: &mut usize,
+        on_progress: &mut dyn FnMut(usize),
     ) -> Result<()> {
         let mut scanned_entries = 0usize;
         let mut entry_errors = 0usize;
-        let exclusions = self.config.exclusions.clone();
-        let mut walker = ignore::WalkBuilder::new(root);
-        walker
-            .follow_links(false)
-            .hidden(false)
-            .ignore(self.config.respect_ignore_files)
-            .git_ignore(self.config.respect_ignore_files)
-            .git_global(false)
-            .git_exclude(self.config.respect_ignore_files)
-            .require_git(false)
-            .filter_entry(move |entry| {
-                vicaya_core::filter::should_index_path(entry.path(), &exclusions)
-            });
+        let exclusions = self.config.exclusion_set();
+        let mut walker = self.walk_builder(root);
+        let root_depth = self.config.root_max_depth(root);
+        if let Some(depth) = admission.max_depth(root_idx) {
+            // The entries kept from a partial level must not depend on
+            // directory listing order.
+            walker
+                .max_depth(Some(root_depth.map_or(depth, |limit| limit.min(depth))))
+                .sort_by_file_name(|a, b| a.cmp(b));
+        } else if root_depth.is_some() {
+            walker.max_depth(root_depth);
+        }
 
+        let mut batch: Vec<PathBuf> = Vec::with_capacity(SCAN_BATCH);
         for entry in walker.build() {
             let entry = match entry {
                 Ok(e) => e,
@@ -86,18 +336,46 @@ impl Scanner {
             if !(file_type.is_file() || file_type.is_dir()) {
                 continue;
             }
+            // Excluded directories are still walked when a negation could
+            // re-include something inside them; don't index them themselves.
+            if file_type.is_dir()
+                && exclusions.has_negations()
+                && exclusions.is_excluded(entry.path())
+            {
+                continue;
+            }
+            // Lite indexes walk directories without indexing them.
+            if file_type.is_dir() && !self.config.index_profile.indexes_directories() {
+                continue;
+            }
 
             scanned_entries += 1;
+            *scanned_total += 1;
+            on_progress(*scanned_total);
+            let indexed = index.file_table.len() + batch.len();
+            if !admission.admit(root_idx, entry.depth(), indexed) {
+                continue;
+            }
+            if !(file_type.is_file() && self.may_be_archive(entry.path())) {
+                batch.push(entry.into_path());
+                if batch.len() == SCAN_BATCH {
+                    self.flush_batch(&mut batch, index);
+                }
+                continue;
+            }
+            // Members are admitted right after their archive, so archives
+            // are indexed in walk order rather than batched.
+            self.flush_batch(&mut batch, index);
             if let Some(scanned) = self.scan_file(entry.path()) {
-                self.add_to_index(
-                    entry.path(),
-                    scanned,
-                    file_table,
-                    string_arena,
-                    trigram_index,
-                );
+                let archive = archive::indexable(&self.config.archives, entry.path(), scanned.size)
+                    .filter(|_| !matches!(admission, Admission::Planned(_)));
+                self.add_to_index(entry.path(), scanned, index);
+                if let Some(format) = archive {
+                    self.add_archive_members(entry.path(), format, scanned, admission, index);
+                }
             }
         }
+        self.flush_batch(&mut batch, index);
 
         if entry_errors > 0 {
             warn!(
@@ -112,6 +390,92 @@ impl Scanner {
         Ok(())
     }
 
+    /// A walk from `walk_root` that follows the index rules: ignore files
+    /// (when respected), exclusions, and no bundle contents, backup volumes
+    /// or Trash unless they are indexed.
+    fn walk_builder(&self, walk_root: &Path) -> ignore::WalkBuilder {
+        let walk_exclusions = self.config.exclusion_set();
+        let skip_bundles = !self.config.index_bundle_contents;
+        let skip_backups = !self.config.index_backup_volumes;
+        let skip_trash = !self.config.trash.index;
+        let walk_root = walk_root.to_path_buf();
+        let mut walker = ignore::WalkBuilder::new(&walk_root);
+        walker
+            .follow_links(false)
+            .hidden(false)
+            .ignore(self.config.respect_ignore_files)
+            .git_ignore(self.config.respect_ignore_files)
+            .git_global(false)
+            .git_exclude(self.config.respect_ignore_files)
+            .require_git(false);
+        walker.filter_entry(move |entry| {
+            // Bundles are indexed themselves, but not their contents.
+            if skip_bundles
+                && entry.path().parent().is_some_and(|parent| {
+                    parent != walk_root
+                        && parent
+                            .file_name()
+                            .and_then(|name| name.to_str())
+                            .is_some_and(paths::is_bundle_name)
+                })
+            {
+                return false;
+            }
+            if skip_backups
+                && entry.depth() > 0
+                && entry.file_type().is_some_and(|kind| kind.is_dir())
+                && volumes::is_backup_location(entry.path())
+            {
+                info!("Skipping backup volume {}", entry.path().display());
+                return false;
+            }
+            if skip_trash
+                && entry.depth() > 0
+                && entry.file_type().is_some_and(|kind| kind.is_dir())
+                && trash::is_trash_location(entry.path())
+            {
+                debug!("Skipping trash {}", entry.path().display());
+                return false;
+            }
+            if entry.file_type().is_some_and(|kind| kind.is_dir()) {
+                !walk_exclusions.prunes(entry.path())
+            } else {
+                !walk_exclusions.is_excluded(entry.path())
+            }
+        });
+        walker
+    }
+
+    /// Whether `path` is an archive whose members may be indexed.
+    fn may_be_archive(&self, path: &Path) -> bool {
+        self.config.archives.enabled
+            && ArchiveFormat::detect(path, &self.config.archives.extensions).is_some()
+    }
+
+    /// Read the metadata of the batched paths, on the scan's threads when it
+    /// has them, and index them in walk order. A full batch is followed by
+    /// the profile's `throttle_ms` pause.
+    fn flush_batch(&self, batch: &mut Vec<PathBuf>, index: &mut IndexSnapshot) {
+        if batch.is_empty() {
+            return;
+        }
+        let scanned: Vec<Option<ScannedFile>> = match self.thread_pool() {
+            Some(pool) => {
+                pool.install(|| batch.par_iter().map(|path| self.scan_file(path)).collect())
+            }
+            None => batch.iter().map(|path| self.scan_file(path)).collect(),
+        };
+        let full = batch.len() == SCAN_BATCH;
+        for (path, scanned) in batch.drain(..).zip(scanned) {
+            if let Some(scanned) = scanned {
+                self.add_to_index(&path, scanned, index);
+            }
+        }
+        if full && self.settings.throttle_ms > 0 {
+            std::thread::sleep(std::time::Duration::from_millis(self.settings.throttle_ms));
+        }
+    }
+
     /// Check if a path should be indexed.
     #[cfg(test)]
     fn should_index(&self, path: &Path) -> bool {
@@ -138,23 +502,27 @@ impl Scanner {
             .ok()?
             .as_secs() as i64;
 
+        let content_hash =
+            self.config
+                .content_hash
+                .compute(path, metadata.len(), metadata.is_dir());
+
         Some(ScannedFile {
             size: metadata.len(),
             mtime,
             dev: metadata.dev(),
             ino: metadata.ino(),
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            mode: metadata.mode(),
+            indexed_at: now_epoch_seconds(),
+            content_hash,
+            is_dir: metadata.is_dir(),
         })
     }
 
     /// Add a scanned file to the index structures.
-    fn add_to_index(
-        &self,
-        path: &Path,
-        file: ScannedFile,
-        file_table: &mut FileTable,
-        string_arena: &mut StringArena,
-        trigram_index: &mut TrigramIndex,
-    ) {
+    fn add_to_index(&self, path: &Path, file: ScannedFile, index: &mut IndexSnapshot) {
         let path_str = path.to_string_lossy();
         let name = path
             .file_name()
@@ -164,32 +532,391 @@ impl Scanner {
             return;
         }
 
-        let (path_offset, path_len) = string_arena.add(&path_str);
-        let (name_offset, name_len) = string_arena.add(&name);
+        let (parent, name_offset, name_len) = index
+            .file_table
+            .intern_path(&path_str, &mut index.string_arena);
 
+        let identity = self.config.index_profile.tracks_file_identity();
         let meta = FileMeta {
-            path_offset,
-            path_len,
+            parent,
             name_offset,
             name_len,
-            size: file.size,
+            size: if identity { file.size } else { 0 },
             mtime: file.mtime,
-            dev: file.dev,
-            ino: file.ino,
+            dev: if identity { file.dev } else { 0 },
+            ino: if identity { file.ino } else { 0 },
+            uid: file.uid,
+            gid: file.gid,
+            mode: file.mode,
+            indexed_at: file.indexed_at,
+            content_hash: file.content_hash,
+            is_dir: file.is_dir,
         };
 
-        let file_id = file_table.insert(meta);
-        trigram_index.add(file_id, &name);
+        let file_id = index.file_table.insert(meta);
+        index.trigram_index.add(file_id, &name);
+    }
+
+    /// Index the members of an archive as `<archive>!/<member>` entries.
+    ///
+    /// Members carry the archive's mtime, owner, mode, and `indexed_at` but no
+    /// inode or content hash, so the daemon never mistakes them for renames of real files. Parent directories missing
+    /// from the listing are added so members can be browsed like a tree.
+    fn add_archive_members(
+        &self,
+        path: &Path,
+        format: ArchiveFormat,
+        archive_file: ScannedFile,
+        admission: &mut Admission,
+        index: &mut IndexSnapshot,
+    ) {
+        let members = match archive::list_members(path, format) {
+            Ok(members) => members,
+            Err(e) => {
+                debug!("Skipping archive members: {}", e);
+                return;
+            }
+        };
+
+        let archive_path = path.to_string_lossy();
+        let mut dirs = HashSet::new();
+        let mut add = |member: &str, size: u64, is_dir: bool, index: &mut IndexSnapshot| {
+            if is_dir && !self.config.index_profile.indexes_directories() {
+                return;
+            }
+            if !admission.admit_member(index.file_table.len()) {
+                return;
+            }
+            let scanned = ScannedFile {
+                size,
+                dev: 0,
+                ino: 0,
+                content_hash: None,
+                is_dir,
+                ..archive_file
+            };
+            let virtual_path = archive::virtual_path(&archive_path, member);
+            self.add_to_index(Path::new(&virtual_path), scanned, index);
+        };
+        for member in &members {
+            let mut parents: Vec<&str> = member
+                .path
+                .match_indices('/')
+                .map(|(idx, _)| &member.path[..idx])
+                .collect();
+            if member.is_dir {
+                parents.push(&member.path);
+            }
+            for dir in parents {
+                if dirs.insert(dir.to_string()) {
+                    add(dir, 0, true, index);
+                }
+            }
+            if !member.is_dir {
+                add(&member.path, member.size, false, index);
+            }
+        }
+        debug!(
+            "Indexed {} archive members in {}",
+            members.len(),
+            path.display()
+        );
     }
 }
 
+/// Which walked entries `max_indexed_files` lets into the index.
+enum Admission {
+    /// First pass: admit entries while the index has room, counting every
+    /// walked entry per root and depth in case a capped pass is needed.
+    UpTo {
+        /// 0 means no cap.
+        limit: usize,
+        /// Walked entries per root, then per depth below it.
+        levels: Vec<Vec<usize>>,
+        /// Archive members left out for lack of room.
+        members_skipped: usize,
+    },
+    /// Capped pass planned from the first pass's counts.
+    Planned(Vec<LevelPlan>),
+}
+
+/// Which levels of one root a capped pass indexes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct LevelPlan {
+    /// Walked entries per depth in the first pass.
+    levels: Vec<usize>,
+    /// Depths below this one are indexed in full.
+    full: usize,
+    /// Entries taken from depth `full`, in file-name walk order.
+    partial: usize,
+    /// Entries taken from depth `full` so far.
+    taken: usize,
+}
+
+impl Admission {
+    /// Whether an entry at `depth` below root `root_idx` goes into an index
+    /// currently holding `indexed` entries.
+    fn admit(&mut self, root_idx: usize, depth: usize, indexed: usize) -> bool {
+        match self {
+            Self::UpTo { limit, levels, .. } => {
+                let levels = &mut levels[root_idx];
+                if levels.len() <= depth {
+                    levels.resize(depth + 1, 0);
+                }
+                levels[depth] += 1;
+                *limit == 0 || indexed < *limit
+            }
+            Self::Planned(plans) => {
+                let plan = &mut plans[root_idx];
+                if depth < plan.full {
+                    true
+                } else if depth == plan.full && plan.taken < plan.partial {
+                    plan.taken += 1;
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    /// Whether an archive member fits in an index holding `indexed` entries.
+    /// A capped pass indexes no members: walked entries take priority.
+    fn admit_member(&mut self, indexed: usize) -> bool {
+        match self {
+            Self::UpTo {
+                limit,
+                members_skipped,
+                ..
+            } => {
+                let fits = *limit == 0 || indexed < *limit;
+                if !fits {
+                    *members_skipped += 1;
+                }
+                fits
+            }
+            Self::Planned(_) => false,
+        }
+    }
+
+    /// Whether a capped pass leaves root `root_idx` out entirely.
+    fn skips_root(&self, root_idx: usize) -> bool {
+        matches!(self, Self::Planned(plans) if plans[root_idx].full == 0 && plans[root_idx].partial == 0)
+    }
+
+    /// The deepest level a capped pass walks below root `root_idx`.
+    fn max_depth(&self, root_idx: usize) -> Option<usize> {
+        let Self::Planned(plans) = self else {
+            return None;
+        };
+        let plan = &plans[root_idx];
+        if plan.full == plan.levels.len() {
+            return None;
+        }
+        if plan.partial > 0 {
+            Some(plan.full)
+        } else {
+            plan.full.checked_sub(1)
+        }
+    }
+
+    /// Plan a capped pass when the first pass walked more than the cap:
+    /// whole levels, earlier roots and shallower depths first, then part of
+    /// the first level that does not fit.
+    fn over_cap(&self) -> Option<Vec<LevelPlan>> {
+        let Self::UpTo { limit, levels, .. } = self else {
+            return None;
+        };
+        let walked: usize = levels.iter().flatten().sum();
+        if *limit == 0 || walked <= *limit {
+            return None;
+        }
+
+        let mut room = *limit;
+        let plans = levels
+            .iter()
+            .map(|levels| {
+                let mut plan = LevelPlan {
+                    levels: levels.clone(),
+                    ..LevelPlan::default()
+                };
+                for &count in levels {
+                    if count > room {
+                        plan.partial = room;
+                        room = 0;
+                        break;
+                    }
+                    room -= count;
+                    plan.full += 1;
+                }
+                plan
+            })
+            .collect();
+        Some(plans)
+    }
+
+    /// Whether the first pass left archive members out.
+    fn skipped_members(&self) -> bool {
+        matches!(self, Self::UpTo { members_skipped, .. } if *members_skipped > 0)
+    }
+
+    /// Describe what the cap left out of `roots`.
+    fn report(&self, roots: &[PathBuf], limit: usize) -> IndexCap {
+        let coverage = |root: &PathBuf, levels: &[usize], full: usize, partial: usize| {
+            let walked: usize = levels.iter().sum();
+            let indexed = levels[..full].iter().sum::<usize>() + partial;
+            RootCoverage {
+                root: root.to_string_lossy().to_string(),
+                indexed,
+                skipped: walked - indexed,
+                complete_depth: full.checked_sub(1),
+            }
+        };
+        let (roots, members_skipped): (Vec<RootCoverage>, usize) = match self {
+            Self::UpTo {
+                levels,
+                members_skipped,
+                ..
+            } => (
+                roots
+                    .iter()
+                    .zip(levels)
+                    .map(|(root, levels)| coverage(root, levels, levels.len(), 0))
+                    .collect(),
+                *members_skipped,
+            ),
+            Self::Planned(plans) => (
+                roots
+                    .iter()
+                    .zip(plans)
+                    .map(|(root, plan)| coverage(root, &plan.levels, plan.full, plan.partial))
+                    .collect(),
+                0,
+            ),
+        };
+        IndexCap {
+            max_indexed_files: limit,
+            skipped: members_skipped + roots.iter().map(|root| root.skipped).sum::<usize>(),
+            skipped_live: 0,
+            roots,
+        }
+    }
+}
+
+fn now_epoch_seconds() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
 /// Check if a path should be indexed under the same high-level rules used by
 /// the scanner. This is also used by the daemon for incremental watcher events.
 pub fn should_index_path(config: &Config, path: &Path, is_dir: bool) -> bool {
-    vicaya_core::filter::should_index_path(path, &config.exclusions)
+    !config.exclusion_set().is_excluded(path)
+        && enclosing_bundle(config, path).is_none()
+        && enclosing_backup(config, path).is_none()
+        && enclosing_trash(config, path).is_none()
+        && beyond_max_depth(config, path).is_none()
         && !is_ignored_by_repo_rules(config, path, is_dir)
 }
 
+/// Why a path is not part of the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExclusionReason {
+    /// The path is not under any configured index root.
+    OutsideRoots,
+    /// A configured `exclusions` pattern matches it.
+    Pattern(String),
+    /// It lives inside this bundle and `index_bundle_contents` is off.
+    InsideBundle(PathBuf),
+    /// It is, or lives inside, this backup or snapshot location and
+    /// `index_backup_volumes` is off.
+    BackupVolume(PathBuf),
+    /// It is, or lives inside, this Trash directory and `[trash] index` is
+    /// off.
+    Trash(PathBuf),
+    /// It lies deeper below this index root than its `root_max_depth`.
+    BeyondMaxDepth { root: PathBuf, max_depth: usize },
+    /// A `.gitignore`, `.ignore`, or `.git/info/exclude` rule ignores it.
+    IgnoreFile,
+}
+
+/// Explain why the scanner would skip `path`, or `None` if it would index it.
+pub fn exclusion_reason(config: &Config, path: &Path, is_dir: bool) -> Option<ExclusionReason> {
+    if matching_index_root(config, path).is_none() {
+        return Some(ExclusionReason::OutsideRoots);
+    }
+    if let Some(pattern) = config.exclusion_set().matching(path) {
+        return Some(ExclusionReason::Pattern(pattern.to_string()));
+    }
+    if let Some(bundle) = enclosing_bundle(config, path) {
+        return Some(ExclusionReason::InsideBundle(bundle.to_path_buf()));
+    }
+    if let Some(backup) = enclosing_backup(config, path) {
+        return Some(ExclusionReason::BackupVolume(backup.to_path_buf()));
+    }
+    if let Some(trash) = enclosing_trash(config, path) {
+        return Some(ExclusionReason::Trash(trash.to_path_buf()));
+    }
+    if let Some((root, max_depth)) = beyond_max_depth(config, path) {
+        return Some(ExclusionReason::BeyondMaxDepth {
+            root: root.to_path_buf(),
+            max_depth,
+        });
+    }
+    if is_ignored_by_repo_rules(config, path, is_dir) {
+        return Some(ExclusionReason::IgnoreFile);
+    }
+    None
+}
+
+/// The bundle below `path`'s index root that hides it, unless bundle
+/// contents are indexed.
+fn enclosing_bundle<'a>(config: &Config, path: &'a Path) -> Option<&'a Path> {
+    if config.index_bundle_contents {
+        return None;
+    }
+    let root = matching_index_root(config, path)?;
+    paths::bundle_below(path, root)
+}
+
+/// The backup or snapshot location below `path`'s index root that hides it,
+/// unless backup volumes are indexed.
+fn enclosing_backup<'a>(config: &Config, path: &'a Path) -> Option<&'a Path> {
+    if config.index_backup_volumes {
+        return None;
+    }
+    let root = matching_index_root(config, path)?;
+    volumes::backup_below(path, root)
+}
+
+/// The Trash directory below `path`'s index root that hides it, unless the
+/// Trash is indexed.
+fn enclosing_trash<'a>(config: &Config, path: &'a Path) -> Option<&'a Path> {
+    if config.trash.index {
+        return None;
+    }
+    let root = matching_index_root(config, path)?;
+    trash::trash_below(path, root)
+}
+
+/// `path`'s index root and its `root_max_depth`, when `path` lies deeper
+/// than that. The outermost root counts, since nested roots are walked as
+/// part of it.
+fn beyond_max_depth<'a>(config: &'a Config, path: &Path) -> Option<(&'a Path, usize)> {
+    if config.root_max_depth.is_empty() {
+        return None;
+    }
+    let root = config
+        .index_roots
+        .iter()
+        .filter(|root| path.starts_with(root))
+        .min_by_key(|root| root.components().count())?;
+    let max_depth = config.root_max_depth(root)?;
+    (vicaya_index::query::depth_below(path, root) > max_depth).then_some((root, max_depth))
+}
+
 fn is_ignored_by_repo_rules(config: &Config, path: &Path, is_dir: bool) -> bool {
     if !config.respect_ignore_files {
         return false;
@@ -276,38 +1003,138 @@ fn apply_ignore_file(
     }
 }
 
+/// Leading bytes of `index.bin`, followed by a little-endian format version.
+const INDEX_MAGIC: &[u8; 4] = b"VCYA";
+
+/// Bumped whenever the serialized layout changes; older files are rebuilt.
+/// Version 2 stores parent directory IDs instead of full paths; version 3
+/// adds owner and mode bits; version 4 adds per-entry `indexed_at`;
+/// version 6 records the name normalization the trigrams were built with;
+/// version 7 adds per-entry `content_hash`.
+const INDEX_FORMAT_VERSION: u32 = 7;
+
 /// Snapshot of the index at a point in time.
 pub struct IndexSnapshot {
     pub file_table: FileTable,
     pub string_arena: StringArena,
     pub trigram_index: TrigramIndex,
+    /// Directory-path trigrams when `index_directory_paths` is on. Derived
+    /// from the file table, so not persisted; see [`IndexSnapshot::set_path_index`].
+    pub path_index: Option<PathIndex>,
+    /// What `max_indexed_files` left out of the scan that built this
+    /// snapshot. Not persisted; `None` for loaded snapshots.
+    pub cap: Option<IndexCap>,
 }
 
 impl IndexSnapshot {
     /// Save the snapshot to disk.
+    ///
+    /// Writes to a sibling temp file and renames it into place, so readers
+    /// never observe a partially written index.
     pub fn save(&self, path: &Path) -> Result<()> {
         use std::io::{BufWriter, Write};
 
-        let file = std::fs::File::create(path)?;
-        let mut writer = BufWriter::new(file);
+        let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+        tmp_name.push(".tmp");
+        let tmp_path = path.with_file_name(tmp_name);
 
-        bincode::serialize_into(
-            &mut writer,
-            &(&self.file_table, &self.string_arena, &self.trigram_index),
-        )
-        .map_err(|e| vicaya_core::Error::Serialization(e.to_string()))?;
+        let write = || -> Result<()> {
+            let file = std::fs::File::create(&tmp_path)?;
+            let mut writer = BufWriter::new(file);
+            writer.write_all(INDEX_MAGIC)?;
+            writer.write_all(&INDEX_FORMAT_VERSION.to_le_bytes())?;
+
+            bincode::serialize_into(
+                &mut writer,
+                &(&self.file_table, &self.string_arena, &self.trigram_index),
+            )
+            .map_err(|e| vicaya_core::Error::Serialization(e.to_string()))?;
+
+            writer.flush()?;
+            std::fs::rename(&tmp_path, path)?;
+            Ok(())
+        };
+        if let Err(e) = write() {
+            let _ = std::fs::remove_file(&tmp_path);
+            return Err(e);
+        }
 
-        writer.flush()?;
         info!("Index snapshot saved to {}", path.display());
         Ok(())
     }
 
+    /// Re-derive the trigram index when it was built with a different
+    /// `normalization`. Returns whether anything changed.
+    pub fn set_normalization(&mut self, normalization: Normalization) -> bool {
+        if self.trigram_index.normalization() == normalization {
+            return false;
+        }
+        let mut trigram_index = TrigramIndex::with_normalization(normalization);
+        for (file_id, meta) in self.file_table.iter() {
+            if let Some(name) = self.string_arena.get(meta.name_offset, meta.name_len) {
+                trigram_index.add(file_id, name);
+            }
+        }
+        self.trigram_index = trigram_index;
+        if self.path_index.is_some() {
+            self.path_index = Some(self.build_path_index());
+        }
+        true
+    }
+
+    /// Build or drop the directory-path index. Returns whether anything
+    /// changed.
+    pub fn set_path_index(&mut self, enabled: bool) -> bool {
+        if self.path_index.is_some() == enabled {
+            return false;
+        }
+        self.path_index = enabled.then(|| self.build_path_index());
+        true
+    }
+
+    /// Whether this snapshot was built with `profile`. Lite snapshots hold
+    /// no directories or inodes, which a full scan of any root always has.
+    pub fn fits_profile(&self, profile: IndexProfile) -> bool {
+        let lite = self
+            .file_table
+            .iter()
+            .all(|(_, meta)| meta.is_tombstone() || !(meta.is_dir || meta.ino != 0));
+        lite == (profile == IndexProfile::Lite)
+    }
+
+    fn build_path_index(&self) -> PathIndex {
+        PathIndex::build(
+            &self.file_table,
+            &self.string_arena,
+            self.trigram_index.normalization(),
+        )
+    }
+
     /// Load a snapshot from disk.
+    ///
+    /// Fails with [`vicaya_core::Error::Serialization`] when the file was
+    /// written by an incompatible version, so callers can rebuild it.
     pub fn load(path: &Path) -> Result<Self> {
-        use std::io::BufReader;
+        use std::io::{BufReader, Read};
 
         let file = std::fs::File::open(path)?;
-        let reader = BufReader::new(file);
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u8; 8];
+        let version = match reader.read_exact(&mut header) {
+            Ok(()) if &header[..4] == INDEX_MAGIC => Some(u32::from_le_bytes([
+                header[4], header[5], header[6], header[7],
+            ])),
+            _ => None,
+        };
+        if version != Some(INDEX_FORMAT_VERSION) {
+            return Err(vicaya_core::Error::Serialization(format!(
+                "{} uses index format {}, expected {}",
+                path.display(),
+                version.map_or_else(|| "1".to_string(), |v| v.to_string()),
+                INDEX_FORMAT_VERSION
+            )));
+        }
 
         let (file_table, string_arena, trigram_index) = bincode::deserialize_from(reader)
             .map_err(|e| vicaya_core::Error::Serialization(e.to_string()))?;
@@ -317,6 +1144,8 @@ impl IndexSnapshot {
             file_table,
             string_arena,
             trigram_index,
+            path_index: None,
+            cap: None,
         })
     }
 }
@@ -339,14 +1168,48 @@ mod tests {
             index_roots: vec![root.to_path_buf()],
             exclusions: Vec::new(),
             respect_ignore_files,
+            case_insensitive_exclusions: false,
+            exclusion_cache: Default::default(),
             index_path: root.join(".vicaya-index"),
             max_memory_mb: 128,
+            max_indexed_files: 0,
             performance: vicaya_core::config::PerformanceConfig {
                 scanner_threads: 2,
                 reconcile_hour: 3,
+                query_timeout_ms: 250,
+                query_max_candidates: 0,
+                warmup_trigrams: 0,
+                reconcile_schedule: Vec::new(),
+                reconcile_min_gap_minutes: 60,
+                reconcile_on_wake: true,
+                initial_scan: Default::default(),
+                reconcile: Default::default(),
             },
             smriti: vicaya_core::config::SmritiConfig::default(),
             content_search: vicaya_core::config::ContentSearchConfig::default(),
+            preview: vicaya_core::config::PreviewConfig::default(),
+            associations: Default::default(),
+            aliases: Default::default(),
+            http: vicaya_core::config::HttpConfig::default(),
+            journal: vicaya_core::config::JournalConfig::default(),
+            telemetry: vicaya_core::config::TelemetryConfig::default(),
+            ranking: vicaya_core::config::RankingConfig::default(),
+            trash: vicaya_core::config::TrashConfig::default(),
+            normalization: Default::default(),
+            hooks: Vec::new(),
+            root_max_depth: Default::default(),
+            show_hidden: true,
+            restore_tui_session: false,
+            offline_while_starting: false,
+            auto_scope: vicaya_core::config::AutoScope::default(),
+            icons: vicaya_core::config::IconStyle::default(),
+            language: Default::default(),
+            archives: vicaya_core::config::ArchiveConfig::default(),
+            content_hash: vicaya_core::config::ContentHashConfig::default(),
+            index_bundle_contents: false,
+            index_backup_volumes: false,
+            index_directory_paths: false,
+            index_profile: Default::default(),
         }
     }
 
@@ -386,6 +1249,592 @@ mod tests {
         assert!(!names.contains(&"app.log".to_string()));
     }
 
+    #[test]
+    fn scan_profiles_index_the_same_entries_in_the_same_order() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in 0..3 {
+            let dir = root.path().join(format!("d{dir}"));
+            std::fs::create_dir(&dir).unwrap();
+            for file in 0..(SCAN_BATCH / 2) {
+                std::fs::write(dir.join(format!("f{file}.txt")), "").unwrap();
+            }
+        }
+
+        let mut config = test_config(root.path(), true);
+        config.performance.initial_scan.threads = Some(1);
+        config.performance.reconcile = vicaya_core::config::ScanSettings {
+            threads: Some(4),
+            throttle_ms: 1,
+        };
+        let sequential = Scanner::new(config.clone());
+        assert_eq!(sequential.settings().threads, 1);
+        let parallel = Scanner::new(config).with_profile(ScanProfile::Reconcile);
+        assert_eq!(parallel.settings().threads, 4);
+        assert_eq!(parallel.settings().throttle_ms, 1);
+
+        let sequential = indexed_names(&sequential.scan().unwrap());
+        // The files plus the root and its three directories.
+        assert_eq!(sequential.len(), 3 * (SCAN_BATCH / 2) + 4);
+        assert_eq!(indexed_names(&parallel.scan().unwrap()), sequential);
+    }
+
+    #[test]
+    fn scan_fingerprints_files_only_when_enabled() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("a.txt"), "same").unwrap();
+        std::fs::write(root.path().join("b.txt"), "same").unwrap();
+        std::fs::write(root.path().join("c.txt"), "different").unwrap();
+        std::fs::create_dir(root.path().join("dir")).unwrap();
+
+        let hashes = |content_hash: vicaya_core::config::ContentHashConfig| {
+            let config = Config {
+                content_hash,
+                ..test_config(root.path(), true)
+            };
+            let snapshot = Scanner::new(config).scan().unwrap();
+            snapshot
+                .file_table
+                .iter()
+                .filter_map(|(_, meta)| {
+                    let name = snapshot.string_arena.get(meta.name_offset, meta.name_len)?;
+                    Some((name.to_string(), meta.content_hash))
+                })
+                .collect::<std::collections::HashMap<_, _>>()
+        };
+
+        let off = hashes(Default::default());
+        assert!(off.values().all(Option::is_none));
+
+        let on = hashes(vicaya_core::config::ContentHashConfig {
+            enabled: true,
+            ..Default::default()
+        });
+        assert!(on["a.txt"].is_some());
+        assert_eq!(on["a.txt"], on["b.txt"]);
+        assert_ne!(on["a.txt"], on["c.txt"]);
+        assert_eq!(on["dir"], None);
+
+        let capped = hashes(vicaya_core::config::ContentHashConfig {
+            enabled: true,
+            max_file_mb: 0,
+            ..Default::default()
+        });
+        assert_eq!(capped["a.txt"], None);
+    }
+
+    #[test]
+    fn scan_matches_mixed_case_components_when_case_insensitive() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["Build", "build", "src"] {
+            std::fs::create_dir(root.path().join(dir)).unwrap();
+        }
+        std::fs::write(root.path().join("Build/upper.o"), "").unwrap();
+        std::fs::write(root.path().join("build/lower.o"), "").unwrap();
+        std::fs::write(root.path().join("src/Notes.TMP"), "").unwrap();
+        std::fs::write(root.path().join("src/main.rs"), "").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        config.exclusions = vec!["build".to_string(), "*.tmp".to_string()];
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"upper.o".to_string()));
+        assert!(names.contains(&"Notes.TMP".to_string()));
+        assert!(!names.contains(&"lower.o".to_string()));
+
+        config.case_insensitive_exclusions = true;
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"main.rs".to_string()));
+        assert!(!names.contains(&"upper.o".to_string()));
+        assert!(!names.contains(&"lower.o".to_string()));
+        assert!(!names.contains(&"Notes.TMP".to_string()));
+        assert_eq!(
+            exclusion_reason(&config, &root.path().join("BUILD/x.o"), false),
+            Some(ExclusionReason::Pattern("build".to_string()))
+        );
+    }
+
+    #[test]
+    fn scan_and_explain_honour_negated_exclusions() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["target/doc/api", "target/debug"] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        std::fs::write(root.path().join("target/doc/api/index.html"), "").unwrap();
+        std::fs::write(root.path().join("target/debug/app"), "").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        config.exclusions = vec!["target".to_string(), "!target/doc".to_string()];
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+
+        assert!(names.contains(&"index.html".to_string()));
+        assert!(names.contains(&"doc".to_string()));
+        assert!(!names.contains(&"app".to_string()));
+        assert!(!names.contains(&"target".to_string()));
+        assert!(!names.contains(&"debug".to_string()));
+
+        let doc = root.path().join("target/doc/api/index.html");
+        assert!(should_index_path(&config, &doc, false));
+        assert_eq!(exclusion_reason(&config, &doc, false), None);
+        assert_eq!(
+            exclusion_reason(&config, &root.path().join("target/debug/app"), false),
+            Some(ExclusionReason::Pattern("target".to_string()))
+        );
+    }
+
+    #[test]
+    fn overlapping_roots_are_scanned_once() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("nested")).unwrap();
+        std::fs::write(root.path().join("nested/once.rs"), "").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        config.index_roots = vec![
+            root.path().join("nested"),
+            root.path().to_path_buf(),
+            root.path().to_path_buf(),
+        ];
+        let names = indexed_names(&Scanner::new(config).scan().unwrap());
+
+        assert_eq!(names.iter().filter(|name| *name == "once.rs").count(), 1);
+        assert_eq!(names.iter().filter(|name| *name == "nested").count(), 1);
+    }
+
+    #[test]
+    fn max_indexed_files_keeps_earlier_roots_and_shallower_paths() {
+        let one = tempfile::tempdir().unwrap();
+        let two = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(one.path().join("sub/deep")).unwrap();
+        for file in ["a.txt", "b.txt", "sub/c.txt", "sub/deep/d.txt"] {
+            std::fs::write(one.path().join(file), "").unwrap();
+        }
+        std::fs::write(two.path().join("e.txt"), "").unwrap();
+
+        let mut config = test_config(one.path(), false);
+        config.index_roots = vec![one.path().to_path_buf(), two.path().to_path_buf()];
+        config.max_indexed_files = 5;
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+
+        let mut names = indexed_names(&snapshot);
+        names.retain(|name| !name.starts_with(".tmp"));
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "b.txt", "c.txt", "sub"]);
+        assert_eq!(snapshot.file_table.len(), 5);
+
+        let cap = snapshot.cap.unwrap();
+        assert_eq!(cap.max_indexed_files, 5);
+        assert_eq!(cap.skipped, 4);
+        assert_eq!(
+            cap.roots
+                .iter()
+                .map(|root| (root.indexed, root.skipped, root.complete_depth))
+                .collect::<Vec<_>>(),
+            vec![(5, 2, Some(1)), (0, 2, None)]
+        );
+
+        config.max_indexed_files = 9;
+        assert!(Scanner::new(config).scan().unwrap().cap.is_none());
+    }
+
+    #[test]
+    fn exclusion_reason_names_the_responsible_rule() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join(".gitignore"), "*.log\n").unwrap();
+        let mut config = test_config(root.path(), true);
+        config.exclusions = vec!["node_modules".to_string(), "*.tmp".to_string()];
+
+        assert_eq!(
+            exclusion_reason(&config, &root.path().join("node_modules/a.js"), false),
+            Some(ExclusionReason::Pattern("node_modules".to_string()))
+        );
+        assert_eq!(
+            exclusion_reason(&config, &root.path().join("build/x.tmp"), false),
+            Some(ExclusionReason::Pattern("*.tmp".to_string()))
+        );
+        assert_eq!(
+            exclusion_reason(&config, &root.path().join("app.log"), false),
+            Some(ExclusionReason::IgnoreFile)
+        );
+        assert_eq!(
+            exclusion_reason(&config, Path::new("/elsewhere/file.rs"), false),
+            Some(ExclusionReason::OutsideRoots)
+        );
+        assert_eq!(
+            exclusion_reason(&config, &root.path().join("src/main.rs"), false),
+            None
+        );
+    }
+
+    #[test]
+    fn root_max_depth_stops_the_walk_and_rejects_deeper_updates() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("a/b")).unwrap();
+        std::fs::write(root.path().join("top.md"), "x").unwrap();
+        std::fs::write(root.path().join("a/mid.md"), "x").unwrap();
+        std::fs::write(root.path().join("a/b/deep.md"), "x").unwrap();
+        let mut config = test_config(root.path(), false);
+        config.root_max_depth.insert(root.path().to_path_buf(), 2);
+
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let names: Vec<_> = snapshot
+            .file_table
+            .iter()
+            .map(|(_, meta)| snapshot.string_arena.get(meta.name_offset, meta.name_len))
+            .collect();
+        assert!(names.contains(&Some("mid.md")));
+        assert!(!names.contains(&Some("deep.md")));
+
+        let deep = root.path().join("a/b/deep.md");
+        assert!(!should_index_path(&config, &deep, false));
+        assert_eq!(
+            exclusion_reason(&config, &deep, false),
+            Some(ExclusionReason::BeyondMaxDepth {
+                root: root.path().to_path_buf(),
+                max_depth: 2,
+            })
+        );
+        assert!(should_index_path(
+            &config,
+            &root.path().join("a/mid.md"),
+            false
+        ));
+    }
+
+    #[test]
+    fn walk_subtree_lists_what_a_scan_would_index_up_to_its_limit() {
+        let root = tempfile::tempdir().unwrap();
+        let moved = root.path().join("moved");
+        for dir in ["src/deep", "target/debug"] {
+            std::fs::create_dir_all(moved.join(dir)).unwrap();
+        }
+        for file in [
+            "README.md",
+            "src/lib.rs",
+            "src/deep/far.rs",
+            "target/debug/app",
+        ] {
+            std::fs::write(moved.join(file), "x").unwrap();
+        }
+        let mut config = test_config(root.path(), false);
+        config.exclusions = vec!["target".to_string()];
+        config.root_max_depth.insert(root.path().to_path_buf(), 3);
+        let scanner = Scanner::new(config);
+
+        let walk = scanner.walk_subtree(&moved, 100);
+        assert!(walk.complete);
+        let mut listed: Vec<_> = walk
+            .paths
+            .iter()
+            .map(|path| path.strip_prefix(&moved).unwrap().to_path_buf())
+            .collect();
+        listed.sort();
+        assert_eq!(
+            listed,
+            ["README.md", "src", "src/deep", "src/lib.rs"].map(PathBuf::from)
+        );
+
+        let walk = scanner.walk_subtree(&moved, 2);
+        assert!(!walk.complete);
+        assert_eq!(walk.paths.len(), 2);
+
+        assert!(scanner
+            .walk_subtree(&moved.join("target"), 100)
+            .paths
+            .is_empty());
+        let outside = tempfile::tempdir().unwrap();
+        assert!(scanner.walk_subtree(outside.path(), 100).paths.is_empty());
+    }
+
+    #[test]
+    fn save_replaces_existing_index_without_leaving_temp_file() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("keep.rs"), "").unwrap();
+        let index_path = root.path().join("index.bin");
+        std::fs::write(&index_path, "stale").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        config.exclusions = vec!["index.bin".to_string()];
+        let snapshot = Scanner::new(config).scan().unwrap();
+        snapshot.save(&index_path).unwrap();
+
+        assert!(!root.path().join("index.bin.tmp").exists());
+        let loaded = IndexSnapshot::load(&index_path).unwrap();
+        assert_eq!(indexed_names(&loaded), indexed_names(&snapshot));
+    }
+
+    #[test]
+    fn bundles_are_indexed_without_their_contents_by_default() {
+        let root = tempfile::tempdir().unwrap();
+        let contents = root.path().join("apps/Tool.app/Contents");
+        std::fs::create_dir_all(&contents).unwrap();
+        std::fs::write(contents.join("Info.plist"), "").unwrap();
+        std::fs::write(root.path().join("apps/readme.txt"), "").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"Tool.app".to_string()));
+        assert!(names.contains(&"readme.txt".to_string()));
+        assert!(!names.contains(&"Contents".to_string()));
+        assert!(!names.contains(&"Info.plist".to_string()));
+
+        let plist = contents.join("Info.plist");
+        assert!(!should_index_path(&config, &plist, false));
+        assert_eq!(
+            exclusion_reason(&config, &plist, false),
+            Some(ExclusionReason::InsideBundle(
+                root.path().join("apps/Tool.app")
+            ))
+        );
+
+        // A bundle given as an index root is always descended into.
+        config.index_roots = vec![root.path().join("apps/Tool.app")];
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"Info.plist".to_string()));
+
+        config.index_roots = vec![root.path().to_path_buf()];
+        config.index_bundle_contents = true;
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"Info.plist".to_string()));
+        assert!(should_index_path(&config, &plist, false));
+    }
+
+    #[test]
+    fn backup_volumes_are_skipped_unless_opted_in() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in [
+            "TM/Backups.backupdb/mac/Latest",
+            "TM/2024-01-15-093012.backup/Users",
+        ] {
+            std::fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        std::fs::write(
+            root.path().join("TM/Backups.backupdb/mac/Latest/old.rs"),
+            "",
+        )
+        .unwrap();
+        std::fs::write(
+            root.path()
+                .join("TM/2024-01-15-093012.backup/Users/snap.rs"),
+            "",
+        )
+        .unwrap();
+        std::fs::write(root.path().join("TM/keep.rs"), "").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"keep.rs".to_string()));
+        for skipped in ["Backups.backupdb", "old.rs", "snap.rs"] {
+            assert!(!names.contains(&skipped.to_string()), "{skipped}");
+        }
+
+        let old = root.path().join("TM/Backups.backupdb/mac/Latest/old.rs");
+        assert!(!should_index_path(&config, &old, false));
+        assert_eq!(
+            exclusion_reason(&config, &old, false),
+            Some(ExclusionReason::BackupVolume(
+                root.path().join("TM/Backups.backupdb")
+            ))
+        );
+
+        config.index_backup_volumes = true;
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"old.rs".to_string()));
+        assert!(names.contains(&"snap.rs".to_string()));
+        assert!(should_index_path(&config, &old, false));
+    }
+
+    #[test]
+    fn trash_is_skipped_unless_opted_in() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join(".Trash/old")).unwrap();
+        std::fs::write(root.path().join(".Trash/old/binned.rs"), "").unwrap();
+        std::fs::write(root.path().join("keep.rs"), "").unwrap();
+
+        let mut config = test_config(root.path(), false);
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"keep.rs".to_string()));
+        assert!(!names.contains(&".Trash".to_string()));
+        assert!(!names.contains(&"binned.rs".to_string()));
+
+        let binned = root.path().join(".Trash/old/binned.rs");
+        assert_eq!(
+            exclusion_reason(&config, &binned, false),
+            Some(ExclusionReason::Trash(root.path().join(".Trash")))
+        );
+
+        config.trash.index = true;
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"binned.rs".to_string()));
+        assert!(should_index_path(&config, &binned, false));
+    }
+
+    #[test]
+    fn enabled_archives_index_members_as_virtual_paths() {
+        let root = tempfile::tempdir().unwrap();
+        let archive = root.path().join("src.tar");
+        let mut tar = tar::Builder::new(std::fs::File::create(&archive).unwrap());
+        for (name, content) in [("pkg/lib.rs", "fn lib() {}\n"), ("README.md", "# hi\n")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        tar.finish().unwrap();
+        drop(tar);
+
+        let mut config = test_config(root.path(), false);
+        let names = indexed_names(&Scanner::new(config.clone()).scan().unwrap());
+        assert!(names.contains(&"src.tar".to_string()));
+        assert!(!names.contains(&"lib.rs".to_string()));
+
+        config.archives.enabled = true;
+        let snapshot = Scanner::new(config.clone()).scan().unwrap();
+        let mut members: Vec<(String, u64)> = snapshot
+            .file_table
+            .iter()
+            .filter_map(|(_, meta)| {
+                let path = snapshot.file_table.path_of(meta, &snapshot.string_arena)?;
+                path.contains("!/").then_some((path, meta.size))
+            })
+            .collect();
+        members.sort();
+        let archive = archive.to_string_lossy();
+        assert_eq!(
+            members,
+            vec![
+                (format!("{archive}!/README.md"), 5),
+                (format!("{archive}!/pkg"), 0),
+                (format!("{archive}!/pkg/lib.rs"), 12),
+            ]
+        );
+
+        config.archives.max_archive_mb = 0;
+        let names = indexed_names(&Scanner::new(config).scan().unwrap());
+        assert!(!names.contains(&"lib.rs".to_string()));
+    }
+
+    #[test]
+    fn load_rejects_index_files_from_older_formats() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/main.rs"), "").unwrap();
+        let index_path = root.path().join("index.bin");
+
+        let snapshot = Scanner::new(test_config(root.path(), false))
+            .scan()
+            .unwrap();
+        let legacy = bincode::serialize(&(
+            &snapshot.file_table,
+            &snapshot.string_arena,
+            &snapshot.trigram_index,
+        ))
+        .unwrap();
+        std::fs::write(&index_path, legacy).unwrap();
+        let err = IndexSnapshot::load(&index_path).err().unwrap();
+        assert!(err.to_string().contains("index format 1"), "{err}");
+
+        snapshot.save(&index_path).unwrap();
+        let loaded = IndexSnapshot::load(&index_path).unwrap();
+        let main_rs = root
+            .path()
+            .join("src/main.rs")
+            .to_string_lossy()
+            .to_string();
+        assert!(loaded
+            .file_table
+            .iter()
+            .any(
+                |(id, _)| loaded.file_table.path(id, &loaded.string_arena) == Some(main_rs.clone())
+            ));
+    }
+
+    #[test]
+    fn set_normalization_and_path_index_rederive_only_when_they_change() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join("résumé.txt"), "").unwrap();
+
+        let mut snapshot = Scanner::new(test_config(root.path(), false))
+            .scan()
+            .unwrap();
+        let resume = vicaya_index::Trigram::extract("resume");
+        assert!(snapshot.trigram_index.query(&resume).is_empty());
+
+        let folded = Normalization {
+            fold_diacritics: true,
+            fold_separators: false,
+        };
+        assert!(snapshot.set_normalization(folded));
+        assert!(!snapshot.set_normalization(folded));
+        assert_eq!(snapshot.trigram_index.normalization(), folded);
+        assert_eq!(snapshot.trigram_index.query(&resume).len(), 1);
+
+        assert!(snapshot.set_path_index(true));
+        assert!(!snapshot.set_path_index(true));
+        assert!(snapshot.set_normalization(Normalization::default()));
+        let path_index = snapshot.path_index.as_ref().unwrap();
+        assert_eq!(path_index.normalization(), Normalization::default());
+        assert!(snapshot.set_path_index(false));
+        assert!(snapshot.path_index.is_none());
+    }
+
+    #[test]
+    fn lite_profile_indexes_files_without_directories_sizes_or_inodes() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src").join("main.rs"), "fn main() {}").unwrap();
+
+        let full = Scanner::new(test_config(root.path(), false))
+            .scan()
+            .unwrap();
+        assert!(full.fits_profile(IndexProfile::Full));
+        assert!(!full.fits_profile(IndexProfile::Lite));
+
+        let mut config = test_config(root.path(), false);
+        config.index_profile = IndexProfile::Lite;
+        let lite = Scanner::new(config).scan().unwrap();
+        let entries: Vec<_> = lite.file_table.iter().map(|(_, meta)| meta).collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(
+            (
+                entries[0].is_dir,
+                entries[0].size,
+                entries[0].dev,
+                entries[0].ino
+            ),
+            (false, 0, 0, 0)
+        );
+        assert!(entries[0].mtime > 0);
+        assert_eq!(
+            lite.file_table
+                .path_of(entries[0], &lite.string_arena)
+                .unwrap(),
+            root.path().join("src/main.rs").to_string_lossy()
+        );
+        assert!(lite.fits_profile(IndexProfile::Lite));
+        assert!(!lite.fits_profile(IndexProfile::Full));
+    }
+
+    #[test]
+    fn scanner_expands_env_vars_in_hand_built_roots_and_exclusions() {
+        let _lock = vicaya_core::paths::test_env_lock();
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("generated")).unwrap();
+        std::fs::write(root.path().join("generated/skip.rs"), "").unwrap();
+        std::fs::write(root.path().join("keep.rs"), "").unwrap();
+        std::env::set_var("VICAYA_TEST_SCAN_ROOT", root.path());
+        std::env::set_var("VICAYA_TEST_SCAN_EXCLUDE", "generated");
+
+        let mut config = test_config(Path::new("${VICAYA_TEST_SCAN_ROOT}/"), false);
+        config.exclusions = vec!["$VICAYA_TEST_SCAN_EXCLUDE".to_string()];
+        let snapshot = Scanner::new(config).scan().unwrap();
+        let names = indexed_names(&snapshot);
+
+        std::env::remove_var("VICAYA_TEST_SCAN_ROOT");
+        std::env::remove_var("VICAYA_TEST_SCAN_EXCLUDE");
+        assert!(names.contains(&"keep.rs".to_string()));
+        assert!(!names.contains(&"skip.rs".to_string()));
+    }
+
     #[test]
     fn gitignore_support_can_be_disabled() {
         let root = tempfile::tempdir().unwrap();
@@ -403,6 +1852,47 @@ mod tests {
         assert!(names.contains(&"app.log".to_string()));
     }
 
+    #[test]
+    fn scan_with_progress_reports_running_entry_count() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.path().join("README.md"), "").unwrap();
+
+        let mut reported = Vec::new();
+        let snapshot = Scanner::new(test_config(root.path(), false))
+            .scan_with_progress(|scanned| reported.push(scanned))
+            .unwrap();
+
+        assert_eq!(reported.len(), snapshot.file_table.len());
+        assert!(reported.windows(2).all(|pair| pair[1] == pair[0] + 1));
+    }
+
+    #[test]
+    fn scan_shard_indexes_one_root_without_the_cap() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        for name in ["a.rs", "b.rs", "c.rs"] {
+            std::fs::write(first.path().join(name), "").unwrap();
+        }
+        std::fs::write(second.path().join("other.rs"), "").unwrap();
+        let mut config = test_config(first.path(), false);
+        config.index_roots.push(second.path().to_path_buf());
+        config.max_indexed_files = 2;
+
+        let mut reported = 0;
+        let shard = Scanner::new(config)
+            .scan_shard(first.path(), |scanned| reported = scanned)
+            .unwrap();
+
+        let mut names = indexed_names(&shard);
+        names.sort();
+        assert!(names.ends_with(&["a.rs".to_string(), "b.rs".into(), "c.rs".into()]));
+        assert!(!names.contains(&"other.rs".to_string()));
+        assert_eq!(reported, shard.file_table.len());
+        assert!(shard.cap.is_none());
+    }
+
     #[test]
     fn watcher_filter_uses_gitignore_rules_for_incremental_paths() {
         let root = tempfile::tempdir().unwrap();
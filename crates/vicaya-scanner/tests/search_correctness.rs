@@ -16,14 +16,48 @@ fn create_test_config(root: &Path) -> Config {
         index_roots: vec![root.to_path_buf()],
         exclusions: vec![".DS_Store".to_string(), ".vicaya-index".to_string()],
         respect_ignore_files: true,
+        case_insensitive_exclusions: false,
+        exclusion_cache: Default::default(),
         index_path: root.join(".vicaya-index"),
         max_memory_mb: 128,
+        max_indexed_files: 0,
         performance: vicaya_core::config::PerformanceConfig {
             scanner_threads: 2,
             reconcile_hour: 3,
+            query_timeout_ms: 250,
+            query_max_candidates: 0,
+            warmup_trigrams: 0,
+            reconcile_schedule: Vec::new(),
+            reconcile_min_gap_minutes: 60,
+            reconcile_on_wake: true,
+            initial_scan: Default::default(),
+            reconcile: Default::default(),
         },
         smriti: vicaya_core::config::SmritiConfig::default(),
         content_search: vicaya_core::config::ContentSearchConfig::default(),
+        preview: vicaya_core::config::PreviewConfig::default(),
+        associations: Default::default(),
+        aliases: Default::default(),
+        http: vicaya_core::config::HttpConfig::default(),
+        journal: vicaya_core::config::JournalConfig::default(),
+        telemetry: vicaya_core::config::TelemetryConfig::default(),
+        ranking: vicaya_core::config::RankingConfig::default(),
+        trash: vicaya_core::config::TrashConfig::default(),
+        normalization: Default::default(),
+        hooks: Vec::new(),
+        root_max_depth: Default::default(),
+        show_hidden: true,
+        restore_tui_session: false,
+        offline_while_starting: false,
+        auto_scope: vicaya_core::config::AutoScope::default(),
+        icons: vicaya_core::config::IconStyle::default(),
+        language: Default::default(),
+        archives: vicaya_core::config::ArchiveConfig::default(),
+        content_hash: vicaya_core::config::ContentHashConfig::default(),
+        index_bundle_contents: false,
+        index_backup_volumes: false,
+        index_directory_paths: false,
+        index_profile: Default::default(),
     }
 }
 
@@ -44,6 +78,15 @@ fn search_files(root: &Path, query_str: &str) -> Vec<String> {
         limit: 100,
         scope: None,
         filter_scope: None,
+        budget: Default::default(),
+        attributes: Default::default(),
+        fuzzy: true,
+        explain: false,
+        scorer: None,
+        directory_bias: 0,
+        min_score: 0.0,
+        max_depth: None,
+        hide_hidden: false,
     };
 
     let results = engine.search(&query);
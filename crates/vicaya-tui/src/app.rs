@@ -1,11 +1,15 @@
 //! Main application loop and event handling.
 
-use crate::state::{AppMode, AppState};
+use crate::prefs::UiPrefs;
+use crate::state::{AppMode, AppState, SplitLayout};
 use crate::ui;
 use crate::worker::{start_worker, WorkerCommand, WorkerEvent};
 use anyhow::Result;
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseButton,
+        MouseEvent, MouseEventKind,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -16,6 +20,7 @@ use ratatui::{
 };
 use std::io;
 use std::sync::mpsc;
+use vicaya_core::bookmarks::BookmarkStore;
 use vicaya_core::smriti::SmritiAction;
 
 /// Open a file in the user's preferred editor
@@ -42,17 +47,100 @@ fn open_file_in_editor(path: &str) -> Result<()> {
     Ok(())
 }
 
-/// Run the TUI application
-pub fn run(startup_scope: Option<std::path::PathBuf>) -> Result<()> {
+/// Options for [`run`].
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// Start with ksetra scoped to this directory.
+    pub startup_scope: Option<std::path::PathBuf>,
+    /// Picker mode: Enter prints the selection and exits, and editor actions
+    /// are disabled.
+    pub pick: bool,
+    /// Terminate printed paths with NUL instead of newline.
+    pub null_separated: bool,
+    /// Scope to offer as the initial ksetra (`auto_scope`); ignored when
+    /// `startup_scope` is set.
+    pub suggested_scope: Option<std::path::PathBuf>,
+    /// Result icon glyphs, overriding the config's `icons`.
+    pub icons: Option<vicaya_core::config::IconStyle>,
+    /// Ignore `restore_tui_session` for this run (`--fresh`).
+    pub fresh: bool,
+}
+
+/// Where the TUI draws. When stdout is captured (`$(vicaya-tui --pick)`),
+/// drawing goes to the controlling terminal so stdout carries only the
+/// printed paths.
+fn terminal_output() -> Box<dyn io::Write> {
+    use std::io::IsTerminal;
+
+    if io::stdout().is_terminal() {
+        return Box::new(io::stdout());
+    }
+    match std::fs::OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(tty) => Box::new(tty),
+        Err(_) => Box::new(io::stderr()),
+    }
+}
+
+/// Write `paths` to stdout, each followed by a newline or, with
+/// `null_separated`, a NUL byte.
+fn print_paths(out: &mut impl io::Write, paths: &[String], null_separated: bool) -> io::Result<()> {
+    let terminator: &[u8] = if null_separated { b"\0" } else { b"\n" };
+    for path in paths {
+        out.write_all(path.as_bytes())?;
+        out.write_all(terminator)?;
+    }
+    out.flush()
+}
+
+/// Run the TUI application.
+///
+/// Returns whether any paths were printed on exit.
+pub fn run(options: RunOptions) -> Result<bool> {
     // Setup terminal
     enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    let mut output = terminal_output();
+    execute!(output, EnterAlternateScreen, EnableMouseCapture)?;
+    let backend = CrosstermBackend::new(output);
     let mut terminal = Terminal::new(backend)?;
 
     // Create app state
-    let mut app = AppState::with_startup_scope(startup_scope);
+    // An explicit scope or a picker run starts where it was asked to.
+    let mut restore_session = !options.fresh && !options.pick && options.startup_scope.is_none();
+    let mut app = AppState::with_startup_scope(options.startup_scope);
+    app.pick_mode = options.pick;
+    match BookmarkStore::load(&vicaya_core::paths::bookmarks_path()) {
+        Ok(store) => app.bookmarks = store.bookmarks,
+        Err(err) => app.error = Some(err.to_string()),
+    }
+    let mut icons = options.icons;
+    match crate::worker::load_config() {
+        Ok(config) => {
+            vicaya_core::i18n::init(config.language);
+            icons = icons.or(Some(config.icons));
+            app.usage_badges = config.smriti.badges && config.smriti_enabled();
+            app.aliases = config.aliases;
+            app.search.show_hidden = config.show_hidden;
+            app.search.content_hash = config.content_hash;
+            restore_session &= config.restore_tui_session;
+        }
+        Err(_) => restore_session = false,
+    }
+    let prefs_path = vicaya_core::paths::tui_state_path();
+    match UiPrefs::load(&prefs_path) {
+        Ok(prefs) => {
+            prefs.apply(&mut app);
+            if restore_session {
+                prefs.restore_session(&mut app);
+            }
+        }
+        Err(err) => app.error = Some(err.to_string()),
+    }
+    if app.ksetra.is_global() {
+        if let Some(scope) = options.suggested_scope {
+            app.mode = AppMode::Confirm(crate::state::Action::SetKsetra(scope));
+        }
+    }
+    app.ui.icons = crate::ui::icons::resolve(icons.unwrap_or_default());
 
     let (cmd_tx, cmd_rx) = mpsc::channel::<WorkerCommand>();
     let (evt_tx, evt_rx) = mpsc::channel::<WorkerEvent>();
@@ -63,6 +151,7 @@ pub fn run(startup_scope: Option<std::path::PathBuf>) -> Result<()> {
 
     let _ = cmd_tx.send(WorkerCommand::Quit);
     let _ = worker_handle.join();
+    let saved = UiPrefs::capture(&app).save_atomic(&prefs_path);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -73,10 +162,12 @@ pub fn run(startup_scope: Option<std::path::PathBuf>) -> Result<()> {
     )?;
     terminal.show_cursor()?;
 
-    // Print path if requested (for terminal integration)
-    if let Some(path) = app.print_on_exit {
-        println!("{}", path);
-    }
+    // Print paths if requested (for terminal integration)
+    print_paths(
+        &mut io::stdout().lock(),
+        &app.print_on_exit,
+        options.null_separated,
+    )?;
 
     // Open file in editor if requested
     if let Some(path) = app.open_in_editor {
@@ -86,8 +177,11 @@ pub fn run(startup_scope: Option<std::path::PathBuf>) -> Result<()> {
     if let Err(err) = res {
         eprintln!("Error: {:?}", err);
     }
+    if let Err(err) = saved {
+        eprintln!("Failed to save TUI layout: {}", err);
+    }
 
-    Ok(())
+    Ok(!app.print_on_exit.is_empty())
 }
 
 /// Main application loop
@@ -97,40 +191,179 @@ fn run_app<B: ratatui::backend::Backend>(
     cmd_tx: mpsc::Sender<WorkerCommand>,
     evt_rx: mpsc::Receiver<WorkerEvent>,
 ) -> Result<()> {
-    let mut last_query = String::new();
-    let mut last_search_sent_at = std::time::Instant::now();
-    let mut last_view = app.view;
-    let mut last_ksetra = app.ksetra.current().cloned();
-    let mut search_id: u64 = 0;
-    let mut active_search_id: u64 = 0;
-
-    let mut preview_id: u64 = 0;
-    let mut active_preview_id: u64 = 0;
-    let mut last_preview_path: Option<String> = None;
-
-    let mut error_clear_time: Option<std::time::Instant> = None;
-
-    // Trigger initial search to populate recent files on startup
-    trigger_search(
-        &cmd_tx,
-        app,
-        &mut search_id,
-        &mut active_search_id,
-        &mut last_search_sent_at,
-    );
+    let mut event_loop = EventLoop::new(app, cmd_tx, evt_rx);
 
     loop {
-        // Apply worker events
-        while let Ok(evt) = evt_rx.try_recv() {
+        event_loop.apply_worker_events(app);
+
+        // Draw UI
+        terminal.draw(|f| ui_render(f, app))?;
+
+        event_loop.sync(app);
+
+        // Handle events
+        if event::poll(std::time::Duration::from_millis(50))? {
+            event_loop.handle_event(app, event::read()?);
+        }
+        event_loop.send_requests(app);
+
+        // Check if should quit
+        if app.should_quit() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// What the main loop remembers between frames: the worker channels, the
+/// ids of the searches and previews in flight, and what they were sent for.
+///
+/// [`run_app`] feeds it crossterm events; [`crate::testing::Harness`] feeds
+/// it scripted ones.
+pub(crate) struct EventLoop {
+    cmd_tx: mpsc::Sender<WorkerCommand>,
+    evt_rx: mpsc::Receiver<WorkerEvent>,
+    last_query: String,
+    last_search_sent_at: std::time::Instant,
+    last_view: crate::state::ViewKind,
+    last_explain: bool,
+    last_show_hidden: bool,
+    last_largest_first: bool,
+    last_ksetra: Option<std::path::PathBuf>,
+    last_tulana: Option<std::path::PathBuf>,
+    search_id: u64,
+    active_search_id: u64,
+    preview_id: u64,
+    active_preview_id: u64,
+    last_preview_path: Option<String>,
+    match_count_id: u64,
+    /// A `SmritiUsage` fetch is on its way.
+    smriti_usage_pending: bool,
+    error_clear_time: Option<std::time::Instant>,
+}
+
+impl EventLoop {
+    /// Start following `app` and send the initial search, which fills the
+    /// phala with recent files.
+    pub(crate) fn new(
+        app: &mut AppState,
+        cmd_tx: mpsc::Sender<WorkerCommand>,
+        evt_rx: mpsc::Receiver<WorkerEvent>,
+    ) -> Self {
+        let mut event_loop = Self {
+            cmd_tx,
+            evt_rx,
+            last_query: String::new(),
+            last_search_sent_at: std::time::Instant::now(),
+            last_view: app.view,
+            last_explain: app.search.explain,
+            last_show_hidden: app.search.show_hidden,
+            last_largest_first: app.search.largest_first,
+            last_ksetra: app.ksetra.current().cloned(),
+            last_tulana: None,
+            search_id: 0,
+            active_search_id: 0,
+            preview_id: 0,
+            active_preview_id: 0,
+            last_preview_path: None,
+            match_count_id: 0,
+            smriti_usage_pending: false,
+            error_clear_time: None,
+        };
+        event_loop.search(app);
+        event_loop
+    }
+
+    /// Whether the prashna has been searched and nothing the worker was
+    /// asked for is still loading.
+    #[cfg_attr(not(feature = "testing"), allow(dead_code))]
+    pub(crate) fn is_settled(&self, app: &AppState) -> bool {
+        self.last_query == app.search.query
+            && !app.search.is_searching
+            && !app.preview.is_loading
+            && app
+                .tulana
+                .as_ref()
+                .is_none_or(|tulana| !tulana.is_searching)
+    }
+
+    fn search(&mut self, app: &mut AppState) {
+        trigger_search(
+            &self.cmd_tx,
+            app,
+            &mut self.search_id,
+            &mut self.active_search_id,
+            &mut self.last_search_sent_at,
+        );
+    }
+
+    /// Fetch Smriti usage for the result badges unless it is loaded or on
+    /// its way.
+    fn request_smriti_usage(&mut self, app: &AppState) {
+        if app.usage_badges && app.smriti_usage.is_none() && !self.smriti_usage_pending {
+            self.smriti_usage_pending = true;
+            let _ = self.cmd_tx.send(WorkerCommand::SmritiUsage);
+        }
+    }
+
+    /// Apply every worker event received so far.
+    pub(crate) fn apply_worker_events(&mut self, app: &mut AppState) {
+        while let Ok(evt) = self.evt_rx.try_recv() {
             match evt {
                 WorkerEvent::Status { status } => {
                     app.daemon_status = status;
                 }
-                WorkerEvent::SearchResults { id, results, error } => {
-                    if id == active_search_id {
+                WorkerEvent::SearchResults {
+                    id,
+                    mut results,
+                    truncated,
+                    suggestions,
+                    outside_scope,
+                    completion,
+                    error,
+                } => {
+                    if id == self.active_search_id {
+                        // Matches outside the ksetra follow under a header.
+                        let broadened = !outside_scope.is_empty();
+                        results.extend(outside_scope);
                         app.search.set_results(results);
+                        app.search.outside_scope = broadened;
+                        app.search.truncated = truncated;
+                        app.search.suggestions = suggestions;
+                        app.search.completion = completion;
                         app.search.is_searching = false;
+                        // The daemon answered, so it can also say what to badge.
+                        if error.is_none() {
+                            self.request_smriti_usage(app);
+                        }
                         app.error = error;
+                        app.preview.request_counts();
+                    }
+                }
+                WorkerEvent::IndexChanged {
+                    id,
+                    removed,
+                    added,
+                    limit,
+                } => {
+                    if id == self.active_search_id && !app.search.is_searching {
+                        app.search.apply_index_change(&removed, added, limit);
+                    }
+                }
+                WorkerEvent::TulanaResults {
+                    id,
+                    results,
+                    truncated,
+                    error,
+                } => {
+                    if let Some(tulana) = app.tulana.as_mut().filter(|t| t.search_id == id) {
+                        tulana.set_results(results);
+                        tulana.truncated = truncated;
+                        tulana.is_searching = false;
+                        if error.is_some() {
+                            app.error = error;
+                        }
                     }
                 }
                 WorkerEvent::PreviewReady {
@@ -139,11 +372,19 @@ fn run_app<B: ratatui::backend::Backend>(
                     title,
                     lines,
                     truncated,
+                    more_available,
+                    hex,
                     anchor_line,
                 } => {
-                    if id == active_preview_id {
+                    if id == self.active_preview_id {
+                        let has_matches =
+                            app.preview.match_counts.get(&path).is_some_and(|n| *n > 0);
+                        app.preview.is_hex = hex;
                         app.preview.is_loading = false;
+                        app.preview.loading_since = None;
                         app.preview.truncated = truncated;
+                        app.preview.more_available = more_available;
+                        app.preview.loading_more = false;
                         app.preview.path = Some(path);
                         app.preview.title = title;
                         app.preview.lines = lines;
@@ -151,130 +392,234 @@ fn run_app<B: ratatui::backend::Backend>(
                             crate::state::compute_content_line_numbers(&app.preview.lines);
                         if let Some(line) = anchor_line {
                             app.preview.scroll = preview_scroll_for_line(app, line);
+                        } else if app.preview.search_all && has_matches {
+                            jump_preview_match(app, 1, true);
+                        }
+                    }
+                }
+                WorkerEvent::PreviewAppend {
+                    id,
+                    lines,
+                    more_available,
+                } => {
+                    if id == self.active_preview_id {
+                        app.preview.append_lines(lines, more_available);
+                    }
+                }
+                WorkerEvent::MatchCount { id, path, count } => {
+                    if id == self.match_count_id && app.preview.search_all {
+                        app.preview.match_counts.insert(path, count);
+                    }
+                }
+                WorkerEvent::PathMissing { path } => {
+                    app.report_missing(path);
+                }
+                WorkerEvent::RootStats { roots, error } => {
+                    if let Some(error) = error {
+                        app.error = Some(error);
+                        if app.mode == AppMode::RootStats {
+                            app.mode = AppMode::Search;
                         }
+                    } else {
+                        app.root_stats = Some(roots);
+                    }
+                }
+                WorkerEvent::SmritiUsage { usage } => {
+                    self.smriti_usage_pending = false;
+                    if usage.is_some() {
+                        app.smriti_usage = usage;
                     }
                 }
             }
         }
+    }
 
-        // Draw UI
-        terminal.draw(|f| ui_render(f, app))?;
-
+    /// Send the searches and previews the state now calls for, after a
+    /// frame was drawn.
+    pub(crate) fn sync(&mut self, app: &mut AppState) {
         // Clear temporary success messages after 2 seconds
-        if let Some(clear_time) = error_clear_time {
+        if let Some(clear_time) = self.error_clear_time {
             if clear_time.elapsed() > std::time::Duration::from_secs(2) {
                 if let Some(ref error) = app.error {
                     if error.starts_with('✓') {
                         app.error = None;
-                        error_clear_time = None;
+                        self.error_clear_time = None;
                     }
                 }
             }
         } else if let Some(ref error) = app.error {
             if error.starts_with('✓') {
-                error_clear_time = Some(std::time::Instant::now());
+                self.error_clear_time = Some(std::time::Instant::now());
+            }
+        }
+
+        // Re-run the current search when switching drishti, with the
+        // prashna the drishti was left with.
+        if app.view != self.last_view {
+            self.last_view = app.view;
+            self.last_query = app.search.query.clone();
+            self.search(app);
+        }
+
+        // Re-run the current search when the inspector needs score breakdowns
+        // the current results were fetched without.
+        if app.search.explain != self.last_explain {
+            self.last_explain = app.search.explain;
+            if app.search.explain && app.search.results.iter().any(|r| r.explain.is_none()) {
+                self.search(app);
             }
         }
 
-        // Re-run the current search when switching drishti.
-        if app.view != last_view {
-            last_view = app.view;
-            trigger_search(
-                &cmd_tx,
-                app,
-                &mut search_id,
-                &mut active_search_id,
-                &mut last_search_sent_at,
-            );
+        // Re-run the current search when `.` flips hidden entries.
+        if app.search.show_hidden != self.last_show_hidden {
+            self.last_show_hidden = app.search.show_hidden;
+            self.search(app);
+        }
+
+        // Re-run the current search when `S` flips the Sthana order.
+        if app.search.largest_first != self.last_largest_first {
+            self.last_largest_first = app.search.largest_first;
+            if app.view == crate::state::ViewKind::Sthana {
+                self.search(app);
+            }
         }
 
-        // Re-run the current search when changing ksetra scope.
-        if app.ksetra.current() != last_ksetra.as_ref() {
-            last_ksetra = app.ksetra.current().cloned();
-            trigger_search(
-                &cmd_tx,
-                app,
-                &mut search_id,
-                &mut active_search_id,
-                &mut last_search_sent_at,
-            );
+        // Re-run the current search when changing either pane's ksetra.
+        let tulana_scope = app.tulana.as_ref().map(|tulana| &tulana.scope);
+        if app.ksetra.current() != self.last_ksetra.as_ref()
+            || tulana_scope != self.last_tulana.as_ref()
+        {
+            if app.ksetra.current() != self.last_ksetra.as_ref() {
+                if let Some(scope) = app.ksetra.current() {
+                    // Lets global searches favor this scope for a while.
+                    let _ = self.cmd_tx.send(WorkerCommand::ScopeUsed {
+                        path: scope.to_string_lossy().to_string(),
+                    });
+                }
+            }
+            self.last_ksetra = app.ksetra.current().cloned();
+            self.last_tulana = tulana_scope.cloned();
+            self.search(app);
         }
 
         // Check if query changed and trigger search (with debounce)
-        if app.search.query != last_query {
-            let elapsed = last_search_sent_at.elapsed();
+        if app.search.query != self.last_query {
+            let elapsed = self.last_search_sent_at.elapsed();
             if elapsed > std::time::Duration::from_millis(150) || app.search.query.is_empty() {
-                last_query = app.search.query.clone();
-                trigger_search(
-                    &cmd_tx,
-                    app,
-                    &mut search_id,
-                    &mut active_search_id,
-                    &mut last_search_sent_at,
-                );
+                self.last_query = app.search.query.clone();
+                self.search(app);
             }
         }
 
+        let sizes_indexed = app.sizes_indexed();
+        app.search.verify_selected(sizes_indexed);
+
         // Schedule preview for selected result (best-effort).
         if app.preview.is_visible && app.mode == AppMode::Search {
             if let Some(result) = app.search.selected_result() {
                 let anchor_line = content_result_anchor(app.view, result);
-                let preview_key = anchor_line
+                if app.preview.path.as_deref() != Some(result.path.as_str()) {
+                    app.preview.mode = crate::state::PreviewMode::Auto;
+                }
+                let mut preview_key = anchor_line
                     .map(|line| format!("{}#{line}", result.path))
                     .unwrap_or_else(|| result.path.clone());
-                if last_preview_path.as_deref() != Some(preview_key.as_str()) {
-                    preview_id = preview_id.wrapping_add(1);
-                    active_preview_id = preview_id;
-                    last_preview_path = Some(preview_key);
+                match app.preview.mode {
+                    crate::state::PreviewMode::Auto => {}
+                    crate::state::PreviewMode::Hex => preview_key.push_str("#hex"),
+                    crate::state::PreviewMode::Text => preview_key.push_str("#text"),
+                }
+                if self.last_preview_path.as_deref() != Some(preview_key.as_str()) {
+                    self.preview_id = self.preview_id.wrapping_add(1);
+                    self.active_preview_id = self.preview_id;
+                    self.last_preview_path = Some(preview_key);
                     app.preview.is_loading = true;
+                    app.preview.loading_since = Some(std::time::Instant::now());
                     app.preview.truncated = false;
+                    app.preview.more_available = false;
+                    app.preview.more_requested = false;
+                    app.preview.loading_more = false;
                     app.preview.path = Some(result.path.clone());
                     app.preview.title = result.name.clone();
                     app.preview.lines.clear();
                     app.preview.content_line_numbers.clear();
                     app.preview.scroll = 0;
                     if anchor_line.is_some() {
-                        app.preview.search_query =
-                            crate::state::parse_query(&app.search.query).term;
-                    } else if app.view != crate::state::ViewKind::Antarvicaya {
+                        app.preview.search_query = app.parsed_query().term;
+                    } else if app.view != crate::state::ViewKind::Antarvicaya
+                        && !app.preview.search_all
+                    {
                         app.preview.clear_search();
                     }
-                    let _ = cmd_tx.send(WorkerCommand::Preview {
-                        id: active_preview_id,
+                    let _ = self.cmd_tx.send(WorkerCommand::Preview {
+                        id: self.active_preview_id,
                         path: result.path.clone(),
                         anchor_line,
+                        mode: app.preview.mode,
                     });
                 }
-            } else if last_preview_path.is_some() {
-                last_preview_path = None;
+            } else if self.last_preview_path.is_some() {
+                self.last_preview_path = None;
                 app.preview.clear();
             }
         }
+    }
 
-        // Handle events
-        if event::poll(std::time::Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
-                handle_key_event(app, key.code, key.modifiers);
-                for event in app.smriti_events.drain(..) {
-                    let _ = cmd_tx.send(WorkerCommand::RecordSmriti {
-                        path: event.path,
-                        query: event.query,
-                        action: event.action,
-                    });
-                }
-                for path in app.smriti_forget_paths.drain(..) {
-                    let _ = cmd_tx.send(WorkerCommand::ForgetSmriti { path });
-                }
+    /// Handle one terminal event and send the requests its key queued.
+    pub(crate) fn handle_event(&mut self, app: &mut AppState, event: Event) {
+        if let Event::Mouse(mouse) = event {
+            handle_mouse_event(app, mouse);
+        }
+        if let Event::Key(key) = event {
+            handle_key_event(app, key.code, key.modifiers);
+            for event in app.smriti_events.drain(..) {
+                let _ = self.cmd_tx.send(WorkerCommand::RecordSmriti {
+                    path: event.path,
+                    query: event.query,
+                    action: event.action,
+                });
+            }
+            for path in app.smriti_forget_paths.drain(..) {
+                let _ = self.cmd_tx.send(WorkerCommand::ForgetSmriti { path });
+            }
+            for event in app.ranking_events.drain(..) {
+                let _ = self.cmd_tx.send(WorkerCommand::RecordRanking { event });
+            }
+            if std::mem::take(&mut app.preview.more_requested) {
+                app.preview.loading_more = true;
+                let _ = self.cmd_tx.send(WorkerCommand::PreviewMore {
+                    id: self.active_preview_id,
+                });
             }
         }
+    }
 
-        // Check if should quit
-        if app.should_quit() {
-            break;
+    /// Send requests queued outside key handling: missing paths, the root
+    /// stats overlay, and match counts for `/` across all results.
+    pub(crate) fn send_requests(&mut self, app: &mut AppState) {
+        for path in app.missing_paths.drain(..) {
+            let _ = self.cmd_tx.send(WorkerCommand::ReportMissing { path });
         }
-    }
 
-    Ok(())
+        if std::mem::take(&mut app.root_stats_requested) {
+            let _ = self.cmd_tx.send(WorkerCommand::RootStats);
+        }
+
+        if std::mem::take(&mut app.preview.counts_requested) {
+            self.match_count_id = self.match_count_id.wrapping_add(1);
+            let _ = self.cmd_tx.send(WorkerCommand::CountMatches {
+                id: self.match_count_id,
+                needle: app.preview.search_query.clone(),
+                paths: app
+                    .search
+                    .results
+                    .iter()
+                    .filter(|result| !result.is_dir)
+                    .map(|result| result.path.clone())
+                    .collect(),
+            });
+        }
+    }
 }
 
 fn content_result_anchor(
@@ -311,10 +656,113 @@ fn handle_key_event(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers) {
         AppMode::KriyaSuchi => handle_kriya_suchi_keys(app, key, modifiers),
         AppMode::PreviewSearch => handle_preview_search_keys(app, key, modifiers),
         AppMode::KsetraInput => handle_ksetra_input_keys(app, key, modifiers),
+        AppMode::OpenWith => handle_open_with_keys(app, key),
+        AppMode::Export => handle_export_keys(app, key, modifiers),
+        AppMode::Inspect => handle_inspect_keys(app, key),
+        AppMode::RootStats => handle_root_stats_keys(app, key),
         AppMode::Confirm(_) => handle_confirm_keys(app, key),
     }
 }
 
+/// Handle keys in the open-with overlay.
+fn handle_open_with_keys(app: &mut AppState, key: KeyCode) {
+    match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('O') => app.close_open_with(),
+        KeyCode::Down | KeyCode::Char('j') => app.ui.open_with.select_next(),
+        KeyCode::Up | KeyCode::Char('k') => app.ui.open_with.select_previous(),
+        KeyCode::Enter => {
+            let Some(target) = app.ui.open_with.selected_target().cloned() else {
+                return;
+            };
+            let path = app.ui.open_with.path.clone();
+            app.close_open_with();
+            open_with_target(&path, &target, app);
+        }
+        _ => {}
+    }
+}
+
+/// Handle keys in the score inspector. The selection can still move so
+/// neighbouring results are easy to compare.
+fn handle_inspect_keys(app: &mut AppState, key: KeyCode) {
+    match key {
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => app.toggle_inspector(),
+        KeyCode::Down | KeyCode::Char('j') => app.search.select_next(),
+        KeyCode::Up | KeyCode::Char('k') => app.search.select_previous(),
+        _ => {}
+    }
+}
+
+/// Handle keys in the per-root index statistics overlay.
+fn handle_root_stats_keys(app: &mut AppState, key: KeyCode) {
+    if matches!(
+        key,
+        KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('I') | KeyCode::Enter
+    ) {
+        app.toggle_root_stats();
+    }
+}
+
+/// Handle keys in the export destination prompt.
+fn handle_export_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers) {
+    match (key, modifiers) {
+        (KeyCode::Char('c'), KeyModifiers::CONTROL) => app.quit(),
+        (KeyCode::Esc, _) => app.close_export(),
+        (KeyCode::Tab, _) => app.ui.export.cycle_format(),
+        (KeyCode::Enter, _) => export_results(app),
+        (KeyCode::Backspace, _) => app.ui.export.pop_char(),
+        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => app.ui.export.push_char(c),
+        _ => {}
+    }
+}
+
+/// Open the export prompt when there is something to export.
+fn show_export(app: &mut AppState) {
+    if app.search.results.is_empty() {
+        app.error = Some("No results to export".to_string());
+        return;
+    }
+    app.show_export();
+}
+
+/// Write the result list, in list order and with varga labels when grouped,
+/// to the destination typed into the export prompt.
+fn export_results(app: &mut AppState) {
+    use crate::export::ExportTarget;
+
+    let scope = app.ksetra.current().map(|path| path.as_path());
+    let groups: Option<Vec<String>> = app
+        .search
+        .results
+        .iter()
+        .map(|result| ui::results::group_label(app.ui.grouping, result, scope))
+        .collect();
+    let format = app.ui.export.format;
+    let text = crate::export::render(&app.search.results, groups.as_deref(), format);
+    let count = app.search.results.len();
+
+    let written = match ExportTarget::parse(&app.ui.export.input) {
+        ExportTarget::Clipboard => vicaya_core::actions::copy_to_clipboard(&text)
+            .map(|()| "clipboard".to_string())
+            .map_err(|e| e.to_string()),
+        ExportTarget::File(path) => std::fs::write(&path, text)
+            .map(|()| path.display().to_string())
+            .map_err(|e| format!("{}: {}", path.display(), e)),
+    };
+    match written {
+        Ok(destination) => {
+            app.close_export();
+            app.error = Some(format!(
+                "✓ Exported {} results ({}) to {}",
+                count,
+                format.label(),
+                destination
+            ));
+        }
+        Err(e) => app.ui.export.error = Some(e),
+    }
+}
+
 /// Handle keys in drishti switcher mode.
 fn handle_drishti_switcher_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers) {
     match (key, modifiers) {
@@ -348,13 +796,12 @@ fn handle_drishti_switcher_keys(app: &mut AppState, key: KeyCode, modifiers: Key
                 return;
             };
             if selected.is_enabled() {
-                app.view = selected;
+                app.set_view(selected);
                 app.toggle_drishti_switcher();
             } else {
-                app.error = Some(format!(
-                    "drishti '{}' ({}) is coming soon",
-                    selected.label(),
-                    selected.english_hint()
+                app.error = Some(vicaya_core::i18n::format(
+                    "tui.drishti.coming_soon",
+                    &[("name", &selected.label()), ("hint", &selected.hint())],
                 ));
                 app.toggle_drishti_switcher();
             }
@@ -406,11 +853,39 @@ fn handle_search_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers)
             app.toggle_ksetra_input();
             return;
         }
+        // Resize the preview and flip the split layout
+        (KeyCode::Char('>'), _) if !app.search.is_input_focused() => {
+            app.ui.resize_preview(1);
+            return;
+        }
+        (KeyCode::Char('<'), _) if !app.search.is_input_focused() => {
+            app.ui.resize_preview(-1);
+            return;
+        }
+        (KeyCode::Char('|'), _) if !app.search.is_input_focused() => {
+            app.ui.split = app.ui.split.toggle();
+            return;
+        }
+        // Show or hide dotfiles
+        (KeyCode::Char('.'), KeyModifiers::NONE) if !app.search.is_input_focused() => {
+            app.toggle_hidden();
+            return;
+        }
+        // Order Sthana by directory size
+        (KeyCode::Char('S'), KeyModifiers::SHIFT) if !app.search.is_input_focused() => {
+            app.toggle_largest_first();
+            return;
+        }
         // Help
         (KeyCode::Char('?'), KeyModifiers::NONE) if !app.search.is_input_focused() => {
             app.toggle_help();
             return;
         }
+        // Jump to a breadcrumb's ksetra
+        (KeyCode::Char(c @ '1'..='9'), KeyModifiers::ALT) => {
+            jump_to_ksetra_depth(app, c as usize - '0' as usize);
+            return;
+        }
         // Toggle focus with Tab
         (KeyCode::Tab, KeyModifiers::NONE) => {
             cycle_focus_forward(app);
@@ -422,7 +897,9 @@ fn handle_search_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers)
         }
         // Escape clears search or changes focus
         (KeyCode::Esc, KeyModifiers::NONE) => {
-            if app.search.is_preview_focused() {
+            if app.search.is_preview_focused()
+                || app.search.focus == crate::state::FocusTarget::Tulana
+            {
                 app.search.focus = crate::state::FocusTarget::Results;
             } else if app.search.is_results_focused() {
                 app.search.focus = crate::state::FocusTarget::Input;
@@ -439,11 +916,74 @@ fn handle_search_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers)
         handle_input_keys(app, key, modifiers);
     } else if app.search.is_results_focused() {
         handle_results_keys(app, key, modifiers);
+    } else if app.search.focus == crate::state::FocusTarget::Tulana {
+        handle_tulana_keys(app, key, modifiers);
     } else {
         handle_preview_keys(app, key, modifiers);
     }
 }
 
+/// Handle keys when the tulana (comparison) pane is focused.
+fn handle_tulana_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers) {
+    let Some(tulana) = app.tulana.as_mut() else {
+        app.search.focus = crate::state::FocusTarget::Results;
+        return;
+    };
+    match (key, modifiers) {
+        (KeyCode::Char('j'), KeyModifiers::NONE) | (KeyCode::Down, KeyModifiers::NONE) => {
+            tulana.select_next();
+        }
+        (KeyCode::Char('k'), KeyModifiers::NONE) | (KeyCode::Up, KeyModifiers::NONE) => {
+            tulana.select_previous();
+        }
+        (KeyCode::Char('g'), KeyModifiers::NONE) => {
+            tulana.selected_index = 0;
+        }
+        (KeyCode::Char('G'), KeyModifiers::SHIFT) => {
+            tulana.selected_index = tulana.results.len().saturating_sub(1);
+        }
+        (KeyCode::Char('y'), KeyModifiers::NONE) => {
+            if let Some(path) = tulana.selected_result().map(|r| r.path.clone()) {
+                copy_to_clipboard(&path, app);
+            }
+        }
+        (KeyCode::Enter, KeyModifiers::NONE) | (KeyCode::Char('o'), KeyModifiers::NONE) => {
+            if let Some(path) = tulana.selected_result().map(|r| r.path.clone()) {
+                if !is_dir(&path, app.view) {
+                    open_in_editor(&path, app);
+                }
+            }
+        }
+        (KeyCode::Char('C'), KeyModifiers::SHIFT) => {
+            toggle_tulana(app);
+        }
+        (KeyCode::Char('q'), KeyModifiers::NONE) => {
+            app.quit();
+        }
+        _ => {}
+    }
+}
+
+/// Open a tulana pane (asking for its ksetra) or close the open one.
+fn toggle_tulana(app: &mut AppState) {
+    if app.tulana.is_some() {
+        app.close_tulana();
+    } else if app.ksetra.is_global() {
+        app.error = Some("Set a ksetra first; tulana compares two scopes".to_string());
+    } else {
+        app.show_tulana_input();
+    }
+}
+
+/// Toggle checking the selected result against the filesystem. Turning it
+/// off drops earlier outcomes, which may be out of date by the next time.
+fn toggle_verify(app: &mut AppState) {
+    app.search.verify = !app.search.verify;
+    if !app.search.verify {
+        app.search.freshness.clear();
+    }
+}
+
 /// Handle keys in Kriya-Suchi mode.
 fn handle_kriya_suchi_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers) {
     match (key, modifiers) {
@@ -509,8 +1049,15 @@ fn handle_input_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers)
         (KeyCode::Left, KeyModifiers::NONE) => {
             app.search.move_cursor_left();
         }
+        // At the end of the query, Right accepts the ghost-text completion
         (KeyCode::Right, KeyModifiers::NONE) => {
-            app.search.move_cursor_right();
+            if !app.search.accept_completion() {
+                app.search.move_cursor_right();
+            }
+        }
+        // Picker mode accepts straight from the query input
+        (KeyCode::Enter, KeyModifiers::NONE) if app.pick_mode => {
+            print_selection(app);
         }
         // Down arrow switches to results if there are any
         (KeyCode::Down, KeyModifiers::NONE) => {
@@ -555,6 +1102,9 @@ fn handle_results_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers
         (KeyCode::Char('h'), KeyModifiers::NONE) | (KeyCode::Left, KeyModifiers::NONE) => {
             pop_ksetra(app);
         }
+        (KeyCode::Char('~'), _) => {
+            jump_to_ksetra_depth(app, 0);
+        }
         (KeyCode::Char('l'), KeyModifiers::NONE) | (KeyCode::Right, KeyModifiers::NONE) => {
             if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
                 if is_dir(&path, app.view) {
@@ -562,15 +1112,23 @@ fn handle_results_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers
                 }
             }
         }
+        // Bookmarks
+        (KeyCode::Char(c @ '1'..='9'), KeyModifiers::NONE) => {
+            jump_to_bookmark(app, c as usize - '0' as usize);
+        }
+        (KeyCode::Char('B'), KeyModifiers::SHIFT) => {
+            bookmark_ksetra(app, &vicaya_core::paths::bookmarks_path());
+        }
+        // Marking for multi-path printing
+        (KeyCode::Char(' '), KeyModifiers::NONE) => {
+            toggle_mark(app);
+        }
         // File actions
+        (KeyCode::Enter, KeyModifiers::NONE) if app.pick_mode => {
+            print_selection(app);
+        }
         (KeyCode::Enter, KeyModifiers::NONE) | (KeyCode::Char('o'), KeyModifiers::NONE) => {
-            if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
-                if is_dir(&path, app.view) {
-                    push_ksetra(app, path);
-                } else {
-                    open_in_editor(&path, app);
-                }
-            }
+            open_or_enter(app);
         }
         (KeyCode::Char('y'), KeyModifiers::NONE) => {
             if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
@@ -579,11 +1137,7 @@ fn handle_results_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers
             }
         }
         (KeyCode::Char('p'), KeyModifiers::NONE) => {
-            if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
-                app.record_smriti_usage(path.clone(), SmritiAction::Print);
-                app.print_on_exit = Some(path);
-                app.quit();
-            }
+            print_selection(app);
         }
         (KeyCode::Char('r'), KeyModifiers::NONE) => {
             if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
@@ -591,6 +1145,26 @@ fn handle_results_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers
                 reveal_in_finder(&path, app);
             }
         }
+        (KeyCode::Char('O'), KeyModifiers::SHIFT) => {
+            if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
+                show_open_with(app, path);
+            }
+        }
+        (KeyCode::Char('e'), KeyModifiers::NONE) => {
+            show_export(app);
+        }
+        (KeyCode::Char('C'), KeyModifiers::SHIFT) => {
+            toggle_tulana(app);
+        }
+        (KeyCode::Char('V'), KeyModifiers::SHIFT) => {
+            toggle_verify(app);
+        }
+        (KeyCode::Char('i'), KeyModifiers::NONE) => {
+            app.toggle_inspector();
+        }
+        (KeyCode::Char('I'), KeyModifiers::SHIFT) => {
+            app.toggle_root_stats();
+        }
         // Quit
         (KeyCode::Char('q'), KeyModifiers::NONE) => {
             app.quit();
@@ -638,6 +1212,12 @@ fn handle_preview_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModifiers
         (KeyCode::Char('G'), KeyModifiers::SHIFT) => {
             app.preview.scroll = preview_max_scroll(app);
         }
+        (KeyCode::Char('L'), KeyModifiers::SHIFT) => {
+            app.preview.request_more();
+        }
+        (KeyCode::Char('x'), KeyModifiers::NONE) => {
+            app.preview.toggle_hex();
+        }
         _ => {}
     }
 }
@@ -658,6 +1238,9 @@ fn handle_preview_search_keys(app: &mut AppState, key: KeyCode, modifiers: KeyMo
                 jump_preview_match(app, 1, true);
             }
         }
+        (KeyCode::Tab, KeyModifiers::NONE) => {
+            app.preview.toggle_search_all();
+        }
         (KeyCode::Backspace, KeyModifiers::NONE) => {
             app.preview.delete_search_char();
         }
@@ -686,7 +1269,11 @@ fn handle_ksetra_input_keys(app: &mut AppState, key: KeyCode, modifiers: KeyModi
         (KeyCode::Enter, KeyModifiers::NONE) => {
             // Validate and apply the ksetra
             let path = app.ksetra_input.expand_path();
-            if path.is_dir() {
+            if path.is_dir() && app.ksetra_input.for_tulana {
+                app.open_tulana(path);
+                app.search.focus = crate::state::FocusTarget::Tulana;
+                app.toggle_ksetra_input();
+            } else if path.is_dir() {
                 // Clear ksetra stack and set to this path
                 while app.ksetra.pop().is_some() {}
                 app.ksetra.push(path);
@@ -809,33 +1396,44 @@ fn trigger_ksetra_completion(app: &mut AppState) {
     app.ksetra_input.set_completions(completions);
 }
 
+// The tulana pane takes the preview's place, so the two never share a cycle.
 fn cycle_focus_forward(app: &mut AppState) {
-    let has_preview = app.preview.is_visible;
+    let has_preview = app.preview.is_visible && app.tulana.is_none();
+    let has_tulana = app.tulana.is_some();
     app.search.focus = match app.search.focus {
         crate::state::FocusTarget::Input => crate::state::FocusTarget::Results,
         crate::state::FocusTarget::Results => {
-            if has_preview {
+            if has_tulana {
+                crate::state::FocusTarget::Tulana
+            } else if has_preview {
                 crate::state::FocusTarget::Preview
             } else {
                 crate::state::FocusTarget::Input
             }
         }
-        crate::state::FocusTarget::Preview => crate::state::FocusTarget::Input,
+        crate::state::FocusTarget::Preview | crate::state::FocusTarget::Tulana => {
+            crate::state::FocusTarget::Input
+        }
     };
 }
 
 fn cycle_focus_backward(app: &mut AppState) {
-    let has_preview = app.preview.is_visible;
+    let has_preview = app.preview.is_visible && app.tulana.is_none();
+    let has_tulana = app.tulana.is_some();
     app.search.focus = match app.search.focus {
         crate::state::FocusTarget::Input => {
-            if has_preview {
+            if has_tulana {
+                crate::state::FocusTarget::Tulana
+            } else if has_preview {
                 crate::state::FocusTarget::Preview
             } else {
                 crate::state::FocusTarget::Results
             }
         }
         crate::state::FocusTarget::Results => crate::state::FocusTarget::Input,
-        crate::state::FocusTarget::Preview => crate::state::FocusTarget::Results,
+        crate::state::FocusTarget::Preview | crate::state::FocusTarget::Tulana => {
+            crate::state::FocusTarget::Results
+        }
     };
 }
 
@@ -950,8 +1548,56 @@ fn find_next_match_line(
     None
 }
 
+/// Push ksetra into the selected directory, or open the selected file.
+fn open_or_enter(app: &mut AppState) {
+    if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
+        if is_dir(&path, app.view) {
+            push_ksetra(app, path);
+        } else {
+            open_in_editor(&path, app);
+        }
+    }
+}
+
+/// Mark or unmark the selected result and advance to the next one.
+fn toggle_mark(app: &mut AppState) {
+    if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
+        app.search.toggle_mark(&path);
+        app.search.select_next();
+    }
+}
+
+/// Print the marked paths, or the selected one when nothing is marked, and
+/// exit.
+fn print_selection(app: &mut AppState) {
+    let paths = if app.search.marked.is_empty() {
+        match app.search.selected_result() {
+            Some(result) => vec![result.path.clone()],
+            None => return,
+        }
+    } else {
+        app.search.marked.clone()
+    };
+    for path in &paths {
+        app.record_smriti_usage(path.clone(), SmritiAction::Print);
+    }
+    app.print_on_exit = paths;
+    app.quit();
+}
+
 /// Open file in $EDITOR or fallback editor
 fn open_in_editor(path: &str, app: &mut AppState) {
+    if app.pick_mode {
+        app.error = Some("editor actions are disabled in picker mode".to_string());
+        return;
+    }
+    if vicaya_core::archive::split_virtual_path(path).is_none()
+        && std::fs::symlink_metadata(path).is_err_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+    {
+        app.error = Some(format!("{path} no longer exists; dropped from the index"));
+        app.report_missing(path.to_string());
+        return;
+    }
     // Store path to open after TUI exits
     app.record_smriti_usage(path.to_string(), SmritiAction::Open);
     app.open_in_editor = Some(path.to_string());
@@ -975,15 +1621,84 @@ fn push_ksetra(app: &mut AppState, path: String) {
     app.search.is_searching = true;
 }
 
-fn pop_ksetra(app: &mut AppState) {
-    if app.ksetra.pop().is_some() {
-        app.clear_results();
-        app.preview.clear();
-        app.ui.scroll_offset = 0;
-        app.search.is_searching = true;
-    } else {
-        app.error = Some("ksetra is already global".to_string());
-    }
+/// Replace the ksetra stack with the bookmark at 1-based `slot`.
+fn jump_to_bookmark(app: &mut AppState, slot: usize) {
+    let Some(bookmark) = slot.checked_sub(1).and_then(|i| app.bookmarks.get(i)) else {
+        app.error = Some(format!(
+            "no bookmark {slot} (add one with B or `vicaya bookmark add`)"
+        ));
+        return;
+    };
+    let path = bookmark.path.clone();
+    if !std::path::Path::new(&path).is_dir() {
+        app.error = Some(format!("bookmark {slot} is not a directory: {path}"));
+        return;
+    }
+    while app.ksetra.pop().is_some() {}
+    push_ksetra(app, path);
+}
+
+/// Bookmark the current ksetra in the store at `store_path`.
+fn bookmark_ksetra(app: &mut AppState, store_path: &std::path::Path) {
+    let Some(scope) = app.ksetra.current().cloned() else {
+        app.error = Some("ksetra is global; enter a directory to bookmark it".to_string());
+        return;
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let result = BookmarkStore::load(store_path).and_then(|mut store| {
+        let name = store.add(None, &scope, now)?.name.clone();
+        store.save_atomic(store_path)?;
+        Ok((store, name))
+    });
+    match result {
+        Ok((store, name)) => {
+            let slot = store
+                .bookmarks
+                .iter()
+                .position(|b| std::path::Path::new(&b.path) == scope)
+                .map(|index| index + 1)
+                .unwrap_or_default();
+            app.bookmarks = store.bookmarks;
+            app.error = Some(format!("✓ Bookmarked '{name}' as {slot}"));
+        }
+        Err(err) => app.error = Some(format!("Failed to bookmark: {err}")),
+    }
+}
+
+fn pop_ksetra(app: &mut AppState) {
+    jump_to_ksetra_depth(app, app.ksetra.depth().saturating_sub(1));
+}
+
+/// Pop every ksetra level above `depth` at once (0 is global), as a
+/// breadcrumb click or `Alt+1`-`Alt+9` does.
+fn jump_to_ksetra_depth(app: &mut AppState, depth: usize) {
+    if app.ksetra.truncate(depth) {
+        app.clear_results();
+        app.preview.clear();
+        app.ui.scroll_offset = 0;
+        app.search.is_searching = true;
+    } else if app.ksetra.is_global() {
+        app.error = Some("ksetra is already global".to_string());
+    }
+}
+
+/// Left clicks on a header breadcrumb jump to its ksetra.
+fn handle_mouse_event(app: &mut AppState, mouse: MouseEvent) {
+    if app.mode != AppMode::Search || mouse.kind != MouseEventKind::Down(MouseButton::Left) {
+        return;
+    }
+    let depth = app
+        .ui
+        .breadcrumb_targets
+        .iter()
+        .find(|target| target.row == mouse.row && target.columns.contains(&mouse.column))
+        .map(|target| target.depth);
+    if let Some(depth) = depth {
+        jump_to_ksetra_depth(app, depth);
+    }
 }
 
 fn run_kriya_action(app: &mut AppState, id: crate::kriya::KriyaId) {
@@ -991,13 +1706,7 @@ fn run_kriya_action(app: &mut AppState, id: crate::kriya::KriyaId) {
 
     match id {
         KriyaId::OpenOrEnter => {
-            if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
-                if is_dir(&path, app.view) {
-                    push_ksetra(app, path);
-                } else {
-                    open_in_editor(&path, app);
-                }
-            }
+            open_or_enter(app);
         }
         KriyaId::CopyPath => {
             if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
@@ -1011,13 +1720,23 @@ fn run_kriya_action(app: &mut AppState, id: crate::kriya::KriyaId) {
                 reveal_in_finder(&path, app);
             }
         }
-        KriyaId::PrintPath => {
+        KriyaId::OpenWith => {
             if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
-                app.record_smriti_usage(path.clone(), SmritiAction::Print);
-                app.print_on_exit = Some(path);
-                app.quit();
+                show_open_with(app, path);
             }
         }
+        KriyaId::PrintPath => {
+            print_selection(app);
+        }
+        KriyaId::ExportResults => {
+            show_export(app);
+        }
+        KriyaId::ToggleMark => {
+            toggle_mark(app);
+        }
+        KriyaId::BookmarkKsetra => {
+            bookmark_ksetra(app, &vicaya_core::paths::bookmarks_path());
+        }
         KriyaId::ForgetSmriti => {
             if let Some(path) = app.search.selected_result().map(|r| r.path.clone()) {
                 app.forget_smriti_path(path.clone());
@@ -1036,15 +1755,48 @@ fn run_kriya_action(app: &mut AppState, id: crate::kriya::KriyaId) {
             app.ui.grouping = app.ui.grouping.next();
             app.ui.scroll_offset = 0;
         }
+        KriyaId::GrowPreview => {
+            app.ui.resize_preview(1);
+        }
+        KriyaId::ShrinkPreview => {
+            app.ui.resize_preview(-1);
+        }
+        KriyaId::ToggleSplitLayout => {
+            app.ui.split = app.ui.split.toggle();
+        }
+        KriyaId::ToggleLargestFirst => {
+            app.toggle_largest_first();
+        }
+        KriyaId::ToggleHidden => {
+            app.toggle_hidden();
+        }
         KriyaId::PopKsetra => {
             pop_ksetra(app);
         }
         KriyaId::SetKsetra => {
             app.toggle_ksetra_input();
         }
+        KriyaId::ToggleTulana => {
+            toggle_tulana(app);
+        }
+        KriyaId::ToggleVerify => {
+            toggle_verify(app);
+        }
+        KriyaId::InspectScore => {
+            app.toggle_inspector();
+        }
+        KriyaId::ShowRootStats => {
+            app.toggle_root_stats();
+        }
         KriyaId::TogglePreviewLineNumbers => {
             app.preview.toggle_line_numbers();
         }
+        KriyaId::TogglePreviewHex => {
+            app.preview.toggle_hex();
+        }
+        KriyaId::LoadMorePreview => {
+            app.preview.request_more();
+        }
         KriyaId::ClearPreviewSearch => {
             app.preview.clear_search();
         }
@@ -1054,6 +1806,10 @@ fn run_kriya_action(app: &mut AppState, id: crate::kriya::KriyaId) {
     }
 }
 
+/// Results per pane while comparing, so "only here" marks are not artifacts
+/// of the usual 100-result cut-off.
+const TULANA_RESULT_LIMIT: usize = 1000;
+
 fn trigger_search(
     cmd_tx: &mpsc::Sender<WorkerCommand>,
     app: &mut AppState,
@@ -1061,18 +1817,43 @@ fn trigger_search(
     active_search_id: &mut u64,
     last_search_sent_at: &mut std::time::Instant,
 ) -> bool {
-    let parsed = crate::state::parse_query(&app.search.query);
+    let parsed = app.parsed_query();
+    let hidden = crate::state::hidden(&parsed.niyamas).unwrap_or(app.search.show_hidden);
+    let limit = if app.tulana.is_some() {
+        TULANA_RESULT_LIMIT
+    } else {
+        100
+    };
+
+    if let Some(tulana) = app.tulana.as_mut() {
+        *search_id = (*search_id).wrapping_add(1);
+        tulana.search_id = *search_id;
+        let command = WorkerCommand::TulanaSearch {
+            id: *search_id,
+            query: parsed.term.clone(),
+            limit,
+            view: app.view,
+            scope: tulana.scope.clone(),
+            niyamas: parsed.niyamas.clone(),
+            hidden,
+            largest_first: app.search.largest_first,
+        };
+        tulana.is_searching = cmd_tx.send(command).is_ok();
+    }
 
     *search_id = (*search_id).wrapping_add(1);
     *active_search_id = *search_id;
     let command = WorkerCommand::Search {
         id: *active_search_id,
         query: parsed.term,
-        limit: 100,
+        limit,
         view: app.view,
         boost_scope: app.ksetra.current().cloned(),
         filter_scope: app.ksetra.current().cloned(),
         niyamas: parsed.niyamas,
+        hidden,
+        largest_first: app.search.largest_first,
+        explain: app.search.explain,
     };
 
     if cmd_tx.send(command).is_err() {
@@ -1088,35 +1869,8 @@ fn trigger_search(
 
 /// Copy path to clipboard
 fn copy_to_clipboard(path: &str, app: &mut AppState) {
-    use std::process::Command;
-
-    let result = if cfg!(target_os = "macos") {
-        Command::new("pbcopy")
-            .stdin(std::process::Stdio::piped())
-            .spawn()
-            .and_then(|mut child| {
-                use std::io::Write;
-                if let Some(stdin) = child.stdin.as_mut() {
-                    stdin.write_all(path.as_bytes())?;
-                }
-                child.wait()
-            })
-    } else {
-        Command::new("xclip")
-            .args(["-selection", "clipboard"])
-            .stdin(std::process::Stdio::piped())
-            .spawn()
-            .and_then(|mut child| {
-                use std::io::Write;
-                if let Some(stdin) = child.stdin.as_mut() {
-                    stdin.write_all(path.as_bytes())?;
-                }
-                child.wait()
-            })
-    };
-
-    match result {
-        Ok(_) => {
+    match vicaya_core::actions::copy_to_clipboard(path) {
+        Ok(()) => {
             app.error = Some(format!("✓ Copied: {}", path));
         }
         Err(e) => {
@@ -1127,25 +1881,72 @@ fn copy_to_clipboard(path: &str, app: &mut AppState) {
 
 /// Reveal file in file manager
 fn reveal_in_finder(path: &str, app: &mut AppState) {
-    use std::process::Command;
+    match vicaya_core::actions::reveal(std::path::Path::new(path)) {
+        Ok(()) => {
+            app.error = Some(format!("✓ Revealed: {}", path));
+        }
+        Err(e) => {
+            app.error = Some(format!("Failed to reveal: {}", e));
+        }
+    }
+}
 
-    let result = if cfg!(target_os = "macos") {
-        Command::new("open").args(["-R", path]).spawn()
-    } else {
-        // On Linux, open the parent directory
-        let parent = std::path::Path::new(path)
-            .parent()
-            .and_then(|p| p.to_str())
-            .unwrap_or(path);
-        Command::new("xdg-open").arg(parent).spawn()
+/// Open the open-with overlay with the applications configured for `path`.
+fn show_open_with(app: &mut AppState, path: String) {
+    if app.pick_mode {
+        app.error = Some("editor actions are disabled in picker mode".to_string());
+        return;
+    }
+    let apps = match crate::worker::load_config() {
+        Ok(config) => config
+            .associations_for(std::path::Path::new(&path))
+            .to_vec(),
+        Err(err) => {
+            app.error = Some(format!("Failed to load associations: {}", err));
+            Vec::new()
+        }
+    };
+    app.show_open_with(path, &apps);
+}
+
+/// Hand `path` to the chosen open-with target.
+fn open_with_target(path: &str, target: &crate::state::OpenWithTarget, app: &mut AppState) {
+    use crate::state::OpenWithTarget;
+    use vicaya_core::actions::spawn_detached;
+
+    let path_ref = std::path::Path::new(path);
+    let result = match target {
+        OpenWithTarget::Editor => {
+            open_in_editor(path, app);
+            return;
+        }
+        OpenWithTarget::QuickLook => spawn_detached("qlmanage", &["-p".to_string()], path_ref),
+        OpenWithTarget::Application(application) => {
+            if cfg!(target_os = "macos") {
+                spawn_detached("open", &["-a".to_string(), application.clone()], path_ref)
+            } else {
+                // Elsewhere an association is a command line; the path is
+                // appended as its last argument.
+                let mut parts = application.split_whitespace().map(str::to_string);
+                match parts.next() {
+                    Some(program) => spawn_detached(&program, &parts.collect::<Vec<_>>(), path_ref),
+                    None => Err(vicaya_core::Error::Other("empty application".to_string())),
+                }
+            }
+        }
     };
 
     match result {
-        Ok(_) => {
-            app.error = Some(format!("✓ Revealed: {}", path));
+        Ok(()) => {
+            // Only a launched application counts as a use, as in the shared
+            // actions path.
+            if matches!(target, OpenWithTarget::Application(_)) {
+                app.record_smriti_usage(path.to_string(), SmritiAction::Open);
+            }
+            app.error = Some(format!("✓ Opened with {}: {}", target.label(), path));
         }
         Err(e) => {
-            app.error = Some(format!("Failed to reveal: {}", e));
+            app.error = Some(format!("Failed to open with {}: {}", target.label(), e));
         }
     }
 }
@@ -1161,12 +1962,31 @@ fn handle_help_keys(app: &mut AppState, key: KeyCode) {
 }
 
 /// Handle keys in confirm mode
-fn handle_confirm_keys(_app: &mut AppState, _key: KeyCode) {
-    // TODO: Implement confirmation dialog handling
+fn handle_confirm_keys(app: &mut AppState, key: KeyCode) {
+    let AppMode::Confirm(action) = app.mode.clone() else {
+        return;
+    };
+    match key {
+        KeyCode::Char('y') | KeyCode::Char('Y') | KeyCode::Enter => {
+            app.mode = AppMode::Search;
+            match action {
+                crate::state::Action::Quit => app.quit(),
+                crate::state::Action::SetKsetra(path) => {
+                    while app.ksetra.pop().is_some() {}
+                    push_ksetra(app, path.to_string_lossy().to_string());
+                }
+                crate::state::Action::RebuildIndex => {}
+            }
+        }
+        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+            app.mode = AppMode::Search;
+        }
+        _ => {}
+    }
 }
 
 /// Render the UI
-fn ui_render(f: &mut Frame, app: &mut AppState) {
+pub(crate) fn ui_render(f: &mut Frame, app: &mut AppState) {
     match app.mode {
         AppMode::Search => render_search(f, app),
         AppMode::Help => ui::overlays::render_help(f),
@@ -1186,7 +2006,26 @@ fn ui_render(f: &mut Frame, app: &mut AppState) {
             render_search(f, app);
             ui::overlays::render_ksetra_input(f, app);
         }
-        AppMode::Confirm(_) => ui::overlays::render_confirm(f, app),
+        AppMode::OpenWith => {
+            render_search(f, app);
+            ui::overlays::render_open_with(f, app);
+        }
+        AppMode::Export => {
+            render_search(f, app);
+            ui::overlays::render_export(f, app);
+        }
+        AppMode::Inspect => {
+            render_search(f, app);
+            ui::overlays::render_inspector(f, app);
+        }
+        AppMode::RootStats => {
+            render_search(f, app);
+            ui::overlays::render_root_stats(f, app);
+        }
+        AppMode::Confirm(_) => {
+            render_search(f, app);
+            ui::overlays::render_confirm(f, app);
+        }
     }
 }
 
@@ -1205,10 +2044,25 @@ fn render_search(f: &mut Frame, app: &mut AppState) {
     ui::header::render(f, chunks[0], app);
     ui::search_input::render(f, chunks[1], app);
 
-    if app.preview.is_visible {
+    let direction = match app.ui.split {
+        SplitLayout::SideBySide => Direction::Horizontal,
+        SplitLayout::Stacked => Direction::Vertical,
+    };
+    if app.tulana.is_some() {
+        let body = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[2]);
+        app.ui.preview_viewport_height = 0;
+        ui::results::render(f, body[0], app);
+        ui::results::render_tulana(f, body[1], app);
+    } else if app.preview.is_visible {
         let body = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .direction(direction)
+            .constraints([
+                Constraint::Percentage(100 - app.ui.preview_percent),
+                Constraint::Percentage(app.ui.preview_percent),
+            ])
             .split(chunks[2]);
         app.ui.preview_viewport_height = body[1].height.saturating_sub(2) as usize;
         ui::results::render(f, body[0], app);
@@ -1236,6 +2090,14 @@ mod tests {
             score: 0.92,
             size,
             mtime: 1_700_000_000,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
         }
     }
 
@@ -1272,6 +2134,7 @@ mod tests {
 
     fn apply_sample_status(app: &mut AppState) {
         app.daemon_status = Some(crate::client::DaemonStatus {
+            pid: 0,
             build: BuildInfo {
                 version: "1.2.0".to_string(),
                 git_sha: "abc1234".to_string(),
@@ -1283,6 +2146,18 @@ mod tests {
             arena_size: 4_096,
             last_updated: 1_700_000_000,
             reconciling: true,
+            pending_watcher_events: 0,
+            journal_entries: 0,
+            reconcile_progress: Some(42),
+            reconcile_scan: None,
+            index_cap: None,
+            index_allocated_bytes: 0,
+            state_allocated_bytes: 0,
+            warmup_ms: None,
+            journal_fsync: None,
+            last_reconcile: None,
+            next_reconcile: None,
+            index_profile: Default::default(),
         });
     }
 
@@ -1328,8 +2203,576 @@ mod tests {
             .set_results(vec![search_result(&file, "README.md", 6)]);
         app.search.focus = FocusTarget::Results;
         handle_key_event(&mut app, KeyCode::Char('p'), KeyModifiers::NONE);
-        assert_eq!(app.print_on_exit, Some(file.to_string_lossy().to_string()));
+        assert_eq!(app.print_on_exit, vec![file.to_string_lossy().to_string()]);
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn marked_results_are_printed_together_in_marking_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.txt");
+        let b = dir.path().join("b.txt");
+        let c = dir.path().join("c.txt");
+        let mut app = AppState::new();
+        app.search.set_results(vec![
+            search_result(&a, "a.txt", 0),
+            search_result(&b, "b.txt", 0),
+            search_result(&c, "c.txt", 0),
+        ]);
+        app.search.focus = FocusTarget::Results;
+
+        // Space marks and advances; marking twice unmarks.
+        handle_key_event(&mut app, KeyCode::Char(' '), KeyModifiers::NONE);
+        handle_key_event(&mut app, KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(app.search.selected_index, 2);
+        handle_key_event(&mut app, KeyCode::Char('g'), KeyModifiers::NONE);
+        handle_key_event(&mut app, KeyCode::Char(' '), KeyModifiers::NONE);
+        assert_eq!(app.search.marked, vec![b.to_string_lossy().to_string()]);
+
+        handle_key_event(&mut app, KeyCode::Char('G'), KeyModifiers::SHIFT);
+        handle_key_event(&mut app, KeyCode::Char(' '), KeyModifiers::NONE);
+        assert!(app.search.is_marked(&c.to_string_lossy()));
+        handle_key_event(&mut app, KeyCode::Char('p'), KeyModifiers::NONE);
+        assert_eq!(
+            app.print_on_exit,
+            vec![
+                b.to_string_lossy().to_string(),
+                c.to_string_lossy().to_string()
+            ]
+        );
+        assert_eq!(app.smriti_events.len(), 2);
+        assert!(app.should_quit());
+    }
+
+    #[test]
+    fn e_exports_the_grouped_result_list_to_the_typed_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.rs");
+        let b = dir.path().join("b.md");
+        let out = dir.path().join("out.csv");
+        let mut app = AppState::new();
+        app.search.focus = FocusTarget::Results;
+
+        handle_key_event(&mut app, KeyCode::Char('e'), KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+        assert_eq!(app.error.as_deref(), Some("No results to export"));
+
+        app.search.set_results(vec![
+            search_result(&a, "a.rs", 1),
+            search_result(&b, "b.md", 2),
+        ]);
+        app.ui.grouping = crate::state::GroupingMode::Extension;
+        handle_key_event(&mut app, KeyCode::Char('e'), KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Export);
+        for c in out.to_string_lossy().chars() {
+            handle_key_event(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        assert_eq!(app.ui.export.format, crate::export::ExportFormat::Csv);
+        assert!(buffer_text(&mut app, 100, 24).contains("export 2 results"));
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(app
+            .error
+            .as_deref()
+            .unwrap()
+            .starts_with("✓ Exported 2 results (csv)"));
+        let csv = std::fs::read_to_string(&out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "group,path,name,size,mtime");
+        assert_eq!(
+            lines[1],
+            format!("ext: rs,{},a.rs,1,1700000000", a.display())
+        );
+        assert!(lines[2].starts_with("ext: md,"));
+
+        // A destination that cannot be written keeps the prompt open.
+        handle_key_event(&mut app, KeyCode::Char('e'), KeyModifiers::NONE);
+        for c in "/nonexistent".chars() {
+            handle_key_event(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Export);
+        assert!(app.ui.export.error.is_some());
+        handle_key_event(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+    }
+
+    #[test]
+    fn pick_mode_prints_on_enter_and_blocks_editor_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let subdir = dir.path().join("src");
+        std::fs::create_dir_all(&subdir).unwrap();
+        let file = dir.path().join("README.md");
+        std::fs::write(&file, "readme").unwrap();
+
+        let mut app = AppState::new();
+        app.pick_mode = true;
+        app.search.set_results(vec![
+            search_result(&file, "README.md", 6),
+            search_result(&subdir, "src", 0),
+        ]);
+        app.search.focus = FocusTarget::Results;
+
+        handle_key_event(&mut app, KeyCode::Char('o'), KeyModifiers::NONE);
+        assert!(app.open_in_editor.is_none());
+        assert!(!app.should_quit());
+        handle_key_event(&mut app, KeyCode::Char('O'), KeyModifiers::SHIFT);
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(!crate::kriya::filtered_kriyas(&app)
+            .iter()
+            .any(|kriya| kriya.id == crate::kriya::KriyaId::OpenWith
+                || kriya.id == crate::kriya::KriyaId::OpenOrEnter));
+
+        // Directories are picked too rather than entered.
+        handle_key_event(&mut app, KeyCode::Char('j'), KeyModifiers::NONE);
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(app.ksetra.is_global());
+        assert_eq!(
+            app.print_on_exit,
+            vec![subdir.to_string_lossy().to_string()]
+        );
         assert!(app.should_quit());
+
+        // Enter in the query input picks the selected result as well.
+        let mut app = AppState::new();
+        app.pick_mode = true;
+        app.search
+            .set_results(vec![search_result(&file, "README.md", 6)]);
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.print_on_exit, vec![file.to_string_lossy().to_string()]);
+    }
+
+    #[test]
+    fn number_keys_jump_to_bookmarks_and_b_bookmarks_the_ksetra() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        let nested = project.join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        let store_path = dir.path().join("bookmarks.json");
+
+        let mut app = AppState::new();
+        app.search.focus = FocusTarget::Results;
+        bookmark_ksetra(&mut app, &store_path);
+        assert!(app.error.as_deref().unwrap().contains("ksetra is global"));
+
+        app.ksetra.push(project.clone());
+        app.ksetra.push(nested.clone());
+        bookmark_ksetra(&mut app, &store_path);
+        assert_eq!(app.error.as_deref(), Some("✓ Bookmarked 'src' as 1"));
+        assert_eq!(
+            BookmarkStore::load(&store_path).unwrap().bookmarks,
+            app.bookmarks
+        );
+
+        while app.ksetra.pop().is_some() {}
+        app.ksetra.push(project.clone());
+        handle_key_event(&mut app, KeyCode::Char('2'), KeyModifiers::NONE);
+        assert!(app.error.as_deref().unwrap().contains("no bookmark 2"));
+        assert_eq!(app.ksetra.current(), Some(&project));
+
+        handle_key_event(&mut app, KeyCode::Char('1'), KeyModifiers::NONE);
+        assert_eq!(app.ksetra.current(), Some(&nested));
+        assert_eq!(app.ksetra.depth(), 1);
+        assert_eq!(
+            app.smriti_events.last().unwrap().action,
+            SmritiAction::Enter
+        );
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn open_with_records_smriti_usage_only_after_a_launch() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.md");
+        std::fs::write(&file, "# notes\n").unwrap();
+        let path = file.to_string_lossy().to_string();
+        let mut app = AppState::new();
+
+        let missing =
+            crate::state::OpenWithTarget::Application("vicaya-surely-missing-app".to_string());
+        open_with_target(&path, &missing, &mut app);
+        assert!(app.error.as_deref().unwrap().starts_with("Failed to open"));
+        assert!(app.smriti_events.is_empty());
+
+        let launched = crate::state::OpenWithTarget::Application("true".to_string());
+        open_with_target(&path, &launched, &mut app);
+        assert!(app.error.as_deref().unwrap().starts_with("✓ Opened"));
+        assert_eq!(app.smriti_events.len(), 1);
+        assert_eq!(app.smriti_events[0].action, SmritiAction::Open);
+    }
+
+    #[test]
+    fn startup_scope_prompt_applies_or_dismisses_the_suggestion() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        let prompt = AppMode::Confirm(crate::state::Action::SetKsetra(project.clone()));
+
+        let mut app = AppState::new();
+        app.mode = prompt.clone();
+        assert!(buffer_text(&mut app, 100, 28).contains("Set ksetra to"));
+        handle_key_event(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(app.mode, prompt);
+        handle_key_event(&mut app, KeyCode::Char('y'), KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+        assert_eq!(app.ksetra.current(), Some(&project));
+        assert!(!app.should_quit());
+
+        let mut app = AppState::new();
+        app.mode = prompt;
+        handle_key_event(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(app.ksetra.is_global());
+    }
+
+    #[test]
+    fn printed_paths_use_the_requested_terminator() {
+        let paths = vec!["/a b/c.txt".to_string(), "/d".to_string()];
+        let mut out = Vec::new();
+        print_paths(&mut out, &paths, false).unwrap();
+        assert_eq!(out, b"/a b/c.txt\n/d\n");
+
+        let mut out = Vec::new();
+        print_paths(&mut out, &paths, true).unwrap();
+        assert_eq!(out, b"/a b/c.txt\0/d\0");
+
+        let mut out = Vec::new();
+        print_paths(&mut out, &[], true).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn truncated_results_are_labelled_partial() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("README.md");
+        let mut app = AppState::new();
+        app.search
+            .set_results(vec![search_result(&file, "README.md", 6)]);
+
+        assert!(!buffer_text(&mut app, 120, 30).contains("partial results"));
+
+        app.search.truncated = true;
+        assert!(buffer_text(&mut app, 120, 30).contains("phala (1)  partial results"));
+    }
+
+    #[test]
+    fn empty_results_offer_suggestions() {
+        let mut app = AppState::new();
+        app.search.query = "cnofig".to_string();
+        app.search.suggestions = vec!["config.toml".to_string(), "config.rs".to_string()];
+
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("No matches. Did you mean:"), "{text}");
+        assert!(text.contains("config.toml"), "{text}");
+        assert!(text.contains("config.rs"), "{text}");
+
+        app.search.is_searching = true;
+        assert!(!buffer_text(&mut app, 120, 30).contains("Did you mean"));
+    }
+
+    #[test]
+    fn ghost_text_completes_the_last_word_and_right_accepts_it() {
+        let mut app = AppState::new();
+        app.search.set_query("ext:rs Carg".to_string());
+        app.search.completion = Some("cargo.toml".to_string());
+        assert_eq!(app.search.ghost_text(), Some("o.toml"));
+        assert!(buffer_text(&mut app, 120, 30).contains("Cargo.toml"));
+
+        // Not mid-query, and not once the word stops matching.
+        handle_key_event(&mut app, KeyCode::Left, KeyModifiers::NONE);
+        assert_eq!(app.search.ghost_text(), None);
+        handle_key_event(&mut app, KeyCode::Right, KeyModifiers::NONE);
+        handle_key_event(&mut app, KeyCode::Char('x'), KeyModifiers::NONE);
+        assert_eq!(app.search.ghost_text(), None);
+        handle_key_event(&mut app, KeyCode::Backspace, KeyModifiers::NONE);
+
+        handle_key_event(&mut app, KeyCode::Right, KeyModifiers::NONE);
+        assert_eq!(app.search.query, "ext:rs Cargo.toml");
+        assert_eq!(app.search.cursor_position, app.search.query.len());
+        assert_eq!(app.search.ghost_text(), None);
+
+        app.search.set_query("ext:".to_string());
+        app.search.completion = Some("ext:rs.md".to_string());
+        assert_eq!(app.search.ghost_text(), None);
+    }
+
+    #[test]
+    fn application_bundles_get_a_badge() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = AppState::new();
+        app.search.set_results(vec![
+            search_result(&dir.path().join("Safari.app"), "Safari.app", 6),
+            search_result(&dir.path().join("notes.txt"), "notes.txt", 5),
+        ]);
+
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("Safari.app ◆app"), "{text}");
+        assert!(!text.contains("notes.txt ◆app"), "{text}");
+    }
+
+    #[test]
+    fn unreadable_results_are_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut locked = search_result(&dir.path().join("secret.key"), "secret.key", 6);
+        (locked.uid, locked.gid, locked.mode) = (u32::MAX, u32::MAX, 0o100_600);
+        let mut app = AppState::new();
+        app.search.set_results(vec![
+            locked,
+            search_result(&dir.path().join("notes.txt"), "notes.txt", 5),
+        ]);
+
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(!text.contains("notes.txt ⊘"), "{text}");
+        // Root reads everything, so only other users see the indicator.
+        let root = vicaya_core::permissions::current_user_can_read(u32::MAX, u32::MAX, 0o100_000);
+        assert_eq!(text.contains("secret.key ⊘unreadable"), !root, "{text}");
+    }
+
+    #[test]
+    fn directory_results_are_marked_in_every_drishti() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut app = AppState::new();
+        app.search.set_results(vec![
+            SearchResult {
+                is_dir: true,
+                ..search_result(&dir.path().join("fixtures"), "fixtures", 0)
+            },
+            search_result(&dir.path().join("fixtures.rs"), "fixtures.rs", 6),
+        ]);
+
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("▣ fixtures/"), "{text}");
+        assert!(!text.contains("fixtures.rs/"), "{text}");
+    }
+
+    #[test]
+    fn results_carry_kind_icons_and_symlinks_are_found_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("main.rs"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("main.rs"), dir.path().join("lib.rs")).unwrap();
+        let mut app = AppState::new();
+        app.search.set_results(vec![
+            search_result(&dir.path().join("main.rs"), "main.rs", 6),
+            search_result(&dir.path().join("lib.rs"), "lib.rs", 5),
+            SearchResult {
+                is_dir: true,
+                ..search_result(&dir.path().join("src"), "src", 4)
+            },
+        ]);
+
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("λ main.rs"), "{text}");
+        assert!(text.contains("↪ lib.rs"), "{text}");
+        assert!(text.contains("▣ src/"), "{text}");
+
+        app.ui.icons = vicaya_core::config::IconStyle::Ascii;
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("c main.rs"), "{text}");
+        assert!(text.contains("l lib.rs"), "{text}");
+        assert!(text.contains("d src/"), "{text}");
+    }
+
+    #[test]
+    fn malformed_niyamas_show_in_the_prashna_and_stay_out_of_the_term() {
+        let mut app = AppState::new();
+        app.search.query = "report ext:rs size:>10xb".to_string();
+
+        let parsed = app.parsed_query();
+        assert_eq!(parsed.term, "report");
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("niyama: ext:rs size:>10xb"), "{text}");
+        assert!(text.contains("expected a size, as in size:>10mb"), "{text}");
+    }
+
+    #[test]
+    fn root_stats_overlay_requests_and_lists_each_root() {
+        use vicaya_core::ipc::RootStats;
+
+        let mut app = AppState::new();
+        app.search.focus = FocusTarget::Results;
+
+        handle_key_event(&mut app, KeyCode::Char('I'), KeyModifiers::SHIFT);
+        assert_eq!(app.mode, AppMode::RootStats);
+        assert!(std::mem::take(&mut app.root_stats_requested));
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("Fetching index statistics"), "{text}");
+
+        app.root_stats = Some(vec![
+            RootStats {
+                root: "/Users/me/src".to_string(),
+                files: 1_200,
+                dirs: 80,
+                memory_bytes: 3 * 1_048_576,
+                last_scan: None,
+                ..RootStats::default()
+            },
+            RootStats {
+                root: "/Users/me/Documents".to_string(),
+                files: 40,
+                memory_bytes: 1_048_576,
+                total_bytes: 5 * 1_048_576,
+                ..RootStats::default()
+            },
+        ]);
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("index by root"), "{text}");
+        assert!(text.contains("/Users/me/src"), "{text}");
+        assert!(
+            text.contains("1200 files · 80 dirs · 3.0 MB (75%)"),
+            "{text}"
+        );
+        assert!(text.contains("5.0 MB on disk"), "{text}");
+        assert!(text.contains("scanned never"), "{text}");
+
+        handle_key_event(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+    }
+
+    #[test]
+    fn score_inspector_shows_the_selected_breakdown() {
+        use vicaya_core::ipc::{MatchStrategy, ScoreExplanation};
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut result = search_result(&dir.path().join("config.rs"), "config.rs", 6);
+        result.explain = Some(ScoreExplanation {
+            strategy: MatchStrategy::Prefix,
+            base_score: 0.92,
+            context_penalty: -60,
+            scope_boost: 119,
+            path_depth: 4,
+            ..ScoreExplanation::default()
+        });
+        let mut app = AppState::new();
+        app.search.set_results(vec![result]);
+        app.search.focus = FocusTarget::Results;
+
+        handle_key_event(&mut app, KeyCode::Char('i'), KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Inspect);
+        assert!(app.search.explain, "searches now ask for breakdowns");
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("score · config.rs"), "{text}");
+        assert!(text.contains("prefix"), "{text}");
+        assert!(text.contains("-60"), "{text}");
+        assert!(text.contains("+119"), "{text}");
+
+        handle_key_event(&mut app, KeyCode::Esc, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(!app.search.explain);
+
+        app.view = crate::state::ViewKind::Smriti;
+        app.toggle_inspector();
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(app.error.is_some());
+    }
+
+    #[test]
+    fn verification_flags_stale_and_missing_results() {
+        let dir = tempfile::tempdir().unwrap();
+        let indexed = |name: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, "hello").unwrap();
+            let mtime = std::fs::metadata(&path)
+                .unwrap()
+                .modified()
+                .unwrap()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs() as i64;
+            SearchResult {
+                mtime,
+                indexed_at: mtime,
+                content_hash: None,
+                ..search_result(&path, name, 5)
+            }
+        };
+        let current = indexed("current.txt");
+        let grown = SearchResult {
+            size: 2,
+            ..indexed("grown.txt")
+        };
+        let gone = indexed("gone.txt");
+        std::fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        let content_match = search_result(&dir.path().join("nowhere.txt"), "nowhere.txt", 1);
+
+        let mut app = AppState::new();
+        app.search.focus = crate::state::FocusTarget::Results;
+        app.search
+            .set_results(vec![current, grown, gone, content_match]);
+        let visit_all = |app: &mut AppState| {
+            for _ in 0..4 {
+                let sizes_indexed = app.sizes_indexed();
+                app.search.verify_selected(sizes_indexed);
+                handle_results_keys(app, KeyCode::Char('j'), KeyModifiers::NONE);
+            }
+        };
+        visit_all(&mut app);
+        assert!(app.search.freshness.is_empty(), "verification is opt-in");
+
+        handle_results_keys(&mut app, KeyCode::Char('V'), KeyModifiers::SHIFT);
+        app.search.select_first();
+        visit_all(&mut app);
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(text.contains("verify"), "{text}");
+        assert!(!text.contains("current.txt ≠"), "{text}");
+        assert!(text.contains("grown.txt ≠stale"), "{text}");
+        assert!(text.contains("gone.txt ✗gone"), "{text}");
+        // Entries without `indexed_at` are not index rows and are left alone.
+        assert!(!text.contains("nowhere.txt ✗"), "{text}");
+
+        handle_results_keys(&mut app, KeyCode::Char('V'), KeyModifiers::SHIFT);
+        assert!(app.search.freshness.is_empty());
+
+        // A lite daemon indexes no sizes, so only mtimes can go stale.
+        app.daemon_status = Some(crate::client::DaemonStatus {
+            pid: 0,
+            build: Default::default(),
+            indexed_files: 3,
+            trigram_count: 0,
+            arena_size: 0,
+            last_updated: 0,
+            reconciling: false,
+            pending_watcher_events: 0,
+            journal_entries: 0,
+            reconcile_progress: None,
+            reconcile_scan: None,
+            index_cap: None,
+            index_allocated_bytes: 0,
+            state_allocated_bytes: 0,
+            warmup_ms: None,
+            journal_fsync: None,
+            last_reconcile: None,
+            next_reconcile: None,
+            index_profile: vicaya_core::config::IndexProfile::Lite,
+        });
+        handle_results_keys(&mut app, KeyCode::Char('V'), KeyModifiers::SHIFT);
+        app.search.select_first();
+        visit_all(&mut app);
+        let text = buffer_text(&mut app, 120, 30);
+        assert!(!text.contains("grown.txt ≠"), "{text}");
+        assert!(text.contains("gone.txt ✗gone"), "{text}");
+    }
+
+    #[test]
+    fn opening_a_ghost_result_reports_it_instead_of_exiting() {
+        let dir = tempfile::tempdir().unwrap();
+        let ghost = dir.path().join("ghost.txt");
+        let mut app = AppState::new();
+        app.search
+            .set_results(vec![search_result(&ghost, "ghost.txt", 3)]);
+        app.search.focus = FocusTarget::Results;
+
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert!(!app.should_quit());
+        assert!(app.open_in_editor.is_none());
+        assert!(app.error.as_deref().unwrap().contains("no longer exists"));
+        assert_eq!(app.missing_paths, vec![ghost.to_string_lossy().to_string()]);
+        assert!(buffer_text(&mut app, 120, 30).contains("ghost.txt ✗gone"));
+
+        // The preview worker finding the same ghost does not report it twice.
+        app.missing_paths.clear();
+        app.report_missing(ghost.to_string_lossy().to_string());
+        assert!(app.missing_paths.is_empty());
     }
 
     #[test]
@@ -1340,6 +2783,14 @@ mod tests {
             score: 1.0,
             size: 0,
             mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
         };
 
         assert_eq!(
@@ -1396,6 +2847,85 @@ mod tests {
         assert_eq!(app.search.focus, FocusTarget::Input);
     }
 
+    #[test]
+    fn breadcrumbs_jump_to_any_ksetra_level_by_click_or_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_path_buf();
+        let mut app = AppState::with_startup_scope(Some(root.clone()));
+        app.ksetra.push(root.join("src"));
+        app.ksetra.push(root.join("src/ui"));
+        app.search.focus = FocusTarget::Results;
+
+        let text = buffer_text(&mut app, 200, 30);
+        let targets = app.ui.breadcrumb_targets.clone();
+        let shown = |target: &crate::state::BreadcrumbTarget| -> String {
+            let line = text.lines().nth(target.row as usize).unwrap();
+            line.chars()
+                .skip(target.columns.start as usize)
+                .take(target.columns.len())
+                .collect()
+        };
+        assert_eq!(shown(&targets[1]), "src");
+        assert_eq!(shown(&targets[2]), "ui");
+        assert_eq!(
+            targets.iter().map(|t| t.depth).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+        let click = |column, row| MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        };
+        handle_mouse_event(&mut app, click(targets[1].columns.start, targets[1].row));
+        assert_eq!(app.ksetra.current(), Some(&root.join("src")));
+        handle_mouse_event(
+            &mut app,
+            click(targets[0].columns.start, targets[0].row + 5),
+        );
+        assert_eq!(app.ksetra.depth(), 2);
+
+        handle_key_event(&mut app, KeyCode::Char('1'), KeyModifiers::ALT);
+        assert_eq!(app.ksetra.current(), Some(&root));
+        app.ksetra.push(root.join("src"));
+        handle_key_event(&mut app, KeyCode::Char('~'), KeyModifiers::SHIFT);
+        assert!(app.ksetra.is_global());
+
+        buffer_text(&mut app, 200, 30);
+        assert_eq!(app.ui.breadcrumb_targets.len(), 1);
+        assert_eq!(app.ui.breadcrumb_targets[0].depth, 0);
+    }
+
+    #[test]
+    fn preview_resizes_and_stacks_below_the_phala() {
+        let row_of = |text: &str, needle: &str| text.lines().position(|line| line.contains(needle));
+        let mut app = AppState::new();
+
+        handle_key_event(&mut app, KeyCode::Char('>'), KeyModifiers::SHIFT);
+        assert_eq!(app.search.query, ">");
+        assert_eq!(app.ui.preview_percent, 45);
+        app.search.clear_query();
+
+        app.search.focus = FocusTarget::Results;
+        handle_key_event(&mut app, KeyCode::Char('>'), KeyModifiers::SHIFT);
+        assert_eq!(app.ui.preview_percent, 50);
+        for _ in 0..20 {
+            handle_key_event(&mut app, KeyCode::Char('<'), KeyModifiers::SHIFT);
+        }
+        assert_eq!(app.ui.preview_percent, 20);
+
+        let text = buffer_text(&mut app, 100, 30);
+        assert_eq!(row_of(&text, "phala ("), row_of(&text, "purvadarshana"));
+
+        handle_key_event(&mut app, KeyCode::Char('|'), KeyModifiers::SHIFT);
+        assert_eq!(app.ui.split, SplitLayout::Stacked);
+        let text = buffer_text(&mut app, 100, 30);
+        assert!(
+            row_of(&text, "phala (") < row_of(&text, "purvadarshana"),
+            "{text}"
+        );
+    }
+
     #[test]
     fn narrow_terminal_control_overlays_do_not_panic() {
         let dir = tempfile::tempdir().unwrap();
@@ -1471,7 +3001,7 @@ mod tests {
         run_kriya_action(&mut app, crate::kriya::KriyaId::PrintPath);
         assert_eq!(
             app.print_on_exit,
-            Some(selected.to_string_lossy().to_string())
+            vec![selected.to_string_lossy().to_string()]
         );
         assert!(app.should_quit());
     }
@@ -1545,6 +3075,35 @@ mod tests {
         assert!(app.preview.search_query.is_empty());
     }
 
+    #[test]
+    fn preview_search_across_results_requests_counts_until_cleared() {
+        let mut app = AppState::new();
+        app.search.focus = FocusTarget::Preview;
+
+        handle_key_event(&mut app, KeyCode::Char('/'), KeyModifiers::NONE);
+        handle_key_event(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+        assert!(app.preview.search_all);
+        for ch in "todo".chars() {
+            handle_key_event(&mut app, KeyCode::Char(ch), KeyModifiers::NONE);
+        }
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.preview.search_query, "todo");
+        assert!(std::mem::take(&mut app.preview.counts_requested));
+
+        app.preview.match_counts.insert("/a.txt".to_string(), 2);
+        app.preview.match_counts.insert("/b.txt".to_string(), 0);
+        assert_eq!(app.preview.matching_results(), 1);
+
+        // New results are counted again.
+        app.preview.request_counts();
+        assert!(app.preview.match_counts.is_empty());
+        assert!(app.preview.counts_requested);
+
+        handle_key_event(&mut app, KeyCode::Char('l'), KeyModifiers::CONTROL);
+        assert!(!app.preview.search_all);
+        assert!(!app.preview.counts_requested);
+    }
+
     #[test]
     fn trigger_search_parses_niyamas_and_scopes_worker_command() {
         let dir = tempfile::tempdir().unwrap();
@@ -1587,6 +3146,125 @@ mod tests {
         assert!(app.search.is_searching);
     }
 
+    #[test]
+    fn dot_toggles_hidden_entries_unless_the_prashna_says_otherwise() {
+        let mut app = AppState::new();
+        let (tx, rx) = mpsc::channel();
+        let (mut search_id, mut active_search_id) = (0, 0);
+        let mut last = std::time::Instant::now();
+        let mut sent_hidden = |app: &mut AppState| {
+            trigger_search(&tx, app, &mut search_id, &mut active_search_id, &mut last);
+            match rx.try_recv().unwrap() {
+                WorkerCommand::Search { hidden, .. } => hidden,
+                _ => panic!("expected search command"),
+            }
+        };
+
+        handle_key_event(&mut app, KeyCode::Char('.'), KeyModifiers::NONE);
+        assert_eq!(app.search.query, ".");
+        assert!(sent_hidden(&mut app));
+        app.search.clear_query();
+
+        app.search.focus = FocusTarget::Results;
+        handle_key_event(&mut app, KeyCode::Char('.'), KeyModifiers::NONE);
+        assert!(!app.search.show_hidden);
+        assert!(!sent_hidden(&mut app));
+        assert!(buffer_text(&mut app, 100, 24).contains("hidden:off"));
+
+        app.search.set_query("bashrc hidden:yes".to_string());
+        assert!(sent_hidden(&mut app));
+    }
+
+    #[test]
+    fn tulana_compares_a_second_ksetra_with_the_same_prashna() {
+        let dir = tempfile::tempdir().unwrap();
+        let live = dir.path().join("live");
+        let backup = dir.path().join("backup");
+        std::fs::create_dir_all(&live).unwrap();
+        std::fs::create_dir_all(&backup).unwrap();
+
+        let mut app = AppState::new();
+        app.search.focus = FocusTarget::Results;
+        handle_key_event(&mut app, KeyCode::Char('C'), KeyModifiers::SHIFT);
+        assert_eq!(app.mode, AppMode::Search);
+        assert!(app.error.as_deref().unwrap().contains("Set a ksetra first"));
+
+        let mut app = AppState::with_startup_scope(Some(live.clone()));
+        app.search.set_query("main ext:rs".to_string());
+        app.search.focus = FocusTarget::Results;
+        handle_key_event(&mut app, KeyCode::Char('C'), KeyModifiers::SHIFT);
+        assert_eq!(app.mode, AppMode::KsetraInput);
+        assert!(buffer_text(&mut app, 100, 28).contains("tulana ksetra"));
+        for c in backup.to_string_lossy().chars() {
+            handle_key_event(&mut app, KeyCode::Char(c), KeyModifiers::NONE);
+        }
+        handle_key_event(&mut app, KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(app.mode, AppMode::Search);
+        assert_eq!(app.ksetra.current(), Some(&live));
+        assert_eq!(app.tulana.as_ref().unwrap().scope, backup);
+        assert_eq!(app.search.focus, FocusTarget::Tulana);
+
+        let (tx, rx) = mpsc::channel();
+        let (mut search_id, mut active_search_id) = (0, 0);
+        let mut last = std::time::Instant::now();
+        assert!(trigger_search(
+            &tx,
+            &mut app,
+            &mut search_id,
+            &mut active_search_id,
+            &mut last,
+        ));
+        match rx.try_recv().unwrap() {
+            WorkerCommand::TulanaSearch {
+                id,
+                query,
+                limit,
+                scope,
+                niyamas,
+                ..
+            } => {
+                assert_eq!(id, app.tulana.as_ref().unwrap().search_id);
+                assert_eq!(query, "main");
+                assert_eq!(limit, TULANA_RESULT_LIMIT);
+                assert_eq!(scope, backup);
+                assert_eq!(niyamas.len(), 1);
+            }
+            _ => panic!("expected tulana search command"),
+        }
+        match rx.try_recv().unwrap() {
+            WorkerCommand::Search {
+                id, filter_scope, ..
+            } => {
+                assert_eq!(id, active_search_id);
+                assert_eq!(filter_scope.as_ref(), Some(&live));
+            }
+            _ => panic!("expected search command"),
+        }
+
+        app.search.set_results(vec![
+            search_result(&live.join("main.rs"), "main.rs", 1),
+            search_result(&live.join("new_main.rs"), "new_main.rs", 1),
+        ]);
+        app.tulana.as_mut().unwrap().set_results(vec![search_result(
+            &backup.join("main.rs"),
+            "main.rs",
+            1,
+        )]);
+        let screen = buffer_text(&mut app, 160, 24);
+        assert!(screen.contains("tulana (1)"));
+        assert!(screen.contains("new_main.rs ◇only here"));
+        assert!(screen.contains("only here:1"));
+        assert!(screen.contains("only here:0"));
+
+        handle_key_event(&mut app, KeyCode::Tab, KeyModifiers::NONE);
+        assert_eq!(app.search.focus, FocusTarget::Input);
+        handle_key_event(&mut app, KeyCode::BackTab, KeyModifiers::NONE);
+        assert_eq!(app.search.focus, FocusTarget::Tulana);
+        handle_key_event(&mut app, KeyCode::Char('C'), KeyModifiers::SHIFT);
+        assert!(app.tulana.is_none());
+        assert_eq!(app.search.focus, FocusTarget::Results);
+    }
+
     #[test]
     fn trigger_search_does_not_leave_tui_stuck_when_worker_is_gone() {
         let (tx, rx) = mpsc::channel();
@@ -1633,6 +3311,7 @@ mod tests {
         assert!(screen.contains("prashna: cargo"));
         assert!(screen.contains("Cargo.toml"));
         assert!(screen.contains("purvadarshana"));
+        assert!(buffer_text(&mut app, 180, 30).contains("⟳ reconciling 42%"));
 
         app.mode = AppMode::Help;
         assert!(buffer_text(&mut app, 100, 28).contains("Help"));
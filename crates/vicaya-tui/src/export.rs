@@ -0,0 +1,198 @@
+//! Results export (`e`): the current result list as plain paths, CSV, or JSON.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use vicaya_index::SearchResult;
+
+/// Output format for an export.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One path per line.
+    Plain,
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            ExportFormat::Plain => "plain",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            ExportFormat::Plain => ExportFormat::Csv,
+            ExportFormat::Csv => ExportFormat::Json,
+            ExportFormat::Json => ExportFormat::Plain,
+        }
+    }
+
+    /// The format implied by a destination's extension, if any.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_ascii_lowercase().as_str() {
+            "csv" => Some(ExportFormat::Csv),
+            "json" => Some(ExportFormat::Json),
+            "txt" => Some(ExportFormat::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// Where an export goes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportTarget {
+    Clipboard,
+    File(PathBuf),
+}
+
+impl ExportTarget {
+    /// An empty destination (or `clipboard`) copies; anything else is a
+    /// file path, with `~` expanded.
+    pub fn parse(input: &str) -> Self {
+        let input = input.trim();
+        if input.is_empty() || input.eq_ignore_ascii_case("clipboard") {
+            ExportTarget::Clipboard
+        } else {
+            ExportTarget::File(vicaya_core::paths::expand_user_path(Path::new(input)))
+        }
+    }
+}
+
+/// One exported result; `group` is the varga header it was listed under.
+#[derive(Debug, Serialize)]
+struct ExportRow<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<&'a str>,
+    path: &'a str,
+    name: &'a str,
+    size: u64,
+    mtime: i64,
+}
+
+/// Render `results` in list order. `groups`, when present, holds each
+/// result's varga label and adds a `group` column.
+pub fn render(results: &[SearchResult], groups: Option<&[String]>, format: ExportFormat) -> String {
+    let rows = results.iter().enumerate().map(|(idx, result)| ExportRow {
+        group: groups
+            .and_then(|groups| groups.get(idx))
+            .map(String::as_str),
+        path: &result.path,
+        name: &result.name,
+        size: result.size,
+        mtime: result.mtime,
+    });
+
+    match format {
+        ExportFormat::Plain => rows.map(|row| format!("{}\n", row.path)).collect(),
+        ExportFormat::Csv => {
+            let mut out = String::new();
+            if groups.is_some() {
+                out.push_str("group,");
+            }
+            out.push_str("path,name,size,mtime\n");
+            for row in rows {
+                if let Some(group) = row.group {
+                    out.push_str(&csv_field(group));
+                    out.push(',');
+                }
+                out.push_str(&format!(
+                    "{},{},{},{}\n",
+                    csv_field(row.path),
+                    csv_field(row.name),
+                    row.size,
+                    row.mtime
+                ));
+            }
+            out
+        }
+        ExportFormat::Json => {
+            let rows: Vec<ExportRow> = rows.collect();
+            let mut out = serde_json::to_string_pretty(&rows).unwrap_or_default();
+            out.push('\n');
+            out
+        }
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote, or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(path: &str, size: u64) -> SearchResult {
+        SearchResult {
+            path: path.to_string(),
+            name: Path::new(path)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string(),
+            score: 1.0,
+            size,
+            mtime: 1_700_000_000,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn results_render_as_plain_csv_and_json() {
+        let results = vec![result("/w/src/main.rs", 12), result("/w/a,b \"q\".md", 3)];
+
+        assert_eq!(
+            render(&results, None, ExportFormat::Plain),
+            "/w/src/main.rs\n/w/a,b \"q\".md\n"
+        );
+        assert_eq!(
+            render(&results, None, ExportFormat::Csv),
+            "path,name,size,mtime\n\
+             /w/src/main.rs,main.rs,12,1700000000\n\
+             \"/w/a,b \"\"q\"\".md\",\"a,b \"\"q\"\".md\",3,1700000000\n"
+        );
+
+        let groups = vec!["ext: rs".to_string(), "ext: md".to_string()];
+        let csv = render(&results, Some(&groups), ExportFormat::Csv);
+        assert!(csv.starts_with("group,path,name,size,mtime\next: rs,/w/src/main.rs,"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&results, Some(&groups), ExportFormat::Json)).unwrap();
+        assert_eq!(json[0]["group"], "ext: rs");
+        assert_eq!(json[1]["name"], "a,b \"q\".md");
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&results, None, ExportFormat::Json)).unwrap();
+        assert!(json[0].get("group").is_none());
+    }
+
+    #[test]
+    fn destinations_and_formats_follow_the_prompt_input() {
+        assert_eq!(ExportTarget::parse("  "), ExportTarget::Clipboard);
+        assert_eq!(ExportTarget::parse("Clipboard"), ExportTarget::Clipboard);
+        assert_eq!(
+            ExportTarget::parse("/tmp/out.csv"),
+            ExportTarget::File(PathBuf::from("/tmp/out.csv"))
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("out.JSON")),
+            Some(ExportFormat::Json)
+        );
+        assert_eq!(ExportFormat::from_path(Path::new("out")), None);
+        assert_eq!(ExportFormat::Json.next(), ExportFormat::Plain);
+    }
+}
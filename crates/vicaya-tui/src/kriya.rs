@@ -7,13 +7,28 @@ pub enum KriyaId {
     OpenOrEnter,
     CopyPath,
     Reveal,
+    OpenWith,
     PrintPath,
+    ExportResults,
+    ToggleMark,
+    BookmarkKsetra,
     ForgetSmriti,
     TogglePreview,
     ToggleGrouping,
+    GrowPreview,
+    ShrinkPreview,
+    ToggleSplitLayout,
+    ToggleHidden,
+    ToggleLargestFirst,
     PopKsetra,
     SetKsetra,
+    ToggleTulana,
+    ToggleVerify,
+    InspectScore,
+    ShowRootStats,
     TogglePreviewLineNumbers,
+    LoadMorePreview,
+    TogglePreviewHex,
     ClearPreviewSearch,
     Quit,
 }
@@ -54,23 +69,50 @@ fn available_kriyas(app: &AppState) -> Vec<KriyaItem> {
     let mut items = Vec::new();
 
     if let (Some(_), Some(is_dir)) = (selected_path, selected_is_dir) {
-        items.push(KriyaItem {
-            id: KriyaId::OpenOrEnter,
-            label: if is_dir {
-                "Enter ksetra"
-            } else {
-                "Open in editor"
-            },
-            keys: if is_dir { "Enter/o, l/→" } else { "Enter/o" },
-            hint: if is_dir {
-                "Push scope to directory"
-            } else {
-                "Open in $EDITOR"
-            },
-            destructive: false,
-        });
+        if app.pick_mode {
+            items.push(KriyaItem {
+                id: KriyaId::PrintPath,
+                label: "Pick",
+                keys: "Enter/p",
+                hint: "Print marked paths (or this one) and exit",
+                destructive: false,
+            });
+        }
+
+        if !app.pick_mode || is_dir {
+            items.push(KriyaItem {
+                id: KriyaId::OpenOrEnter,
+                label: if is_dir {
+                    "Enter ksetra"
+                } else {
+                    "Open in editor"
+                },
+                keys: match (is_dir, app.pick_mode) {
+                    (true, true) => "l/→",
+                    (true, false) => "Enter/o, l/→",
+                    (false, _) => "Enter/o",
+                },
+                hint: if is_dir {
+                    "Push scope to directory"
+                } else {
+                    "Open in $EDITOR"
+                },
+                destructive: false,
+            });
+        }
 
         items.extend([
+            KriyaItem {
+                id: KriyaId::ToggleMark,
+                label: if selected_path.is_some_and(|p| app.search.is_marked(p)) {
+                    "Unmark"
+                } else {
+                    "Mark"
+                },
+                keys: "Space",
+                hint: "Select for printing several paths at once",
+                destructive: false,
+            },
             KriyaItem {
                 id: KriyaId::CopyPath,
                 label: "Copy path",
@@ -85,15 +127,34 @@ fn available_kriyas(app: &AppState) -> Vec<KriyaItem> {
                 hint: "Reveal in Finder / file manager",
                 destructive: false,
             },
-            KriyaItem {
-                id: KriyaId::PrintPath,
-                label: "Print path",
-                keys: "p",
-                hint: "Print path and exit (shell integration)",
-                destructive: false,
-            },
         ]);
 
+        if !app.pick_mode {
+            items.extend([
+                KriyaItem {
+                    id: KriyaId::OpenWith,
+                    label: "Open with…",
+                    keys: "O",
+                    hint: "Choose an application from [associations]",
+                    destructive: false,
+                },
+                KriyaItem {
+                    id: KriyaId::PrintPath,
+                    label: "Print path",
+                    keys: "p",
+                    hint: "Print marked paths (or this one) and exit",
+                    destructive: false,
+                },
+                KriyaItem {
+                    id: KriyaId::ExportResults,
+                    label: "Export results",
+                    keys: "e",
+                    hint: "Write the result list to a file or clipboard (plain/CSV/JSON)",
+                    destructive: false,
+                },
+            ]);
+        }
+
         if app.view == ViewKind::Smriti {
             items.push(KriyaItem {
                 id: KriyaId::ForgetSmriti,
@@ -113,6 +174,13 @@ fn available_kriyas(app: &AppState) -> Vec<KriyaItem> {
             hint: "Go back toward global scope",
             destructive: false,
         });
+        items.push(KriyaItem {
+            id: KriyaId::BookmarkKsetra,
+            label: "Bookmark ksetra",
+            keys: "B",
+            hint: "Bind this scope to the next free 1-9 key",
+            destructive: false,
+        });
     }
 
     // Always available: direct ksetra input
@@ -124,6 +192,51 @@ fn available_kriyas(app: &AppState) -> Vec<KriyaItem> {
         destructive: false,
     });
 
+    if app.tulana.is_some() {
+        items.push(KriyaItem {
+            id: KriyaId::ToggleTulana,
+            label: "Close tulana",
+            keys: "C",
+            hint: "Close the comparison pane",
+            destructive: false,
+        });
+    } else if !app.ksetra.is_global() {
+        items.push(KriyaItem {
+            id: KriyaId::ToggleTulana,
+            label: "Compare ksetra (tulana)",
+            keys: "C",
+            hint: "Run the prashna in a second scope and flag files only in one",
+            destructive: false,
+        });
+    }
+
+    items.push(KriyaItem {
+        id: KriyaId::ToggleVerify,
+        label: if app.search.verify {
+            "Stop verifying results"
+        } else {
+            "Verify results"
+        },
+        keys: "V",
+        hint: "Stat the selected file and flag stale or missing entries",
+        destructive: false,
+    });
+
+    if selected.is_some()
+        && !matches!(
+            app.view,
+            ViewKind::Smriti | ViewKind::Antarvicaya | ViewKind::Tyakta
+        )
+    {
+        items.push(KriyaItem {
+            id: KriyaId::InspectScore,
+            label: "Explain score",
+            keys: "i",
+            hint: "Show how the selected result was ranked",
+            destructive: false,
+        });
+    }
+
     items.extend([
         KriyaItem {
             id: KriyaId::TogglePreview,
@@ -139,8 +252,66 @@ fn available_kriyas(app: &AppState) -> Vec<KriyaItem> {
             hint: "Toggle grouping (none/dir/ext)",
             destructive: false,
         },
+        KriyaItem {
+            id: KriyaId::ToggleSplitLayout,
+            label: "Toggle split layout",
+            keys: "|",
+            hint: "Preview beside or below the results",
+            destructive: false,
+        },
+        KriyaItem {
+            id: KriyaId::ShowRootStats,
+            label: "Index by root",
+            keys: "I",
+            hint: "Files, memory and last scan per index root",
+            destructive: false,
+        },
+        KriyaItem {
+            id: KriyaId::ToggleHidden,
+            label: if app.search.show_hidden {
+                "Hide hidden files"
+            } else {
+                "Show hidden files"
+            },
+            keys: ".",
+            hint: "Dotfiles and dot-directories in phala",
+            destructive: false,
+        },
     ]);
 
+    if app.view == ViewKind::Sthana {
+        items.push(KriyaItem {
+            id: KriyaId::ToggleLargestFirst,
+            label: if app.search.largest_first {
+                "Order by relevance"
+            } else {
+                "Largest directories first"
+            },
+            keys: "S",
+            hint: "Sort Sthana by the size of each directory's contents",
+            destructive: false,
+        });
+    }
+
+    if app.preview.is_visible {
+        items.extend([
+            KriyaItem {
+                id: KriyaId::GrowPreview,
+                label: "Grow purvadarshana",
+                keys: ">",
+                hint: "Give the preview pane more room",
+                destructive: false,
+            },
+            KriyaItem {
+                id: KriyaId::ShrinkPreview,
+                label: "Shrink purvadarshana",
+                keys: "<",
+                hint: "Give the results more room",
+                destructive: false,
+            },
+        ]);
+    }
+
     if app.preview.is_visible && !app.preview.lines.is_empty() {
         items.push(KriyaItem {
             id: KriyaId::TogglePreviewLineNumbers,
@@ -151,6 +322,26 @@ fn available_kriyas(app: &AppState) -> Vec<KriyaItem> {
         });
     }
 
+    if app.preview.is_visible && app.preview.path.is_some() {
+        items.push(KriyaItem {
+            id: KriyaId::TogglePreviewHex,
+            label: "Toggle hex view",
+            keys: "x",
+            hint: "Switch purvadarshana between hex dump and text",
+            destructive: false,
+        });
+    }
+
+    if app.preview.is_visible && app.preview.more_available {
+        items.push(KriyaItem {
+            id: KriyaId::LoadMorePreview,
+            label: "Load more preview",
+            keys: "L",
+            hint: "Append the next chunk of a truncated file",
+            destructive: false,
+        });
+    }
+
     if !app.preview.search_query.trim().is_empty() {
         items.push(KriyaItem {
             id: KriyaId::ClearPreviewSearch,
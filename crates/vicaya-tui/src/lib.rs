@@ -2,11 +2,14 @@
 
 pub mod app;
 pub mod client;
+mod export;
 mod kriya;
+mod prefs;
 pub mod state;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod ui;
 mod worker;
 
-pub use app::run;
-pub use client::IpcClient;
+pub use app::{run, RunOptions};
 pub use state::{AppMode, AppState};
@@ -1,9 +1,10 @@
 //! Vicaya TUI - Beautiful terminal UI for fast file search.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use clap::{ArgAction, Parser};
+use vicaya_core::config::{AutoScope, IconStyle};
 
 #[derive(Debug, Parser)]
 #[command(name = "vicaya-tui")]
@@ -14,6 +15,34 @@ struct Cli {
     #[arg(short = 'V', long = "version", action = ArgAction::SetTrue)]
     version: bool,
 
+    /// Picker mode: Enter prints the selected (or marked) paths and exits;
+    /// editor actions are disabled. Exits 1 when nothing was picked.
+    #[arg(long)]
+    pick: bool,
+
+    /// Separate printed paths with NUL instead of newline (for `xargs -0`)
+    #[arg(short = '0', long = "print0")]
+    print0: bool,
+
+    /// Scope to the enclosing project root (git), the current directory
+    /// (cwd), or nothing (off) without asking; overrides `auto_scope`
+    #[arg(long, value_name = "MODE")]
+    auto_scope: Option<AutoScope>,
+
+    /// Result icons: auto, nerd (needs a Nerd Font), unicode, or ascii;
+    /// overrides `icons`
+    #[arg(long, value_name = "STYLE")]
+    icons: Option<IconStyle>,
+
+    /// Start global in the default drishti even with `restore_tui_session`
+    #[arg(long)]
+    fresh: bool,
+
+    /// Use a named profile with its own config, index, and daemon
+    /// (also `VICAYA_PROFILE`)
+    #[arg(long, value_name = "NAME")]
+    profile: Option<String>,
+
     /// Start with ksetra scoped to this directory
     scope: Option<PathBuf>,
 }
@@ -25,8 +54,26 @@ fn parse_startup_scope(scope: Option<PathBuf>) -> Result<Option<PathBuf>> {
         .map_err(anyhow::Error::from)
 }
 
+/// Resolve the initial ksetra for a launch without an explicit scope.
+///
+/// Returns `(scope, ask)`: `--auto-scope` applies its scope directly, while
+/// the config's `auto_scope` only offers it with a startup prompt.
+fn auto_scope(flag: Option<AutoScope>, cwd: &Path) -> (Option<PathBuf>, bool) {
+    match flag {
+        Some(mode) => (mode.resolve(cwd), false),
+        None => {
+            let config_path = vicaya_core::paths::config_path();
+            let mode = vicaya_core::Config::load(&config_path)
+                .map(|config| config.auto_scope)
+                .unwrap_or_default();
+            (mode.resolve(cwd), true)
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    vicaya_core::paths::init_profile(cli.profile.as_deref())?;
 
     if cli.version {
         println!(
@@ -36,16 +83,37 @@ fn main() -> Result<()> {
         return Ok(());
     }
 
-    let startup_scope = parse_startup_scope(cli.scope)?;
+    let mut startup_scope = parse_startup_scope(cli.scope)?;
+    let mut suggested_scope = None;
+    if startup_scope.is_none() {
+        if let Ok(cwd) = std::env::current_dir() {
+            match auto_scope(cli.auto_scope, &cwd) {
+                (scope, true) => suggested_scope = scope,
+                (scope, false) => startup_scope = scope,
+            }
+        }
+    }
 
     tracing_subscriber::fmt()
         .with_env_filter(
             tracing_subscriber::EnvFilter::from_default_env()
                 .add_directive(tracing::Level::INFO.into()),
         )
+        .with_writer(std::io::stderr)
         .init();
 
-    vicaya_tui::run(startup_scope)
+    let printed = vicaya_tui::run(vicaya_tui::RunOptions {
+        startup_scope,
+        pick: cli.pick,
+        null_separated: cli.print0,
+        suggested_scope,
+        icons: cli.icons,
+        fresh: cli.fresh,
+    })?;
+    if cli.pick && !printed {
+        std::process::exit(1);
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -69,6 +137,33 @@ mod tests {
         assert!(cli.scope.is_none());
     }
 
+    #[test]
+    fn cli_parses_pick_flags() {
+        let cli = Cli::parse_from(["vicaya-tui", "--pick", "-0", "."]);
+        assert!(cli.pick);
+        assert!(cli.print0);
+        assert_eq!(cli.scope, Some(PathBuf::from(".")));
+
+        let cli = Cli::parse_from(["vicaya-tui"]);
+        assert!(!cli.pick);
+        assert!(!cli.print0);
+        assert!(!cli.fresh);
+        assert!(Cli::parse_from(["vicaya-tui", "--fresh"]).fresh);
+    }
+
+    #[test]
+    fn auto_scope_flag_applies_directly() {
+        let temp = tempfile::tempdir().unwrap();
+        let cwd = temp.path().join("project");
+        std::fs::create_dir_all(&cwd).unwrap();
+
+        let cli = Cli::parse_from(["vicaya-tui", "--auto-scope", "cwd"]);
+        assert_eq!(cli.auto_scope, Some(AutoScope::Cwd));
+        assert_eq!(auto_scope(cli.auto_scope, &cwd), (Some(cwd.clone()), false));
+        assert_eq!(auto_scope(Some(AutoScope::Off), &cwd), (None, false));
+        assert!(Cli::try_parse_from(["vicaya-tui", "--auto-scope", "repo"]).is_err());
+    }
+
     #[test]
     fn cli_parses_relative_scope() {
         let cli = Cli::parse_from(["vicaya-tui", "."]);
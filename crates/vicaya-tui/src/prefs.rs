@@ -0,0 +1,176 @@
+//! Layout and display preferences kept across TUI runs.
+//!
+//! The split layout and ratio, preview visibility, preview line numbers and
+//! varga are saved to a small versioned JSON file on exit and restored at
+//! startup, so the TUI opens the way it was left. The ksetra stack and
+//! drishti are saved alongside but only restored with `restore_tui_session`.
+
+use crate::state::{AppState, GroupingMode, SplitLayout, ViewKind, DEFAULT_PREVIEW_PERCENT};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const CURRENT_VERSION: u16 = 1;
+
+/// Saved UI preferences. Missing fields take their defaults, so files from
+/// older versions still load.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UiPrefs {
+    /// On-disk schema version.
+    pub version: u16,
+    pub split: SplitLayout,
+    pub preview_percent: u16,
+    pub preview_visible: bool,
+    pub line_numbers: bool,
+    pub grouping: GroupingMode,
+    /// Ksetra stack at exit, outermost first.
+    pub ksetra: Vec<PathBuf>,
+    /// Drishti at exit.
+    pub view: ViewKind,
+}
+
+impl Default for UiPrefs {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            split: SplitLayout::SideBySide,
+            preview_percent: DEFAULT_PREVIEW_PERCENT,
+            preview_visible: true,
+            line_numbers: false,
+            grouping: GroupingMode::None,
+            ksetra: Vec::new(),
+            view: ViewKind::Patra,
+        }
+    }
+}
+
+impl UiPrefs {
+    /// Load preferences, returning the defaults when the file is absent.
+    pub fn load(path: &Path) -> vicaya_core::Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&content).map_err(|e| {
+            vicaya_core::Error::Config(format!(
+                "failed to parse TUI state {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Persist as pretty JSON using a temporary file followed by `rename`.
+    pub fn save_atomic(&self, path: &Path) -> vicaya_core::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut tmp = path.as_os_str().to_os_string();
+        tmp.push(".tmp");
+        let tmp_path = PathBuf::from(tmp);
+        let content = serde_json::to_vec_pretty(self)
+            .map_err(|e| vicaya_core::Error::Serialization(e.to_string()))?;
+        let mut file = std::fs::File::create(&tmp_path)?;
+        file.write_all(&content)?;
+        file.sync_data()?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// The preferences `app` currently shows.
+    pub fn capture(app: &AppState) -> Self {
+        Self {
+            version: CURRENT_VERSION,
+            split: app.ui.split,
+            preview_percent: app.ui.preview_percent,
+            preview_visible: app.preview.is_visible,
+            line_numbers: app.preview.show_line_numbers,
+            grouping: app.ui.grouping,
+            ksetra: app.ksetra.paths().to_vec(),
+            view: app.view,
+        }
+    }
+
+    /// Show these preferences in `app`; an out-of-range ratio is clamped.
+    pub fn apply(&self, app: &mut AppState) {
+        app.ui.split = self.split;
+        app.ui.preview_percent = self.preview_percent;
+        app.ui.resize_preview(0);
+        app.preview.is_visible = self.preview_visible;
+        app.preview.show_line_numbers = self.line_numbers;
+        app.ui.grouping = self.grouping;
+    }
+
+    /// Reopen the saved ksetra stack and drishti in `app`. Directories that
+    /// no longer exist are left out of the stack.
+    pub fn restore_session(&self, app: &mut AppState) {
+        for path in self.ksetra.iter().filter(|path| path.is_dir()) {
+            app.ksetra.push(path.clone());
+        }
+        app.set_view(self.view);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preferences_round_trip_through_the_state_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tui-state.json");
+        assert_eq!(UiPrefs::load(&path).unwrap(), UiPrefs::default());
+
+        let mut app = AppState::new();
+        app.ui.split = SplitLayout::Stacked;
+        app.ui.resize_preview(2);
+        app.preview.is_visible = false;
+        app.preview.show_line_numbers = true;
+        app.ui.grouping = GroupingMode::Extension;
+        UiPrefs::capture(&app).save_atomic(&path).unwrap();
+
+        let mut restored = AppState::new();
+        UiPrefs::load(&path).unwrap().apply(&mut restored);
+        assert_eq!(UiPrefs::capture(&restored), UiPrefs::capture(&app));
+        assert_eq!(restored.ui.preview_percent, 55);
+
+        // Older files lack fields; hand edits may be out of range.
+        std::fs::write(&path, r#"{"version":1,"preview_percent":95}"#).unwrap();
+        let mut restored = AppState::new();
+        UiPrefs::load(&path).unwrap().apply(&mut restored);
+        assert_eq!(restored.ui.preview_percent, 80);
+        assert!(restored.preview.is_visible);
+
+        std::fs::write(&path, "{").unwrap();
+        assert!(UiPrefs::load(&path).is_err());
+    }
+
+    #[test]
+    fn session_restores_existing_ksetras_and_drishti() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tui-state.json");
+        let outer = dir.path().join("outer");
+        let inner = outer.join("inner");
+        std::fs::create_dir_all(&inner).unwrap();
+
+        let mut app = AppState::new();
+        app.ksetra.push(outer.clone());
+        app.ksetra.push(inner.clone());
+        app.set_view(ViewKind::Sthana);
+        UiPrefs::capture(&app).save_atomic(&path).unwrap();
+
+        // Layout preferences alone leave the ksetra and drishti alone.
+        let prefs = UiPrefs::load(&path).unwrap();
+        let mut restored = AppState::new();
+        prefs.apply(&mut restored);
+        assert!(restored.ksetra.is_global());
+        assert_eq!(restored.view, ViewKind::Patra);
+
+        std::fs::remove_dir(&inner).unwrap();
+        prefs.restore_session(&mut restored);
+        assert_eq!(restored.ksetra.paths(), [outer]);
+        assert_eq!(restored.view, ViewKind::Sthana);
+    }
+}
@@ -1,8 +1,15 @@
 //! Application state management.
 
 use crate::client::DaemonStatus;
+use crate::export::ExportFormat;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use vicaya_core::bookmarks::Bookmark;
+use vicaya_core::config::{ContentHashConfig, IconStyle};
+use vicaya_core::ipc::RootStats;
 use vicaya_core::smriti::SmritiAction;
+use vicaya_core::telemetry::RankingEvent;
 use vicaya_index::SearchResult;
 
 /// Application mode
@@ -20,6 +27,14 @@ pub enum AppMode {
     PreviewSearch,
     /// Ksetra (scope) direct input overlay
     KsetraInput,
+    /// Open-with application chooser overlay
+    OpenWith,
+    /// Results export destination prompt
+    Export,
+    /// Score breakdown of the selected result
+    Inspect,
+    /// Per-root index statistics
+    RootStats,
     /// Confirmation dialog
     Confirm(Action),
 }
@@ -29,6 +44,8 @@ pub enum AppMode {
 pub enum Action {
     Quit,
     RebuildIndex,
+    /// Replace the ksetra with this directory (startup `auto_scope` offer).
+    SetKsetra(PathBuf),
 }
 
 /// Application state
@@ -53,14 +70,74 @@ pub struct AppState {
     pub should_quit: bool,
     /// Last error message
     pub error: Option<String>,
-    /// Path to print on exit (for terminal integration)
-    pub print_on_exit: Option<String>,
+    /// Paths to print on exit (for terminal integration)
+    pub print_on_exit: Vec<String>,
+    /// Picker mode (`--pick`): Enter prints the selection and exits, and
+    /// editor actions are disabled.
+    pub pick_mode: bool,
     /// Path to open in editor after exit
     pub open_in_editor: Option<String>,
     /// Best-effort Smriti usage events queued for the worker.
     pub smriti_events: Vec<SmritiUsageEvent>,
     /// Smriti paths queued for forgetting.
     pub smriti_forget_paths: Vec<String>,
+    /// Paths found missing on open or preview, queued for reporting to the
+    /// daemon.
+    pub missing_paths: Vec<String>,
+    /// Ranking telemetry events queued for the worker, which drops them
+    /// unless `[telemetry] enabled` is set.
+    pub ranking_events: Vec<RankingEvent>,
+    /// Directory bookmarks; the first nine are bound to keys `1`-`9`.
+    pub bookmarks: Vec<Bookmark>,
+    /// Query aliases from `[aliases]`; `@name` expands before parsing.
+    pub aliases: BTreeMap<String, String>,
+    /// Second phala pane comparing another ksetra, when open.
+    pub tulana: Option<TulanaState>,
+    /// Where the user left each drishti other than the current one.
+    pub view_snapshots: HashMap<ViewKind, ViewSnapshot>,
+    /// Per-root index breakdown for the status overlay; `None` until the
+    /// daemon answers.
+    pub root_stats: Option<Vec<RootStats>>,
+    /// Ask the worker for a fresh per-root breakdown.
+    pub root_stats_requested: bool,
+    /// Mark results used before with a Smriti badge (`[smriti] badges`).
+    pub usage_badges: bool,
+    /// Smriti usage by path for the badges; `None` until the daemon answers.
+    pub smriti_usage: Option<HashMap<String, PathUsage>>,
+}
+
+/// What Smriti remembers about one path, for its result badge.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PathUsage {
+    /// Times it was opened in the editor.
+    pub opens: u64,
+    /// Times any action was taken on it.
+    pub uses: u64,
+    /// When it was last used (Unix timestamp).
+    pub last_used: i64,
+}
+
+impl From<&vicaya_core::smriti::SmritiEntry> for PathUsage {
+    fn from(entry: &vicaya_core::smriti::SmritiEntry) -> Self {
+        Self {
+            opens: entry.open_count,
+            uses: entry.total_count,
+            last_used: entry.last_used,
+        }
+    }
+}
+
+/// The prashna, results, selection and scroll a drishti showed when the
+/// user switched away, restored on return.
+#[derive(Debug, Clone)]
+pub struct ViewSnapshot {
+    query: String,
+    cursor_position: usize,
+    results: Vec<SearchResult>,
+    truncated: bool,
+    outside_scope: bool,
+    selected_index: usize,
+    scroll_offset: usize,
 }
 
 /// A queued Smriti usage event.
@@ -77,6 +154,49 @@ impl AppState {
         Self::with_startup_scope(None)
     }
 
+    /// Switch drishti, remembering where the user left the current one and
+    /// restoring the target's prashna, results, selection and scroll. A
+    /// drishti not visited yet keeps the current prashna. The caller re-runs
+    /// the search, whose fresh results keep the restored selection by path.
+    pub fn set_view(&mut self, view: ViewKind) {
+        if view == self.view {
+            return;
+        }
+        let left = ViewSnapshot {
+            query: self.search.query.clone(),
+            cursor_position: self.search.cursor_position,
+            results: self.search.results.clone(),
+            truncated: self.search.truncated,
+            outside_scope: self.search.outside_scope,
+            selected_index: self.search.selected_index,
+            scroll_offset: self.ui.scroll_offset,
+        };
+        self.view_snapshots.insert(self.view, left);
+        self.view = view;
+
+        let Some(snapshot) = self.view_snapshots.remove(&view) else {
+            return;
+        };
+        self.search.query = snapshot.query;
+        self.search.cursor_position = snapshot.cursor_position;
+        self.search.completion = None;
+        self.search.suggestions.clear();
+        self.search.set_results(snapshot.results);
+        self.search.truncated = snapshot.truncated;
+        self.search.outside_scope = snapshot.outside_scope;
+        self.search.selected_index = snapshot.selected_index;
+        self.search.clamp_selection();
+        self.search.keep_selection = self.search.selected_result().map(|r| r.path.clone());
+        self.ui.scroll_offset = snapshot.scroll_offset;
+    }
+
+    /// Whether result sizes come from the index; a lite daemon reports zero.
+    pub fn sizes_indexed(&self) -> bool {
+        self.daemon_status
+            .as_ref()
+            .is_none_or(|status| status.index_profile.tracks_file_identity())
+    }
+
     /// Create a new application state with an optional startup ksetra scope.
     pub fn with_startup_scope(startup_scope: Option<PathBuf>) -> Self {
         let mut ksetra = KsetraState::new();
@@ -95,13 +215,32 @@ impl AppState {
             daemon_status: None,
             should_quit: false,
             error: None,
-            print_on_exit: None,
+            print_on_exit: Vec::new(),
+            pick_mode: false,
             open_in_editor: None,
             smriti_events: Vec::new(),
             smriti_forget_paths: Vec::new(),
+            missing_paths: Vec::new(),
+            ranking_events: Vec::new(),
+            bookmarks: Vec::new(),
+            aliases: BTreeMap::new(),
+            tulana: None,
+            view_snapshots: HashMap::new(),
+            root_stats: None,
+            root_stats_requested: false,
+            usage_badges: false,
+            smriti_usage: None,
         }
     }
 
+    /// The prashna with aliases expanded, split into term and niyamas.
+    pub fn parsed_query(&self) -> ParsedQuery {
+        parse_query(&vicaya_core::aliases::expand(
+            &self.search.query,
+            &self.aliases,
+        ))
+    }
+
     /// Perform a search
     pub fn clear_results(&mut self) {
         self.search.results.clear();
@@ -120,6 +259,19 @@ impl AppState {
 
     /// Queue a best-effort Smriti usage event.
     pub fn record_smriti_usage(&mut self, path: String, action: SmritiAction) {
+        self.record_ranking_event(&path, action);
+        if let Some(usage) = self.smriti_usage.as_mut() {
+            // Badge the use right away instead of waiting for a refetch.
+            let usage = usage.entry(path.clone()).or_default();
+            usage.uses += 1;
+            if action == SmritiAction::Open {
+                usage.opens += 1;
+            }
+            usage.last_used = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+        }
         self.smriti_events.push(SmritiUsageEvent {
             path,
             query: self.search.query.clone(),
@@ -127,11 +279,56 @@ impl AppState {
         });
     }
 
+    /// Queue a ranking telemetry event when `path` is an accepted result of a
+    /// ranked name search.
+    fn record_ranking_event(&mut self, path: &str, action: SmritiAction) {
+        if !matches!(self.view, ViewKind::Patra | ViewKind::Sthana)
+            || self.search.query.trim().is_empty()
+        {
+            return;
+        }
+        let Some(position) = self.search.results.iter().position(|r| r.path == path) else {
+            return;
+        };
+        let at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let time_to_select_ms = self
+            .search
+            .results_shown_at
+            .map(|shown| shown.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+        self.ranking_events.push(RankingEvent {
+            at,
+            query: self.search.query.trim().to_string(),
+            rank: position + 1,
+            result_count: self.search.results.len(),
+            time_to_select_ms,
+            action,
+        });
+    }
+
     /// Queue a best-effort Smriti forget request.
     pub fn forget_smriti_path(&mut self, path: String) {
+        if let Some(usage) = self.smriti_usage.as_mut() {
+            usage.remove(&path);
+        }
         self.smriti_forget_paths.push(path);
     }
 
+    /// Flag `path` as gone and queue a report so the daemon drops its entry.
+    /// Repeat reports for the same results are skipped.
+    pub fn report_missing(&mut self, path: String) {
+        let previous = self
+            .search
+            .freshness
+            .insert(path.clone(), Freshness::Missing);
+        if previous != Some(Freshness::Missing) {
+            self.missing_paths.push(path);
+        }
+    }
+
     /// Toggle help overlay
     pub fn toggle_help(&mut self) {
         self.mode = match self.mode {
@@ -140,6 +337,94 @@ impl AppState {
         };
     }
 
+    /// Flip whether searches return dotfiles and dot-directory contents;
+    /// the app re-runs the current search.
+    pub fn toggle_hidden(&mut self) {
+        self.search.show_hidden = !self.search.show_hidden;
+        self.error = Some(
+            if self.search.show_hidden {
+                "✓ Showing hidden files"
+            } else {
+                "✓ Hiding hidden files"
+            }
+            .to_string(),
+        );
+    }
+
+    /// Flip whether Sthana orders directories by the size of their
+    /// contents; the app re-runs the current search.
+    pub fn toggle_largest_first(&mut self) {
+        self.search.largest_first = !self.search.largest_first;
+        self.error = Some(
+            if self.search.largest_first {
+                "✓ Sthana: largest directories first"
+            } else {
+                "✓ Sthana: most relevant first"
+            }
+            .to_string(),
+        );
+    }
+
+    /// Show the open-with overlay for `path`, listing `apps` from the
+    /// `[associations]` config section ahead of the built-in targets.
+    pub fn show_open_with(&mut self, path: String, apps: &[String]) {
+        self.ui.open_with.reset(path, apps);
+        self.mode = AppMode::OpenWith;
+    }
+
+    /// Open or close the score inspector. While it is open searches carry
+    /// score breakdowns; the app re-runs the current search for them.
+    pub fn toggle_inspector(&mut self) {
+        if self.mode == AppMode::Inspect {
+            self.mode = AppMode::Search;
+            self.search.explain = false;
+        } else if matches!(
+            self.view,
+            ViewKind::Smriti | ViewKind::Antarvicaya | ViewKind::Tyakta
+        ) {
+            self.error = Some(format!(
+                "Score breakdowns are not available in {}",
+                self.view.label()
+            ));
+        } else {
+            self.mode = AppMode::Inspect;
+            self.search.explain = true;
+        }
+    }
+
+    /// Open or close the per-root index statistics overlay. Opening asks
+    /// the daemon for a fresh breakdown.
+    pub fn toggle_root_stats(&mut self) {
+        if self.mode == AppMode::RootStats {
+            self.mode = AppMode::Search;
+        } else {
+            self.mode = AppMode::RootStats;
+            self.root_stats = None;
+            self.root_stats_requested = true;
+        }
+    }
+
+    /// Close the open-with overlay.
+    pub fn close_open_with(&mut self) {
+        if self.mode == AppMode::OpenWith {
+            self.mode = AppMode::Search;
+        }
+    }
+
+    /// Show the export prompt. The previous destination and format are kept
+    /// so repeated exports are one keypress.
+    pub fn show_export(&mut self) {
+        self.ui.export.error = None;
+        self.mode = AppMode::Export;
+    }
+
+    /// Close the export prompt.
+    pub fn close_export(&mut self) {
+        if self.mode == AppMode::Export {
+            self.mode = AppMode::Search;
+        }
+    }
+
     /// Toggle drishti switcher overlay.
     pub fn toggle_drishti_switcher(&mut self) {
         self.mode = match self.mode {
@@ -172,6 +457,26 @@ impl AppState {
             }
         };
     }
+
+    /// Open the ksetra input for the tulana pane's scope.
+    pub fn show_tulana_input(&mut self) {
+        self.ksetra_input.reset();
+        self.ksetra_input.for_tulana = true;
+        self.mode = AppMode::KsetraInput;
+    }
+
+    /// Compare the current results against `scope` in a second pane.
+    pub fn open_tulana(&mut self, scope: PathBuf) {
+        self.tulana = Some(TulanaState::new(scope));
+    }
+
+    /// Close the tulana pane, moving focus back to the main results.
+    pub fn close_tulana(&mut self) {
+        self.tulana = None;
+        if self.search.focus == FocusTarget::Tulana {
+            self.search.focus = FocusTarget::Results;
+        }
+    }
 }
 
 impl Default for AppState {
@@ -213,6 +518,8 @@ pub enum FocusTarget {
     Results,
     /// Preview pane is focused
     Preview,
+    /// Tulana (comparison) pane is focused
+    Tulana,
 }
 
 /// Search state
@@ -225,10 +532,48 @@ pub struct SearchState {
     pub selected_index: usize,
     /// Whether currently searching
     pub is_searching: bool,
+    /// Whether the last results are partial (daemon query budget exhausted)
+    pub truncated: bool,
+    /// Near-miss names offered when the last search found nothing
+    pub suggestions: Vec<String>,
+    /// Whether results from outside the ksetra follow, because nothing
+    /// inside it matched
+    pub outside_scope: bool,
+    /// File name the query's term completes to, shown as ghost text
+    pub completion: Option<String>,
     /// Cursor position in query input
     pub cursor_position: usize,
     /// Current focus target
     pub focus: FocusTarget,
+    /// When the current results arrived, for ranking telemetry.
+    pub results_shown_at: Option<std::time::Instant>,
+    /// Paths marked for printing, in marking order. Marks survive new
+    /// queries and ksetra changes.
+    pub marked: Vec<String>,
+    /// Whether the selected result is checked against the filesystem.
+    pub verify: bool,
+    /// Verification outcomes for the current results, keyed by path.
+    pub freshness: HashMap<String, Freshness>,
+    /// How the daemon fingerprints files, so verification can rehash.
+    pub content_hash: ContentHashConfig,
+    /// Whether searches ask the daemon for score breakdowns (while the
+    /// inspector is open).
+    pub explain: bool,
+    /// Path to select again when the next results arrive, after a drishti
+    /// was restored.
+    pub keep_selection: Option<String>,
+    /// Which shown results are symlinks, checked as rows first scroll into
+    /// view; the index stores what links point to.
+    pub symlinks: HashMap<String, bool>,
+    /// When index updates last patched the results in place, for the
+    /// refresh mark in the phala title.
+    pub live_updated_at: Option<std::time::Instant>,
+    /// Whether searches return dotfiles and dot-directory contents when the
+    /// prashna has no `hidden:` Niyama (`.` toggles it).
+    pub show_hidden: bool,
+    /// Whether Sthana lists the directories holding the most bytes first
+    /// instead of by relevance (`S` toggles it).
+    pub largest_first: bool,
 }
 
 impl SearchState {
@@ -239,8 +584,23 @@ impl SearchState {
             results: Vec::new(),
             selected_index: 0,
             is_searching: false,
+            truncated: false,
+            suggestions: Vec::new(),
+            outside_scope: false,
+            completion: None,
             cursor_position: 0,
             focus: FocusTarget::Input,
+            results_shown_at: None,
+            marked: Vec::new(),
+            verify: false,
+            freshness: HashMap::new(),
+            content_hash: ContentHashConfig::default(),
+            explain: false,
+            keep_selection: None,
+            symlinks: HashMap::new(),
+            live_updated_at: None,
+            show_hidden: true,
+            largest_first: false,
         }
     }
 
@@ -249,7 +609,7 @@ impl SearchState {
         self.focus = match self.focus {
             FocusTarget::Input => FocusTarget::Results,
             FocusTarget::Results => FocusTarget::Input,
-            FocusTarget::Preview => FocusTarget::Input,
+            FocusTarget::Preview | FocusTarget::Tulana => FocusTarget::Input,
         };
     }
 
@@ -280,6 +640,42 @@ impl SearchState {
         self.cursor_position = 0;
         self.results.clear();
         self.selected_index = 0;
+        self.completion = None;
+    }
+
+    /// The rest of `completion` after the query's last word, while the
+    /// cursor is at the end and that word (not a niyama) is a
+    /// case-insensitive prefix of it.
+    pub fn ghost_text(&self) -> Option<&str> {
+        let completion = self.completion.as_deref()?;
+        if self.cursor_position != self.query.len() {
+            return None;
+        }
+        let word = self.query.rsplit(char::is_whitespace).next()?;
+        if word.is_empty() || word.contains(':') {
+            return None;
+        }
+        let typed = word.chars().count();
+        let same_start = completion.chars().count() > typed
+            && word
+                .chars()
+                .zip(completion.chars())
+                .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()));
+        if !same_start {
+            return None;
+        }
+        let (start, _) = completion.char_indices().nth(typed)?;
+        Some(&completion[start..])
+    }
+
+    /// Append the ghost text to the query. Returns whether there was any.
+    pub fn accept_completion(&mut self) -> bool {
+        let Some(rest) = self.ghost_text().map(str::to_string) else {
+            return false;
+        };
+        self.query.push_str(&rest);
+        self.cursor_position = self.query.len();
+        true
     }
 
     /// Add character at cursor
@@ -340,19 +736,186 @@ impl SearchState {
         self.results.get(self.selected_index)
     }
 
+    /// Mark `path` for printing, or unmark it if already marked.
+    pub fn toggle_mark(&mut self, path: &str) {
+        if let Some(position) = self.marked.iter().position(|marked| marked == path) {
+            self.marked.remove(position);
+        } else {
+            self.marked.push(path.to_string());
+        }
+    }
+
+    /// Check whether `path` is marked for printing.
+    pub fn is_marked(&self, path: &str) -> bool {
+        self.marked.iter().any(|marked| marked == path)
+    }
+
     /// Update results
     pub fn set_results(&mut self, results: Vec<SearchResult>) {
         self.results = results;
+        self.results_shown_at = Some(std::time::Instant::now());
+        self.freshness.clear();
+        self.symlinks.clear();
+        self.live_updated_at = None;
+        self.outside_scope = false;
+        if let Some(path) = self.keep_selection.take() {
+            if let Some(index) = self.results.iter().position(|r| r.path == path) {
+                self.selected_index = index;
+            }
+        }
         // Reset selection if out of bounds
         self.clamp_selection();
     }
 
+    /// Patch the results with a pushed index change: drop `removed` paths,
+    /// refresh the entries `added` lists again where they are, and slot new
+    /// matches in by score while the list stays within `limit`. Newcomers
+    /// go ahead of equal scores, so the newest of a recent-files list comes
+    /// first. The selection follows its path. Returns whether anything
+    /// changed.
+    pub fn apply_index_change(
+        &mut self,
+        removed: &[String],
+        added: Vec<SearchResult>,
+        limit: usize,
+    ) -> bool {
+        let selected = self.selected_result().map(|r| r.path.clone());
+        let before = self.results.len();
+        let removed: std::collections::HashSet<&str> = removed.iter().map(String::as_str).collect();
+        self.results.retain(|r| !removed.contains(r.path.as_str()));
+        let mut changed = self.results.len() != before;
+
+        let limit = limit.max(self.results.len());
+        for result in added {
+            if let Some(shown) = self.results.iter_mut().find(|r| r.path == result.path) {
+                self.freshness.remove(&result.path);
+                *shown = result;
+                changed = true;
+                continue;
+            }
+            let at = self
+                .results
+                .iter()
+                .position(|r| r.score <= result.score)
+                .unwrap_or(self.results.len());
+            if at < limit {
+                self.results.insert(at, result);
+                self.results.truncate(limit);
+                changed = true;
+            }
+        }
+
+        if !changed {
+            return false;
+        }
+        if let Some(index) =
+            selected.and_then(|path| self.results.iter().position(|r| r.path == path))
+        {
+            self.selected_index = index;
+        }
+        self.clamp_selection();
+        self.live_updated_at = Some(std::time::Instant::now());
+        true
+    }
+
     /// Clamp selection to available results.
     pub fn clamp_selection(&mut self) {
         if self.selected_index >= self.results.len() {
             self.selected_index = self.results.len().saturating_sub(1);
         }
     }
+
+    /// `lstat` the `paths` not checked yet and remember which are symlinks.
+    pub fn check_symlinks(&mut self, paths: Vec<String>) {
+        for path in paths {
+            if let std::collections::hash_map::Entry::Vacant(entry) = self.symlinks.entry(path) {
+                let is_symlink = std::fs::symlink_metadata(entry.key())
+                    .is_ok_and(|metadata| metadata.file_type().is_symlink());
+                entry.insert(is_symlink);
+            }
+        }
+    }
+
+    /// Whether `path` was found to be a symlink.
+    pub fn is_symlink(&self, path: &str) -> bool {
+        self.symlinks.get(path).copied().unwrap_or(false)
+    }
+
+    /// With verification on, stat the selected result once and remember how
+    /// it compares with the index. Results without an `indexed_at` (content
+    /// matches, older daemons) are skipped, and sizes are only compared when
+    /// `sizes_indexed`.
+    pub fn verify_selected(&mut self, sizes_indexed: bool) {
+        if !self.verify {
+            return;
+        }
+        let Some(result) = self.selected_result() else {
+            return;
+        };
+        if result.indexed_at == 0 || self.freshness.contains_key(&result.path) {
+            return;
+        }
+        let freshness = Freshness::check(result, sizes_indexed, &self.content_hash);
+        let path = result.path.clone();
+        self.freshness.insert(path, freshness);
+    }
+}
+
+/// How an indexed result compares with the filesystem right now.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Freshness {
+    /// Size and mtime (or content fingerprint) still match the index.
+    Current,
+    /// The entry exists but changed since it was indexed.
+    Stale,
+    /// The entry no longer exists.
+    Missing,
+}
+
+impl Freshness {
+    /// Stat `result` and compare it with its indexed metadata. Archive
+    /// members are checked through their archive, whose mtime they carry,
+    /// and sizes are skipped unless `sizes_indexed`. A fingerprinted file is
+    /// rehashed instead of trusting its mtime, so a touched file stays
+    /// current and a rewrite with a restored mtime goes stale.
+    pub fn check(
+        result: &SearchResult,
+        sizes_indexed: bool,
+        content_hash: &ContentHashConfig,
+    ) -> Self {
+        let (path, archive_member) = match vicaya_core::archive::split_virtual_path(&result.path) {
+            Some((archive, _)) => (archive.to_path_buf(), true),
+            None => (PathBuf::from(&result.path), false),
+        };
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            return Self::Missing;
+        };
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|since| since.as_secs() as i64);
+        // A directory's indexed size totals its contents, not its own st_size.
+        let size_matches =
+            archive_member || !sizes_indexed || result.is_dir || metadata.len() == result.size;
+        if let (Some(indexed), false) = (result.content_hash, archive_member) {
+            let rehashed = vicaya_core::content_hash::compute(
+                &path,
+                metadata.len(),
+                content_hash.head_bytes(),
+            );
+            return if size_matches && rehashed == Some(indexed) {
+                Self::Current
+            } else {
+                Self::Stale
+            };
+        }
+        if mtime == Some(result.mtime) && size_matches {
+            Self::Current
+        } else {
+            Self::Stale
+        }
+    }
 }
 
 impl Default for SearchState {
@@ -375,6 +938,18 @@ pub struct UiState {
     pub drishti_switcher: DrishtiSwitcherState,
     /// Kriya-Suchi (action palette) state
     pub kriya_suchi: KriyaSuchiState,
+    /// Open-with overlay state
+    pub open_with: OpenWithState,
+    /// Results export prompt state
+    pub export: ExportInputState,
+    /// Glyph set of the result icon column, with `Auto` resolved
+    pub icons: IconStyle,
+    /// Where the header's ksetra breadcrumbs were last drawn, for clicks
+    pub breadcrumb_targets: Vec<BreadcrumbTarget>,
+    /// Preview pane placement
+    pub split: SplitLayout,
+    /// Share of the body the preview pane takes, in percent
+    pub preview_percent: u16,
 }
 
 impl UiState {
@@ -387,9 +962,25 @@ impl UiState {
             grouping: GroupingMode::None,
             drishti_switcher: DrishtiSwitcherState::new(),
             kriya_suchi: KriyaSuchiState::new(),
+            open_with: OpenWithState::new(),
+            export: ExportInputState::new(),
+            icons: IconStyle::Unicode,
+            breadcrumb_targets: Vec::new(),
+            split: SplitLayout::SideBySide,
+            preview_percent: DEFAULT_PREVIEW_PERCENT,
         }
     }
 
+    /// Grow (positive `steps`) or shrink the preview pane within
+    /// [`PREVIEW_PERCENT_RANGE`].
+    pub fn resize_preview(&mut self, steps: i16) {
+        let percent = self.preview_percent as i16 + steps * PREVIEW_PERCENT_STEP as i16;
+        self.preview_percent = percent.clamp(
+            *PREVIEW_PERCENT_RANGE.start() as i16,
+            *PREVIEW_PERCENT_RANGE.end() as i16,
+        ) as u16;
+    }
+
     /// Update scroll offset to keep selection visible
     pub fn update_scroll(&mut self, selected_row: usize, total_rows: usize) {
         let total_rows = total_rows.max(1);
@@ -413,7 +1004,8 @@ impl Default for UiState {
 }
 
 /// Drishti (view) in the TUI.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ViewKind {
     /// `Patra` — Files
     Patra,
@@ -437,10 +1029,13 @@ pub enum ViewKind {
     Sambandha,
     /// `Ankita` — Pinned
     Ankita,
+    /// `Tyakta` — Recently moved to the Trash
+    Tyakta,
 }
 
 /// Varga (grouping) mode for the results list.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum GroupingMode {
     None,
     Directory,
@@ -465,6 +1060,41 @@ impl GroupingMode {
     }
 }
 
+/// Screen cells of one header breadcrumb and the ksetra depth a click on
+/// it jumps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreadcrumbTarget {
+    pub row: u16,
+    pub columns: std::ops::Range<u16>,
+    pub depth: usize,
+}
+
+/// Where the preview (or tulana) pane sits relative to the phala.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SplitLayout {
+    /// Panes next to each other.
+    #[default]
+    SideBySide,
+    /// The preview below the phala, for narrow terminals.
+    Stacked,
+}
+
+impl SplitLayout {
+    pub fn toggle(self) -> Self {
+        match self {
+            SplitLayout::SideBySide => SplitLayout::Stacked,
+            SplitLayout::Stacked => SplitLayout::SideBySide,
+        }
+    }
+}
+
+/// Share of the body the preview pane takes by default, in percent.
+pub const DEFAULT_PREVIEW_PERCENT: u16 = 45;
+/// Bounds and step of preview resizing.
+pub const PREVIEW_PERCENT_RANGE: std::ops::RangeInclusive<u16> = 20..=80;
+const PREVIEW_PERCENT_STEP: u16 = 5;
+
 impl ViewKind {
     pub const ALL: &'static [ViewKind] = &[
         ViewKind::Patra,
@@ -478,6 +1108,7 @@ impl ViewKind {
         ViewKind::Parivartana,
         ViewKind::Sambandha,
         ViewKind::Ankita,
+        ViewKind::Tyakta,
     ];
 
     pub fn label(self) -> &'static str {
@@ -493,6 +1124,7 @@ impl ViewKind {
             ViewKind::Parivartana => "Parivartana",
             ViewKind::Sambandha => "Sambandha",
             ViewKind::Ankita => "Ankita",
+            ViewKind::Tyakta => "Tyakta",
         }
     }
 
@@ -509,13 +1141,37 @@ impl ViewKind {
             ViewKind::Parivartana => "Changed",
             ViewKind::Sambandha => "Related",
             ViewKind::Ankita => "Pinned",
+            ViewKind::Tyakta => "Deleted",
         }
     }
 
+    /// [`english_hint`](Self::english_hint) in the configured `language`.
+    pub fn hint(self) -> &'static str {
+        vicaya_core::i18n::t(match self {
+            ViewKind::Patra => "tui.drishti.patra",
+            ViewKind::Sthana => "tui.drishti.sthana",
+            ViewKind::Smriti => "tui.drishti.smriti",
+            ViewKind::Navatama => "tui.drishti.navatama",
+            ViewKind::Brihat => "tui.drishti.brihat",
+            ViewKind::Antarvicaya => "tui.drishti.antarvicaya",
+            ViewKind::Sanketa => "tui.drishti.sanketa",
+            ViewKind::Itihasa => "tui.drishti.itihasa",
+            ViewKind::Parivartana => "tui.drishti.parivartana",
+            ViewKind::Sambandha => "tui.drishti.sambandha",
+            ViewKind::Ankita => "tui.drishti.ankita",
+            ViewKind::Tyakta => "tui.drishti.tyakta",
+        })
+    }
+
     pub fn is_enabled(self) -> bool {
         matches!(
             self,
-            ViewKind::Patra | ViewKind::Sthana | ViewKind::Smriti | ViewKind::Antarvicaya
+            ViewKind::Patra
+                | ViewKind::Sthana
+                | ViewKind::Smriti
+                | ViewKind::Navatama
+                | ViewKind::Antarvicaya
+                | ViewKind::Tyakta
         )
     }
 }
@@ -539,6 +1195,11 @@ impl KsetraState {
         self.stack.len()
     }
 
+    /// The stack, outermost ksetra first.
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.stack
+    }
+
     pub fn is_global(&self) -> bool {
         self.stack.is_empty()
     }
@@ -559,6 +1220,16 @@ impl KsetraState {
         self.stack.pop()
     }
 
+    /// Pop levels until `depth` remain (0 is global); false when there were
+    /// not more than that.
+    pub fn truncate(&mut self, depth: usize) -> bool {
+        if depth >= self.stack.len() {
+            return false;
+        }
+        self.stack.truncate(depth);
+        true
+    }
+
     pub fn breadcrumbs(&self) -> String {
         if self.stack.is_empty() {
             return "global".to_string();
@@ -656,7 +1327,7 @@ fn take_last_n_chars(s: &str, n: usize) -> &str {
         .unwrap_or(s)
 }
 
-fn pretty_path(path: &Path) -> String {
+pub(crate) fn pretty_path(path: &Path) -> String {
     let Ok(home) = std::env::var("HOME") else {
         return path.display().to_string();
     };
@@ -675,312 +1346,10 @@ fn pretty_path(path: &Path) -> String {
     path.display().to_string()
 }
 
-/// Parsed query into a daemon search term + Niyama filters.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ParsedQuery {
-    pub term: String,
-    pub niyamas: Vec<Niyama>,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum NiyamaType {
-    File,
-    Dir,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum CmpOp {
-    Lt,
-    Lte,
-    Gt,
-    Gte,
-    Eq,
-}
-
-impl CmpOp {
-    pub fn matches_i64(self, left: i64, right: i64) -> bool {
-        match self {
-            CmpOp::Lt => left < right,
-            CmpOp::Lte => left <= right,
-            CmpOp::Gt => left > right,
-            CmpOp::Gte => left >= right,
-            CmpOp::Eq => left == right,
-        }
-    }
-
-    pub fn matches_u64(self, left: u64, right: u64) -> bool {
-        match self {
-            CmpOp::Lt => left < right,
-            CmpOp::Lte => left <= right,
-            CmpOp::Gt => left > right,
-            CmpOp::Gte => left >= right,
-            CmpOp::Eq => left == right,
-        }
-    }
-
-    pub fn invert(self) -> Self {
-        match self {
-            CmpOp::Lt => CmpOp::Gt,
-            CmpOp::Lte => CmpOp::Gte,
-            CmpOp::Gt => CmpOp::Lt,
-            CmpOp::Gte => CmpOp::Lte,
-            CmpOp::Eq => CmpOp::Eq,
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct CmpI64 {
-    pub op: CmpOp,
-    pub value: i64,
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct CmpU64 {
-    pub op: CmpOp,
-    pub value: u64,
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Niyama {
-    Type { kind: NiyamaType, raw: String },
-    Ext { exts: Vec<String>, raw: String },
-    Path { needle: String, raw: String },
-    Mtime { cmp: CmpI64, raw: String },
-    Size { cmp: CmpU64, raw: String },
-}
-
-impl Niyama {
-    pub fn raw(&self) -> &str {
-        match self {
-            Niyama::Type { raw, .. }
-            | Niyama::Ext { raw, .. }
-            | Niyama::Path { raw, .. }
-            | Niyama::Mtime { raw, .. }
-            | Niyama::Size { raw, .. } => raw,
-        }
-    }
-}
-
-pub fn parse_query(raw: &str) -> ParsedQuery {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or(0);
-
-    let mut term_tokens: Vec<&str> = Vec::new();
-
-    let mut type_filter: Option<NiyamaType> = None;
-    let mut exts: Vec<String> = Vec::new();
-    let mut path_filters: Vec<Niyama> = Vec::new();
-    let mut mtime: Option<CmpI64> = None;
-    let mut mtime_raw: Option<String> = None;
-    let mut size: Option<CmpU64> = None;
-    let mut size_raw: Option<String> = None;
-
-    for token in raw.split_whitespace() {
-        if let Some(value) = token.strip_prefix("type:") {
-            if let Some(kind) = parse_type(value) {
-                type_filter = Some(kind);
-                continue;
-            }
-        }
-
-        if let Some(value) = token.strip_prefix("ext:") {
-            let mut parsed = parse_exts(value);
-            if !parsed.is_empty() {
-                exts.append(&mut parsed);
-                continue;
-            }
-        }
-
-        if let Some(value) = token.strip_prefix("path:") {
-            if !value.is_empty() {
-                path_filters.push(Niyama::Path {
-                    needle: value.to_lowercase(),
-                    raw: token.to_string(),
-                });
-                continue;
-            }
-        }
-
-        if let Some(value) = token.strip_prefix("mtime:") {
-            if let Some(cmp) = parse_mtime_expr(value, now) {
-                mtime = Some(cmp);
-                mtime_raw = Some(token.to_string());
-                continue;
-            }
-        }
-
-        if let Some(value) = token.strip_prefix("size:") {
-            if let Some(cmp) = parse_size_expr(value) {
-                size = Some(cmp);
-                size_raw = Some(token.to_string());
-                continue;
-            }
-        }
-
-        term_tokens.push(token);
-    }
-
-    exts.sort();
-    exts.dedup();
-
-    let mut niyamas: Vec<Niyama> = Vec::new();
-    if let Some(kind) = type_filter {
-        niyamas.push(Niyama::Type {
-            kind,
-            raw: format!(
-                "type:{}",
-                match kind {
-                    NiyamaType::File => "file",
-                    NiyamaType::Dir => "dir",
-                }
-            ),
-        });
-    }
-
-    if !exts.is_empty() {
-        niyamas.push(Niyama::Ext {
-            raw: format!("ext:{}", exts.join(",")),
-            exts,
-        });
-    }
-
-    niyamas.extend(path_filters);
-
-    if let (Some(cmp), Some(raw)) = (mtime, mtime_raw) {
-        niyamas.push(Niyama::Mtime { cmp, raw });
-    }
-
-    if let (Some(cmp), Some(raw)) = (size, size_raw) {
-        niyamas.push(Niyama::Size { cmp, raw });
-    }
-
-    ParsedQuery {
-        term: term_tokens.join(" "),
-        niyamas,
-    }
-}
-
-fn parse_type(value: &str) -> Option<NiyamaType> {
-    match value.trim().to_lowercase().as_str() {
-        "file" | "f" => Some(NiyamaType::File),
-        "dir" | "d" | "directory" => Some(NiyamaType::Dir),
-        _ => None,
-    }
-}
-
-fn parse_exts(value: &str) -> Vec<String> {
-    value
-        .split(',')
-        .filter_map(|ext| {
-            let ext = ext.trim().trim_start_matches('.').to_lowercase();
-            if ext.is_empty() {
-                None
-            } else {
-                Some(ext)
-            }
-        })
-        .collect()
-}
-
-fn parse_op_and_value(input: &str) -> Option<(CmpOp, &str)> {
-    let s = input.trim();
-    if let Some(rest) = s.strip_prefix(">=") {
-        return Some((CmpOp::Gte, rest));
-    }
-    if let Some(rest) = s.strip_prefix("<=") {
-        return Some((CmpOp::Lte, rest));
-    }
-    if let Some(rest) = s.strip_prefix('>') {
-        return Some((CmpOp::Gt, rest));
-    }
-    if let Some(rest) = s.strip_prefix('<') {
-        return Some((CmpOp::Lt, rest));
-    }
-    if let Some(rest) = s.strip_prefix('=') {
-        return Some((CmpOp::Eq, rest));
-    }
-    None
-}
-
-fn parse_size_expr(input: &str) -> Option<CmpU64> {
-    let (op, value) = parse_op_and_value(input)?;
-    let value = value.trim().to_lowercase();
-    let (num_str, unit) = value
-        .trim()
-        .chars()
-        .position(|c| !c.is_ascii_digit())
-        .map(|idx| (&value[..idx], &value[idx..]))
-        .unwrap_or((value.as_str(), ""));
-
-    let n: u64 = num_str.parse().ok()?;
-    let multiplier: u64 = match unit {
-        "" | "b" => 1,
-        "k" | "kb" => 1024,
-        "m" | "mb" => 1024 * 1024,
-        "g" | "gb" => 1024 * 1024 * 1024,
-        "t" | "tb" => 1024 * 1024 * 1024 * 1024,
-        _ => return None,
-    };
-
-    Some(CmpU64 {
-        op,
-        value: n.saturating_mul(multiplier),
-    })
-}
-
-fn parse_mtime_expr(input: &str, now: i64) -> Option<CmpI64> {
-    use chrono::{Local, NaiveDate, TimeZone};
-
-    let (op, value) = parse_op_and_value(input)?;
-    let value = value.trim();
-
-    if let Some((n, unit)) = parse_duration(value) {
-        let seconds = match unit {
-            's' => n,
-            'm' => n * 60,
-            'h' => n * 60 * 60,
-            'd' => n * 60 * 60 * 24,
-            'w' => n * 60 * 60 * 24 * 7,
-            _ => return None,
-        };
-
-        let threshold = now.saturating_sub(seconds);
-        return Some(CmpI64 {
-            op: op.invert(),
-            value: threshold,
-        });
-    }
-
-    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
-        let naive = date.and_hms_opt(0, 0, 0)?;
-        let timestamp = match Local.from_local_datetime(&naive) {
-            chrono::LocalResult::Single(dt) => dt.timestamp(),
-            chrono::LocalResult::Ambiguous(dt, _) => dt.timestamp(),
-            chrono::LocalResult::None => {
-                chrono::DateTime::<chrono::Utc>::from_naive_utc_and_offset(naive, chrono::Utc)
-                    .timestamp()
-            }
-        };
-
-        return Some(CmpI64 {
-            op,
-            value: timestamp,
-        });
-    }
-
-    None
-}
-
-fn parse_duration(input: &str) -> Option<(i64, char)> {
-    let s = input.trim().to_lowercase();
-    let mut chars = s.chars();
-    let unit = chars.next_back()?;
-    let number = chars.as_str().parse::<i64>().ok()?;
-    Some((number, unit))
-}
+pub use vicaya_core::query::{
+    attribute_filter, dir_size, hidden, max_depth, parse_query, CmpI64, CmpOp, CmpU64, Niyama,
+    NiyamaType, ParsedQuery,
+};
 
 /// State for the Drishti switcher overlay.
 pub struct DrishtiSwitcherState {
@@ -1027,6 +1396,7 @@ impl DrishtiSwitcherState {
             .filter(|view| {
                 matches_view_filter(view.label(), &needle)
                     || matches_view_filter(view.english_hint(), &needle)
+                    || matches_view_filter(view.hint(), &needle)
             })
             .collect()
     }
@@ -1137,6 +1507,216 @@ impl Default for KriyaSuchiState {
     }
 }
 
+/// Something the open-with overlay can hand a path to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenWithTarget {
+    /// An application from the `[associations]` config section.
+    Application(String),
+    /// macOS Quick Look (`qlmanage -p`).
+    QuickLook,
+    /// The regular `Enter` action ($EDITOR, then `open`).
+    Editor,
+}
+
+impl OpenWithTarget {
+    pub fn label(&self) -> &str {
+        match self {
+            Self::Application(app) => app,
+            Self::QuickLook => "Quick Look",
+            Self::Editor => "Editor",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::Application(_) => "from [associations]",
+            Self::QuickLook => "qlmanage -p",
+            Self::Editor => "$EDITOR / open",
+        }
+    }
+}
+
+/// State for the open-with overlay.
+pub struct OpenWithState {
+    /// Path the chosen target will open.
+    pub path: String,
+    pub targets: Vec<OpenWithTarget>,
+    pub selected_index: usize,
+}
+
+impl OpenWithState {
+    pub fn new() -> Self {
+        Self {
+            path: String::new(),
+            targets: Vec::new(),
+            selected_index: 0,
+        }
+    }
+
+    pub fn reset(&mut self, path: String, apps: &[String]) {
+        self.path = path;
+        self.targets = apps
+            .iter()
+            .map(|app| OpenWithTarget::Application(app.clone()))
+            .collect();
+        if cfg!(target_os = "macos") {
+            self.targets.push(OpenWithTarget::QuickLook);
+        }
+        self.targets.push(OpenWithTarget::Editor);
+        self.selected_index = 0;
+    }
+
+    pub fn selected_target(&self) -> Option<&OpenWithTarget> {
+        self.targets.get(self.selected_index)
+    }
+
+    pub fn select_next(&mut self) {
+        if self.targets.is_empty() {
+            return;
+        }
+        self.selected_index = (self.selected_index + 1) % self.targets.len();
+    }
+
+    pub fn select_previous(&mut self) {
+        if self.targets.is_empty() {
+            return;
+        }
+        self.selected_index = if self.selected_index == 0 {
+            self.targets.len() - 1
+        } else {
+            self.selected_index - 1
+        };
+    }
+}
+
+impl Default for OpenWithState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// State for the results export prompt.
+pub struct ExportInputState {
+    /// Destination file; empty (or `clipboard`) copies to the clipboard.
+    pub input: String,
+    pub format: ExportFormat,
+    pub error: Option<String>,
+}
+
+impl ExportInputState {
+    pub fn new() -> Self {
+        Self {
+            input: String::new(),
+            format: ExportFormat::Plain,
+            error: None,
+        }
+    }
+
+    pub fn push_char(&mut self, c: char) {
+        self.input.push(c);
+        self.input_changed();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.input.pop();
+        self.input_changed();
+    }
+
+    pub fn cycle_format(&mut self) {
+        self.format = self.format.next();
+    }
+
+    /// A `.csv` / `.json` / `.txt` destination picks its format.
+    fn input_changed(&mut self) {
+        self.error = None;
+        if let Some(format) = ExportFormat::from_path(Path::new(self.input.trim())) {
+            self.format = format;
+        }
+    }
+}
+
+impl Default for ExportInputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Tulana (comparison): a second phala pane with its own ksetra that runs the
+/// same prashna, for comparing two checkouts or a backup with the live tree.
+#[derive(Debug, Clone)]
+pub struct TulanaState {
+    /// Ksetra of the right-hand pane.
+    pub scope: PathBuf,
+    pub results: Vec<SearchResult>,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+    pub is_searching: bool,
+    pub truncated: bool,
+    /// Id of the latest search sent for this pane; older results are dropped.
+    pub search_id: u64,
+}
+
+impl TulanaState {
+    pub fn new(scope: PathBuf) -> Self {
+        Self {
+            scope,
+            results: Vec::new(),
+            selected_index: 0,
+            scroll_offset: 0,
+            is_searching: false,
+            truncated: false,
+            search_id: 0,
+        }
+    }
+
+    pub fn set_results(&mut self, results: Vec<SearchResult>) {
+        self.results = results;
+        self.selected_index = self
+            .selected_index
+            .min(self.results.len().saturating_sub(1));
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected_index < self.results.len().saturating_sub(1) {
+            self.selected_index += 1;
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    pub fn selected_result(&self) -> Option<&SearchResult> {
+        self.results.get(self.selected_index)
+    }
+}
+
+/// For each of `results` (listed under `scope`), whether its path relative to
+/// `scope` is missing from `other` (listed under `other_scope`). Without a
+/// scope, paths are compared whole.
+pub fn only_in(
+    results: &[SearchResult],
+    scope: Option<&Path>,
+    other: &[SearchResult],
+    other_scope: Option<&Path>,
+) -> Vec<bool> {
+    fn relative<'a>(path: &'a str, scope: Option<&Path>) -> &'a Path {
+        let path = Path::new(path);
+        scope
+            .and_then(|scope| path.strip_prefix(scope).ok())
+            .unwrap_or(path)
+    }
+
+    let theirs: std::collections::HashSet<&Path> = other
+        .iter()
+        .map(|result| relative(&result.path, other_scope))
+        .collect();
+    results
+        .iter()
+        .map(|result| !theirs.contains(relative(&result.path, scope)))
+        .collect()
+}
+
 /// State for the ksetra (scope) direct input overlay
 pub struct KsetraInputState {
     /// The path being typed
@@ -1149,6 +1729,8 @@ pub struct KsetraInputState {
     pub selected_completion: usize,
     /// Error message (e.g., "Directory not found")
     pub error: Option<String>,
+    /// The path sets the tulana pane's ksetra instead of the main one.
+    pub for_tulana: bool,
 }
 
 impl KsetraInputState {
@@ -1159,6 +1741,7 @@ impl KsetraInputState {
             completions: Vec::new(),
             selected_completion: 0,
             error: None,
+            for_tulana: false,
         }
     }
 
@@ -1168,6 +1751,7 @@ impl KsetraInputState {
         self.completions.clear();
         self.selected_completion = 0;
         self.error = None;
+        self.for_tulana = false;
     }
 
     /// Expand `~/` to home directory path
@@ -1325,11 +1909,32 @@ pub fn compute_content_line_numbers(lines: &[StyledLine]) -> Vec<Option<usize>>
         .collect()
 }
 
+/// How the preview pane renders a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreviewMode {
+    /// Text with syntax highlighting; hex dump for binary files.
+    #[default]
+    Auto,
+    Hex,
+    Text,
+}
+
 /// Preview state for the selected item.
 pub struct PreviewState {
     pub is_visible: bool,
     pub is_loading: bool,
+    /// When the in-flight preview was requested; drives the loading spinner.
+    pub loading_since: Option<std::time::Instant>,
     pub truncated: bool,
+    /// The worker can append another chunk of this file.
+    pub more_available: bool,
+    /// `L` was pressed; the event loop forwards it to the worker.
+    pub more_requested: bool,
+    pub loading_more: bool,
+    /// Requested rendering; reset to `Auto` when the selection changes.
+    pub mode: PreviewMode,
+    /// The current preview is a hex dump.
+    pub is_hex: bool,
     pub path: Option<String>,
     pub title: String,
     pub lines: Vec<StyledLine>,
@@ -1339,6 +1944,15 @@ pub struct PreviewState {
     pub search_query: String,
     pub search_input: String,
     pub search_cursor: usize,
+    /// `/` searches the contents of every listed result rather than just
+    /// this preview (Tab in the search prompt).
+    pub search_all: bool,
+    /// Matching lines per result path for `search_query`, while `search_all`
+    /// is on; filled in as the worker reads each file.
+    pub match_counts: HashMap<String, usize>,
+    /// Counting was asked for; the event loop forwards it to the worker
+    /// with the current results.
+    pub counts_requested: bool,
 }
 
 impl PreviewState {
@@ -1346,7 +1960,13 @@ impl PreviewState {
         Self {
             is_visible: true,
             is_loading: false,
+            loading_since: None,
             truncated: false,
+            more_available: false,
+            more_requested: false,
+            loading_more: false,
+            mode: PreviewMode::Auto,
+            is_hex: false,
             path: None,
             title: String::new(),
             lines: Vec::new(),
@@ -1356,12 +1976,21 @@ impl PreviewState {
             search_query: String::new(),
             search_input: String::new(),
             search_cursor: 0,
+            search_all: false,
+            match_counts: HashMap::new(),
+            counts_requested: false,
         }
     }
 
     pub fn clear(&mut self) {
         self.is_loading = false;
+        self.loading_since = None;
         self.truncated = false;
+        self.more_available = false;
+        self.more_requested = false;
+        self.loading_more = false;
+        self.mode = PreviewMode::Auto;
+        self.is_hex = false;
         self.path = None;
         self.title.clear();
         self.lines.clear();
@@ -1373,6 +2002,39 @@ impl PreviewState {
         self.is_visible = !self.is_visible;
     }
 
+    /// Flip between hex dump and text for the current preview.
+    pub fn toggle_hex(&mut self) {
+        self.mode = if self.is_hex {
+            PreviewMode::Text
+        } else {
+            PreviewMode::Hex
+        };
+    }
+
+    /// Ask for the next chunk of a truncated file preview.
+    pub fn request_more(&mut self) {
+        if self.more_available && !self.loading_more {
+            self.more_requested = true;
+        }
+    }
+
+    /// Append a chunk loaded with `request_more`, replacing the previous
+    /// truncation footer and renumbering content lines.
+    pub fn append_lines(&mut self, lines: Vec<StyledLine>, more_available: bool) {
+        while self
+            .lines
+            .last()
+            .is_some_and(|line| line.iter().all(|seg| seg.style.kind == TextKind::Meta))
+        {
+            self.lines.pop();
+        }
+        self.lines.extend(lines);
+        self.content_line_numbers = compute_content_line_numbers(&self.lines);
+        self.more_available = more_available;
+        self.truncated = more_available;
+        self.loading_more = false;
+    }
+
     pub fn toggle_line_numbers(&mut self) {
         self.show_line_numbers = !self.show_line_numbers;
     }
@@ -1390,12 +2052,40 @@ impl PreviewState {
     pub fn apply_search(&mut self) {
         self.search_query = self.search_input.trim().to_string();
         self.search_cursor = self.search_input.len();
+        self.request_counts();
     }
 
     pub fn clear_search(&mut self) {
         self.search_query.clear();
         self.search_input.clear();
         self.search_cursor = 0;
+        self.search_all = false;
+        self.match_counts.clear();
+        self.counts_requested = false;
+    }
+
+    /// Switch `/` between this preview and all listed results.
+    pub fn toggle_search_all(&mut self) {
+        self.search_all = !self.search_all;
+        if !self.search_all {
+            self.match_counts.clear();
+            self.counts_requested = false;
+        }
+    }
+
+    /// Count `search_query` across the results again (new query or new
+    /// results), when searching all of them.
+    pub fn request_counts(&mut self) {
+        self.match_counts.clear();
+        self.counts_requested = self.search_all && !self.search_query.is_empty();
+    }
+
+    /// Listed results known to contain `search_query`.
+    pub fn matching_results(&self) -> usize {
+        self.match_counts
+            .values()
+            .filter(|count| **count > 0)
+            .count()
     }
 
     pub fn insert_search_char(&mut self, c: char) {
@@ -1436,33 +2126,295 @@ mod tests {
     use super::*;
 
     #[test]
-    fn parse_query_extracts_term_and_filters() {
-        let parsed = parse_query("foo ext:rs,md type:file path:src/");
-        assert_eq!(parsed.term, "foo");
-        assert_eq!(parsed.niyamas.len(), 3);
-        assert!(matches!(
-            parsed.niyamas[0],
-            Niyama::Type {
-                kind: NiyamaType::File,
-                ..
-            }
+    fn open_with_lists_configured_apps_before_builtin_targets() {
+        let mut app = AppState::new();
+        app.show_open_with(
+            "/notes/todo.md".to_string(),
+            &["Typora".to_string(), "code".to_string()],
+        );
+
+        assert_eq!(app.mode, AppMode::OpenWith);
+        let open_with = &mut app.ui.open_with;
+        assert_eq!(open_with.path, "/notes/todo.md");
+        assert_eq!(
+            open_with.selected_target(),
+            Some(&OpenWithTarget::Application("Typora".to_string()))
+        );
+        assert_eq!(open_with.targets.last(), Some(&OpenWithTarget::Editor));
+        assert_eq!(
+            open_with.targets.contains(&OpenWithTarget::QuickLook),
+            cfg!(target_os = "macos")
+        );
+
+        open_with.select_previous();
+        assert_eq!(open_with.selected_target(), Some(&OpenWithTarget::Editor));
+        open_with.select_next();
+        assert_eq!(open_with.selected_index, 0);
+
+        app.close_open_with();
+        assert_eq!(app.mode, AppMode::Search);
+    }
+
+    #[test]
+    fn index_changes_patch_results_in_place_and_keep_the_selection() {
+        let result = |path: &str, score: f32, size: u64| SearchResult {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            score,
+            size,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+        let paths = |search: &SearchState| -> Vec<String> {
+            search.results.iter().map(|r| r.path.clone()).collect()
+        };
+        let mut search = SearchState::new();
+        search.set_results(vec![
+            result("/a.rs", 0.9, 1),
+            result("/b.rs", 0.5, 1),
+            result("/c.rs", 0.3, 1),
+        ]);
+        search.selected_index = 2;
+
+        // Unknown paths and nothing new change nothing.
+        assert!(!search.apply_index_change(&["/elsewhere".to_string()], Vec::new(), 4));
+        assert!(search.live_updated_at.is_none());
+
+        // A deletion, a modified entry refreshed where it is, and a new
+        // match slotted in by score.
+        assert!(search.apply_index_change(
+            &["/a.rs".to_string()],
+            vec![result("/b.rs", 0.8, 42), result("/new.rs", 0.4, 1)],
+            4,
         ));
-        assert!(matches!(parsed.niyamas[1], Niyama::Ext { .. }));
-        assert!(matches!(parsed.niyamas[2], Niyama::Path { .. }));
+        assert_eq!(paths(&search), ["/b.rs", "/new.rs", "/c.rs"]);
+        assert_eq!(search.results[0].size, 42);
+        assert_eq!(search.selected_result().unwrap().path, "/c.rs");
+        assert!(search.live_updated_at.is_some());
+
+        // The list does not grow past the search limit.
+        search.apply_index_change(&[], vec![result("/top.rs", 1.0, 1)], 4);
+        search.apply_index_change(&[], vec![result("/low.rs", 0.1, 1)], 4);
+        assert_eq!(paths(&search), ["/top.rs", "/b.rs", "/new.rs", "/c.rs"]);
+
+        // Removing the selected row leaves the selection in range.
+        search.apply_index_change(&["/c.rs".to_string()], Vec::new(), 4);
+        assert_eq!(search.selected_result().unwrap().path, "/new.rs");
+
+        search.set_results(Vec::new());
+        assert!(search.live_updated_at.is_none());
+    }
+
+    #[test]
+    fn fingerprinted_results_are_verified_by_content_not_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ContentHashConfig::default();
+        let hashed = |name: &str, indexed: &str, now: &str| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, now).unwrap();
+            SearchResult {
+                path: path.to_string_lossy().to_string(),
+                name: name.to_string(),
+                score: 1.0,
+                size: indexed.len() as u64,
+                // Deliberately wrong: fingerprints take precedence.
+                mtime: 1,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 1,
+                content_hash: Some(vicaya_core::content_hash::fingerprint(
+                    indexed.as_bytes(),
+                    indexed.len() as u64,
+                )),
+                is_dir: false,
+                total_size: None,
+                explain: None,
+            }
+        };
+
+        let touched = hashed("touched.txt", "hello", "hello");
+        assert_eq!(
+            Freshness::check(&touched, true, &config),
+            Freshness::Current
+        );
+        let rewritten = hashed("rewritten.txt", "hello", "jello");
+        assert_eq!(
+            Freshness::check(&rewritten, true, &config),
+            Freshness::Stale
+        );
+    }
+
+    #[test]
+    fn switching_drishti_restores_where_each_was_left() {
+        let result = |path: &str| SearchResult {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+        let mut app = AppState::new();
+        app.search.set_query("main ext:rs".to_string());
+        app.search
+            .set_results(vec![result("/a.rs"), result("/b.rs"), result("/c.rs")]);
+        app.search.selected_index = 2;
+        app.ui.scroll_offset = 1;
+
+        // A drishti not visited yet starts from the current prashna.
+        app.set_view(ViewKind::Sthana);
+        assert_eq!(app.search.query, "main ext:rs");
+        app.search.set_query("src".to_string());
+        app.search.set_results(vec![result("/src")]);
+        app.search.selected_index = 0;
+        app.ui.scroll_offset = 0;
+
+        app.set_view(ViewKind::Patra);
+        assert_eq!(app.search.query, "main ext:rs");
+        assert_eq!(app.search.results.len(), 3);
+        assert_eq!(app.search.selected_result().unwrap().path, "/c.rs");
+        assert_eq!(app.ui.scroll_offset, 1);
+        // The re-run search keeps the selection on the same path.
+        app.search
+            .set_results(vec![result("/c.rs"), result("/a.rs"), result("/b.rs")]);
+        assert_eq!(app.search.selected_index, 0);
+
+        app.set_view(ViewKind::Sthana);
+        assert_eq!(app.search.query, "src");
+        assert_eq!(app.search.selected_result().unwrap().path, "/src");
+    }
+
+    #[test]
+    fn tulana_flags_relative_paths_missing_from_the_other_scope() {
+        let result = |path: &str| SearchResult {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+        let live = vec![result("/live/src/main.rs"), result("/live/src/new.rs")];
+        let backup = vec![result("/backup/src/main.rs"), result("/backup/old.rs")];
+        let live_scope = Some(Path::new("/live"));
+        let backup_scope = Some(Path::new("/backup"));
+
+        assert_eq!(
+            only_in(&live, live_scope, &backup, backup_scope),
+            vec![false, true]
+        );
+        assert_eq!(
+            only_in(&backup, backup_scope, &live, live_scope),
+            vec![false, true]
+        );
+        assert_eq!(only_in(&live, None, &backup, None), vec![true, true]);
+
+        let mut app = AppState::new();
+        app.search.focus = FocusTarget::Tulana;
+        app.open_tulana(PathBuf::from("/backup"));
+        let tulana = app.tulana.as_mut().unwrap();
+        tulana.set_results(backup);
+        tulana.select_next();
+        tulana.select_next();
+        assert_eq!(tulana.selected_result().unwrap().name, "old.rs");
+        app.close_tulana();
+        assert!(app.tulana.is_none());
+        assert_eq!(app.search.focus, FocusTarget::Results);
+    }
+
+    #[test]
+    fn accepted_name_search_results_queue_ranking_events() {
+        let result = |path: &str| SearchResult {
+            path: path.to_string(),
+            name: path.rsplit('/').next().unwrap().to_string(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+        let mut app = AppState::new();
+        app.search.set_query("main".to_string());
+        app.search
+            .set_results(vec![result("/repo/main.rs"), result("/repo/src/main.rs")]);
+
+        app.record_smriti_usage("/repo/src/main.rs".to_string(), SmritiAction::Open);
+        // Paths outside the results (e.g. an entered scope) are not rankings.
+        app.record_smriti_usage("/elsewhere".to_string(), SmritiAction::Enter);
+
+        assert_eq!(app.smriti_events.len(), 2);
+        assert_eq!(app.ranking_events.len(), 1);
+        let event = &app.ranking_events[0];
+        assert_eq!(event.query, "main");
+        assert_eq!(event.rank, 2);
+        assert_eq!(event.result_count, 2);
+        assert_eq!(event.action, SmritiAction::Open);
+
+        // Content matches are not ranked by the name scorer.
+        app.view = ViewKind::Antarvicaya;
+        app.record_smriti_usage("/repo/main.rs".to_string(), SmritiAction::Open);
+        assert_eq!(app.ranking_events.len(), 1);
     }
 
     #[test]
-    fn parse_size_expr_parses_units() {
-        let cmp = parse_size_expr(">10mb").unwrap();
-        assert_eq!(cmp.op, CmpOp::Gt);
-        assert_eq!(cmp.value, 10 * 1024 * 1024);
+    fn usage_badges_follow_local_actions() {
+        let mut app = AppState::new();
+        // Nothing is counted before the daemon's usage arrives.
+        app.record_smriti_usage("/repo/a.rs".to_string(), SmritiAction::Open);
+        assert!(app.smriti_usage.is_none());
+
+        app.smriti_usage = Some(HashMap::new());
+        app.record_smriti_usage("/repo/a.rs".to_string(), SmritiAction::Open);
+        app.record_smriti_usage("/repo/a.rs".to_string(), SmritiAction::Copy);
+        let usage = app.smriti_usage.as_ref().unwrap()["/repo/a.rs"];
+        assert_eq!((usage.opens, usage.uses), (1, 2));
+        assert!(usage.last_used > 0);
+
+        app.forget_smriti_path("/repo/a.rs".to_string());
+        assert!(app.smriti_usage.as_ref().unwrap().is_empty());
     }
 
     #[test]
-    fn parse_mtime_expr_inverts_relative_age() {
-        let cmp = parse_mtime_expr("<7d", 1000).unwrap();
-        assert_eq!(cmp.op, CmpOp::Gt);
-        assert_eq!(cmp.value, 1000 - 7 * 60 * 60 * 24);
+    fn aliases_expand_into_niyamas_before_parsing() {
+        let mut app = AppState::new();
+        app.aliases
+            .insert("cfg".to_string(), "ext:toml,yaml type:file".to_string());
+        app.search.query = "server @cfg".to_string();
+
+        let parsed = app.parsed_query();
+        assert_eq!(parsed.term, "server");
+        assert_eq!(
+            parsed.niyamas.iter().map(Niyama::raw).collect::<Vec<_>>(),
+            vec!["type:file", "ext:toml,yaml"]
+        );
     }
 
     #[test]
@@ -1709,6 +2661,9 @@ mod tests {
         assert!(ViewKind::Patra.is_enabled());
         assert!(ViewKind::Sthana.is_enabled());
         assert!(ViewKind::Smriti.is_enabled());
+        assert!(ViewKind::Navatama.is_enabled());
+        assert!(ViewKind::Tyakta.is_enabled());
+        assert_eq!(ViewKind::Tyakta.english_hint(), "Deleted");
         assert!(!ViewKind::Itihasa.is_enabled());
     }
 
@@ -1793,6 +2748,7 @@ mod tests {
         preview.scroll = 2;
         preview.truncated = true;
         preview.is_loading = true;
+        preview.loading_since = Some(std::time::Instant::now());
         preview.toggle();
         assert!(!preview.is_visible);
         preview.toggle_line_numbers();
@@ -1811,11 +2767,45 @@ mod tests {
         preview.clear_search();
         assert!(preview.search_query.is_empty());
 
+        let line = |text: &str, kind| {
+            vec![StyledSegment {
+                text: text.to_string(),
+                style: TextStyle {
+                    kind,
+                    ..TextStyle::default()
+                },
+            }]
+        };
+        preview.lines = vec![
+            line("one", TextKind::Normal),
+            line("", TextKind::Meta),
+            line("… (preview truncated — L loads more)", TextKind::Meta),
+        ];
+        preview.request_more();
+        assert!(!preview.more_requested);
+        preview.more_available = true;
+        preview.request_more();
+        assert!(preview.more_requested);
+        preview.loading_more = true;
+        preview.append_lines(vec![line("two", TextKind::Normal)], false);
+        assert_eq!(preview.lines.len(), 2);
+        assert_eq!(preview.content_line_numbers, vec![Some(1), Some(2)]);
+        assert!(!preview.more_available);
+        assert!(!preview.loading_more);
+
+        preview.toggle_hex();
+        assert_eq!(preview.mode, PreviewMode::Hex);
+        preview.is_hex = true;
+        preview.toggle_hex();
+        assert_eq!(preview.mode, PreviewMode::Text);
+
         preview.clear();
+        assert_eq!(preview.mode, PreviewMode::Auto);
         assert!(preview.path.is_none());
         assert!(preview.lines.is_empty());
         assert_eq!(preview.scroll, 0);
         assert!(!preview.is_loading);
+        assert!(preview.loading_since.is_none());
         assert!(!preview.truncated);
     }
 }
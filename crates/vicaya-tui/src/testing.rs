@@ -0,0 +1,341 @@
+//! Headless scripting for UI tests (the `testing` feature).
+//!
+//! A [`Harness`] runs the real main loop and worker against an [`AppState`],
+//! drawing to a ratatui `TestBackend` instead of the terminal. Scripts feed it
+//! key and mouse events, wait for the worker to answer, and assert on the
+//! state or the rendered screen. The worker talks to the daemon socket of the
+//! current `VICAYA_DIR`; [`MockDaemon`] serves canned responses there.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use crossterm::event::KeyCode;
+//! use vicaya_tui::testing::{Harness, MockDaemon};
+//!
+//! let dir = tempfile::tempdir().unwrap();
+//! std::env::set_var("VICAYA_DIR", dir.path());
+//! let _daemon = MockDaemon::with_results(dir.path(), Vec::new()).unwrap();
+//!
+//! let mut tui = Harness::new(vicaya_tui::AppState::new(), 100, 30);
+//! tui.type_text("main").key(KeyCode::Tab);
+//! assert!(tui.settle(Duration::from_secs(5)));
+//! assert!(tui.screen().contains("prashna: main"));
+//! ```
+
+use std::io::{BufReader, Write};
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::backend::TestBackend;
+use ratatui::buffer::Buffer;
+use ratatui::Terminal;
+use vicaya_core::ipc::{BuildInfo, DaemonStatus, Request, Response, SearchResult};
+
+use crate::app::{ui_render, EventLoop};
+use crate::state::AppState;
+use crate::worker::{start_worker, WorkerCommand};
+
+/// How long [`Harness::wait_for`] sleeps between passes of the main loop.
+const WAIT_STEP: Duration = Duration::from_millis(10);
+
+/// The TUI main loop driven by scripted events instead of a terminal.
+///
+/// Every event runs one pass of the loop, the same as a keypress does in
+/// `vicaya-tui`: the event is handled, worker answers received so far are
+/// applied, a frame is drawn, and any searches or previews the state now
+/// calls for are sent. Answers arrive asynchronously, so scripts that need
+/// them call [`wait_for`](Self::wait_for) or [`settle`](Self::settle).
+pub struct Harness {
+    app: AppState,
+    terminal: Terminal<TestBackend>,
+    event_loop: EventLoop,
+    cmd_tx: mpsc::Sender<WorkerCommand>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Harness {
+    /// Start a worker and draw `app` on a `width` x `height` buffer.
+    pub fn new(mut app: AppState, width: u16, height: u16) -> Self {
+        let (cmd_tx, cmd_rx) = mpsc::channel();
+        let (evt_tx, evt_rx) = mpsc::channel();
+        let worker = start_worker(cmd_rx, evt_tx);
+        let event_loop = EventLoop::new(&mut app, cmd_tx.clone(), evt_rx);
+        let terminal = Terminal::new(TestBackend::new(width, height))
+            .expect("a TestBackend terminal cannot fail to start");
+
+        let mut harness = Self {
+            app,
+            terminal,
+            event_loop,
+            cmd_tx,
+            worker: Some(worker),
+        };
+        harness.tick();
+        harness
+    }
+
+    /// The state being driven.
+    pub fn app(&self) -> &AppState {
+        &self.app
+    }
+
+    /// The state being driven, for setting up a scenario between events.
+    pub fn app_mut(&mut self) -> &mut AppState {
+        &mut self.app
+    }
+
+    /// Run one pass of the main loop without an event.
+    pub fn tick(&mut self) -> &mut Self {
+        self.event_loop.apply_worker_events(&mut self.app);
+        let app = &mut self.app;
+        self.terminal
+            .draw(|f| ui_render(f, app))
+            .expect("drawing to a TestBackend cannot fail");
+        self.event_loop.sync(&mut self.app);
+        self.event_loop.send_requests(&mut self.app);
+        self
+    }
+
+    /// Handle `event`, then run one pass of the main loop.
+    pub fn event(&mut self, event: Event) -> &mut Self {
+        self.event_loop.handle_event(&mut self.app, event);
+        self.event_loop.send_requests(&mut self.app);
+        self.tick()
+    }
+
+    /// Press `code` without modifiers.
+    pub fn key(&mut self, code: KeyCode) -> &mut Self {
+        self.key_with(code, KeyModifiers::NONE)
+    }
+
+    /// Press `code` with `modifiers`.
+    pub fn key_with(&mut self, code: KeyCode, modifiers: KeyModifiers) -> &mut Self {
+        self.event(Event::Key(KeyEvent::new(code, modifiers)))
+    }
+
+    /// Type `text` one character at a time; uppercase letters are sent with
+    /// Shift, as a terminal reports them.
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for ch in text.chars() {
+            let modifiers = if ch.is_uppercase() {
+                KeyModifiers::SHIFT
+            } else {
+                KeyModifiers::NONE
+            };
+            self.key_with(KeyCode::Char(ch), modifiers);
+        }
+        self
+    }
+
+    /// Click the left mouse button at `column`, `row`.
+    pub fn click(&mut self, column: u16, row: u16) -> &mut Self {
+        self.event(Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        }))
+    }
+
+    /// Run the main loop until `done` holds, for at most `timeout`. Returns
+    /// whether it did.
+    pub fn wait_for(&mut self, timeout: Duration, mut done: impl FnMut(&AppState) -> bool) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.tick();
+            if done(&self.app) {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(WAIT_STEP);
+        }
+    }
+
+    /// Run the main loop until the prashna has been searched and no search
+    /// or preview is still loading, for at most `timeout`. Returns whether
+    /// that happened.
+    pub fn settle(&mut self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            self.tick();
+            if self.event_loop.is_settled(&self.app) {
+                // Draw the answers the last pass applied.
+                self.tick();
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(WAIT_STEP);
+        }
+    }
+
+    /// The last frame drawn.
+    pub fn buffer(&self) -> &Buffer {
+        self.terminal.backend().buffer()
+    }
+
+    /// The last frame drawn as text, one line per row.
+    pub fn screen(&self) -> String {
+        let buffer = self.buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        let _ = self.cmd_tx.send(WorkerCommand::Quit);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+type Responder = dyn Fn(&Request) -> Response + Send + Sync;
+
+/// A stand-in daemon on `<vicaya_dir>/daemon.sock` that answers each request
+/// with a caller-supplied response and records what it was asked.
+pub struct MockDaemon {
+    stop: Arc<AtomicBool>,
+    requests: Arc<Mutex<Vec<Request>>>,
+    listener: Option<JoinHandle<()>>,
+}
+
+impl MockDaemon {
+    /// Listen on the daemon socket in `vicaya_dir`, answering every request
+    /// with `respond`. A `Subscribed` answer keeps the connection open, as
+    /// the daemon does, but nothing is pushed on it.
+    pub fn start(
+        vicaya_dir: &Path,
+        respond: impl Fn(&Request) -> Response + Send + Sync + 'static,
+    ) -> std::io::Result<Self> {
+        let listener = UnixListener::bind(vicaya_dir.join("daemon.sock"))?;
+        listener.set_nonblocking(true)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let respond: Arc<Responder> = Arc::new(respond);
+
+        let handle = {
+            let stop = Arc::clone(&stop);
+            let requests = Arc::clone(&requests);
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    match listener.accept() {
+                        Ok((stream, _)) => {
+                            let requests = Arc::clone(&requests);
+                            let respond = Arc::clone(&respond);
+                            std::thread::spawn(move || serve(stream, &requests, &*respond));
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                            std::thread::sleep(WAIT_STEP);
+                        }
+                        Err(_) => break,
+                    }
+                }
+            })
+        };
+
+        Ok(Self {
+            stop,
+            requests,
+            listener: Some(handle),
+        })
+    }
+
+    /// A daemon whose searches all return `results`; see [`canned_response`].
+    pub fn with_results(vicaya_dir: &Path, results: Vec<SearchResult>) -> std::io::Result<Self> {
+        Self::start(vicaya_dir, move |request| {
+            canned_response(request, &results)
+        })
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockDaemon {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(listener) = self.listener.take() {
+            let _ = listener.join();
+        }
+    }
+}
+
+fn serve(
+    mut stream: std::os::unix::net::UnixStream,
+    requests: &Mutex<Vec<Request>>,
+    respond: &Responder,
+) {
+    let Ok(read) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(read);
+    while let Ok(Some(line)) = vicaya_core::ipc::read_message(&mut reader) {
+        let Ok(request) = Request::from_json(&line) else {
+            return;
+        };
+        let response = respond(&request);
+        requests.lock().unwrap().push(request);
+        let Ok(mut json) = response.to_json() else {
+            return;
+        };
+        json.push('\n');
+        if stream.write_all(json.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+/// The answer an idle daemon holding `results` gives: every search returns
+/// `results`, status reports an idle index of that size, subscriptions are
+/// acknowledged, and anything else gets `Ok`.
+pub fn canned_response(request: &Request, results: &[SearchResult]) -> Response {
+    match request {
+        Request::Search(_) => Response::SearchResults {
+            results: results.to_vec(),
+            truncated: false,
+            suggestions: Vec::new(),
+            outside_scope: Vec::new(),
+        },
+        Request::Status => Response::Status(DaemonStatus {
+            pid: std::process::id() as i32,
+            build: BuildInfo::default(),
+            indexed_files: results.len(),
+            trigram_count: 0,
+            arena_size: 0,
+            index_allocated_bytes: 0,
+            state_allocated_bytes: 0,
+            last_updated: 0,
+            reconciling: false,
+            pending_watcher_events: 0,
+            journal_entries: 0,
+            reconcile_progress: None,
+            reconcile_scan: None,
+            warmup_ms: None,
+            journal_fsync: None,
+            index_cap: None,
+            last_reconcile: None,
+            next_reconcile: None,
+            index_profile: Default::default(),
+        }),
+        Request::Subscribe(_) => Response::Subscribed,
+        Request::Suggest { .. } => Response::Suggestions { names: Vec::new() },
+        _ => Response::Ok,
+    }
+}
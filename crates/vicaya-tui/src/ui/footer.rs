@@ -11,14 +11,15 @@ use ratatui::{
 };
 use unicode_width::UnicodeWidthStr;
 use vicaya_core::build_info::BUILD_INFO;
+use vicaya_core::i18n::t;
 
 pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     // Priority order: discovery first, then navigation, then features (least used last)
     let mut spans = vec![
         Span::styled("?:", Style::default().fg(ui::PRIMARY)),
-        Span::styled(" help  ", Style::default().fg(ui::TEXT_SECONDARY)),
+        hint("tui.footer.help"),
         Span::styled("Tab:", Style::default().fg(ui::PRIMARY)),
-        Span::styled(" focus  ", Style::default().fg(ui::TEXT_SECONDARY)),
+        hint("tui.footer.focus"),
         Span::styled("Ctrl+T:", Style::default().fg(ui::PRIMARY)),
         Span::styled(" drishti  ", Style::default().fg(ui::TEXT_SECONDARY)),
         Span::styled("Ctrl+P:", Style::default().fg(ui::PRIMARY)),
@@ -29,37 +30,55 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
         Span::styled(" ksetra  ", Style::default().fg(ui::TEXT_SECONDARY)),
     ];
 
-    if app.search.is_results_focused() {
+    if app.search.is_results_focused() && app.pick_mode {
         spans.extend(vec![
             Span::styled("↵:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" open/enter  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.pick"),
+            Span::styled("Space:", Style::default().fg(ui::PRIMARY)),
+            hint("tui.footer.mark"),
             Span::styled("h/l:", Style::default().fg(ui::PRIMARY)),
             Span::styled(" ksetra  ", Style::default().fg(ui::TEXT_SECONDARY)),
             Span::styled("y:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" copy  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.copy"),
+        ]);
+    } else if app.search.is_results_focused() {
+        spans.extend(vec![
+            Span::styled("↵:", Style::default().fg(ui::PRIMARY)),
+            hint("tui.footer.open_enter"),
+            Span::styled("h/l:", Style::default().fg(ui::PRIMARY)),
+            Span::styled(" ksetra  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            Span::styled("Space:", Style::default().fg(ui::PRIMARY)),
+            hint("tui.footer.mark"),
+            Span::styled("y:", Style::default().fg(ui::PRIMARY)),
+            hint("tui.footer.copy"),
             Span::styled("p:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" print  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.print"),
             Span::styled("r:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" reveal  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.reveal"),
+            Span::styled("O:", Style::default().fg(ui::PRIMARY)),
+            hint("tui.footer.open_with"),
         ]);
     }
 
     if app.search.is_preview_focused() || app.mode == crate::state::AppMode::PreviewSearch {
         spans.extend(vec![
             Span::styled("/:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" find  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.find"),
             Span::styled("n/N:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" next/prev  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.next_prev"),
             Span::styled("Ctrl+N:", Style::default().fg(ui::PRIMARY)),
-            Span::styled(" lines  ", Style::default().fg(ui::TEXT_SECONDARY)),
+            hint("tui.footer.lines"),
         ]);
     }
 
     spans.extend(vec![
         Span::styled("Esc:", Style::default().fg(ui::PRIMARY)),
-        Span::styled(" clear  ", Style::default().fg(ui::TEXT_SECONDARY)),
+        hint("tui.footer.clear"),
         Span::styled("Ctrl-C:", Style::default().fg(ui::PRIMARY)),
-        Span::styled(" quit", Style::default().fg(ui::TEXT_SECONDARY)),
+        Span::styled(
+            format!(" {}", t("tui.footer.quit")),
+            Style::default().fg(ui::TEXT_SECONDARY),
+        ),
     ]);
 
     if let Some(msg) = &app.error {
@@ -98,6 +117,14 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     f.render_widget(build, chunks[1]);
 }
 
+/// A translated key hint, padded like the Sanskrit ones.
+fn hint(key: &'static str) -> Span<'static> {
+    Span::styled(
+        format!(" {}  ", t(key)),
+        Style::default().fg(ui::TEXT_SECONDARY),
+    )
+}
+
 fn compact_build_info(app: &AppState) -> String {
     let version = BUILD_INFO.version;
     let sha = BUILD_INFO.git_sha;
@@ -1,21 +1,23 @@
 //! Header rendering.
 
-use crate::state::AppState;
+use crate::client::DaemonStatus;
+use crate::state::{AppState, BreadcrumbTarget};
 use crate::ui;
 use ratatui::{
     layout::Rect,
-    style::{Modifier, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
+use vicaya_core::i18n::t;
 
-pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
-    let drishti = format!(
-        "drishti: {} ({})",
-        app.view.label(),
-        app.view.english_hint()
-    );
+/// Pending watcher events above which results may visibly lag the filesystem.
+const WATCHER_LAG_THRESHOLD: usize = 1_000;
+
+pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let drishti = format!("drishti: {} ({})", app.view.label(), app.view.hint());
 
     // Calculate available width for ksetra breadcrumbs.
     // Fixed elements: "vicaya" (6) + separators (4x3=12) + icons (8) + drishti (~30)
@@ -27,24 +29,30 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
         .ksetra
         .breadcrumbs_truncated(available_for_ksetra.max(20));
     let ksetra = format!("ksetra: {}", ksetra_breadcrumbs);
+    let ksetra_prefix = "ksetra: ".len();
 
-    let (rakshaka_text, rakshaka_color, suchi_text, reconciling) =
+    let (rakshaka_text, rakshaka_color, suchi_text, indicators) =
         if let Some(status) = &app.daemon_status {
-            let suchi = format!("suchi  {}", format_count(status.indexed_files));
-            let reconciling = status.reconciling;
-            let rakshaka = "rakshaka  ok";
-            let rakshaka_color = if reconciling {
-                ui::WARNING
+            let lite = if status.index_profile.tracks_file_identity() {
+                String::new()
             } else {
+                format!(" ({})", t("tui.header.lite"))
+            };
+            let suchi = format!("suchi  {}{lite}", format_count(status.indexed_files));
+            let indicators = activity_indicators(status);
+            let rakshaka = format!("rakshaka  {}", t("tui.header.ok"));
+            let rakshaka_color = if indicators.is_empty() {
                 ui::SUCCESS
+            } else {
+                ui::WARNING
             };
-            (rakshaka.to_string(), rakshaka_color, suchi, reconciling)
+            (rakshaka, rakshaka_color, suchi, indicators)
         } else {
             (
-                "rakshaka  offline".to_string(),
+                format!("rakshaka  {}", t("tui.header.offline")),
                 ui::ERROR,
                 "suchi  ?".to_string(),
-                false,
+                Vec::new(),
             )
         };
 
@@ -62,7 +70,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
         Span::styled(drishti, Style::default().fg(ui::ACCENT)),
         sep.clone(),
         Span::styled("⌁ ", Style::default().fg(ui::TEXT_SECONDARY)),
-        Span::styled(ksetra, Style::default().fg(ui::TEXT_SECONDARY)),
+        Span::styled(ksetra.clone(), Style::default().fg(ui::TEXT_SECONDARY)),
         sep.clone(),
         Span::styled("● ", Style::default().fg(rakshaka_color)),
         Span::styled(rakshaka_text, Style::default().fg(rakshaka_color)),
@@ -71,13 +79,28 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
         Span::styled(suchi_text, Style::default().fg(ui::INFO)),
     ];
 
-    if reconciling {
+    let ksetra_column = spans
+        .iter()
+        .take_while(|span| span.content.as_ref() != ksetra)
+        .map(|span| span.width())
+        .sum::<usize>()
+        + ksetra_prefix;
+    let inner_end = area.right().saturating_sub(1);
+    app.ui.breadcrumb_targets = breadcrumb_targets(
+        &ksetra_breadcrumbs,
+        app.ksetra.depth(),
+        area.x + 1 + ksetra_column as u16,
+        area.y + 1,
+    )
+    .into_iter()
+    .filter(|target| target.columns.start < inner_end)
+    .collect();
+
+    for (text, color) in indicators {
         spans.push(Span::styled("  ", Style::default()));
         spans.push(Span::styled(
-            "reconciling…",
-            Style::default()
-                .fg(ui::WARNING)
-                .add_modifier(Modifier::ITALIC),
+            text,
+            Style::default().fg(color).add_modifier(Modifier::ITALIC),
         ));
     }
 
@@ -91,6 +114,65 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     f.render_widget(header, area);
 }
 
+/// Click targets for breadcrumbs `shown` from `column` on `row`. The shown
+/// segments are the last ones of a ksetra `depth` levels deep, so each jumps
+/// to its own level; `global` jumps to depth 0.
+fn breadcrumb_targets(shown: &str, depth: usize, column: u16, row: u16) -> Vec<BreadcrumbTarget> {
+    let parts: Vec<&str> = if depth == 0 {
+        vec![shown]
+    } else {
+        shown.split(" ▸ ").collect()
+    };
+    let first_depth = (depth + 1).saturating_sub(parts.len());
+    let mut start = column;
+    let mut targets = Vec::with_capacity(parts.len());
+    for (idx, part) in parts.iter().enumerate() {
+        let end = start + part.width() as u16;
+        targets.push(BreadcrumbTarget {
+            row,
+            columns: start..end,
+            depth: first_depth + idx,
+        });
+        start = end + " ▸ ".width() as u16;
+    }
+    targets
+}
+
+/// Compact indicators explaining why results may be stale.
+fn activity_indicators(status: &DaemonStatus) -> Vec<(String, Color)> {
+    let mut out = Vec::new();
+
+    if status.reconciling {
+        let mut text = match status.reconcile_progress {
+            Some(progress) => format!("⟳ reconciling {progress}%"),
+            None => "⟳ reconciling…".to_string(),
+        };
+        if let Some(scan) = status.reconcile_scan {
+            text.push_str(&format!(" · {}", scan.profile.as_str()));
+        }
+        out.push((text, ui::WARNING));
+    }
+
+    if let Some(cap) = &status.index_cap {
+        out.push((
+            format!("⚠ capped ({} skipped)", format_count(cap.total_skipped())),
+            ui::WARNING,
+        ));
+    }
+
+    if status.pending_watcher_events >= WATCHER_LAG_THRESHOLD {
+        out.push((
+            format!(
+                "⚠ watcher lagging ({})",
+                format_count(status.pending_watcher_events)
+            ),
+            ui::ERROR,
+        ));
+    }
+
+    out
+}
+
 fn format_count(n: usize) -> String {
     let s = n.to_string();
     let mut out = String::with_capacity(s.len() + s.len() / 3);
@@ -102,3 +184,110 @@ fn format_count(n: usize) -> String {
     }
     out.chars().rev().collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vicaya_core::ipc::BuildInfo;
+
+    fn status() -> DaemonStatus {
+        DaemonStatus {
+            pid: 0,
+            build: BuildInfo::default(),
+            indexed_files: 10,
+            trigram_count: 20,
+            arena_size: 30,
+            last_updated: 0,
+            reconciling: false,
+            pending_watcher_events: 0,
+            journal_entries: 0,
+            reconcile_progress: None,
+            reconcile_scan: None,
+            index_cap: None,
+            index_allocated_bytes: 0,
+            state_allocated_bytes: 0,
+            warmup_ms: None,
+            journal_fsync: None,
+            last_reconcile: None,
+            next_reconcile: None,
+            index_profile: Default::default(),
+        }
+    }
+
+    #[test]
+    fn truncated_breadcrumbs_click_through_to_their_own_level() {
+        let targets = breadcrumb_targets("…src ▸ ui", 5, 10, 1);
+        assert_eq!(
+            targets,
+            [
+                BreadcrumbTarget {
+                    row: 1,
+                    columns: 10..14,
+                    depth: 4
+                },
+                BreadcrumbTarget {
+                    row: 1,
+                    columns: 17..19,
+                    depth: 5
+                },
+            ]
+        );
+        assert_eq!(breadcrumb_targets("global", 0, 10, 1)[0].depth, 0);
+    }
+
+    #[test]
+    fn activity_indicators_are_empty_when_daemon_is_idle() {
+        let mut idle = status();
+        idle.pending_watcher_events = WATCHER_LAG_THRESHOLD - 1;
+        idle.journal_entries = 50_000;
+        assert!(activity_indicators(&idle).is_empty());
+    }
+
+    #[test]
+    fn activity_indicators_report_reconcile_progress_and_watcher_lag() {
+        let mut busy = status();
+        busy.reconciling = true;
+        busy.reconcile_progress = Some(42);
+        busy.pending_watcher_events = 12_345;
+
+        let texts: Vec<String> = activity_indicators(&busy)
+            .into_iter()
+            .map(|(text, _)| text)
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "⟳ reconciling 42%".to_string(),
+                "⚠ watcher lagging (12,345)".to_string()
+            ]
+        );
+
+        busy.reconcile_progress = None;
+        assert_eq!(activity_indicators(&busy)[0].0, "⟳ reconciling…");
+
+        busy.reconcile_scan = Some(vicaya_core::config::ResolvedScan {
+            profile: vicaya_core::config::ScanProfile::Reconcile,
+            threads: 1,
+            throttle_ms: 10,
+        });
+        assert_eq!(
+            activity_indicators(&busy)[0].0,
+            "⟳ reconciling… · reconcile"
+        );
+    }
+
+    #[test]
+    fn activity_indicators_warn_when_the_index_is_capped() {
+        let mut capped = status();
+        capped.index_cap = Some(vicaya_core::ipc::IndexCap {
+            max_indexed_files: 10,
+            skipped: 1_500,
+            skipped_live: 2,
+            roots: Vec::new(),
+        });
+        assert_eq!(
+            activity_indicators(&capped),
+            vec![("⚠ capped (1,502 skipped)".to_string(), ui::WARNING)]
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Result icons: one glyph per file kind in front of every phala row.
+//!
+//! Kinds come from what the index knows (directory flag, mode bits, name),
+//! plus an `lstat` for symlinks, which the index resolves. Glyphs come in
+//! three sets picked by the `icons` config: Nerd Font codepoints, plain
+//! Unicode symbols, and ASCII letters for terminals without UTF-8.
+
+use crate::ui;
+use ratatui::style::Color;
+use vicaya_core::config::IconStyle;
+use vicaya_index::SearchResult;
+
+/// What a result is, for its icon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Directory,
+    Application,
+    Symlink,
+    Executable,
+    Code,
+    Text,
+    Document,
+    Image,
+    Audio,
+    Video,
+    Archive,
+    Config,
+    File,
+}
+
+const CODE: &[&str] = &[
+    "c", "cc", "cpp", "cs", "css", "go", "h", "hpp", "html", "java", "js", "jsx", "kt", "lua", "m",
+    "mm", "php", "pl", "py", "rb", "rs", "scss", "sh", "sql", "swift", "ts", "tsx", "vue", "zig",
+    "zsh", "fish", "bash",
+];
+const TEXT: &[&str] = &["txt", "md", "markdown", "rst", "org", "log", "tex", "adoc"];
+const DOCUMENT: &[&str] = &[
+    "pdf", "doc", "docx", "rtf", "odt", "pages", "xls", "xlsx", "csv", "numbers", "ppt", "pptx",
+    "key", "epub",
+];
+const IMAGE: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "bmp", "svg", "webp", "heic", "tif", "tiff", "ico", "psd", "raw",
+];
+const AUDIO: &[&str] = &["mp3", "wav", "flac", "aac", "m4a", "ogg", "aiff", "opus"];
+const VIDEO: &[&str] = &["mp4", "mov", "mkv", "avi", "webm", "m4v", "wmv"];
+const ARCHIVE: &[&str] = &[
+    "zip", "tar", "gz", "tgz", "bz2", "xz", "zst", "7z", "rar", "dmg", "iso", "jar", "whl",
+];
+const CONFIG: &[&str] = &[
+    "toml", "yaml", "yml", "json", "ini", "conf", "cfg", "plist", "xml", "env", "lock",
+];
+
+impl FileKind {
+    /// The kind of `result`, shown as a directory when `as_dir` (Sthana
+    /// lists directories even from indexes without the flag).
+    pub fn of(result: &SearchResult, as_dir: bool, is_symlink: bool) -> Self {
+        if is_symlink {
+            return Self::Symlink;
+        }
+        if vicaya_core::paths::is_application_name(&result.name) {
+            return Self::Application;
+        }
+        if as_dir {
+            return Self::Directory;
+        }
+        let ext = std::path::Path::new(&result.name)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(str::to_ascii_lowercase);
+        let Some(ext) = ext else {
+            return if result.mode & 0o111 != 0 {
+                Self::Executable
+            } else {
+                Self::File
+            };
+        };
+        let ext = ext.as_str();
+        [
+            (CODE, Self::Code),
+            (TEXT, Self::Text),
+            (DOCUMENT, Self::Document),
+            (IMAGE, Self::Image),
+            (AUDIO, Self::Audio),
+            (VIDEO, Self::Video),
+            (ARCHIVE, Self::Archive),
+            (CONFIG, Self::Config),
+        ]
+        .into_iter()
+        .find(|(exts, _)| exts.contains(&ext))
+        .map_or(Self::File, |(_, kind)| kind)
+    }
+
+    /// The glyph for this kind in `style`; `Auto` should be resolved first
+    /// and draws Unicode.
+    pub fn glyph(self, style: IconStyle) -> &'static str {
+        let (nerd, unicode, ascii) = match self {
+            Self::Directory => ("\u{f07b}", "▣", "d"),
+            Self::Application => ("\u{f135}", "◆", "A"),
+            Self::Symlink => ("\u{f0c1}", "↪", "l"),
+            Self::Executable => ("\u{f489}", "»", "x"),
+            Self::Code => ("\u{f121}", "λ", "c"),
+            Self::Text => ("\u{f15c}", "≡", "t"),
+            Self::Document => ("\u{f1c1}", "▤", "p"),
+            Self::Image => ("\u{f1c5}", "▨", "i"),
+            Self::Audio => ("\u{f1c7}", "♪", "a"),
+            Self::Video => ("\u{f1c8}", "▶", "v"),
+            Self::Archive => ("\u{f1c6}", "▥", "z"),
+            Self::Config => ("\u{f013}", "≔", "k"),
+            Self::File => ("\u{f15b}", "·", "-"),
+        };
+        match style {
+            IconStyle::Nerd => nerd,
+            IconStyle::Unicode | IconStyle::Auto => unicode,
+            IconStyle::Ascii => ascii,
+        }
+    }
+
+    /// Icon color.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Directory | Self::Application => ui::ACCENT,
+            Self::Symlink => ui::INFO,
+            Self::Executable => ui::SUCCESS,
+            Self::Code | Self::Config => ui::PRIMARY,
+            Self::Image | Self::Audio | Self::Video => ui::PRIMARY_DIM,
+            Self::Archive => ui::WARNING,
+            Self::Text | Self::Document | Self::File => ui::TEXT_SECONDARY,
+        }
+    }
+}
+
+/// Resolve `Auto` for the current terminal: ASCII when the locale names a
+/// non-UTF-8 character set or the terminal is the Linux console, whose font
+/// lacks most symbols, and Unicode otherwise. An unset locale counts as
+/// UTF-8, since the TUI's borders already assume it.
+pub fn resolve(style: IconStyle) -> IconStyle {
+    resolve_with(style, |name| std::env::var(name).ok())
+}
+
+fn resolve_with(style: IconStyle, var: impl Fn(&str) -> Option<String>) -> IconStyle {
+    if style != IconStyle::Auto {
+        return style;
+    }
+    if matches!(var("TERM").as_deref(), Some("linux" | "dumb")) {
+        return IconStyle::Ascii;
+    }
+    let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+        .into_iter()
+        .find_map(|name| var(name).filter(|value| !value.is_empty()));
+    match locale {
+        Some(locale) => {
+            let locale = locale.to_ascii_lowercase();
+            if locale.contains("utf-8") || locale.contains("utf8") {
+                IconStyle::Unicode
+            } else {
+                IconStyle::Ascii
+            }
+        }
+        None => IconStyle::Unicode,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(name: &str, mode: u32) -> SearchResult {
+        SearchResult {
+            path: format!("/tmp/{name}"),
+            name: name.to_string(),
+            score: 1.0,
+            size: 0,
+            mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        }
+    }
+
+    #[test]
+    fn kinds_follow_symlinks_bundles_directories_then_extensions() {
+        let kind = |name, mode, as_dir, link| FileKind::of(&result(name, mode), as_dir, link);
+        assert_eq!(kind("main.RS", 0o100644, false, false), FileKind::Code);
+        assert_eq!(kind("Cargo.toml", 0o100644, false, false), FileKind::Config);
+        assert_eq!(kind("photo.HEIC", 0o100644, false, false), FileKind::Image);
+        assert_eq!(kind("notes", 0o100644, false, false), FileKind::File);
+        assert_eq!(kind("rg", 0o100755, false, false), FileKind::Executable);
+        assert_eq!(kind("build.sh", 0o100755, false, false), FileKind::Code);
+        assert_eq!(kind("src", 0o040755, true, false), FileKind::Directory);
+        assert_eq!(
+            kind("Safari.app", 0o040755, true, false),
+            FileKind::Application
+        );
+        assert_eq!(kind("src", 0o040755, true, true), FileKind::Symlink);
+
+        assert_eq!(FileKind::Directory.glyph(IconStyle::Unicode), "▣");
+        assert_eq!(FileKind::Directory.glyph(IconStyle::Ascii), "d");
+        assert_eq!(FileKind::Code.glyph(IconStyle::Nerd), "\u{f121}");
+    }
+
+    #[test]
+    fn auto_falls_back_to_ascii_outside_utf8_terminals() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+        let auto = |vars| resolve_with(IconStyle::Auto, env(vars));
+
+        assert_eq!(auto(&[]), IconStyle::Unicode);
+        assert_eq!(auto(&[("LANG", "en_US.UTF-8")]), IconStyle::Unicode);
+        assert_eq!(auto(&[("LANG", "C")]), IconStyle::Ascii);
+        // LC_ALL overrides LANG; an empty one does not.
+        assert_eq!(
+            auto(&[("LC_ALL", "POSIX"), ("LANG", "en_US.utf8")]),
+            IconStyle::Ascii
+        );
+        assert_eq!(
+            auto(&[("LC_ALL", ""), ("LANG", "en_US.utf8")]),
+            IconStyle::Unicode
+        );
+        assert_eq!(
+            auto(&[("TERM", "linux"), ("LANG", "en_US.UTF-8")]),
+            IconStyle::Ascii
+        );
+        assert_eq!(
+            resolve_with(IconStyle::Nerd, env(&[("LANG", "C")])),
+            IconStyle::Nerd
+        );
+    }
+}
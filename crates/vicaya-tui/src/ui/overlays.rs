@@ -21,6 +21,7 @@ pub fn render_help(f: &mut Frame) {
         "  niyama        Filters (chips in prashna)",
         "  phala         Results list",
         "  purvadarshana Preview pane",
+        "  tulana        Comparison pane (second ksetra)",
         "",
         "Keys:",
         "  Tab           Cycle focus (prashna / phala / purvadarshana)",
@@ -29,8 +30,13 @@ pub fn render_help(f: &mut Frame) {
         "  Ctrl+P        kriya-suchi (action palette)",
         "  Ctrl+O        Toggle purvadarshana",
         "  Ctrl+G        Cycle varga grouping (none/dir/ext)",
+        "  < / >         Shrink / grow purvadarshana",
+        "  |             Preview beside or below phala",
+        "  .             Show / hide dotfiles (outside prashna)",
+        "  S             Sthana: largest directories first / by relevance",
         "  Ctrl+K        ksetra (direct path input)",
         "  ↓ (in input)  Move to phala",
+        "  → (at end)    Accept the dimmed name completion",
         "  ↑ (at top)    Move to prashna",
         "",
         "Navigation (phala):",
@@ -38,23 +44,38 @@ pub fn render_help(f: &mut Frame) {
         "  k / ↑         Up",
         "  g / G         Top / Bottom",
         "  h / l         Ksetra pop / push (dirs)",
+        "  Alt+1-9 / ~   Jump to breadcrumb N / global ksetra",
+        "  1-9 / B       Jump to bookmark / bookmark ksetra",
         "",
         "Preview (purvadarshana):",
         "  PgUp / PgDn   Scroll preview",
         "  Ctrl+U / Ctrl+D  Scroll preview",
-        "  /             Search in preview",
+        "  /             Search in preview (Tab: across all results)",
         "  n / N         Next / previous match",
         "  Ctrl+N        Toggle line numbers",
+        "  L             Load more of a truncated file",
+        "  x             Toggle hex / text view",
         "  Ctrl+L        Clear preview search",
         "",
         "Actions (phala):",
         "  Enter / o     Open (files) / Enter scope (dirs)",
         "  y             Copy path",
-        "  p             Print path and exit",
+        "  Space         Mark / unmark for printing",
+        "  p             Print marked paths (or this one) and exit",
         "  r             Reveal in file manager",
+        "  O             Open with… ([associations], Quick Look)",
+        "  e             Export results (plain / CSV / JSON)",
+        "  C             Compare with another ksetra (tulana) / close it",
+        "  V             Verify selected result on disk (≠stale, ✗gone)",
+        "  i             Explain the selected result's score",
+        "  I             Index statistics by root",
         "",
         "Niyama syntax:",
         "  ext:rs,md  type:file|dir  path:src/  size:>10mb  mtime:<7d",
+        "  in-bundle:xcode  (with index_bundle_contents)",
+        "  owner:alice  readonly:yes  executable:yes  depth:<=2  hidden:yes",
+        "  dirsize:>1gb",
+        "  (checked by the daemon)",
         "",
         "Press Esc to close",
     ];
@@ -74,8 +95,16 @@ pub fn render_help(f: &mut Frame) {
     f.render_widget(help, area);
 }
 
-pub fn render_confirm(f: &mut Frame, _app: &AppState) {
-    let confirm = Paragraph::new("Are you sure? (y/n)")
+pub fn render_confirm(f: &mut Frame, app: &AppState) {
+    let prompt = match &app.mode {
+        crate::state::AppMode::Confirm(crate::state::Action::SetKsetra(path)) => format!(
+            "Set ksetra to {}? (y/n)",
+            crate::state::KsetraInputState::display_path(path)
+        ),
+        _ => "Are you sure? (y/n)".to_string(),
+    };
+    let confirm = Paragraph::new(prompt)
+        .wrap(ratatui::widgets::Wrap { trim: true })
         .style(Style::default().fg(ui::TEXT_PRIMARY).bg(ui::BG_DARK))
         .block(
             Block::default()
@@ -131,7 +160,7 @@ pub fn render_drishti_switcher(f: &mut Frame, app: &AppState) {
             .map(|view| {
                 let enabled = view.is_enabled();
                 let prefix = if enabled { " " } else { "·" };
-                let label = format!("{prefix} {:<12}  {}", view.label(), view.english_hint());
+                let label = format!("{prefix} {:<12}  {}", view.label(), view.hint());
 
                 let style = if enabled {
                     Style::default().fg(ui::TEXT_PRIMARY)
@@ -286,7 +315,11 @@ pub fn render_preview_search(f: &mut Frame, app: &AppState) {
             Span::styled(query, Style::default().fg(ui::TEXT_PRIMARY)),
         ]),
         Line::from(vec![Span::styled(
-            "Enter: apply   Esc: cancel",
+            if app.preview.search_all {
+                "Enter: apply   Tab: this preview only   Esc: cancel"
+            } else {
+                "Enter: apply   Tab: all results   Esc: cancel"
+            },
             Style::default()
                 .fg(ui::TEXT_SECONDARY)
                 .add_modifier(Modifier::ITALIC),
@@ -296,7 +329,11 @@ pub fn render_preview_search(f: &mut Frame, app: &AppState) {
         Block::default()
             .borders(Borders::ALL)
             .border_style(Style::default().fg(ui::PRIMARY))
-            .title(" preview search ")
+            .title(if app.preview.search_all {
+                " search all results "
+            } else {
+                " preview search "
+            })
             .style(Style::default().bg(ui::BG_DARK)),
     )
     .style(Style::default().bg(ui::BG_DARK));
@@ -312,6 +349,210 @@ pub fn render_preview_search(f: &mut Frame, app: &AppState) {
     f.set_cursor_position((cursor_x, cursor_y));
 }
 
+pub fn render_open_with(f: &mut Frame, app: &AppState) {
+    use ratatui::widgets::ListState;
+
+    let open_with = &app.ui.open_with;
+    let root = f.area();
+    let width = overlay_width(root, 0.5, 40, 4);
+    let height = open_with.targets.len() as u16 + 2;
+    let area = centered_fixed_rect(width, height, root);
+    f.render_widget(Clear, area);
+
+    let name = std::path::Path::new(&open_with.path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(open_with.path.as_str());
+
+    let items: Vec<ListItem> = open_with
+        .targets
+        .iter()
+        .map(|target| {
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<24}", target.label()),
+                    Style::default().fg(ui::TEXT_PRIMARY),
+                ),
+                Span::styled(target.hint(), Style::default().fg(ui::TEXT_MUTED)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui::PRIMARY))
+                .title(format!(" open {name} with "))
+                .style(Style::default().bg(ui::BG_DARK)),
+        )
+        .style(Style::default().bg(ui::BG_DARK))
+        .highlight_style(Style::default().bg(ui::BG_ELEVATED).fg(ui::PRIMARY))
+        .highlight_symbol("▸ ");
+
+    let mut state = ListState::default();
+    state.select(Some(open_with.selected_index));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+/// Score breakdown of the selected result (`i`).
+pub fn render_inspector(f: &mut Frame, app: &AppState) {
+    let root = f.area();
+    let width = overlay_width(root, 0.5, 48, 4);
+    let area = centered_fixed_rect(width, 13, root);
+    f.render_widget(Clear, area);
+
+    let row = |label: &str, value: String, hint: &str| {
+        Line::from(vec![
+            Span::styled(format!(" {label:<10}"), Style::default().fg(ui::ACCENT)),
+            Span::styled(
+                format!("{value:<10}"),
+                Style::default().fg(ui::TEXT_PRIMARY),
+            ),
+            Span::styled(hint.to_string(), Style::default().fg(ui::TEXT_MUTED)),
+        ])
+    };
+    let note = |text: &str| {
+        Line::from(Span::styled(
+            format!(" {text}"),
+            Style::default()
+                .fg(ui::TEXT_SECONDARY)
+                .add_modifier(Modifier::ITALIC),
+        ))
+    };
+
+    let selected = app.search.selected_result();
+    let lines = match selected.map(|result| (result, result.explain)) {
+        None => vec![note("No result selected")],
+        Some((_, None)) if app.search.is_searching => vec![note("Fetching score breakdown…")],
+        Some((_, None)) => vec![note("The daemon sent no breakdown for this result")],
+        Some((result, Some(explain))) => vec![
+            row("match", explain.strategy.label().to_string(), ""),
+            row("base", format!("{:.3}", explain.base_score), ""),
+            row(
+                "smriti",
+                format!("{:+.3}", explain.smriti_boost),
+                "usage boost",
+            ),
+            row(
+                "recent",
+                format!("{:+.3}", explain.recent_scope_boost),
+                "recently used scope",
+            ),
+            row("score", format!("{:.3}", result.score), "ranks first"),
+            row(
+                "context",
+                format!("{:+}", explain.context_penalty),
+                "cache / build / tool dirs",
+            ),
+            row(
+                "scope",
+                format!("{:+}", explain.scope_boost),
+                "under the search scope",
+            ),
+            row(
+                "directory",
+                format!("{:+}", explain.directory_bias),
+                "drishti prefers or demotes dirs",
+            ),
+            row(
+                "depth",
+                explain.path_depth.to_string(),
+                "shallower wins ties",
+            ),
+            Line::default(),
+            note("ties: context+scope+dir, newer mtime, depth, path"),
+        ],
+    };
+
+    let name = selected.map(|result| result.name.as_str()).unwrap_or("");
+    let inspector = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui::PRIMARY))
+                .title(format!(" score · {name} "))
+                .style(Style::default().bg(ui::BG_DARK)),
+        )
+        .style(Style::default().bg(ui::BG_DARK));
+    f.render_widget(inspector, area);
+}
+
+pub fn render_root_stats(f: &mut Frame, app: &AppState) {
+    let note = |text: &str| {
+        Line::from(Span::styled(
+            format!(" {text}"),
+            Style::default()
+                .fg(ui::TEXT_SECONDARY)
+                .add_modifier(Modifier::ITALIC),
+        ))
+    };
+    let mb = |bytes: u64| bytes as f64 / 1_048_576.0;
+
+    let lines = match app.root_stats.as_deref() {
+        None => vec![note("Fetching index statistics…")],
+        Some([]) => vec![note("The daemon has no index roots")],
+        Some(roots) => {
+            let total_memory: u64 = roots.iter().map(|root| root.memory_bytes).sum();
+            let mut lines = Vec::with_capacity(roots.len() * 2);
+            for root in roots {
+                let share = if total_memory > 0 {
+                    root.memory_bytes as f64 * 100.0 / total_memory as f64
+                } else {
+                    0.0
+                };
+                let scanned = root
+                    .last_scan
+                    .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                    .map(|dt| {
+                        dt.with_timezone(&chrono::Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string()
+                    })
+                    .unwrap_or_else(|| "never".to_string());
+
+                let mut detail = format!(
+                    " {} files · {} dirs · {:.1} MB ({share:.0}%)",
+                    root.files,
+                    root.dirs,
+                    mb(root.memory_bytes)
+                );
+                if root.total_bytes > 0 {
+                    detail.push_str(&format!(" · {:.1} MB on disk", mb(root.total_bytes)));
+                }
+                detail.push_str(&format!(" · scanned {scanned}"));
+
+                lines.push(Line::from(Span::styled(
+                    format!(" {}", root.root),
+                    Style::default().fg(ui::ACCENT),
+                )));
+                lines.push(Line::from(Span::styled(
+                    detail,
+                    Style::default().fg(ui::TEXT_PRIMARY),
+                )));
+            }
+            lines
+        }
+    };
+
+    let root = f.area();
+    let width = overlay_width(root, 0.6, 60, 4);
+    let height = (lines.len() as u16).saturating_add(2).max(5);
+    let area = centered_fixed_rect(width, height, root);
+    f.render_widget(Clear, area);
+
+    let stats = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui::PRIMARY))
+                .title(" index by root ")
+                .style(Style::default().bg(ui::BG_DARK)),
+        )
+        .style(Style::default().bg(ui::BG_DARK));
+    f.render_widget(stats, area);
+}
+
 fn overlay_width(root: Rect, fraction: f32, preferred_min: u16, margin: u16) -> u16 {
     let max_width = root.width.saturating_sub(margin).max(1);
     let min_width = preferred_min.min(max_width);
@@ -333,6 +574,71 @@ fn centered_fixed_rect(width: u16, height: u16, r: Rect) -> Rect {
     }
 }
 
+pub fn render_export(f: &mut Frame, app: &AppState) {
+    let root = f.area();
+    let width = overlay_width(root, 0.6, 50, 4);
+    let area = centered_fixed_rect(width, 4, root);
+
+    f.render_widget(Clear, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(1)])
+        .split(area);
+
+    let export = &app.ui.export;
+    let mut input_spans = vec![
+        Span::styled("to: ", Style::default().fg(ui::ACCENT)),
+        Span::styled(export.input.as_str(), Style::default().fg(ui::TEXT_PRIMARY)),
+    ];
+    if export.input.is_empty() {
+        input_spans.push(Span::styled(
+            "clipboard",
+            Style::default()
+                .fg(ui::TEXT_MUTED)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    }
+    if let Some(err) = &export.error {
+        input_spans.push(Span::styled(
+            format!("  {}", err),
+            Style::default()
+                .fg(ui::ERROR)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    }
+
+    let title = format!(
+        " export {} results · {} ",
+        app.search.results.len(),
+        export.format.label()
+    );
+    let input_widget = Paragraph::new(Line::from(input_spans))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(ui::PRIMARY))
+                .title(title)
+                .style(Style::default().bg(ui::BG_DARK)),
+        )
+        .style(Style::default().bg(ui::BG_DARK));
+    f.render_widget(input_widget, chunks[0]);
+
+    let cursor_offset = ("to: ".len() as u16)
+        .saturating_add(ui::display_width_up_to(&export.input, export.input.len()) as u16)
+        .min(chunks[0].width.saturating_sub(3));
+    f.set_cursor_position((chunks[0].x + 1 + cursor_offset, chunks[0].y + 1));
+
+    let help = Paragraph::new(Line::from(vec![Span::styled(
+        " Enter: export    Esc: cancel    Tab: format    empty: clipboard",
+        Style::default()
+            .fg(ui::TEXT_SECONDARY)
+            .add_modifier(Modifier::ITALIC),
+    )]))
+    .style(Style::default().bg(ui::BG_DARK));
+    f.render_widget(help, chunks[1]);
+}
+
 pub fn render_ksetra_input(f: &mut Frame, app: &AppState) {
     use crate::state::KsetraInputState;
     use ratatui::widgets::ListState;
@@ -389,7 +695,11 @@ pub fn render_ksetra_input(f: &mut Frame, app: &AppState) {
             Block::default()
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(ui::PRIMARY))
-                .title(" ksetra ")
+                .title(if app.ksetra_input.for_tulana {
+                    " tulana ksetra "
+                } else {
+                    " ksetra "
+                })
                 .style(Style::default().bg(ui::BG_DARK)),
         )
         .style(Style::default().bg(ui::BG_DARK));
@@ -25,7 +25,11 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
         } else {
             format!(" /{search_query}/")
         };
-        let truncated = if app.preview.truncated {
+        let truncated = if app.preview.loading_more {
+            "  (loading more…)"
+        } else if app.preview.more_available {
+            "  (truncated — L loads more)"
+        } else if app.preview.truncated {
             "  (truncated)"
         } else {
             ""
@@ -42,8 +46,13 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     let text = if !app.preview.is_visible {
         vec![Line::raw("")]
     } else if app.preview.is_loading {
+        let elapsed = app
+            .preview
+            .loading_since
+            .map(|since| since.elapsed())
+            .unwrap_or_default();
         vec![Line::styled(
-            "loading preview…",
+            loading_label(elapsed),
             Style::default()
                 .fg(ui::TEXT_SECONDARY)
                 .add_modifier(Modifier::ITALIC),
@@ -112,6 +121,19 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     f.render_widget(preview, area);
 }
 
+const SPINNER_FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+/// Loads faster than this show no elapsed time, to avoid flicker.
+const SHOW_ELAPSED_AFTER: std::time::Duration = std::time::Duration::from_millis(300);
+
+fn loading_label(elapsed: std::time::Duration) -> String {
+    let frame = SPINNER_FRAMES[(elapsed.as_millis() / 100) as usize % SPINNER_FRAMES.len()];
+    if elapsed < SHOW_ELAPSED_AFTER {
+        format!("{frame} loading preview…")
+    } else {
+        format!("{frame} loading preview… {:.1}s", elapsed.as_secs_f32())
+    }
+}
+
 fn segment_style(style: crate::state::TextStyle) -> Style {
     let mut out = match style.kind {
         TextKind::Normal => Style::default().fg(ui::TEXT_PRIMARY),
@@ -2,6 +2,7 @@
 
 use crate::state::AppState;
 use crate::ui;
+use crate::ui::icons::FileKind;
 use ratatui::{
     layout::Rect,
     style::{Modifier, Style},
@@ -10,6 +11,9 @@ use ratatui::{
     Frame,
 };
 
+/// How long the phala title marks results patched by index updates.
+const LIVE_UPDATE_MARK: std::time::Duration = std::time::Duration::from_secs(2);
+
 #[derive(Debug, Clone)]
 enum RenderRow {
     Header(String),
@@ -21,6 +25,14 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     let selected = app.search.selected_index;
 
     let (rows, selected_row) = build_rows(app);
+    let only_here = app.tulana.as_ref().map(|tulana| {
+        crate::state::only_in(
+            results,
+            app.ksetra.current().map(|p| p.as_path()),
+            &tulana.results,
+            Some(&tulana.scope),
+        )
+    });
 
     // Update scrolling state.
     let viewport_height = area.height.saturating_sub(2) as usize; // borders
@@ -34,6 +46,16 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     let available_width = area.width.saturating_sub(4); // Account for borders
     let max_path_len = available_width.saturating_sub(30) as usize; // Reserve space for name, score, marker
 
+    let shown: Vec<usize> = rows[start..end]
+        .iter()
+        .filter_map(|row| match row {
+            RenderRow::Result(idx) => Some(*idx),
+            RenderRow::Header(_) => None,
+        })
+        .collect();
+    check_symlinks(app, shown.into_iter(), false);
+    let results = &app.search.results;
+
     let items: Vec<ListItem> = if rows.is_empty() {
         empty_rows(app)
     } else {
@@ -52,55 +74,16 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
                     ]);
                     ListItem::new(line).style(Style::default())
                 }
-                RenderRow::Result(result_index) => {
-                    let result = &results[*result_index];
-                    let marker = if *result_index == selected {
-                        "▸"
-                    } else {
-                        " "
-                    };
-                    let score_color = ui::score_color(result.score);
-                    let is_selected = *result_index == selected;
-
-                    let path = std::path::Path::new(&result.path);
-                    let dir_path = path.parent().and_then(|p| p.to_str()).unwrap_or("");
-
-                    // Truncate path if not selected
-                    let display_path = truncate_path(dir_path, max_path_len.max(30), is_selected);
-
-                    let mut spans = vec![
-                        Span::styled(marker, Style::default().fg(ui::PRIMARY)),
-                        Span::raw(" "),
-                    ];
-
-                    let (name, name_style) = if app.view == crate::state::ViewKind::Sthana {
-                        (format!("{}/", result.name), Style::default().fg(ui::ACCENT))
-                    } else {
-                        (result.name.clone(), Style::default().fg(ui::TEXT_PRIMARY))
-                    };
-
-                    spans.extend(vec![
-                        Span::styled(name, name_style),
-                        Span::raw(" "),
-                        Span::styled(
-                            format!("({}) ", display_path),
-                            Style::default().fg(ui::TEXT_MUTED),
-                        ),
-                        Span::styled(
-                            format!("{:.2}", result.score),
-                            Style::default().fg(score_color),
-                        ),
-                    ]);
-
-                    let line = Line::from(spans);
-                    let style = if is_selected {
-                        Style::default().bg(ui::BG_ELEVATED)
-                    } else {
-                        Style::default()
-                    };
-
-                    ListItem::new(line).style(style)
-                }
+                RenderRow::Result(result_index) => result_item(
+                    app,
+                    &results[*result_index],
+                    *result_index == selected,
+                    app.search.is_marked(&results[*result_index].path),
+                    only_here
+                        .as_ref()
+                        .is_some_and(|only_here| only_here[*result_index]),
+                    max_path_len,
+                ),
             })
             .collect()
     };
@@ -111,12 +94,18 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
         Style::default().fg(ui::BORDER_DIM)
     };
 
-    let title = if app.search.is_searching {
+    let mut title = if app.search.is_searching {
         format!(
             "phala ({})  searching…  varga:{}",
             results.len(),
             app.ui.grouping.label()
         )
+    } else if app.search.truncated {
+        format!(
+            "phala ({})  partial results  varga:{}",
+            results.len(),
+            app.ui.grouping.label()
+        )
     } else {
         format!(
             "phala ({})  varga:{}",
@@ -125,6 +114,40 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
         )
     };
 
+    if let Some(only_here) = &only_here {
+        let count = only_here.iter().filter(|only| **only).count();
+        title.push_str(&format!("  only here:{}", count));
+    }
+    if !app.search.marked.is_empty() {
+        title.push_str(&format!("  marked:{}", app.search.marked.len()));
+    }
+    if app
+        .search
+        .live_updated_at
+        .is_some_and(|at| at.elapsed() < LIVE_UPDATE_MARK)
+    {
+        title.push_str("  ↻ updated");
+    }
+    if app.search.outside_scope {
+        title.push_str("  none in ksetra");
+    }
+    if app.search.verify {
+        title.push_str("  verify");
+    }
+    if app.preview.search_all && !app.preview.search_query.is_empty() {
+        title.push_str(&format!(
+            "  /{}: {} files",
+            app.preview.search_query,
+            app.preview.matching_results()
+        ));
+    }
+    if !app.search.show_hidden {
+        title.push_str("  hidden:off");
+    }
+    if app.pick_mode {
+        title.push_str("  pick");
+    }
+
     let list = List::new(items).block(
         Block::default()
             .borders(Borders::ALL)
@@ -136,6 +159,225 @@ pub fn render(f: &mut Frame, area: Rect, app: &mut AppState) {
     f.render_widget(list.style(Style::default().bg(ui::BG_SURFACE)), area);
 }
 
+/// One result row: selection and mark gutters, name with badges, parent
+/// directory, and score. `only_here` flags a tulana comparison miss.
+fn result_item(
+    app: &AppState,
+    result: &vicaya_index::SearchResult,
+    is_selected: bool,
+    is_marked: bool,
+    only_here: bool,
+    max_path_len: usize,
+) -> ListItem<'static> {
+    let marker = if is_selected { "▸" } else { " " };
+    let score_color = ui::score_color(result.score);
+
+    let path = std::path::Path::new(&result.path);
+    let dir_path = path.parent().and_then(|p| p.to_str()).unwrap_or("");
+
+    // Truncate path if not selected
+    let display_path = truncate_path(dir_path, max_path_len.max(30), is_selected);
+
+    let mark = if is_marked { "●" } else { " " };
+
+    let mut spans = vec![
+        Span::styled(marker, Style::default().fg(ui::PRIMARY)),
+        Span::styled(mark, Style::default().fg(ui::ACCENT)),
+    ];
+
+    let as_dir = result.is_dir || app.view == crate::state::ViewKind::Sthana;
+    let kind = FileKind::of(result, as_dir, app.search.is_symlink(&result.path));
+    spans.push(Span::styled(
+        format!("{} ", kind.glyph(app.ui.icons)),
+        Style::default().fg(kind.color()),
+    ));
+
+    let (name, name_style) = if as_dir {
+        (format!("{}/", result.name), Style::default().fg(ui::ACCENT))
+    } else {
+        (result.name.clone(), Style::default().fg(ui::TEXT_PRIMARY))
+    };
+    let name_style = if only_here {
+        name_style.fg(ui::WARNING)
+    } else {
+        name_style
+    };
+
+    spans.push(Span::styled(name, name_style));
+    if only_here {
+        spans.push(Span::styled(
+            " ◇only here",
+            Style::default().fg(ui::WARNING),
+        ));
+    }
+    if vicaya_core::paths::is_application_name(&result.name) {
+        spans.push(Span::styled(" ◆app", Style::default().fg(ui::ACCENT)));
+    }
+    if !vicaya_core::permissions::current_user_can_read(result.uid, result.gid, result.mode) {
+        spans.push(Span::styled(" ⊘unreadable", Style::default().fg(ui::ERROR)));
+    }
+    match app.search.freshness.get(&result.path) {
+        Some(crate::state::Freshness::Stale) => {
+            spans.push(Span::styled(" ≠stale", Style::default().fg(ui::WARNING)));
+        }
+        Some(crate::state::Freshness::Missing) => {
+            spans.push(Span::styled(" ✗gone", Style::default().fg(ui::ERROR)));
+        }
+        Some(crate::state::Freshness::Current) | None => {}
+    }
+    if let Some(count) = app
+        .preview
+        .match_counts
+        .get(&result.path)
+        .filter(|count| **count > 0)
+    {
+        spans.push(Span::styled(
+            format!(" /{count}"),
+            Style::default().fg(ui::ACCENT),
+        ));
+    }
+    if let Some(usage) = app
+        .smriti_usage
+        .as_ref()
+        // Every Smriti drishti row was used; the badge only adds noise there.
+        .filter(|_| app.usage_badges && app.view != crate::state::ViewKind::Smriti)
+        .and_then(|usage| usage.get(&result.path))
+    {
+        spans.push(Span::styled(
+            format!(" {}", usage_badge(usage)),
+            Style::default().fg(ui::TEXT_MUTED),
+        ));
+    }
+    spans.extend(vec![
+        Span::raw(" "),
+        Span::styled(
+            format!("({}) ", display_path),
+            Style::default().fg(ui::TEXT_MUTED),
+        ),
+        Span::styled(
+            format!("{:.2}", result.score),
+            Style::default().fg(score_color),
+        ),
+    ]);
+
+    let line = Line::from(spans);
+    let style = if is_selected {
+        Style::default().bg(ui::BG_ELEVATED)
+    } else {
+        Style::default()
+    };
+
+    ListItem::new(line).style(style)
+}
+
+/// `opened 12× · 2d ago`; counts every action when the path was never
+/// opened in the editor (`used 3× · 5m ago`).
+fn usage_badge(usage: &crate::state::PathUsage) -> String {
+    let count = if usage.opens > 0 {
+        format!("opened {}×", usage.opens)
+    } else {
+        format!("used {}×", usage.uses)
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0);
+    let when = match now.saturating_sub(usage.last_used).max(0) {
+        0..=59 => "just now".to_string(),
+        secs @ 60..=3_599 => format!("{}m ago", secs / 60),
+        secs @ 3_600..=86_399 => format!("{}h ago", secs / 3_600),
+        secs => format!("{}d ago", secs / 86_400),
+    };
+    format!("{count} · {when}")
+}
+
+/// Check the results at `indices` of the main phala (or the tulana pane)
+/// for symlinks before their rows are drawn.
+fn check_symlinks(app: &mut AppState, indices: impl Iterator<Item = usize>, tulana: bool) {
+    let results = match (&app.tulana, tulana) {
+        (Some(tulana), true) => &tulana.results,
+        (None, true) => return,
+        (_, false) => &app.search.results,
+    };
+    let paths = indices
+        .filter_map(|idx| results.get(idx))
+        .filter(|result| !app.search.symlinks.contains_key(&result.path))
+        .map(|result| result.path.clone())
+        .collect();
+    app.search.check_symlinks(paths);
+}
+
+/// Render the tulana pane: the same prashna in the comparison ksetra, with
+/// entries missing from the main phala flagged.
+pub fn render_tulana(f: &mut Frame, area: Rect, app: &mut AppState) {
+    let viewport_height = area.height.saturating_sub(2).max(1) as usize;
+    if let Some(tulana) = app.tulana.as_mut() {
+        if tulana.selected_index < tulana.scroll_offset {
+            tulana.scroll_offset = tulana.selected_index;
+        } else if tulana.selected_index >= tulana.scroll_offset + viewport_height {
+            tulana.scroll_offset = tulana.selected_index + 1 - viewport_height;
+        }
+        let start = tulana.scroll_offset;
+        check_symlinks(app, start..start + viewport_height, true);
+    }
+    let Some(tulana) = app.tulana.as_ref() else {
+        return;
+    };
+
+    let only_here = crate::state::only_in(
+        &tulana.results,
+        Some(&tulana.scope),
+        &app.search.results,
+        app.ksetra.current().map(|p| p.as_path()),
+    );
+
+    let start = tulana.scroll_offset.min(tulana.results.len());
+    let end = (start + viewport_height).min(tulana.results.len());
+    let max_path_len = area.width.saturating_sub(4).saturating_sub(30) as usize;
+
+    let items: Vec<ListItem> = (start..end)
+        .map(|idx| {
+            result_item(
+                app,
+                &tulana.results[idx],
+                idx == tulana.selected_index,
+                false,
+                only_here[idx],
+                max_path_len,
+            )
+        })
+        .collect();
+
+    let border_style = if app.search.focus == crate::state::FocusTarget::Tulana {
+        Style::default().fg(ui::BORDER_FOCUS)
+    } else {
+        Style::default().fg(ui::BORDER_DIM)
+    };
+    let status = if tulana.is_searching {
+        "  searching…"
+    } else if tulana.truncated {
+        "  partial results"
+    } else {
+        ""
+    };
+    let title = format!(
+        "tulana ({})  {}  only here:{}{}",
+        tulana.results.len(),
+        crate::state::pretty_path(&tulana.scope),
+        only_here.iter().filter(|only| **only).count(),
+        status
+    );
+
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(border_style)
+            .title(title)
+            .style(Style::default().bg(ui::BG_SURFACE)),
+    );
+    f.render_widget(list.style(Style::default().bg(ui::BG_SURFACE)), area);
+}
+
 fn empty_rows(app: &AppState) -> Vec<ListItem<'static>> {
     if let Some(error) = app.error.as_ref().filter(|msg| !msg.starts_with('✓')) {
         return vec![ListItem::new(Line::from(vec![
@@ -146,8 +388,21 @@ fn empty_rows(app: &AppState) -> Vec<ListItem<'static>> {
             ),
         ]))];
     }
+    if app.search.is_searching || app.search.suggestions.is_empty() {
+        return Vec::new();
+    }
 
-    Vec::new()
+    let mut rows = vec![ListItem::new(Line::from(Span::styled(
+        "No matches. Did you mean:",
+        Style::default().fg(ui::TEXT_SECONDARY),
+    )))];
+    rows.extend(app.search.suggestions.iter().map(|name| {
+        ListItem::new(Line::from(vec![
+            Span::styled("  ", Style::default()),
+            Span::styled(name.clone(), Style::default().fg(ui::ACCENT)),
+        ]))
+    }));
+    rows
 }
 
 fn build_rows(app: &AppState) -> (Vec<RenderRow>, usize) {
@@ -160,15 +415,21 @@ fn build_rows(app: &AppState) -> (Vec<RenderRow>, usize) {
     let mut selected_row: usize = 0;
     let mut last_group: Option<String> = None;
     let scope = app.ksetra.current().map(|p| p.as_path());
+    let mut was_outside = false;
 
     for (idx, result) in results.iter().enumerate() {
-        if app.ui.grouping != crate::state::GroupingMode::None {
-            let group = match app.ui.grouping {
-                crate::state::GroupingMode::None => String::new(),
-                crate::state::GroupingMode::Directory => directory_group_label(result, scope),
-                crate::state::GroupingMode::Extension => extension_group_label(result),
-            };
-
+        let outside = scope.filter(|scope| {
+            app.search.outside_scope && !std::path::Path::new(&result.path).starts_with(scope)
+        });
+        if let Some(scope) = outside.filter(|_| !was_outside) {
+            rows.push(RenderRow::Header(format!(
+                "outside {}",
+                crate::state::pretty_path(scope)
+            )));
+            last_group = None;
+        }
+        was_outside = outside.is_some();
+        if let Some(group) = group_label(app.ui.grouping, result, scope) {
             if last_group.as_deref() != Some(group.as_str()) {
                 rows.push(RenderRow::Header(group.clone()));
                 last_group = Some(group);
@@ -184,6 +445,19 @@ fn build_rows(app: &AppState) -> (Vec<RenderRow>, usize) {
     (rows, selected_row)
 }
 
+/// The varga header `result` falls under, or `None` when grouping is off.
+pub(crate) fn group_label(
+    grouping: crate::state::GroupingMode,
+    result: &vicaya_index::SearchResult,
+    scope: Option<&std::path::Path>,
+) -> Option<String> {
+    match grouping {
+        crate::state::GroupingMode::None => None,
+        crate::state::GroupingMode::Directory => Some(directory_group_label(result, scope)),
+        crate::state::GroupingMode::Extension => Some(extension_group_label(result)),
+    }
+}
+
 fn directory_group_label(
     result: &vicaya_index::SearchResult,
     scope: Option<&std::path::Path>,
@@ -15,7 +15,7 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     let cursor_pos = app.search.cursor_position;
     let is_focused = app.search.is_input_focused();
 
-    let parsed = crate::state::parse_query(query);
+    let parsed = app.parsed_query();
 
     let border_style = if is_focused {
         Style::default().fg(ui::BORDER_FOCUS)
@@ -24,12 +24,16 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
     };
 
     let mut lines = Vec::with_capacity(2);
-    lines.push(Line::from(vec![
+    let mut prashna = vec![
         Span::styled("prashna: ", Style::default().fg(ui::ACCENT)),
         Span::styled(query, Style::default().fg(ui::TEXT_PRIMARY)),
-    ]));
+    ];
+    if let Some(ghost) = app.search.ghost_text().filter(|_| is_focused) {
+        prashna.push(Span::styled(ghost, Style::default().fg(ui::TEXT_MUTED)));
+    }
+    lines.push(Line::from(prashna));
 
-    if parsed.niyamas.is_empty() {
+    if parsed.niyamas.is_empty() && parsed.errors.is_empty() {
         lines.push(Line::from(vec![
             Span::styled("niyama: ", Style::default().fg(ui::TEXT_SECONDARY)),
             Span::styled("—", Style::default().fg(ui::TEXT_MUTED)),
@@ -52,6 +56,25 @@ pub fn render(f: &mut Frame, area: Rect, app: &AppState) {
                     .add_modifier(Modifier::BOLD),
             ));
         }
+        // Malformed niyamas are left out of the search; say why.
+        for (idx, error) in parsed.errors.iter().enumerate() {
+            if idx > 0 || !parsed.niyamas.is_empty() {
+                spans.push(Span::raw(" "));
+            }
+            spans.push(Span::styled(
+                error.token.clone(),
+                Style::default()
+                    .fg(ui::ERROR)
+                    .bg(ui::BG_ELEVATED)
+                    .add_modifier(Modifier::CROSSED_OUT),
+            ));
+        }
+        if let Some(error) = parsed.errors.first() {
+            spans.push(Span::styled(
+                format!("  {}", error.message),
+                Style::default().fg(ui::TEXT_MUTED),
+            ));
+        }
         lines.push(Line::from(spans));
     }
 
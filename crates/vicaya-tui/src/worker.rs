@@ -1,18 +1,27 @@
 //! Background worker for daemon IPC and preview loading.
 
-use crate::client::{DaemonStatus, IpcClient};
-use crate::state::{Niyama, NiyamaType, StyledLine, StyledSegment, TextKind, TextStyle, ViewKind};
+use crate::client::{self, Client, DaemonStatus, Subscription};
+use crate::state::{
+    attribute_filter, dir_size, max_depth, Niyama, NiyamaType, PathUsage, PreviewMode, StyledLine,
+    StyledSegment, TextKind, TextStyle, ViewKind,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Arc;
 use std::time::Duration;
 use vicaya_core::content_search::{ContentSearchOptions, ContentSearchReport};
+use vicaya_core::ipc::{
+    DirectoryRank, RootStats, SearchFilters, SearchFlags, SearchOptions, SearchSort,
+};
 use vicaya_core::smriti::SmritiAction;
-use vicaya_index::SearchResult;
+use vicaya_core::telemetry::RankingEvent;
+use vicaya_index::scorer::RECENCY_SCORER;
+use vicaya_index::{SearchOutcome, SearchResult};
 
 use syntect::{
     easy::HighlightLines,
-    highlighting::{FontStyle, Theme, ThemeSet},
-    parsing::SyntaxSet,
-    util::LinesWithEndings,
+    highlighting::{FontStyle, HighlightState, Theme, ThemeSet},
+    parsing::{ParseState, SyntaxSet},
 };
 
 const STATUS_FAILURES_BEFORE_OFFLINE: usize = 2;
@@ -26,6 +35,14 @@ const STATUS_POLL_SLEEP_STEP: Duration = if cfg!(test) {
 } else {
     Duration::from_millis(50)
 };
+/// Approximate memory budget for cached previews.
+const PREVIEW_CACHE_BUDGET_BYTES: usize = 16 * 1024 * 1024;
+/// Bytes read per chunk while loading a preview; cancellation is checked
+/// between chunks.
+const PREVIEW_READ_CHUNK: usize = 64 * 1024;
+/// Lines highlighted (or directory entries listed) between cancellation checks.
+const PREVIEW_CANCEL_CHECK_LINES: usize = 64;
+const HEX_BYTES_PER_ROW: usize = 16;
 
 pub enum WorkerCommand {
     Search {
@@ -36,11 +53,41 @@ pub enum WorkerCommand {
         boost_scope: Option<std::path::PathBuf>,
         filter_scope: Option<std::path::PathBuf>,
         niyamas: Vec<Niyama>,
+        /// Return dotfiles and dot-directory contents.
+        hidden: bool,
+        /// Order Sthana by directory size.
+        largest_first: bool,
+        /// Ask the daemon for per-result score breakdowns.
+        explain: bool,
+    },
+    /// Run the same search in the tulana pane's ksetra. Coalesced separately
+    /// from `Search` so neither pane starves the other.
+    TulanaSearch {
+        id: u64,
+        query: String,
+        limit: usize,
+        view: ViewKind,
+        scope: std::path::PathBuf,
+        niyamas: Vec<Niyama>,
+        hidden: bool,
+        largest_first: bool,
     },
     Preview {
         id: u64,
         path: String,
         anchor_line: Option<usize>,
+        mode: PreviewMode,
+    },
+    /// Append the next chunk of preview `id` (the `L` key).
+    PreviewMore {
+        id: u64,
+    },
+    /// Count lines containing `needle` in each of `paths`, for `/` across
+    /// all results. Supersedes any count still running.
+    CountMatches {
+        id: u64,
+        needle: String,
+        paths: Vec<String>,
     },
     RecordSmriti {
         path: String,
@@ -50,6 +97,23 @@ pub enum WorkerCommand {
     ForgetSmriti {
         path: String,
     },
+    /// Tell the daemon the phala was scoped to `path`, so global searches
+    /// rank it higher for a while.
+    ScopeUsed {
+        path: String,
+    },
+    /// Tell the daemon `path` is gone so it drops the entry now.
+    ReportMissing {
+        path: String,
+    },
+    /// Append to the ranking telemetry log when `[telemetry] enabled` is set.
+    RecordRanking {
+        event: RankingEvent,
+    },
+    /// Fetch the per-root index breakdown for the status overlay.
+    RootStats,
+    /// Fetch what Smriti remembers of every path, for result badges.
+    SmritiUsage,
     Quit,
 }
 
@@ -57,6 +121,22 @@ pub enum WorkerEvent {
     SearchResults {
         id: u64,
         results: Vec<SearchResult>,
+        /// The daemon ran out of query budget; these are partial results.
+        truncated: bool,
+        /// "Did you mean" names from the daemon when nothing matched.
+        suggestions: Vec<String>,
+        /// Matches outside the ksetra, when nothing inside it matched.
+        outside_scope: Vec<SearchResult>,
+        /// The most used or most shared file name the query is a prefix of,
+        /// for ghost text.
+        completion: Option<String>,
+        error: Option<String>,
+    },
+    /// Results of a `TulanaSearch`.
+    TulanaResults {
+        id: u64,
+        results: Vec<SearchResult>,
+        truncated: bool,
         error: Option<String>,
     },
     PreviewReady {
@@ -65,11 +145,48 @@ pub enum WorkerEvent {
         title: String,
         lines: Vec<StyledLine>,
         truncated: bool,
+        /// More of the file can be appended with `PreviewMore`.
+        more_available: bool,
+        /// The lines are a hex dump rather than text.
+        hex: bool,
         anchor_line: Option<usize>,
     },
+    PreviewAppend {
+        id: u64,
+        lines: Vec<StyledLine>,
+        more_available: bool,
+    },
+    /// Matching lines in one result for `CountMatches` `id`.
+    MatchCount {
+        id: u64,
+        path: String,
+        count: usize,
+    },
     Status {
         status: Option<DaemonStatus>,
     },
+    /// A previewed path no longer exists on disk.
+    PathMissing {
+        path: String,
+    },
+    /// Answer to `RootStats`.
+    RootStats {
+        roots: Vec<RootStats>,
+        error: Option<String>,
+    },
+    /// Answer to `SmritiUsage`; `None` when the daemon could not be asked.
+    SmritiUsage {
+        usage: Option<std::collections::HashMap<String, PathUsage>>,
+    },
+    /// The daemon's watcher changed what search `id` would return: drop
+    /// `removed`, and refresh or slot in `added` without growing past
+    /// `limit` results.
+    IndexChanged {
+        id: u64,
+        removed: Vec<String>,
+        added: Vec<SearchResult>,
+        limit: usize,
+    },
 }
 
 pub fn start_worker(
@@ -80,13 +197,31 @@ pub fn start_worker(
 }
 
 fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
-    let mut search_client = IpcClient::new();
+    let mut search_client = client::interactive();
     let status_stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
     let status_handle = start_status_worker(evt_tx.clone(), status_stop.clone());
 
-    let syntaxes = SyntaxSet::load_defaults_newlines();
-    let themes = ThemeSet::load_defaults();
-    let theme = pick_theme(&themes);
+    let (preview_tx, preview_rx) = std::sync::mpsc::channel();
+    let config = load_config().ok();
+    let preview_limits = config
+        .as_ref()
+        .map(|config| PreviewLimits::from_config(&config.preview))
+        .unwrap_or_default();
+    let telemetry_path = config
+        .as_ref()
+        .filter(|config| config.telemetry.enabled)
+        .map(|_| vicaya_core::paths::telemetry_path());
+    let preview_handle = start_preview_worker(preview_rx, evt_tx.clone(), preview_limits);
+    let mut counts = MatchCounter {
+        max_bytes: preview_limits.max_bytes,
+        cancel: CancelToken::default(),
+    };
+    let (live_tx, live_rx) = std::sync::mpsc::channel();
+    let live_handle = start_live_worker(live_rx, evt_tx.clone());
+    let mut previews = PreviewRequests {
+        tx: preview_tx,
+        cancel: CancelToken::default(),
+    };
 
     #[derive(Debug)]
     struct PendingSearch {
@@ -97,10 +232,13 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
         boost_scope: Option<std::path::PathBuf>,
         filter_scope: Option<std::path::PathBuf>,
         niyamas: Vec<Niyama>,
+        hidden: bool,
+        largest_first: bool,
+        explain: bool,
     }
 
     let mut pending_search: Option<PendingSearch> = None;
-    let mut pending_preview: Option<(u64, String, Option<usize>)> = None;
+    let mut pending_tulana: Option<PendingSearch> = None;
 
     'worker: loop {
         // Receive at least one command, but wake periodically for status.
@@ -114,6 +252,9 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                     boost_scope,
                     filter_scope,
                     niyamas,
+                    hidden,
+                    largest_first,
+                    explain,
                 } => {
                     pending_search = Some(PendingSearch {
                         id,
@@ -123,13 +264,44 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                         boost_scope,
                         filter_scope,
                         niyamas,
+                        hidden,
+                        largest_first,
+                        explain,
+                    })
+                }
+                WorkerCommand::TulanaSearch {
+                    id,
+                    query,
+                    limit,
+                    view,
+                    scope,
+                    niyamas,
+                    hidden,
+                    largest_first,
+                } => {
+                    pending_tulana = Some(PendingSearch {
+                        id,
+                        query,
+                        limit,
+                        view,
+                        boost_scope: Some(scope.clone()),
+                        filter_scope: Some(scope),
+                        niyamas,
+                        hidden,
+                        largest_first,
+                        explain: false,
                     })
                 }
                 WorkerCommand::Preview {
                     id,
                     path,
                     anchor_line,
-                } => pending_preview = Some((id, path, anchor_line)),
+                    mode,
+                } => previews.build(id, path, anchor_line, mode),
+                WorkerCommand::PreviewMore { id } => previews.more(id),
+                WorkerCommand::CountMatches { id, needle, paths } => {
+                    counts.start(id, needle, paths, &evt_tx)
+                }
                 WorkerCommand::RecordSmriti {
                     path,
                     query,
@@ -140,13 +312,24 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                 WorkerCommand::ForgetSmriti { path } => {
                     let _ = search_client.smriti_forget(&path);
                 }
+                WorkerCommand::ScopeUsed { path } => {
+                    let _ = search_client.scope_used(&path);
+                }
+                WorkerCommand::ReportMissing { path } => {
+                    let _ = search_client.report_missing(&path);
+                }
+                WorkerCommand::RecordRanking { event } => {
+                    record_ranking(telemetry_path.as_deref(), &event);
+                }
+                WorkerCommand::RootStats => send_root_stats(&mut search_client, &evt_tx),
+                WorkerCommand::SmritiUsage => send_smriti_usage(&mut search_client, &evt_tx),
                 WorkerCommand::Quit => break 'worker,
             },
             Err(std::sync::mpsc::RecvTimeoutError::Timeout) => {}
             Err(std::sync::mpsc::RecvTimeoutError::Disconnected) => break 'worker,
         }
 
-        // Coalesce bursts: keep only the latest search/preview request.
+        // Coalesce bursts: keep only the latest search request.
         while let Ok(cmd) = cmd_rx.try_recv() {
             match cmd {
                 WorkerCommand::Search {
@@ -157,6 +340,9 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                     boost_scope,
                     filter_scope,
                     niyamas,
+                    hidden,
+                    largest_first,
+                    explain,
                 } => {
                     pending_search = Some(PendingSearch {
                         id,
@@ -166,13 +352,44 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                         boost_scope,
                         filter_scope,
                         niyamas,
+                        hidden,
+                        largest_first,
+                        explain,
+                    })
+                }
+                WorkerCommand::TulanaSearch {
+                    id,
+                    query,
+                    limit,
+                    view,
+                    scope,
+                    niyamas,
+                    hidden,
+                    largest_first,
+                } => {
+                    pending_tulana = Some(PendingSearch {
+                        id,
+                        query,
+                        limit,
+                        view,
+                        boost_scope: Some(scope.clone()),
+                        filter_scope: Some(scope),
+                        niyamas,
+                        hidden,
+                        largest_first,
+                        explain: false,
                     })
                 }
                 WorkerCommand::Preview {
                     id,
                     path,
                     anchor_line,
-                } => pending_preview = Some((id, path, anchor_line)),
+                    mode,
+                } => previews.build(id, path, anchor_line, mode),
+                WorkerCommand::PreviewMore { id } => previews.more(id),
+                WorkerCommand::CountMatches { id, needle, paths } => {
+                    counts.start(id, needle, paths, &evt_tx)
+                }
                 WorkerCommand::RecordSmriti {
                     path,
                     query,
@@ -183,20 +400,41 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                 WorkerCommand::ForgetSmriti { path } => {
                     let _ = search_client.smriti_forget(&path);
                 }
+                WorkerCommand::ScopeUsed { path } => {
+                    let _ = search_client.scope_used(&path);
+                }
+                WorkerCommand::ReportMissing { path } => {
+                    let _ = search_client.report_missing(&path);
+                }
+                WorkerCommand::RecordRanking { event } => {
+                    record_ranking(telemetry_path.as_deref(), &event);
+                }
+                WorkerCommand::RootStats => send_root_stats(&mut search_client, &evt_tx),
+                WorkerCommand::SmritiUsage => send_smriti_usage(&mut search_client, &evt_tx),
                 WorkerCommand::Quit => break 'worker,
             }
         }
 
-        if let Some(PendingSearch {
-            id,
-            query,
-            limit,
-            view,
-            boost_scope,
-            filter_scope,
-            niyamas,
-        }) = pending_search.take()
-        {
+        for (pending, tulana) in [
+            (pending_search.take(), false),
+            (pending_tulana.take(), true),
+        ] {
+            let Some(PendingSearch {
+                id,
+                query,
+                limit,
+                view,
+                boost_scope,
+                filter_scope,
+                niyamas,
+                hidden,
+                largest_first,
+                explain,
+            }) = pending
+            else {
+                continue;
+            };
+
             let trimmed = query.trim().to_string();
             let filter_scope = filter_scope.as_deref();
             let boost_scope = boost_scope
@@ -208,6 +446,12 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
             // When query is empty, request recent files from daemon
             let recent_if_empty = trimmed.is_empty();
 
+            let mut truncated = false;
+            let mut suggestions = Vec::new();
+            let mut outside_scope = Vec::new();
+            let mut completion = None;
+            // Daemon searches shown in the phala follow index updates.
+            let mut live = None;
             let mut results = if view == ViewKind::Smriti {
                 match search_client.smriti_list(Some(&trimmed), limit, filter_scope) {
                     Ok(entries) => entries
@@ -218,15 +462,58 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                             score: entry.total_count.min(100) as f32 / 100.0,
                             size: 0,
                             mtime: entry.last_used,
+                            uid: 0,
+                            gid: 0,
+                            mode: 0,
+                            indexed_at: 0,
+                            content_hash: None,
+                            is_dir: false,
+                            total_size: None,
+                            explain: None,
                         })
                         .collect(),
                     Err(e) => {
                         search_client.reconnect();
-                        let _ = evt_tx.send(WorkerEvent::SearchResults {
+                        let _ = evt_tx.send(results_event(
+                            tulana,
                             id,
-                            results: Vec::new(),
-                            error: Some(format!("Smriti error: {}", e)),
-                        });
+                            SearchOutcome::default(),
+                            None,
+                            Some(format!("Smriti error: {}", e)),
+                        ));
+                        continue;
+                    }
+                }
+            } else if view == ViewKind::Tyakta {
+                // Results point into the Trash, so previews and reveal still work.
+                match search_client.recently_deleted(Some(&trimmed), limit) {
+                    Ok(entries) => entries
+                        .into_iter()
+                        .map(|entry| SearchResult {
+                            path: entry.trash_path,
+                            name: entry.name,
+                            score: 1.0,
+                            size: entry.size,
+                            mtime: entry.deleted_at,
+                            uid: 0,
+                            gid: 0,
+                            mode: 0,
+                            indexed_at: 0,
+                            content_hash: None,
+                            is_dir: entry.is_dir,
+                            total_size: None,
+                            explain: None,
+                        })
+                        .collect(),
+                    Err(e) => {
+                        search_client.reconnect();
+                        let _ = evt_tx.send(results_event(
+                            tulana,
+                            id,
+                            SearchOutcome::default(),
+                            None,
+                            Some(format!("Trash error: {}", e)),
+                        ));
                         continue;
                     }
                 }
@@ -234,71 +521,464 @@ fn worker_loop(cmd_rx: Receiver<WorkerCommand>, evt_tx: Sender<WorkerEvent>) {
                 match content_search_results(&trimmed, limit, filter_scope.or(boost_scope)) {
                     Ok(r) => r,
                     Err(e) => {
-                        let _ = evt_tx.send(WorkerEvent::SearchResults {
+                        let _ = evt_tx.send(results_event(
+                            tulana,
                             id,
-                            results: Vec::new(),
-                            error: Some(format!("Content search error: {}", e)),
-                        });
+                            SearchOutcome::default(),
+                            None,
+                            Some(format!("Content search error: {}", e)),
+                        ));
                         continue;
                     }
                 }
             } else {
-                match search_client.search(
-                    &trimmed,
-                    limit,
-                    boost_scope,
-                    filter_scope,
-                    recent_if_empty,
-                ) {
-                    Ok(r) => r,
+                let path_string = |path: &std::path::Path| path.to_string_lossy().to_string();
+                let options = SearchOptions {
+                    scope: boost_scope.map(path_string),
+                    filters: SearchFilters {
+                        scope: filter_scope.map(path_string),
+                        attributes: attribute_filter(&niyamas),
+                        max_depth: max_depth(&niyamas),
+                        hidden: Some(hidden),
+                        dir_size: dir_size(&niyamas),
+                    },
+                    flags: SearchFlags {
+                        recent_if_empty,
+                        explain,
+                        no_broaden: tulana,
+                    },
+                    // Directory results carry the size of their contents.
+                    sort: if largest_first && view == ViewKind::Sthana {
+                        SearchSort::Size
+                    } else {
+                        SearchSort::Relevance
+                    },
+                    // Navatama lists matches newest first.
+                    scorer: (view == ViewKind::Navatama).then(|| RECENCY_SCORER.to_string()),
+                    directories: match view {
+                        ViewKind::Sthana => DirectoryRank::Prefer,
+                        ViewKind::Patra => DirectoryRank::Demote,
+                        _ => DirectoryRank::Neutral,
+                    },
+                    ..SearchOptions::new(&trimmed, limit)
+                };
+                live = (!tulana).then(|| options.clone());
+                match client::search(&mut search_client, options) {
+                    Ok(outcome) => {
+                        truncated = outcome.truncated;
+                        suggestions = outcome.suggestions;
+                        outside_scope = outcome.outside_scope;
+                        if !tulana {
+                            completion = complete_term(&mut search_client, &trimmed);
+                        }
+                        previews.invalidate(&outcome.results);
+                        outcome.results
+                    }
                     Err(e) => {
                         search_client.reconnect();
-                        let _ = evt_tx.send(WorkerEvent::SearchResults {
+                        let _ = live_tx.send(None);
+                        let _ = evt_tx.send(results_event(
+                            tulana,
                             id,
-                            results: Vec::new(),
-                            error: Some(format!("Search error: {}", e)),
-                        });
+                            SearchOutcome::default(),
+                            None,
+                            Some(format!("Search error: {}", e)),
+                        ));
                         continue;
                     }
                 }
             };
 
-            // Scope + Niyama filtering (best-effort).
+            // Scope + Niyama filtering (best-effort). Trashed entries no
+            // longer live under the ksetra they came from.
+            let filter_scope = filter_scope.filter(|_| view != ViewKind::Tyakta);
             results.retain(|r| matches_filters(r, view, filter_scope, &niyamas));
+            outside_scope.retain(|r| matches_filters(r, view, None, &niyamas));
+            if !tulana {
+                let _ = live_tx.send(live.map(|options| LiveSearch {
+                    id,
+                    view,
+                    filter_scope: filter_scope.map(std::path::Path::to_path_buf),
+                    niyamas: niyamas.clone(),
+                    options,
+                }));
+            }
 
-            let _ = evt_tx.send(WorkerEvent::SearchResults {
-                id,
+            let outcome = SearchOutcome {
                 results,
-                error: None,
-            });
-        }
-
-        if let Some((id, path, anchor_line)) = pending_preview.take() {
-            let (title, lines, truncated, error) = build_preview(&path, &syntaxes, theme);
-            let _ = evt_tx.send(WorkerEvent::PreviewReady {
-                id,
-                path,
-                title,
-                lines,
                 truncated,
-                anchor_line,
-            });
-            if let Some(error) = error {
-                tracing::debug!("Preview error: {}", error);
-            }
+                suggestions,
+                outside_scope,
+            };
+            let _ = evt_tx.send(results_event(tulana, id, outcome, completion, None));
         }
     }
 
+    previews.cancel.cancel();
+    drop(previews);
+    let _ = preview_handle.join();
+    drop(live_tx);
+    let _ = live_handle.join();
     status_stop.store(true, std::sync::atomic::Ordering::Relaxed);
     let _ = status_handle.join();
 }
 
+/// Search results for the main phala or, with `tulana`, the comparison pane,
+/// which shows no suggestions or matches outside its ksetra.
+fn results_event(
+    tulana: bool,
+    id: u64,
+    outcome: SearchOutcome,
+    completion: Option<String>,
+    error: Option<String>,
+) -> WorkerEvent {
+    let SearchOutcome {
+        results,
+        truncated,
+        suggestions,
+        outside_scope,
+    } = outcome;
+    if tulana {
+        WorkerEvent::TulanaResults {
+            id,
+            results,
+            truncated,
+            error,
+        }
+    } else {
+        WorkerEvent::SearchResults {
+            id,
+            results,
+            truncated,
+            suggestions,
+            outside_scope,
+            completion,
+            error,
+        }
+    }
+}
+
+/// The daemon's best file name that a single-word `term` is a prefix of.
+fn complete_term(client: &mut Client, term: &str) -> Option<String> {
+    if term.is_empty() || term.contains(char::is_whitespace) {
+        return None;
+    }
+    match client.suggest(term, 1) {
+        Ok(names) => names
+            .into_iter()
+            .next()
+            .filter(|name| name.chars().count() > term.chars().count()),
+        Err(_) => {
+            // An older daemon drops the connection on an unknown request.
+            client.reconnect();
+            None
+        }
+    }
+}
+
+/// Shared flag that lets the worker abandon a preview that is still loading.
+#[derive(Debug, Clone, Default)]
+struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Worker-side handle to the preview thread.
+struct PreviewRequests {
+    tx: Sender<PreviewMessage>,
+    /// Token of the most recent `Build`.
+    cancel: CancelToken,
+}
+
+impl PreviewRequests {
+    fn build(&mut self, id: u64, path: String, anchor_line: Option<usize>, mode: PreviewMode) {
+        // A newer selection supersedes whatever is still loading.
+        self.cancel.cancel();
+        self.cancel = CancelToken::default();
+        let _ = self.tx.send(PreviewMessage::Build(PreviewJob {
+            id,
+            path,
+            anchor_line,
+            mode,
+            cancel: self.cancel.clone(),
+        }));
+    }
+
+    fn more(&self, id: u64) {
+        let _ = self.tx.send(PreviewMessage::More {
+            id,
+            cancel: self.cancel.clone(),
+        });
+    }
+
+    fn invalidate(&self, results: &[SearchResult]) {
+        let _ = self.tx.send(PreviewMessage::Invalidate(results.to_vec()));
+    }
+}
+
+/// Worker-side handle to the thread counting `/` matches across results.
+struct MatchCounter {
+    /// Bytes read from the head of each file, as for a preview.
+    max_bytes: usize,
+    /// Token of the count in progress.
+    cancel: CancelToken,
+}
+
+impl MatchCounter {
+    fn start(&mut self, id: u64, needle: String, paths: Vec<String>, evt_tx: &Sender<WorkerEvent>) {
+        self.cancel.cancel();
+        self.cancel = CancelToken::default();
+        let cancel = self.cancel.clone();
+        let evt_tx = evt_tx.clone();
+        let max_bytes = self.max_bytes;
+        std::thread::spawn(move || {
+            for path in paths {
+                if cancel.is_cancelled() {
+                    return;
+                }
+                let Some(count) = count_file_matches(&path, &needle, max_bytes) else {
+                    continue;
+                };
+                if evt_tx
+                    .send(WorkerEvent::MatchCount { id, path, count })
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+/// Lines containing `needle` (ignoring ASCII case, like preview search) in
+/// the first `max_bytes` of `path`. `None` for directories, binary files and
+/// anything unreadable.
+fn count_file_matches(path: &str, needle: &str, max_bytes: usize) -> Option<usize> {
+    let (head, _) = vicaya_core::preview::read_head(path, max_bytes).ok()?;
+    if vicaya_core::preview::is_binary(&head) {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&head);
+    let count = text
+        .lines()
+        .filter(|line| !vicaya_core::preview::match_ranges(line, needle).is_empty())
+        .count();
+    Some(count)
+}
+
+struct PreviewJob {
+    id: u64,
+    path: String,
+    anchor_line: Option<usize>,
+    mode: PreviewMode,
+    cancel: CancelToken,
+}
+
+enum PreviewMessage {
+    Build(PreviewJob),
+    More {
+        id: u64,
+        cancel: CancelToken,
+    },
+    /// Fresh daemon results; drop cached previews they show as changed.
+    Invalidate(Vec<SearchResult>),
+}
+
+/// The most recent truncated file preview, kept so `More` can continue it.
+struct PreviewSession {
+    id: u64,
+    path: String,
+    resume: PreviewResume,
+}
+
+/// Builds previews off the IPC worker so a slow read never delays searches.
+fn start_preview_worker(
+    rx: Receiver<PreviewMessage>,
+    evt_tx: Sender<WorkerEvent>,
+    limits: PreviewLimits,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        let themes = ThemeSet::load_defaults();
+        let ctx = PreviewContext {
+            syntaxes: &syntaxes,
+            theme: pick_theme(&themes),
+            limits,
+        };
+        let mut cache = PreviewCache::new(PREVIEW_CACHE_BUDGET_BYTES);
+        let mut session: Option<PreviewSession> = None;
+
+        while let Ok(msg) = rx.recv() {
+            // A newer Build supersedes everything queued before it.
+            let mut tasks = Vec::new();
+            for msg in std::iter::once(msg).chain(rx.try_iter()) {
+                match msg {
+                    PreviewMessage::Build(job) => {
+                        tasks.clear();
+                        tasks.push(PreviewMessage::Build(job));
+                    }
+                    PreviewMessage::Invalidate(results) => cache.invalidate_changed(&results),
+                    more => tasks.push(more),
+                }
+            }
+
+            for task in tasks {
+                match task {
+                    PreviewMessage::Build(job) => {
+                        if job.cancel.is_cancelled() {
+                            continue;
+                        }
+                        let preview =
+                            load_preview(&job.path, job.mode, &mut cache, &ctx, &job.cancel);
+                        if job.cancel.is_cancelled() {
+                            tracing::debug!("Preview cancelled: {}", job.path);
+                            continue;
+                        }
+                        if let Some(error) = &preview.error {
+                            tracing::debug!("Preview error: {}", error);
+                        }
+                        if preview.missing {
+                            let _ = evt_tx.send(WorkerEvent::PathMissing {
+                                path: job.path.clone(),
+                            });
+                        }
+                        session = preview.resume.map(|resume| PreviewSession {
+                            id: job.id,
+                            path: job.path.clone(),
+                            resume,
+                        });
+                        let _ = evt_tx.send(WorkerEvent::PreviewReady {
+                            id: job.id,
+                            path: job.path,
+                            title: preview.title,
+                            lines: preview.lines,
+                            truncated: preview.truncated,
+                            more_available: session.is_some(),
+                            hex: preview.hex,
+                            anchor_line: job.anchor_line,
+                        });
+                    }
+                    PreviewMessage::More { id, cancel } => {
+                        let Some(active) = session.as_mut().filter(|s| s.id == id) else {
+                            continue;
+                        };
+                        let Some((lines, next)) =
+                            preview_more(&active.path, &active.resume, &ctx, &cancel)
+                        else {
+                            continue;
+                        };
+                        let more_available = next.is_some();
+                        match next {
+                            Some(resume) => active.resume = resume,
+                            None => session = None,
+                        }
+                        let _ = evt_tx.send(WorkerEvent::PreviewAppend {
+                            id,
+                            lines,
+                            more_available,
+                        });
+                    }
+                    PreviewMessage::Invalidate(_) => {}
+                }
+            }
+        }
+    })
+}
+
+/// The daemon search the phala shows, followed by the live worker.
+struct LiveSearch {
+    id: u64,
+    view: ViewKind,
+    filter_scope: Option<std::path::PathBuf>,
+    niyamas: Vec<Niyama>,
+    options: SearchOptions,
+}
+
+/// Keep a `Subscribe` connection on the search the phala shows and turn
+/// the daemon's pushes into `IndexChanged` events. `None` stops following,
+/// for views the daemon does not search. A dropped connection is opened
+/// again with the next search, so an older daemon costs one attempt each.
+fn start_live_worker(
+    rx: Receiver<Option<LiveSearch>>,
+    evt_tx: Sender<WorkerEvent>,
+) -> std::thread::JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut current: Option<LiveSearch> = None;
+        let mut subscription: Option<Subscription> = None;
+        loop {
+            let next = if subscription.is_some() {
+                match rx.try_recv() {
+                    Ok(next) => Some(next),
+                    Err(std::sync::mpsc::TryRecvError::Empty) => None,
+                    Err(std::sync::mpsc::TryRecvError::Disconnected) => return,
+                }
+            } else {
+                match rx.recv() {
+                    Ok(next) => Some(next),
+                    Err(_) => return,
+                }
+            };
+            if let Some(mut next) = next {
+                while let Ok(later) = rx.try_recv() {
+                    next = later;
+                }
+                subscription = match (subscription.take(), &next) {
+                    (_, None) => None,
+                    (Some(mut subscription), Some(search)) => subscription
+                        .resubscribe(search.options.clone())
+                        .ok()
+                        .map(|_| subscription),
+                    (None, Some(search)) => client::subscribe(search.options.clone()).ok(),
+                };
+                current = next;
+            }
+
+            let (Some(live), Some(search)) = (subscription.as_mut(), current.as_ref()) else {
+                continue;
+            };
+            match live.next_change() {
+                Ok(Some(change)) => {
+                    let mut removed = change.removed;
+                    let (added, unmatched): (Vec<_>, Vec<_>) = change
+                        .added
+                        .into_iter()
+                        .map(client::index_result)
+                        .partition(|r| {
+                            matches_filters(
+                                r,
+                                search.view,
+                                search.filter_scope.as_deref(),
+                                &search.niyamas,
+                            )
+                        });
+                    removed.extend(unmatched.into_iter().map(|r| r.path));
+                    let _ = evt_tx.send(WorkerEvent::IndexChanged {
+                        id: search.id,
+                        removed,
+                        added,
+                        limit: search.options.limit,
+                    });
+                }
+                Ok(None) => {}
+                Err(_) => subscription = None,
+            }
+        }
+    })
+}
+
 fn start_status_worker(
     evt_tx: Sender<WorkerEvent>,
     stop: std::sync::Arc<std::sync::atomic::AtomicBool>,
 ) -> std::thread::JoinHandle<()> {
     std::thread::spawn(move || {
-        let mut client = IpcClient::best_effort();
+        let mut client = client::best_effort();
         let mut last_status: Option<DaemonStatus> = None;
         let mut failures = 0usize;
 
@@ -369,44 +1049,15 @@ fn matches_filters(
         return false;
     }
 
-    for niyama in niyamas {
-        match niyama {
-            Niyama::Type { kind: want, .. } => {
-                if kind != Some(*want) {
-                    return false;
-                }
-            }
-            Niyama::Ext { exts, .. } => {
-                let ext = path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .map(|e| e.to_lowercase());
-                let Some(ext) = ext else {
-                    return false;
-                };
-                if !exts.iter().any(|e| e == &ext) {
-                    return false;
-                }
-            }
-            Niyama::Path { needle, .. } => {
-                if !result.path.to_lowercase().contains(needle) {
-                    return false;
-                }
-            }
-            Niyama::Mtime { cmp, .. } => {
-                if !cmp.op.matches_i64(result.mtime, cmp.value) {
-                    return false;
-                }
-            }
-            Niyama::Size { cmp, .. } => {
-                if !cmp.op.matches_u64(result.size, cmp.value) {
-                    return false;
-                }
-            }
-        }
-    }
-
-    true
+    // Type niyamas and Sthana check the kind on disk.
+    let is_dir = match kind {
+        Some(kind) => kind == NiyamaType::Dir,
+        None if needs_kind => return false,
+        None => result.is_dir,
+    };
+    niyamas
+        .iter()
+        .all(|niyama| niyama.matches(&result.path, is_dir, result.size, result.mtime))
 }
 
 fn content_search_results(
@@ -414,7 +1065,7 @@ fn content_search_results(
     limit: usize,
     scope: Option<&std::path::Path>,
 ) -> anyhow::Result<Vec<SearchResult>> {
-    let config = load_config_for_worker()?;
+    let config = load_config()?;
     if !config.content_search_enabled() {
         anyhow::bail!("content search is disabled");
     }
@@ -431,7 +1082,38 @@ fn content_search_results(
     Ok(report_to_search_results(report, limit))
 }
 
-fn load_config_for_worker() -> anyhow::Result<vicaya_core::Config> {
+/// Best-effort append to the ranking telemetry log; `None` means disabled.
+fn record_ranking(path: Option<&std::path::Path>, event: &RankingEvent) {
+    if let Some(path) = path {
+        let _ = vicaya_core::telemetry::append_event(path, event);
+    }
+}
+
+fn send_root_stats(client: &mut Client, evt_tx: &Sender<WorkerEvent>) {
+    let event = match client.root_stats() {
+        Ok(roots) => WorkerEvent::RootStats { roots, error: None },
+        Err(e) => WorkerEvent::RootStats {
+            roots: Vec::new(),
+            error: Some(e.to_string()),
+        },
+    };
+    let _ = evt_tx.send(event);
+}
+
+fn send_smriti_usage(client: &mut Client, evt_tx: &Sender<WorkerEvent>) {
+    let usage = client
+        .smriti_list(None, usize::MAX, None)
+        .ok()
+        .map(|entries| {
+            entries
+                .iter()
+                .map(|entry| (entry.path.clone(), PathUsage::from(entry)))
+                .collect()
+        });
+    let _ = evt_tx.send(WorkerEvent::SmritiUsage { usage });
+}
+
+pub(crate) fn load_config() -> anyhow::Result<vicaya_core::Config> {
     let config_path = vicaya_core::paths::config_path();
     if config_path.exists() {
         Ok(vicaya_core::Config::load(&config_path)?)
@@ -469,6 +1151,14 @@ fn report_to_search_results(report: ContentSearchReport, limit: usize) -> Vec<Se
                 score: (1.0 - (idx as f32 / total) * 0.25).max(0.01),
                 size,
                 mtime,
+                uid: 0,
+                gid: 0,
+                mode: 0,
+                indexed_at: 0,
+                content_hash: None,
+                is_dir: false,
+                total_size: None,
+                explain: None,
             }
         })
         .collect()
@@ -529,40 +1219,267 @@ fn plain_line(text: impl Into<String>) -> StyledLine {
     }]
 }
 
+/// Identity of a file's on-disk contents; a change in either field means a
+/// cached preview is stale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PreviewStamp {
+    mtime: Option<std::time::SystemTime>,
+    size: u64,
+}
+
+impl PreviewStamp {
+    fn of(path: &str) -> Option<Self> {
+        let meta = std::fs::metadata(path).ok()?;
+        Some(Self {
+            mtime: meta.modified().ok(),
+            size: meta.len(),
+        })
+    }
+
+    fn mtime_secs(&self) -> Option<i64> {
+        let since_epoch = self.mtime?.duration_since(std::time::UNIX_EPOCH).ok()?;
+        Some(since_epoch.as_secs() as i64)
+    }
+}
+
+/// Per-chunk preview limits, from the `[preview]` config section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PreviewLimits {
+    max_bytes: usize,
+    max_lines: usize,
+}
+
+impl PreviewLimits {
+    fn from_config(config: &vicaya_core::config::PreviewConfig) -> Self {
+        Self {
+            max_bytes: config.max_bytes.max(1),
+            max_lines: config.max_lines.max(1),
+        }
+    }
+}
+
+impl Default for PreviewLimits {
+    fn default() -> Self {
+        Self::from_config(&vicaya_core::config::PreviewConfig::default())
+    }
+}
+
+/// Where a truncated file preview stopped, so the next chunk continues with
+/// the same syntax highlighting state.
+#[derive(Clone)]
+struct PreviewResume {
+    offset: u64,
+    /// Continue as a hex dump rather than highlighted text.
+    hex: bool,
+    highlight: Option<(HighlightState, ParseState)>,
+}
+
+#[derive(Clone)]
+struct BuiltPreview {
+    title: String,
+    lines: Vec<StyledLine>,
+    truncated: bool,
+    hex: bool,
+    error: Option<String>,
+    /// The path does not exist (ENOENT), so its index entry is a ghost.
+    missing: bool,
+    /// Set when more of the file can be loaded with `L`.
+    resume: Option<PreviewResume>,
+}
+
+impl BuiltPreview {
+    fn new(title: String, lines: Vec<StyledLine>) -> Self {
+        Self {
+            title,
+            lines,
+            truncated: false,
+            hex: false,
+            error: None,
+            missing: false,
+            resume: None,
+        }
+    }
+
+    fn failed(title: String, mut lines: Vec<StyledLine>, message: String, error: String) -> Self {
+        lines.push(error_line(message));
+        Self {
+            error: Some(error),
+            ..Self::new(title, lines)
+        }
+    }
+}
+
+struct CachedPreview {
+    stamp: PreviewStamp,
+    preview: BuiltPreview,
+    bytes: usize,
+    last_used: u64,
+}
+
+/// LRU cache of built previews keyed by (path, mtime, size).
+struct PreviewCache {
+    entries: std::collections::HashMap<String, CachedPreview>,
+    budget_bytes: usize,
+    used_bytes: usize,
+    clock: u64,
+}
+
+impl PreviewCache {
+    fn new(budget_bytes: usize) -> Self {
+        Self {
+            entries: std::collections::HashMap::new(),
+            budget_bytes,
+            used_bytes: 0,
+            clock: 0,
+        }
+    }
+
+    fn get(&mut self, path: &str, stamp: PreviewStamp) -> Option<BuiltPreview> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(path)?;
+        if entry.stamp != stamp {
+            self.remove(path);
+            return None;
+        }
+        entry.last_used = self.clock;
+        Some(entry.preview.clone())
+    }
+
+    fn insert(&mut self, path: &str, stamp: PreviewStamp, preview: &BuiltPreview) {
+        let bytes = preview_bytes(path, &preview.title, &preview.lines);
+        if bytes > self.budget_bytes {
+            return;
+        }
+        self.remove(path);
+        while self.used_bytes + bytes > self.budget_bytes {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            self.remove(&oldest);
+        }
+
+        self.clock += 1;
+        self.used_bytes += bytes;
+        self.entries.insert(
+            path.to_string(),
+            CachedPreview {
+                stamp,
+                preview: preview.clone(),
+                bytes,
+                last_used: self.clock,
+            },
+        );
+    }
+
+    /// Drop entries whose file the daemon now reports with a different
+    /// size or mtime.
+    fn invalidate_changed(&mut self, results: &[SearchResult]) {
+        for result in results {
+            let Some(entry) = self.entries.get(&result.path) else {
+                continue;
+            };
+            // Directory results carry the size of their contents.
+            let size_changed = !result.is_dir && entry.stamp.size != result.size;
+            if size_changed || entry.stamp.mtime_secs() != Some(result.mtime) {
+                self.remove(&result.path);
+            }
+        }
+    }
+
+    fn remove(&mut self, path: &str) {
+        if let Some(entry) = self.entries.remove(path) {
+            self.used_bytes -= entry.bytes;
+        }
+    }
+}
+
+fn preview_bytes(path: &str, title: &str, lines: &[StyledLine]) -> usize {
+    let segments: usize = lines
+        .iter()
+        .flatten()
+        .map(|seg| std::mem::size_of::<StyledSegment>() + seg.text.len())
+        .sum();
+    path.len() + title.len() + std::mem::size_of_val(lines) + segments
+}
+
+/// Build a preview through the cache. Only `Auto` previews are cached;
+/// explicit hex/text toggles are rebuilt on demand.
+fn load_preview(
+    path: &str,
+    mode: PreviewMode,
+    cache: &mut PreviewCache,
+    ctx: &PreviewContext<'_>,
+    cancel: &CancelToken,
+) -> BuiltPreview {
+    let stamp = PreviewStamp::of(path).filter(|_| mode == PreviewMode::Auto);
+    if let Some(stamp) = stamp {
+        if let Some(preview) = cache.get(path, stamp) {
+            return preview;
+        }
+    }
+
+    let preview = build_preview(path, mode, ctx, cancel);
+    if cancel.is_cancelled() {
+        return preview;
+    }
+    if let (Some(stamp), None) = (stamp, preview.error.as_ref()) {
+        cache.insert(path, stamp, &preview);
+    }
+    preview
+}
+
+/// Syntax tables and limits shared by every preview the thread builds.
+struct PreviewContext<'a> {
+    syntaxes: &'a SyntaxSet,
+    theme: &'a Theme,
+    limits: PreviewLimits,
+}
+
+/// Build a preview, returning early (with partial output) once `cancel` is set.
 fn build_preview(
     path: &str,
-    syntaxes: &SyntaxSet,
-    theme: &Theme,
-) -> (String, Vec<StyledLine>, bool, Option<String>) {
+    mode: PreviewMode,
+    ctx: &PreviewContext<'_>,
+    cancel: &CancelToken,
+) -> BuiltPreview {
     let p = std::path::Path::new(path);
     let title = p
         .file_name()
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or_else(|| path.to_string());
 
+    if let Some((archive, member)) = vicaya_core::archive::split_virtual_path(path) {
+        return preview_archive_member(archive, member, title, ctx, cancel);
+    }
+
     let meta = match std::fs::metadata(p) {
         Ok(m) => m,
         Err(e) => {
-            return (
-                title,
-                vec![error_line(format!("(unable to read metadata) {}", e))],
-                false,
-                Some(e.to_string()),
-            );
+            return BuiltPreview {
+                missing: e.kind() == std::io::ErrorKind::NotFound,
+                ..BuiltPreview::failed(
+                    title,
+                    Vec::new(),
+                    format!("(unable to read metadata) {}", e),
+                    e.to_string(),
+                )
+            };
         }
     };
 
     if meta.is_dir() {
-        return preview_dir(p, title);
+        return preview_dir(p, title, cancel);
     }
 
-    preview_file(p, title, meta.len(), syntaxes, theme)
+    preview_file(p, title, meta.len(), mode, ctx, cancel)
 }
 
-fn preview_dir(
-    path: &std::path::Path,
-    title: String,
-) -> (String, Vec<StyledLine>, bool, Option<String>) {
+fn preview_dir(path: &std::path::Path, title: String, cancel: &CancelToken) -> BuiltPreview {
     const MAX_ENTRIES: usize = 200;
 
     let mut lines = vec![meta_line(format!("{}", path.display())), meta_line("")];
@@ -570,8 +1487,12 @@ fn preview_dir(
     let entries = match std::fs::read_dir(path) {
         Ok(e) => e,
         Err(e) => {
-            lines.push(error_line(format!("(unable to read directory) {}", e)));
-            return (title, lines, false, Some(e.to_string()));
+            return BuiltPreview::failed(
+                title,
+                lines,
+                format!("(unable to read directory) {}", e),
+                e.to_string(),
+            );
         }
     };
 
@@ -583,6 +1504,9 @@ fn preview_dir(
             truncated = true;
             break;
         }
+        if shown.is_multiple_of(PREVIEW_CANCEL_CHECK_LINES) && cancel.is_cancelled() {
+            break;
+        }
 
         let entry = match next {
             Ok(e) => e,
@@ -606,121 +1530,389 @@ fn preview_dir(
         )));
     }
 
-    (title, lines, truncated, None)
+    BuiltPreview {
+        truncated,
+        ..BuiltPreview::new(title, lines)
+    }
 }
 
 fn preview_file(
     path: &std::path::Path,
     title: String,
     size: u64,
-    syntaxes: &SyntaxSet,
-    theme: &Theme,
-) -> (String, Vec<StyledLine>, bool, Option<String>) {
-    const MAX_BYTES: usize = 256 * 1024;
-    const MAX_LINES: usize = 4000;
-
+    mode: PreviewMode,
+    ctx: &PreviewContext<'_>,
+    cancel: &CancelToken,
+) -> BuiltPreview {
     let mut lines = vec![
         meta_line(format!("{}", path.display())),
         meta_line(format!("{} bytes", size)),
         meta_line(""),
     ];
 
-    let mut file = match std::fs::File::open(path) {
-        Ok(f) => f,
-        Err(e) => {
-            lines.push(error_line(format!("(unable to open file) {}", e)));
-            return (title, lines, false, Some(e.to_string()));
+    if mode == PreviewMode::Hex {
+        return preview_hex(path, title, lines, size, ctx.limits, cancel);
+    }
+
+    let buf = match read_preview_bytes(path, 0, ctx.limits, cancel) {
+        Ok(Some(buf)) => buf,
+        Ok(None) => return BuiltPreview::new(title, lines),
+        Err(e) => return BuiltPreview::failed(title, lines, e.0, e.1),
+    };
+
+    if vicaya_core::preview::is_binary(&buf) {
+        if mode == PreviewMode::Auto {
+            return preview_hex(path, title, lines, size, ctx.limits, cancel);
         }
+        lines.push(meta_line("(binary file preview)"));
+        return BuiltPreview {
+            truncated: true,
+            ..BuiltPreview::new(title, lines)
+        };
+    }
+
+    let first_line = buf.split(|&b| b == b'\n').next().unwrap_or_default();
+    let syntax = find_syntax(path, &String::from_utf8_lossy(first_line), ctx.syntaxes);
+    let highlighter = syntax.map(|s| HighlightLines::new(s, ctx.theme));
+
+    let mut preview = BuiltPreview::new(title, lines);
+    let Some((chunk, resume)) = highlight_chunk(&buf, 0, size, highlighter, ctx, cancel) else {
+        return preview;
     };
+    preview.lines.extend(chunk);
+    push_truncation_footer(&mut preview.lines, resume.is_some());
+    preview.truncated = resume.is_some();
+    preview.resume = resume;
+    preview
+}
 
-    use std::io::Read;
-    let mut buf = vec![0u8; MAX_BYTES];
-    let read = match file.read(&mut buf) {
-        Ok(n) => n,
+/// Preview a member of an indexed archive (`app.jar!/META-INF/MANIFEST.MF`),
+/// decompressing only that member. `L` is not offered; the first chunk is
+/// all a member preview shows.
+fn preview_archive_member(
+    archive: &std::path::Path,
+    member: &str,
+    title: String,
+    ctx: &PreviewContext<'_>,
+    cancel: &CancelToken,
+) -> BuiltPreview {
+    let mut lines = vec![meta_line(format!("{} ▸ {}", archive.display(), member))];
+    let (buf, size) = match vicaya_core::archive::read_member(archive, member, ctx.limits.max_bytes)
+    {
+        Ok(read) => read,
         Err(e) => {
-            lines.push(error_line(format!("(unable to read file) {}", e)));
-            return (title, lines, false, Some(e.to_string()));
+            let message = format!("(unable to read archive member) {}", e);
+            return BuiltPreview::failed(title, lines, message, e.to_string());
         }
     };
-    buf.truncate(read);
+    lines.push(meta_line(format!("{} bytes (in archive)", size)));
+    lines.push(meta_line(""));
 
-    if buf.contains(&0) {
-        lines.push(meta_line("(binary file preview)"));
-        return (title, lines, true, None);
+    if vicaya_core::preview::is_binary(&buf) {
+        lines.push(meta_line("(binary archive member)"));
+        return BuiltPreview::new(title, lines);
+    }
+
+    let member_path = std::path::Path::new(member);
+    let first_line = buf.split(|&b| b == b'\n').next().unwrap_or_default();
+    let syntax = find_syntax(
+        member_path,
+        &String::from_utf8_lossy(first_line),
+        ctx.syntaxes,
+    );
+    let highlighter = syntax.map(|s| HighlightLines::new(s, ctx.theme));
+    let Some((chunk, resume)) =
+        highlight_chunk(&buf, 0, buf.len() as u64, highlighter, ctx, cancel)
+    else {
+        return BuiltPreview::new(title, lines);
+    };
+    lines.extend(chunk);
+    let truncated = resume.is_some() || size > buf.len() as u64;
+    if truncated {
+        lines.push(meta_line(""));
+        lines.push(meta_line("… (archive member preview truncated)"));
     }
+    BuiltPreview {
+        truncated,
+        ..BuiltPreview::new(title, lines)
+    }
+}
 
-    let text = String::from_utf8_lossy(&buf);
-    let mut truncated_lines = false;
+/// Load the chunk after `resume`, continuing line numbering and highlighting.
+///
+/// Returns the new lines (with a footer if still truncated) and the next
+/// resume point, or `None` if cancelled.
+fn preview_more(
+    path: &str,
+    resume: &PreviewResume,
+    ctx: &PreviewContext<'_>,
+    cancel: &CancelToken,
+) -> Option<(Vec<StyledLine>, Option<PreviewResume>)> {
+    let size = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if resume.hex {
+        return hex_chunk(
+            std::path::Path::new(path),
+            resume.offset,
+            size,
+            ctx.limits,
+            cancel,
+        )
+        .map(|(mut lines, next)| {
+            push_truncation_footer(&mut lines, next.is_some());
+            (lines, next)
+        });
+    }
+    let buf = match read_preview_bytes(
+        std::path::Path::new(path),
+        resume.offset,
+        ctx.limits,
+        cancel,
+    ) {
+        Ok(Some(buf)) => buf,
+        Ok(None) => return None,
+        Err((message, _)) => return Some((vec![error_line(message)], None)),
+    };
 
-    let syntax = find_syntax(path, &text, syntaxes);
-    let mut highlighter = syntax.map(|s| HighlightLines::new(s, theme));
+    let highlighter = resume
+        .highlight
+        .clone()
+        .map(|(highlight, parse)| HighlightLines::from_state(ctx.theme, highlight, parse));
+    let (mut lines, next) = highlight_chunk(&buf, resume.offset, size, highlighter, ctx, cancel)?;
+    push_truncation_footer(&mut lines, next.is_some());
+    Some((lines, next))
+}
 
-    for (i, raw_line) in LinesWithEndings::from(text.as_ref()).enumerate() {
-        if i >= MAX_LINES {
-            truncated_lines = true;
-            break;
+fn preview_hex(
+    path: &std::path::Path,
+    title: String,
+    mut lines: Vec<StyledLine>,
+    size: u64,
+    limits: PreviewLimits,
+    cancel: &CancelToken,
+) -> BuiltPreview {
+    if let Some(header) = lines.get_mut(1) {
+        *header = meta_line(format!("{} bytes · hex", size));
+    }
+    let (chunk, resume) = match hex_chunk(path, 0, size, limits, cancel) {
+        Some(chunk) => chunk,
+        None => {
+            return BuiltPreview {
+                hex: true,
+                ..BuiltPreview::new(title, lines)
+            }
         }
+    };
+    lines.extend(chunk);
+    push_truncation_footer(&mut lines, resume.is_some());
+    BuiltPreview {
+        truncated: resume.is_some(),
+        hex: true,
+        resume,
+        ..BuiltPreview::new(title, lines)
+    }
+}
 
-        if let Some(ref mut highlighter) = highlighter {
-            let sanitized = sanitize_line(raw_line);
-            match highlighter.highlight_line(&sanitized, syntaxes) {
-                Ok(ranges) => {
-                    let mut out = Vec::with_capacity(ranges.len().max(1));
-                    for (style, fragment) in ranges {
-                        let fragment = strip_line_endings(fragment);
-                        if fragment.is_empty() {
-                            continue;
-                        }
-                        out.push(StyledSegment {
-                            text: fragment.to_string(),
-                            style: syntect_style_to_text_style(style),
-                        });
-                    }
+/// Bytes per hex chunk: whole rows, bounded by both preview limits.
+fn hex_chunk_len(limits: PreviewLimits) -> usize {
+    let rows = (limits.max_bytes / HEX_BYTES_PER_ROW)
+        .min(limits.max_lines)
+        .max(1);
+    rows * HEX_BYTES_PER_ROW
+}
 
-                    if out.is_empty() {
-                        lines.push(plain_line(""));
-                    } else {
-                        lines.push(out);
-                    }
+/// Hex-dump one chunk starting at `offset`, or `None` if cancelled.
+fn hex_chunk(
+    path: &std::path::Path,
+    offset: u64,
+    size: u64,
+    limits: PreviewLimits,
+    cancel: &CancelToken,
+) -> Option<(Vec<StyledLine>, Option<PreviewResume>)> {
+    let chunk_limits = PreviewLimits {
+        max_bytes: hex_chunk_len(limits),
+        ..limits
+    };
+    let buf = match read_preview_bytes(path, offset, chunk_limits, cancel) {
+        Ok(Some(buf)) => buf,
+        Ok(None) => return None,
+        Err((message, _)) => return Some((vec![error_line(message)], None)),
+    };
+
+    let next_offset = offset + buf.len() as u64;
+    let resume = (next_offset < size && !buf.is_empty()).then_some(PreviewResume {
+        offset: next_offset,
+        hex: true,
+        highlight: None,
+    });
+    Some((hex_dump_lines(&buf, offset), resume))
+}
+
+/// Classic `offset  hex bytes  |ascii|` rows.
+fn hex_dump_lines(buf: &[u8], offset: u64) -> Vec<StyledLine> {
+    use std::fmt::Write;
+
+    buf.chunks(HEX_BYTES_PER_ROW)
+        .enumerate()
+        .map(|(row, bytes)| {
+            let mut hex = String::with_capacity(HEX_BYTES_PER_ROW * 3 + 1);
+            for i in 0..HEX_BYTES_PER_ROW {
+                if i == HEX_BYTES_PER_ROW / 2 {
+                    hex.push(' ');
                 }
-                Err(_) => {
-                    lines.push(plain_line(strip_line_endings(&sanitized)));
+                match bytes.get(i) {
+                    Some(byte) => {
+                        let _ = write!(hex, "{byte:02x} ");
+                    }
+                    None => hex.push_str("   "),
                 }
             }
-        } else {
-            let sanitized = sanitize_line(raw_line);
-            lines.push(plain_line(strip_line_endings(&sanitized)));
-        }
-    }
-
-    let truncated_bytes = read >= MAX_BYTES;
-    let truncated = truncated_bytes || truncated_lines;
+            let ascii: String = bytes
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            let row_offset = offset + (row * HEX_BYTES_PER_ROW) as u64;
+
+            let mut line = meta_line(format!("{row_offset:08x}  "));
+            line.extend(plain_line(hex));
+            line.extend(meta_line(format!("|{ascii}|")));
+            line
+        })
+        .collect()
+}
 
+fn push_truncation_footer(lines: &mut Vec<StyledLine>, truncated: bool) {
     if truncated {
         lines.push(meta_line(""));
-        lines.push(meta_line("… (preview truncated)"));
+        lines.push(meta_line("… (preview truncated — L loads more)"));
+    }
+}
+
+/// Read up to `limits.max_bytes` starting at `offset`, checking `cancel`
+/// between chunks. `Ok(None)` means cancelled; errors carry a display line
+/// and the raw error text.
+fn read_preview_bytes(
+    path: &std::path::Path,
+    offset: u64,
+    limits: PreviewLimits,
+    cancel: &CancelToken,
+) -> Result<Option<Vec<u8>>, (String, String)> {
+    use std::io::{Read, Seek};
+
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| (format!("(unable to open file) {}", e), e.to_string()))?;
+    if offset > 0 {
+        file.seek(std::io::SeekFrom::Start(offset))
+            .map_err(|e| (format!("(unable to read file) {}", e), e.to_string()))?;
     }
 
-    (title, lines, truncated, None)
+    let mut buf = vec![0u8; limits.max_bytes];
+    let mut read = 0;
+    while read < limits.max_bytes {
+        if cancel.is_cancelled() {
+            return Ok(None);
+        }
+        let end = (read + PREVIEW_READ_CHUNK).min(limits.max_bytes);
+        match file.read(&mut buf[read..end]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err((format!("(unable to read file) {}", e), e.to_string())),
+        }
+    }
+    buf.truncate(read);
+    Ok(Some(buf))
 }
 
-fn strip_line_endings(s: &str) -> &str {
-    let s = s.strip_suffix('\n').unwrap_or(s);
-    s.strip_suffix('\r').unwrap_or(s)
+/// Highlight the complete lines of `buf` (read from `offset` of a `size`-byte
+/// file) up to `limits.max_lines`, returning where the next chunk starts if
+/// the file continues.
+fn highlight_chunk(
+    buf: &[u8],
+    offset: u64,
+    size: u64,
+    mut highlighter: Option<HighlightLines<'_>>,
+    ctx: &PreviewContext<'_>,
+    cancel: &CancelToken,
+) -> Option<(Vec<StyledLine>, Option<PreviewResume>)> {
+    let more_bytes = offset + (buf.len() as u64) < size;
+    // Stop at the last newline so the next chunk starts on a line boundary.
+    let usable = if more_bytes {
+        buf.iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(buf.len(), |pos| pos + 1)
+    } else {
+        buf.len()
+    };
+
+    let mut lines = Vec::new();
+    let mut consumed = 0usize;
+    for (i, raw_line) in buf[..usable].split_inclusive(|&b| b == b'\n').enumerate() {
+        if i >= ctx.limits.max_lines {
+            break;
+        }
+        if i.is_multiple_of(PREVIEW_CANCEL_CHECK_LINES) && cancel.is_cancelled() {
+            return None;
+        }
+        consumed += raw_line.len();
+        let sanitized = vicaya_core::preview::sanitize_line(&String::from_utf8_lossy(raw_line));
+        lines.push(highlight_line(
+            &sanitized,
+            highlighter.as_mut(),
+            ctx.syntaxes,
+        ));
+    }
+
+    let next_offset = offset + consumed as u64;
+    let resume = (next_offset < size).then(|| PreviewResume {
+        offset: next_offset,
+        hex: false,
+        highlight: highlighter.map(HighlightLines::state),
+    });
+    Some((lines, resume))
 }
 
-fn sanitize_line(s: &str) -> String {
-    let mut out = String::with_capacity(s.len());
-    for ch in s.chars() {
-        match ch {
-            '\t' => out.push_str("    "),
-            '\r' => {}
-            // Avoid raw ANSI/control chars affecting terminal state.
-            c if c.is_control() && c != '\n' => out.push('�'),
-            _ => out.push(ch),
+fn highlight_line(
+    sanitized: &str,
+    highlighter: Option<&mut HighlightLines<'_>>,
+    syntaxes: &SyntaxSet,
+) -> StyledLine {
+    let Some(highlighter) = highlighter else {
+        return plain_line(strip_line_endings(sanitized));
+    };
+
+    match highlighter.highlight_line(sanitized, syntaxes) {
+        Ok(ranges) => {
+            let mut out = Vec::with_capacity(ranges.len().max(1));
+            for (style, fragment) in ranges {
+                let fragment = strip_line_endings(fragment);
+                if fragment.is_empty() {
+                    continue;
+                }
+                out.push(StyledSegment {
+                    text: fragment.to_string(),
+                    style: syntect_style_to_text_style(style),
+                });
+            }
+
+            if out.is_empty() {
+                plain_line("")
+            } else {
+                out
+            }
         }
+        Err(_) => plain_line(strip_line_endings(sanitized)),
     }
-    out
+}
+
+fn strip_line_endings(s: &str) -> &str {
+    let s = s.strip_suffix('\n').unwrap_or(s);
+    s.strip_suffix('\r').unwrap_or(s)
 }
 
 fn find_syntax<'a>(
@@ -777,6 +1969,14 @@ mod tests {
             score: 1.0,
             size,
             mtime,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
         }
     }
 
@@ -787,6 +1987,37 @@ mod tests {
         )
     }
 
+    fn test_context<'a>(syntaxes: &'a SyntaxSet, theme: &'a Theme) -> PreviewContext<'a> {
+        PreviewContext {
+            syntaxes,
+            theme,
+            limits: PreviewLimits::default(),
+        }
+    }
+
+    #[test]
+    fn count_file_matches_reads_a_bounded_head_and_skips_binaries() {
+        let dir = tempdir().unwrap();
+        let text = dir.path().join("notes.txt");
+        std::fs::write(&text, "TODO one\nnothing\ntodo two\nTODO three\n").unwrap();
+        let binary = dir.path().join("blob.bin");
+        std::fs::write(&binary, b"TODO\0TODO").unwrap();
+        let text = text.to_string_lossy();
+
+        assert_eq!(count_file_matches(&text, "todo", 1024), Some(3));
+        // Only "TODO one\nnothing\ntodo" is read.
+        assert_eq!(count_file_matches(&text, "todo", 22), Some(2));
+        assert_eq!(count_file_matches(&text, "absent", 1024), Some(0));
+        assert_eq!(
+            count_file_matches(&binary.to_string_lossy(), "todo", 1024),
+            None
+        );
+        assert_eq!(
+            count_file_matches(&dir.path().to_string_lossy(), "todo", 1024),
+            None
+        );
+    }
+
     #[test]
     fn matches_filters_applies_scope_and_size() {
         let dir = tempdir().unwrap();
@@ -799,6 +2030,14 @@ mod tests {
             score: 1.0,
             size: 5,
             mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
         };
 
         let scope = dir.path();
@@ -827,6 +2066,14 @@ mod tests {
             score: 1.0,
             size: 0,
             mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
         };
         let subdir = SearchResult {
             path: dir_path.to_string_lossy().to_string(),
@@ -834,6 +2081,14 @@ mod tests {
             score: 1.0,
             size: 0,
             mtime: 0,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: true,
+            total_size: None,
+            explain: None,
         };
 
         let type_dir = vec![Niyama::Type {
@@ -938,6 +2193,30 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn in_bundle_niyama_keeps_results_inside_matching_bundles() {
+        let niyamas = vec![Niyama::InBundle {
+            needle: "xcode".to_string(),
+            raw: "in-bundle:xcode".to_string(),
+        }];
+        let matches = |path: &str| {
+            matches_filters(
+                &result(std::path::Path::new(path), "x", 1, 1),
+                ViewKind::Patra,
+                None,
+                &niyamas,
+            )
+        };
+
+        assert!(matches("/Applications/Xcode.app/Contents/Info.plist"));
+        assert!(matches(
+            "/Applications/Xcode.app/Contents/Frameworks/A.framework/A"
+        ));
+        assert!(!matches("/Applications/Xcode.app"));
+        assert!(!matches("/Applications/Safari.app/Contents/Info.plist"));
+        assert!(!matches("/Users/me/xcode/notes.txt"));
+    }
+
     #[test]
     fn preview_file_sanitizes_controls_and_assigns_highlight_styles() {
         let dir = tempdir().unwrap();
@@ -946,8 +2225,18 @@ mod tests {
         let (syntaxes, themes) = test_syntaxes_and_theme();
         let theme = pick_theme(&themes);
 
-        let (title, lines, truncated, error) =
-            build_preview(file.to_str().unwrap(), &syntaxes, theme);
+        let BuiltPreview {
+            title,
+            lines,
+            truncated,
+            error,
+            ..
+        } = build_preview(
+            file.to_str().unwrap(),
+            PreviewMode::Auto,
+            &test_context(&syntaxes, theme),
+            &CancelToken::default(),
+        );
 
         assert_eq!(title, "main.rs");
         assert!(!truncated);
@@ -963,25 +2252,80 @@ mod tests {
     }
 
     #[test]
-    fn preview_binary_file_is_marked_truncated_without_decoding() {
+    fn preview_binary_file_renders_hex_dump_with_ascii_gutter() {
         let dir = tempdir().unwrap();
         let file = dir.path().join("archive.bin");
         std::fs::write(&file, b"abc\0def").unwrap();
         let (syntaxes, themes) = test_syntaxes_and_theme();
-        let theme = pick_theme(&themes);
+        let ctx = test_context(&syntaxes, pick_theme(&themes));
 
-        let (title, lines, truncated, error) =
-            build_preview(file.to_str().unwrap(), &syntaxes, theme);
+        let preview = build_preview(
+            file.to_str().unwrap(),
+            PreviewMode::Auto,
+            &ctx,
+            &CancelToken::default(),
+        );
 
-        assert_eq!(title, "archive.bin");
-        assert!(truncated);
-        assert!(error.is_none());
-        assert!(lines
+        assert_eq!(preview.title, "archive.bin");
+        assert!(preview.hex);
+        assert!(!preview.truncated);
+        assert!(preview.error.is_none());
+        let row: String = preview
+            .lines
+            .last()
+            .unwrap()
+            .iter()
+            .map(|seg| seg.text.as_str())
+            .collect();
+        assert_eq!(
+            row,
+            "00000000  61 62 63 00 64 65 66                             |abc.def|"
+        );
+
+        let text = build_preview(
+            file.to_str().unwrap(),
+            PreviewMode::Text,
+            &ctx,
+            &CancelToken::default(),
+        );
+        assert!(!text.hex);
+        assert!(text.truncated);
+        assert!(text
+            .lines
             .iter()
             .flatten()
             .any(|seg| seg.text.contains("binary file preview")));
     }
 
+    #[test]
+    fn hex_preview_pages_through_file_in_whole_rows() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        let body: Vec<u8> = (0u8..=99).collect();
+        std::fs::write(&file, &body).unwrap();
+        let path = file.to_str().unwrap();
+        let (syntaxes, themes) = test_syntaxes_and_theme();
+        let ctx = PreviewContext {
+            syntaxes: &syntaxes,
+            theme: pick_theme(&themes),
+            limits: PreviewLimits {
+                max_bytes: 40,
+                max_lines: 4000,
+            },
+        };
+
+        let first = build_preview(path, PreviewMode::Hex, &ctx, &CancelToken::default());
+        assert!(first.hex);
+        let resume = first.resume.expect("more rows");
+        assert_eq!(resume.offset, 32);
+        assert!(resume.hex);
+
+        let (lines, next) = preview_more(path, &resume, &ctx, &CancelToken::default()).unwrap();
+        let first_offset: String = lines[0][0].text.clone();
+        assert_eq!(first_offset, "00000020  ");
+        assert_eq!(next.unwrap().offset, 64);
+    }
+
     #[test]
     fn preview_directory_lists_entries_and_marks_directories() {
         let dir = tempdir().unwrap();
@@ -990,8 +2334,18 @@ mod tests {
         let (syntaxes, themes) = test_syntaxes_and_theme();
         let theme = pick_theme(&themes);
 
-        let (_title, lines, truncated, error) =
-            build_preview(dir.path().to_str().unwrap(), &syntaxes, theme);
+        let BuiltPreview {
+            title: _,
+            lines,
+            truncated,
+            error,
+            ..
+        } = build_preview(
+            dir.path().to_str().unwrap(),
+            PreviewMode::Auto,
+            &test_context(&syntaxes, theme),
+            &CancelToken::default(),
+        );
 
         assert!(!truncated);
         assert!(error.is_none());
@@ -1004,6 +2358,53 @@ mod tests {
         assert!(rendered.contains("src/"));
     }
 
+    #[test]
+    fn preview_archive_member_extracts_only_that_member() {
+        let dir = tempdir().unwrap();
+        let archive = dir.path().join("src.tar");
+        let mut tar = tar::Builder::new(std::fs::File::create(&archive).unwrap());
+        for (name, content) in [("pkg/lib.rs", "fn lib() {}\n"), ("pkg/other.rs", "other")] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, content.as_bytes())
+                .unwrap();
+        }
+        tar.finish().unwrap();
+        drop(tar);
+        let (syntaxes, themes) = test_syntaxes_and_theme();
+        let theme = pick_theme(&themes);
+        let member = format!("{}!/pkg/lib.rs", archive.display());
+
+        let preview = build_preview(
+            &member,
+            PreviewMode::Auto,
+            &test_context(&syntaxes, theme),
+            &CancelToken::default(),
+        );
+
+        assert_eq!(preview.title, "lib.rs");
+        assert!(preview.error.is_none());
+        assert!(preview.resume.is_none());
+        let rendered = preview
+            .lines
+            .iter()
+            .map(|line| line.iter().map(|seg| seg.text.as_str()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(rendered.contains("fn lib()"), "{rendered}");
+        assert!(!rendered.contains("other"), "{rendered}");
+
+        let missing = build_preview(
+            &format!("{}!/pkg/missing.rs", archive.display()),
+            PreviewMode::Auto,
+            &test_context(&syntaxes, theme),
+            &CancelToken::default(),
+        );
+        assert!(missing.error.is_some());
+    }
+
     #[test]
     fn preview_directory_truncates_large_listing() {
         let dir = tempdir().unwrap();
@@ -1013,8 +2414,18 @@ mod tests {
         let (syntaxes, themes) = test_syntaxes_and_theme();
         let theme = pick_theme(&themes);
 
-        let (_title, lines, truncated, error) =
-            build_preview(dir.path().to_str().unwrap(), &syntaxes, theme);
+        let BuiltPreview {
+            title: _,
+            lines,
+            truncated,
+            error,
+            ..
+        } = build_preview(
+            dir.path().to_str().unwrap(),
+            PreviewMode::Auto,
+            &test_context(&syntaxes, theme),
+            &CancelToken::default(),
+        );
 
         assert!(truncated);
         assert!(error.is_none());
@@ -1024,6 +2435,230 @@ mod tests {
             .any(|seg| seg.text.contains("showing first 200 entries")));
     }
 
+    #[test]
+    fn preview_more_continues_at_line_boundary_with_highlighting() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("long.rs");
+        let body: String = (0..25)
+            .map(|i| format!("let line_{i:02} = {i};\n"))
+            .collect();
+        std::fs::write(&file, &body).unwrap();
+        let path = file.to_str().unwrap();
+        let (syntaxes, themes) = test_syntaxes_and_theme();
+        let ctx = PreviewContext {
+            syntaxes: &syntaxes,
+            theme: pick_theme(&themes),
+            limits: PreviewLimits {
+                max_bytes: 64,
+                max_lines: 10,
+            },
+        };
+        let content = |lines: &[StyledLine]| -> Vec<String> {
+            lines
+                .iter()
+                .filter(|line| line.iter().any(|seg| seg.style.kind != TextKind::Meta))
+                .map(|line| line.iter().map(|seg| seg.text.as_str()).collect())
+                .collect()
+        };
+
+        let first = build_preview(path, PreviewMode::Auto, &ctx, &CancelToken::default());
+        assert!(first.truncated);
+        let mut shown = content(&first.lines);
+        // 64 bytes hold three 18-byte lines; the partial fourth is deferred.
+        assert_eq!(shown.len(), 3);
+
+        let mut resume = first.resume.unwrap();
+        assert!(resume.highlight.is_some());
+        loop {
+            let (lines, next) =
+                preview_more(path, &resume, &ctx, &CancelToken::default()).expect("not cancelled");
+            assert!(lines.iter().flatten().any(|seg| seg.style.fg.is_some()));
+            shown.extend(content(&lines));
+            match next {
+                Some(next) => resume = next,
+                None => break,
+            }
+        }
+
+        let expected: Vec<String> = body.lines().map(str::to_string).collect();
+        assert_eq!(shown, expected);
+    }
+
+    #[test]
+    fn preview_cache_reuses_until_file_changes() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("lib.rs");
+        std::fs::write(&file, "fn a() {}\n").unwrap();
+        let path = file.to_str().unwrap();
+        let (syntaxes, themes) = test_syntaxes_and_theme();
+        let theme = pick_theme(&themes);
+        let mut cache = PreviewCache::new(PREVIEW_CACHE_BUDGET_BYTES);
+
+        let ctx = test_context(&syntaxes, theme);
+        let first = load_preview(
+            path,
+            PreviewMode::Auto,
+            &mut cache,
+            &ctx,
+            &CancelToken::default(),
+        )
+        .lines;
+        assert_eq!(cache.entries.len(), 1);
+        let stamp = PreviewStamp::of(path).unwrap();
+        assert!(cache.get(path, stamp).is_some());
+
+        std::fs::write(&file, "fn a() {}\nfn longer_body() {}\n").unwrap();
+        let second = load_preview(
+            path,
+            PreviewMode::Auto,
+            &mut cache,
+            &ctx,
+            &CancelToken::default(),
+        )
+        .lines;
+        assert!(second.len() > first.len());
+        assert_eq!(cache.entries.len(), 1);
+    }
+
+    #[test]
+    fn cancelled_preview_stops_early_and_is_not_cached() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("big.rs");
+        std::fs::write(&file, "fn a() {}\n".repeat(1000)).unwrap();
+        let path = file.to_str().unwrap();
+        let (syntaxes, themes) = test_syntaxes_and_theme();
+        let theme = pick_theme(&themes);
+        let mut cache = PreviewCache::new(PREVIEW_CACHE_BUDGET_BYTES);
+        let cancel = CancelToken::default();
+        cancel.cancel();
+
+        let BuiltPreview {
+            lines,
+            truncated,
+            error,
+            ..
+        } = load_preview(
+            path,
+            PreviewMode::Auto,
+            &mut cache,
+            &test_context(&syntaxes, theme),
+            &cancel,
+        );
+
+        assert!(error.is_none());
+        assert!(!truncated);
+        assert!(lines.len() < 10);
+        assert!(cache.entries.is_empty());
+    }
+
+    #[test]
+    fn preview_worker_skips_superseded_jobs() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("main.rs");
+        std::fs::write(&file, "fn main() {}\n").unwrap();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (evt_tx, evt_rx) = std::sync::mpsc::channel();
+
+        let stale = CancelToken::default();
+        stale.cancel();
+        for (id, cancel) in [(1, stale), (2, CancelToken::default())] {
+            tx.send(PreviewMessage::Build(PreviewJob {
+                id,
+                path: file.to_string_lossy().to_string(),
+                anchor_line: None,
+                mode: PreviewMode::Auto,
+                cancel,
+            }))
+            .unwrap();
+        }
+        drop(tx);
+        start_preview_worker(rx, evt_tx, PreviewLimits::default())
+            .join()
+            .unwrap();
+
+        let ids: Vec<u64> = evt_rx
+            .try_iter()
+            .filter_map(|evt| match evt {
+                WorkerEvent::PreviewReady { id, .. } => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids, vec![2]);
+    }
+
+    #[test]
+    fn preview_cache_evicts_least_recently_used_within_budget() {
+        let line = vec![plain_line("x".repeat(100))];
+        let stamp = PreviewStamp {
+            mtime: None,
+            size: 100,
+        };
+        let one = preview_bytes("/a", "a", &line);
+        let mut cache = PreviewCache::new(one * 2);
+
+        cache.insert(
+            "/a",
+            stamp,
+            &BuiltPreview::new("a".to_string(), line.clone()),
+        );
+        cache.insert(
+            "/b",
+            stamp,
+            &BuiltPreview::new("b".to_string(), line.clone()),
+        );
+        assert!(cache.get("/a", stamp).is_some());
+        cache.insert(
+            "/c",
+            stamp,
+            &BuiltPreview::new("c".to_string(), line.clone()),
+        );
+
+        assert!(cache.entries.contains_key("/a"));
+        assert!(!cache.entries.contains_key("/b"));
+        assert!(cache.entries.contains_key("/c"));
+        assert!(cache.used_bytes <= one * 2);
+    }
+
+    #[test]
+    fn preview_cache_drops_entries_the_daemon_reports_changed() {
+        let line = vec![plain_line("body")];
+        let stamp = PreviewStamp {
+            mtime: Some(std::time::UNIX_EPOCH + Duration::from_secs(10)),
+            size: 4,
+        };
+        let mut cache = PreviewCache::new(PREVIEW_CACHE_BUDGET_BYTES);
+        cache.insert(
+            "/same",
+            stamp,
+            &BuiltPreview::new("same".to_string(), line.clone()),
+        );
+        cache.insert(
+            "/changed",
+            stamp,
+            &BuiltPreview::new("changed".to_string(), line.clone()),
+        );
+        let result = |path: &str, mtime| SearchResult {
+            path: path.to_string(),
+            name: String::new(),
+            score: 1.0,
+            size: 4,
+            mtime,
+            uid: 0,
+            gid: 0,
+            mode: 0,
+            indexed_at: 0,
+            content_hash: None,
+            is_dir: false,
+            total_size: None,
+            explain: None,
+        };
+
+        cache.invalidate_changed(&[result("/same", 10), result("/changed", 11)]);
+
+        assert!(cache.entries.contains_key("/same"));
+        assert!(!cache.entries.contains_key("/changed"));
+    }
+
     #[test]
     fn preview_missing_path_returns_error_line() {
         let dir = tempdir().unwrap();
@@ -1031,11 +2666,23 @@ mod tests {
         let (syntaxes, themes) = test_syntaxes_and_theme();
         let theme = pick_theme(&themes);
 
-        let (_title, lines, truncated, error) =
-            build_preview(missing.to_str().unwrap(), &syntaxes, theme);
+        let BuiltPreview {
+            title: _,
+            lines,
+            truncated,
+            error,
+            missing: is_missing,
+            ..
+        } = build_preview(
+            missing.to_str().unwrap(),
+            PreviewMode::Auto,
+            &test_context(&syntaxes, theme),
+            &CancelToken::default(),
+        );
 
         assert!(!truncated);
         assert!(error.is_some());
+        assert!(is_missing);
         assert!(lines.iter().flatten().any(|seg| {
             seg.style.kind == TextKind::Error && seg.text.contains("unable to read metadata")
         }));
@@ -1056,12 +2703,15 @@ mod tests {
                     Ok((mut stream, _)) => {
                         stream.set_nonblocking(false).unwrap();
                         let mut reader = BufReader::new(stream.try_clone().unwrap());
-                        let line = vicaya_core::ipc::read_message(&mut reader)
-                            .unwrap()
-                            .unwrap();
+                        // A status thread left over from an earlier test can
+                        // connect and hang up without sending anything.
+                        let Some(line) = vicaya_core::ipc::read_message(&mut reader).unwrap()
+                        else {
+                            continue;
+                        };
                         let request = Request::from_json(&line).unwrap();
                         let response = match &request {
-                            Request::Status => Response::Status {
+                            Request::Status => Response::Status(DaemonStatus {
                                 pid: 77,
                                 build: BuildInfo {
                                     version: "1.2.0".to_string(),
@@ -1076,8 +2726,18 @@ mod tests {
                                 state_allocated_bytes: 512,
                                 last_updated: 1_700_000_000,
                                 reconciling: false,
-                            },
-                            Request::Search { .. } => Response::SearchResults {
+                                pending_watcher_events: 0,
+                                journal_entries: 0,
+                                reconcile_progress: None,
+                                reconcile_scan: None,
+                                warmup_ms: None,
+                                journal_fsync: None,
+                                index_cap: None,
+                                last_reconcile: None,
+                                next_reconcile: None,
+                                index_profile: Default::default(),
+                            }),
+                            Request::Search(_) => Response::SearchResults {
                                 results: vec![
                                     vicaya_core::ipc::SearchResult {
                                         path: "/tmp/repo/src/main.rs".to_string(),
@@ -1085,6 +2745,14 @@ mod tests {
                                         score: 1.0,
                                         size: 12,
                                         mtime: 1_700_000_000,
+                                        uid: 0,
+                                        gid: 0,
+                                        mode: 0,
+                                        indexed_at: 0,
+                                        content_hash: None,
+                                        is_dir: false,
+                                        total_size: None,
+                                        explain: None,
                                     },
                                     vicaya_core::ipc::SearchResult {
                                         path: "/tmp/repo/target/main.rs".to_string(),
@@ -1092,15 +2760,54 @@ mod tests {
                                         score: 0.5,
                                         size: 12,
                                         mtime: 1_700_000_000,
+                                        uid: 0,
+                                        gid: 0,
+                                        mode: 0,
+                                        indexed_at: 0,
+                                        content_hash: None,
+                                        is_dir: false,
+                                        total_size: None,
+                                        explain: None,
                                     },
                                 ],
+                                truncated: true,
+                                suggestions: Vec::new(),
+                                outside_scope: Vec::new(),
                             },
+                            Request::Subscribe(_) => Response::Subscribed,
                             _ => Response::Ok,
                         };
-                        requests.push(request);
                         let mut json = response.to_json().unwrap();
                         json.push('\n');
                         let _ = stream.write_all(json.as_bytes());
+                        if matches!(request, Request::Subscribe(_)) {
+                            let result = |path: &str| vicaya_core::ipc::SearchResult {
+                                path: path.to_string(),
+                                name: "new_main.rs".to_string(),
+                                score: 0.9,
+                                size: 1,
+                                mtime: 1_700_000_000,
+                                uid: 0,
+                                gid: 0,
+                                mode: 0,
+                                indexed_at: 0,
+                                content_hash: None,
+                                is_dir: false,
+                                total_size: None,
+                                explain: None,
+                            };
+                            let push = Response::IndexChanged {
+                                removed: vec!["/tmp/repo/src/main.rs".to_string()],
+                                added: vec![
+                                    result("/tmp/repo/src/new_main.rs"),
+                                    result("/tmp/repo/target/new_main.rs"),
+                                ],
+                            };
+                            let mut json = push.to_json().unwrap();
+                            json.push('\n');
+                            let _ = stream.write_all(json.as_bytes());
+                        }
+                        requests.push(request);
                     }
                     Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                         std::thread::sleep(std::time::Duration::from_millis(10));
@@ -1144,7 +2851,7 @@ mod tests {
                                         std::thread::sleep(Duration::from_millis(25));
                                     }
                                 }
-                                Request::Search { .. } => {
+                                Request::Search(_) => {
                                     let response = Response::SearchResults {
                                         results: vec![vicaya_core::ipc::SearchResult {
                                             path: "/tmp/repo/src/main.rs".to_string(),
@@ -1152,7 +2859,18 @@ mod tests {
                                             score: 1.0,
                                             size: 12,
                                             mtime: 1_700_000_000,
+                                            uid: 0,
+                                            gid: 0,
+                                            mode: 0,
+                                            indexed_at: 0,
+                                            content_hash: None,
+                                            is_dir: false,
+                                            total_size: None,
+                                            explain: None,
                                         }],
+                                        truncated: false,
+                                        suggestions: Vec::new(),
+                                        outside_scope: Vec::new(),
                                     };
                                     let mut json = response.to_json().unwrap();
                                     json.push('\n');
@@ -1206,7 +2924,7 @@ mod tests {
                                             return;
                                         }
 
-                                        Response::Status {
+                                        Response::Status(DaemonStatus {
                                             pid: 77,
                                             build: BuildInfo {
                                                 version: "1.2.0".to_string(),
@@ -1221,7 +2939,17 @@ mod tests {
                                             state_allocated_bytes: 512,
                                             last_updated: 1_700_000_000,
                                             reconciling: false,
-                                        }
+                                            pending_watcher_events: 0,
+                                            journal_entries: 0,
+                                            reconcile_progress: None,
+                                            reconcile_scan: None,
+                                            warmup_ms: None,
+                                            journal_fsync: None,
+                                            index_cap: None,
+                                            last_reconcile: None,
+                                            next_reconcile: None,
+                                            index_profile: Default::default(),
+                                        })
                                     }
                                     _ => Response::Ok,
                                 };
@@ -1267,6 +2995,9 @@ mod tests {
                 boost_scope: Some(std::path::PathBuf::from("/tmp/repo")),
                 filter_scope: Some(std::path::PathBuf::from("/tmp/repo/src")),
                 niyamas: Vec::new(),
+                hidden: true,
+                largest_first: false,
+                explain: false,
             })
             .unwrap();
         cmd_tx
@@ -1281,6 +3012,21 @@ mod tests {
                     needle: "src".to_string(),
                     raw: "path:src".to_string(),
                 }],
+                hidden: true,
+                largest_first: false,
+                explain: false,
+            })
+            .unwrap();
+        cmd_tx
+            .send(WorkerCommand::TulanaSearch {
+                id: 3,
+                query: "main".to_string(),
+                limit: 10,
+                view: ViewKind::Navatama,
+                scope: std::path::PathBuf::from("/tmp/backup"),
+                niyamas: Vec::new(),
+                hidden: true,
+                largest_first: false,
             })
             .unwrap();
         cmd_tx
@@ -1288,6 +3034,7 @@ mod tests {
                 id: 9,
                 path: preview_file.to_string_lossy().to_string(),
                 anchor_line: None,
+                mode: PreviewMode::Auto,
             })
             .unwrap();
 
@@ -1296,7 +3043,9 @@ mod tests {
         let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
         let mut saw_status = false;
         let mut saw_search = false;
+        let mut saw_tulana = false;
         let mut saw_preview = false;
+        let mut saw_live = false;
         while std::time::Instant::now() < deadline {
             if let Ok(event) = evt_rx.recv_timeout(std::time::Duration::from_millis(100)) {
                 match event {
@@ -1305,9 +3054,16 @@ mod tests {
                             .as_ref()
                             .is_some_and(|status| status.indexed_files == 3);
                     }
-                    WorkerEvent::SearchResults { id, results, error } => {
+                    WorkerEvent::SearchResults {
+                        id,
+                        results,
+                        truncated,
+                        error,
+                        ..
+                    } => {
                         if id == 2 {
                             assert!(error.is_none());
+                            assert!(truncated);
                             assert_eq!(results.len(), 1);
                             assert!(results[0].path.contains("/src/"));
                             saw_search = true;
@@ -1333,9 +3089,32 @@ mod tests {
                             saw_preview = true;
                         }
                     }
+                    WorkerEvent::TulanaResults { id, error, .. } => {
+                        assert_eq!(id, 3);
+                        assert!(error.is_none());
+                        saw_tulana = true;
+                    }
+                    WorkerEvent::IndexChanged {
+                        id, removed, added, ..
+                    } => {
+                        // Pushes follow the phala's search and its filters.
+                        assert_eq!(id, 2);
+                        let added: Vec<_> = added.iter().map(|r| r.path.as_str()).collect();
+                        assert_eq!(added, ["/tmp/repo/src/new_main.rs"]);
+                        assert_eq!(
+                            removed,
+                            ["/tmp/repo/src/main.rs", "/tmp/repo/target/new_main.rs"]
+                        );
+                        saw_live = true;
+                    }
+                    WorkerEvent::PreviewAppend { .. }
+                    | WorkerEvent::MatchCount { .. }
+                    | WorkerEvent::RootStats { .. }
+                    | WorkerEvent::SmritiUsage { .. }
+                    | WorkerEvent::PathMissing { .. } => {}
                 }
             }
-            if saw_status && saw_search && saw_preview {
+            if saw_status && saw_search && saw_tulana && saw_preview && saw_live {
                 break;
             }
         }
@@ -1347,14 +3126,23 @@ mod tests {
 
         assert!(saw_status, "worker did not report daemon status");
         assert!(saw_search, "worker did not report latest search results");
+        assert!(saw_tulana, "tulana search was coalesced away");
         assert!(saw_preview, "worker did not report preview");
+        assert!(saw_live, "worker did not report index changes");
         assert!(requests.iter().any(|req| matches!(req, Request::Status)));
         assert!(requests
             .iter()
-            .any(|req| { matches!(req, Request::Search { query, .. } if query == "main") }));
+            .any(|req| { matches!(req, Request::Subscribe(options) if options.term == "main") }));
+        assert!(requests.iter().any(|req| {
+            matches!(req, Request::Search(options) if options.term == "main" && options.scorer.is_none())
+        }));
         assert!(!requests
             .iter()
-            .any(|req| { matches!(req, Request::Search { query, .. } if query == "stale") }));
+            .any(|req| { matches!(req, Request::Search(options) if options.term == "stale") }));
+        assert!(requests.iter().any(|req| {
+            matches!(req, Request::Search(options) if options.filters.scope.as_deref() == Some("/tmp/backup")
+                && options.scorer.as_deref() == Some(RECENCY_SCORER))
+        }));
     }
 
     #[test]
@@ -1379,14 +3167,18 @@ mod tests {
                 boost_scope: Some(std::path::PathBuf::from("/tmp/repo")),
                 filter_scope: Some(std::path::PathBuf::from("/tmp/repo/src")),
                 niyamas: Vec::new(),
+                hidden: true,
+                largest_first: false,
+                explain: false,
             })
             .unwrap();
 
         let deadline = Instant::now() + Duration::from_secs(2);
         let mut saw_search = false;
         while Instant::now() < deadline {
-            if let Ok(WorkerEvent::SearchResults { id, results, error }) =
-                evt_rx.recv_timeout(Duration::from_millis(100))
+            if let Ok(WorkerEvent::SearchResults {
+                id, results, error, ..
+            }) = evt_rx.recv_timeout(Duration::from_millis(100))
             {
                 if id == 1 {
                     assert!(error.is_none(), "unexpected search error: {error:?}");
@@ -1407,7 +3199,7 @@ mod tests {
         assert!(requests.iter().any(|req| matches!(req, Request::Status)));
         assert!(requests
             .iter()
-            .any(|req| matches!(req, Request::Search { query, .. } if query == "main")));
+            .any(|req| matches!(req, Request::Search(options) if options.term == "main")));
     }
 
     #[test]
@@ -0,0 +1,257 @@
+//! Scripted UI tests: drive the TUI main loop and worker against a mock
+//! daemon and check the state and the rendered screen.
+
+use std::path::Path;
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use vicaya_core::ipc::{Request, Response, SearchResult, SearchSort};
+use vicaya_core::smriti::{SmritiAction, SmritiEntry};
+use vicaya_tui::state::{FocusTarget, ViewKind};
+use vicaya_tui::testing::{canned_response, Harness, MockDaemon};
+use vicaya_tui::{AppMode, AppState};
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn result(path: &Path, is_dir: bool) -> SearchResult {
+    SearchResult {
+        path: path.to_string_lossy().to_string(),
+        name: path.file_name().unwrap().to_string_lossy().to_string(),
+        score: 1.0,
+        size: 0,
+        mtime: 1_700_000_000,
+        uid: 0,
+        gid: 0,
+        mode: 0,
+        indexed_at: 0,
+        content_hash: None,
+        is_dir,
+        total_size: None,
+        explain: None,
+    }
+}
+
+#[test]
+fn tab_cycles_focus_through_prashna_phala_and_preview() {
+    let _lock = vicaya_core::paths::test_env_lock();
+    let vicaya_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("VICAYA_DIR", vicaya_dir.path());
+    let file = vicaya_dir.path().join("notes.md");
+    std::fs::write(&file, "# notes\n").unwrap();
+    let _daemon = MockDaemon::with_results(vicaya_dir.path(), vec![result(&file, false)]).unwrap();
+
+    let mut tui = Harness::new(AppState::new(), 120, 30);
+    tui.type_text("notes");
+    assert!(tui.settle(TIMEOUT));
+    assert_eq!(tui.app().search.results.len(), 1);
+    assert!(tui.screen().contains("prashna: notes"));
+    assert!(tui.screen().contains("notes.md"));
+
+    let mut focus = Vec::new();
+    for _ in 0..3 {
+        tui.key(KeyCode::Tab);
+        focus.push(tui.app().search.focus);
+    }
+    assert_eq!(
+        focus,
+        [
+            FocusTarget::Results,
+            FocusTarget::Preview,
+            FocusTarget::Input
+        ]
+    );
+
+    tui.key(KeyCode::BackTab);
+    assert_eq!(tui.app().search.focus, FocusTarget::Preview);
+    // Without a preview pane, focus skips it.
+    tui.key_with(KeyCode::Char('o'), KeyModifiers::CONTROL);
+    assert_eq!(tui.app().search.focus, FocusTarget::Results);
+    tui.key(KeyCode::Tab);
+    assert_eq!(tui.app().search.focus, FocusTarget::Input);
+}
+
+#[test]
+fn entering_a_directory_pushes_a_ksetra_and_scopes_the_search() {
+    let _lock = vicaya_core::paths::test_env_lock();
+    let vicaya_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("VICAYA_DIR", vicaya_dir.path());
+    let project = vicaya_dir.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let daemon = MockDaemon::with_results(vicaya_dir.path(), vec![result(&project, true)]).unwrap();
+
+    let mut tui = Harness::new(AppState::new(), 120, 30);
+    tui.type_text("project");
+    assert!(tui.settle(TIMEOUT));
+    tui.key(KeyCode::Tab).key(KeyCode::Right);
+    assert_eq!(tui.app().ksetra.current(), Some(&project));
+    assert!(tui.settle(TIMEOUT));
+    assert!(tui.screen().contains("project"));
+
+    let scope = project.to_string_lossy().to_string();
+    assert!(daemon.requests().iter().any(|request| matches!(
+        request,
+        Request::Search(options) if options.filters.scope.as_deref() == Some(scope.as_str())
+    )));
+
+    tui.key(KeyCode::Left);
+    assert!(tui.app().ksetra.is_global());
+}
+
+#[test]
+fn preview_search_scrolls_the_loaded_preview_to_the_first_match() {
+    let _lock = vicaya_core::paths::test_env_lock();
+    let vicaya_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("VICAYA_DIR", vicaya_dir.path());
+    let file = vicaya_dir.path().join("story.txt");
+    let text: String = (1..=80)
+        .map(|n| {
+            if n % 30 == 0 {
+                format!("line {n} needle\n")
+            } else {
+                format!("line {n}\n")
+            }
+        })
+        .collect();
+    std::fs::write(&file, text).unwrap();
+    let _daemon = MockDaemon::with_results(vicaya_dir.path(), vec![result(&file, false)]).unwrap();
+
+    let mut tui = Harness::new(AppState::new(), 120, 30);
+    tui.type_text("story");
+    assert!(tui.settle(TIMEOUT));
+    assert!(tui.wait_for(TIMEOUT, |app| app.preview.lines.len() > 80));
+
+    tui.key(KeyCode::Tab).key(KeyCode::Tab);
+    assert_eq!(tui.app().search.focus, FocusTarget::Preview);
+    tui.key(KeyCode::Char('/'));
+    assert_eq!(tui.app().mode, AppMode::PreviewSearch);
+    tui.type_text("needle").key(KeyCode::Enter);
+    assert_eq!(tui.app().mode, AppMode::Search);
+    assert_eq!(tui.app().preview.search_query, "needle");
+    assert!(tui.screen().contains("line 30 needle"));
+
+    assert!(tui.app().preview.scroll > 0);
+    tui.key_with(KeyCode::Char('G'), KeyModifiers::SHIFT);
+    assert!(tui.screen().contains("line 80"));
+
+    tui.key_with(KeyCode::Char('l'), KeyModifiers::CONTROL);
+    assert!(tui.app().preview.search_query.is_empty());
+}
+
+#[test]
+fn sthana_lists_largest_directories_first_on_request() {
+    let _lock = vicaya_core::paths::test_env_lock();
+    let vicaya_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("VICAYA_DIR", vicaya_dir.path());
+    let project = vicaya_dir.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let daemon = MockDaemon::with_results(vicaya_dir.path(), vec![result(&project, true)]).unwrap();
+
+    let mut app = AppState::new();
+    app.set_view(ViewKind::Sthana);
+    let mut tui = Harness::new(app, 120, 30);
+    tui.type_text("proj");
+    assert!(tui.settle(TIMEOUT));
+    tui.key(KeyCode::Tab);
+    tui.key_with(KeyCode::Char('S'), KeyModifiers::SHIFT);
+    assert!(tui.app().search.largest_first);
+    assert!(tui.settle(TIMEOUT));
+
+    let sorts: Vec<SearchSort> = daemon
+        .requests()
+        .into_iter()
+        .filter_map(|request| match request {
+            Request::Search(options) => Some(options.sort),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(sorts.first(), Some(&SearchSort::Relevance));
+    assert_eq!(sorts.last(), Some(&SearchSort::Size));
+}
+
+#[test]
+fn results_opened_before_carry_a_usage_badge() {
+    let _lock = vicaya_core::paths::test_env_lock();
+    let vicaya_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("VICAYA_DIR", vicaya_dir.path());
+    let report = vicaya_dir.path().join("report.md");
+    let draft = vicaya_dir.path().join("report-draft.md");
+    for path in [&report, &draft] {
+        std::fs::write(path, "# report\n").unwrap();
+    }
+    let results = vec![result(&report, false), result(&draft, false)];
+    let two_days_ago = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs() as i64
+        - 2 * 86_400
+        - 60;
+    let entry = SmritiEntry {
+        path: report.to_string_lossy().to_string(),
+        name: "report.md".to_string(),
+        total_count: 14,
+        open_count: 12,
+        copy_count: 2,
+        reveal_count: 0,
+        print_count: 0,
+        enter_count: 0,
+        first_used: two_days_ago - 86_400,
+        last_used: two_days_ago,
+        last_query: "report".to_string(),
+        last_action: SmritiAction::Open,
+    };
+    let _daemon = MockDaemon::start(vicaya_dir.path(), move |request| match request {
+        Request::SmritiList { .. } => Response::SmritiEntries {
+            entries: vec![entry.clone()],
+        },
+        request => canned_response(request, &results),
+    })
+    .unwrap();
+
+    let mut app = AppState::new();
+    app.usage_badges = true;
+    let mut tui = Harness::new(app, 120, 30);
+    tui.type_text("report");
+    assert!(tui.settle(TIMEOUT));
+    assert!(tui.wait_for(TIMEOUT, |app| app.smriti_usage.is_some()));
+    let screen = tui.screen();
+    assert!(screen.contains("opened 12× · 2d ago"), "{screen}");
+    assert_eq!(screen.matches("opened").count(), 1);
+
+    // Forgetting the path drops its badge at once.
+    tui.app_mut()
+        .forget_smriti_path(report.to_string_lossy().to_string());
+    tui.tick();
+    assert!(!tui.screen().contains("opened 12×"));
+}
+
+#[test]
+fn a_ksetra_without_matches_lists_results_outside_it_under_a_header() {
+    let _lock = vicaya_core::paths::test_env_lock();
+    let vicaya_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("VICAYA_DIR", vicaya_dir.path());
+    let project = vicaya_dir.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    let guide = vicaya_dir.path().join("guide.md");
+    std::fs::write(&guide, "# guide\n").unwrap();
+    let outside = vec![result(&guide, false)];
+    let _daemon = MockDaemon::start(vicaya_dir.path(), move |request| match request {
+        Request::Search(options) if !options.term.is_empty() => Response::SearchResults {
+            results: Vec::new(),
+            truncated: false,
+            suggestions: Vec::new(),
+            outside_scope: outside.clone(),
+        },
+        request => canned_response(request, &[]),
+    })
+    .unwrap();
+
+    let mut tui = Harness::new(AppState::with_startup_scope(Some(project)), 120, 30);
+    tui.type_text("guide");
+    assert!(tui.settle(TIMEOUT));
+    assert!(tui.app().search.outside_scope);
+    assert_eq!(tui.app().search.results.len(), 1);
+    let screen = tui.screen();
+    assert!(screen.contains("none in ksetra"), "{screen}");
+    assert!(screen.contains("┈ outside"), "{screen}");
+    assert!(screen.contains("guide.md"), "{screen}");
+}
@@ -1,14 +1,20 @@
 //! vicaya-watcher: FSEvents-based file watcher.
 
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+mod replay;
+mod source;
+
+pub use replay::{current_event_id, replay_since, Replay, ResumeCheckpoint, ResumePoint};
+pub use source::{EventSource, NotifySource, ScriptedSource};
+
+use notify::Event;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::sync::mpsc::{channel, Receiver};
-use tracing::{debug, info};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
 use vicaya_core::Result;
 
 /// Events that update the index.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum IndexUpdate {
     /// A new file was created.
     Create { path: String },
@@ -21,131 +27,162 @@ pub enum IndexUpdate {
 }
 
 /// File system watcher.
-pub struct FileWatcher {
-    _watcher: RecommendedWatcher,
-    receiver: Receiver<notify::Result<Event>>,
+pub struct FileWatcher<S: EventSource = NotifySource> {
+    source: S,
 }
 
 impl FileWatcher {
     /// Create a new file watcher for the given paths.
     pub fn new<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
-        let (tx, rx) = channel();
-
-        let mut watcher = RecommendedWatcher::new(tx, Config::default())
-            .map_err(|e| vicaya_core::Error::Watcher(e.to_string()))?;
+        Ok(Self::with_source(NotifySource::new(paths)?))
+    }
+}
 
-        for path in paths {
-            info!("Watching path: {}", path.as_ref().display());
-            watcher
-                .watch(path.as_ref(), RecursiveMode::Recursive)
-                .map_err(|e| vicaya_core::Error::Watcher(e.to_string()))?;
-        }
+impl<S: EventSource> FileWatcher<S> {
+    /// Create a watcher over any event source, such as a [`ScriptedSource`].
+    pub fn with_source(source: S) -> Self {
+        Self { source }
+    }
 
-        Ok(Self {
-            _watcher: watcher,
-            receiver: rx,
-        })
+    /// The event source, e.g. to queue more scripted events.
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
     }
 
     /// Get the next batch of index updates (non-blocking).
-    pub fn poll_updates(&self) -> Vec<IndexUpdate> {
+    ///
+    /// Drains every pending event and collapses the batch with
+    /// [`collapse_updates`], so the burst of events an editor save produces
+    /// reaches the index as one update per path.
+    pub fn poll_updates(&mut self) -> Vec<IndexUpdate> {
         let mut updates = Vec::new();
 
-        while let Ok(Ok(event)) = self.receiver.try_recv() {
-            debug!("File event: {:?}", event);
-            updates.extend(Self::event_to_updates(event));
+        while let Some(event) = self.source.try_next() {
+            match event {
+                Ok(event) => {
+                    debug!("File event: {:?}", event);
+                    updates.extend(event_to_updates(event));
+                }
+                Err(e) => warn!("File watcher error: {}", e),
+            }
         }
 
-        updates
+        collapse_updates(updates)
     }
+}
 
-    /// Convert a notify event to index updates.
-    fn event_to_updates(event: Event) -> Vec<IndexUpdate> {
-        use notify::event::{ModifyKind, RenameMode};
-        use notify::EventKind;
+/// Drop updates superseded by a later update to the same path.
+///
+/// Creates and modifies re-read the file when applied, so only the last
+/// create/modify/delete of a path determines the outcome. Moves touch two
+/// paths and depend on what came before them, so they are kept and act as a
+/// barrier for both of their paths.
+pub fn collapse_updates(updates: Vec<IndexUpdate>) -> Vec<IndexUpdate> {
+    let mut superseded = HashSet::new();
+    let mut kept: Vec<IndexUpdate> = updates
+        .into_iter()
+        .rev()
+        .filter(|update| match update {
+            IndexUpdate::Create { path }
+            | IndexUpdate::Modify { path }
+            | IndexUpdate::Delete { path } => superseded.insert(path.clone()),
+            IndexUpdate::Move { from, to } => {
+                superseded.remove(from);
+                superseded.remove(to);
+                true
+            }
+        })
+        .collect();
+    kept.reverse();
+    kept
+}
 
-        match event.kind {
-            EventKind::Create(_) => event
-                .paths
-                .into_iter()
-                .map(|p| IndexUpdate::Create {
-                    path: p.to_string_lossy().to_string(),
-                })
-                .collect(),
-            EventKind::Modify(ModifyKind::Name(rename_mode)) => match rename_mode {
-                RenameMode::From => event
-                    .paths
-                    .into_iter()
-                    .map(|p| IndexUpdate::Delete {
-                        path: p.to_string_lossy().to_string(),
-                    })
-                    .collect(),
-                RenameMode::To => event
-                    .paths
-                    .into_iter()
-                    .map(|p| IndexUpdate::Create {
-                        path: p.to_string_lossy().to_string(),
-                    })
-                    .collect(),
-                RenameMode::Both => Self::ordered_move_update(event.paths),
-                RenameMode::Any | RenameMode::Other => Self::heuristic_move_update(event.paths),
-            },
-            EventKind::Modify(_) => event
+/// Convert a notify event to index updates.
+fn event_to_updates(event: Event) -> Vec<IndexUpdate> {
+    use notify::event::{ModifyKind, RenameMode};
+    use notify::EventKind;
+
+    match event.kind {
+        EventKind::Create(_) => event
+            .paths
+            .into_iter()
+            .map(|p| IndexUpdate::Create {
+                path: p.to_string_lossy().to_string(),
+            })
+            .collect(),
+        EventKind::Modify(ModifyKind::Name(rename_mode)) => match rename_mode {
+            RenameMode::From => event
                 .paths
                 .into_iter()
-                .map(|p| IndexUpdate::Modify {
+                .map(|p| IndexUpdate::Delete {
                     path: p.to_string_lossy().to_string(),
                 })
                 .collect(),
-            EventKind::Remove(_) => event
+            RenameMode::To => event
                 .paths
                 .into_iter()
-                .map(|p| IndexUpdate::Delete {
+                .map(|p| IndexUpdate::Create {
                     path: p.to_string_lossy().to_string(),
                 })
                 .collect(),
-            _ => Vec::new(),
-        }
+            RenameMode::Both => ordered_move_update(event.paths),
+            RenameMode::Any | RenameMode::Other => heuristic_move_update(event.paths),
+        },
+        EventKind::Modify(_) => event
+            .paths
+            .into_iter()
+            .map(|p| IndexUpdate::Modify {
+                path: p.to_string_lossy().to_string(),
+            })
+            .collect(),
+        EventKind::Remove(_) => event
+            .paths
+            .into_iter()
+            .map(|p| IndexUpdate::Delete {
+                path: p.to_string_lossy().to_string(),
+            })
+            .collect(),
+        _ => Vec::new(),
     }
+}
 
-    fn ordered_move_update(paths: Vec<std::path::PathBuf>) -> Vec<IndexUpdate> {
-        match paths.as_slice() {
-            [from, to] => vec![IndexUpdate::Move {
-                from: from.to_string_lossy().to_string(),
-                to: to.to_string_lossy().to_string(),
-            }],
-            _ => Self::best_effort_modify_updates(paths),
-        }
+fn ordered_move_update(paths: Vec<PathBuf>) -> Vec<IndexUpdate> {
+    match paths.as_slice() {
+        [from, to] => vec![IndexUpdate::Move {
+            from: from.to_string_lossy().to_string(),
+            to: to.to_string_lossy().to_string(),
+        }],
+        _ => best_effort_modify_updates(paths),
     }
+}
 
-    fn heuristic_move_update(paths: Vec<std::path::PathBuf>) -> Vec<IndexUpdate> {
-        match paths.as_slice() {
-            [first, second] => {
-                let (from, to) = match (first.exists(), second.exists()) {
-                    (false, true) => (first, second),
-                    (true, false) => (second, first),
-                    _ => (first, second),
-                };
-
-                vec![IndexUpdate::Move {
-                    from: from.to_string_lossy().to_string(),
-                    to: to.to_string_lossy().to_string(),
-                }]
-            }
-            _ => Self::best_effort_modify_updates(paths),
+fn heuristic_move_update(paths: Vec<PathBuf>) -> Vec<IndexUpdate> {
+    match paths.as_slice() {
+        [first, second] => {
+            let (from, to) = match (first.exists(), second.exists()) {
+                (false, true) => (first, second),
+                (true, false) => (second, first),
+                _ => (first, second),
+            };
+
+            vec![IndexUpdate::Move {
+                from: from.to_string_lossy().to_string(),
+                to: to.to_string_lossy().to_string(),
+            }]
         }
+        _ => best_effort_modify_updates(paths),
     }
+}
 
-    fn best_effort_modify_updates(paths: Vec<std::path::PathBuf>) -> Vec<IndexUpdate> {
-        // Some backends may emit a rename without both endpoints. Upsert whatever
-        // paths we have as a best-effort; the daemon can dedupe by inode.
-        paths
-            .into_iter()
-            .map(|p| IndexUpdate::Modify {
-                path: p.to_string_lossy().to_string(),
-            })
-            .collect()
-    }
+fn best_effort_modify_updates(paths: Vec<PathBuf>) -> Vec<IndexUpdate> {
+    // Some backends may emit a rename without both endpoints. Upsert whatever
+    // paths we have as a best-effort; the daemon can dedupe by inode.
+    paths
+        .into_iter()
+        .map(|p| IndexUpdate::Modify {
+            path: p.to_string_lossy().to_string(),
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -169,7 +206,7 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let updates = FileWatcher::event_to_updates(event);
+        let updates = event_to_updates(event);
         let from_str = from.to_string_lossy().to_string();
         let to_str = to.to_string_lossy().to_string();
 
@@ -202,7 +239,7 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let updates = FileWatcher::event_to_updates(event);
+        let updates = event_to_updates(event);
         let reported_from = to.to_string_lossy().to_string();
         let reported_to = from.to_string_lossy().to_string();
 
@@ -233,7 +270,7 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let updates = FileWatcher::event_to_updates(event);
+        let updates = event_to_updates(event);
         let from_str = from.to_string_lossy().to_string();
         let to_str = to.to_string_lossy().to_string();
 
@@ -263,7 +300,7 @@ mod tests {
             attrs: Default::default(),
         };
 
-        let updates = FileWatcher::event_to_updates(event);
+        let updates = event_to_updates(event);
         let expected: Vec<String> = vec![first, second, third]
             .into_iter()
             .map(|path| path.to_string_lossy().to_string())
@@ -279,4 +316,168 @@ mod tests {
             );
         }
     }
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    fn create(path: &str) -> IndexUpdate {
+        IndexUpdate::Create {
+            path: path.to_string(),
+        }
+    }
+
+    fn modify(path: &str) -> IndexUpdate {
+        IndexUpdate::Modify {
+            path: path.to_string(),
+        }
+    }
+
+    fn delete(path: &str) -> IndexUpdate {
+        IndexUpdate::Delete {
+            path: path.to_string(),
+        }
+    }
+
+    #[test]
+    fn collapsing_keeps_the_last_update_per_path_and_moves_in_order() {
+        let updates = vec![
+            create("/a"),
+            modify("/a"),
+            create("/b"),
+            IndexUpdate::Move {
+                from: "/b".to_string(),
+                to: "/c".to_string(),
+            },
+            modify("/a"),
+            modify("/c"),
+            delete("/a"),
+        ];
+
+        assert_eq!(
+            collapse_updates(updates),
+            vec![
+                create("/b"),
+                IndexUpdate::Move {
+                    from: "/b".to_string(),
+                    to: "/c".to_string(),
+                },
+                modify("/c"),
+                delete("/a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn vim_save_on_fsevents_leaves_one_update_per_path() {
+        use notify::event::{CreateKind, DataChange, RemoveKind};
+
+        // FSEvents reports one path per event. Vim probes the directory with
+        // `4913`, renames the original to a backup, writes a new file, and
+        // removes the backup.
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Any));
+        let mut watcher = FileWatcher::with_source(ScriptedSource::new([
+            event(EventKind::Create(CreateKind::File), "/w/4913"),
+            event(EventKind::Remove(RemoveKind::File), "/w/4913"),
+            event(rename, "/w/notes.txt"),
+            event(rename, "/w/notes.txt~"),
+            event(EventKind::Create(CreateKind::File), "/w/notes.txt"),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/w/notes.txt",
+            ),
+            event(EventKind::Remove(RemoveKind::File), "/w/notes.txt~"),
+        ]));
+
+        assert_eq!(
+            watcher.poll_updates(),
+            vec![
+                delete("/w/4913"),
+                modify("/w/notes.txt"),
+                delete("/w/notes.txt~"),
+            ]
+        );
+        assert!(watcher.poll_updates().is_empty());
+    }
+
+    #[test]
+    fn atomic_save_on_inotify_keeps_the_rename_as_a_move() {
+        use notify::event::{CreateKind, DataChange};
+
+        // VSCode-style atomic save: write a temp file, rename it over the
+        // target. inotify reports the rename as From, To, and a paired Both.
+        let (tmp, target) = ("/w/.main.rs.tmp", "/w/main.rs");
+        let mut watcher = FileWatcher::with_source(ScriptedSource::new([
+            event(EventKind::Create(CreateKind::File), tmp),
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                tmp,
+            ),
+            event(EventKind::Modify(ModifyKind::Name(RenameMode::From)), tmp),
+            event(EventKind::Modify(ModifyKind::Name(RenameMode::To)), target),
+            Event::new(EventKind::Modify(ModifyKind::Name(RenameMode::Both)))
+                .add_path(PathBuf::from(tmp))
+                .add_path(PathBuf::from(target)),
+        ]));
+
+        assert_eq!(
+            watcher.poll_updates(),
+            vec![
+                delete(tmp),
+                create(target),
+                IndexUpdate::Move {
+                    from: tmp.to_string(),
+                    to: target.to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rsync_temp_files_collapse_within_a_batch_but_not_across_batches() {
+        use notify::event::{CreateKind, DataChange, MetadataKind};
+
+        // rsync writes `.name.XXXXXX`, sets its mtime, and renames it into
+        // place, one file after another.
+        let rename = EventKind::Modify(ModifyKind::Name(RenameMode::Any));
+        let written = |tmp: &str| {
+            [
+                event(EventKind::Create(CreateKind::File), tmp),
+                event(
+                    EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                    tmp,
+                ),
+                event(
+                    EventKind::Modify(ModifyKind::Metadata(MetadataKind::WriteTime)),
+                    tmp,
+                ),
+            ]
+        };
+        let mut source = ScriptedSource::new(written("/w/.a.pdf.Xa1b2c"));
+        source.extend([event(rename, "/w/.a.pdf.Xa1b2c"), event(rename, "/w/a.pdf")]);
+        source.push_error(notify::Error::generic("event dropped"));
+        source.extend(written("/w/.b.pdf.Xd3e4f"));
+        let mut watcher = FileWatcher::with_source(source);
+
+        assert_eq!(
+            watcher.poll_updates(),
+            vec![
+                modify("/w/.a.pdf.Xa1b2c"),
+                modify("/w/a.pdf"),
+                modify("/w/.b.pdf.Xd3e4f"),
+            ]
+        );
+        assert_eq!(watcher.source_mut().pending(), 0);
+
+        // The rename lands in the next batch, so the temp file's upsert has
+        // already gone out and the rename reaches the daemon as an upsert of
+        // both paths, which it reconciles by inode.
+        watcher
+            .source_mut()
+            .extend([event(rename, "/w/.b.pdf.Xd3e4f"), event(rename, "/w/b.pdf")]);
+        assert_eq!(
+            watcher.poll_updates(),
+            vec![modify("/w/.b.pdf.Xd3e4f"), modify("/w/b.pdf")]
+        );
+    }
 }
@@ -0,0 +1,421 @@
+//! Gapless restarts on macOS: replay FSEvents history from a saved event ID.
+//!
+//! FSEvents numbers every change with a system-wide, increasing event ID and
+//! keeps a history of them on each volume. The daemon saves the ID it has
+//! seen everything up to as a [`ResumePoint`] when it stops; on the next
+//! start [`replay_since`] asks FSEvents for every path changed since then,
+//! which is far cheaper than the full reconcile it replaces.
+//!
+//! Replayed paths are turned into updates by looking at the disk now, not by
+//! decoding the event flags, so replaying from an older ID than necessary only
+//! costs time. When FSEvents reports that its history has a hole (dropped
+//! events, wrapped IDs, a purged database), the replay is marked incomplete
+//! and the daemon falls back to a reconcile.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use vicaya_core::Result;
+
+use crate::IndexUpdate;
+
+/// How long an observed event ID must age before it counts as delivered:
+/// FSEvents hands events to the watcher some milliseconds after they get
+/// their ID, and a resume point past an undelivered event would skip it.
+const CHECKPOINT_SETTLE: Duration = Duration::from_secs(5);
+
+/// Where the watcher can pick up after a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumePoint {
+    /// FSEvents event ID every earlier change was applied up to.
+    pub event_id: u64,
+}
+
+impl ResumePoint {
+    /// Read and remove the resume point at `path`. A point is used once: after
+    /// a crash the file is gone and the daemon reconciles instead of
+    /// replaying from a stale ID.
+    pub fn take(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        let _ = std::fs::remove_file(path);
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Write the resume point to `path` (tmp + rename).
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp = path.with_extension("tmp");
+        let json = serde_json::to_string(self)
+            .map_err(|e| vicaya_core::Error::Serialization(e.to_string()))?;
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Keeps an event ID at least [`CHECKPOINT_SETTLE`] old, so every event up
+/// to it has reached the watcher by the time it is saved.
+#[derive(Debug, Clone)]
+pub struct ResumeCheckpoint {
+    settled: Option<u64>,
+    pending: Option<(Instant, u64)>,
+}
+
+impl ResumeCheckpoint {
+    /// Start from an ID already known to be safe, such as the one a replay
+    /// started from.
+    pub fn new(settled: Option<u64>) -> Self {
+        Self {
+            settled,
+            pending: None,
+        }
+    }
+
+    /// Sample the current event ID once the pending sample has settled.
+    pub fn observe(&mut self, now: Instant, current: impl FnOnce() -> Option<u64>) {
+        match self.pending {
+            Some((at, _)) if now.saturating_duration_since(at) < CHECKPOINT_SETTLE => {}
+            pending => {
+                if let Some((_, event_id)) = pending {
+                    self.settled = Some(event_id);
+                }
+                self.pending = current().map(|event_id| (now, event_id));
+            }
+        }
+    }
+
+    /// The newest event ID that is safe to resume from.
+    pub fn settled(&self) -> Option<u64> {
+        self.settled
+    }
+}
+
+/// What changed under the watched roots since a resume point.
+#[derive(Debug, Default)]
+pub struct Replay {
+    /// One upsert or delete per changed path.
+    pub updates: Vec<IndexUpdate>,
+    /// False when FSEvents could not account for every change, so the
+    /// caller must reconcile.
+    pub complete: bool,
+}
+
+/// The latest FSEvents event ID, or `None` where event IDs do not exist.
+pub fn current_event_id() -> Option<u64> {
+    #[cfg(target_os = "macos")]
+    {
+        Some(fsevents::current_event_id())
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        None
+    }
+}
+
+/// Replay the changes under `roots` since event `since`, giving up after
+/// `timeout`.
+pub fn replay_since<P: AsRef<Path>>(roots: &[P], since: u64, timeout: Duration) -> Result<Replay> {
+    #[cfg(target_os = "macos")]
+    {
+        let roots: Vec<PathBuf> = roots
+            .iter()
+            .map(|root| vicaya_core::paths::expand_user_path(root.as_ref()))
+            .filter(|root| root.exists())
+            .collect();
+        let (paths, complete) = fsevents::changed_paths(&roots, since, timeout)?;
+        Ok(Replay {
+            updates: updates_for_paths(paths),
+            complete,
+        })
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (roots, since, timeout);
+        Err(vicaya_core::Error::Watcher(
+            "event replay needs FSEvents (macOS)".to_string(),
+        ))
+    }
+}
+
+/// Upsert the replayed paths that exist and delete the rest.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn updates_for_paths(paths: BTreeSet<PathBuf>) -> Vec<IndexUpdate> {
+    paths
+        .into_iter()
+        .map(|path| {
+            let exists = path.symlink_metadata().is_ok();
+            let path = path.to_string_lossy().to_string();
+            if exists {
+                IndexUpdate::Modify { path }
+            } else {
+                IndexUpdate::Delete { path }
+            }
+        })
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+mod fsevents {
+    //! A one-shot FSEvents stream that runs from a past event ID until the
+    //! `HistoryDone` sentinel, modelled on notify's FSEvents backend.
+
+    use fsevent_sys as fs;
+    use fsevent_sys::core_foundation as cf;
+    use std::collections::BTreeSet;
+    use std::ffi::{c_void, CStr, OsStr};
+    use std::os::raw::c_char;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+    use std::ptr;
+    use std::sync::mpsc::{channel, Sender};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use vicaya_core::{Error, Result};
+
+    /// Replays touching more paths than this are abandoned for a reconcile,
+    /// which handles bulk changes (a large checkout, a restored backup) better.
+    const MAX_REPLAY_PATHS: usize = 100_000;
+
+    /// Flags meaning FSEvents lost track of changes below the event's path.
+    const HISTORY_GAP: fs::FSEventStreamEventFlags = fs::kFSEventStreamEventFlagMustScanSubDirs
+        | fs::kFSEventStreamEventFlagUserDropped
+        | fs::kFSEventStreamEventFlagKernelDropped
+        | fs::kFSEventStreamEventFlagEventIdsWrapped
+        | fs::kFSEventStreamEventFlagRootChanged;
+
+    #[link(name = "CoreServices", kind = "framework")]
+    extern "C" {
+        fn FSEventsGetCurrentEventId() -> fs::FSEventStreamEventId;
+    }
+
+    extern "C" {
+        fn CFRunLoopIsWaiting(runloop: cf::CFRunLoopRef) -> cf::Boolean;
+    }
+
+    enum Message {
+        Changed(PathBuf),
+        Gap,
+        HistoryDone,
+    }
+
+    /// Moves CoreFoundation references to the run loop thread and back.
+    struct CFSendWrapper(cf::CFRef);
+
+    // Safety: CoreFoundation references may be used from any thread.
+    unsafe impl Send for CFSendWrapper {}
+
+    pub(super) fn current_event_id() -> u64 {
+        // Safety: no arguments; returns the system-wide counter.
+        unsafe { FSEventsGetCurrentEventId() }
+    }
+
+    /// Paths changed under `roots` since `since`, and whether FSEvents
+    /// accounted for all of them.
+    pub(super) fn changed_paths(
+        roots: &[PathBuf],
+        since: u64,
+        timeout: Duration,
+    ) -> Result<(BTreeSet<PathBuf>, bool)> {
+        if roots.is_empty() {
+            return Ok((BTreeSet::new(), true));
+        }
+        // A saved ID ahead of the counter means the event database was
+        // reset; its history says nothing about what happened since.
+        if since > current_event_id() {
+            return Ok((BTreeSet::new(), false));
+        }
+
+        let (tx, rx) = channel::<Message>();
+        let stream = create_stream(roots, since, tx)?;
+        let (rl_tx, rl_rx) = channel();
+        let runloop_thread = thread::Builder::new()
+            .name("vicaya fsevents replay".to_string())
+            .spawn(move || {
+                let stream = stream;
+                // Safety: the stream was created above and is only used on
+                // this thread from here on.
+                unsafe {
+                    let runloop = cf::CFRunLoopGetCurrent();
+                    fs::FSEventStreamScheduleWithRunLoop(
+                        stream.0,
+                        runloop,
+                        cf::kCFRunLoopDefaultMode,
+                    );
+                    fs::FSEventStreamStart(stream.0);
+                    let _ = rl_tx.send(CFSendWrapper(runloop));
+                    cf::CFRunLoopRun();
+                    fs::FSEventStreamStop(stream.0);
+                    fs::FSEventStreamInvalidate(stream.0);
+                    fs::FSEventStreamRelease(stream.0);
+                }
+            })?;
+        let runloop = rl_rx
+            .recv()
+            .map_err(|_| Error::Watcher("FSEvents replay thread exited".to_string()))?;
+
+        let deadline = Instant::now() + timeout;
+        let mut paths = BTreeSet::new();
+        let mut complete = false;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok(Message::Changed(path)) => {
+                    paths.insert(path);
+                    if paths.len() > MAX_REPLAY_PATHS {
+                        break;
+                    }
+                }
+                Ok(Message::HistoryDone) => {
+                    complete = true;
+                    break;
+                }
+                Ok(Message::Gap) | Err(_) => break,
+            }
+        }
+
+        // Safety: the run loop belongs to the replay thread, which is still
+        // running it; stopping it lets the thread release the stream.
+        unsafe {
+            while CFRunLoopIsWaiting(runloop.0) == 0 {
+                thread::yield_now();
+            }
+            cf::CFRunLoopStop(runloop.0);
+        }
+        let _ = runloop_thread.join();
+        Ok((paths, complete))
+    }
+
+    fn create_stream(roots: &[PathBuf], since: u64, tx: Sender<Message>) -> Result<CFSendWrapper> {
+        // Safety: the array and path strings are released once the stream,
+        // which copies them, has been created; the context box is owned by
+        // the stream and freed through `release_context`.
+        unsafe {
+            let paths =
+                cf::CFArrayCreateMutable(cf::kCFAllocatorDefault, 0, &cf::kCFTypeArrayCallBacks);
+            for root in roots {
+                let Some(root) = root.to_str() else {
+                    continue;
+                };
+                let mut err: cf::CFErrorRef = ptr::null_mut();
+                let cf_path = cf::str_path_to_cfstring_ref(root, &mut err);
+                if cf_path.is_null() {
+                    cf::CFRelease(err as cf::CFRef);
+                    continue;
+                }
+                cf::CFArrayAppendValue(paths, cf_path);
+                cf::CFRelease(cf_path);
+            }
+
+            let context = Box::into_raw(Box::new(tx));
+            let stream_context = fs::FSEventStreamContext {
+                version: 0,
+                info: context as *mut c_void,
+                retain: None,
+                release: Some(release_context),
+                copy_description: None,
+            };
+            let stream = fs::FSEventStreamCreate(
+                cf::kCFAllocatorDefault,
+                callback,
+                &stream_context,
+                paths,
+                since,
+                0.0,
+                fs::kFSEventStreamCreateFlagFileEvents | fs::kFSEventStreamCreateFlagNoDefer,
+            );
+            cf::CFRelease(paths);
+            if stream.is_null() {
+                drop(Box::from_raw(context));
+                return Err(Error::Watcher(
+                    "FSEvents refused to create a replay stream".to_string(),
+                ));
+            }
+            Ok(CFSendWrapper(stream))
+        }
+    }
+
+    extern "C" fn release_context(info: *const c_void) {
+        // Safety: `info` is the box leaked in `create_stream`, and FSEvents
+        // calls this once, when the stream is deallocated.
+        unsafe { drop(Box::from_raw(info as *mut Sender<Message>)) }
+    }
+
+    extern "C" fn callback(
+        _stream: fs::FSEventStreamRef,
+        info: *mut c_void,
+        num_events: usize,
+        event_paths: *mut c_void,
+        event_flags: *const fs::FSEventStreamEventFlags,
+        _event_ids: *const fs::FSEventStreamEventId,
+    ) {
+        // Safety: FSEvents passes `num_events` entries in each array, paths
+        // as C strings, and the context `info` registered for the stream.
+        unsafe {
+            let tx = &*(info as *const Sender<Message>);
+            let event_paths = event_paths as *const *const c_char;
+            for i in 0..num_events {
+                let flags = *event_flags.add(i);
+                let message = if flags & fs::kFSEventStreamEventFlagHistoryDone != 0 {
+                    Message::HistoryDone
+                } else if flags & HISTORY_GAP != 0 {
+                    Message::Gap
+                } else {
+                    let path = CStr::from_ptr(*event_paths.add(i)).to_bytes();
+                    Message::Changed(PathBuf::from(OsStr::from_bytes(path)))
+                };
+                let _ = tx.send(message);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resume_points_are_taken_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("watcher.resume");
+        assert_eq!(ResumePoint::take(&path), None);
+
+        ResumePoint { event_id: 42 }.save(&path).unwrap();
+        assert_eq!(ResumePoint::take(&path), Some(ResumePoint { event_id: 42 }));
+        assert!(!path.exists());
+
+        std::fs::write(&path, "not json").unwrap();
+        assert_eq!(ResumePoint::take(&path), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn checkpoint_only_settles_ids_older_than_the_settle_time() {
+        let start = Instant::now();
+        let mut checkpoint = ResumeCheckpoint::new(Some(10));
+
+        checkpoint.observe(start, || Some(20));
+        assert_eq!(checkpoint.settled(), Some(10));
+        checkpoint.observe(start + Duration::from_secs(1), || panic!("not sampled yet"));
+        assert_eq!(checkpoint.settled(), Some(10));
+
+        checkpoint.observe(start + CHECKPOINT_SETTLE, || Some(30));
+        assert_eq!(checkpoint.settled(), Some(20));
+        checkpoint.observe(start + CHECKPOINT_SETTLE * 2, || None);
+        assert_eq!(checkpoint.settled(), Some(30));
+    }
+
+    #[test]
+    fn replayed_paths_become_upserts_or_deletes_by_what_is_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.txt");
+        let gone = dir.path().join("gone.txt");
+        std::fs::write(&kept, "").unwrap();
+
+        let updates = updates_for_paths(BTreeSet::from([kept.clone(), gone.clone()]));
+        assert!(matches!(
+            &updates[..],
+            [IndexUpdate::Delete { path: d }, IndexUpdate::Modify { path: m }]
+                if *d == gone.to_string_lossy() && *m == kept.to_string_lossy()
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+//! Where a [`FileWatcher`](crate::FileWatcher) gets raw events from.
+//!
+//! [`NotifySource`] is the live backend. [`ScriptedSource`] replays events
+//! handed to it, so event translation and coalescing can be tested against
+//! exact backend event sequences without touching the filesystem.
+
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::mpsc::{channel, Receiver};
+use tracing::info;
+use vicaya_core::Result;
+
+/// A stream of raw filesystem events.
+pub trait EventSource: Send {
+    /// The next pending event without blocking, or `None` when there is none
+    /// yet.
+    fn try_next(&mut self) -> Option<notify::Result<Event>>;
+}
+
+/// Events from the platform's recommended notify backend (FSEvents on macOS).
+pub struct NotifySource {
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<Event>>,
+}
+
+impl NotifySource {
+    /// Watch `paths` recursively.
+    pub fn new<P: AsRef<Path>>(paths: &[P]) -> Result<Self> {
+        let (tx, rx) = channel();
+
+        let mut watcher = RecommendedWatcher::new(tx, Config::default())
+            .map_err(|e| vicaya_core::Error::Watcher(e.to_string()))?;
+
+        for path in paths {
+            let path = vicaya_core::paths::expand_user_path(path.as_ref());
+            info!("Watching path: {}", path.display());
+            watcher
+                .watch(&path, RecursiveMode::Recursive)
+                .map_err(|e| vicaya_core::Error::Watcher(e.to_string()))?;
+        }
+
+        Ok(Self {
+            _watcher: watcher,
+            receiver: rx,
+        })
+    }
+}
+
+impl EventSource for NotifySource {
+    fn try_next(&mut self) -> Option<notify::Result<Event>> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+/// Events queued by hand, delivered in order.
+#[derive(Debug, Default)]
+pub struct ScriptedSource {
+    events: VecDeque<notify::Result<Event>>,
+}
+
+impl ScriptedSource {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        let mut source = Self::default();
+        source.extend(events);
+        source
+    }
+
+    /// Queue `event` after those already queued.
+    pub fn push(&mut self, event: Event) {
+        self.events.push_back(Ok(event));
+    }
+
+    /// Queue a backend error, as notify reports dropped or failed watches.
+    pub fn push_error(&mut self, error: notify::Error) {
+        self.events.push_back(Err(error));
+    }
+
+    /// Number of events not yet delivered.
+    pub fn pending(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl Extend<Event> for ScriptedSource {
+    fn extend<I: IntoIterator<Item = Event>>(&mut self, events: I) {
+        self.events.extend(events.into_iter().map(Ok));
+    }
+}
+
+impl EventSource for ScriptedSource {
+    fn try_next(&mut self) -> Option<notify::Result<Event>> {
+        self.events.pop_front()
+    }
+}